@@ -170,6 +170,42 @@ impl Generator {
             }
         }
     }
+
+    /// # Smallest Prime At Least x
+    /// Deterministic: the same `x` always gives the same prime. Returns `None` if there is no such prime below 2^64.
+    /// ```
+    /// use num_primes::Generator;
+    ///
+    /// fn main(){
+    ///     assert_eq!(Generator::next_prime(90), Some(97));
+    ///     assert_eq!(Generator::next_prime(97), Some(97));
+    ///     assert_eq!(Generator::next_prime(u64::MAX), None);
+    /// }
+    /// ```
+    pub fn next_prime(x: u64) -> Option<u64> {
+        (x..=u64::MAX).find(|candidate| is_prime_u64(*candidate))
+    }
+
+    /// # Smallest Safe Prime At Least x
+    /// Deterministic, like `next_prime`, for a safe prime p = 2q + 1 where q is prime as well.
+    /// ```
+    /// use num_primes::Generator;
+    ///
+    /// fn main(){
+    ///     // 23 = 2 * 11 + 1
+    ///     assert_eq!(Generator::next_safe_prime(12), Some(23));
+    /// }
+    /// ```
+    pub fn next_safe_prime(x: u64) -> Option<u64> {
+        let mut candidate = x;
+        loop {
+            candidate = Self::next_prime(candidate)?;
+            if is_prime_u64(candidate / 2) {
+                return Some(candidate);
+            }
+            candidate = candidate.checked_add(1)?;
+        }
+    }
 }
 
 impl Verification {
@@ -190,6 +226,23 @@ impl Verification {
     pub fn is_safe_prime(n: &BigUint) -> bool {
         return is_safe_prime(n);
     }
+    /// # Deterministic Primality of a u64
+    /// Miller-Rabin with the first twelve primes as bases, which has no false positive below 2^64, so unlike `is_prime` the answer is exact.
+    /// ```
+    /// use num_primes::Verification;
+    ///
+    /// fn main(){
+    ///     assert!(Verification::is_prime_u64(18_446_744_073_709_551_557));
+    ///     // Strong pseudoprime to the bases 2, 3, 5, 7, 11 and 13
+    ///     assert!(!Verification::is_prime_u64(3_474_749_660_383));
+    /// }
+    /// ```
+    pub fn is_prime_u64(n: u64) -> bool {
+        return is_prime_u64(n);
+    }
+    pub fn is_safe_prime_u64(n: u64) -> bool {
+        return is_prime_u64(n) && is_prime_u64(n / 2);
+    }
 }
 
 impl Factorization {
@@ -509,6 +562,55 @@ fn is_prime(candidate: &BigUint) -> bool {
     true
 }
 
+/// Bases for which Miller-Rabin is exact on every u64
+const U64_BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exponent: u64, m: u64) -> u64 {
+    let mut result = 1;
+    base %= m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn is_prime_u64(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in U64_BASES.iter() {
+        if n % p == 0 {
+            return n == *p;
+        }
+    }
+
+    // n - 1 = d * 2^s with d odd
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    U64_BASES.iter().all(|a| {
+        let mut x = pow_mod(*a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
 // (p - 1)/2
 fn is_safe_prime(number: &BigUint) -> bool {
     let one = BigUint::one();
@@ -532,6 +634,23 @@ fn generate_safe_prime() {
     let _p = Generator::safe_prime(512);
 }
 
+#[cfg(test)]
+#[test]
+fn deterministic_u64() {
+    let sieve: Vec<u64> = (0..1000u64)
+        .filter(|n| *n >= 2 && (2..*n).all(|d| n % d != 0))
+        .collect();
+    let tested: Vec<u64> = (0..1000).filter(|n| is_prime_u64(*n)).collect();
+    assert_eq!(tested, sieve);
+
+    // Carmichael numbers, and the largest prime below 2^64
+    assert!(!is_prime_u64(561));
+    assert!(!is_prime_u64(3_215_031_751));
+    assert!(is_prime_u64(18_446_744_073_709_551_557));
+    assert_eq!(Generator::next_prime(18_446_744_073_709_551_558), None);
+    assert!(Verification::is_safe_prime_u64(Generator::next_safe_prime(1 << 40).unwrap()));
+}
+
 #[test]
 fn prime_factor() {
     let x = Generator::new_uint(16);
@@ -0,0 +1,606 @@
+//! Regression Corpora
+//!
+//! A randomized colorer that fails once in a hundred runs makes for a flaky test, and the failing graph is lost as soon as the
+//! test is run again. A [Corpus] keeps such runs as cases in a directory, one file per case, so that they are replayed by every
+//! later test run:
+//!
+//! ```text
+//! # g-raph regression case
+//! algorithm: bcg
+//! graph: bernoulli 40 0.3
+//! seed: 17
+//! param k: 4
+//! outcome: fail
+//! coloring: 0=1 1=0 2=1
+//! ```
+//!
+//! The graph is not stored, but regenerated from its configuration and seed with a [ChaCha20Rng], whose output is stable across
+//! versions of `rand`. The parameters are a record of how the colorer was configured, the runners of the [Registry] choose their own.
+
+use std::{
+    error::Error,
+    fmt::{Display, Write as _},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use rand::{prelude::Distribution, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::{
+    graph::{
+        static_a::{coloring::Colorer, partition::Partition},
+        Graph, Graphed, VertexId,
+    },
+    random_graph::{
+        bernoulli::BernoulliGraphDistribution, block::StochasticBlockModel, planted::PlantedClique,
+        regular::RandomRegularGraph,
+    },
+    registry::{Registry, RegistryError},
+};
+
+/// Extension of the files of the cases
+pub const EXTENSION: &str = "case";
+
+/// How the graph of a case is generated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GraphSource {
+    /// Every edge among `nodes` vertices is present with probability `p`
+    Bernoulli { nodes: VertexId, p: f64 },
+    /// `blocks` blocks of `size` vertices, edges within a block with probability `p_in`, between blocks with `p_out`
+    StochasticBlock {
+        blocks: VertexId,
+        size: VertexId,
+        p_in: f64,
+        p_out: f64,
+    },
+    /// Every one of `nodes` vertices has `degree` neighbors
+    Regular { nodes: VertexId, degree: VertexId },
+    /// A Bernoulli graph, with a clique on `k` of its vertices
+    PlantedClique {
+        nodes: VertexId,
+        p: f64,
+        k: VertexId,
+    },
+}
+
+impl GraphSource {
+    /// The graph of the configuration, the same for the same seed
+    pub fn generate(&self, seed: u64) -> Graph<VertexId, ()> {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        match self {
+            Self::Bernoulli { nodes, p } => BernoulliGraphDistribution::init(*nodes, *p)
+                .unwrap_or_else(|_| panic!("[Corpus] Invalid Probability: {}", p))
+                .sample(&mut rng),
+            Self::StochasticBlock {
+                blocks,
+                size,
+                p_in,
+                p_out,
+            } => StochasticBlockModel::init(*blocks, *size, *p_in, *p_out)
+                .unwrap_or_else(|_| panic!("[Corpus] Invalid Probabilities: {}, {}", p_in, p_out))
+                .sample(&mut rng),
+            Self::Regular { nodes, degree } => RandomRegularGraph::init(*nodes, *degree)
+                .unwrap_or_else(|| {
+                    panic!("[Corpus] No {}-regular graph on {} vertices", degree, nodes)
+                })
+                .sample(&mut rng),
+            Self::PlantedClique { nodes, p, k } => PlantedClique::init(*nodes, *p, *k)
+                .unwrap_or_else(|_| panic!("[Corpus] Invalid Probability: {}", p))
+                .sample(&mut rng),
+        }
+    }
+}
+
+impl Display for GraphSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bernoulli { nodes, p } => write!(f, "bernoulli {} {}", nodes, p),
+            Self::StochasticBlock {
+                blocks,
+                size,
+                p_in,
+                p_out,
+            } => write!(f, "sbm {} {} {} {}", blocks, size, p_in, p_out),
+            Self::Regular { nodes, degree } => write!(f, "regular {} {}", nodes, degree),
+            Self::PlantedClique { nodes, p, k } => write!(f, "planted {} {} {}", nodes, p, k),
+        }
+    }
+}
+
+impl FromStr for GraphSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn number<N: FromStr>(field: &str, what: &str) -> Result<N, String>
+        where
+            N::Err: Display,
+        {
+            field
+                .parse()
+                .map_err(|e| format!("invalid {} {:?}: {}", what, field, e))
+        }
+
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        match fields.as_slice() {
+            ["bernoulli", nodes, p] => Ok(Self::Bernoulli {
+                nodes: number(nodes, "number of nodes")?,
+                p: number(p, "probability")?,
+            }),
+            ["sbm", blocks, size, p_in, p_out] => Ok(Self::StochasticBlock {
+                blocks: number(blocks, "number of blocks")?,
+                size: number(size, "block size")?,
+                p_in: number(p_in, "probability")?,
+                p_out: number(p_out, "probability")?,
+            }),
+            ["regular", nodes, degree] => Ok(Self::Regular {
+                nodes: number(nodes, "number of nodes")?,
+                degree: number(degree, "degree")?,
+            }),
+            ["planted", nodes, p, k] => Ok(Self::PlantedClique {
+                nodes: number(nodes, "number of nodes")?,
+                p: number(p, "probability")?,
+                k: number(k, "clique size")?,
+            }),
+            _ => Err(format!("unknown graph {:?}", s)),
+        }
+    }
+}
+
+/// Whether the coloring of a case was proper
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    /// The coloring was not proper, or the algorithm failed to produce one
+    Fail,
+}
+
+/// A run of an algorithm of the [Registry], on a generated graph
+#[derive(Clone, Debug)]
+pub struct Case {
+    pub algorithm: String,
+    pub graph: GraphSource,
+    /// Seed of the graph
+    pub seed: u64,
+    /// Configuration of the algorithm, for the record
+    pub params: Vec<(String, f64)>,
+    pub outcome: Outcome,
+    /// The coloring, `None` if the algorithm failed to produce one
+    pub coloring: Option<Partition<VertexId>>,
+}
+
+/// Result of replaying a [Case]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Replay {
+    /// The case passed, and still does
+    Passed,
+    /// The case failed, and passes now
+    Fixed,
+    /// The case passed, and fails now
+    Regressed,
+    /// The case failed, and still does
+    Failing,
+    /// The recorded coloring is not proper on the regenerated graph, or is proper on a failed case: the graph generation changed,
+    /// and the case no longer describes the run it was recorded from
+    Stale,
+}
+
+impl Replay {
+    /// Whether the algorithm produced a proper coloring on the replay
+    pub fn passes(&self) -> bool {
+        matches!(self, Self::Passed | Self::Fixed)
+    }
+}
+
+impl Case {
+    /// Run the algorithm on the graph generated from the seed, and record its outcome
+    pub fn record(
+        registry: &Registry,
+        algorithm: &str,
+        graph: GraphSource,
+        seed: u64,
+        params: Vec<(String, f64)>,
+    ) -> Result<Self, RegistryError> {
+        let (outcome, coloring) = run(registry, algorithm, &graph.generate(seed))?;
+
+        Ok(Self {
+            algorithm: algorithm.to_string(),
+            graph,
+            seed,
+            params,
+            outcome,
+            coloring,
+        })
+    }
+
+    /// Name of the file of the case, unique to its algorithm, graph and seed
+    pub fn file_name(&self) -> String {
+        let graph = self.graph.to_string().replace(' ', "-");
+        format!("{}-{}-{}.{}", self.algorithm, graph, self.seed, EXTENSION)
+    }
+
+    /// Run the algorithm again on the regenerated graph
+    pub fn replay(&self, registry: &Registry) -> Result<Replay, RegistryError> {
+        let graph = self.graph.generate(self.seed);
+
+        let recorded = self
+            .coloring
+            .as_ref()
+            .is_some_and(|coloring| proper(&graph, coloring));
+        if recorded != (self.outcome == Outcome::Pass) {
+            return Ok(Replay::Stale);
+        }
+
+        let (outcome, _) = run(registry, &self.algorithm, &graph)?;
+        Ok(match (self.outcome, outcome) {
+            (Outcome::Pass, Outcome::Pass) => Replay::Passed,
+            (Outcome::Fail, Outcome::Pass) => Replay::Fixed,
+            (Outcome::Pass, Outcome::Fail) => Replay::Regressed,
+            (Outcome::Fail, Outcome::Fail) => Replay::Failing,
+        })
+    }
+}
+
+/// A coloring is proper if it colors every vertex, and no edge is monochromatic
+pub(crate) fn proper(graph: &Graph<VertexId, ()>, coloring: &Partition<VertexId>) -> bool {
+    graph.vertices().into_iter().all(|v| coloring.contains(v)) && graph.is_proper(coloring)
+}
+
+fn run(
+    registry: &Registry,
+    algorithm: &str,
+    graph: &Graph<VertexId, ()>,
+) -> Result<(Outcome, Option<Partition<VertexId>>), RegistryError> {
+    match registry.run(algorithm, graph) {
+        Ok(coloring) if proper(graph, &coloring) => Ok((Outcome::Pass, Some(coloring))),
+        Ok(coloring) => Ok((Outcome::Fail, Some(coloring))),
+        Err(RegistryError::Failed(_)) => Ok((Outcome::Fail, None)),
+        Err(e) => Err(e),
+    }
+}
+
+impl Display for Case {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# g-raph regression case")?;
+        writeln!(f, "algorithm: {}", self.algorithm)?;
+        writeln!(f, "graph: {}", self.graph)?;
+        writeln!(f, "seed: {}", self.seed)?;
+        for (name, value) in &self.params {
+            writeln!(f, "param {}: {}", name, value)?;
+        }
+        let outcome = match self.outcome {
+            Outcome::Pass => "pass",
+            Outcome::Fail => "fail",
+        };
+        writeln!(f, "outcome: {}", outcome)?;
+
+        if let Some(coloring) = &self.coloring {
+            let mut colors: Vec<_> = coloring.iter().collect();
+            colors.sort_unstable();
+
+            let mut line = String::new();
+            for (v, color) in colors {
+                write!(line, " {}={}", v, color)?;
+            }
+            writeln!(f, "coloring:{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Case {
+    type Err = CorpusError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut algorithm = None;
+        let mut graph = None;
+        let mut seed = None;
+        let mut params = vec![];
+        let mut outcome = None;
+        let mut coloring = None;
+
+        for (i, line) in s.lines().enumerate() {
+            let error = |message: String| CorpusError::Parse {
+                file: None,
+                line: i + 1,
+                message,
+            };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| error(format!("expected a key and a value: {:?}", line)))?;
+            let value = value.trim();
+
+            match key.trim() {
+                "algorithm" => algorithm = Some(value.to_string()),
+                "graph" => graph = Some(value.parse().map_err(error)?),
+                "seed" => {
+                    seed = Some(
+                        value
+                            .parse()
+                            .map_err(|e| error(format!("invalid seed {:?}: {}", value, e)))?,
+                    )
+                }
+                "outcome" => {
+                    outcome = Some(match value {
+                        "pass" => Outcome::Pass,
+                        "fail" => Outcome::Fail,
+                        _ => return Err(error(format!("invalid outcome {:?}", value))),
+                    })
+                }
+                "coloring" => {
+                    coloring = Some(
+                        value
+                            .split_whitespace()
+                            .map(|pair| {
+                                let (v, color) = pair.split_once('=')?;
+                                Some((v.parse().ok()?, color.parse().ok()?))
+                            })
+                            .collect::<Option<Partition<VertexId>>>()
+                            .ok_or_else(|| error(format!("invalid coloring {:?}", value)))?,
+                    )
+                }
+                key => match key.strip_prefix("param ") {
+                    Some(name) => params.push((
+                        name.trim().to_string(),
+                        value
+                            .parse()
+                            .map_err(|e| error(format!("invalid parameter {:?}: {}", value, e)))?,
+                    )),
+                    None => return Err(error(format!("unknown key {:?}", key))),
+                },
+            }
+        }
+
+        let missing = |key: &str| CorpusError::Parse {
+            file: None,
+            line: s.lines().count(),
+            message: format!("missing {}", key),
+        };
+        Ok(Self {
+            algorithm: algorithm.ok_or_else(|| missing("algorithm"))?,
+            graph: graph.ok_or_else(|| missing("graph"))?,
+            seed: seed.ok_or_else(|| missing("seed"))?,
+            params,
+            outcome: outcome.ok_or_else(|| missing("outcome"))?,
+            coloring,
+        })
+    }
+}
+
+/// Reasons for which a corpus cannot be read, or replayed
+#[derive(Debug)]
+pub enum CorpusError {
+    Io(io::Error),
+    /// The line, numbered from 1, of the case is malformed
+    Parse {
+        file: Option<PathBuf>,
+        line: usize,
+        message: String,
+    },
+    Registry(RegistryError),
+}
+
+impl Display for CorpusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to access the corpus: {}", e),
+            Self::Parse {
+                file: Some(file),
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file.display(), line, message),
+            Self::Parse { line, message, .. } => write!(f, "line {}: {}", line, message),
+            Self::Registry(e) => write!(f, "failed to replay a case: {}", e),
+        }
+    }
+}
+
+impl Error for CorpusError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse { .. } => None,
+            Self::Registry(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for CorpusError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<RegistryError> for CorpusError {
+    fn from(e: RegistryError) -> Self {
+        Self::Registry(e)
+    }
+}
+
+/// A directory of cases
+#[derive(Clone, Debug)]
+pub struct Corpus {
+    directory: PathBuf,
+}
+
+impl Corpus {
+    /// The corpus of the directory, created if it does not exist
+    pub fn open<P: AsRef<Path>>(directory: P) -> io::Result<Self> {
+        fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory: directory.as_ref().to_path_buf(),
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Save a case, replacing the case of the same algorithm, graph and seed
+    pub fn save(&self, case: &Case) -> io::Result<PathBuf> {
+        let path = self.directory.join(case.file_name());
+        fs::write(&path, case.to_string())?;
+        Ok(path)
+    }
+
+    /// Every case of the corpus, in the order of their file names
+    pub fn cases(&self) -> Result<Vec<Case>, CorpusError> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        paths
+            .into_iter()
+            .map(|path| {
+                fs::read_to_string(&path)?.parse().map_err(|e| match e {
+                    CorpusError::Parse { line, message, .. } => CorpusError::Parse {
+                        file: Some(path.clone()),
+                        line,
+                        message,
+                    },
+                    e => e,
+                })
+            })
+            .collect()
+    }
+
+    /// Replay every case of the corpus
+    pub fn replay(&self, registry: &Registry) -> Result<Vec<(Case, Replay)>, CorpusError> {
+        self.cases()?
+            .into_iter()
+            .map(|case| {
+                let replay = case.replay(registry)?;
+                Ok((case, replay))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("g-raph-corpus-{}", name));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn deterministic_graphs() {
+        let source = GraphSource::Bernoulli { nodes: 30, p: 0.2 };
+        assert_eq!(source.generate(3).adj_list(), source.generate(3).adj_list());
+        assert_eq!("bernoulli 30 0.2".parse(), Ok(source));
+
+        for source in [
+            GraphSource::StochasticBlock {
+                blocks: 3,
+                size: 8,
+                p_in: 0.7,
+                p_out: 0.05,
+            },
+            GraphSource::Regular {
+                nodes: 20,
+                degree: 4,
+            },
+            GraphSource::PlantedClique {
+                nodes: 25,
+                p: 0.1,
+                k: 6,
+            },
+        ] {
+            assert_eq!(source.generate(3).adj_list(), source.generate(3).adj_list());
+            assert_eq!(source.to_string().parse(), Ok(source));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let case = Case::record(
+            &Registry::default(),
+            "degeneracy",
+            GraphSource::Bernoulli { nodes: 20, p: 0.3 },
+            5,
+            vec![("k".to_string(), 4.0)],
+        )
+        .unwrap();
+        assert_eq!(case.outcome, Outcome::Pass);
+
+        let parsed: Case = case.to_string().parse().unwrap();
+        assert_eq!(parsed.to_string(), case.to_string());
+        assert_eq!(parsed.params, vec![("k".to_string(), 4.0)]);
+        assert_eq!(
+            parsed.coloring.unwrap().colors(),
+            case.coloring.unwrap().colors()
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let error = "algorithm: bcg\ngraph: bernoulli 10\n"
+            .parse::<Case>()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "line 2: unknown graph \"bernoulli 10\"");
+
+        let error = "algorithm: bcg\n".parse::<Case>().unwrap_err();
+        assert_eq!(error.to_string(), "line 1: missing graph");
+    }
+
+    #[test]
+    fn replay() {
+        let mut registry = Registry::default();
+        // Colors every vertex the same, which is only proper without edges
+        registry.register("one", "Every vertex colored 0", |graph| {
+            Some(graph.vertices().into_iter().map(|v| (*v, 0)).collect())
+        });
+
+        let corpus = Corpus::open(directory("replay")).unwrap();
+        let graph = GraphSource::Bernoulli { nodes: 12, p: 0.5 };
+        for algorithm in ["one", "greedy"] {
+            let case = Case::record(&registry, algorithm, graph, 1, vec![]).unwrap();
+            corpus.save(&case).unwrap();
+        }
+
+        let replays: Vec<_> = corpus
+            .replay(&registry)
+            .unwrap()
+            .into_iter()
+            .map(|(case, replay)| (case.algorithm, replay))
+            .collect();
+        assert_eq!(
+            replays,
+            vec![
+                ("greedy".to_string(), Replay::Passed),
+                ("one".to_string(), Replay::Failing)
+            ]
+        );
+
+        // Once the algorithm is fixed, the failing case passes
+        registry.register("one", "Greedy, under another name", |graph| {
+            Some(graph.greedy(None))
+        });
+        let fixed = corpus.replay(&registry).unwrap();
+        assert_eq!(fixed[1].1, Replay::Fixed);
+
+        // A case whose coloring does not match its outcome was recorded from another graph
+        let mut stale = fixed[0].0.clone();
+        stale.outcome = Outcome::Fail;
+        assert_eq!(stale.replay(&registry).unwrap(), Replay::Stale);
+
+        fs::remove_dir_all(corpus.directory()).unwrap();
+    }
+}
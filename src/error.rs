@@ -0,0 +1,76 @@
+//! Errors of the crate
+//!
+//! The fallible constructors and queries return a [Result], rather than panicking on parameters, orders or streams they cannot
+//! work with. The errors of the modules that have their own, e.g. [ParseGraphError], convert into an [Error], so `?` works
+//! across them. The few constructors that still panic are named `*_unchecked`, for callers that already validated their input.
+
+use rand::distributions::Bernoulli;
+use thiserror::Error;
+
+use crate::{
+    corpus::CorpusError,
+    graph::{
+        parse::ParseGraphError, static_a::tree::TreeError,
+        streaming::coloring::ack::AckParametersError, streaming::io::ReadError, VertexId,
+    },
+    registry::RegistryError,
+};
+
+/// Errors of the crate
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A probability outside of `[0, 1]`, or NaN
+    #[error("Probability must be within [0, 1], not {0}")]
+    InvalidProbability(f64),
+    /// The order of a finite field of characteristic 2 that is not two to the degree of its irreducible polynomial
+    #[error(
+        "[FiniteField] Order must be two to the degree of the irreducible polynomial, not {0}"
+    )]
+    FieldOrder(u64),
+    /// A streaming colorer found no color of its samples for the vertex, left to its neighbors in the conflict graph
+    #[error("[StreamColoring] No color available for vertex {0}")]
+    NoColorAvailable(VertexId),
+    /// A structure sized from the vertices of a graph was given none
+    #[error("The graph must have at least one vertex")]
+    EmptyGraph,
+    /// There were more conflicting edges than the sparse recovery of a streaming colorer could recover
+    #[error("[StreamColoring] The conflicting edges could not be recovered")]
+    Unrecovered,
+    #[error(transparent)]
+    AckParameters(#[from] AckParametersError),
+    #[error(transparent)]
+    Parse(#[from] ParseGraphError),
+    #[error(transparent)]
+    Tree(#[from] TreeError),
+    #[error(transparent)]
+    Read(#[from] ReadError),
+    #[error(transparent)]
+    Corpus(#[from] CorpusError),
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A Bernoulli distribution of probability `p`
+pub(crate) fn bernoulli(p: f64) -> Result<Bernoulli> {
+    Bernoulli::new(p).map_err(|_| Error::InvalidProbability(p))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn converts() {
+        let error: Error = AckParametersError::NotPositive("epsilon", 0.0).into();
+        assert_eq!(error.to_string(), "epsilon must be positive, not 0");
+
+        assert!(matches!(bernoulli(1.5), Err(Error::InvalidProbability(p)) if p == 1.5));
+        assert!(matches!(
+            bernoulli(f64::NAN),
+            Err(Error::InvalidProbability(_))
+        ));
+        assert!(bernoulli(0.5).is_ok());
+    }
+}
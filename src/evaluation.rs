@@ -0,0 +1,286 @@
+//! Evaluation of Colorers
+//!
+//! Comparing the colorers of this crate used to take a handful of ignored tests, each running one colorer on one graph and printing
+//! its own numbers. [evaluate] runs every algorithm of a [Registry] on every graph of a [Suite], for every seed, and collects the
+//! colors used, the runtime and the space of the streaming structures into a single [Report]:
+//!
+//! ```text
+//! graph                    algorithm    runs  failures  colors  max  runtime    memory
+//! bernoulli 60 0.2         degeneracy   3     0         6.3     7    0.4 ms     -
+//! bernoulli 60 0.2         bcg          3     0         9.0     10   2.1 ms     38.2 kB
+//! ```
+//!
+//! The graphs are generated from their [GraphSource] and seed with the same generator as the regression [corpus](crate::corpus),
+//! so that a surprising measurement can be recorded there as a case and replayed.
+
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    corpus::{proper, GraphSource},
+    graph::streaming::space::human_bytes,
+    registry::Registry,
+};
+
+/// The graphs the colorers are compared on, each generated once per seed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suite {
+    pub graphs: Vec<GraphSource>,
+    pub seeds: Vec<u64>,
+}
+
+impl Suite {
+    pub fn init(graphs: Vec<GraphSource>, seeds: Vec<u64>) -> Self {
+        Self { graphs, seeds }
+    }
+
+    /// Sparse and dense Bernoulli graphs, communities, regular graphs and a planted clique, of a few hundred vertices, on 5 seeds
+    pub fn standard() -> Self {
+        Self::init(
+            vec![
+                GraphSource::Bernoulli {
+                    nodes: 300,
+                    p: 0.05,
+                },
+                GraphSource::Bernoulli { nodes: 200, p: 0.5 },
+                GraphSource::StochasticBlock {
+                    blocks: 6,
+                    size: 40,
+                    p_in: 0.6,
+                    p_out: 0.02,
+                },
+                GraphSource::Regular {
+                    nodes: 300,
+                    degree: 8,
+                },
+                GraphSource::PlantedClique {
+                    nodes: 300,
+                    p: 0.1,
+                    k: 30,
+                },
+            ],
+            (0..5).collect(),
+        )
+    }
+}
+
+impl Default for Suite {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// A run of an algorithm on a graph of the suite
+#[derive(Clone, Debug)]
+pub struct Measurement {
+    pub algorithm: &'static str,
+    pub graph: GraphSource,
+    pub seed: u64,
+    /// Colors of the partition, `None` if the algorithm failed to produce one
+    pub colors: Option<usize>,
+    /// Whether the partition is a proper coloring of every vertex
+    pub proper: bool,
+    pub runtime: Duration,
+    /// Bytes of the data structures of the algorithm, `None` if it does not report them
+    pub memory: Option<usize>,
+}
+
+/// Measurements of an algorithm on a graph, over the seeds
+#[derive(Clone, Debug)]
+pub struct Summary {
+    pub algorithm: &'static str,
+    pub graph: GraphSource,
+    pub runs: usize,
+    /// Runs without a proper coloring
+    pub failures: usize,
+    /// Mean colors of the proper colorings, `None` if there were none
+    pub mean_colors: Option<f64>,
+    pub max_colors: Option<usize>,
+    pub mean_runtime: Duration,
+    pub mean_memory: Option<f64>,
+}
+
+/// Every measurement of an evaluation, in the order of the suite: by graph, then seed, then algorithm
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub measurements: Vec<Measurement>,
+}
+
+/// Run every algorithm of the registry on every graph of the suite
+pub fn evaluate(registry: &Registry, suite: &Suite) -> Report {
+    let mut measurements = vec![];
+
+    for graph_source in &suite.graphs {
+        for seed in &suite.seeds {
+            let graph = graph_source.generate(*seed);
+
+            for algorithm in registry.algorithms() {
+                let start = Instant::now();
+                let (coloring, space) = algorithm.run_measured(&graph);
+                let runtime = start.elapsed();
+
+                let coloring = coloring.ok();
+                measurements.push(Measurement {
+                    algorithm: algorithm.name,
+                    graph: *graph_source,
+                    seed: *seed,
+                    colors: coloring.as_ref().map(|coloring| coloring.num_classes()),
+                    proper: coloring
+                        .as_ref()
+                        .is_some_and(|coloring| proper(&graph, coloring)),
+                    runtime,
+                    memory: space.map(|space| space.measured_bytes),
+                });
+            }
+        }
+    }
+
+    Report { measurements }
+}
+
+impl Report {
+    /// Measurements aggregated over the seeds, by graph then algorithm, in the order of the suite
+    pub fn summaries(&self) -> Vec<Summary> {
+        let mut groups: Vec<Vec<&Measurement>> = vec![];
+        for measurement in &self.measurements {
+            match groups.iter_mut().find(|group| {
+                group[0].graph == measurement.graph && group[0].algorithm == measurement.algorithm
+            }) {
+                Some(group) => group.push(measurement),
+                None => groups.push(vec![measurement]),
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|group| {
+                let runs = group.len();
+                let colors: Vec<usize> = group
+                    .iter()
+                    .filter(|measurement| measurement.proper)
+                    .filter_map(|measurement| measurement.colors)
+                    .collect();
+                let memory: Vec<usize> = group
+                    .iter()
+                    .filter_map(|measurement| measurement.memory)
+                    .collect();
+                let mean = |values: &[usize]| {
+                    (!values.is_empty())
+                        .then(|| values.iter().sum::<usize>() as f64 / values.len() as f64)
+                };
+
+                Summary {
+                    algorithm: group[0].algorithm,
+                    graph: group[0].graph,
+                    runs,
+                    failures: runs - colors.len(),
+                    mean_colors: mean(&colors),
+                    max_colors: colors.iter().max().copied(),
+                    mean_runtime: group
+                        .iter()
+                        .map(|measurement| measurement.runtime)
+                        .sum::<Duration>()
+                        / runs as u32,
+                    mean_memory: mean(&memory),
+                }
+            })
+            .collect()
+    }
+
+    /// Fewest colors of a proper coloring of the graph, over all algorithms and seeds
+    pub fn best(&self, graph: &GraphSource) -> Option<usize> {
+        self.measurements
+            .iter()
+            .filter(|measurement| measurement.graph == *graph && measurement.proper)
+            .filter_map(|measurement| measurement.colors)
+            .min()
+    }
+}
+
+impl Display for Report {
+    /// A table of the [summaries](Report::summaries)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:<12} {:<5} {:<9} {:<7} {:<4} {:<10} memory",
+            "graph", "algorithm", "runs", "failures", "colors", "max", "runtime"
+        )?;
+
+        for summary in self.summaries() {
+            let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "{:<24} {:<12} {:<5} {:<9} {:<7} {:<4} {:<10} {}",
+                summary.graph.to_string(),
+                summary.algorithm,
+                summary.runs,
+                summary.failures,
+                or_dash(summary.mean_colors.map(|colors| format!("{:.1}", colors))),
+                or_dash(summary.max_colors.map(|colors| colors.to_string())),
+                format!("{:.1} ms", summary.mean_runtime.as_secs_f64() * 1000.0),
+                or_dash(summary.mean_memory.map(human_bytes)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_algorithm_on_every_graph() {
+        let registry = Registry::default();
+        let suite = Suite::init(
+            vec![
+                GraphSource::Bernoulli { nodes: 30, p: 0.3 },
+                GraphSource::StochasticBlock {
+                    blocks: 3,
+                    size: 10,
+                    p_in: 0.7,
+                    p_out: 0.05,
+                },
+                GraphSource::Regular {
+                    nodes: 30,
+                    degree: 4,
+                },
+                GraphSource::PlantedClique {
+                    nodes: 30,
+                    p: 0.1,
+                    k: 8,
+                },
+            ],
+            vec![0, 1],
+        );
+
+        let report = evaluate(&registry, &suite);
+        let algorithms = registry.names().count();
+        assert_eq!(report.measurements.len(), 4 * 2 * algorithms);
+
+        for measurement in &report.measurements {
+            if ["degeneracy", "greedy", "randomized"].contains(&measurement.algorithm) {
+                assert!(measurement.proper, "{:?}", measurement);
+                assert_eq!(measurement.memory, None);
+            }
+            if measurement.algorithm == "bcg" {
+                assert!(measurement.memory.is_some());
+            }
+        }
+
+        // No coloring of the planted clique has fewer colors than the clique
+        assert!(report.best(&suite.graphs[3]).unwrap() >= 8);
+
+        let summaries = report.summaries();
+        assert_eq!(summaries.len(), 4 * algorithms);
+        assert!(summaries.iter().all(|summary| summary.runs == 2));
+        assert_eq!(
+            report.to_string().lines().count(),
+            summaries.len() + 1,
+            "{}",
+            report
+        );
+    }
+}
@@ -1,15 +1,18 @@
 //! Contains all things related to graphs
 
 use std::{
-    cmp::Reverse,
-    collections::{HashMap, HashSet},
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     fmt::{Debug, Display},
-    hash::Hash,
+    hash::{Hash, Hasher},
     str::FromStr,
 };
 
+use self::{
+    parse::{ParseGraphError, Weight},
+    recaller::{Degrees, Recaller},
+};
 use itertools::Itertools;
-use priority_queue::PriorityQueue;
 
 #[doc(hidden)]
 pub mod edge;
@@ -17,6 +20,12 @@ pub mod edge;
 #[doc(inline)]
 pub use edge::*;
 
+/// Vertex identifiers used by the streaming algorithms and random graph generators
+///
+/// Vertices are dense ids in `[n]`, and edges are indexed in `[n choose 2]` as a `u64`, see [Edge::to_d1].
+/// Using the same width for both avoids lossy conversions between the two spaces.
+pub type VertexId = u64;
+
 /// A graph is, conceptually, a tuple G = (V, E), where:
 ///
 /// V \subset R i.e. The graphs set of vertices
@@ -33,14 +42,16 @@ pub trait Graphed<T, W>: Clone + Sized + Debug {
     fn vertices(&self) -> HashSet<&T>;
     /// Get the neighbors of a provided vertex
     fn get_neighbors(&self, vertex: &T) -> Option<&HashSet<EdgeDestination<T, W>>>;
-    /// Add an edge to a graph, if the vertices of the edge do not exist, the edge is not added.
+    /// Add an edge to a graph, adding its vertices if they do not exist yet. It cannot fail.
     fn add_edge(&mut self, edge: Edge<T, W>);
-    /// Remove an edge from a graph
+    /// Remove an edge from a graph, its endpoints stay in the graph even when left without neighbors
     fn remove_edge(&mut self, edge: Edge<T, W>);
-    /// Remove a vertex, and all of it's incident edges from the graph
+    /// Remove a vertex, and all of it's incident edges from the graph, its neighbors stay in the graph even when left isolated
     fn remove_vertex(&mut self, vertex: &T);
     /// Fetch the minimum degree of a graph
     fn min_degree(&self) -> Option<(T, usize)>;
+    /// Fetch the maximum degree of a graph, `Δ`
+    fn max_degree(&self) -> Option<(T, usize)>;
     /// Remove the vertex of minimum degree
     fn remove_min(&mut self) -> Option<T>;
     /// Check if the graph is empty.
@@ -49,6 +60,23 @@ pub trait Graphed<T, W>: Clone + Sized + Debug {
     fn has_edge(&self, edge: &Edge<T, W>) -> bool;
 }
 
+/// The number of neighbors of both endpoints of the edge, `None` for an endpoint not in the graph
+///
+/// An edit changed the graph if and only if it changed these.
+pub(crate) fn endpoint_degrees<T, W, G>(
+    graph: &G,
+    edge: &Edge<T, W>,
+) -> (Option<usize>, Option<usize>)
+where
+    G: Graphed<T, W>,
+    T: Eq + PartialOrd,
+    W: Default,
+{
+    let (u, v) = edge.vertices();
+    let degree = |vertex| graph.get_neighbors(vertex).map(HashSet::len);
+    (degree(u), degree(v))
+}
+
 /// Simple Graph
 ///
 /// Simplest version of a Graph that contains just the Adjacency list, where each destination may or may not have an edge weight associated with it.
@@ -62,50 +90,68 @@ where
 
 /// A more comprehensive Graph representation
 ///
-/// This graph also holds a PriorityQueue to keep track of vertex degrees.
-
+/// This graph also keeps track of vertex degrees, in the two heaps of [Degrees], one for the vertex of minimum degree, and one for
+/// the vertex of maximum degree. Any other [Recaller] can be attached, to keep more invariants of the graph up to date as it is
+/// edited, see [recaller].
 #[derive(Clone, Debug, Default)]
-pub struct GraphWithRecaller<T, W>
+pub struct GraphWithRecaller<T, W, R = ()>
 where
     T: Hash + Eq,
 {
     graph: Graph<T, W>,
     /// Component of the graph that keeps track of degree orderings
-    vertex_heap: PriorityQueue<T, Reverse<usize>>,
+    degrees: Degrees<T>,
+    /// Invariants attached to the graph
+    recaller: R,
 }
 
-impl<T, W> From<Graph<T, W>> for GraphWithRecaller<T, W>
+impl<T, W, R> From<Graph<T, W>> for GraphWithRecaller<T, W, R>
 where
-    T: Hash + Eq + Clone,
-    W: Clone,
+    T: Hash + Eq + Clone + PartialOrd,
+    W: Clone + Default,
+    R: Recaller<T, W>,
 {
     /// Add a vertex recaller structure to our Graph
     ///
-    /// This enabled us to always know the vertex of minimum degree
+    /// This enabled us to always know the vertices of minimum and maximum degree
     ///
-    /// Runtime: `O(nlog(n))`
+    /// Runtime: `O(nlog(n))`, and the initialization of the recaller
     fn from(graph: Graph<T, W>) -> Self {
-        let mut queue = PriorityQueue::new();
-
-        graph
-            .adjacency_list
-            .clone()
-            .into_iter()
-            .for_each(|(v, edges)| {
-                queue.push(v, Reverse(edges.len()));
-            });
-
         Self {
+            degrees: Recaller::init(&graph),
+            recaller: R::init(&graph),
             graph,
-            vertex_heap: queue,
         }
     }
 }
 
-impl<T, W> Graphed<T, W> for GraphWithRecaller<T, W>
+impl<T, W, R> GraphWithRecaller<T, W, R>
+where
+    T: Hash + Eq,
+{
+    pub fn graph(&self) -> &Graph<T, W> {
+        &self.graph
+    }
+
+    pub fn degrees(&self) -> &Degrees<T> {
+        &self.degrees
+    }
+
+    pub fn recaller(&self) -> &R {
+        &self.recaller
+    }
+
+    /// The graph and the recaller, which are no longer linked
+    pub fn into_parts(self) -> (Graph<T, W>, R) {
+        (self.graph, self.recaller)
+    }
+}
+
+impl<T, W, R> Graphed<T, W> for GraphWithRecaller<T, W, R>
 where
     T: Debug + Hash + Eq + Clone + PartialOrd,
     W: Debug + Hash + Eq + Clone + Default,
+    R: Recaller<T, W> + Clone + Debug,
 {
     fn adj_list(&self) -> &HashMap<T, HashSet<EdgeDestination<T, W>>> {
         self.graph.adj_list()
@@ -121,35 +167,24 @@ where
         self.graph.vertices()
     }
 
-    /// Runtime: O(nlog(n))
+    /// Runtime: O(log(n)), the recallers only see edits that change the graph
     fn add_edge(&mut self, edge: Edge<T, W>) {
+        let before = endpoint_degrees(&self.graph, &edge);
         self.graph.add_edge(edge.clone());
-        let (v1, v2) = edge.vertices();
-
-        self.vertex_heap.push_decrease(
-            v1.clone(),
-            Reverse(self.graph.get_neighbors(v1).unwrap().len()),
-        );
-
-        if !edge.directed {
-            self.vertex_heap.push_decrease(
-                v2.clone(),
-                Reverse(self.graph.get_neighbors(v2).unwrap().len()),
-            );
+        if endpoint_degrees(&self.graph, &edge) != before {
+            self.degrees.on_edge_added(&self.graph, &edge);
+            self.recaller.on_edge_added(&self.graph, &edge);
         }
     }
 
+    /// Endpoints left without neighbors stay in the graph, with a degree of 0
     fn remove_edge(&mut self, edge: Edge<T, W>) {
+        let before = endpoint_degrees(&self.graph, &edge);
         self.graph.remove_edge(edge.clone());
-        let (v1, v2) = edge.vertices();
-        self.vertex_heap.push_increase(
-            v1.clone(),
-            Reverse(self.graph.get_neighbors(v1).unwrap().len()),
-        );
-        self.vertex_heap.push_increase(
-            v2.clone(),
-            Reverse(self.graph.get_neighbors(v2).unwrap().len()),
-        );
+        if endpoint_degrees(&self.graph, &edge) != before {
+            self.degrees.on_edge_removed(&self.graph, &edge);
+            self.recaller.on_edge_removed(&self.graph, &edge);
+        }
     }
     /// Runtime: O(1)
     fn get_neighbors(&self, vertex: &T) -> Option<&HashSet<EdgeDestination<T, W>>> {
@@ -161,32 +196,34 @@ where
     }
     /// Runtime: O(1)
     fn min_degree(&self) -> Option<(T, usize)> {
-        self.vertex_heap.peek().map(|(v, r)| (v.clone(), r.0))
+        self.degrees.min()
+    }
+    /// Runtime: O(1)
+    fn max_degree(&self) -> Option<(T, usize)> {
+        self.degrees.max()
     }
     ///
     /// Runtime: O(nlog(n)); where n = number of neighbors
     fn remove_vertex(&mut self, vertex: &T) {
-        let Self { graph, vertex_heap } = self;
+        let neighbors: Option<Vec<T>> = self
+            .graph
+            .get_neighbors(vertex)
+            .map(|neighbors| neighbors.iter().map(|n| n.destination.clone()).collect());
 
-        if let Some(neighbors) = graph.adjacency_list.get(&vertex) {
-            neighbors.iter().for_each(|neighbor| {
-                let destination = &neighbor.destination;
-                if let Some(current) = vertex_heap.get_priority(destination).cloned() {
-                    vertex_heap.change_priority(destination, Reverse(current.0 - 1));
-                }
-            })
+        if let Some(neighbors) = neighbors {
+            self.graph.remove_vertex(vertex);
+            self.degrees
+                .on_vertex_removed(&self.graph, vertex, &neighbors);
+            self.recaller
+                .on_vertex_removed(&self.graph, vertex, &neighbors);
         }
-        self.graph.remove_vertex(&vertex);
     }
     /// Runtime: O(nlog(n))
     fn remove_min(&mut self) -> Option<T> {
-        let Self { vertex_heap, .. } = self;
-        if let Some((vertex, _)) = vertex_heap.pop() {
+        self.min_degree().map(|(vertex, _)| {
             self.remove_vertex(&vertex);
-            return Some(vertex);
-        }
-
-        None
+            vertex
+        })
     }
 
     fn has_edge(&self, edge: &Edge<T, W>) -> bool {
@@ -252,6 +289,14 @@ where
         vertex.and_then(|v| min.map(|m| (v, m)))
     }
 
+    /// Runtime: O(n)
+    fn max_degree(&self) -> Option<(T, usize)> {
+        self.adjacency_list
+            .iter()
+            .max_by_key(|(_, edges)| edges.len())
+            .map(|(v, edges)| (v.clone(), edges.len()))
+    }
+
     /// Runtime: O(1)
     fn is_empty(&self) -> bool {
         self.adjacency_list.is_empty()
@@ -262,19 +307,17 @@ where
         let graph = &mut self.adjacency_list;
         let (u, v) = edge.vertices();
 
-        if !graph.contains_key(&u) {
-            graph.insert(u.clone(), HashSet::new());
-        }
-        graph.get_mut(&u).unwrap().insert((&edge).into());
+        graph.entry(u.clone()).or_default().insert((&edge).into());
 
-        if !edge.directed {
-            if !graph.contains_key(&v) {
-                graph.insert(v.clone(), HashSet::new());
-            }
-            graph.get_mut(&v).unwrap().insert((&edge.reverse()).into());
+        if !edge.is_directed() {
+            graph
+                .entry(v.clone())
+                .or_default()
+                .insert((&edge.reverse()).into());
         }
     }
 
+    /// An arc is only removed from the adjacency list of its source
     fn remove_edge(&mut self, edge: Edge<T, W>) {
         let graph = &mut self.adjacency_list;
         let (u, v) = edge.vertices();
@@ -283,15 +326,10 @@ where
             .entry(u.clone())
             .and_modify(|set| set.retain(|dest| dest.destination != *v));
 
-        graph
-            .entry(v.clone())
-            .and_modify(|set| set.retain(|dest| dest.destination != *u));
-
-        if graph.get(u).map(|u| u.is_empty()).unwrap_or_default() {
-            graph.remove(u);
-        }
-        if graph.get(v).map(|v| v.is_empty()).unwrap_or_default() {
-            graph.remove(v);
+        if !edge.is_directed() {
+            graph
+                .entry(v.clone())
+                .and_modify(|set| set.retain(|dest| dest.destination != *u));
         }
     }
 
@@ -313,32 +351,6 @@ where
     }
 }
 
-fn from_str<G, T, W>(s: &str) -> Result<G, <T as FromStr>::Err>
-where
-    T: Debug + Hash + Eq + Clone + PartialOrd + FromStr,
-    W: Debug + Hash + Eq + Clone + Default,
-    G: Graphed<T, W>,
-{
-    let mut graph = G::new(Default::default());
-    s.lines().into_iter().try_for_each(|line| {
-        let mut split = line.split(':');
-        split.next().unwrap().trim().parse().and_then(|vertex: T| {
-            split
-                .next()
-                .unwrap()
-                .trim()
-                .split(',')
-                .try_for_each(|neighbor| -> Result<(), _> {
-                    neighbor.parse().map(|neighbor| {
-                        let edge = Edge::<T, W>::init_directed(vertex.clone(), neighbor);
-                        graph.add_edge(edge);
-                    })
-                })
-        })
-    })?;
-    Ok(graph)
-}
-
 fn to_str<G, T, W>(graph: &G) -> String
 where
     T: Debug + Hash + Eq + Clone + PartialOrd + Display + Ord,
@@ -363,24 +375,29 @@ where
 impl<T, W> std::str::FromStr for Graph<T, W>
 where
     T: Debug + Hash + Eq + Clone + PartialOrd + FromStr,
-    W: Debug + Hash + Eq + Clone + Default,
+    <T as FromStr>::Err: Display,
+    W: Debug + Hash + Eq + Clone + Weight,
 {
-    type Err = <T as FromStr>::Err;
+    type Err = ParseGraphError;
 
+    /// Parse the [text format](parse) of a graph
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        from_str(s)
+        parse::from_str(s)
     }
 }
 
-impl<T, W> std::str::FromStr for GraphWithRecaller<T, W>
+impl<T, W, R> std::str::FromStr for GraphWithRecaller<T, W, R>
 where
     T: Debug + Hash + Eq + Clone + PartialOrd + FromStr,
-    W: Debug + Hash + Eq + Clone + Default,
+    <T as FromStr>::Err: Display,
+    W: Debug + Hash + Eq + Clone + Weight,
+    R: Recaller<T, W> + Clone + Debug,
 {
-    type Err = <T as FromStr>::Err;
+    type Err = ParseGraphError;
 
+    /// Parse the [text format](parse) of a graph
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        from_str(s)
+        parse::from_str(s)
     }
 }
 
@@ -394,7 +411,7 @@ where
     }
 }
 
-impl<T, W> Display for GraphWithRecaller<T, W>
+impl<T, W, R> Display for GraphWithRecaller<T, W, R>
 where
     T: Debug + Hash + Eq + Clone + PartialOrd + Display + Ord,
     W: Debug + Hash + Eq + Clone + Default,
@@ -404,6 +421,24 @@ where
     }
 }
 
+/// An edge of the first vertex of the adjacency list, or the vertex if it is isolated, `None` if the list is empty
+///
+/// Iterating over a graph consumes it, so the isolated vertices met on the way are dropped rather than yielded.
+fn first_edge<T, W>(
+    adjacency_list: &HashMap<T, HashSet<EdgeDestination<T, W>>>,
+) -> Option<Result<Edge<T, W>, T>>
+where
+    T: Hash + Eq + Clone + PartialOrd,
+    W: Default,
+{
+    let (v1, neighbors) = adjacency_list.iter().next()?;
+
+    Some(match neighbors.iter().next() {
+        Some(v2) => Ok(Edge::init(v1.clone(), v2.destination.clone())),
+        None => Err(v1.clone()),
+    })
+}
+
 impl<T, W> Iterator for Graph<T, W>
 where
     T: Debug + Hash + Eq + Clone + PartialOrd + FromStr,
@@ -412,51 +447,193 @@ where
     type Item = Edge<T, W>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let Self { adjacency_list } = self;
-
-        let v1 = adjacency_list.iter().next().map(|e| e.0.clone());
-
-        if let Some(v1) = v1 {
-            let v2 = adjacency_list.get(&v1).and_then(|set| set.iter().next());
-
-            if let Some(v2) = v2 {
-                let edge = Edge::init(v1, v2.destination.clone());
-                self.remove_edge(edge.clone());
-                Some(edge)
-            } else {
-                None
+        loop {
+            match first_edge(&self.adjacency_list)? {
+                Ok(edge) => {
+                    self.remove_edge(edge.clone());
+                    return Some(edge);
+                }
+                Err(isolated) => {
+                    self.adjacency_list.remove(&isolated);
+                }
             }
-        } else {
-            None
         }
     }
 }
 
-impl<T, W> Iterator for GraphWithRecaller<T, W>
+impl<T, W, R> Iterator for GraphWithRecaller<T, W, R>
 where
     T: Debug + Hash + Eq + Clone + PartialOrd + FromStr,
     W: Debug + Hash + Eq + Clone + Default,
+    R: Recaller<T, W> + Clone + Debug,
 {
     type Item = Edge<T, W>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.graph.next().map(|next| {
-            self.remove_edge(next.clone());
-            next
-        })
+        loop {
+            match first_edge(&self.graph.adjacency_list)? {
+                Ok(edge) => {
+                    self.remove_edge(edge.clone());
+                    return Some(edge);
+                }
+                Err(isolated) => self.remove_vertex(&isolated),
+            }
+        }
     }
 }
 
 impl<T, W> PartialEq for Graph<T, W>
 where
-    T: Debug + Hash + Eq + Clone + PartialOrd + FromStr,
-    W: Debug + Hash + Eq + Clone + Default,
+    T: Hash + Eq,
+    W: Hash + Eq,
 {
+    /// Two graphs are equal when they have the same edges, with the same labels, vertices without neighbors aside
+    ///
+    /// Runtime: O(n + m)
     fn eq(&self, other: &Self) -> bool {
-        let edges: HashSet<Edge<T, W>> = self.clone().into_iter().collect();
-        let other: HashSet<Edge<T, W>> = other.clone().into_iter().collect();
+        let neighbors = |graph: &Self| {
+            graph
+                .adjacency_list
+                .values()
+                .filter(|neighbors| !neighbors.is_empty())
+                .count()
+        };
+
+        neighbors(self) == neighbors(other)
+            && self
+                .adjacency_list
+                .iter()
+                .filter(|(_, neighbors)| !neighbors.is_empty())
+                .all(|(v, neighbors)| other.adjacency_list.get(v) == Some(neighbors))
+    }
+}
 
-        edges.difference(&other).count() == 0
+impl<T, W> Eq for Graph<T, W>
+where
+    T: Hash + Eq,
+    W: Hash + Eq,
+{
+}
+
+impl<T, W> Hash for Graph<T, W>
+where
+    T: Hash + Eq,
+    W: Hash + Eq,
+{
+    /// The hash of every arc, with a fixed key, summed so that the order of the adjacency list does not matter
+    ///
+    /// Runtime: O(n + m)
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let (arcs, sum) = self
+            .adjacency_list
+            .iter()
+            .flat_map(|(v, neighbors)| neighbors.iter().map(move |n| (v, n)))
+            .fold((0_usize, 0_u64), |(arcs, sum), arc| {
+                let mut hasher = DefaultHasher::new();
+                arc.hash(&mut hasher);
+                (arcs + 1, sum.wrapping_add(hasher.finish()))
+            });
+
+        arcs.hash(state);
+        sum.hash(state);
+    }
+}
+
+impl<T, W, R> PartialEq for GraphWithRecaller<T, W, R>
+where
+    T: Hash + Eq,
+    W: Hash + Eq,
+{
+    /// The recallers only follow the graphs, which are compared
+    fn eq(&self, other: &Self) -> bool {
+        self.graph == other.graph
+    }
+}
+
+impl<T, W, R> Eq for GraphWithRecaller<T, W, R>
+where
+    T: Hash + Eq,
+    W: Hash + Eq,
+{
+}
+
+impl<T, W, R> Hash for GraphWithRecaller<T, W, R>
+where
+    T: Hash + Eq,
+    W: Hash + Eq,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.graph.hash(state)
+    }
+}
+
+impl<T, W> Graph<T, W>
+where
+    T: Hash + Eq + Clone + PartialOrd,
+    W: Hash + Eq + Clone + Default,
+{
+    /// Every edge of the graph, with its label, without consuming the graph
+    ///
+    /// An undirected edge is yielded once, from its smaller endpoint, and an arc without its reverse as a directed edge, so that
+    /// adding the edges to an empty graph gives back the graph.
+    ///
+    /// Runtime: O(n + mΔ), each edge checking for its reverse
+    pub fn edges(&self) -> impl Iterator<Item = Edge<T, W>> + '_ {
+        let adjacency_list = &self.adjacency_list;
+        adjacency_list.iter().flat_map(move |(u, neighbors)| {
+            neighbors.iter().filter_map(move |n| {
+                let v = &n.destination;
+                let reciprocated = adjacency_list
+                    .get(v)
+                    .into_iter()
+                    .flatten()
+                    .any(|back| back.destination == *u);
+                if reciprocated && v.partial_cmp(u) == Some(Ordering::Less) {
+                    return None;
+                }
+
+                let mut edge = if reciprocated {
+                    Edge::init(u.clone(), v.clone())
+                } else {
+                    Edge::init_directed(u.clone(), v.clone())
+                };
+                edge.update_label(n.label.clone());
+                Some(edge)
+            })
+        })
+    }
+
+    /// Every edge of the graph, with its label, consuming the graph
+    ///
+    /// Unlike iterating over the graph, which looks for the next edge from the start of the adjacency list, the adjacency list is
+    /// drained a vertex at a time. An edge and its reverse are yielded once, as long as they carry the same label, and an arc
+    /// without its reverse as a directed edge.
+    ///
+    /// Runtime: O(n + m)
+    pub fn into_edges(self) -> impl Iterator<Item = Edge<T, W>> {
+        let order: Vec<T> = self.adjacency_list.keys().cloned().collect();
+        let mut adjacency_list = self.adjacency_list;
+
+        order.into_iter().flat_map(move |u| {
+            let neighbors = adjacency_list.remove(&u).unwrap_or_default();
+            neighbors
+                .into_iter()
+                .map(|n| {
+                    let reverse = EdgeDestination::init_with_label(u.clone(), n.label.clone());
+                    let reciprocated = adjacency_list
+                        .get_mut(&n.destination)
+                        .is_some_and(|back| back.remove(&reverse));
+
+                    let mut edge = if reciprocated || n.destination == u {
+                        Edge::init(u.clone(), n.destination)
+                    } else {
+                        Edge::init_directed(u.clone(), n.destination)
+                    };
+                    edge.update_label(n.label);
+                    edge
+                })
+                .collect::<Vec<_>>()
+        })
     }
 }
 
@@ -478,7 +655,13 @@ where
     }
 }
 
+pub mod algorithms;
+pub mod editor;
+pub mod observer;
+pub mod parse;
+pub mod recaller;
 pub mod static_a;
+pub mod stats;
 pub mod streaming;
 
 #[cfg(test)]
@@ -495,18 +678,9 @@ mod test {
         graph.add_edge(Edge::init(2, 3));
         graph.add_edge(Edge::init(1, 3));
 
-        assert_eq!(
-            graph.vertex_heap.get_priority(&1).unwrap(),
-            &Reverse(2_usize)
-        );
-        assert_eq!(
-            graph.vertex_heap.get_priority(&2).unwrap(),
-            &Reverse(2_usize)
-        );
-        assert_eq!(
-            graph.vertex_heap.get_priority(&3).unwrap(),
-            &Reverse(2_usize)
-        );
+        assert_eq!(graph.degrees().degree(&1), Some(2));
+        assert_eq!(graph.degrees().degree(&2), Some(2));
+        assert_eq!(graph.degrees().degree(&3), Some(2));
     }
 
     #[test]
@@ -520,4 +694,228 @@ mod test {
             .parse()
             .unwrap();
     }
+
+    /// The vertices of a graph with their degree
+    fn degrees<G: Graphed<u32, ()>>(graph: &G) -> Vec<(u32, usize)> {
+        let mut degrees: Vec<_> = graph
+            .adj_list()
+            .iter()
+            .map(|(v, neighbors)| (*v, neighbors.len()))
+            .collect();
+        degrees.sort_unstable();
+        degrees
+    }
+
+    enum Edit {
+        Add(u32, u32),
+        Remove(u32, u32),
+        RemoveVertex(u32),
+    }
+
+    fn apply<G: Graphed<u32, ()>>(graph: &mut G, edit: &Edit) {
+        match edit {
+            Edit::Add(u, v) => graph.add_edge(Edge::init(*u, *v)),
+            Edit::Remove(u, v) => graph.remove_edge(Edge::init(*u, *v)),
+            Edit::RemoveVertex(v) => graph.remove_vertex(v),
+        }
+    }
+
+    #[test]
+    fn isolated_vertices() {
+        let path: Graph<u32, ()> = r"0: 1
+        1: 0,2
+        2: 1,3
+        3: 2"
+            .parse()
+            .unwrap();
+        let mut graph = path.clone();
+        let mut recaller: GraphWithRecaller<u32, ()> = path.clone().into();
+        let mut observed = observer::ObservedGraph::init(path, vec![]);
+
+        // Every backend goes through the same edits, and keeps the vertices left without neighbors
+        let edits = [
+            (Edit::Remove(0, 1), vec![(0, 0), (1, 1), (2, 2), (3, 1)]),
+            (Edit::Remove(2, 4), vec![(0, 0), (1, 1), (2, 2), (3, 1)]),
+            (Edit::RemoveVertex(2), vec![(0, 0), (1, 0), (3, 0)]),
+            (Edit::RemoveVertex(0), vec![(1, 0), (3, 0)]),
+            (Edit::Add(3, 4), vec![(1, 0), (3, 1), (4, 1)]),
+            (Edit::Remove(4, 3), vec![(1, 0), (3, 0), (4, 0)]),
+        ];
+        for (edit, expected) in edits.iter() {
+            apply(&mut graph, edit);
+            apply(&mut recaller, edit);
+            apply(&mut observed, edit);
+
+            assert_eq!(&degrees(&graph), expected);
+            assert_eq!(&degrees(&recaller), expected);
+            assert_eq!(&degrees(&observed), expected);
+            // The heap holds exactly the vertices of the graph, with their degree
+            let mut heap: Vec<_> = recaller.degrees().iter().collect();
+            heap.sort_unstable();
+            assert_eq!(&heap, expected);
+            assert_eq!(
+                recaller.min_degree().map(|(_, d)| d),
+                graph.min_degree().map(|(_, d)| d)
+            );
+            assert_eq!(
+                recaller.max_degree().map(|(_, d)| d),
+                graph.max_degree().map(|(_, d)| d)
+            );
+        }
+
+        assert_eq!(recaller.min_degree().map(|(_, d)| d), Some(0));
+        assert_eq!(observed.observer().len(), 5);
+        // Iterating drops the isolated vertices, which have no edge to yield
+        assert_eq!(graph.count(), 0);
+        assert_eq!(recaller.count(), 0);
+    }
+    #[test]
+    fn mixed_arcs() {
+        let mut graph: Graph<u32, u32> = Graph::default();
+        let mut recaller: GraphWithRecaller<u32, u32> = Graph::default().into();
+
+        // The edge completes the arc with its reverse, and a new label is stored next to the old one
+        let mut labelled = Edge::init(0, 1);
+        labelled.update_label(7);
+        let edits = vec![
+            (Edge::init_directed(0, 1), vec![(0, 1)]),
+            (Edge::init(0, 1), vec![(0, 1), (1, 1)]),
+            (Edge::init_directed(1, 0), vec![(0, 1), (1, 1)]),
+            (labelled, vec![(0, 2), (1, 2)]),
+            (Edge::init_directed(2, 0), vec![(0, 2), (1, 2), (2, 1)]),
+        ];
+        for (edge, expected) in edits {
+            graph.add_edge(edge);
+            recaller.add_edge(edge);
+
+            assert_eq!(graph, *recaller.graph());
+            let mut heap: Vec<_> = recaller.degrees().iter().collect();
+            heap.sort_unstable();
+            assert_eq!(heap, expected);
+        }
+
+        // The reverse arc goes with the edge, even when the arc 0 -> 2 is missing
+        graph.remove_edge(Edge::init(0, 2));
+        recaller.remove_edge(Edge::init(0, 2));
+        assert_eq!(graph, *recaller.graph());
+        assert_eq!(recaller.degrees().degree(&2), Some(0));
+    }
+
+    #[test]
+    fn max_degree() {
+        let mut graph = GraphWithRecaller::<u32, ()>::new(Default::default());
+        assert_eq!(graph.max_degree(), None);
+
+        // A star around 0, and a triangle on 5, 6, 7
+        (1..5).for_each(|v| graph.add_edge(Edge::init(0, v)));
+        graph.add_edge(Edge::init(5, 6));
+        graph.add_edge(Edge::init(6, 7));
+        graph.add_edge(Edge::init(5, 7));
+        assert_eq!(graph.max_degree(), Some((0, 4)));
+
+        graph.remove_edge(Edge::init(0, 1));
+        graph.remove_edge(Edge::init(0, 2));
+        assert_eq!(graph.max_degree().map(|(_, d)| d), Some(2));
+
+        graph.remove_vertex(&6);
+        assert_eq!(graph.max_degree(), Some((0, 2)));
+        while graph.remove_min().is_some() {}
+        assert_eq!(graph.max_degree(), None);
+    }
+
+    #[test]
+    fn borrowed_edges() {
+        let mut graph: Graph<u32, u32> = Graph::default();
+        let mut weighted = Edge::init(0, 1);
+        weighted.update_label(7);
+        graph.add_edge(weighted);
+        graph.add_edge(Edge::init(1, 2));
+        graph.add_edge(Edge::init(3, 2));
+        graph.add_edge(Edge::init_directed(4, 0));
+
+        let mut edges: Vec<_> = graph
+            .edges()
+            .map(|e| {
+                let (u, v) = e.vertices();
+                (*u, *v, *e.label())
+            })
+            .collect();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![(0, 1, 7), (1, 2, 0), (2, 3, 0), (4, 0, 0)]);
+        // The graph is left as it was
+        assert_eq!(graph.edges().count(), graph.clone().count());
+
+        // Only the arc is yielded directed, and the edges rebuild the graph
+        assert!(graph.edges().any(|e| e == Edge::init_directed(4, 0)));
+        let mut rebuilt = Graph::default();
+        graph.edges().for_each(|e| rebuilt.add_edge(e));
+        assert_eq!(rebuilt, graph);
+        let mut rebuilt = Graph::default();
+        graph.clone().into_edges().for_each(|e| rebuilt.add_edge(e));
+        assert_eq!(rebuilt, graph);
+
+        let mut owned: Vec<_> = graph
+            .into_edges()
+            .map(|e| {
+                let (u, v) = e.vertices();
+                (*u.min(v), *u.max(v), *e.label())
+            })
+            .collect();
+        owned.sort_unstable();
+        assert_eq!(owned, vec![(0, 1, 7), (0, 4, 0), (1, 2, 0), (2, 3, 0)]);
+    }
+
+    #[test]
+    fn directed_removal() {
+        let mut graph: Graph<u32, ()> = Graph::default();
+        graph.add_edge(Edge::init_directed(0, 1));
+        graph.add_edge(Edge::init_directed(1, 0));
+        graph.add_edge(Edge::init(1, 2));
+
+        // The arc back from 1 is left alone
+        graph.remove_edge(Edge::init_directed(0, 1));
+        assert!(!graph.has_edge(&Edge::init_directed(0, 1)));
+        assert!(graph.has_edge(&Edge::init_directed(1, 0)));
+
+        graph.remove_edge(Edge::init(2, 1));
+        assert!(!graph.has_edge(&Edge::init(1, 2)));
+        assert!(!graph.has_edge(&Edge::init(2, 1)));
+    }
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equality() {
+        let path: Graph<u32, ()> = r"0: 1
+        1: 0,2
+        2: 1"
+            .parse()
+            .unwrap();
+        let mut other = Graph::default();
+        other.add_edge(Edge::init(2, 1));
+        other.add_edge(Edge::init(1, 0));
+        assert_eq!(path, other);
+        assert_eq!(hash_of(&path), hash_of(&other));
+
+        // A vertex left without neighbors does not tell the graphs apart
+        other.add_edge(Edge::init(2, 3));
+        assert_ne!(path, other);
+        assert_ne!(other, path);
+        other.remove_edge(Edge::init(2, 3));
+        assert_eq!(path, other);
+        assert_eq!(hash_of(&path), hash_of(&other));
+
+        other.remove_edge(Edge::init(0, 1));
+        other.add_edge(Edge::init(0, 2));
+        assert_ne!(path, other);
+
+        let recaller: GraphWithRecaller<u32, ()> = path.clone().into();
+        let graphs: HashSet<GraphWithRecaller<u32, ()>> =
+            vec![recaller.clone(), path.into()].into_iter().collect();
+        assert_eq!(graphs.len(), 1);
+    }
 }
@@ -0,0 +1,40 @@
+//! Graph Algorithms, by Area
+//!
+//! The core [Graphed](super::Graphed) trait only stores and edits a graph. Every algorithm is a method of an extension trait of
+//! its area, implemented for all [Graphed](super::Graphed) types, so that bringing the traits into scope is enough to call
+//! `graph.dijkstra(&start)` or `graph.color_degeneracy()`:
+//!
+//! ```
+//! use g_raph::graph::{algorithms::*, Graph};
+//!
+//! let graph: Graph<u32, ()> = "0: 1\n1: 0".parse().unwrap();
+//! assert!(graph.is_proper(&graph.color_degeneracy()));
+//! ```
+//!
+//! Glob importing this module covers the search, shortest path, coloring and matching traits. The algorithms of [heavy] are
+//! superlinear in the number of edges, or only worth it on large graphs, and have to be imported from it explicitly, so that they
+//! do not show up among the methods of every graph by accident.
+
+/// Maximum matchings of bipartite graphs
+pub use super::static_a::matching::MatchingT;
+/// Shortest paths of graphs whose labels are weights
+pub use super::static_a::search::WeightedSearch;
+/// Breadth and depth first searches, and the searchers they drive
+pub use super::static_a::search::{Search, Searcher};
+/// Colorings, greedy and of chordal graphs
+pub use super::static_a::{chordal::Chordal, coloring::Colorer, weighted::WeightedGreedy};
+
+/// Algorithms that are expensive enough to be opted into
+pub mod heavy {
+    /// Colorings improved by local search until a time budget expires
+    pub use crate::graph::static_a::anytime::AnytimeColorer;
+    /// Minimum cuts, in O(n^3)
+    pub use crate::graph::static_a::cut::MinCut;
+    /// The sparse-dense decomposition, in O(m Δ)
+    pub use crate::graph::static_a::decomposition::SparseDense;
+    /// Feedback arc sets of directed graphs, in O(n^2 + m)
+    pub use crate::graph::static_a::feedback::FeedbackArcSet;
+    /// Parallel searches and colorings, with the `rayon` feature
+    #[cfg(feature = "rayon")]
+    pub use crate::graph::static_a::parallel::{ParallelColoring, ParallelSearch};
+}
@@ -1,10 +1,24 @@
 //! Supporting Edge Definitions
 
-use roots::find_roots_quadratic;
-use std::fmt::Debug;
+use num_integer::Roots;
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
-/// Undirected Edge
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+use super::{
+    parse::{self, ParseGraphError, Weight},
+    VertexId,
+};
+
+/// An edge, undirected unless built by [init_directed](Edge::init_directed)
+///
+/// An undirected edge is the same edge whichever order its endpoints are given in, so that it compares and hashes equal to its
+/// [reverse](Edge::reverse), while an arc only equals arcs of the same source and destination. An edge never equals an arc.
+#[derive(Debug, Clone, Copy)]
 pub struct Edge<T, W> {
     /// If directed, the source
     v1: T,
@@ -12,8 +26,8 @@ pub struct Edge<T, W> {
     v2: T,
     /// The weight, or any label associated with the edge
     label: W,
-    // Whether or not the edge is directed
-    pub directed: bool,
+    /// Whether or not the edge is directed
+    directed: bool,
 }
 
 impl<T, W> Edge<T, W>
@@ -46,17 +60,28 @@ where
         self.label = new;
     }
 
+    pub fn label(&self) -> &W {
+        &self.label
+    }
+
+    /// Whether the edge is an arc, from the first of its [vertices](Edge::vertices) to the second
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
     /// Determines whether a vertex is incident to an edge
     pub fn is_incident(&self, vertex: &T) -> bool {
         self.v1 == *vertex || self.v2 == *vertex
     }
 
-    /// Returns the vertices indicent to an edge
+    /// Returns the vertices indicent to an edge, the source first if directed, in the order they were given otherwise
     pub fn vertices(&self) -> (&T, &T) {
         (&self.v1, &self.v2)
     }
 
     /// Reverse the direction of the edge, but swapping v1 and v2
+    ///
+    /// The reverse of an arc is the arc back from its destination, that of an undirected edge is the same edge.
     pub fn reverse(self) -> Self {
         Self {
             v1: self.v2,
@@ -67,42 +92,171 @@ where
     }
 }
 
-impl<W> Edge<u32, W>
+impl<T: PartialOrd, W> Edge<T, W> {
+    /// The endpoints of the edge, the smaller first
+    fn endpoints(&self) -> (&T, &T) {
+        if self.v2 < self.v1 {
+            (&self.v2, &self.v1)
+        } else {
+            (&self.v1, &self.v2)
+        }
+    }
+}
+
+impl<T, W> PartialEq for Edge<T, W>
+where
+    T: PartialEq,
+    W: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        if self.directed != other.directed || self.label != other.label {
+            return false;
+        }
+        let forward = self.v1 == other.v1 && self.v2 == other.v2;
+        forward || (!self.directed && self.v1 == other.v2 && self.v2 == other.v1)
+    }
+}
+
+impl<T: Eq, W: Eq> Eq for Edge<T, W> {}
+
+impl<T, W> Hash for Edge<T, W>
+where
+    T: Hash + PartialOrd,
+    W: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let (u, v) = if self.directed {
+            (&self.v1, &self.v2)
+        } else {
+            self.endpoints()
+        };
+        u.hash(state);
+        v.hash(state);
+        self.label.hash(state);
+        self.directed.hash(state);
+    }
+}
+
+impl<T: Ord, W: Ord> PartialOrd for Edge<T, W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord, W: Ord> Ord for Edge<T, W> {
+    /// Lexicographic on the endpoints, the smaller first, undirected edges coming before arcs, and arcs by their source, then by
+    /// their label
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.endpoints()
+            .cmp(&other.endpoints())
+            .then(self.directed.cmp(&other.directed))
+            .then_with(|| {
+                if self.directed {
+                    self.v1.cmp(&other.v1)
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .then_with(|| self.label.cmp(&other.label))
+    }
+}
+
+impl<T: Display, W: Weight> Display for Edge<T, W> {
+    /// `u,v`, or `u->v` for an arc, followed by the weight if the label has one, as in `u,v:w`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let separator = if self.directed { "->" } else { "," };
+        write!(f, "{}{}{}", self.v1, separator, self.v2)?;
+        match self.label.format_weight() {
+            Some(weight) => write!(f, ":{}", weight),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T, W> FromStr for Edge<T, W>
+where
+    T: FromStr + Eq + PartialOrd,
+    <T as FromStr>::Err: Display,
+    W: Weight,
+{
+    type Err = ParseGraphError;
+
+    /// Parse a line of an edge list, `u v` or `u,v[:w]`, or an arc `u->v[:w]`, the format of [Display]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse::edge_from_str(s)
+    }
+}
+
+impl<W> Edge<VertexId, W>
 where
     W: Default,
 {
     /// Creates an edge from a 1-dimensional space value, assuming a total possible number of edges being n Choose 2
     ///
+    /// The larger vertex is the largest `max` with `max (max - 1) / 2 <= d1`, i.e. `⌈⌊√(1 + 8 d1)⌋ / 2⌉`, computed with an
+    /// integer square root, so that every index of a `u64` is inverted exactly.
+    ///
     /// Assumes default weight
+    ///
+    /// Runtime: O(1)
     pub fn from_d1(d1: u64) -> Self {
-        let roots = find_roots_quadratic(0.5, -0.5, -(d1 as f64));
-        match roots {
-            roots::Roots::Two([_, root]) => {
-                let max = (root / 1.0) as u64;
-                let min = d1 - ((max * (max - 1)) / 2);
-                Self {
-                    v1: min as u32,
-                    v2: max as u32,
-                    label: W::default(),
-                    directed: false,
-                }
-            }
-            _ => panic!(
-                "The quadratic didn't have two roots, while it should have {:?}",
-                roots
-            ),
+        let max = (1 + 8 * d1 as u128).sqrt().div_ceil(2);
+        let min = d1 as u128 - max * (max - 1) / 2;
+
+        Self {
+            v1: min as VertexId,
+            v2: max as VertexId,
+            label: W::default(),
+            directed: false,
+        }
+    }
+
+    /// [from_d1](Edge::from_d1) over the `u128` edge space, which holds the index of every edge between `u64` vertices
+    ///
+    /// With `m = ⌊√(2 d1)⌋`, the larger vertex is `m` or `m + 1`, so the square root is taken of a value that fits in a `u128`.
+    /// Panics if the index is beyond the last edge between `u64` vertices.
+    ///
+    /// Runtime: O(1)
+    pub fn from_d1_wide(d1: u128) -> Self {
+        let doubled = d1
+            .checked_mul(2)
+            .unwrap_or_else(|| panic!("[Edge] {} is not the index of an edge", d1));
+        let m = doubled.sqrt();
+        let max = if m * (m + 1) <= doubled { m + 1 } else { m };
+        let min = d1 - max * (max - 1) / 2;
+
+        let max = VertexId::try_from(max)
+            .unwrap_or_else(|_| panic!("[Edge] {} is not the index of an edge", d1));
+        Self {
+            v1: min as VertexId,
+            v2: max,
+            label: W::default(),
+            directed: false,
         }
     }
 
     /// Converts an edge in `n Choose 2` space to `n` space, provided a number of vertices in the graph
+    ///
+    /// Panics if the index does not fit in a `u64`, i.e. if the larger vertex is at least `2^32`, see
+    /// [to_d1_wide](Edge::to_d1_wide) for larger graphs.
     pub fn to_d1(&self) -> u64 {
         let (min, max) = self.vertices_ord();
 
         Self::formula(min, max)
     }
 
+    /// [to_d1](Edge::to_d1) in the `u128` edge space, which never overflows
+    ///
+    /// Sketches over more than `2^64` potential edges, e.g. a [SparseRecovery](crate::graph::streaming::sparse_recovery::s_sparse::SparseRecovery)
+    /// built by [init_wide](crate::graph::streaming::sparse_recovery::s_sparse::SparseRecovery::init_wide), are fed this index.
+    pub fn to_d1_wide(&self) -> u128 {
+        let (min, max) = self.vertices_ord();
+
+        Self::wide_formula(min, max)
+    }
+
     #[doc(hidden)]
-    pub fn vertices_ord(&self) -> (&u32, &u32) {
+    pub fn vertices_ord(&self) -> (&VertexId, &VertexId) {
         if self.v1 <= self.v2 {
             (&self.v1, &self.v2)
         } else {
@@ -111,27 +265,215 @@ where
     }
 
     #[doc(hidden)]
-    fn formula(min: &u32, max: &u32) -> u64 {
+    fn formula(min: &VertexId, max: &VertexId) -> u64 {
+        u64::try_from(Self::wide_formula(min, max)).unwrap_or_else(|_| {
+            panic!("[Edge] ({}, {}) has no index in a u64 edge space", min, max)
+        })
+    }
+
+    #[doc(hidden)]
+    fn wide_formula(min: &VertexId, max: &VertexId) -> u128 {
         if *max == 0 {
             return 0;
         }
-        (*max as u64 * ((*max as u64) - 1) / 2) + *min as u64
+        let (min, max) = (*min as u128, *max as u128);
+        max * (max - 1) / 2 + min
     }
 }
 
 #[cfg(test)]
 mod test {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
 
     use super::*;
+    use crate::graph::parse::ParseErrorKind;
 
     #[test]
     fn from_d1() {
         for i in 0..100 {
-            let edge = Edge::<u32, ()>::from_d1(i);
+            let edge = Edge::<VertexId, ()>::from_d1(i);
             let d1 = edge.to_d1();
             assert_eq!(i, d1);
         }
     }
+
+    #[test]
+    fn from_d1_round_trip() {
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+
+        // Indices of every magnitude, and the indices around the first edge of a larger vertex
+        let widths = (0..64).map(|bits| rng.gen_range(0..=u64::MAX >> bits));
+        let boundaries = (2..1 << 16).flat_map(|max: u64| {
+            let first = max * (max - 1) / 2;
+            [first - 1, first]
+        });
+        for d1 in widths.chain(boundaries).chain([u64::MAX]) {
+            let edge = Edge::<VertexId, ()>::from_d1(d1);
+            let (min, max) = edge.vertices();
+            assert!(min < max, "{} gives ({}, {})", d1, min, max);
+            assert_eq!(edge.to_d1(), d1);
+        }
+
+        // And the other way around
+        for _ in 0..1000 {
+            let max = rng.gen_range(1..1 << 32);
+            let min = rng.gen_range(0..max);
+            let edge = Edge::<VertexId, ()>::from_d1(Edge::<VertexId, ()>::init(min, max).to_d1());
+            assert_eq!(edge.vertices(), (&min, &max));
+        }
+    }
+
+    #[test]
+    fn from_d1_wide() {
+        let mut rng = ChaCha20Rng::seed_from_u64(4);
+        let last = Edge::<VertexId, ()>::init(u64::MAX - 1, u64::MAX);
+
+        for _ in 0..1000 {
+            let max = rng.gen_range(1..=u64::MAX);
+            let min = rng.gen_range(0..max);
+            let edge = Edge::<VertexId, ()>::init(min, max);
+            let wide = Edge::<VertexId, ()>::from_d1_wide(edge.to_d1_wide());
+            assert_eq!(wide.vertices(), (&min, &max));
+        }
+        for d1 in (0..1000).chain([u64::MAX as u128, u64::MAX as u128 + 1, last.to_d1_wide()]) {
+            assert_eq!(Edge::<VertexId, ()>::from_d1_wide(d1).to_d1_wide(), d1);
+        }
+        // The u64 space is the start of the u128 one
+        assert_eq!(
+            Edge::<VertexId, ()>::from_d1_wide(1 << 40).vertices(),
+            Edge::<VertexId, ()>::from_d1(1 << 40).vertices()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_d1_wide_overflow() {
+        let last = Edge::<VertexId, ()>::init(u64::MAX - 1, u64::MAX);
+        Edge::<VertexId, ()>::from_d1_wide(last.to_d1_wide() + 1);
+    }
+
+    #[test]
+    fn to_d1_largest() {
+        let max = u32::MAX as VertexId;
+        let edge = Edge::<VertexId, ()>::init(max - 1, max);
+
+        assert_eq!(edge.to_d1(), max * (max - 1) / 2 + max - 1);
+    }
+
+    #[test]
+    fn to_d1_wide() {
+        let mut rng = ChaCha20Rng::seed_from_u64(5);
+        let edge = |min: VertexId, max: VertexId| Edge::<VertexId, ()>::init(min, max);
+
+        // The first edges past the u64 space follow on from its last one
+        assert_eq!(edge(0, 1 << 32).to_d1_wide(), (1 << 63) - (1 << 31));
+        assert_eq!(
+            edge(0, 1 << 32).to_d1_wide(),
+            edge((1 << 32) - 2, (1 << 32) - 1).to_d1_wide() + 1
+        );
+        assert_eq!(
+            edge(0, u64::MAX).to_d1_wide(),
+            edge(u64::MAX - 2, u64::MAX - 1).to_d1_wide() + 1
+        );
+
+        for _ in 0..1000 {
+            let max = rng.gen_range(1 << 32..=u64::MAX);
+            let min = rng.gen_range(0..max);
+            let d1 = edge(min, max).to_d1_wide();
+            assert!(d1 >= (1 << 63) - (1 << 31));
+            assert_eq!(edge(max, min).to_d1_wide(), d1);
+            assert_eq!(
+                Edge::<VertexId, ()>::from_d1_wide(d1).vertices(),
+                (&min, &max)
+            );
+        }
+    }
+
+    #[test]
+    fn direction() {
+        let hash = |edge: &Edge<VertexId, u8>| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            edge.hash(&mut hasher);
+            hasher.finish()
+        };
+        let (edge, arc) = (Edge::init(3, 1), Edge::init_directed(3, 1));
+
+        assert_eq!(edge, edge.reverse());
+        assert_eq!(hash(&edge), hash(&edge.reverse()));
+        assert_eq!(edge.reverse().vertices(), (&1, &3));
+        assert!(!edge.is_directed());
+
+        assert_ne!(arc, arc.reverse());
+        assert!(arc.reverse().is_directed());
+        assert_eq!(arc.reverse().vertices(), (&1, &3));
+        assert_ne!(edge, arc);
+
+        let mut labelled = edge;
+        labelled.update_label(2);
+        assert_ne!(edge, labelled);
+    }
+
+    #[test]
+    fn ordering() {
+        let mut edges: Vec<Edge<VertexId, ()>> = vec![
+            Edge::init_directed(2, 1),
+            Edge::init(4, 0),
+            Edge::init(2, 1),
+            Edge::init_directed(1, 2),
+            Edge::init(0, 3),
+        ];
+        edges.sort();
+
+        assert_eq!(
+            edges,
+            vec![
+                Edge::init(0, 3),
+                Edge::init(0, 4),
+                Edge::init(1, 2),
+                Edge::init_directed(1, 2),
+                Edge::init_directed(2, 1),
+            ]
+        );
+        assert_eq!(
+            Edge::<VertexId, ()>::init(1, 2).cmp(&Edge::init(2, 1)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn text() {
+        let mut weighted = Edge::<VertexId, u64>::init(3, 7);
+        weighted.update_label(5);
+        assert_eq!(weighted.to_string(), "3,7:5");
+        assert_eq!("3,7:5".parse(), Ok(weighted));
+        assert_eq!(" 7 , 3 : 5 ".parse(), Ok(weighted));
+
+        let arc = Edge::<VertexId, ()>::init_directed(4, 1);
+        assert_eq!(arc.to_string(), "4->1");
+        assert_eq!(arc.to_string().parse(), Ok(arc));
+        assert_eq!("4 1".parse(), Ok(Edge::<VertexId, ()>::init(1, 4)));
+
+        let error = |s: &str| s.parse::<Edge<VertexId, u64>>().unwrap_err();
+        assert_eq!(error("4").kind, ParseErrorKind::Unexpected("a vertex"));
+        assert_eq!(
+            (error("4 1 2").column, error("4 1 2").token),
+            (5, "2".into())
+        );
+        assert!(matches!(error("4,x").kind, ParseErrorKind::Vertex(_)));
+        assert!(matches!(error("4,1:x").kind, ParseErrorKind::Weight(_)));
+        assert_eq!(error("4,1:").kind, ParseErrorKind::Unexpected("a weight"));
+
+        // Edge lists fail alongside the other errors of the crate
+        let read = || -> crate::Result<Edge<VertexId, ()>> { Ok("1 x".parse()?) };
+        assert!(matches!(read(), Err(crate::Error::Parse(_))));
+    }
+
+    #[test]
+    #[should_panic]
+    fn to_d1_overflow() {
+        Edge::<VertexId, ()>::init(0, 1 << 40).to_d1();
+    }
 }
 
 /// The destination of an edge, used in an adjacency list representation
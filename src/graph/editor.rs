@@ -0,0 +1,252 @@
+//! Transactional Graph Edits
+//!
+//! Algorithms that modify a graph tentatively, e.g. swapping a Kempe chain, augmenting along a path, or contracting edges in a
+//! Karger-Stein trial, would otherwise clone the whole graph to be able to go back. A [GraphEditor] applies the edits in place and
+//! records, for every pair of vertices it touches, the arcs between them beforehand, so that undoing costs as much as the edits did.
+//!
+//! The [Graphed] trait cannot add a vertex without an edge, so a vertex left without neighbors before the batch, which an edit
+//! removes, is not restored by a rollback. Every edge, and so every vertex with a neighbor, is, and a vertex that an edit added
+//! is removed again.
+
+use std::{fmt::Debug, hash::Hash};
+
+use super::{Edge, Graphed};
+
+/// The arcs between two vertices before an edit, as their labels
+#[derive(Clone, Debug)]
+struct PairSnapshot<T, W> {
+    u: T,
+    v: T,
+    forward: Vec<W>,
+    backward: Vec<W>,
+    /// The vertices of the pair that were not in the graph
+    absent: Vec<T>,
+}
+
+/// Applies a batch of edits to a graph, which can be committed or rolled back
+///
+/// Edits that are neither committed nor rolled back are rolled back when the editor is dropped.
+pub struct GraphEditor<'g, G, T, W>
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq + Clone + Debug + PartialOrd,
+    W: Clone + Default,
+{
+    graph: &'g mut G,
+    log: Vec<PairSnapshot<T, W>>,
+}
+
+impl<'g, G, T, W> GraphEditor<'g, G, T, W>
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq + Clone + Debug + PartialOrd,
+    W: Clone + Default,
+{
+    pub fn init(graph: &'g mut G) -> Self {
+        Self { graph, log: vec![] }
+    }
+
+    /// The graph, with the edits applied so far
+    pub fn graph(&self) -> &G {
+        self.graph
+    }
+
+    fn labels(&self, u: &T, v: &T) -> Vec<W> {
+        self.graph
+            .get_neighbors(u)
+            .map(|neighbors| {
+                neighbors
+                    .iter()
+                    .filter(|n| n.destination == *v)
+                    .map(|n| n.label.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn snapshot(&mut self, u: &T, v: &T) {
+        let snapshot = PairSnapshot {
+            u: u.clone(),
+            v: v.clone(),
+            forward: self.labels(u, v),
+            backward: self.labels(v, u),
+            absent: vec![u, v]
+                .into_iter()
+                .filter(|w| self.graph.get_neighbors(w).is_none())
+                .cloned()
+                .collect(),
+        };
+        self.log.push(snapshot);
+    }
+
+    pub fn add_edge(&mut self, edge: Edge<T, W>) {
+        let (u, v) = edge.vertices();
+        self.snapshot(u, v);
+        self.graph.add_edge(edge);
+    }
+
+    pub fn remove_edge(&mut self, edge: Edge<T, W>) {
+        let (u, v) = edge.vertices();
+        self.snapshot(u, v);
+        self.graph.remove_edge(edge);
+    }
+
+    pub fn remove_vertex(&mut self, vertex: &T) {
+        let neighbors: Vec<T> = self
+            .graph
+            .get_neighbors(vertex)
+            .map(|neighbors| neighbors.iter().map(|n| n.destination.clone()).collect())
+            .unwrap_or_default();
+
+        for neighbor in neighbors.iter() {
+            self.snapshot(vertex, neighbor);
+        }
+        self.graph.remove_vertex(vertex);
+    }
+
+    /// A point of the batch that [rollback_to](GraphEditor::rollback_to) can return to
+    pub fn checkpoint(&self) -> usize {
+        self.log.len()
+    }
+
+    /// Undo every edit made since the checkpoint, keeping the earlier ones
+    pub fn rollback_to(&mut self, checkpoint: usize) {
+        while self.log.len() > checkpoint {
+            let PairSnapshot {
+                u,
+                v,
+                forward,
+                backward,
+                absent,
+            } = self.log.pop().unwrap();
+
+            self.graph.remove_edge(Edge::init(u.clone(), v.clone()));
+            for label in forward {
+                let mut arc = Edge::init_directed(u.clone(), v.clone());
+                arc.update_label(label);
+                self.graph.add_edge(arc);
+            }
+            for label in backward {
+                let mut arc = Edge::init_directed(v.clone(), u.clone());
+                arc.update_label(label);
+                self.graph.add_edge(arc);
+            }
+            // Isolated vertices stay in the graph, so the endpoints the edit added have to be removed
+            absent
+                .iter()
+                .for_each(|vertex| self.graph.remove_vertex(vertex));
+        }
+    }
+
+    /// Keep every edit
+    pub fn commit(mut self) {
+        self.log.clear();
+    }
+
+    /// Undo every edit
+    pub fn rollback(mut self) {
+        self.rollback_to(0);
+    }
+}
+
+impl<'g, G, T, W> Drop for GraphEditor<'g, G, T, W>
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq + Clone + Debug + PartialOrd,
+    W: Clone + Default,
+{
+    fn drop(&mut self) {
+        self.rollback_to(0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{Graph, GraphWithRecaller};
+
+    fn edges<G: Graphed<u32, ()>>(graph: &G) -> Vec<(u32, u32)> {
+        let mut edges: Vec<_> = graph
+            .adj_list()
+            .iter()
+            .flat_map(|(u, n)| n.iter().map(move |n| (*u, n.destination)))
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    fn path() -> Graph<u32, ()> {
+        r"0: 1
+        1: 0,2
+        2: 1,3
+        3: 2"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn rollback() {
+        let mut graph = path();
+        let before = edges(&graph);
+
+        let mut editor = GraphEditor::init(&mut graph);
+        editor.add_edge(Edge::init(0, 3));
+        editor.remove_edge(Edge::init(1, 2));
+        editor.remove_vertex(&3);
+        assert_eq!(edges(editor.graph()), vec![(0, 1), (1, 0)]);
+        editor.rollback();
+
+        assert_eq!(edges(&graph), before);
+        assert_eq!(graph.vertices().len(), 4);
+    }
+
+    #[test]
+    fn added_vertices() {
+        let mut graph = path();
+
+        let mut editor = GraphEditor::init(&mut graph);
+        editor.add_edge(Edge::init(3, 4));
+        editor.remove_edge(Edge::init(2, 3));
+        editor.rollback();
+
+        assert!(graph.get_neighbors(&4).is_none());
+        assert!(graph.has_edge(&Edge::init(2, 3)));
+    }
+
+    #[test]
+    fn checkpoints() {
+        let mut graph = path();
+
+        let mut editor = GraphEditor::init(&mut graph);
+        editor.add_edge(Edge::init(0, 2));
+        let checkpoint = editor.checkpoint();
+        editor.remove_vertex(&1);
+        editor.add_edge(Edge::init(1, 3));
+        editor.rollback_to(checkpoint);
+        editor.commit();
+
+        assert!(graph.has_edge(&Edge::init(0, 2)));
+        assert!(graph.has_edge(&Edge::init(1, 0)));
+        assert!(!graph.has_edge(&Edge::init(1, 3)));
+
+        // Dropping an editor rolls its edits back
+        {
+            let mut editor = GraphEditor::init(&mut graph);
+            editor.remove_edge(Edge::init(0, 2));
+        }
+        assert!(graph.has_edge(&Edge::init(2, 0)));
+    }
+
+    #[test]
+    fn recaller() {
+        let mut graph: GraphWithRecaller<u32, ()> = path().into();
+
+        let mut editor = GraphEditor::init(&mut graph);
+        editor.remove_vertex(&1);
+        assert_eq!(editor.graph().min_degree().map(|(_, d)| d), Some(0));
+        editor.rollback();
+
+        assert_eq!(graph.min_degree().map(|(_, d)| d), Some(1));
+        assert_eq!(edges(&graph), edges(&path()));
+    }
+}
@@ -0,0 +1,366 @@
+//! Graph Modification Events
+//!
+//! Dynamic algorithms keep auxiliary indexes next to a graph, e.g. a heap of degrees, the components, or a coloring, which every
+//! edit has to update. An [ObservedGraph] wraps any [Graphed] graph and reports each edit to a [GraphObserver], so that such
+//! indexes can be maintained outside of the graph types, and combined: a pair of observers is an observer, and so is the
+//! sending half of a channel of [GraphEvent]s.
+//!
+//! Every edit is made, but those that leave the graph unchanged, adding an edge it already has or removing one it does not have,
+//! are not reported. An edge is only had once both of its arcs are, so adding it where one of them is missing is reported. A
+//! vertex removal is reported once, with the neighbors it had, rather than as the removal of each of its edges.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    sync::mpsc::Sender,
+};
+
+use super::{endpoint_degrees, Edge, EdgeDestination, Graphed};
+
+/// An edit of a graph
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphEvent<T, W> {
+    EdgeAdded(Edge<T, W>),
+    EdgeRemoved(Edge<T, W>),
+    /// A vertex, and the neighbors it had before its removal
+    VertexRemoved(T, Vec<T>),
+}
+
+/// Callbacks run after each edit of an [ObservedGraph], every one of them does nothing by default
+pub trait GraphObserver<T, W> {
+    fn on_edge_added(&mut self, _edge: &Edge<T, W>) {}
+
+    fn on_edge_removed(&mut self, _edge: &Edge<T, W>) {}
+
+    /// The vertex is no longer in the graph, nor are its edges to the `neighbors`
+    fn on_vertex_removed(&mut self, _vertex: &T, _neighbors: &[T]) {}
+}
+
+/// Observes nothing
+impl<T, W> GraphObserver<T, W> for () {}
+
+/// Both observers see every edit, the first one first
+impl<T, W, A, B> GraphObserver<T, W> for (A, B)
+where
+    A: GraphObserver<T, W>,
+    B: GraphObserver<T, W>,
+{
+    fn on_edge_added(&mut self, edge: &Edge<T, W>) {
+        self.0.on_edge_added(edge);
+        self.1.on_edge_added(edge);
+    }
+
+    fn on_edge_removed(&mut self, edge: &Edge<T, W>) {
+        self.0.on_edge_removed(edge);
+        self.1.on_edge_removed(edge);
+    }
+
+    fn on_vertex_removed(&mut self, vertex: &T, neighbors: &[T]) {
+        self.0.on_vertex_removed(vertex, neighbors);
+        self.1.on_vertex_removed(vertex, neighbors);
+    }
+}
+
+/// Records every edit, in order
+impl<T, W> GraphObserver<T, W> for Vec<GraphEvent<T, W>>
+where
+    T: Clone,
+    W: Clone,
+{
+    fn on_edge_added(&mut self, edge: &Edge<T, W>) {
+        self.push(GraphEvent::EdgeAdded(edge.clone()));
+    }
+
+    fn on_edge_removed(&mut self, edge: &Edge<T, W>) {
+        self.push(GraphEvent::EdgeRemoved(edge.clone()));
+    }
+
+    fn on_vertex_removed(&mut self, vertex: &T, neighbors: &[T]) {
+        self.push(GraphEvent::VertexRemoved(
+            vertex.clone(),
+            neighbors.to_vec(),
+        ));
+    }
+}
+
+/// Sends every edit to the receiving half of the channel, edits are still made once it is dropped
+impl<T, W> GraphObserver<T, W> for Sender<GraphEvent<T, W>>
+where
+    T: Clone,
+    W: Clone,
+{
+    fn on_edge_added(&mut self, edge: &Edge<T, W>) {
+        self.send(GraphEvent::EdgeAdded(edge.clone())).ok();
+    }
+
+    fn on_edge_removed(&mut self, edge: &Edge<T, W>) {
+        self.send(GraphEvent::EdgeRemoved(edge.clone())).ok();
+    }
+
+    fn on_vertex_removed(&mut self, vertex: &T, neighbors: &[T]) {
+        self.send(GraphEvent::VertexRemoved(
+            vertex.clone(),
+            neighbors.to_vec(),
+        ))
+        .ok();
+    }
+}
+
+/// A graph that reports its edits to an observer
+///
+/// With an observer that has a default, it is itself [Graphed], so algorithms that edit a graph, e.g. by repeatedly removing the vertex of minimum degree, keep the
+/// observer up to date as they run.
+#[derive(Clone, Debug)]
+pub struct ObservedGraph<G, O, T, W> {
+    graph: G,
+    observer: O,
+    vertex: PhantomData<(T, W)>,
+}
+
+impl<G, O, T, W> ObservedGraph<G, O, T, W>
+where
+    G: Graphed<T, W>,
+    O: GraphObserver<T, W>,
+    T: Hash + Eq + Clone + Debug + PartialOrd,
+    W: Clone + Default + Debug,
+{
+    pub fn init(graph: G, observer: O) -> Self {
+        Self {
+            graph,
+            observer,
+            vertex: PhantomData,
+        }
+    }
+
+    pub fn graph(&self) -> &G {
+        &self.graph
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    pub fn add_edge(&mut self, edge: Edge<T, W>) {
+        let before = endpoint_degrees(&self.graph, &edge);
+        self.graph.add_edge(edge.clone());
+        if endpoint_degrees(&self.graph, &edge) != before {
+            self.observer.on_edge_added(&edge);
+        }
+    }
+
+    pub fn remove_edge(&mut self, edge: Edge<T, W>) {
+        let before = endpoint_degrees(&self.graph, &edge);
+        self.graph.remove_edge(edge.clone());
+        if endpoint_degrees(&self.graph, &edge) != before {
+            self.observer.on_edge_removed(&edge);
+        }
+    }
+
+    pub fn remove_vertex(&mut self, vertex: &T) {
+        let neighbors: Option<Vec<T>> = self
+            .graph
+            .get_neighbors(vertex)
+            .map(|neighbors| neighbors.iter().map(|n| n.destination.clone()).collect());
+
+        if let Some(neighbors) = neighbors {
+            self.graph.remove_vertex(vertex);
+            self.observer.on_vertex_removed(vertex, &neighbors);
+        }
+    }
+
+    /// Remove the vertex of minimum degree, reported as any vertex removal
+    pub fn remove_min(&mut self) -> Option<T> {
+        self.graph.min_degree().map(|(vertex, _)| {
+            self.remove_vertex(&vertex);
+            vertex
+        })
+    }
+
+    /// The graph and the observer, which are no longer linked
+    pub fn into_parts(self) -> (G, O) {
+        (self.graph, self.observer)
+    }
+}
+
+impl<G, O, T, W> Graphed<T, W> for ObservedGraph<G, O, T, W>
+where
+    G: Graphed<T, W>,
+    O: GraphObserver<T, W> + Clone + Debug + Default,
+    T: Hash + Eq + Clone + Debug + PartialOrd,
+    W: Clone + Default + Debug,
+{
+    /// A graph with a new observer, which is not told of the edges already there
+    fn new(adjacency_list: HashMap<T, HashSet<EdgeDestination<T, W>>>) -> Self {
+        Self::init(G::new(adjacency_list), O::default())
+    }
+
+    fn adj_list(&self) -> &HashMap<T, HashSet<EdgeDestination<T, W>>> {
+        self.graph.adj_list()
+    }
+
+    fn vertices(&self) -> HashSet<&T> {
+        self.graph.vertices()
+    }
+
+    fn get_neighbors(&self, vertex: &T) -> Option<&HashSet<EdgeDestination<T, W>>> {
+        self.graph.get_neighbors(vertex)
+    }
+
+    fn add_edge(&mut self, edge: Edge<T, W>) {
+        ObservedGraph::add_edge(self, edge)
+    }
+
+    fn remove_edge(&mut self, edge: Edge<T, W>) {
+        ObservedGraph::remove_edge(self, edge)
+    }
+
+    fn remove_vertex(&mut self, vertex: &T) {
+        ObservedGraph::remove_vertex(self, vertex)
+    }
+
+    fn min_degree(&self) -> Option<(T, usize)> {
+        self.graph.min_degree()
+    }
+
+    fn max_degree(&self) -> Option<(T, usize)> {
+        self.graph.max_degree()
+    }
+
+    fn remove_min(&mut self) -> Option<T> {
+        ObservedGraph::remove_min(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.graph.is_empty()
+    }
+
+    fn has_edge(&self, edge: &Edge<T, W>) -> bool {
+        self.graph.has_edge(edge)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc::channel;
+
+    use super::*;
+    use crate::graph::{static_a::coloring::Colorer, Graph, GraphWithRecaller};
+
+    fn path() -> Graph<u32, ()> {
+        r"0: 1
+        1: 0,2
+        2: 1,3
+        3: 2"
+            .parse()
+            .unwrap()
+    }
+
+    /// Number of edges, maintained from the events alone
+    #[derive(Clone, Debug, Default)]
+    struct EdgeCount(usize);
+
+    impl GraphObserver<u32, ()> for EdgeCount {
+        fn on_edge_added(&mut self, _edge: &Edge<u32, ()>) {
+            self.0 += 1;
+        }
+
+        fn on_edge_removed(&mut self, _edge: &Edge<u32, ()>) {
+            self.0 -= 1;
+        }
+
+        fn on_vertex_removed(&mut self, _vertex: &u32, neighbors: &[u32]) {
+            self.0 -= neighbors.len();
+        }
+    }
+
+    #[test]
+    fn events() {
+        let mut graph = ObservedGraph::init(path(), vec![]);
+
+        graph.add_edge(Edge::init(0, 3));
+        // Neither edit changes the graph
+        graph.add_edge(Edge::init(3, 0));
+        graph.remove_edge(Edge::init(0, 2));
+        graph.remove_edge(Edge::init(1, 2));
+        graph.remove_vertex(&0);
+
+        let (graph, mut events) = graph.into_parts();
+        if let Some(GraphEvent::VertexRemoved(_, neighbors)) = events.last_mut() {
+            neighbors.sort_unstable();
+        }
+        assert_eq!(
+            events,
+            vec![
+                GraphEvent::EdgeAdded(Edge::init(0, 3)),
+                GraphEvent::EdgeRemoved(Edge::init(1, 2)),
+                GraphEvent::VertexRemoved(0, vec![1, 3]),
+            ]
+        );
+        assert!(graph.has_edge(&Edge::init(2, 3)) && graph.get_neighbors(&0).is_none());
+    }
+
+    #[test]
+    fn half_edges() {
+        let mut arc: Graph<u32, ()> = Graph::default();
+        arc.add_edge(Edge::init_directed(0, 1));
+        let mut graph = ObservedGraph::init(arc, vec![]);
+
+        // The edge adds the missing arc 1 -> 0, then removing it again takes both arcs out
+        graph.add_edge(Edge::init(0, 1));
+        assert!(graph.has_edge(&Edge::init_directed(1, 0)));
+        graph.remove_edge(Edge::init(0, 1));
+        graph.add_edge(Edge::init_directed(2, 3));
+        graph.remove_edge(Edge::init(3, 2));
+
+        let (graph, events) = graph.into_parts();
+        assert_eq!(
+            events,
+            vec![
+                GraphEvent::EdgeAdded(Edge::init(0, 1)),
+                GraphEvent::EdgeRemoved(Edge::init(0, 1)),
+                GraphEvent::EdgeAdded(Edge::init_directed(2, 3)),
+                GraphEvent::EdgeRemoved(Edge::init(3, 2)),
+            ]
+        );
+        assert!(graph.edges().next().is_none());
+    }
+
+    #[test]
+    fn composed() {
+        let (sender, receiver) = channel();
+        let graph: GraphWithRecaller<u32, ()> = path().into();
+        let mut graph = ObservedGraph::init(graph, (EdgeCount(3), sender));
+
+        // Peel the graph through the observed graph, every removal reaches both observers
+        while graph.remove_min().is_some() {}
+
+        assert_eq!(graph.observer().0 .0, 0);
+        let removed: usize = receiver
+            .try_iter()
+            .map(|event| match event {
+                GraphEvent::VertexRemoved(_, neighbors) => neighbors.len(),
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(removed, 3);
+    }
+
+    #[test]
+    fn algorithms() {
+        let mut graph: ObservedGraph<Graph<u32, ()>, EdgeCount, _, _> =
+            ObservedGraph::new(Default::default());
+        graph.add_edge(Edge::init(0, 1));
+        graph.add_edge(Edge::init(2, 3));
+
+        let coloring = graph.color_degeneracy();
+        assert!(graph.is_proper(&coloring));
+        // The colorer peels a clone of the graph, leaving this one and its observer untouched
+        assert_eq!(graph.observer().0, 2);
+    }
+}
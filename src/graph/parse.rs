@@ -0,0 +1,474 @@
+//! Text Format of Graphs
+//!
+//! Graphs are parsed from text, one statement per line:
+//!
+//! ```text
+//! # Comments and blank lines are skipped
+//! 0: 1,2,3      adjacency list, an arc from 0 to each of 1, 2 and 3
+//! 4:            a vertex without any arc
+//! 1 -> 2        an arc from 1 to 2
+//! 2 -> 3 7      an arc, labelled 7
+//! 3 - 4         an undirected edge
+//! 4 - 5 0.5     an undirected edge, labelled 0.5
+//! ```
+//!
+//! The adjacency list format is the one produced by the [Display](std::fmt::Display) implementations of the graphs. An operator
+//! `-` must be separated from its vertices by whitespace, so that it is not read as the sign of a vertex. Errors carry the line
+//! and column, both counted from 1, of the offending token.
+//!
+//! A single line of an edge list, `u v` or `u,v[:w]`, or an arc `u->v[:w]`, is parsed by the [FromStr] implementation of
+//! [Edge], with the same errors.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Debug, Display},
+    hash::Hash,
+    str::FromStr,
+};
+
+use super::{Edge, Graph, Graphed};
+
+/// Label of an edge, parsed from its weight
+pub trait Weight: Default + Sized {
+    fn parse_weight(field: &str) -> Result<Self, String>;
+
+    /// The weight written after the vertices of an [Edge], `None` for labels that carry no weight
+    fn format_weight(&self) -> Option<String> {
+        None
+    }
+}
+
+impl Weight for () {
+    /// The weight is ignored
+    fn parse_weight(_: &str) -> Result<Self, String> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_weight {
+    ($($t:ty),*) => {
+        $(impl Weight for $t {
+            fn parse_weight(field: &str) -> Result<Self, String> {
+                field.parse().map_err(|e| format!("{}", e))
+            }
+
+            fn format_weight(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+        })*
+    };
+}
+
+impl_weight!(u32, u64, i64, f32, f64);
+
+/// What is wrong with a token
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The token is not a vertex, with the reason given by the vertex type
+    Vertex(String),
+    /// The token is not a weight, with the reason given by the label type
+    Weight(String),
+    /// The token does not fit the syntax, a description of what was expected instead
+    Unexpected(&'static str),
+}
+
+/// A malformed line of a graph
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseGraphError {
+    /// Line of the offending token, counted from 1
+    pub line: usize,
+    /// Column, in characters, of the offending token, counted from 1
+    pub column: usize,
+    /// The offending token, empty at the end of a line
+    pub token: String,
+    pub kind: ParseErrorKind,
+}
+
+impl Display for ParseGraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::Vertex(e) => write!(f, "invalid vertex {:?}: {}", self.token, e),
+            ParseErrorKind::Weight(e) => write!(f, "invalid weight {:?}: {}", self.token, e),
+            ParseErrorKind::Unexpected(expected) if self.token.is_empty() => {
+                write!(f, "expected {} at the end of the line", expected)
+            }
+            ParseErrorKind::Unexpected(expected) => {
+                write!(f, "expected {}, found {:?}", expected, self.token)
+            }
+        }
+    }
+}
+
+impl Error for ParseGraphError {}
+
+/// A token of a line, and the column of its first character
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    text: &'a str,
+    column: usize,
+}
+
+/// Push the word starting at `start`, if any, and ending at the byte `end`
+fn close<'a>(
+    line: &'a str,
+    tokens: &mut Vec<Token<'a>>,
+    start: &mut Option<(usize, usize)>,
+    end: usize,
+) {
+    if let Some((byte, column)) = start.take() {
+        tokens.push(Token {
+            text: &line[byte..end],
+            column,
+        });
+    }
+}
+
+/// Split a line into words separated by whitespace, `:` and `,` being tokens of their own, as is `->` within a word
+fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut start: Option<(usize, usize)> = None;
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let (byte, c) = chars[i];
+        let column = i + 1;
+        let arrow = c == '-' && chars.get(i + 1).map(|(_, c)| *c) == Some('>');
+
+        if c.is_whitespace() {
+            close(line, &mut tokens, &mut start, byte);
+        } else if c == ':' || c == ',' || arrow {
+            close(line, &mut tokens, &mut start, byte);
+            let len = if arrow { 2 } else { 1 };
+            tokens.push(Token {
+                text: &line[byte..byte + len],
+                column,
+            });
+            i += len;
+            continue;
+        } else if start.is_none() {
+            start = Some((byte, column));
+        }
+        i += 1;
+    }
+    close(line, &mut tokens, &mut start, line.len());
+
+    tokens
+}
+
+/// An error at the token, or at the end of the line if there is none
+fn error(line: usize, end: usize, token: Option<Token>, kind: ParseErrorKind) -> ParseGraphError {
+    ParseGraphError {
+        line,
+        column: token.map_or(end, |t| t.column),
+        token: token.map_or(String::new(), |t| t.text.to_string()),
+        kind,
+    }
+}
+
+fn vertex<T>(line: usize, end: usize, token: Option<Token>) -> Result<T, ParseGraphError>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+{
+    let token =
+        token.ok_or_else(|| error(line, end, None, ParseErrorKind::Unexpected("a vertex")))?;
+    token.text.parse().map_err(|e: <T as FromStr>::Err| {
+        error(
+            line,
+            end,
+            Some(token),
+            ParseErrorKind::Vertex(e.to_string()),
+        )
+    })
+}
+
+/// Parses the statements of a graph, one line at a time
+struct Parser<T, W>
+where
+    T: Hash + Eq,
+{
+    graph: Graph<T, W>,
+    line: usize,
+    /// Column just past the end of the current line, for errors at its end
+    end: usize,
+}
+
+impl<T, W> Parser<T, W>
+where
+    T: Debug + Hash + Eq + Clone + PartialOrd + FromStr,
+    <T as FromStr>::Err: Display,
+    W: Debug + Hash + Eq + Clone + Weight,
+{
+    fn error(&self, token: Option<Token>, kind: ParseErrorKind) -> ParseGraphError {
+        error(self.line, self.end, token, kind)
+    }
+
+    fn vertex(&self, token: Option<Token>) -> Result<T, ParseGraphError> {
+        vertex(self.line, self.end, token)
+    }
+
+    fn statement(&mut self, tokens: &[Token]) -> Result<(), ParseGraphError> {
+        let u = self.vertex(tokens.first().copied())?;
+        let operator = tokens.get(1).copied();
+
+        match operator.map(|t| t.text) {
+            Some(":") => self.adjacency(u, &tokens[2..]),
+            Some("->") | Some("-") => {
+                let v = self.vertex(tokens.get(2).copied())?;
+                let mut edge = if operator.unwrap().text == "->" {
+                    Edge::init_directed(u, v)
+                } else {
+                    Edge::init(u, v)
+                };
+
+                if let Some(weight) = tokens.get(3) {
+                    let label = W::parse_weight(weight.text)
+                        .map_err(|e| self.error(Some(*weight), ParseErrorKind::Weight(e)))?;
+                    edge.update_label(label);
+                }
+                if let Some(extra) = tokens.get(4) {
+                    return Err(self.error(
+                        Some(*extra),
+                        ParseErrorKind::Unexpected("the end of the line"),
+                    ));
+                }
+
+                self.graph.add_edge(edge);
+                Ok(())
+            }
+            _ => Err(self.error(operator, ParseErrorKind::Unexpected("`:`, `->` or `-`"))),
+        }
+    }
+
+    /// The neighbors of `u`, separated by commas, empty entries being skipped
+    fn adjacency(&mut self, u: T, tokens: &[Token]) -> Result<(), ParseGraphError> {
+        self.graph.adjacency_list.entry(u.clone()).or_default();
+
+        let mut expect_vertex = true;
+        for token in tokens {
+            match (token.text, expect_vertex) {
+                (",", _) => expect_vertex = true,
+                (_, true) => {
+                    let v = self.vertex(Some(*token))?;
+                    self.graph.add_edge(Edge::init_directed(u.clone(), v));
+                    expect_vertex = false;
+                }
+                (_, false) => {
+                    return Err(self.error(Some(*token), ParseErrorKind::Unexpected("`,`")))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn from_str<G, T, W>(s: &str) -> Result<G, ParseGraphError>
+where
+    T: Debug + Hash + Eq + Clone + PartialOrd + FromStr,
+    <T as FromStr>::Err: Display,
+    W: Debug + Hash + Eq + Clone + Weight,
+    G: Graphed<T, W>,
+{
+    let mut parser = Parser {
+        graph: Graph::new(HashMap::new()),
+        line: 0,
+        end: 0,
+    };
+
+    for (i, line) in s.lines().enumerate() {
+        parser.line = i + 1;
+        parser.end = line.chars().count() + 1;
+
+        let tokens = tokenize(line);
+        if tokens.is_empty() || tokens[0].text.starts_with('#') {
+            continue;
+        }
+        parser.statement(&tokens)?;
+    }
+
+    Ok(G::new(parser.graph.adjacency_list))
+}
+
+/// Parse a line of an edge list, `u v` or `u,v[:w]`, or an arc `u->v[:w]`, its errors being on line 1
+pub(super) fn edge_from_str<T, W>(s: &str) -> Result<Edge<T, W>, ParseGraphError>
+where
+    T: Eq + PartialOrd + FromStr,
+    <T as FromStr>::Err: Display,
+    W: Weight,
+{
+    let end = s.chars().count() + 1;
+    let tokens = tokenize(s);
+
+    let u = vertex(1, end, tokens.first().copied())?;
+    // The vertices of `u v` are only separated by whitespace
+    let (directed, second) = match tokens.get(1).map(|t| t.text) {
+        Some("->") => (true, 2),
+        Some(",") => (false, 2),
+        _ => (false, 1),
+    };
+    let v = vertex(1, end, tokens.get(second).copied())?;
+    let mut edge = if directed {
+        Edge::init_directed(u, v)
+    } else {
+        Edge::init(u, v)
+    };
+
+    let rest = &tokens[second + 1..];
+    match rest {
+        [] => {}
+        [colon, weight] if colon.text == ":" => {
+            let label = W::parse_weight(weight.text)
+                .map_err(|e| error(1, end, Some(*weight), ParseErrorKind::Weight(e)))?;
+            edge.update_label(label);
+        }
+        [colon] if colon.text == ":" => {
+            return Err(error(1, end, None, ParseErrorKind::Unexpected("a weight")))
+        }
+        [colon, _, extra, ..] if colon.text == ":" => {
+            return Err(error(
+                1,
+                end,
+                Some(*extra),
+                ParseErrorKind::Unexpected("the end of the line"),
+            ))
+        }
+        [token, ..] => {
+            return Err(error(
+                1,
+                end,
+                Some(*token),
+                ParseErrorKind::Unexpected("`:` or the end of the line"),
+            ))
+        }
+    }
+
+    Ok(edge)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::graph::{GraphWithRecaller, VertexId};
+
+    fn arcs<G, W>(graph: &G) -> Vec<(VertexId, VertexId, W)>
+    where
+        G: Graphed<VertexId, W>,
+        W: Clone,
+    {
+        let mut arcs: Vec<_> = graph
+            .adj_list()
+            .iter()
+            .flat_map(|(u, n)| n.iter().map(move |n| (*u, n.destination, n.label.clone())))
+            .collect();
+        arcs.sort_unstable_by_key(|(u, v, _)| (*u, *v));
+        arcs
+    }
+
+    #[test]
+    fn syntax() {
+        let graph: Graph<VertexId, u64> = r"
+            # A comment
+            0: 1, 2,
+            4:
+            1 -> 2 7
+            2->3
+            3 - 5 9"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            arcs(&graph),
+            vec![
+                (0, 1, 0),
+                (0, 2, 0),
+                (1, 2, 7),
+                (2, 3, 0),
+                (3, 5, 9),
+                (5, 3, 9)
+            ]
+        );
+        assert!(graph.get_neighbors(&4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let graph: Graph<VertexId, ()> = "0 - 1\n1 - 2\n2 -> 0\n3:".parse().unwrap();
+        let text = super::super::to_str(&graph);
+
+        let parsed: GraphWithRecaller<VertexId, ()> = text.parse().unwrap();
+        assert_eq!(arcs(&graph), arcs(&parsed));
+    }
+
+    #[test]
+    fn positions() {
+        let error = |s: &str| s.parse::<Graph<VertexId, u64>>().unwrap_err();
+
+        let e = error("0: 1\n1: 2 3");
+        assert_eq!((e.line, e.column, e.token.as_str()), (2, 6, "3"));
+        assert_eq!(e.to_string(), "2:6: expected `,`, found \"3\"");
+
+        let e = error("0 - x");
+        assert_eq!((e.line, e.column), (1, 5));
+        assert!(matches!(e.kind, ParseErrorKind::Vertex(_)));
+
+        let e = error("0 -> 1 heavy");
+        assert_eq!((e.line, e.column), (1, 8));
+        assert!(matches!(e.kind, ParseErrorKind::Weight(_)));
+
+        let e = error("\n  0 ->");
+        assert_eq!((e.line, e.column), (2, 7));
+        assert_eq!(
+            e.to_string(),
+            "2:7: expected a vertex at the end of the line"
+        );
+
+        let e = error("0 1");
+        assert_eq!((e.line, e.column, e.token.as_str()), (1, 3, "1"));
+    }
+
+    /// Random corruptions of a valid text either parse, or fail at a position within the text
+    #[test]
+    fn corruption() {
+        let valid = "0: 1,2,3\n1 -> 2 4\n2 - 3 5\n# comment\n3:\n";
+        let alphabet: Vec<char> = "0123456789:,->#x \n\t".chars().collect();
+        let mut rng = StdRng::seed_from_u64(17);
+
+        for _ in 0..2000 {
+            let mut chars: Vec<char> = valid.chars().collect();
+            for _ in 0..rng.gen_range(1..4) {
+                let i = rng.gen_range(0..chars.len());
+                match rng.gen_range(0..3) {
+                    0 => chars[i] = alphabet[rng.gen_range(0..alphabet.len())],
+                    1 => chars.insert(i, alphabet[rng.gen_range(0..alphabet.len())]),
+                    _ => {
+                        chars.remove(i);
+                    }
+                }
+            }
+            let text: String = chars.into_iter().collect();
+
+            if let Err(e) = text.parse::<Graph<VertexId, u64>>() {
+                let line = text.lines().nth(e.line - 1).unwrap();
+                assert!(
+                    e.column >= 1 && e.column <= line.chars().count() + 1,
+                    "{:?}",
+                    text
+                );
+                if !e.token.is_empty() {
+                    let at: String = line
+                        .chars()
+                        .skip(e.column - 1)
+                        .take(e.token.chars().count())
+                        .collect();
+                    assert_eq!(at, e.token, "{:?}", text);
+                }
+            }
+        }
+    }
+}
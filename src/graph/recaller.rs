@@ -0,0 +1,370 @@
+//! Incremental Invariants
+//!
+//! A [GraphWithRecaller](super::GraphWithRecaller) keeps invariants of its graph up to date as it is edited, rather than
+//! recomputing them on every query. Each invariant is a [Recaller], told of every edit right after it is made, along with the
+//! graph as it now stands: the heaps of [Degrees], that every [GraphWithRecaller](super::GraphWithRecaller) holds, are one, and
+//! any other can be attached, e.g. a [DegreeHistogram] or a [TriangleCount]. A pair of recallers is a recaller, so several of
+//! them can be attached at once.
+//!
+//! As for the [observers](super::observer), edits that leave the graph unchanged are not reported, and a vertex removal is
+//! reported once, with the neighbors it had. The vertices without neighbors dropped while iterating over the graph are reported
+//! as vertex removals as well.
+
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use priority_queue::PriorityQueue;
+
+use super::{Edge, Graph};
+
+/// An invariant of a graph, kept up to date through the edits of the graph
+///
+/// Every callback runs after the edit, and does nothing by default.
+pub trait Recaller<T, W>
+where
+    T: Hash + Eq,
+{
+    /// The invariant of a graph that has not been edited yet
+    fn init(graph: &Graph<T, W>) -> Self;
+
+    fn on_edge_added(&mut self, _graph: &Graph<T, W>, _edge: &Edge<T, W>) {}
+
+    fn on_edge_removed(&mut self, _graph: &Graph<T, W>, _edge: &Edge<T, W>) {}
+
+    /// The vertex is no longer in the graph, nor are its edges to the `neighbors`
+    fn on_vertex_removed(&mut self, _graph: &Graph<T, W>, _vertex: &T, _neighbors: &[T]) {}
+}
+
+/// Recalls nothing
+impl<T, W> Recaller<T, W> for ()
+where
+    T: Hash + Eq,
+{
+    fn init(_graph: &Graph<T, W>) -> Self {}
+}
+
+/// Both recallers see every edit, the first one first
+impl<T, W, A, B> Recaller<T, W> for (A, B)
+where
+    T: Hash + Eq,
+    A: Recaller<T, W>,
+    B: Recaller<T, W>,
+{
+    fn init(graph: &Graph<T, W>) -> Self {
+        (A::init(graph), B::init(graph))
+    }
+
+    fn on_edge_added(&mut self, graph: &Graph<T, W>, edge: &Edge<T, W>) {
+        self.0.on_edge_added(graph, edge);
+        self.1.on_edge_added(graph, edge);
+    }
+
+    fn on_edge_removed(&mut self, graph: &Graph<T, W>, edge: &Edge<T, W>) {
+        self.0.on_edge_removed(graph, edge);
+        self.1.on_edge_removed(graph, edge);
+    }
+
+    fn on_vertex_removed(&mut self, graph: &Graph<T, W>, vertex: &T, neighbors: &[T]) {
+        self.0.on_vertex_removed(graph, vertex, neighbors);
+        self.1.on_vertex_removed(graph, vertex, neighbors);
+    }
+}
+
+/// The degree of a vertex of the graph, `None` if it is not in the graph
+fn degree<T, W>(graph: &Graph<T, W>, vertex: &T) -> Option<usize>
+where
+    T: Hash + Eq,
+{
+    graph
+        .adjacency_list
+        .get(vertex)
+        .map(|neighbors| neighbors.len())
+}
+
+/// Every vertex of the graph in two heaps, keyed by its degree, one with the vertex of minimum degree on top, and one with the
+/// vertex of maximum degree
+#[derive(Clone, Debug, Default)]
+pub struct Degrees<T>
+where
+    T: Hash + Eq,
+{
+    min_heap: PriorityQueue<T, Reverse<usize>>,
+    max_heap: PriorityQueue<T, usize>,
+}
+
+impl<T> Degrees<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// A vertex of minimum degree, with its degree
+    ///
+    /// Runtime: O(1)
+    pub fn min(&self) -> Option<(T, usize)> {
+        self.min_heap.peek().map(|(v, d)| (v.clone(), d.0))
+    }
+
+    /// A vertex of maximum degree, with its degree
+    ///
+    /// Runtime: O(1)
+    pub fn max(&self) -> Option<(T, usize)> {
+        self.max_heap.peek().map(|(v, d)| (v.clone(), *d))
+    }
+
+    /// The degree of the vertex, `None` if it is not in the graph
+    pub fn degree(&self, vertex: &T) -> Option<usize> {
+        self.max_heap.get_priority(vertex).copied()
+    }
+
+    /// Every vertex with its degree, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (T, usize)> + '_ {
+        self.max_heap.iter().map(|(v, d)| (v.clone(), *d))
+    }
+
+    /// Runtime: O(log(n))
+    fn update<W>(&mut self, graph: &Graph<T, W>, vertex: &T) {
+        match degree(graph, vertex) {
+            Some(d) => {
+                self.min_heap.push(vertex.clone(), Reverse(d));
+                self.max_heap.push(vertex.clone(), d);
+            }
+            None => {
+                self.min_heap.remove(vertex);
+                self.max_heap.remove(vertex);
+            }
+        }
+    }
+}
+
+impl<T, W> Recaller<T, W> for Degrees<T>
+where
+    T: Hash + Eq + Clone + PartialOrd,
+    W: Default,
+{
+    /// Runtime: O(nlog(n))
+    fn init(graph: &Graph<T, W>) -> Self {
+        let mut degrees = Self {
+            min_heap: PriorityQueue::new(),
+            max_heap: PriorityQueue::new(),
+        };
+        graph
+            .adjacency_list
+            .keys()
+            .for_each(|v| degrees.update(graph, v));
+        degrees
+    }
+
+    fn on_edge_added(&mut self, graph: &Graph<T, W>, edge: &Edge<T, W>) {
+        let (v1, v2) = edge.vertices();
+        self.update(graph, v1);
+        self.update(graph, v2);
+    }
+
+    fn on_edge_removed(&mut self, graph: &Graph<T, W>, edge: &Edge<T, W>) {
+        let (v1, v2) = edge.vertices();
+        self.update(graph, v1);
+        self.update(graph, v2);
+    }
+
+    fn on_vertex_removed(&mut self, graph: &Graph<T, W>, vertex: &T, neighbors: &[T]) {
+        self.update(graph, vertex);
+        neighbors.iter().for_each(|v| self.update(graph, v));
+    }
+}
+
+/// The number of vertices of every degree
+///
+/// Storage: O(n), the degree of every vertex
+#[derive(Clone, Debug, Default)]
+pub struct DegreeHistogram<T>
+where
+    T: Hash + Eq,
+{
+    degrees: HashMap<T, usize>,
+    histogram: HashMap<usize, usize>,
+}
+
+impl<T> DegreeHistogram<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// The number of vertices of degree `d`
+    pub fn count(&self, d: usize) -> usize {
+        self.histogram.get(&d).copied().unwrap_or_default()
+    }
+
+    /// Every degree with its number of vertices, by increasing degree
+    pub fn histogram(&self) -> Vec<(usize, usize)> {
+        let mut histogram: Vec<_> = self.histogram.iter().map(|(d, c)| (*d, *c)).collect();
+        histogram.sort_unstable();
+        histogram
+    }
+
+    fn update<W>(&mut self, graph: &Graph<T, W>, vertex: &T) {
+        if let Some(old) = self.degrees.remove(vertex) {
+            let count = self.histogram.entry(old).or_default();
+            *count -= 1;
+            if *count == 0 {
+                self.histogram.remove(&old);
+            }
+        }
+        if let Some(d) = degree(graph, vertex) {
+            self.degrees.insert(vertex.clone(), d);
+            *self.histogram.entry(d).or_default() += 1;
+        }
+    }
+}
+
+impl<T, W> Recaller<T, W> for DegreeHistogram<T>
+where
+    T: Hash + Eq + Clone + PartialOrd,
+    W: Default,
+{
+    fn init(graph: &Graph<T, W>) -> Self {
+        let mut histogram = Self {
+            degrees: HashMap::new(),
+            histogram: HashMap::new(),
+        };
+        graph
+            .adjacency_list
+            .keys()
+            .for_each(|v| histogram.update(graph, v));
+        histogram
+    }
+
+    fn on_edge_added(&mut self, graph: &Graph<T, W>, edge: &Edge<T, W>) {
+        let (v1, v2) = edge.vertices();
+        self.update(graph, v1);
+        self.update(graph, v2);
+    }
+
+    fn on_edge_removed(&mut self, graph: &Graph<T, W>, edge: &Edge<T, W>) {
+        let (v1, v2) = edge.vertices();
+        self.update(graph, v1);
+        self.update(graph, v2);
+    }
+
+    fn on_vertex_removed(&mut self, graph: &Graph<T, W>, vertex: &T, neighbors: &[T]) {
+        self.update(graph, vertex);
+        neighbors.iter().for_each(|v| self.update(graph, v));
+    }
+}
+
+/// The number of triangles of an undirected graph
+///
+/// An edit costs the intersection of two neighborhoods, and the removal of a vertex of degree `d` costs `O(d^2)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TriangleCount(pub u64);
+
+/// Number of neighbors shared by both vertices
+fn common_neighbors<T, W>(graph: &Graph<T, W>, u: &T, v: &T) -> u64
+where
+    T: Hash + Eq,
+{
+    let neighbors = |w: &T| -> HashSet<&T> {
+        graph
+            .adjacency_list
+            .get(w)
+            .map(|n| n.iter().map(|d| &d.destination).collect())
+            .unwrap_or_default()
+    };
+    neighbors(u).intersection(&neighbors(v)).count() as u64
+}
+
+impl<T, W> Recaller<T, W> for TriangleCount
+where
+    T: Hash + Eq + PartialOrd,
+    W: Default,
+{
+    /// Runtime: O(mΔ)
+    fn init(graph: &Graph<T, W>) -> Self {
+        // Every triangle is met once from each of its 3 edges, in both directions
+        let wedges: u64 = graph
+            .adjacency_list
+            .iter()
+            .flat_map(|(u, neighbors)| neighbors.iter().map(move |n| (u, &n.destination)))
+            .map(|(u, v)| common_neighbors(graph, u, v))
+            .sum();
+        Self(wedges / 6)
+    }
+
+    fn on_edge_added(&mut self, graph: &Graph<T, W>, edge: &Edge<T, W>) {
+        let (u, v) = edge.vertices();
+        self.0 += common_neighbors(graph, u, v);
+    }
+
+    fn on_edge_removed(&mut self, graph: &Graph<T, W>, edge: &Edge<T, W>) {
+        let (u, v) = edge.vertices();
+        self.0 -= common_neighbors(graph, u, v);
+    }
+
+    /// The triangles lost are the edges between the neighbors
+    fn on_vertex_removed(&mut self, graph: &Graph<T, W>, _vertex: &T, neighbors: &[T]) {
+        let neighborhood: HashSet<&T> = neighbors.iter().collect();
+        let edges: u64 = neighbors
+            .iter()
+            .filter_map(|v| graph.adjacency_list.get(v))
+            .flat_map(|n| n.iter())
+            .filter(|n| neighborhood.contains(&n.destination))
+            .count() as u64;
+        self.0 -= edges / 2;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{GraphWithRecaller, Graphed};
+
+    type Invariants = (DegreeHistogram<u32>, TriangleCount);
+
+    /// The invariants recomputed from scratch
+    fn fresh(graph: &GraphWithRecaller<u32, (), Invariants>) -> Invariants {
+        Recaller::init(graph.graph())
+    }
+
+    #[test]
+    fn incremental_invariants() {
+        // Two triangles sharing the edge 1 - 2, and a pendant vertex 4
+        let graph: Graph<u32, ()> = r"0: 1,2
+        1: 0,2,3
+        2: 0,1,3
+        3: 1,2,4
+        4: 3"
+            .parse()
+            .unwrap();
+        let mut recaller: GraphWithRecaller<u32, (), Invariants> = graph.into();
+
+        let (histogram, triangles) = recaller.recaller();
+        assert_eq!(triangles, &TriangleCount(2));
+        assert_eq!(histogram.histogram(), vec![(1, 1), (2, 1), (3, 3)]);
+
+        recaller.add_edge(Edge::init(0, 3));
+        assert_eq!(recaller.recaller().1, TriangleCount(4));
+        // Already there, and not counted twice
+        recaller.add_edge(Edge::init(3, 0));
+        assert_eq!(recaller.recaller().1, TriangleCount(4));
+
+        recaller.remove_edge(Edge::init(1, 2));
+        assert_eq!(recaller.recaller().1, TriangleCount(2));
+        assert_eq!(recaller.recaller().0.count(2), 2);
+
+        recaller.remove_vertex(&3);
+        assert_eq!(recaller.recaller().1, TriangleCount(0));
+        assert_eq!(
+            recaller.recaller().0.histogram(),
+            fresh(&recaller).0.histogram()
+        );
+
+        while recaller.remove_min().is_some() {
+            assert_eq!(recaller.recaller().1, fresh(&recaller).1);
+            assert_eq!(
+                recaller.recaller().0.histogram(),
+                fresh(&recaller).0.histogram()
+            );
+        }
+        assert!(recaller.recaller().0.histogram().is_empty());
+    }
+}
@@ -0,0 +1,441 @@
+//! Anytime Coloring
+//!
+//! For users who care about the number of colors more than about guarantees, and can spend a given amount of time on it. The
+//! driver first colors the graph greedily in smallest-last order, then tries to do with one color less until the wall-clock budget
+//! expires:
+//! 1. Each vertex of the last color class is moved to a smaller color, possibly after swapping a Kempe chain, the connected
+//!    component of two colors `c` and `d` around its neighbors colored `c`, so that `c` becomes free. If the whole class can be
+//!    moved, it is gone.
+//! 2. Otherwise, the vertices of the last class are given random smaller colors, and the conflicts are removed by the tabu search
+//!    `TabuCol` of [Hertz and de Werra](https://doi.org/10.1007/BF02239976), as improved by
+//!    [Galinier and Hao](https://doi.org/10.1023/A:1009823419804): every move recolors a conflicting vertex to the color
+//!    decreasing the conflicts the most, and forbids it to go back to its previous color for a while.
+//!
+//! The graph is assumed to be undirected, i.e. every edge is present in the adjacency lists of both endpoints.
+
+use std::{
+    collections::BTreeSet,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+
+use super::coloring::Coloring;
+use crate::graph::{stats::Csr, Graphed};
+
+/// Parameters of an [AnytimeColorer]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnytimeParameters {
+    /// Wall-clock time the improvements may take, the greedy coloring is computed regardless
+    pub budget: Duration,
+    /// Moves of the tabu search for each number of colors, before it gives up on it
+    pub tabu_iterations: usize,
+}
+
+impl Default for AnytimeParameters {
+    fn default() -> Self {
+        Self {
+            budget: Duration::from_secs(1),
+            tabu_iterations: 10_000,
+        }
+    }
+}
+
+/// The step that found a coloring
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Greedy,
+    Kempe,
+    Tabu,
+}
+
+/// A coloring found by the driver, with fewer colors than every one before
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Improvement {
+    pub phase: Phase,
+    pub colors: usize,
+    /// Time since the driver started
+    pub elapsed: Duration,
+}
+
+/// The best coloring found within the budget, and the colorings found on the way
+#[derive(Clone, Debug)]
+pub struct AnytimeOutcome<T> {
+    pub coloring: Coloring<T>,
+    /// In the order they were found, the greedy coloring first
+    pub history: Vec<Improvement>,
+}
+
+impl<T> AnytimeOutcome<T> {
+    /// Number of colors of the best coloring
+    pub fn colors(&self) -> usize {
+        self.history
+            .last()
+            .map_or(0, |improvement| improvement.colors)
+    }
+}
+
+/// Coloring a graph with as few colors as a time budget allows
+pub trait AnytimeColorer<T, W> {
+    /// Runtime: O(m log n) for the greedy coloring, then the budget
+    fn anytime_coloring(&self, parameters: AnytimeParameters) -> AnytimeOutcome<T>;
+
+    /// [anytime_coloring](AnytimeColorer::anytime_coloring), drawing the random moves from the generator
+    fn anytime_coloring_with_rng<R: Rng + ?Sized>(
+        &self,
+        parameters: AnytimeParameters,
+        rng: &mut R,
+    ) -> AnytimeOutcome<T>;
+}
+
+impl<G, T, W> AnytimeColorer<T, W> for G
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq + Clone,
+{
+    fn anytime_coloring(&self, parameters: AnytimeParameters) -> AnytimeOutcome<T> {
+        self.anytime_coloring_with_rng(parameters, &mut rand::thread_rng())
+    }
+
+    fn anytime_coloring_with_rng<R: Rng + ?Sized>(
+        &self,
+        parameters: AnytimeParameters,
+        rng: &mut R,
+    ) -> AnytimeOutcome<T> {
+        let start = Instant::now();
+        let deadline = start + parameters.budget;
+        let csr = Csr::from_graph(self);
+
+        let mut colors = greedy(&csr, &smallest_last(&csr));
+        let mut k = colors.iter().max().map_or(0, |c| c + 1);
+        let mut history = vec![Improvement {
+            phase: Phase::Greedy,
+            colors: k,
+            elapsed: start.elapsed(),
+        }];
+
+        while k > 1 && Instant::now() < deadline {
+            let phase = if eliminate_last_class(&csr, &mut colors, k, deadline) {
+                Phase::Kempe
+            } else {
+                match tabucol(
+                    &csr,
+                    &colors,
+                    k - 1,
+                    parameters.tabu_iterations,
+                    deadline,
+                    rng,
+                ) {
+                    Some(tabu_colors) => {
+                        colors = tabu_colors;
+                        Phase::Tabu
+                    }
+                    None => break,
+                }
+            };
+
+            k -= 1;
+            history.push(Improvement {
+                phase,
+                colors: k,
+                elapsed: start.elapsed(),
+            });
+        }
+
+        let coloring = colors
+            .into_iter()
+            .enumerate()
+            .map(|(i, color)| (csr.vertex(i).clone(), color))
+            .collect();
+
+        AnytimeOutcome { coloring, history }
+    }
+}
+
+/// The neighbors of `i`, without `i` itself
+fn neighbors<T>(csr: &Csr<T>, i: usize) -> impl Iterator<Item = usize> + '_
+where
+    T: Hash + Eq + Clone,
+{
+    csr.neighbors(i).iter().copied().filter(move |j| *j != i)
+}
+
+/// Vertices in smallest-last order: repeatedly removing a vertex of minimum degree, the last one removed first
+fn smallest_last<T>(csr: &Csr<T>) -> Vec<usize>
+where
+    T: Hash + Eq + Clone,
+{
+    let mut degrees: Vec<usize> = (0..csr.len()).map(|i| neighbors(csr, i).count()).collect();
+    let mut queue: BTreeSet<(usize, usize)> = degrees.iter().copied().zip(0..).collect();
+    let mut removed = vec![false; csr.len()];
+
+    let mut order = Vec::with_capacity(csr.len());
+    while let Some((degree, i)) = queue.iter().next().copied() {
+        queue.remove(&(degree, i));
+        removed[i] = true;
+        order.push(i);
+
+        for j in neighbors(csr, i) {
+            if !removed[j] && queue.remove(&(degrees[j], j)) {
+                degrees[j] -= 1;
+                queue.insert((degrees[j], j));
+            }
+        }
+    }
+
+    order.reverse();
+    order
+}
+
+/// Every vertex in order gets the smallest color none of its colored neighbors has
+fn greedy<T>(csr: &Csr<T>, order: &[usize]) -> Vec<usize>
+where
+    T: Hash + Eq + Clone,
+{
+    let mut colors: Vec<Option<usize>> = vec![None; csr.len()];
+    for i in order {
+        let bound = csr.neighbors(*i).len() + 1;
+        let mut taken: Vec<bool> = vec![false; bound];
+        neighbors(csr, *i)
+            .filter_map(|j| colors[j])
+            .filter(|c| *c < bound)
+            .for_each(|c| taken[c] = true);
+        colors[*i] = taken.iter().position(|taken| !taken);
+    }
+
+    colors.into_iter().map(Option::unwrap).collect()
+}
+
+/// Move every vertex of the color `k - 1` to a smaller color, leaving the coloring unchanged if one of them cannot be moved
+fn eliminate_last_class<T>(
+    csr: &Csr<T>,
+    colors: &mut Vec<usize>,
+    k: usize,
+    deadline: Instant,
+) -> bool
+where
+    T: Hash + Eq + Clone,
+{
+    let before = colors.clone();
+    let class: Vec<usize> = (0..csr.len()).filter(|i| colors[*i] == k - 1).collect();
+
+    for i in class {
+        if Instant::now() >= deadline || !recolor(csr, colors, i, k - 1) {
+            *colors = before;
+            return false;
+        }
+    }
+    true
+}
+
+/// Give `i` a color smaller than `top`, directly or by swapping a Kempe chain, and whether it could
+fn recolor<T>(csr: &Csr<T>, colors: &mut [usize], i: usize, top: usize) -> bool
+where
+    T: Hash + Eq + Clone,
+{
+    let mut taken = vec![false; top];
+    neighbors(csr, i)
+        .filter(|j| colors[*j] < top)
+        .for_each(|j| taken[colors[j]] = true);
+    if let Some(c) = taken.iter().position(|taken| !taken) {
+        colors[i] = c;
+        return true;
+    }
+
+    for c in 0..top {
+        for d in (0..top).filter(|d| *d != c) {
+            // The (c, d) chains through the neighbors of i colored c
+            let mut chain = vec![false; csr.len()];
+            let mut stack: Vec<usize> = neighbors(csr, i).filter(|j| colors[*j] == c).collect();
+            stack.iter().for_each(|j| chain[*j] = true);
+            while let Some(j) = stack.pop() {
+                for l in neighbors(csr, j) {
+                    if !chain[l] && (colors[l] == c || colors[l] == d) {
+                        chain[l] = true;
+                        stack.push(l);
+                    }
+                }
+            }
+
+            // Swapping the chain would otherwise give a neighbor of i the color c
+            if neighbors(csr, i).any(|j| colors[j] == d && chain[j]) {
+                continue;
+            }
+            for (j, in_chain) in chain.into_iter().enumerate() {
+                if in_chain {
+                    colors[j] = if colors[j] == c { d } else { c };
+                }
+            }
+            colors[i] = c;
+            return true;
+        }
+    }
+
+    false
+}
+
+/// A proper coloring with `k` colors, found by a tabu search from the coloring given, `None` if the search gave up
+fn tabucol<T, R>(
+    csr: &Csr<T>,
+    colors: &[usize],
+    k: usize,
+    iterations: usize,
+    deadline: Instant,
+    rng: &mut R,
+) -> Option<Vec<usize>>
+where
+    T: Hash + Eq + Clone,
+    R: Rng + ?Sized,
+{
+    let n = csr.len();
+    let mut colors: Vec<usize> = colors
+        .iter()
+        .map(|c| if *c < k { *c } else { rng.gen_range(0..k) })
+        .collect();
+
+    // Number of neighbors of i colored c, at i k + c
+    let mut gamma = vec![0_usize; n * k];
+    for i in 0..n {
+        neighbors(csr, i).for_each(|j| gamma[i * k + colors[j]] += 1);
+    }
+    let mut conflicts = (0..n).map(|i| gamma[i * k + colors[i]]).sum::<usize>() / 2;
+    let mut best = conflicts;
+    // The move of i to c is tabu until the iteration at i k + c
+    let mut tabu = vec![0_usize; n * k];
+
+    for iteration in 0..iterations {
+        if conflicts == 0 {
+            return Some(colors);
+        }
+        if iteration % 64 == 0 && Instant::now() >= deadline {
+            return None;
+        }
+
+        // The best allowed move, ties broken uniformly at random
+        let mut chosen: Option<(i64, usize, usize)> = None;
+        let mut ties = 0;
+        for i in (0..n).filter(|i| gamma[i * k + colors[*i]] > 0) {
+            let current = gamma[i * k + colors[i]] as i64;
+            for c in (0..k).filter(|c| *c != colors[i]) {
+                let delta = gamma[i * k + c] as i64 - current;
+                let aspiration = (conflicts as i64 + delta) < best as i64;
+                if tabu[i * k + c] > iteration && !aspiration {
+                    continue;
+                }
+
+                match chosen {
+                    Some((best_delta, _, _)) if delta > best_delta => {}
+                    Some((best_delta, _, _)) if delta == best_delta => {
+                        ties += 1;
+                        if rng.gen_range(0..ties) == 0 {
+                            chosen = Some((delta, i, c));
+                        }
+                    }
+                    _ => {
+                        ties = 1;
+                        chosen = Some((delta, i, c));
+                    }
+                }
+            }
+        }
+
+        if let Some((delta, i, c)) = chosen {
+            let previous = colors[i];
+            for j in neighbors(csr, i) {
+                gamma[j * k + previous] -= 1;
+                gamma[j * k + c] += 1;
+            }
+            colors[i] = c;
+            conflicts = (conflicts as i64 + delta) as usize;
+            best = best.min(conflicts);
+
+            let tenure = (0.6 * conflicts as f64) as usize + rng.gen_range(0..10);
+            tabu[i * k + previous] = iteration + 1 + tenure;
+        }
+    }
+
+    if conflicts == 0 {
+        Some(colors)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{prelude::Distribution, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::{
+        graph::{static_a::coloring::Colorer, Edge, Graph, VertexId},
+        random_graph::bernoulli::BernoulliGraphDistribution,
+    };
+
+    /// `K_{n,n}` minus a perfect matching, on which greedy colorings can be very bad
+    fn crown(n: u32) -> Graph<u32, ()> {
+        let mut graph = Graph::default();
+        for u in 0..n {
+            for v in (0..n).filter(|v| *v != u) {
+                graph.add_edge(Edge::init(2 * u, 2 * v + 1));
+            }
+        }
+        graph
+    }
+
+    fn assert_history(outcome: &AnytimeOutcome<impl Hash + Eq + Clone>) {
+        assert_eq!(outcome.history[0].phase, Phase::Greedy);
+        assert!(
+            outcome
+                .history
+                .windows(2)
+                .all(|pair| pair[1].colors == pair[0].colors - 1
+                    && pair[1].elapsed >= pair[0].elapsed)
+        );
+        assert_eq!(outcome.colors(), outcome.coloring.num_classes());
+    }
+
+    #[test]
+    fn crown_graph() {
+        let graph = crown(8);
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let parameters = AnytimeParameters {
+            budget: Duration::from_secs(10),
+            ..Default::default()
+        };
+
+        let outcome = graph.anytime_coloring_with_rng(parameters, &mut rng);
+        assert!(graph.is_proper(&outcome.coloring));
+        assert_history(&outcome);
+        assert_eq!(outcome.colors(), 2);
+    }
+
+    #[test]
+    fn random_graph() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let graph: Graph<VertexId, ()> = BernoulliGraphDistribution::init(60, 0.3)
+            .unwrap()
+            .sample(&mut rng);
+
+        let outcome = graph.anytime_coloring_with_rng(Default::default(), &mut rng);
+        assert!(graph.is_proper(&outcome.coloring));
+        assert_history(&outcome);
+        assert_eq!(outcome.coloring.len(), graph.vertices().len());
+    }
+
+    #[test]
+    fn no_budget() {
+        let graph = crown(6);
+        let parameters = AnytimeParameters {
+            budget: Duration::from_secs(0),
+            ..Default::default()
+        };
+
+        // Only the greedy coloring is computed
+        let outcome = graph.anytime_coloring(parameters);
+        assert_eq!(outcome.history.len(), 1);
+        assert!(graph.is_proper(&outcome.coloring));
+    }
+}
@@ -0,0 +1,227 @@
+//! Chordal Graphs
+//!
+//! A graph is chordal if every cycle of length at least 4 has a chord. Equivalently, it has a perfect elimination ordering:
+//! an ordering of the vertices in which the neighbors of each vertex that come after it form a clique.
+//!
+//! The reverse of a [Lex-BFS](https://en.wikipedia.org/wiki/Lexicographic_breadth-first_search) ordering is a perfect elimination
+//! ordering whenever the graph is chordal, and given one, coloring and finding a maximum clique are both solved exactly.
+//!
+//! The graph is assumed to be undirected, i.e. every edge is present in the adjacency lists of both endpoints.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use super::coloring::Coloring;
+use crate::graph::Graphed;
+
+/// Algorithms on chordal graphs
+pub trait Chordal<T, W> {
+    /// Lexicographic Breadth First Search ordering of the vertices, by partition refinement
+    ///
+    /// Runtime: O(n^2)
+    fn lex_bfs(&self) -> Vec<T>;
+    /// A perfect elimination ordering of the graph, `None` if the graph is not chordal
+    ///
+    /// Runtime: O(n^2 + m)
+    fn perfect_elimination_ordering(&self) -> Option<Vec<T>>;
+    /// Whether every cycle of length at least 4 has a chord
+    fn is_chordal(&self) -> bool;
+    /// An optimal coloring, using as many colors as the size of a maximum clique, `None` if the graph is not chordal
+    fn chordal_coloring(&self) -> Option<Coloring<T>>;
+    /// A maximum clique, `None` if the graph is not chordal
+    fn maximum_clique(&self) -> Option<HashSet<T>>;
+}
+
+impl<G, T, W> Chordal<T, W> for G
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq + Clone,
+{
+    fn lex_bfs(&self) -> Vec<T> {
+        let mut classes: Vec<Vec<T>> = vec![self.adj_list().keys().cloned().collect()];
+        let mut ordering = Vec::with_capacity(self.adj_list().len());
+
+        while let Some(first) = classes.first_mut() {
+            let v = match first.pop() {
+                Some(v) => v,
+                None => {
+                    classes.remove(0);
+                    continue;
+                }
+            };
+
+            let neighbors = neighbor_set(self, &v);
+
+            // Neighbors of v move ahead of the other vertices of their class
+            classes = classes
+                .into_iter()
+                .flat_map(|class| {
+                    let (inside, outside): (Vec<T>, Vec<T>) =
+                        class.into_iter().partition(|u| neighbors.contains(u));
+                    vec![inside, outside]
+                })
+                .filter(|class| !class.is_empty())
+                .collect();
+
+            ordering.push(v);
+        }
+
+        ordering
+    }
+
+    fn perfect_elimination_ordering(&self) -> Option<Vec<T>> {
+        let mut ordering = self.lex_bfs();
+        ordering.reverse();
+
+        let position: HashMap<&T, usize> =
+            ordering.iter().enumerate().map(|(i, v)| (v, i)).collect();
+        let neighbors: HashMap<&T, HashSet<&T>> = ordering
+            .iter()
+            .map(|v| (v, neighbor_set(self, v)))
+            .collect();
+
+        // The later neighbors of v form a clique if they are all adjacent to the earliest of them,
+        // the later neighbors of which are checked in turn.
+        for (i, v) in ordering.iter().enumerate() {
+            let later: Vec<&T> = neighbors[v]
+                .iter()
+                .copied()
+                .filter(|u| position.get(u).map(|p| *p > i).unwrap_or_default())
+                .collect();
+
+            if let Some(parent) = later.iter().min_by_key(|u| position[*u]) {
+                if later
+                    .iter()
+                    .any(|u| u != parent && !neighbors[parent].contains(u))
+                {
+                    return None;
+                }
+            }
+        }
+
+        Some(ordering)
+    }
+
+    fn is_chordal(&self) -> bool {
+        self.perfect_elimination_ordering().is_some()
+    }
+
+    fn chordal_coloring(&self) -> Option<Coloring<T>> {
+        let ordering = self.perfect_elimination_ordering()?;
+        let mut coloring = Coloring::new();
+
+        // In reverse, the colored neighbors of each vertex form a clique, so greedy uses at most the clique number of colors
+        for v in ordering.into_iter().rev() {
+            let neighbor_colors: HashSet<usize> = neighbor_set(self, &v)
+                .into_iter()
+                .filter_map(|u| coloring.get(u))
+                .copied()
+                .collect();
+
+            let color = (0..).find(|c| !neighbor_colors.contains(c)).unwrap();
+            coloring.insert(v, color);
+        }
+
+        Some(coloring)
+    }
+
+    fn maximum_clique(&self) -> Option<HashSet<T>> {
+        let ordering = self.perfect_elimination_ordering()?;
+        let position: HashMap<&T, usize> =
+            ordering.iter().enumerate().map(|(i, v)| (v, i)).collect();
+
+        // Every maximal clique is a vertex along with its later neighbors in the ordering
+        ordering
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let mut clique: HashSet<T> = neighbor_set(self, v)
+                    .into_iter()
+                    .filter(|u| position.get(u).map(|p| *p > i).unwrap_or_default())
+                    .cloned()
+                    .collect();
+                clique.insert(v.clone());
+                clique
+            })
+            .max_by_key(|clique| clique.len())
+            .or_else(|| Some(HashSet::new()))
+    }
+}
+
+fn neighbor_set<'g, G, T, W>(graph: &'g G, vertex: &T) -> HashSet<&'g T>
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq,
+    W: 'g,
+{
+    graph
+        .get_neighbors(vertex)
+        .map(|neighbors| neighbors.iter().map(|n| &n.destination).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{static_a::coloring::Colorer, Edge, Graph};
+
+    fn graph(edges: &[(u32, u32)]) -> Graph<u32, ()> {
+        let mut graph = Graph::default();
+        for (u, v) in edges {
+            graph.add_edge(Edge::init(*u, *v));
+        }
+        graph
+    }
+
+    /// Two triangles 0 1 2 and 1 2 8 sharing the edge (1, 2), a K4 on [3, 7) attached through 2 - 3, and a pendant vertex 7
+    fn chordal_graph() -> Graph<u32, ()> {
+        graph(&[
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (1, 8),
+            (2, 8),
+            (2, 3),
+            (3, 4),
+            (3, 5),
+            (3, 6),
+            (4, 5),
+            (4, 6),
+            (5, 6),
+            (6, 7),
+        ])
+    }
+
+    #[test]
+    fn lex_bfs() {
+        let graph = chordal_graph();
+        let ordering = graph.lex_bfs();
+
+        assert_eq!(ordering.len(), 9);
+        assert_eq!(ordering.iter().collect::<HashSet<_>>().len(), 9);
+    }
+
+    #[test]
+    fn chordal() {
+        let graph = chordal_graph();
+
+        assert!(graph.is_chordal());
+        assert_eq!(graph.maximum_clique(), Some((3..7).collect::<HashSet<_>>()));
+
+        let coloring = graph.chordal_coloring().unwrap();
+        assert!(graph.is_proper(&coloring));
+        assert_eq!(coloring.num_classes(), 4);
+    }
+
+    #[test]
+    fn not_chordal() {
+        // A cycle on 5 vertices, with a single chord leaving a 4-cycle
+        let graph = graph(&[(0, 1), (1, 2), (2, 3), (3, 4), (4, 0), (0, 2)]);
+
+        assert!(!graph.is_chordal());
+        assert_eq!(graph.perfect_elimination_ordering(), None);
+        assert_eq!(graph.chordal_coloring(), None);
+    }
+}
@@ -1,11 +1,15 @@
 //! Relating to all things coloring
 
+use log::debug;
 use rand::Rng;
 
 use super::super::*;
 use std::{cmp::max, collections::HashSet};
 
-pub type Coloring<T> = HashMap<T, usize>;
+pub use super::partition::Partition;
+
+/// A coloring of the vertices, partitioned into color classes
+pub type Coloring<T> = Partition<T>;
 
 /// Coloring a Graph
 pub trait Colorer<T, W> {
@@ -41,7 +45,7 @@ where
 
         ordering.reverse();
 
-        let mut coloring = HashMap::new();
+        let mut coloring = Coloring::new();
 
         ordering.into_iter().for_each(|v| {
             let mut color: usize = 0;
@@ -65,7 +69,7 @@ where
     }
 
     fn randomized(&self) -> Coloring<T> {
-        let mut coloring = HashMap::new();
+        let mut coloring = Coloring::new();
 
         let delta_1 = self
             .adj_list()
@@ -121,7 +125,7 @@ where
                         .unwrap_or_else(|| panic!("The provided coloring is not one for the provided graph, Could not find a color for: {:?}", neighbor.destination))
                         == color
                     {
-                        println!("Coloring was not proper under the following vertices: {:?}, {:?}", neighbor.destination, v);
+                        debug!("Coloring was not proper under the following vertices: {:?}, {:?}", neighbor.destination, v);
                         return false;
                     }
                 }
@@ -139,7 +143,7 @@ where
                         .map(|c| c == color)
                         .unwrap_or_default()
                     {
-                        println!(
+                        debug!(
                             "Coloring was not proper(partial) under the following vertices: {:?}, {:?}",
                             neighbor.destination, v
                         );
@@ -152,7 +156,7 @@ where
     }
 
     fn greedy(&self, options: Option<HashMap<T, HashSet<u32>>>) -> Coloring<T> {
-        let mut coloring: HashMap<T, usize> = HashMap::new();
+        let mut coloring = Coloring::new();
 
         for v in self.vertices() {
             let neighbor_colors = self
@@ -0,0 +1,161 @@
+//! Minimum Cuts
+//!
+//! Cuts of graphs whose edge labels are integer weights, e.g. the output of a
+//! [CutSparsifier](crate::graph::streaming::sparsifier::CutSparsifier). Unweighted graphs can be given weight `1` on every edge.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::graph::Graphed;
+
+/// Cuts of an undirected graph, weighted by its edge labels
+pub trait MinCut<T> {
+    /// Total weight of the edges with exactly one endpoint in `side`
+    fn cut_weight(&self, side: &HashSet<T>) -> u64;
+
+    /// A global minimum cut by the algorithm of [Stoer and Wagner](https://doi.org/10.1145/263867.263872), as its weight and one of
+    /// its sides, `None` if the graph has fewer than two vertices
+    ///
+    /// Runtime: O(n^3)
+    fn stoer_wagner(&self) -> Option<(u64, HashSet<T>)>;
+}
+
+impl<G, T> MinCut<T> for G
+where
+    G: Graphed<T, u64>,
+    T: Hash + Eq + Clone,
+{
+    fn cut_weight(&self, side: &HashSet<T>) -> u64 {
+        side.iter()
+            .filter_map(|u| self.get_neighbors(u))
+            .flatten()
+            .filter(|n| !side.contains(&n.destination))
+            .map(|n| n.label)
+            .sum()
+    }
+
+    #[allow(clippy::needless_range_loop)]
+    fn stoer_wagner(&self) -> Option<(u64, HashSet<T>)> {
+        let vertices: Vec<T> = self.adj_list().keys().cloned().collect();
+        let n = vertices.len();
+        if n < 2 {
+            return None;
+        }
+
+        let index: HashMap<&T, usize> = vertices.iter().enumerate().map(|(i, v)| (v, i)).collect();
+        let mut weights = vec![vec![0_u64; n]; n];
+        for (u, neighbors) in self.adj_list() {
+            for neighbor in neighbors {
+                let (i, j) = (index[u], index[&neighbor.destination]);
+                if i != j {
+                    weights[i][j] += neighbor.label;
+                }
+            }
+        }
+        // Undirected edges are stored in both directions, keep a single copy of arcs stored only once
+        for i in 0..n {
+            for j in 0..i {
+                let weight = weights[i][j].max(weights[j][i]);
+                weights[i][j] = weight;
+                weights[j][i] = weight;
+            }
+        }
+
+        let mut groups: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+        let mut active: Vec<usize> = (0..n).collect();
+        let mut best: Option<(u64, Vec<usize>)> = None;
+
+        while active.len() > 1 {
+            // Maximum adjacency ordering of the active vertices
+            let mut attached = vec![0_u64; n];
+            let mut added = vec![false; n];
+            let (mut previous, mut last) = (active[0], active[0]);
+
+            for _ in 0..active.len() {
+                let next = *active
+                    .iter()
+                    .filter(|v| !added[**v])
+                    .max_by_key(|v| attached[**v])
+                    .unwrap();
+                added[next] = true;
+                previous = last;
+                last = next;
+                for v in active.iter() {
+                    attached[*v] += weights[next][*v];
+                }
+            }
+
+            // The cut of the phase separates the last vertex from the others
+            if best
+                .as_ref()
+                .map(|(w, _)| attached[last] < *w)
+                .unwrap_or(true)
+            {
+                best = Some((attached[last], groups[last].clone()));
+            }
+
+            let moved = std::mem::take(&mut groups[last]);
+            groups[previous].extend(moved);
+            for v in 0..n {
+                weights[previous][v] += weights[last][v];
+                weights[v][previous] = weights[previous][v];
+            }
+            weights[previous][previous] = 0;
+            active.retain(|v| *v != last);
+        }
+
+        best.map(|(weight, side)| {
+            (
+                weight,
+                side.into_iter().map(|i| vertices[i].clone()).collect(),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{Edge, Graph};
+
+    fn weighted(edges: &[(u32, u32, u64)]) -> Graph<u32, u64> {
+        let mut graph = Graph::default();
+        for (u, v, w) in edges {
+            let mut edge = Edge::init(*u, *v);
+            edge.update_label(*w);
+            graph.add_edge(edge);
+        }
+        graph
+    }
+
+    #[test]
+    fn two_triangles() {
+        // Heavy triangles joined by a light edge
+        let graph = weighted(&[
+            (0, 1, 5),
+            (1, 2, 5),
+            (2, 0, 5),
+            (3, 4, 5),
+            (4, 5, 5),
+            (5, 3, 5),
+            (2, 3, 2),
+        ]);
+
+        let (weight, side) = graph.stoer_wagner().unwrap();
+        assert_eq!(weight, 2);
+        assert_eq!(graph.cut_weight(&side), 2);
+        assert!(side == (0..3).collect() || side == (3..6).collect());
+
+        assert_eq!(graph.cut_weight(&vec![0].into_iter().collect()), 10);
+    }
+
+    #[test]
+    fn disconnected() {
+        let graph = weighted(&[(0, 1, 3), (2, 3, 1)]);
+        assert_eq!(graph.stoer_wagner().map(|(w, _)| w), Some(0));
+
+        assert_eq!(weighted(&[]).stoer_wagner(), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Sparse-Dense Decomposition
+//!
+//! The exact decomposition of [Harris, Schneider and Su](https://arxiv.org/abs/1608.01689), as used by the
+//! [ACK paper](https://arxiv.org/pdf/1807.08886.pdf), for a graph of maximum degree `Δ` and a parameter `ε`:
+//! 1. An edge `(u, v)` is an `ε`-friend edge if `u` and `v` have at least `(1 - ε)Δ` common neighbors
+//! 2. A vertex is `ε`-dense if it has at least `(1 - ε)Δ` friend edges, and sparse otherwise
+//! 3. The almost-cliques are the connected components of the dense vertices, joined by friend edges
+//!
+//! The streaming decompositions only approximate this, by sampling neighborhoods, so this in-memory version serves as their oracle.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    graph::{streaming::coloring::almost_clique::AlmostClique, Edge, Graph, Graphed, VertexId},
+    utils::slab::sorted_intersection_count,
+};
+
+/// The vertices of a graph, split into sparse vertices and almost-cliques
+#[derive(Clone, Debug)]
+pub struct SparseDenseDecomposition {
+    pub sparse: HashSet<VertexId>,
+    pub almost_cliques: Vec<AlmostClique>,
+}
+
+impl SparseDenseDecomposition {
+    /// The almost-clique of a vertex, `None` if it is sparse
+    pub fn almost_clique(&self, vertex: &VertexId) -> Option<&AlmostClique> {
+        self.almost_cliques.iter().find(|c| c.contains(vertex))
+    }
+}
+
+/// Exact sparse-dense decomposition of a graph of maximum degree `delta`, with parameter `epsilon`
+pub trait SparseDense {
+    /// The graph of the `ε`-friend edges
+    ///
+    /// Runtime: O(m Δ)
+    fn friend_edges(&self, delta: u32, epsilon: f64) -> Graph<VertexId, ()>;
+
+    /// The vertices with at least `(1 - ε)Δ` friend edges
+    fn dense_vertices(&self, delta: u32, epsilon: f64) -> HashSet<VertexId>;
+
+    /// Runtime: O(m Δ)
+    fn sparse_dense(&self, delta: u32, epsilon: f64) -> SparseDenseDecomposition;
+}
+
+impl<G> SparseDense for G
+where
+    G: Graphed<VertexId, ()>,
+{
+    fn friend_edges(&self, delta: u32, epsilon: f64) -> Graph<VertexId, ()> {
+        let threshold = (1.0 - epsilon) * delta as f64;
+
+        let neighborhoods: HashMap<VertexId, Vec<VertexId>> = self
+            .adj_list()
+            .iter()
+            .map(|(v, neighbors)| {
+                let mut neighbors: Vec<VertexId> = neighbors
+                    .iter()
+                    .map(|n| n.destination)
+                    .filter(|u| u != v)
+                    .collect();
+                neighbors.sort_unstable();
+                neighbors.dedup();
+                (*v, neighbors)
+            })
+            .collect();
+
+        let mut friends = Graph::default();
+        for (v, neighbors) in neighborhoods.iter() {
+            for u in neighbors.iter().filter(|u| *u > v) {
+                let common = neighborhoods
+                    .get(u)
+                    .map(|other| sorted_intersection_count(neighbors, other))
+                    .unwrap_or_default();
+
+                if common as f64 >= threshold {
+                    friends.add_edge(Edge::init(*v, *u));
+                }
+            }
+        }
+
+        friends
+    }
+
+    fn dense_vertices(&self, delta: u32, epsilon: f64) -> HashSet<VertexId> {
+        let threshold = (1.0 - epsilon) * delta as f64;
+
+        self.friend_edges(delta, epsilon)
+            .adj_list()
+            .iter()
+            .filter(|(_, friends)| friends.len() as f64 >= threshold)
+            .map(|(v, _)| *v)
+            .collect()
+    }
+
+    fn sparse_dense(&self, delta: u32, epsilon: f64) -> SparseDenseDecomposition {
+        let threshold = (1.0 - epsilon) * delta as f64;
+        let friends = self.friend_edges(delta, epsilon);
+
+        let dense: HashSet<VertexId> = friends
+            .adj_list()
+            .iter()
+            .filter(|(_, friends)| friends.len() as f64 >= threshold)
+            .map(|(v, _)| *v)
+            .collect();
+
+        let mut assigned: HashSet<VertexId> = HashSet::new();
+        let mut almost_cliques = vec![];
+        for start in dense.iter() {
+            if !assigned.insert(*start) {
+                continue;
+            }
+
+            let mut component = HashSet::new();
+            let mut stack = vec![*start];
+            while let Some(v) = stack.pop() {
+                component.insert(v);
+                for n in friends.get_neighbors(&v).into_iter().flatten() {
+                    if dense.contains(&n.destination) && assigned.insert(n.destination) {
+                        stack.push(n.destination);
+                    }
+                }
+            }
+
+            almost_cliques.push(AlmostClique::init(component, self));
+        }
+
+        SparseDenseDecomposition {
+            sparse: self
+                .vertices()
+                .into_iter()
+                .filter(|v| !dense.contains(v))
+                .copied()
+                .collect(),
+            almost_cliques,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Two cliques on `[0, 10)` and `[10, 20)` joined by the edge `(0, 10)`, and a path from 19 through `[20, 25)`
+    fn test_graph() -> Graph<VertexId, ()> {
+        let mut graph = Graph::default();
+        for offset in [0, 10] {
+            for u in 0..10 {
+                for v in (u + 1)..10 {
+                    graph.add_edge(Edge::init(u + offset, v + offset));
+                }
+            }
+        }
+        graph.add_edge(Edge::init(0, 10));
+        for v in 19..24 {
+            graph.add_edge(Edge::init(v, v + 1));
+        }
+        graph
+    }
+
+    #[test]
+    fn friend_edges() {
+        let graph = test_graph();
+        let friends = graph.friend_edges(10, 0.2);
+
+        // Adjacent vertices of a clique share the 8 other vertices
+        assert!(friends.has_edge(&Edge::init(3, 4)));
+        assert!(friends.has_edge(&Edge::init(0, 1)));
+        assert!(!friends.has_edge(&Edge::init(0, 10)));
+        assert!(!friends.has_edge(&Edge::init(19, 20)));
+    }
+
+    #[test]
+    fn decomposition() {
+        let graph = test_graph();
+        let decomposition = graph.sparse_dense(10, 0.2);
+
+        assert_eq!(decomposition.almost_cliques.len(), 2);
+        assert_eq!(decomposition.sparse, (20..25).collect());
+
+        let first = decomposition.almost_clique(&3).unwrap();
+        assert_eq!(first.vertices, (0..10).collect());
+        assert!(first.validate(&graph, 10, 0.05).is_ok());
+        assert!(decomposition.almost_clique(&22).is_none());
+
+        assert_eq!(
+            graph.dense_vertices(10, 0.2),
+            (0..20).collect::<HashSet<_>>()
+        );
+    }
+}
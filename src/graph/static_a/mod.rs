@@ -2,6 +2,17 @@
 //!
 //! All of the Algorithms here assume that complete knowledge of the graph is known, and stored in local memory
 
+pub mod anytime;
+pub mod chordal;
 pub mod coloring;
+pub mod cut;
+pub mod decomposition;
+pub mod feedback;
 pub mod matching;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod partition;
 pub mod search;
+pub mod spectral;
+pub mod tree;
+pub mod weighted;
@@ -0,0 +1,288 @@
+//! Colorings as Partitions
+//!
+//! A coloring is a partition of the vertices into color classes. [Partition] keeps both views in sync,
+//! the color of each vertex and the vertices of each color, so that class based algorithms (equitable colorings,
+//! Kempe chains, reporting) do not have to invert the map themselves.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    hash::Hash,
+    iter::FromIterator,
+};
+
+/// Vertices partitioned into color classes, colors of type `C`
+///
+/// Only non empty classes are stored, so the number of classes is the number of colors used.
+#[derive(Clone, Debug)]
+pub struct Partition<T, C = usize> {
+    colors: HashMap<T, C>,
+    classes: BTreeMap<C, HashSet<T>>,
+}
+
+/// Summary of the sizes of the color classes of a [Partition]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClassSizes {
+    pub classes: usize,
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+}
+
+impl<T, C> Default for Partition<T, C> {
+    fn default() -> Self {
+        Self {
+            colors: HashMap::new(),
+            classes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Partitions are equal if they color every vertex the same
+impl<T, C> PartialEq for Partition<T, C>
+where
+    T: Hash + Eq,
+    C: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.colors == other.colors
+    }
+}
+
+impl<T, C> Eq for Partition<T, C>
+where
+    T: Hash + Eq,
+    C: Eq,
+{
+}
+
+impl<T, C> Partition<T, C>
+where
+    T: Hash + Eq + Clone,
+    C: Ord + Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Color a vertex, moving it out of its previous class, and returning its previous color
+    pub fn insert(&mut self, vertex: T, color: C) -> Option<C> {
+        let previous = self.remove(&vertex);
+        self.classes
+            .entry(color.clone())
+            .or_default()
+            .insert(vertex.clone());
+        self.colors.insert(vertex, color);
+        previous
+    }
+
+    /// Uncolor a vertex, returning its color
+    pub fn remove(&mut self, vertex: &T) -> Option<C> {
+        let color = self.colors.remove(vertex)?;
+        if let Some(class) = self.classes.get_mut(&color) {
+            class.remove(vertex);
+            if class.is_empty() {
+                self.classes.remove(&color);
+            }
+        }
+        Some(color)
+    }
+
+    pub fn get(&self, vertex: &T) -> Option<&C> {
+        self.colors.get(vertex)
+    }
+
+    /// Whether the vertex is colored
+    pub fn contains(&self, vertex: &T) -> bool {
+        self.colors.contains_key(vertex)
+    }
+
+    /// Number of colored vertices
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Number of colors used
+    pub fn num_classes(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// The vertices of a color, `None` if no vertex has that color
+    pub fn class(&self, color: &C) -> Option<&HashSet<T>> {
+        self.classes.get(color)
+    }
+
+    /// The color classes, in increasing order of color
+    pub fn classes(&self) -> impl Iterator<Item = (&C, &HashSet<T>)> {
+        self.classes.iter()
+    }
+
+    /// The color of each vertex
+    pub fn colors(&self) -> &HashMap<T, C> {
+        &self.colors
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&T, &C)> {
+        self.colors.iter()
+    }
+
+    /// Recolor every vertex of the class `from` with the color `into`, returning the number of vertices moved
+    ///
+    /// The result is only proper if no edge joins the two classes.
+    pub fn merge(&mut self, into: C, from: C) -> usize {
+        if into == from {
+            return 0;
+        }
+
+        let moved = match self.classes.remove(&from) {
+            Some(moved) => moved,
+            None => return 0,
+        };
+
+        for vertex in moved.iter() {
+            self.colors.insert(vertex.clone(), into.clone());
+        }
+        let count = moved.len();
+        self.classes.entry(into).or_default().extend(moved);
+        count
+    }
+
+    /// Sizes of the color classes, `None` if no vertex is colored
+    pub fn class_sizes(&self) -> Option<ClassSizes> {
+        let sizes = self.classes.values().map(|class| class.len());
+
+        Some(ClassSizes {
+            classes: self.classes.len(),
+            min: sizes.clone().min()?,
+            max: sizes.max()?,
+            mean: self.colors.len() as f64 / self.classes.len() as f64,
+        })
+    }
+
+    /// Whether the sizes of any two classes differ by at most one
+    pub fn is_equitable(&self) -> bool {
+        self.class_sizes()
+            .map(|sizes| sizes.max - sizes.min <= 1)
+            .unwrap_or(true)
+    }
+
+    /// The color of each vertex, dropping the classes
+    pub fn into_colors(self) -> HashMap<T, C> {
+        self.colors
+    }
+}
+
+impl<T, C> FromIterator<(T, C)> for Partition<T, C>
+where
+    T: Hash + Eq + Clone,
+    C: Ord + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = (T, C)>>(iter: I) -> Self {
+        let mut partition = Self::new();
+        for (vertex, color) in iter {
+            partition.insert(vertex, color);
+        }
+        partition
+    }
+}
+
+impl<T, C> From<HashMap<T, C>> for Partition<T, C>
+where
+    T: Hash + Eq + Clone,
+    C: Ord + Clone,
+{
+    fn from(colors: HashMap<T, C>) -> Self {
+        colors.into_iter().collect()
+    }
+}
+
+impl<T, C> IntoIterator for Partition<T, C>
+where
+    T: Hash + Eq,
+{
+    type Item = (T, C);
+    type IntoIter = std::collections::hash_map::IntoIter<T, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.colors.into_iter()
+    }
+}
+
+impl<'a, T, C> IntoIterator for &'a Partition<T, C>
+where
+    T: Hash + Eq,
+{
+    type Item = (&'a T, &'a C);
+    type IntoIter = std::collections::hash_map::Iter<'a, T, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.colors.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn classes() {
+        let mut partition: Partition<u32> =
+            vec![(0, 0), (1, 1), (2, 0), (3, 2)].into_iter().collect();
+
+        assert_eq!(partition.num_classes(), 3);
+        assert_eq!(partition.class(&0), Some(&vec![0, 2].into_iter().collect()));
+
+        // Recoloring the only vertex of a class removes the class
+        assert_eq!(partition.insert(3, 1), Some(2));
+        assert_eq!(partition.num_classes(), 2);
+        assert_eq!(partition.class(&2), None);
+        assert_eq!(
+            partition.classes().map(|(c, _)| *c).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+
+        assert_eq!(partition.remove(&1), Some(1));
+        assert!(!partition.contains(&1));
+        assert_eq!(partition.len(), 3);
+    }
+
+    #[test]
+    fn merge() {
+        let mut partition: Partition<u32> =
+            vec![(0, 0), (1, 1), (2, 1), (3, 2)].into_iter().collect();
+
+        assert_eq!(partition.merge(0, 1), 2);
+        assert_eq!(partition.merge(0, 7), 0);
+        assert_eq!(partition.get(&2), Some(&0));
+        assert_eq!(partition.class(&0).map(|class| class.len()), Some(3));
+        assert_eq!(partition.num_classes(), 2);
+    }
+
+    #[test]
+    fn class_sizes() {
+        let mut partition: Partition<u32, (u32, u32)> = Partition::new();
+        assert_eq!(partition.class_sizes(), None);
+        assert!(partition.is_equitable());
+
+        partition.insert(0, (0, 0));
+        partition.insert(1, (0, 0));
+        partition.insert(2, (1, 0));
+
+        assert_eq!(
+            partition.class_sizes(),
+            Some(ClassSizes {
+                classes: 2,
+                min: 1,
+                max: 2,
+                mean: 1.5
+            })
+        );
+        assert!(partition.is_equitable());
+
+        partition.insert(3, (0, 0));
+        assert!(!partition.is_equitable());
+    }
+}
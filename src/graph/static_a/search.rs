@@ -1,7 +1,9 @@
 //! Different Graph Search Algorithms
 
 use crate::graph::{edge::EdgeDestination, Edge, Graph, Graphed};
+use priority_queue::PriorityQueue;
 use std::{
+    cmp::Reverse,
     collections::{HashMap, HashSet, LinkedList},
     f32::INFINITY,
     fmt::Debug,
@@ -38,6 +40,18 @@ pub trait Search<'s, T, W> {
     fn depth_first<S>(&self, searcher: &'s mut S, start: &T)
     where
         S: Searcher<T, W>;
+    /// Induced subgraph on the vertices within distance `k` of `vertex`, i.e. its `k`-hop ego network, empty if `vertex` is not in
+    /// the graph
+    ///
+    /// Runs a breadth first search from `vertex` that does not expand the vertices at depth `k`, so only the neighborhood and the
+    /// edges leaving it are searched, whatever the size of the rest of the graph.
+    fn k_hop_neighborhood(&self, vertex: &T, k: usize) -> Self
+    where
+        Self: Sized;
+    /// Find a cycle within the graph, returning the sequence of vertices along it if one exists
+    ///
+    /// - *directed* : Whether the adjacency list should be read as directed edges. Otherwise each edge is expected in both directions, and going back along the same edge is not a cycle.
+    fn find_cycle(&self, directed: bool) -> Option<Vec<T>>;
 }
 
 impl<'s, G, T, W> Search<'s, T, W> for G
@@ -102,9 +116,144 @@ where
             }
         }
     }
+
+    fn k_hop_neighborhood(&self, vertex: &T, k: usize) -> Self {
+        if self.get_neighbors(vertex).is_none() {
+            return G::new(HashMap::new());
+        }
+
+        // One layer of depth at a time, the last layer is found but not expanded
+        let mut within: HashSet<&T> = vec![vertex].into_iter().collect();
+        let mut layer = vec![vertex];
+        for _ in 0..k {
+            let mut next = vec![];
+            for current in layer {
+                for neighbor in self.get_neighbors(current).into_iter().flatten() {
+                    if within.insert(&neighbor.destination) {
+                        next.push(&neighbor.destination);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            layer = next;
+        }
+
+        G::new(
+            within
+                .iter()
+                .map(|v| {
+                    let neighbors = self
+                        .get_neighbors(v)
+                        .map(|neighbors| {
+                            neighbors
+                                .iter()
+                                .filter(|n| within.contains(&n.destination))
+                                .cloned()
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    ((*v).clone(), neighbors)
+                })
+                .collect(),
+        )
+    }
+
+    fn find_cycle(&self, directed: bool) -> Option<Vec<T>> {
+        if !directed {
+            let mut cycle_finder = CycleFinder::default();
+            if let Some(start) = self.vertices().into_iter().next() {
+                self.depth_first(&mut cycle_finder, start);
+            }
+            return cycle_finder.cycle;
+        }
+
+        // Directed cycles need to know which vertices are on the current DFS path, so we run the DFS explicitly
+        let mut finished: HashSet<&T> = HashSet::new();
+
+        for root in self.vertices() {
+            if finished.contains(root) {
+                continue;
+            }
+
+            let mut path = vec![root];
+            let mut on_path: HashSet<&T> = vec![root].into_iter().collect();
+            let mut stack = vec![(root, self.get_neighbors(root).into_iter().flatten())];
+
+            while let Some((vertex, neighbors)) = stack.last_mut() {
+                if let Some(next) = neighbors.next() {
+                    let next = &next.destination;
+                    if on_path.contains(next) {
+                        let start = path.iter().position(|v| *v == next).unwrap();
+                        return Some(path[start..].iter().map(|v| (*v).clone()).collect());
+                    }
+                    if !finished.contains(next) {
+                        path.push(next);
+                        on_path.insert(next);
+                        stack.push((next, self.get_neighbors(next).into_iter().flatten()));
+                    }
+                } else {
+                    let vertex = *vertex;
+                    finished.insert(vertex);
+                    on_path.remove(vertex);
+                    path.pop();
+                    stack.pop();
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Structure for maintaining the distance of each vertex from the start of a BFS search
+///
+/// Only the component of the start vertex is explored, once the search moves on to a new component we stop recording.
+#[derive(Clone, Debug)]
+pub struct Depths<T> {
+    pub data: HashMap<T, usize>,
+    exhausted: bool,
+}
+
+impl<T> Depths<T>
+where
+    T: Eq + Hash,
+{
+    pub fn init(start: T) -> Self {
+        let mut data = HashMap::new();
+        data.insert(start, 0);
+
+        Self {
+            data,
+            exhausted: false,
+        }
+    }
+}
+
+impl<T, W> Searcher<T, W> for Depths<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn new_component(&mut self, _node: &T) {
+        self.exhausted = true;
+    }
+    fn visit(&mut self, source: &T, node: &EdgeDestination<T, W>) {
+        if self.exhausted {
+            return;
+        }
+        if let Some(depth) = self.data.get(source).copied() {
+            self.data
+                .entry(node.destination.clone())
+                .or_insert(depth + 1);
+        }
+    }
 }
 
 /// Structure for maintaining backtracking data in a DFS or BFS search
+///
+/// Weights are accumulated in the order the search visits vertices, so paths are only minimal for unweighted graphs searched with BFS.
+/// For weighted graphs use [`WeightedSearch::dijkstra`], which produces this same structure.
 #[derive(Default, Clone, Debug)]
 pub struct BackTracking<T, W>(HashMap<T, (T, W)>);
 
@@ -151,6 +300,63 @@ where
 
         path
     }
+
+    /// Weight of the path found to the target, `None` if the target was never reached
+    pub fn distance(&self, target: &T) -> Option<&W> {
+        self.0.get(target).map(|(_, w)| w)
+    }
+}
+
+/// Search functions on a graph whose edge labels are weights
+pub trait WeightedSearch<T, W> {
+    /// Dijkstra's Algorithm
+    ///
+    /// Vertices are popped in order of their distance from `start`, so the backtracking holds the minimal path to every reachable vertex.
+    /// Weights are expected to be non-negative.
+    ///
+    /// Runtime: O((n + m)log(n))
+    fn dijkstra(&self, start: &T) -> BackTracking<T, W>;
+}
+
+impl<G, T, W> WeightedSearch<T, W> for G
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq + Clone,
+    W: Default + Ord + Add<Output = W> + Copy,
+{
+    fn dijkstra(&self, start: &T) -> BackTracking<T, W> {
+        let mut backtracking = HashMap::new();
+        let mut distances: HashMap<&T, W> = HashMap::new();
+        let mut finished: HashSet<&T> = HashSet::new();
+        let mut queue = PriorityQueue::new();
+
+        distances.insert(start, W::default());
+        queue.push(start, Reverse(W::default()));
+
+        while let Some((current, Reverse(distance))) = queue.pop() {
+            finished.insert(current);
+
+            for neighbor in self.get_neighbors(current).into_iter().flatten() {
+                let destination = &neighbor.destination;
+                if finished.contains(destination) {
+                    continue;
+                }
+
+                let next = distance + neighbor.label;
+                if distances
+                    .get(destination)
+                    .map(|d| next < *d)
+                    .unwrap_or(true)
+                {
+                    distances.insert(destination, next);
+                    backtracking.insert(destination.clone(), (current.clone(), next));
+                    queue.push_increase(destination, Reverse(next));
+                }
+            }
+        }
+
+        BackTracking(backtracking)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -191,6 +397,85 @@ where
     }
 }
 
+/// Structure for detecting a cycle in an undirected graph during a DFS or BFS search
+///
+/// Keeps the tree of discovery of the search, any edge that is not part of this tree closes a cycle with the tree paths of its endpoints.
+#[derive(Clone, Debug)]
+pub struct CycleFinder<T> {
+    parents: HashMap<T, Option<T>>,
+    pub cycle: Option<Vec<T>>,
+}
+
+impl<T> Default for CycleFinder<T> {
+    fn default() -> Self {
+        Self {
+            parents: HashMap::new(),
+            cycle: None,
+        }
+    }
+}
+
+impl<T> CycleFinder<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// Path from a vertex up to the root of its discovery tree
+    fn ancestors(&self, vertex: &T) -> Vec<T> {
+        let mut path = vec![vertex.clone()];
+        while let Some(Some(parent)) = self.parents.get(path.last().unwrap()) {
+            path.push(parent.clone());
+        }
+        path
+    }
+}
+
+impl<T, W> Searcher<T, W> for CycleFinder<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn new_component(&mut self, _node: &T) {}
+    fn visit(&mut self, source: &T, node: &EdgeDestination<T, W>) {
+        if self.cycle.is_some() {
+            return;
+        }
+
+        let destination = &node.destination;
+        if destination == source {
+            self.cycle = Some(vec![source.clone()]);
+            return;
+        }
+
+        self.parents.entry(source.clone()).or_insert(None);
+
+        match self.parents.get(destination) {
+            None => {
+                self.parents
+                    .insert(destination.clone(), Some(source.clone()));
+            }
+            Some(parent) => {
+                let is_tree_edge = parent.as_ref() == Some(source)
+                    || self.parents.get(source) == Some(&Some(destination.clone()));
+                if is_tree_edge {
+                    return;
+                }
+
+                // Both paths end at the root, so cut them at their lowest common ancestor
+                let mut cycle = self.ancestors(source);
+                let mut from_destination = self.ancestors(destination);
+                let mut lowest_common = None;
+                while cycle.last().is_some() && cycle.last() == from_destination.last() {
+                    lowest_common = cycle.pop();
+                    from_destination.pop();
+                }
+                cycle.extend(lowest_common);
+                cycle.reverse();
+                cycle.append(&mut from_destination);
+                self.cycle = Some(cycle);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::{HashMap, HashSet};
@@ -198,6 +483,7 @@ mod test {
     use super::*;
 
     use crate::graph::{EdgeDestination, Graph};
+    use itertools::Itertools;
     #[test]
     fn test_graph() {
         let graph: Graph<u32, u32> = r"2: 1,0
@@ -220,6 +506,46 @@ mod test {
         assert_eq!(expected, backtracking.shortest_path(0));
     }
 
+    fn weighted_graph(edges: Vec<(u32, u32, u32)>) -> Graph<u32, u32> {
+        let mut graph = Graph::default();
+        for (u, v, w) in edges {
+            let mut edge = Edge::init_directed(u, v);
+            edge.update_label(w);
+            graph.add_edge(edge);
+        }
+        graph
+    }
+
+    #[test]
+    fn dijkstra() {
+        let graph = weighted_graph(vec![
+            (0, 1, 10),
+            (0, 2, 1),
+            (2, 3, 1),
+            (3, 1, 1),
+            (1, 4, 1),
+            (2, 4, 20),
+        ]);
+
+        let backtracking = graph.dijkstra(&0);
+
+        assert_eq!(backtracking.shortest_path(1), vec![0, 2, 3, 1]);
+        assert_eq!(backtracking.distance(&1), Some(&3));
+        assert_eq!(backtracking.shortest_path(4), vec![0, 2, 3, 1, 4]);
+        assert_eq!(backtracking.distance(&4), Some(&4));
+        assert_eq!(backtracking.distance(&0), None);
+    }
+
+    #[test]
+    fn dijkstra_unreachable() {
+        let graph = weighted_graph(vec![(0, 1, 1), (2, 3, 1)]);
+
+        let backtracking = graph.dijkstra(&0);
+
+        assert_eq!(backtracking.distance(&3), None);
+        assert_eq!(backtracking.shortest_path(3), vec![3]);
+    }
+
     #[test]
     fn connected_components() {
         let graph: Graph<u32, ()> = r"0: 1
@@ -242,4 +568,93 @@ mod test {
 
         assert_eq!(conn.data[1], expected_subgraph);
     }
+
+    #[test]
+    fn k_hop_neighborhood() {
+        let graph: Graph<u32, ()> = r"0: 1
+        1: 0,2
+        2: 1,3
+        3: 2,4
+        4: 3
+        5: 6
+        6: 5"
+            .parse()
+            .unwrap();
+
+        let neighborhood = graph.k_hop_neighborhood(&1, 2);
+
+        let expected: Graph<u32, ()> = r"0: 1
+        1: 0,2
+        2: 1,3
+        3: 2"
+            .parse()
+            .unwrap();
+
+        assert_eq!(neighborhood.adj_list(), expected.adj_list());
+
+        assert_eq!(graph.k_hop_neighborhood(&5, 0).vertices().len(), 1);
+        assert_eq!(graph.k_hop_neighborhood(&5, 3).adj_list().len(), 2);
+        assert!(graph.k_hop_neighborhood(&7, 2).is_empty());
+    }
+
+    fn assert_is_cycle(graph: &Graph<u32, ()>, cycle: &[u32]) {
+        assert_eq!(cycle.iter().unique().count(), cycle.len());
+        for (u, v) in cycle.iter().zip(cycle.iter().cycle().skip(1)) {
+            assert!(graph
+                .get_neighbors(u)
+                .map(|n| n.contains(&EdgeDestination::init(*v)))
+                .unwrap_or_default());
+        }
+    }
+
+    #[test]
+    fn undirected_cycle() {
+        let mut graph: Graph<u32, ()> = Graph::default();
+        for (u, v) in [(0, 1), (1, 2), (2, 3), (3, 4), (4, 1), (4, 5), (6, 7)] {
+            graph.add_edge(Edge::init(u, v));
+        }
+
+        let cycle = graph.find_cycle(false).unwrap();
+
+        assert_eq!(cycle.len(), 4);
+        assert_is_cycle(&graph, &cycle);
+    }
+
+    #[test]
+    fn undirected_acyclic() {
+        let mut graph: Graph<u32, ()> = Graph::default();
+        for (u, v) in [(0, 1), (1, 2), (1, 3), (3, 4), (5, 6)] {
+            graph.add_edge(Edge::init(u, v));
+        }
+
+        assert_eq!(graph.find_cycle(false), None);
+    }
+
+    #[test]
+    fn directed_cycle() {
+        let graph: Graph<u32, ()> = r"0: 1,2
+        1: 3
+        2: 3
+        3: 4
+        4: 1"
+            .parse()
+            .unwrap();
+
+        let cycle = graph.find_cycle(true).unwrap();
+
+        assert_eq!(cycle.len(), 3);
+        assert_is_cycle(&graph, &cycle);
+    }
+
+    #[test]
+    fn directed_acyclic() {
+        let graph: Graph<u32, ()> = r"0: 1,2
+        1: 3
+        2: 3
+        3: 4"
+            .parse()
+            .unwrap();
+
+        assert_eq!(graph.find_cycle(true), None);
+    }
 }
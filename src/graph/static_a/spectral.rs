@@ -0,0 +1,341 @@
+//! Spectral Sparsification
+//!
+//! A weighted graph `H` is a `(1 ± ε)` spectral sparsifier of `G` when `(1 - ε) xᵀ L_G x <= xᵀ L_H x <= (1 + ε) xᵀ L_G x` for every
+//! vector `x`, `L` being the Laplacian, and `xᵀ L x = Σ w_uv (x_u - x_v)^2`. Taking `x` the indicator of a set of vertices shows
+//! that every cut is preserved as well, so a spectral sparsifier is also a cut sparsifier.
+//!
+//! Following [Spielman and Srivastava](https://doi.org/10.1137/080734029), `q = O(n log n / ε^2)` edges are sampled with
+//! replacement, each with probability `p_e` proportional to `w_e R_e`, `R_e` the effective resistance between its endpoints, and
+//! each sample adds `w_e / (q p_e)` to the weight of its edge in `H`.
+//!
+//! The effective resistances are read off the inverse of `L + P`, `P` the projection onto the vectors constant on every connected
+//! component, which costs `O(n^3)` time and `O(n^2)` space: the graphs handled here fit in memory, and the streaming variant,
+//! [SpectralStreamSparsifier](crate::graph::streaming::sparsifier::SpectralStreamSparsifier), only sparsifies blocks of its stream.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+
+use crate::graph::Graphed;
+
+/// Weight of every edge `(u, v)`, `u < v`
+pub type EdgeWeights<T> = HashMap<(T, T), f64>;
+
+/// Parameters of a spectral sparsifier
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectralParameters {
+    /// Relative error on every quadratic form of the Laplacian
+    pub epsilon: f64,
+    /// The number of samples is `q = sampling_constant · n ln n / ε^2`
+    pub sampling_constant: f64,
+}
+
+impl Default for SpectralParameters {
+    fn default() -> Self {
+        Self {
+            epsilon: 0.5,
+            sampling_constant: 1.0,
+        }
+    }
+}
+
+impl SpectralParameters {
+    pub fn with_epsilon(self, epsilon: f64) -> Self {
+        Self { epsilon, ..self }
+    }
+
+    /// The number of samples `q` over `n` vertices
+    pub fn samples(&self, n: usize) -> usize {
+        let n = n.max(2) as f64;
+        (self.sampling_constant * n * n.ln() / self.epsilon.powi(2)).ceil() as usize
+    }
+}
+
+/// The effective resistance between the endpoints of every edge, the edges being resistors of conductance their weight
+///
+/// Every edge of a tree has resistance `1 / w`, and the resistances sum to `n - c` when weighted, `c` the number of components.
+///
+/// Runtime: O(n^3 + m)
+pub fn effective_resistances<T>(edges: &EdgeWeights<T>) -> EdgeWeights<T>
+where
+    T: Hash + Eq + Clone,
+{
+    let mut index: HashMap<&T, usize> = HashMap::new();
+    for (u, v) in edges.keys() {
+        for w in [u, v] {
+            let next = index.len();
+            index.entry(w).or_insert(next);
+        }
+    }
+    let n = index.len();
+
+    let mut laplacian = vec![vec![0.0; n]; n];
+    let mut neighbors = vec![vec![]; n];
+    for ((u, v), w) in edges.iter() {
+        let (i, j) = (index[u], index[v]);
+        if i == j {
+            continue;
+        }
+        laplacian[i][i] += w;
+        laplacian[j][j] += w;
+        laplacian[i][j] -= w;
+        laplacian[j][i] -= w;
+        neighbors[i].push(j);
+        neighbors[j].push(i);
+    }
+
+    // L + P is invertible, and agrees with the pseudo-inverse of L on the differences of vertices of a component
+    let mut component = vec![usize::MAX; n];
+    let mut members: Vec<Vec<usize>> = vec![];
+    for root in 0..n {
+        if component[root] != usize::MAX {
+            continue;
+        }
+        let mut stack = vec![root];
+        component[root] = members.len();
+        let mut member = vec![];
+        while let Some(i) = stack.pop() {
+            member.push(i);
+            for j in neighbors[i].iter() {
+                if component[*j] == usize::MAX {
+                    component[*j] = members.len();
+                    stack.push(*j);
+                }
+            }
+        }
+        members.push(member);
+    }
+    for member in members.iter() {
+        let share = 1.0 / member.len() as f64;
+        for i in member.iter() {
+            for j in member.iter() {
+                laplacian[*i][*j] += share;
+            }
+        }
+    }
+
+    let inverse = invert(laplacian);
+    edges
+        .keys()
+        .map(|(u, v)| {
+            let (i, j) = (index[u], index[v]);
+            let resistance = inverse[i][i] + inverse[j][j] - inverse[i][j] - inverse[j][i];
+            ((u.clone(), v.clone()), resistance.max(0.0))
+        })
+        .collect()
+}
+
+/// Inverse of a symmetric positive definite matrix, by Gauss-Jordan elimination with partial pivoting
+fn invert(mut matrix: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|a, b| {
+                matrix[*a][column]
+                    .abs()
+                    .partial_cmp(&matrix[*b][column].abs())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap();
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let scale = matrix[column][column];
+        for j in 0..n {
+            matrix[column][j] /= scale;
+            inverse[column][j] /= scale;
+        }
+
+        for row in 0..n {
+            let factor = matrix[row][column];
+            if row == column || factor == 0.0 {
+                continue;
+            }
+            for j in 0..n {
+                matrix[row][j] -= factor * matrix[column][j];
+                inverse[row][j] -= factor * inverse[column][j];
+            }
+        }
+    }
+
+    inverse
+}
+
+/// A `(1 ± ε)` spectral sparsifier of the weighted graph with high probability, drawing the samples from the generator
+///
+/// A graph of fewer edges than the number of samples is already sparse, and is returned as is.
+///
+/// Runtime: O(n^3 + m + q log m)
+pub fn spectral_sparsify<T, R>(
+    edges: &EdgeWeights<T>,
+    parameters: SpectralParameters,
+    rng: &mut R,
+) -> EdgeWeights<T>
+where
+    T: Hash + Eq + Clone,
+    R: Rng + ?Sized,
+{
+    let vertices: HashSet<&T> = edges.keys().flat_map(|(u, v)| [u, v]).collect();
+    let q = parameters.samples(vertices.len());
+    if q >= edges.len() {
+        return edges.clone();
+    }
+
+    let resistances = effective_resistances(edges);
+    let candidates: Vec<(&(T, T), f64)> = edges
+        .iter()
+        .map(|(e, w)| (e, w * resistances[e]))
+        .filter(|(_, importance)| *importance > 0.0)
+        .collect();
+    let total: f64 = candidates.iter().map(|(_, importance)| importance).sum();
+    let distribution = match WeightedIndex::new(candidates.iter().map(|(_, i)| *i)) {
+        Ok(distribution) => distribution,
+        Err(_) => return EdgeWeights::new(),
+    };
+
+    let mut sparsifier = EdgeWeights::new();
+    for _ in 0..q {
+        let (e, importance) = candidates[distribution.sample(rng)];
+        let p = importance / total;
+        *sparsifier.entry(e.clone()).or_default() += edges[e] / (q as f64 * p);
+    }
+    sparsifier
+}
+
+/// The quadratic form `xᵀ L x = Σ w_uv (x_u - x_v)^2` of the Laplacian, vertices missing from `x` taking `0`
+pub fn quadratic_form<T>(edges: &EdgeWeights<T>, x: &HashMap<T, f64>) -> f64
+where
+    T: Hash + Eq,
+{
+    let value = |v: &T| x.get(v).copied().unwrap_or_default();
+    edges
+        .iter()
+        .map(|((u, v), w)| w * (value(u) - value(v)).powi(2))
+        .sum()
+}
+
+/// Spectral sparsification of a graph, every edge of weight `1`
+pub trait SpectralSparsify<T, W> {
+    /// The edges of the graph, of weight `1`, the keys ordered by their endpoints
+    fn unit_weights(&self) -> EdgeWeights<T>;
+
+    /// A `(1 ± ε)` spectral sparsifier of the graph with high probability, see [spectral_sparsify]
+    fn spectral_sparsifier(&self, parameters: SpectralParameters) -> EdgeWeights<T>;
+
+    /// [spectral_sparsifier](SpectralSparsify::spectral_sparsifier), drawing the samples from the generator
+    fn spectral_sparsifier_with_rng<R: Rng + ?Sized>(
+        &self,
+        parameters: SpectralParameters,
+        rng: &mut R,
+    ) -> EdgeWeights<T>;
+}
+
+impl<G, T, W> SpectralSparsify<T, W> for G
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq + Clone + Ord,
+{
+    fn unit_weights(&self) -> EdgeWeights<T> {
+        self.adj_list()
+            .iter()
+            .flat_map(|(u, neighbors)| {
+                neighbors
+                    .iter()
+                    .filter(move |n| *u < n.destination)
+                    .map(move |n| ((u.clone(), n.destination.clone()), 1.0))
+            })
+            .collect()
+    }
+
+    fn spectral_sparsifier(&self, parameters: SpectralParameters) -> EdgeWeights<T> {
+        self.spectral_sparsifier_with_rng(parameters, &mut rand::thread_rng())
+    }
+
+    fn spectral_sparsifier_with_rng<R: Rng + ?Sized>(
+        &self,
+        parameters: SpectralParameters,
+        rng: &mut R,
+    ) -> EdgeWeights<T> {
+        spectral_sparsify(&self.unit_weights(), parameters, rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::graph::{Edge, Graph, VertexId};
+
+    fn complete(n: VertexId) -> Graph<VertexId, ()> {
+        let mut graph = Graph::default();
+        for u in 0..n {
+            for v in u + 1..n {
+                graph.add_edge(Edge::init(u, v));
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn resistances() {
+        // A 4-cycle 0..4, and a path 4 - 5 - 6
+        let edges: EdgeWeights<VertexId> = [(0, 1), (1, 2), (2, 3), (0, 3), (4, 5), (5, 6)]
+            .iter()
+            .map(|e| (*e, 1.0))
+            .collect();
+        let resistances = effective_resistances(&edges);
+
+        assert!((resistances[&(0, 1)] - 0.75).abs() < 1e-9);
+        assert!((resistances[&(4, 5)] - 1.0).abs() < 1e-9);
+        // n - c = 7 - 2
+        assert!((resistances.values().sum::<f64>() - 5.0).abs() < 1e-9);
+
+        let doubled: EdgeWeights<VertexId> = edges.keys().map(|e| (*e, 2.0)).collect();
+        assert!((effective_resistances(&doubled)[&(5, 6)] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sparse_graphs_kept() {
+        let graph = complete(5);
+        let sparsifier = graph.spectral_sparsifier(SpectralParameters::default());
+        assert_eq!(sparsifier, graph.unit_weights());
+    }
+
+    #[test]
+    fn quadratic_forms() {
+        let graph = complete(40);
+        let edges = graph.unit_weights();
+        let parameters = SpectralParameters::default();
+        assert!(parameters.samples(40) < edges.len());
+
+        let mut rng = ChaCha20Rng::seed_from_u64(11);
+        let sparsifier = spectral_sparsify(&edges, parameters, &mut rng);
+        assert!(sparsifier.len() < edges.len());
+
+        for _ in 0..20 {
+            let x: HashMap<VertexId, f64> =
+                (0..40).map(|v| (v, rng.gen_range(-1.0..1.0))).collect();
+            let (exact, sparse) = (quadratic_form(&edges, &x), quadratic_form(&sparsifier, &x));
+            assert!(
+                (sparse - exact).abs() <= parameters.epsilon * exact,
+                "{} instead of {}",
+                sparse,
+                exact
+            );
+        }
+
+        // The cut of a single vertex, of 39 edges
+        let side: HashMap<VertexId, f64> = [(0, 1.0)].into();
+        assert!((quadratic_form(&sparsifier, &side) - 39.0).abs() <= 0.5 * 39.0);
+    }
+}
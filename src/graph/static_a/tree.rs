@@ -0,0 +1,382 @@
+//! Tree Algorithms
+//!
+//! Utilities for graphs that are trees or forests: rooting, lowest common ancestors by
+//! [binary lifting](https://cp-algorithms.com/graph/lca_binary_lifting.html), subtree sizes and diameters.
+//!
+//! The graph is assumed to be undirected, i.e. every edge is present in the adjacency lists of both endpoints.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    error::Error,
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::graph::Graphed;
+
+/// Reasons for which a graph cannot be rooted into a tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeError {
+    /// The root is not a vertex of the graph
+    MissingRoot,
+    /// The component of the root contains a cycle
+    Cycle,
+}
+
+impl Display for TreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingRoot => write!(f, "the root is not a vertex of the graph"),
+            Self::Cycle => write!(f, "the component of the root contains a cycle"),
+        }
+    }
+}
+
+impl Error for TreeError {}
+
+/// A tree spanning the component of its root
+///
+/// Vertices are relabeled to `0..n` in breadth first order from the root, so the root is `0` and every parent has a smaller label than its children.
+///
+/// Storage: O(n log n)
+#[derive(Clone, Debug)]
+pub struct RootedTree<T> {
+    vertices: Vec<T>,
+    index: HashMap<T, usize>,
+    depth: Vec<usize>,
+    /// `up[k][v]` is the `2^k`-th ancestor of `v`, or the root if `v` is not that deep
+    up: Vec<Vec<usize>>,
+    children: Vec<Vec<usize>>,
+    subtree_size: Vec<usize>,
+}
+
+impl<T> RootedTree<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Root the component of `root` within the graph
+    ///
+    /// Runtime: O(n log n)
+    pub fn from_graph<G, W>(graph: &G, root: T) -> Result<Self, TreeError>
+    where
+        G: Graphed<T, W>,
+    {
+        if graph.get_neighbors(&root).is_none() {
+            return Err(TreeError::MissingRoot);
+        }
+
+        let mut vertices = vec![root.clone()];
+        let mut index = HashMap::new();
+        index.insert(root, 0);
+        let mut parent = vec![0];
+        let mut depth = vec![0];
+        let mut children = vec![vec![]];
+
+        let mut queue = VecDeque::new();
+        queue.push_back(0);
+
+        while let Some(v) = queue.pop_front() {
+            let neighbors = graph
+                .get_neighbors(&vertices[v])
+                .into_iter()
+                .flatten()
+                .map(|n| n.destination.clone())
+                .collect::<HashSet<T>>();
+
+            for neighbor in neighbors {
+                match index.get(&neighbor) {
+                    Some(u) if v != 0 && *u == parent[v] => {}
+                    Some(_) => return Err(TreeError::Cycle),
+                    None => {
+                        let u = vertices.len();
+                        index.insert(neighbor.clone(), u);
+                        vertices.push(neighbor);
+                        parent.push(v);
+                        depth.push(depth[v] + 1);
+                        children.push(vec![]);
+                        children[v].push(u);
+                        queue.push_back(u);
+                    }
+                }
+            }
+        }
+
+        // Children have larger labels than their parents, so a reverse pass visits every subtree before its root
+        let mut subtree_size = vec![1; vertices.len()];
+        for v in (1..vertices.len()).rev() {
+            subtree_size[parent[v]] += subtree_size[v];
+        }
+
+        let mut up = vec![parent];
+        while 1 << up.len() < vertices.len() {
+            let last = up.last().unwrap();
+            let next = last.iter().map(|a| last[*a]).collect();
+            up.push(next);
+        }
+
+        Ok(Self {
+            vertices,
+            index,
+            depth,
+            up,
+            children,
+            subtree_size,
+        })
+    }
+
+    /// Root every component of the graph, at an arbitrary vertex
+    ///
+    /// Runtime: O(n log n)
+    pub fn forest<G, W>(graph: &G) -> Result<Vec<Self>, TreeError>
+    where
+        G: Graphed<T, W>,
+    {
+        let mut seen = HashSet::new();
+        let mut forest = vec![];
+
+        for vertex in graph.adj_list().keys() {
+            if seen.contains(vertex) {
+                continue;
+            }
+            let tree = Self::from_graph(graph, vertex.clone())?;
+            seen.extend(tree.vertices.iter().cloned());
+            forest.push(tree);
+        }
+
+        Ok(forest)
+    }
+
+    /// The root of the tree
+    pub fn root(&self) -> &T {
+        &self.vertices[0]
+    }
+
+    /// Number of vertices
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Whether the tree has no vertices, never true as a tree contains at least its root
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Whether the vertex belongs to the tree
+    pub fn contains(&self, vertex: &T) -> bool {
+        self.index.contains_key(vertex)
+    }
+
+    /// The parent of a vertex, `None` for the root or if it is not in the tree
+    pub fn parent(&self, vertex: &T) -> Option<&T> {
+        match self.index.get(vertex) {
+            Some(0) | None => None,
+            Some(v) => Some(&self.vertices[self.up[0][*v]]),
+        }
+    }
+
+    /// The children of a vertex, `None` if it is not in the tree
+    pub fn children(&self, vertex: &T) -> Option<Vec<&T>> {
+        self.index.get(vertex).map(|v| {
+            self.children[*v]
+                .iter()
+                .map(|c| &self.vertices[*c])
+                .collect()
+        })
+    }
+
+    /// Distance from the root to a vertex, `None` if it is not in the tree
+    pub fn depth(&self, vertex: &T) -> Option<usize> {
+        self.index.get(vertex).map(|v| self.depth[*v])
+    }
+
+    /// Number of vertices in the subtree of a vertex, including itself, `None` if it is not in the tree
+    pub fn subtree_size(&self, vertex: &T) -> Option<usize> {
+        self.index.get(vertex).map(|v| self.subtree_size[*v])
+    }
+
+    /// Number of vertices in the subtree of every vertex
+    pub fn subtree_sizes(&self) -> HashMap<T, usize> {
+        self.vertices
+            .iter()
+            .cloned()
+            .zip(self.subtree_size.iter().copied())
+            .collect()
+    }
+
+    /// Lowest common ancestor of two vertices, `None` if either is not in the tree
+    ///
+    /// Runtime: O(log n)
+    pub fn lca(&self, u: &T, v: &T) -> Option<&T> {
+        let (u, v) = (*self.index.get(u)?, *self.index.get(v)?);
+        Some(&self.vertices[self.lca_of(u, v)])
+    }
+
+    /// Number of edges on the path between two vertices, `None` if either is not in the tree
+    ///
+    /// Runtime: O(log n)
+    pub fn distance(&self, u: &T, v: &T) -> Option<usize> {
+        let (u, v) = (*self.index.get(u)?, *self.index.get(v)?);
+        Some(self.distance_of(u, v))
+    }
+
+    /// The vertices on the path from `u` to `v`, both included, `None` if either is not in the tree
+    ///
+    /// Runtime: O(length of the path + log n)
+    pub fn path(&self, u: &T, v: &T) -> Option<Vec<T>> {
+        let (u, v) = (*self.index.get(u)?, *self.index.get(v)?);
+        let lca = self.lca_of(u, v);
+
+        let climb = |mut w: usize| {
+            let mut path = vec![];
+            while w != lca {
+                path.push(self.vertices[w].clone());
+                w = self.up[0][w];
+            }
+            path
+        };
+
+        let mut path = climb(u);
+        path.push(self.vertices[lca].clone());
+        path.extend(climb(v).into_iter().rev());
+
+        Some(path)
+    }
+
+    /// Endpoints and length of a longest path within the tree
+    ///
+    /// The endpoint of a longest path from any vertex is an endpoint of a diameter, so we search twice.
+    ///
+    /// Runtime: O(n log n)
+    pub fn diameter(&self) -> (&T, &T, usize) {
+        // The deepest vertex is the furthest from the root
+        let a = (0..self.len()).max_by_key(|v| self.depth[*v]).unwrap_or(0);
+        let (b, length) = (0..self.len())
+            .map(|v| (v, self.distance_of(a, v)))
+            .max_by_key(|(_, d)| *d)
+            .unwrap_or((a, 0));
+
+        (&self.vertices[a], &self.vertices[b], length)
+    }
+
+    fn lca_of(&self, u: usize, v: usize) -> usize {
+        let (mut u, mut v) = if self.depth[u] < self.depth[v] {
+            (v, u)
+        } else {
+            (u, v)
+        };
+
+        let diff = self.depth[u] - self.depth[v];
+        for (k, up) in self.up.iter().enumerate() {
+            if diff & (1 << k) != 0 {
+                u = up[u];
+            }
+        }
+        if u == v {
+            return u;
+        }
+
+        for up in self.up.iter().rev() {
+            if up[u] != up[v] {
+                u = up[u];
+                v = up[v];
+            }
+        }
+        self.up[0][u]
+    }
+
+    fn distance_of(&self, u: usize, v: usize) -> usize {
+        self.depth[u] + self.depth[v] - 2 * self.depth[self.lca_of(u, v)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{Edge, Graph};
+
+    /// ```text
+    ///       0
+    ///     /   \
+    ///    1     2
+    ///   / \     \
+    ///  3   4     5
+    ///  |
+    ///  6
+    /// ```
+    fn test_tree() -> Graph<u32, ()> {
+        let mut graph = Graph::default();
+        for (u, v) in [(0, 1), (0, 2), (1, 3), (1, 4), (2, 5), (3, 6)] {
+            graph.add_edge(Edge::init(u, v));
+        }
+        graph
+    }
+
+    #[test]
+    fn rooted_tree() {
+        let tree = RootedTree::from_graph(&test_tree(), 0).unwrap();
+
+        assert_eq!(tree.len(), 7);
+        assert_eq!(tree.root(), &0);
+        assert_eq!(tree.parent(&6), Some(&3));
+        assert_eq!(tree.parent(&0), None);
+        assert_eq!(tree.depth(&6), Some(3));
+        assert_eq!(tree.subtree_size(&1), Some(4));
+        assert_eq!(tree.subtree_sizes().get(&0), Some(&7));
+    }
+
+    #[test]
+    fn lca() {
+        let tree = RootedTree::from_graph(&test_tree(), 0).unwrap();
+
+        assert_eq!(tree.lca(&6, &4), Some(&1));
+        assert_eq!(tree.lca(&6, &5), Some(&0));
+        assert_eq!(tree.lca(&3, &6), Some(&3));
+        assert_eq!(tree.lca(&6, &42), None);
+
+        assert_eq!(tree.distance(&6, &5), Some(5));
+        assert_eq!(tree.path(&6, &5), Some(vec![6, 3, 1, 0, 2, 5]));
+    }
+
+    #[test]
+    fn diameter() {
+        let tree = RootedTree::from_graph(&test_tree(), 4).unwrap();
+
+        let (a, b, length) = tree.diameter();
+
+        assert_eq!(length, 5);
+        assert_eq!(
+            vec![*a, *b].into_iter().collect::<HashSet<_>>(),
+            vec![5, 6].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn not_a_tree() {
+        let mut graph = test_tree();
+        graph.add_edge(Edge::init(4, 5));
+
+        assert_eq!(
+            RootedTree::from_graph(&graph, 0).unwrap_err(),
+            TreeError::Cycle
+        );
+        assert_eq!(
+            RootedTree::from_graph(&graph, 42).unwrap_err(),
+            TreeError::MissingRoot
+        );
+    }
+
+    #[test]
+    fn forest() {
+        let mut graph = test_tree();
+        graph.add_edge(Edge::init(10, 11));
+
+        let mut forest = RootedTree::forest(&graph).unwrap();
+        forest.sort_by_key(|tree| tree.len());
+
+        assert_eq!(
+            forest.iter().map(|tree| tree.len()).collect::<Vec<_>>(),
+            vec![2, 7]
+        );
+        assert!(forest[0].contains(&10) && forest[0].contains(&11));
+    }
+}
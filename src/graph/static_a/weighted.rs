@@ -0,0 +1,161 @@
+//! Vertex Weighted Greedy Algorithms
+//!
+//! Weights are given as a map from vertices to `f64`, vertices missing from the map have weight `0`.
+//!
+//! The independent set is the GWMIN heuristic of [Sakai, Togasaki and Yamazaki](https://doi.org/10.1016/S0166-218X(02)00205-6),
+//! which repeatedly takes the vertex maximizing `w(v) / (d(v) + 1)` in the remaining graph, and guarantees a weight of at least
+//! `sum w(v) / (d(v) + 1)`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use super::coloring::Coloring;
+use crate::graph::Graphed;
+
+/// Greedy algorithms taking vertex weights into account
+pub trait WeightedGreedy<T, W> {
+    /// An independent set of large total weight, by the GWMIN heuristic
+    ///
+    /// Runtime: O(n^2 + m)
+    fn max_weight_independent_set_greedy(&self, weights: &HashMap<T, f64>) -> HashSet<T>;
+
+    /// The vertices by decreasing weight, ties broken by decreasing degree
+    fn weight_ordering(&self, weights: &HashMap<T, f64>) -> Vec<T>;
+
+    /// Greedy coloring following the [weight_ordering](WeightedGreedy::weight_ordering), so that the heaviest vertices get the smallest colors
+    fn greedy_weighted(&self, weights: &HashMap<T, f64>) -> Coloring<T>;
+}
+
+impl<G, T, W> WeightedGreedy<T, W> for G
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq + Clone,
+{
+    fn max_weight_independent_set_greedy(&self, weights: &HashMap<T, f64>) -> HashSet<T> {
+        let weight = |v: &T| weights.get(v).copied().unwrap_or_default();
+
+        let mut remaining: HashMap<T, HashSet<T>> = self
+            .adj_list()
+            .iter()
+            .map(|(v, neighbors)| {
+                let neighbors = neighbors
+                    .iter()
+                    .map(|n| n.destination.clone())
+                    .filter(|n| n != v)
+                    .collect();
+                (v.clone(), neighbors)
+            })
+            .collect();
+
+        let mut independent = HashSet::new();
+        while let Some(v) = remaining
+            .iter()
+            .map(|(v, neighbors)| (v, weight(v) / (neighbors.len() + 1) as f64))
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(v, _)| v.clone())
+        {
+            let neighbors = remaining.remove(&v).unwrap_or_default();
+            for u in neighbors {
+                for w in remaining.remove(&u).unwrap_or_default() {
+                    if let Some(n) = remaining.get_mut(&w) {
+                        n.remove(&u);
+                    }
+                }
+            }
+            independent.insert(v);
+        }
+
+        independent
+    }
+
+    fn weight_ordering(&self, weights: &HashMap<T, f64>) -> Vec<T> {
+        let weight = |v: &T| weights.get(v).copied().unwrap_or_default();
+
+        let mut ordering: Vec<(T, f64, usize)> = self
+            .adj_list()
+            .iter()
+            .map(|(v, neighbors)| (v.clone(), weight(v), neighbors.len()))
+            .collect();
+        ordering.sort_by(|(_, w1, d1), (_, w2, d2)| {
+            w2.partial_cmp(w1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(d2.cmp(d1))
+        });
+
+        ordering.into_iter().map(|(v, _, _)| v).collect()
+    }
+
+    fn greedy_weighted(&self, weights: &HashMap<T, f64>) -> Coloring<T> {
+        let mut coloring = Coloring::new();
+
+        for v in self.weight_ordering(weights) {
+            let neighbor_colors: HashSet<usize> = self
+                .get_neighbors(&v)
+                .map(|neighbors| {
+                    neighbors
+                        .iter()
+                        .filter_map(|n| coloring.get(&n.destination))
+                        .copied()
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let color = (0..).find(|c| !neighbor_colors.contains(c)).unwrap();
+            coloring.insert(v, color);
+        }
+
+        coloring
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{static_a::coloring::Colorer, Graph};
+
+    fn star() -> Graph<u32, ()> {
+        r"0: 1,2,3,4
+        1: 0
+        2: 0
+        3: 0
+        4: 0"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn independent_set() {
+        let graph = star();
+
+        // The leaves outweigh the center
+        let light: HashMap<u32, f64> = (0..5).map(|v| (v, 1.0)).collect();
+        assert_eq!(
+            graph.max_weight_independent_set_greedy(&light),
+            (1..5).collect()
+        );
+
+        // Unless the center is heavy enough
+        let mut heavy = light;
+        heavy.insert(0, 10.0);
+        assert_eq!(
+            graph.max_weight_independent_set_greedy(&heavy),
+            vec![0].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn coloring() {
+        let graph = star();
+        let weights: HashMap<u32, f64> = vec![(3, 5.0), (0, 1.0)].into_iter().collect();
+
+        assert_eq!(graph.weight_ordering(&weights)[..2], [3, 0]);
+
+        let coloring = graph.greedy_weighted(&weights);
+        assert!(graph.is_proper(&coloring));
+        assert_eq!(coloring.get(&3), Some(&0));
+        assert_eq!(coloring.get(&0), Some(&1));
+        assert_eq!(coloring.num_classes(), 2);
+    }
+}
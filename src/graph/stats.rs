@@ -0,0 +1,274 @@
+//! Degree Statistics
+//!
+//! Useful for analyzing the bias of vertex sampled sketches: a vertex sampled through one of its edges is more likely to have a high degree
+//! than a vertex sampled uniformly, this is the [friendship paradox](https://en.wikipedia.org/wiki/Friendship_paradox).
+//!
+//! Degrees are the sizes of the adjacency lists, so for directed graphs these are out-degrees.
+
+use std::{collections::HashMap, hash::Hash};
+
+use super::Graphed;
+
+/// Compressed Sparse Row snapshot of a graph's adjacency
+///
+/// Vertices are relabeled to `0..n`, and the neighbors of vertex `i` are `targets[offsets[i]..offsets[i + 1]]`.
+/// This makes a pass over every edge a pass over one contiguous array.
+#[derive(Clone, Debug)]
+pub struct Csr<T> {
+    vertices: Vec<T>,
+    index: HashMap<T, usize>,
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+}
+
+/// Graph level degree statistics
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DegreeSummary {
+    pub vertices: usize,
+    /// Number of adjacency entries, i.e. twice the number of edges for an undirected graph
+    pub adjacencies: usize,
+    pub max_degree: usize,
+    /// Mean degree of a uniformly random vertex
+    pub average_degree: f64,
+    /// Mean, over the vertices with at least one neighbor, of the average degree of their neighbors
+    pub average_neighbor_degree: f64,
+    /// Fraction of the vertices with at least one neighbor, whose neighbors have on average a higher degree than themselves
+    pub friendship_paradox: f64,
+}
+
+impl<T> Csr<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Build the snapshot of a graph
+    ///
+    /// Runtime: O(n + m)
+    pub fn from_graph<G, W>(graph: &G) -> Self
+    where
+        G: Graphed<T, W>,
+    {
+        let vertices: Vec<T> = graph.adj_list().keys().cloned().collect();
+        let mut index: HashMap<T, usize> = vertices
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.clone(), i))
+            .collect();
+        let mut vertices = vertices;
+
+        let mut offsets = Vec::with_capacity(vertices.len() + 1);
+        let mut targets = vec![];
+        offsets.push(0);
+
+        for i in 0..vertices.len() {
+            if let Some(neighbors) = graph.get_neighbors(&vertices[i]) {
+                for neighbor in neighbors {
+                    // Destinations of directed edges may not have an adjacency list of their own
+                    let target = match index.get(&neighbor.destination) {
+                        Some(target) => *target,
+                        None => {
+                            index.insert(neighbor.destination.clone(), vertices.len());
+                            vertices.push(neighbor.destination.clone());
+                            vertices.len() - 1
+                        }
+                    };
+                    targets.push(target);
+                }
+            }
+            offsets.push(targets.len());
+        }
+        offsets.resize(vertices.len() + 1, targets.len());
+
+        Self {
+            vertices,
+            index,
+            offsets,
+            targets,
+        }
+    }
+
+    /// Number of vertices
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Whether the graph has no vertices
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// The vertex relabeled as `i`
+    pub fn vertex(&self, i: usize) -> &T {
+        &self.vertices[i]
+    }
+
+    /// The label of a vertex, `None` if it is not in the graph
+    pub fn index(&self, vertex: &T) -> Option<usize> {
+        self.index.get(vertex).copied()
+    }
+
+    /// The labels of the neighbors of the vertex labeled `i`
+    pub fn neighbors(&self, i: usize) -> &[usize] {
+        &self.targets[self.offsets[i]..self.offsets[i + 1]]
+    }
+
+    /// The degree of a vertex, `None` if it is not in the graph
+    pub fn degree(&self, vertex: &T) -> Option<usize> {
+        self.index.get(vertex).map(|i| self.degree_of(*i))
+    }
+
+    /// Average degree of the neighbors of a vertex, `None` if it is not in the graph or has no neighbors
+    pub fn average_neighbor_degree(&self, vertex: &T) -> Option<f64> {
+        self.index
+            .get(vertex)
+            .and_then(|i| self.average_neighbor_degree_of(*i))
+    }
+
+    /// Graph level degree statistics, computed in a single pass over the edges
+    pub fn summary(&self) -> DegreeSummary {
+        let n = self.len();
+        if n == 0 {
+            return DegreeSummary::default();
+        }
+
+        let (mut max_degree, mut with_neighbors, mut neighbor_degree_total, mut paradox) =
+            (0, 0, 0.0, 0);
+
+        for i in 0..n {
+            let degree = self.degree_of(i);
+            max_degree = max_degree.max(degree);
+
+            if let Some(average) = self.average_neighbor_degree_of(i) {
+                with_neighbors += 1;
+                neighbor_degree_total += average;
+                if average > degree as f64 {
+                    paradox += 1;
+                }
+            }
+        }
+
+        DegreeSummary {
+            vertices: n,
+            adjacencies: self.targets.len(),
+            max_degree,
+            average_degree: self.targets.len() as f64 / n as f64,
+            average_neighbor_degree: if with_neighbors > 0 {
+                neighbor_degree_total / with_neighbors as f64
+            } else {
+                0.0
+            },
+            friendship_paradox: if with_neighbors > 0 {
+                paradox as f64 / with_neighbors as f64
+            } else {
+                0.0
+            },
+        }
+    }
+
+    fn degree_of(&self, i: usize) -> usize {
+        self.offsets[i + 1] - self.offsets[i]
+    }
+
+    fn average_neighbor_degree_of(&self, i: usize) -> Option<f64> {
+        let neighbors = self.neighbors(i);
+        if neighbors.is_empty() {
+            return None;
+        }
+
+        let total: usize = neighbors.iter().map(|j| self.degree_of(*j)).sum();
+        Some(total as f64 / neighbors.len() as f64)
+    }
+}
+
+/// Degree statistics on a graph
+pub trait Stats<T, W> {
+    /// Average degree of the neighbors of a vertex, `None` if it is not in the graph or has no neighbors
+    ///
+    /// Runtime: O(deg(v))
+    fn average_neighbor_degree(&self, vertex: &T) -> Option<f64>;
+    /// Graph level degree statistics
+    ///
+    /// Runtime: O(n + m)
+    fn degree_summary(&self) -> DegreeSummary;
+}
+
+impl<G, T, W> Stats<T, W> for G
+where
+    G: Graphed<T, W>,
+    T: Hash + Eq + Clone,
+{
+    fn average_neighbor_degree(&self, vertex: &T) -> Option<f64> {
+        let neighbors = self.get_neighbors(vertex)?;
+        if neighbors.is_empty() {
+            return None;
+        }
+
+        let total: usize = neighbors
+            .iter()
+            .map(|n| {
+                self.get_neighbors(&n.destination)
+                    .map(|d| d.len())
+                    .unwrap_or_default()
+            })
+            .sum();
+        Some(total as f64 / neighbors.len() as f64)
+    }
+
+    fn degree_summary(&self) -> DegreeSummary {
+        Csr::from_graph(self).summary()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{Edge, Graph};
+
+    /// A star centered at 0 with leaves 1 through 4
+    fn star() -> Graph<u32, ()> {
+        let mut graph = Graph::default();
+        for leaf in 1..5 {
+            graph.add_edge(Edge::init(0, leaf));
+        }
+        graph
+    }
+
+    #[test]
+    fn average_neighbor_degree() {
+        let graph = star();
+
+        assert_eq!(graph.average_neighbor_degree(&0), Some(1.0));
+        assert_eq!(graph.average_neighbor_degree(&3), Some(4.0));
+        assert_eq!(graph.average_neighbor_degree(&7), None);
+
+        let csr = Csr::from_graph(&graph);
+        assert_eq!(csr.average_neighbor_degree(&0), Some(1.0));
+        assert_eq!(csr.average_neighbor_degree(&3), Some(4.0));
+        assert_eq!(csr.degree(&0), Some(4));
+    }
+
+    #[test]
+    fn summary() {
+        let summary = star().degree_summary();
+
+        assert_eq!(summary.vertices, 5);
+        assert_eq!(summary.adjacencies, 8);
+        assert_eq!(summary.max_degree, 4);
+        assert!((summary.average_degree - 1.6).abs() < 1e-9);
+        assert!((summary.average_neighbor_degree - 3.4).abs() < 1e-9);
+        assert!((summary.friendship_paradox - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn directed_destinations() {
+        let graph: Graph<u32, ()> = r"0: 1,2
+        1: 2"
+            .parse()
+            .unwrap();
+
+        let csr = Csr::from_graph(&graph);
+
+        assert_eq!(csr.len(), 3);
+        assert_eq!(csr.degree(&2), Some(0));
+        assert_eq!(csr.average_neighbor_degree(&0), Some(0.5));
+    }
+}
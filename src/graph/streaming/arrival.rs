@@ -0,0 +1,376 @@
+//! Vertex Arrival Streams
+//!
+//! In the vertex-arrival (adjacency list) model, every token of the stream is a vertex along with its full list of neighbors, so
+//! that each edge is seen twice, once from each of its endpoints. Seeing a whole neighborhood at once makes some problems much
+//! easier than in the edge-arrival model: the first fit greedy coloring, which colors a vertex with the smallest color none of its
+//! neighbors has, is a proper `Δ + 1` coloring in O(n log Δ) bits, and the greedy matching is maximal in O(n log n) bits.
+//!
+//! [vertex_arrivals] turns an insertion-only stream of edges into vertex arrivals, buffering the graph, and [edge_arrivals] turns
+//! vertex arrivals back into a stream of edges, for the structures of the edge-arrival model.
+
+use std::{collections::BTreeMap, fmt::Debug, mem::size_of};
+
+use super::{
+    provenance::{AlgorithmInfo, Provenance},
+    space::{log, SpaceReport, SpaceUsage},
+    StreamProcessor,
+};
+use crate::graph::{static_a::coloring::Coloring, Edge, VertexId};
+
+/// A token of a vertex-arrival stream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VertexArrival {
+    pub vertex: VertexId,
+    /// Every neighbor of the vertex, whether it already arrived or not
+    pub neighbors: Vec<VertexId>,
+}
+
+/// The vertices of an insertion-only stream of edges, with their neighbors, in increasing order
+///
+/// Storage: O(m log n) bits, every edge is buffered until the stream ends. Self loops are dropped.
+pub fn vertex_arrivals<W, I>(edges: I) -> Vec<VertexArrival>
+where
+    W: Debug + Default,
+    I: IntoIterator<Item = Edge<VertexId, W>>,
+{
+    let mut lists: BTreeMap<VertexId, Vec<VertexId>> = BTreeMap::new();
+    for edge in edges {
+        let (u, v) = edge.vertices();
+        if u != v {
+            lists.entry(*u).or_default().push(*v);
+            lists.entry(*v).or_default().push(*u);
+        }
+    }
+
+    lists
+        .into_iter()
+        .map(|(vertex, mut neighbors)| {
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            VertexArrival { vertex, neighbors }
+        })
+        .collect()
+}
+
+/// The edges of a vertex-arrival stream, as insertions, each once
+///
+/// An edge is listed by both of its endpoints, and is only yielded from the list of its smaller endpoint.
+pub fn edge_arrivals<I>(arrivals: I) -> impl Iterator<Item = (Edge<VertexId, ()>, bool)>
+where
+    I: IntoIterator<Item = VertexArrival>,
+{
+    arrivals
+        .into_iter()
+        .flat_map(|VertexArrival { vertex, neighbors }| {
+            neighbors
+                .into_iter()
+                .filter(move |neighbor| vertex < *neighbor)
+                .map(move |neighbor| (Edge::init(vertex, neighbor), true))
+        })
+}
+
+/// Colors each vertex, as it arrives, with the smallest color that none of its neighbors has
+///
+/// Storage: O(n log Δ) bits, the color of every vertex
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct ArrivalColoring {
+    /// Color of every vertex of `[n]` that arrived
+    colors: Vec<Option<usize>>,
+}
+
+impl ArrivalColoring {
+    /// Initialize a colorer of the vertices `[n]`
+    pub fn init(n: VertexId) -> Self {
+        Self {
+            colors: vec![None; n as usize],
+        }
+    }
+
+    /// Color of a vertex, if it arrived
+    pub fn color(&self, vertex: VertexId) -> Option<usize> {
+        self.colors.get(vertex as usize).copied().flatten()
+    }
+
+    /// Number of colors used so far, at most `Δ + 1`
+    pub fn num_colors(&self) -> usize {
+        self.colors.iter().flatten().max().map_or(0, |c| c + 1)
+    }
+
+    /// Color the vertex that arrived, from the colors of its neighbors
+    ///
+    /// Panics if the vertex is not in `[n]`, or arrived already.
+    pub fn feed(&mut self, arrival: VertexArrival) {
+        let VertexArrival { vertex, neighbors } = arrival;
+        match self.colors.get(vertex as usize) {
+            None => panic!(
+                "[ArrivalColoring] {} is not a vertex of [{}]",
+                vertex,
+                self.colors.len()
+            ),
+            Some(Some(_)) => panic!("[ArrivalColoring] {} arrived twice", vertex),
+            Some(None) => {}
+        }
+
+        // One of the first deg(v) + 1 colors is free
+        let mut taken = vec![false; neighbors.len() + 1];
+        for neighbor in neighbors {
+            if let Some(color) = self.color(neighbor) {
+                if color < taken.len() {
+                    taken[color] = true;
+                }
+            }
+        }
+        let color = taken.iter().position(|taken| !taken).unwrap();
+
+        self.colors[vertex as usize] = Some(color);
+    }
+
+    /// The coloring of the vertices that arrived
+    pub fn query(self) -> Coloring<VertexId> {
+        self.colors
+            .into_iter()
+            .enumerate()
+            .filter_map(|(v, color)| color.map(|color| (v as VertexId, color)))
+            .collect()
+    }
+}
+
+impl StreamProcessor<VertexArrival, Coloring<VertexId>> for ArrivalColoring {
+    fn feed(&mut self, token: VertexArrival) {
+        ArrivalColoring::feed(self, token)
+    }
+
+    fn query(self) -> Coloring<VertexId> {
+        ArrivalColoring::query(self)
+    }
+}
+
+impl SpaceUsage for ArrivalColoring {
+    /// One color per vertex
+    fn space(&self) -> SpaceReport {
+        let n = self.colors.len() as f64;
+
+        SpaceReport {
+            structure: "ArrivalColoring",
+            bound: "O(n log Δ)",
+            parameters: vec![("n", n), ("colors", self.num_colors() as f64)],
+            theoretical_bits: n * log(self.num_colors() as f64 + 1.0),
+            measured_bytes: size_of::<Self>() + self.colors.capacity() * size_of::<Option<usize>>(),
+            components: vec![],
+        }
+    }
+}
+
+impl Provenance for ArrivalColoring {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "ArrivalColoring",
+            paper_ref: "First fit greedy coloring, in the order of arrival of the vertices",
+            params: vec![("n", self.colors.len() as f64)],
+            seed: None,
+        }
+    }
+}
+
+/// Matches each vertex, as it arrives, to its first unmatched neighbor, if it is unmatched itself
+///
+/// Once a vertex arrived, it or all of its neighbors are matched, so the matching is maximal, and at least half the size of a
+/// maximum matching.
+///
+/// Storage: O(n log n) bits, the mate of every vertex
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct ArrivalMatching {
+    /// Mate of every vertex of `[n]`
+    mates: Vec<Option<VertexId>>,
+}
+
+impl ArrivalMatching {
+    /// Initialize a matching of the vertices `[n]`
+    pub fn init(n: VertexId) -> Self {
+        Self {
+            mates: vec![None; n as usize],
+        }
+    }
+
+    pub fn mate(&self, vertex: VertexId) -> Option<VertexId> {
+        self.mates.get(vertex as usize).copied().flatten()
+    }
+
+    /// Number of edges matched so far
+    pub fn len(&self) -> usize {
+        self.mates.iter().flatten().count() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Match the vertex that arrived, unless it already is
+    ///
+    /// Panics if the vertex, or one of its neighbors, is not in `[n]`.
+    pub fn feed(&mut self, arrival: VertexArrival) {
+        let VertexArrival { vertex, neighbors } = arrival;
+        let n = self.mates.len() as VertexId;
+        if let Some(v) = std::iter::once(&vertex)
+            .chain(neighbors.iter())
+            .find(|v| **v >= n)
+        {
+            panic!("[ArrivalMatching] {} is not a vertex of [{}]", v, n);
+        }
+
+        if self.mate(vertex).is_some() {
+            return;
+        }
+        if let Some(neighbor) = neighbors
+            .into_iter()
+            .find(|u| *u != vertex && self.mate(*u).is_none())
+        {
+            self.mates[vertex as usize] = Some(neighbor);
+            self.mates[neighbor as usize] = Some(vertex);
+        }
+    }
+
+    /// The matched edges, in increasing order of their smaller endpoint
+    pub fn query(self) -> Vec<Edge<VertexId, ()>> {
+        self.mates
+            .into_iter()
+            .enumerate()
+            .filter_map(|(v, mate)| {
+                mate.filter(|u| v < *u as usize)
+                    .map(|u| Edge::init(v as VertexId, u))
+            })
+            .collect()
+    }
+}
+
+impl StreamProcessor<VertexArrival, Vec<Edge<VertexId, ()>>> for ArrivalMatching {
+    fn feed(&mut self, token: VertexArrival) {
+        ArrivalMatching::feed(self, token)
+    }
+
+    fn query(self) -> Vec<Edge<VertexId, ()>> {
+        ArrivalMatching::query(self)
+    }
+}
+
+impl SpaceUsage for ArrivalMatching {
+    /// One mate per vertex
+    fn space(&self) -> SpaceReport {
+        let n = self.mates.len() as f64;
+
+        SpaceReport {
+            structure: "ArrivalMatching",
+            bound: "O(n log n)",
+            parameters: vec![("n", n)],
+            theoretical_bits: n * log(n),
+            measured_bytes: size_of::<Self>()
+                + self.mates.capacity() * size_of::<Option<VertexId>>(),
+            components: vec![],
+        }
+    }
+}
+
+impl Provenance for ArrivalMatching {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "ArrivalMatching",
+            paper_ref: "Greedy maximal matching, in the order of arrival of the vertices",
+            params: vec![
+                ("n", self.mates.len() as f64),
+                ("matched", self.len() as f64),
+            ],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::distributions::Distribution;
+
+    use super::*;
+    use crate::{
+        graph::{streaming::coloring::verifier::verify, Graph, Graphed},
+        random_graph::bernoulli::BernoulliGraphDistribution,
+    };
+
+    fn graph() -> Graph<VertexId, ()> {
+        BernoulliGraphDistribution::init(60, 0.2)
+            .unwrap()
+            .sample(&mut rand::thread_rng())
+    }
+
+    fn edges(graph: &Graph<VertexId, ()>) -> Vec<(VertexId, VertexId)> {
+        let mut edges: Vec<_> = graph
+            .clone()
+            .map(|edge| {
+                let (u, v) = edge.vertices_ord();
+                (*u, *v)
+            })
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn round_trip() {
+        let graph = graph();
+        let arrivals = vertex_arrivals(graph.clone());
+
+        for arrival in arrivals.iter() {
+            let degree = graph.get_neighbors(&arrival.vertex).unwrap().len();
+            assert_eq!(arrival.neighbors.len(), degree);
+        }
+
+        let mut round_trip: Vec<_> = edge_arrivals(arrivals)
+            .map(|(edge, _)| (*edge.vertices().0, *edge.vertices().1))
+            .collect();
+        round_trip.sort_unstable();
+        assert_eq!(round_trip, edges(&graph));
+    }
+
+    #[test]
+    fn coloring() {
+        let graph = graph();
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap();
+
+        let mut colorer = ArrivalColoring::init(60);
+        // Arrivals in decreasing order of the vertices
+        vertex_arrivals(graph.clone())
+            .into_iter()
+            .rev()
+            .for_each(|arrival| colorer.feed(arrival));
+        assert!(colorer.num_colors() <= delta + 1);
+
+        let coloring = colorer.query();
+        let stream = graph.clone().map(|edge| (edge, true));
+        assert!(verify(&coloring, stream).is_proper());
+    }
+
+    #[test]
+    fn matching() {
+        let graph = graph();
+
+        let mut matching = ArrivalMatching::init(60);
+        vertex_arrivals(graph.clone())
+            .into_iter()
+            .for_each(|arrival| matching.feed(arrival));
+        let matched = matching.query();
+
+        let mut endpoints: Vec<VertexId> = matched
+            .iter()
+            .flat_map(|edge| vec![*edge.vertices().0, *edge.vertices().1])
+            .collect();
+        endpoints.sort_unstable();
+        endpoints.dedup();
+        assert_eq!(endpoints.len(), 2 * matched.len());
+        assert!(matched.iter().all(|edge| graph.has_edge(edge)));
+
+        // Maximal, no edge has both endpoints unmatched
+        assert!(edges(&graph).iter().all(|(u, v)| {
+            endpoints.binary_search(u).is_ok() || endpoints.binary_search(v).is_ok()
+        }));
+    }
+}
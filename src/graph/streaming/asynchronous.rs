@@ -0,0 +1,120 @@
+//! Asynchronous Stream Feeding
+//!
+//! When the edges arrive over a socket, the ingest is driven by an async runtime rather than by a loop over a file. The
+//! functions here feed any [StreamProcessor] from a [Stream] of its tokens, awaiting each one, so that a task can own a sketch
+//! while the runtime keeps serving the connection. Only the `futures` traits are used, so that any runtime can drive them: a
+//! tokio channel becomes a [Stream] through `tokio_stream::wrappers::ReceiverStream`, and the bounded channels of
+//! [futures::channel::mpsc] already are one.
+//!
+//! The structures themselves are not async: each feed runs to completion, so a slow sketch holds up the task feeding it.
+//! Available with the `futures` feature.
+
+use futures::{pin_mut, Stream, StreamExt, TryStream, TryStreamExt};
+
+use super::StreamProcessor;
+
+/// Feed every token of the stream to the structure, returning the number of tokens fed once the stream ends
+pub async fn feed_stream<P, T, Q, S>(processor: &mut P, stream: S) -> u64
+where
+    P: StreamProcessor<T, Q>,
+    S: Stream<Item = T>,
+{
+    pin_mut!(stream);
+
+    let mut tokens = 0;
+    while let Some(token) = stream.next().await {
+        processor.feed(token);
+        tokens += 1;
+    }
+    tokens
+}
+
+/// Feed the tokens of a fallible stream, e.g. of lines parsed off a socket, stopping at the first error
+///
+/// The tokens before the error stay fed, so the structure can still be queried, or fed the rest of the stream.
+pub async fn try_feed_stream<P, T, Q, S>(processor: &mut P, stream: S) -> Result<u64, S::Error>
+where
+    P: StreamProcessor<T, Q>,
+    S: TryStream<Ok = T>,
+{
+    let stream = stream.into_stream();
+    pin_mut!(stream);
+
+    let mut tokens = 0;
+    while let Some(token) = stream.try_next().await? {
+        processor.feed(token);
+        tokens += 1;
+    }
+    Ok(tokens)
+}
+
+/// Feed the whole stream to the structure, and query it once the stream ends
+pub async fn query_stream<P, T, Q, S>(mut processor: P, stream: S) -> Q
+where
+    P: StreamProcessor<T, Q>,
+    S: Stream<Item = T>,
+{
+    feed_stream(&mut processor, stream).await;
+    processor.query()
+}
+
+#[cfg(test)]
+mod test {
+    use futures::{channel::mpsc, executor::block_on, stream, SinkExt};
+
+    use super::*;
+    use crate::{
+        graph::{
+            streaming::{
+                connectivity::ComponentCounter,
+                io::{EdgeStreamParameters, EdgeStreamReader},
+            },
+            Edge, VertexId,
+        },
+        Error,
+    };
+
+    #[test]
+    fn from_channel() {
+        let (mut sender, receiver) = mpsc::channel(4);
+        let edges: Vec<Edge<VertexId, ()>> = (0..8).map(|v| Edge::init(v, v + 2)).collect();
+
+        let components = block_on(async {
+            let send = async move {
+                for edge in edges {
+                    sender.send(edge).await.unwrap();
+                }
+            };
+            let query = query_stream(ComponentCounter::init(10), receiver);
+
+            let ((), components) = futures::join!(send, query);
+            components
+        });
+        assert_eq!(components, 2);
+    }
+
+    #[test]
+    fn counts_tokens() {
+        let mut counter = ComponentCounter::init(5);
+        let edges = (0..4).map(|v| Edge::init(v, v + 1));
+
+        let tokens = block_on(feed_stream(&mut counter, stream::iter(edges)));
+        assert_eq!(tokens, 4);
+        assert_eq!(StreamProcessor::<_, usize>::query(counter), 1);
+    }
+
+    #[test]
+    fn stops_at_error() {
+        let lines = "0 1\n1 2\n2 x\n3 4\n";
+        let reader =
+            EdgeStreamReader::<_, ()>::init(lines.as_bytes(), EdgeStreamParameters::default());
+        let tokens = reader.map(|token| token.map(|(edge, _)| edge).map_err(Error::from));
+
+        let mut counter = ComponentCounter::init(5);
+        let result = block_on(try_feed_stream(&mut counter, stream::iter(tokens)));
+        assert!(matches!(result, Err(Error::Read(_))));
+
+        // 3 and 4 are left alone
+        assert_eq!(StreamProcessor::<_, usize>::query(counter), 3);
+    }
+}
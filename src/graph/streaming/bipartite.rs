@@ -0,0 +1,195 @@
+//! Streaming Bipartiteness
+//!
+//! The bipartite double cover of `G` has the vertices `(v, 0)` and `(v, 1)` for every vertex `v`, and the edges `(u, 0) - (v, 1)` and
+//! `(u, 1) - (v, 0)` for every edge `u - v`. A connected component of `G` lifts to two components of the cover if it is bipartite, and
+//! to a single one if it has an odd cycle, so `G` is bipartite if and only if the cover has twice as many components as `G`.
+//!
+//! Both component counts come from [ForestSketch]es, so the test works in the turnstile model.
+
+use std::{collections::HashMap, mem::size_of};
+
+use crate::{
+    graph::{
+        static_a::partition::Partition,
+        streaming::{
+            connectivity::ForestSketch,
+            provenance::{AlgorithmInfo, Provenance},
+            space::{SpaceReport, SpaceUsage},
+            StreamProcessor,
+        },
+        Edge, VertexId,
+    },
+    utils::hash_function::HashFunction,
+};
+
+/// Tests whether a turnstile graph stream over the vertices `[n]` is bipartite
+///
+/// Storage: two spanning forest sketches, over `n` and `2n` vertices, O(n log^3 n) bits
+#[derive(Clone, Debug)]
+pub struct StreamBipartite<H> {
+    n: VertexId,
+    graph: ForestSketch<H>,
+    /// The vertex `(v, i)` of the double cover is `2v + i`
+    cover: ForestSketch<H>,
+}
+
+impl<H> StreamBipartite<H>
+where
+    H: HashFunction,
+{
+    /// Initialize a new tester over the vertices `[n]`
+    pub fn init(n: VertexId) -> Self {
+        Self {
+            n,
+            graph: ForestSketch::init(n),
+            cover: ForestSketch::init(2 * n),
+        }
+    }
+
+    /// Feed an edge insertion or deletion, self loops are ignored
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
+        let (u, v) = edge.vertices();
+        if u == v {
+            return;
+        }
+
+        self.graph.feed(token);
+        self.cover.feed((Edge::init(2 * u, 2 * v + 1), c));
+        self.cover.feed((Edge::init(2 * u + 1, 2 * v), c));
+    }
+
+    /// A bipartition of the streamed graph, `None` if it is not bipartite
+    ///
+    /// Every vertex is colored `0` or `1`, isolated vertices with `0`.
+    pub fn query(self) -> Option<Partition<VertexId>> {
+        let Self { n, graph, cover } = self;
+
+        let forest = graph.query();
+        let graph_components = n - forest.len() as VertexId;
+        let cover_components = 2 * n - cover.query().len() as VertexId;
+
+        if cover_components != 2 * graph_components {
+            return None;
+        }
+
+        // Two color the spanning forest, which colors the graph properly since it is bipartite
+        let mut neighbors: HashMap<VertexId, Vec<VertexId>> = HashMap::new();
+        for edge in forest.iter() {
+            let (u, v) = edge.vertices();
+            neighbors.entry(*u).or_default().push(*v);
+            neighbors.entry(*v).or_default().push(*u);
+        }
+
+        let mut bipartition = Partition::new();
+        for root in 0..n {
+            if bipartition.contains(&root) {
+                continue;
+            }
+            bipartition.insert(root, 0);
+
+            let mut stack = vec![root];
+            while let Some(u) = stack.pop() {
+                let side = 1 - *bipartition.get(&u).unwrap();
+                for v in neighbors.get(&u).into_iter().flatten() {
+                    if !bipartition.contains(v) {
+                        bipartition.insert(*v, side);
+                        stack.push(*v);
+                    }
+                }
+            }
+        }
+
+        Some(bipartition)
+    }
+}
+
+impl<H> SpaceUsage for StreamBipartite<H>
+where
+    H: HashFunction,
+{
+    fn space(&self) -> SpaceReport {
+        let components = vec![self.graph.space(), self.cover.space()];
+
+        SpaceReport {
+            structure: "StreamBipartite",
+            bound: "O(n log^3 n)",
+            parameters: vec![("n", self.n as f64)],
+            theoretical_bits: components.iter().map(|c| c.theoretical_bits).sum(),
+            measured_bytes: size_of::<Self>()
+                + components
+                    .iter()
+                    .map(|c| c.measured_bytes - size_of::<ForestSketch<H>>())
+                    .sum::<usize>(),
+            components,
+        }
+    }
+}
+
+impl<H> StreamProcessor<(Edge<VertexId, ()>, bool), Option<Partition<VertexId>>>
+    for StreamBipartite<H>
+where
+    H: HashFunction,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        StreamBipartite::feed(self, token)
+    }
+
+    fn query(self) -> Option<Partition<VertexId>> {
+        StreamBipartite::query(self)
+    }
+}
+
+impl<H> Provenance for StreamBipartite<H>
+where
+    H: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "StreamBipartite",
+            paper_ref: "Ahn, Guha, McGregor, SODA 2012, https://doi.org/10.1137/1.9781611973099.40",
+            params: vec![("n", self.n as f64)],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        graph::{static_a::coloring::Colorer, Graph, Graphed},
+        utils::hash_function::PowerFiniteFieldHasher,
+    };
+
+    type Tester = StreamBipartite<PowerFiniteFieldHasher>;
+
+    #[test]
+    fn even_and_odd_cycles() {
+        let n = 9;
+        let mut tester = Tester::init(n);
+
+        // An even cycle on 0..6, and a path 6 - 7 - 8
+        (0..6).for_each(|v| tester.feed((Edge::init(v, (v + 1) % 6), true)));
+        tester.feed((Edge::init(6, 7), true));
+        tester.feed((Edge::init(7, 8), true));
+
+        let bipartition = tester.clone().query().unwrap();
+        let mut graph = Graph::<VertexId, ()>::default();
+        (0..6).for_each(|v| graph.add_edge(Edge::init(v, (v + 1) % 6)));
+        graph.add_edge(Edge::init(6, 7));
+        graph.add_edge(Edge::init(7, 8));
+
+        assert_eq!(bipartition.len(), n as usize);
+        assert!(bipartition.num_classes() <= 2);
+        assert!(graph.is_proper(&bipartition));
+
+        // Closing the path into a triangle
+        tester.feed((Edge::init(6, 8), true));
+        assert!(tester.clone().query().is_none());
+
+        // And opening it again
+        tester.feed((Edge::init(7, 8), false));
+        assert!(tester.query().is_some());
+    }
+}
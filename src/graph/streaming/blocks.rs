@@ -0,0 +1,447 @@
+//! Coarse to Fine Block Sketching
+//!
+//! Vertices are hashed into `b` blocks, and a first pass over a turnstile stream keeps the net number of edges between every pair of
+//! blocks, `O(b^2)` counters whatever the length of the stream. Blocks whose internal edge density is high are the candidates for
+//! community structure. A second pass drills down only into those heavy blocks, recovering their internal edges with a sparse
+//! recovery sized by the exact count of the first pass, so the space is proportional to the edges of the heavy blocks alone.
+//!
+//! ```text
+//! let mut coarse = BlockSketch::init(n, BlockParameters::default());
+//! stream.for_each(|token| coarse.feed(token));
+//! let mut fine = coarse.query().drill_down();
+//! stream.for_each(|token| fine.feed(token));
+//! let dense_blocks = fine.query();
+//! ```
+
+use std::mem::size_of;
+
+use num_integer::binomial;
+
+use crate::{
+    graph::{
+        streaming::{
+            provenance::{AlgorithmInfo, Provenance},
+            space::{log, SpaceReport, SpaceUsage},
+            sparse_recovery::{s_sparse::SparseRecovery, SparseRecoveryOutput},
+            StreamProcessor,
+        },
+        Edge, Graph, Graphed, VertexId,
+    },
+    utils::hash_function::{HashFunction, PowerFiniteFieldHasher},
+};
+
+/// Parameters of a [BlockSketch]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlockParameters {
+    /// Number of blocks, rounded up to a power of two
+    pub blocks: u64,
+    /// A block is heavy if at least this fraction of the pairs of its vertices are edges
+    pub density: f64,
+    /// Error parameter of the sparse recoveries of the second pass
+    pub del: f32,
+}
+
+impl Default for BlockParameters {
+    fn default() -> Self {
+        Self {
+            blocks: 64,
+            density: 0.1,
+            del: 0.01,
+        }
+    }
+}
+
+/// Index of the counter of the blocks `a <= b`, within the upper triangle of a `blocks × blocks` matrix
+fn pair_index(blocks: u64, a: u64, b: u64) -> usize {
+    let (a, b) = (a.min(b), a.max(b));
+    (a * blocks - a * (a + 1) / 2 + b) as usize
+}
+
+/// First pass: the net number of edges between every pair of blocks
+///
+/// Storage: O(b^2) counters, plus the hash function
+#[derive(Clone, Debug)]
+pub struct BlockSketch<F: HashFunction = PowerFiniteFieldHasher> {
+    n: VertexId,
+    hasher: F,
+    counts: Vec<i64>,
+    parameters: BlockParameters,
+}
+
+impl<F> BlockSketch<F>
+where
+    F: HashFunction,
+{
+    /// Initialize a sketch over the vertices `[n]`
+    pub fn init(n: VertexId, parameters: BlockParameters) -> Self {
+        let blocks = parameters.blocks.max(1).next_power_of_two();
+        let parameters = BlockParameters {
+            blocks,
+            ..parameters
+        };
+
+        Self {
+            n,
+            hasher: F::init(n.max(2).next_power_of_two(), blocks),
+            counts: vec![0; (blocks * (blocks + 1) / 2) as usize],
+            parameters,
+        }
+    }
+
+    pub fn parameters(&self) -> &BlockParameters {
+        &self.parameters
+    }
+
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
+        let (u, v) = edge.vertices();
+        let index = pair_index(
+            self.parameters.blocks,
+            self.hasher.compute(*u),
+            self.hasher.compute(*v),
+        );
+
+        self.counts[index] += if c { 1 } else { -1 };
+    }
+
+    pub fn query(self) -> BlockCounts<F> {
+        let Self {
+            n,
+            hasher,
+            counts,
+            parameters,
+        } = self;
+
+        let mut sizes = vec![0; parameters.blocks as usize];
+        for v in 0..n {
+            sizes[hasher.compute(v) as usize] += 1;
+        }
+
+        BlockCounts {
+            n,
+            hasher,
+            counts,
+            sizes,
+            parameters,
+        }
+    }
+}
+
+/// The block structure found by the first pass
+#[derive(Clone, Debug)]
+pub struct BlockCounts<F: HashFunction = PowerFiniteFieldHasher> {
+    n: VertexId,
+    hasher: F,
+    counts: Vec<i64>,
+    /// Number of vertices of `[n]` hashed into each block
+    sizes: Vec<u64>,
+    parameters: BlockParameters,
+}
+
+impl<F> BlockCounts<F>
+where
+    F: HashFunction,
+{
+    /// The block of a vertex
+    pub fn block(&self, vertex: VertexId) -> u64 {
+        self.hasher.compute(vertex)
+    }
+
+    /// Net number of edges between the blocks `a` and `b`, or within `a` if they are equal
+    pub fn count(&self, a: u64, b: u64) -> i64 {
+        self.counts[pair_index(self.parameters.blocks, a, b)]
+    }
+
+    /// Number of vertices of the block
+    pub fn size(&self, block: u64) -> u64 {
+        self.sizes[block as usize]
+    }
+
+    /// Fraction of the pairs of vertices of the block that are edges
+    pub fn density(&self, block: u64) -> f64 {
+        let pairs = binomial(self.size(block), 2);
+        if pairs == 0 {
+            0.0
+        } else {
+            self.count(block, block) as f64 / pairs as f64
+        }
+    }
+
+    /// Blocks with a density of at least the threshold, in increasing order
+    pub fn heavy_blocks(&self) -> Vec<u64> {
+        (0..self.parameters.blocks)
+            .filter(|b| self.count(*b, *b) > 0 && self.density(*b) >= self.parameters.density)
+            .collect()
+    }
+
+    /// Second pass over the heavy blocks
+    pub fn drill_down(self) -> BlockDrillDown<F> {
+        let heavy = self.heavy_blocks();
+        let edges = binomial(self.n, 2);
+
+        let mut recoveries: Vec<Option<SparseRecovery<F>>> =
+            (0..self.parameters.blocks).map(|_| None).collect();
+        for block in heavy {
+            let internal = self.count(block, block) as u64;
+            recoveries[block as usize] =
+                Some(SparseRecovery::init(edges, internal, self.parameters.del));
+        }
+
+        BlockDrillDown {
+            counts: self,
+            recoveries,
+        }
+    }
+}
+
+/// A heavy block, with the edges recovered within it
+#[derive(Clone, Debug)]
+pub struct DenseBlock {
+    pub block: u64,
+    pub density: f64,
+    /// `None` if the sparse recovery failed
+    pub edges: Option<Graph<VertexId, ()>>,
+}
+
+/// Second pass: a sparse recovery of the internal edges of every heavy block
+///
+/// Storage: O(b^2) counters, plus O(m_B log(m_B/δ) log n) for the `m_B` internal edges of the heavy blocks
+#[derive(Clone, Debug)]
+pub struct BlockDrillDown<F: HashFunction = PowerFiniteFieldHasher> {
+    counts: BlockCounts<F>,
+    /// Indexed by block, `None` for the light blocks
+    recoveries: Vec<Option<SparseRecovery<F>>>,
+}
+
+impl<F> BlockDrillDown<F>
+where
+    F: HashFunction,
+{
+    /// The counts of the first pass
+    pub fn counts(&self) -> &BlockCounts<F> {
+        &self.counts
+    }
+
+    /// Feed a token of the replayed stream
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
+        let (u, v) = edge.vertices();
+        let block = self.counts.block(*u);
+        if block != self.counts.block(*v) {
+            return;
+        }
+
+        if let Some(recovery) = &mut self.recoveries[block as usize] {
+            recovery.feed((edge.to_d1(), c));
+        }
+    }
+
+    /// The heavy blocks, in increasing order
+    pub fn query(self) -> Vec<DenseBlock> {
+        let Self { counts, recoveries } = self;
+
+        recoveries
+            .into_iter()
+            .enumerate()
+            .filter_map(|(block, recovery)| recovery.map(|r| (block as u64, r)))
+            .map(|(block, recovery)| {
+                let edges = match recovery.query() {
+                    SparseRecoveryOutput::Recovered(support) => {
+                        Some(support.into_iter().filter(|(_, c)| *c > 0).fold(
+                            Graph::default(),
+                            |mut graph, (d1, _)| {
+                                graph.add_edge(Edge::from_d1(d1));
+                                graph
+                            },
+                        ))
+                    }
+                    SparseRecoveryOutput::Zero => Some(Graph::default()),
+                    _ => None,
+                };
+
+                DenseBlock {
+                    block,
+                    density: counts.density(block),
+                    edges,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<F> SpaceUsage for BlockSketch<F>
+where
+    F: HashFunction,
+{
+    /// A counter for every pair of blocks
+    fn space(&self) -> SpaceReport {
+        let blocks = self.parameters.blocks as f64;
+
+        SpaceReport {
+            structure: "BlockSketch",
+            bound: "O(b^2 log n)",
+            parameters: vec![("n", self.n as f64), ("b", blocks)],
+            theoretical_bits: blocks * blocks * log(self.n as f64),
+            measured_bytes: size_of::<Self>() + self.counts.capacity() * size_of::<i64>(),
+            components: vec![],
+        }
+    }
+}
+
+impl<F> SpaceUsage for BlockDrillDown<F>
+where
+    F: HashFunction,
+{
+    /// The counters of the first pass, and the recoveries of the heavy blocks
+    fn space(&self) -> SpaceReport {
+        let blocks = self.counts.parameters.blocks as f64;
+        let recoveries: Vec<SpaceReport> = self
+            .recoveries
+            .iter()
+            .flatten()
+            .map(|r| r.space())
+            .collect();
+
+        SpaceReport {
+            structure: "BlockDrillDown",
+            bound: "O(b^2 log n + m_B log(m_B/δ) log n)",
+            parameters: vec![
+                ("n", self.counts.n as f64),
+                ("b", blocks),
+                ("heavy", recoveries.len() as f64),
+            ],
+            theoretical_bits: blocks * blocks * log(self.counts.n as f64)
+                + recoveries.iter().map(|r| r.theoretical_bits).sum::<f64>(),
+            measured_bytes: size_of::<Self>()
+                + self.counts.counts.capacity() * size_of::<i64>()
+                + self.counts.sizes.capacity() * size_of::<u64>()
+                + self.recoveries.capacity() * size_of::<Option<SparseRecovery<F>>>()
+                + recoveries
+                    .iter()
+                    .map(|r| r.measured_bytes - size_of::<SparseRecovery<F>>())
+                    .sum::<usize>(),
+            components: recoveries,
+        }
+    }
+}
+
+impl<F> StreamProcessor<(Edge<VertexId, ()>, bool), BlockCounts<F>> for BlockSketch<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        BlockSketch::feed(self, token)
+    }
+
+    fn query(self) -> BlockCounts<F> {
+        BlockSketch::query(self)
+    }
+}
+
+impl<F> StreamProcessor<(Edge<VertexId, ()>, bool), Vec<DenseBlock>> for BlockDrillDown<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        BlockDrillDown::feed(self, token)
+    }
+
+    fn query(self) -> Vec<DenseBlock> {
+        BlockDrillDown::query(self)
+    }
+}
+
+impl<F> Provenance for BlockSketch<F>
+where
+    F: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "BlockSketch",
+            paper_ref: "Block level edge counts, drilled down into the dense blocks",
+            params: vec![
+                ("n", self.n as f64),
+                ("b", self.parameters.blocks as f64),
+                ("density", self.parameters.density),
+            ],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A clique on `[0, 12)`, and a sparse path through `[12, 64)`
+    fn stream() -> Vec<(Edge<VertexId, ()>, bool)> {
+        let clique = (0..12).flat_map(|u| ((u + 1)..12).map(move |v| Edge::init(u, v)));
+        let path = (12..63).map(|v| Edge::init(v, v + 1));
+        clique.chain(path).map(|e| (e, true)).collect()
+    }
+
+    #[test]
+    fn pair_indices() {
+        let blocks = 4;
+        let mut indices: Vec<usize> = (0..blocks)
+            .flat_map(|a| (a..blocks).map(move |b| pair_index(blocks, a, b)))
+            .collect();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..10).collect::<Vec<_>>());
+        assert_eq!(pair_index(blocks, 3, 1), pair_index(blocks, 1, 3));
+    }
+
+    #[test]
+    fn coarse_counts() {
+        let parameters = BlockParameters {
+            blocks: 1,
+            ..Default::default()
+        };
+        let mut sketch: BlockSketch = BlockSketch::init(64, parameters);
+        stream().into_iter().for_each(|token| sketch.feed(token));
+        sketch.feed((Edge::init(0, 1), false));
+
+        let counts = sketch.query();
+        assert_eq!(counts.count(0, 0), 66 + 51 - 1);
+        assert_eq!(counts.size(0), 64);
+    }
+
+    #[test]
+    fn drill_down() {
+        let stream = stream();
+        let parameters = BlockParameters {
+            blocks: 4,
+            density: 0.0,
+            ..Default::default()
+        };
+
+        let mut sketch: BlockSketch = BlockSketch::init(64, parameters);
+        stream.iter().for_each(|token| sketch.feed(*token));
+        let counts = sketch.query();
+
+        let total: i64 = (0..4)
+            .flat_map(|a| (a..4).map(move |b| (a, b)))
+            .map(|(a, b)| counts.count(a, b))
+            .sum();
+        assert_eq!(total, stream.len() as i64);
+
+        let heavy = counts.heavy_blocks();
+        let mut drill_down = counts.drill_down();
+        stream.iter().for_each(|token| drill_down.feed(*token));
+        let counts = drill_down.counts().clone();
+        let dense = drill_down.query();
+
+        assert_eq!(dense.iter().map(|d| d.block).collect::<Vec<_>>(), heavy);
+        for block in dense {
+            let edges = block.edges.as_ref().unwrap();
+            assert_eq!(
+                edges.clone().count() as i64,
+                counts.count(block.block, block.block)
+            );
+            assert!(edges
+                .vertices()
+                .into_iter()
+                .all(|v| counts.block(*v) == block.block));
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Chromatic Number Estimation
+//!
+//! Before running a colorer over a stream, it helps to know roughly how many colors it will need. A [ChromaticEstimator] reads an
+//! insertion only stream of edges once, keeping the degrees, the number of edges, and a [TriangleCounter], and bounds `χ(G)`
+//! from those statistics alone:
+//!
+//! - Above, by `κ + 1`, the degeneracy `κ` being at most `Δ`, and at most `(√(8m + 1) - 1) / 2` since a subgraph of minimum
+//!   degree `κ` has at least `κ (κ + 1) / 2` edges
+//! - Below, by the clique number `ω`. By Turán's theorem, a graph on `n` vertices without a `K_{r+1}` has at most
+//!   `(1 - 1/r) n² / 2` edges, and by Zykov's at most `C(r, 3) (n/r)³` triangles, so more edges or triangles than that imply
+//!   `ω >= r + 1`
+//!
+//! The triangle count is an estimate, so the lower bound only holds with the success probability of the [TriangleCounter], and
+//! when its `m Δ / T` ratio is large enough. The range is a first answer, not a coloring.
+
+use std::mem::size_of;
+
+use num_integer::binomial;
+
+use crate::graph::{
+    streaming::{
+        provenance::{AlgorithmInfo, Provenance},
+        space::{log, SpaceReport, SpaceUsage},
+        triangles::{TriangleCounter, TriangleParameters},
+        StreamProcessor,
+    },
+    Edge, VertexId,
+};
+
+/// Parameters of a [ChromaticEstimator]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChromaticParameters {
+    /// Parameters of the triangle counter, coarser than its defaults since only the order of magnitude of the count matters
+    pub triangles: TriangleParameters,
+}
+
+impl Default for ChromaticParameters {
+    fn default() -> Self {
+        Self {
+            triangles: TriangleParameters {
+                epsilon: 0.5,
+                ratio: 10.0,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Likely range of the chromatic number, with the statistics it was derived from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChromaticRange {
+    /// Lower bound on `χ`, from the clique number
+    pub lower: u64,
+    /// Upper bound on `χ`, from the degeneracy
+    pub upper: u64,
+    /// Number of vertices with at least one edge
+    pub vertices: u64,
+    pub edges: u64,
+    pub max_degree: u64,
+    /// Upper bound on the degeneracy
+    pub degeneracy: u64,
+    /// Estimated number of triangles
+    pub triangles: f64,
+}
+
+impl ChromaticRange {
+    /// Whether `chi` colors are within the range
+    pub fn contains(&self, chi: u64) -> bool {
+        self.lower <= chi && chi <= self.upper
+    }
+}
+
+/// Bounds the chromatic number of the graph of an insertion only stream over the vertices `[n]`
+///
+/// Every edge must appear at most once, and self loops are ignored.
+///
+/// Storage: O(n log n) bits for the degrees, and the estimators of the [TriangleCounter]
+#[derive(Clone, Debug)]
+pub struct ChromaticEstimator {
+    degrees: Vec<u32>,
+    edges: u64,
+    triangles: TriangleCounter,
+}
+
+impl ChromaticEstimator {
+    pub fn init(n: VertexId, parameters: ChromaticParameters) -> Self {
+        Self {
+            degrees: vec![0; n as usize],
+            edges: 0,
+            triangles: TriangleCounter::init(parameters.triangles),
+        }
+    }
+
+    /// Feed the next edge of the stream
+    pub fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        let (u, v) = edge.vertices();
+        if u == v {
+            return;
+        }
+
+        for w in [u, v] {
+            let degree = self.degrees.get_mut(*w as usize).unwrap_or_else(|| {
+                panic!(
+                    "[ChromaticEstimator] Vertex {} is outside of the universe",
+                    w
+                )
+            });
+            *degree += 1;
+        }
+        self.edges += 1;
+        self.triangles.feed(edge);
+    }
+
+    /// The range of the chromatic number
+    pub fn query(self) -> ChromaticRange {
+        let Self {
+            degrees,
+            edges: m,
+            triangles,
+        } = self;
+        let epsilon = triangles.parameters().epsilon;
+
+        let n = degrees.iter().filter(|d| **d > 0).count() as u64;
+        let max_degree = degrees.iter().max().copied().unwrap_or_default() as u64;
+        let triangles = triangles.query();
+
+        // κ (κ + 1) / 2 <= m
+        let dense = ((((8 * m + 1) as f64).sqrt() - 1.0) / 2.0).floor() as u64;
+        let degeneracy = max_degree.min(dense);
+        let upper = if n == 0 { 0 } else { degeneracy + 1 };
+
+        // The largest r such that the edges, or the triangles, imply a K_{r+1}
+        let (n_f, m_f) = (n as f64, m as f64);
+        let triangles_low = triangles / (1.0 + epsilon);
+        let mut clique = n.min(1);
+        for r in 1..upper {
+            let r_f = r as f64;
+            let turan = m_f > (1.0 - 1.0 / r_f) * n_f.powi(2) / 2.0;
+            let zykov = triangles_low > binomial(r, 3) as f64 * (n_f / r_f).powi(3);
+            if !(turan || zykov) {
+                break;
+            }
+            clique = r + 1;
+        }
+
+        ChromaticRange {
+            lower: clique.min(upper),
+            upper,
+            vertices: n,
+            edges: m,
+            max_degree,
+            degeneracy,
+            triangles,
+        }
+    }
+}
+
+impl StreamProcessor<Edge<VertexId, ()>, ChromaticRange> for ChromaticEstimator {
+    fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        ChromaticEstimator::feed(self, edge)
+    }
+
+    fn query(self) -> ChromaticRange {
+        ChromaticEstimator::query(self)
+    }
+}
+
+impl SpaceUsage for ChromaticEstimator {
+    /// A degree per vertex, and the triangle counter
+    fn space(&self) -> SpaceReport {
+        let n = self.degrees.len() as f64;
+        let triangles = self.triangles.space();
+
+        SpaceReport {
+            structure: "ChromaticEstimator",
+            bound: "O(n log n) + space of TriangleCounter",
+            parameters: vec![("n", n)],
+            theoretical_bits: n * log(n) + triangles.theoretical_bits,
+            measured_bytes: size_of::<Self>()
+                + self.degrees.capacity() * size_of::<u32>()
+                + triangles.measured_bytes
+                - size_of::<TriangleCounter>(),
+            components: vec![triangles],
+        }
+    }
+}
+
+impl Provenance for ChromaticEstimator {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "ChromaticEstimator",
+            paper_ref:
+                "Turán, 1941; Zykov, 1949; with the triangle counting of Pavan et al., VLDB 2013",
+            params: vec![
+                ("n", self.degrees.len() as f64),
+                ("ε", self.triangles.parameters().epsilon),
+                ("δ", self.triangles.parameters().delta),
+            ],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        graph::{static_a::coloring::Colorer, Graph},
+        random_graph::bernoulli::BernoulliGraphDistribution,
+    };
+    use rand::prelude::Distribution;
+
+    fn estimate(
+        n: VertexId,
+        edges: impl IntoIterator<Item = Edge<VertexId, ()>>,
+    ) -> ChromaticRange {
+        let mut estimator = ChromaticEstimator::init(n, Default::default());
+        edges.into_iter().for_each(|edge| estimator.feed(edge));
+        estimator.query()
+    }
+
+    #[test]
+    fn clique() {
+        let edges = (0..5).flat_map(|u| (u + 1..5).map(move |v| Edge::init(u, v)));
+        let range = estimate(5, edges);
+
+        assert_eq!((range.lower, range.upper), (5, 5));
+        assert_eq!(range.max_degree, 4);
+    }
+
+    #[test]
+    fn bipartite() {
+        // An even cycle, with isolated vertices in the universe
+        let edges = (0..20).map(|v| Edge::init(v, (v + 1) % 20));
+        let range = estimate(30, edges);
+
+        assert_eq!(range.vertices, 20);
+        assert_eq!(range.triangles, 0.0);
+        assert_eq!((range.lower, range.upper), (2, 3));
+        assert!(range.contains(2));
+
+        assert_eq!(estimate(30, vec![]).upper, 0);
+    }
+
+    #[test]
+    fn random() {
+        let graph: Graph<VertexId, ()> = BernoulliGraphDistribution::init(60, 0.3)
+            .unwrap()
+            .sample(&mut rand::thread_rng());
+        let range = estimate(60, graph.clone());
+
+        // The degeneracy coloring uses at most κ + 1 colors, and at least χ
+        let colors = graph.color_degeneracy().num_classes() as u64;
+        assert!(range.lower <= colors, "{:?} against {}", range, colors);
+        assert!(colors <= range.upper, "{:?} against {}", range, colors);
+        assert!(range.lower >= 3);
+    }
+}
@@ -0,0 +1,409 @@
+//! Streaming Clustering Coefficient
+//!
+//! Wedge sampling, from [Jha, Seshadhri and Pinar](https://doi.org/10.1145/2487575.2487678), over an insertion only stream of `m`
+//! edges. The global clustering coefficient `κ = 3T / W` is the fraction of the `W` wedges, paths of two edges, that are closed by a
+//! third edge into one of the `T` triangles.
+//!
+//! A reservoir of `s_e` edges is kept uniform over the stream, and a reservoir of `s_w` wedges uniform over the wedges formed by
+//! the edges of the first reservoir. A sampled wedge is marked closed when the edge joining its ends arrives. Of the three wedges
+//! of a triangle, only the one formed by its first two edges can be closed afterwards, so the fraction `ρ` of closed wedges
+//! estimates `T / W`, and `3ρ` estimates `κ`. The number of wedges of the reservoir, scaled by `m^2 / (s_e (s_e - 1))`, also
+//! estimates `W`, and `ρ W` the number of triangles, complementing the [TriangleCounter](super::triangles::TriangleCounter).
+//!
+//! The confidence interval is Hoeffding's for the `s_w` sampled wedges, taken as independent draws among the wedges of the edge
+//! reservoir: it accounts for the sampling of the wedges, not for how well the edge reservoir represents the graph.
+
+use std::{collections::HashMap, mem::size_of};
+
+use rand::Rng;
+
+use crate::graph::{
+    streaming::{
+        provenance::{AlgorithmInfo, Provenance},
+        space::{log, SpaceReport, SpaceUsage},
+        StreamProcessor,
+    },
+    Edge, VertexId,
+};
+
+/// Parameters of a [ClusteringCoefficient]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClusteringParameters {
+    /// Size of the edge reservoir, `s_e`, at least 2
+    pub edges: usize,
+    /// Size of the wedge reservoir, `s_w`
+    pub wedges: usize,
+    /// Probability that the coefficient of the sampled wedges is outside of the confidence interval
+    pub delta: f64,
+}
+
+impl Default for ClusteringParameters {
+    fn default() -> Self {
+        Self {
+            edges: 1000,
+            wedges: 1000,
+            delta: 0.05,
+        }
+    }
+}
+
+/// Estimates of a [ClusteringCoefficient]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClusteringEstimate {
+    /// The global clustering coefficient `3T / W`, in `[0, 1]`
+    pub coefficient: f64,
+    /// Bounds of the confidence interval of the coefficient
+    pub lower: f64,
+    pub upper: f64,
+    /// Number of triangles
+    pub triangles: f64,
+    /// Number of wedges
+    pub wedges: f64,
+}
+
+/// A wedge of the reservoir, by its ends
+#[derive(Clone, Copy, Debug)]
+struct SampledWedge {
+    ends: (VertexId, VertexId),
+    closed: bool,
+}
+
+/// Estimates the global clustering coefficient of an insertion only graph stream
+///
+/// Every edge must appear at most once, and self loops are ignored.
+///
+/// Storage: O(s_e + s_w) edges and wedges, of O(log n) bits
+///
+/// Runtime: O(s_e + s_w) per edge
+#[derive(Clone, Debug)]
+pub struct ClusteringCoefficient {
+    parameters: ClusteringParameters,
+    edges: Vec<Option<Edge<VertexId, ()>>>,
+    wedges: Vec<Option<SampledWedge>>,
+    /// Wedges formed by pairs of slots of the edge reservoir
+    reservoir_wedges: u64,
+    /// Number of edges so far
+    m: u64,
+}
+
+impl ClusteringCoefficient {
+    /// Panics if the edge reservoir holds less than 2 edges
+    pub fn init(parameters: ClusteringParameters) -> Self {
+        if parameters.edges < 2 {
+            panic!(
+                "[ClusteringCoefficient] The edge reservoir must hold at least 2 edges, not {}",
+                parameters.edges
+            );
+        }
+
+        Self {
+            parameters,
+            edges: vec![None; parameters.edges],
+            wedges: vec![None; parameters.wedges],
+            reservoir_wedges: 0,
+            m: 0,
+        }
+    }
+
+    pub fn parameters(&self) -> &ClusteringParameters {
+        &self.parameters
+    }
+
+    /// Feed the next edge of the stream
+    pub fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        self.feed_with_rng(edge, &mut rand::thread_rng())
+    }
+
+    /// [feed](ClusteringCoefficient::feed), drawing the samples from the generator
+    pub fn feed_with_rng<R: Rng + ?Sized>(&mut self, edge: Edge<VertexId, ()>, rng: &mut R) {
+        let (u, v) = edge.vertices_ord();
+        if u == v {
+            return;
+        }
+        let ends = (*u, *v);
+
+        self.m += 1;
+        for wedge in self.wedges.iter_mut().flatten() {
+            if wedge.ends == ends {
+                wedge.closed = true;
+            }
+        }
+
+        let mut copies = 0;
+        for slot in self.edges.iter_mut() {
+            if rng.gen_range(0..self.m) == 0 {
+                *slot = Some(edge);
+                copies += 1;
+            }
+        }
+        if copies == 0 {
+            return;
+        }
+
+        self.reservoir_wedges = reservoir_wedges(&self.edges);
+        let formed: Vec<(VertexId, VertexId)> = self
+            .edges
+            .iter()
+            .flatten()
+            .filter_map(|other| wedge_ends(&edge, other))
+            .collect();
+        if formed.is_empty() {
+            return;
+        }
+
+        // A sampled wedge is replaced with the probability p that a uniform wedge of the reservoir is a new one, the gaps between
+        // replaced wedges are geometric
+        let p = (copies * formed.len()) as f64 / self.reservoir_wedges as f64;
+        let mut i = 0;
+        while i < self.wedges.len() {
+            if p < 1.0 {
+                let gap = rng.gen::<f64>().ln() / (1.0 - p).ln();
+                if gap >= (self.wedges.len() - i) as f64 {
+                    break;
+                }
+                i += gap as usize;
+            }
+
+            self.wedges[i] = Some(SampledWedge {
+                ends: formed[rng.gen_range(0..formed.len())],
+                closed: false,
+            });
+            i += 1;
+        }
+    }
+
+    /// The estimates, without consuming the structure
+    pub fn estimate(&self) -> ClusteringEstimate {
+        let sampled = self.wedges.iter().flatten().count();
+        let s_e = self.parameters.edges as f64;
+        let wedges = self.reservoir_wedges as f64 * (self.m as f64).powi(2) / (s_e * (s_e - 1.0));
+
+        if sampled == 0 {
+            return ClusteringEstimate {
+                coefficient: 0.0,
+                lower: 0.0,
+                upper: 1.0,
+                triangles: 0.0,
+                wedges,
+            };
+        }
+
+        let closed = self.wedges.iter().flatten().filter(|w| w.closed).count();
+        let rho = closed as f64 / sampled as f64;
+        let width = ((2.0 / self.parameters.delta).ln() / (2.0 * sampled as f64)).sqrt();
+
+        ClusteringEstimate {
+            coefficient: (3.0 * rho).min(1.0),
+            lower: (3.0 * (rho - width)).clamp(0.0, 1.0),
+            upper: (3.0 * (rho + width)).clamp(0.0, 1.0),
+            triangles: rho * wedges,
+            wedges,
+        }
+    }
+
+    pub fn query(self) -> ClusteringEstimate {
+        self.estimate()
+    }
+}
+
+/// The ends of the wedge formed by two distinct edges sharing an endpoint, in increasing order
+fn wedge_ends(e: &Edge<VertexId, ()>, f: &Edge<VertexId, ()>) -> Option<(VertexId, VertexId)> {
+    let ((a, b), (c, d)) = (e.vertices_ord(), f.vertices_ord());
+    let (x, y) = match (a == c, a == d, b == c, b == d) {
+        _ if (a, b) == (c, d) => return None,
+        (true, _, _, _) => (b, d),
+        (_, true, _, _) => (b, c),
+        (_, _, true, _) => (a, d),
+        (_, _, _, true) => (a, c),
+        _ => return None,
+    };
+    Some((*x.min(y), *x.max(y)))
+}
+
+/// Pairs of slots holding distinct edges that share an endpoint
+fn reservoir_wedges(edges: &[Option<Edge<VertexId, ()>>]) -> u64 {
+    let mut degrees: HashMap<VertexId, u64> = HashMap::new();
+    let mut copies: HashMap<(VertexId, VertexId), u64> = HashMap::new();
+    for edge in edges.iter().flatten() {
+        let (u, v) = edge.vertices_ord();
+        *degrees.entry(*u).or_default() += 1;
+        *degrees.entry(*v).or_default() += 1;
+        *copies.entry((*u, *v)).or_default() += 1;
+    }
+
+    let pairs = |k: &u64| k * k.saturating_sub(1) / 2;
+    // Two slots holding the same edge share both of its endpoints
+    degrees.values().map(pairs).sum::<u64>() - 2 * copies.values().map(pairs).sum::<u64>()
+}
+
+impl SpaceUsage for ClusteringCoefficient {
+    /// Each slot holds an edge, each sampled wedge its two ends and a flag
+    fn space(&self) -> SpaceReport {
+        let (s_e, s_w) = (self.parameters.edges as f64, self.parameters.wedges as f64);
+        let n_bits = log(self.m as f64);
+
+        SpaceReport {
+            structure: "ClusteringCoefficient",
+            bound: "O((s_e + s_w) log n)",
+            parameters: vec![("s_e", s_e), ("s_w", s_w), ("δ", self.parameters.delta)],
+            theoretical_bits: (s_e + s_w) * (2.0 * n_bits + 1.0),
+            measured_bytes: size_of::<Self>()
+                + self.edges.capacity() * size_of::<Option<Edge<VertexId, ()>>>()
+                + self.wedges.capacity() * size_of::<Option<SampledWedge>>(),
+            components: vec![],
+        }
+    }
+}
+
+impl StreamProcessor<Edge<VertexId, ()>, ClusteringEstimate> for ClusteringCoefficient {
+    fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        ClusteringCoefficient::feed(self, edge)
+    }
+
+    fn query(self) -> ClusteringEstimate {
+        ClusteringCoefficient::query(self)
+    }
+}
+
+impl Provenance for ClusteringCoefficient {
+    fn info(&self) -> AlgorithmInfo {
+        let ClusteringParameters {
+            edges,
+            wedges,
+            delta,
+        } = self.parameters;
+
+        AlgorithmInfo {
+            name: "ClusteringCoefficient",
+            paper_ref: "Jha, Seshadhri, Pinar, KDD 2013, https://doi.org/10.1145/2487575.2487678",
+            params: vec![("s_e", edges as f64), ("s_w", wedges as f64), ("δ", delta)],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{prelude::Distribution, seq::SliceRandom, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::{
+        graph::{Graph, Graphed},
+        random_graph::bernoulli::BernoulliGraphDistribution,
+    };
+
+    /// Exact `3T / W`
+    fn exact(graph: &Graph<VertexId, ()>) -> f64 {
+        let adj_list = graph.adj_list();
+        let neighbors =
+            |v: &VertexId| -> Vec<VertexId> { adj_list[v].iter().map(|d| d.destination).collect() };
+
+        let (mut wedges, mut closed) = (0, 0);
+        for v in adj_list.keys() {
+            let around = neighbors(v);
+            for (i, a) in around.iter().enumerate() {
+                for b in &around[i + 1..] {
+                    wedges += 1;
+                    if neighbors(a).contains(b) {
+                        closed += 1;
+                    }
+                }
+            }
+        }
+        closed as f64 / wedges as f64
+    }
+
+    fn estimate(
+        edges: &[Edge<VertexId, ()>],
+        parameters: ClusteringParameters,
+        seed: u64,
+    ) -> ClusteringEstimate {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let mut edges = edges.to_vec();
+        edges.shuffle(&mut rng);
+
+        let mut estimator = ClusteringCoefficient::init(parameters);
+        edges
+            .into_iter()
+            .for_each(|edge| estimator.feed_with_rng(edge, &mut rng));
+        estimator.query()
+    }
+
+    #[test]
+    fn wedges() {
+        let (a, b) = (Edge::init(0, 1), Edge::init(2, 1));
+        assert_eq!(wedge_ends(&a, &b), Some((0, 2)));
+        assert_eq!(wedge_ends(&a, &Edge::init(1, 0)), None);
+        assert_eq!(wedge_ends(&a, &Edge::init(2, 3)), None);
+
+        // The pair of copies of a is not a wedge, both are with b
+        assert_eq!(reservoir_wedges(&[Some(a), Some(a), Some(b), None]), 2);
+    }
+
+    #[test]
+    fn triangle_free() {
+        let n = 40;
+        let edges: Vec<_> = (0..n)
+            .flat_map(|u| (0..n).map(move |v| Edge::init(2 * u, 2 * v + 1)))
+            .collect();
+
+        let estimate = estimate(&edges, Default::default(), 0);
+        assert_eq!((estimate.coefficient, estimate.lower), (0.0, 0.0));
+        assert_eq!(estimate.triangles, 0.0);
+    }
+
+    #[test]
+    fn complete_graph() {
+        let n = 30;
+        let edges: Vec<_> = (0..n)
+            .flat_map(|u| (u + 1..n).map(move |v| Edge::init(u, v)))
+            .collect();
+
+        let estimate = estimate(&edges, Default::default(), 1);
+        assert!(estimate.coefficient >= 0.85, "{:?}", estimate);
+        assert_eq!(estimate.upper, 1.0);
+    }
+
+    #[test]
+    fn random_graphs() {
+        for seed in 0..3 {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            let graph: Graph<VertexId, ()> = BernoulliGraphDistribution::init(80, 0.3)
+                .unwrap()
+                .sample(&mut rng);
+            let edges: Vec<_> = graph.clone().collect();
+            let expected = exact(&graph);
+
+            let parameters = ClusteringParameters {
+                edges: 400,
+                wedges: 4000,
+                ..Default::default()
+            };
+            let estimate = estimate(&edges, parameters, seed);
+
+            assert!(
+                estimate.lower <= estimate.coefficient && estimate.coefficient <= estimate.upper
+            );
+            assert!(
+                (estimate.coefficient - expected).abs() <= 0.1,
+                "seed {}: estimated {:?}, expected {}",
+                seed,
+                estimate,
+                expected
+            );
+
+            let wedges: f64 = graph
+                .adj_list()
+                .values()
+                .map(|neighbors| (neighbors.len() * (neighbors.len() - 1) / 2) as f64)
+                .sum();
+            assert!(
+                (estimate.wedges - wedges).abs() <= 0.2 * wedges,
+                "{:?}",
+                estimate
+            );
+        }
+    }
+}
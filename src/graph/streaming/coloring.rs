@@ -1,9 +1,25 @@
 // pub mod ack;
 pub mod ack;
-mod ack_2;
+pub mod ack_2;
+pub mod almost_clique;
+pub mod bank;
 pub mod bcg;
+pub mod colorful_matching;
+pub mod delta_bank;
+pub mod palette_sparsification;
+pub mod verifier;
 
-pub fn compute_s(n: u32) -> f64 {
-    const C: f32 = 15.0;
-    (C * n as f32) as f64 * (n as f64).log2()
+use crate::graph::VertexId;
+
+/// Default constant `C` of the number of edges `s = C n log n` recovered by the colorers
+pub const RECOVERY_CONSTANT: f64 = 15.0;
+
+/// Number of edges `s = C n log n` recovered by the colorers, for the default [RECOVERY_CONSTANT]
+pub fn compute_s(n: VertexId) -> f64 {
+    compute_s_with(n, RECOVERY_CONSTANT)
+}
+
+/// Number of edges `s = C n log n` recovered by the colorers
+pub fn compute_s_with(n: VertexId, constant: f64) -> f64 {
+    constant * n as f64 * (n as f64).log2()
 }
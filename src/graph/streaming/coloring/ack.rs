@@ -1,7 +1,13 @@
 // //! Coloring Algorithm as defined in the ACK paper
 // /// This is a Work In Progress, and by no means correct or complete yet.
 
-use super::compute_s;
+use super::{
+    almost_clique::AlmostClique,
+    colorful_matching::colorful_matching,
+    compute_s_with,
+    verifier::{verify, Verdict},
+    RECOVERY_CONSTANT,
+};
 use crate::{
     graph::{
         static_a::{
@@ -10,37 +16,222 @@ use crate::{
             search::{ConnectedComponents, Search},
         },
         streaming::{
-            sparse_recovery::s_sparse::{SparseRecovery, SparseRecoveryOutput},
-            Query, Stream,
+            frequency::DegreeSketch,
+            provenance::{AlgorithmInfo, Provenance},
+            sketch_bank::{DegreeEstimate, VertexSketchBank},
+            space::{log, SpaceReport, SpaceUsage},
+            sparse_recovery::s_sparse::SparseRecovery,
+            Merge, StreamProcessor,
         },
-        Edge, Graph, GraphWithRecaller, Graphed,
+        Edge, Graph, Graphed, VertexId,
+    },
+    logdur, start_dur,
+    utils::{
+        hash_function::{HashFunction, PowerFiniteFieldHasher},
+        parameters::{ParameterLog, SampleTolerance},
+        slab::{sorted_intersect, sorted_intersection_count, SliceArena},
     },
-    utils::hash_function::PowerFiniteFieldHasher,
+    Error,
 };
-use itertools::Itertools;
+use log::{debug, trace, warn};
 use num_integer::binomial;
-use num_traits::Pow;
-use rand::{distributions::Bernoulli, prelude::Distribution};
+use rand::{distributions::Bernoulli, prelude::Distribution, Rng};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use std::{
     collections::{HashMap, HashSet},
     convert::TryInto,
     f32::EPSILON,
-    fmt::Debug,
+    fmt::{Debug, Display},
+    mem::size_of,
 };
 
 type Color = usize;
-pub struct PairQuerier {
+
+/// Parameters of the ACK [StreamColoring]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AckParameters {
+    /// The sparse-dense decomposition uses `δ = ε / 10`
+    pub epsilon: f64,
+    /// Each vertex samples each color with probability `α log n / (3 ε^2 (Δ + 1))`, in each of its batches
+    pub alpha: f64,
+    /// The colorer recovers up to `s = C n log n` conflicting edges, see [compute_s_with]
+    pub recovery_constant: f64,
+    /// Failure probability of the sparse recovery of the conflicting edges
+    pub recovery_failure: f32,
+    /// The pair querier samples each vertex with probability `C log n / (δ^2 Δ)`
+    pub querier_constant: f64,
+    /// Failure probability of the sparse recovery of the neighborhood of each vertex sampled by the pair querier
+    pub querier_failure: f32,
+    /// How far the sampled palettes, and the vertices sampled by the pair querier, may be from their expected size
+    pub sample_tolerance: SampleTolerance,
+}
+
+impl Default for AckParameters {
+    fn default() -> Self {
+        Self {
+            epsilon: 40.0,
+            alpha: 10000.0,
+            recovery_constant: RECOVERY_CONSTANT,
+            recovery_failure: 0.01,
+            querier_constant: 10.0,
+            querier_failure: 0.01,
+            sample_tolerance: SampleTolerance::default(),
+        }
+    }
+}
+
+/// A parameter of the ACK colorer outside of its range
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AckParametersError {
+    /// A constant that must be positive and finite, with its name and value
+    NotPositive(&'static str, f64),
+    /// A failure probability that must be within `(0, 1)`, with its name and value
+    NotProbability(&'static str, f32),
+}
+
+impl Display for AckParametersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotPositive(name, value) => write!(f, "{} must be positive, not {}", name, value),
+            Self::NotProbability(name, value) => {
+                write!(f, "{} must be within (0, 1), not {}", name, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AckParametersError {}
+
+impl AckParameters {
+    pub fn with_epsilon(self, epsilon: f64) -> Self {
+        Self { epsilon, ..self }
+    }
+
+    pub fn with_alpha(self, alpha: f64) -> Self {
+        Self { alpha, ..self }
+    }
+
+    pub fn with_recovery_constant(self, recovery_constant: f64) -> Self {
+        Self {
+            recovery_constant,
+            ..self
+        }
+    }
+
+    pub fn with_querier_constant(self, querier_constant: f64) -> Self {
+        Self {
+            querier_constant,
+            ..self
+        }
+    }
+
+    /// Use the failure probability for every sparse recovery, a smaller one costs `log(1/δ)` more space
+    pub fn with_failure_probability(self, failure: f32) -> Self {
+        Self {
+            recovery_failure: failure,
+            querier_failure: failure,
+            ..self
+        }
+    }
+
+    pub fn with_sample_tolerance(self, sample_tolerance: SampleTolerance) -> Self {
+        Self {
+            sample_tolerance,
+            ..self
+        }
+    }
+
+    /// Check that the constants are positive, and the failure probabilities within `(0, 1)`
+    ///
+    /// The probabilities derived from valid parameters may still exceed 1 on small graphs, they are then clamped, see
+    /// [ParameterLog::probability].
+    pub fn validate(&self) -> Result<(), AckParametersError> {
+        let constants = [
+            ("epsilon", self.epsilon),
+            ("alpha", self.alpha),
+            ("recovery_constant", self.recovery_constant),
+            ("querier_constant", self.querier_constant),
+        ];
+        if let Some((name, value)) = constants
+            .iter()
+            .find(|(_, value)| !(value.is_finite() && *value > 0.0))
+        {
+            return Err(AckParametersError::NotPositive(name, *value));
+        }
+
+        let failures = [
+            ("recovery_failure", self.recovery_failure),
+            ("querier_failure", self.querier_failure),
+        ];
+        if let Some((name, value)) = failures
+            .iter()
+            .find(|(_, value)| !(*value > 0.0 && *value < 1.0))
+        {
+            return Err(AckParametersError::NotProbability(name, *value));
+        }
+
+        Ok(())
+    }
+
+    /// Probability with which the pair querier samples each vertex, before clamping into `[0, 1]`
+    pub fn querier_probability(&self, n: VertexId, delta: u32) -> f64 {
+        let del = self.epsilon / 10.0;
+        (self.querier_constant * (n as f64).log2()) / (del.powi(2) * (delta as f64))
+    }
+
+    /// Probability with which a vertex samples each color in each of its batches, before clamping into `[0, 1]`
+    pub fn sampling_probability(&self, n: VertexId, delta: u32) -> f64 {
+        (self.alpha * (n.max(2) as f64).log2())
+            / (3.0 * self.epsilon.powi(2) * (delta as f64 + 1.0))
+    }
+
+    /// Expected number of colors sampled by a vertex, over all of its batches
+    pub fn expected_list_size(&self, n: VertexId, delta: u32) -> f64 {
+        BATCHES as f64 * self.sampling_probability(n, delta).min(1.0) * (delta as f64 + 1.0)
+    }
+
+    /// Number of conflicting edges recovered over the vertices `[n]`
+    pub fn s(&self, n: VertexId) -> f64 {
+        compute_s_with(n, self.recovery_constant)
+    }
+
+    /// The parameters, with the recovery constant derived from `ε` and `α` as in the paper
+    ///
+    /// Two neighbors conflict when their lists of `ℓ` colors out of `Δ + 1` intersect, which happens with probability at most
+    /// `ℓ^2 / (Δ + 1)`. A vertex has at most `ℓ^2` conflicting edges in expectation, and the conflict graph at most `n ℓ^2 / 2`,
+    /// i.e. `C n log n` edges for `C = ℓ^2 / (2 log n)`. With `ℓ = α log n / ε^2`, this is `C = α^2 log n / (2 ε^4)`.
+    pub fn derived(self, n: VertexId, delta: u32) -> Self {
+        let list = self.expected_list_size(n, delta);
+
+        Self {
+            recovery_constant: list.powi(2) / (2.0 * (n.max(2) as f64).log2()),
+            ..self
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone)]
+pub struct PairQuerier<F: HashFunction = PowerFiniteFieldHasher> {
     // Data
-    pub inner: HashMap<u32, SparseRecovery<PowerFiniteFieldHasher>>,
+    pub inner: VertexSketchBank<SparseRecovery<F>>,
 
     // Metadata
-    n: u32,
+    n: VertexId,
     delta: u64,
     p: f32,
     del: f64,
+    /// Number of conflicting edges recovered by the colorer, the sampled pairs need `(1 - δ) s / n` overlapping neighbors
+    s: f64,
 }
 
-impl Debug for PairQuerier {
+impl<F> Debug for PairQuerier<F>
+where
+    F: HashFunction,
+{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
@@ -51,110 +242,84 @@ impl Debug for PairQuerier {
     }
 }
 
-impl PairQuerier {
+impl<F> PairQuerier<F>
+where
+    F: HashFunction,
+{
     // We might be able to cut down on data if we only store inner on the vertices and not on all n
-    fn init(n: u32, delta: u64, del: f64) -> Self {
-        let p = (10.0 * (n as f32).log2()) / (del.pow(2) as f32 * (delta as f32));
-        // let p = 1.0;
-
-        println!("[Pair Querier]: {:?}", p);
-        let bern = Bernoulli::new(p as f64)
-            .unwrap_or_else(|_| panic!("[PairQuerier] Invalid Probability: {}", p));
-
-        let mut rng = rand::thread_rng();
-
-        println!("{:?}, {}, {}", del, delta, p);
-
-        println!(
-            "[Pair Querier] Threshold: {:?}",
-            (1.0 - (1.5 * del)) as f64 * (delta as f64) * (p as f64)
+    fn init(
+        n: VertexId,
+        delta: u64,
+        ack_parameters: &AckParameters,
+        parameters: &mut ParameterLog,
+    ) -> crate::Result<Self> {
+        let del = ack_parameters.epsilon / 10.0;
+        let s = ack_parameters.s(n);
+        let tolerance = ack_parameters.sample_tolerance;
+        let p = parameters.probability(
+            "[PairQuerier] p",
+            ack_parameters.querier_probability(n, delta as u32),
+        ) as f32;
+
+        debug!("[PairQuerier] p: {}, δ: {}, Δ: {}", p, del, delta);
+        debug!(
+            "[PairQuerier] Threshold: {:?}",
+            (1.0 - (1.5 * del)) * (delta as f64) * (p as f64)
         );
 
         // Pick a set S of vertices at the beginning of the stream by choosing each vertex
         // independently with probability p.
         //
         // For any chosen vertex in S, run the algorithm in Proposition 4.2(Sparse Recovery) with P
-        // being the set of all edge slots incident to the vertex and k = delta
-        let base = SparseRecovery::init(n.into(), delta, 0.01);
-        let inner: HashMap<u32, _> = (0..n)
-            .into_iter()
-            .filter(|_| bern.sample(&mut rng))
-            .map(|v| (v, base.clone()))
-            .collect();
-
-        println!("[Pair Querier]: Completed Initialization");
-
-        Self {
+        // being the set of all edge slots incident to the vertex and k = delta. Both range over the vertices [0, n].
+        let start = start_dur!();
+        let universe = n + 1;
+        let base = SparseRecovery::init(universe, delta, ack_parameters.querier_failure);
+        let inner = parameters.sample(
+            "[PairQuerier] sampled vertices",
+            p as f64 * universe as f64,
+            tolerance,
+            || VertexSketchBank::init(universe, p as f64, |_| base.random_copy()),
+            |bank| bank.as_ref().map_or(0, VertexSketchBank::len),
+        )?;
+
+        logdur!("[PairQuerier] Initialization", start);
+
+        Ok(Self {
             n,
             inner,
             delta,
             p,
             del,
-        }
+            s,
+        })
     }
 
-    fn feed(&mut self, token: (Edge<u32, ()>, bool)) {
-        let (u, v) = token.0.vertices();
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        self.inner.feed(token);
+    }
 
-        self.inner
-            .entry(*u)
-            .and_modify(|recovery| recovery.feed((*v as u64, token.1)));
-
-        self.inner
-            .entry(*v)
-            .and_modify(|recovery| recovery.feed((*u as u64, token.1)));
-    }
-
-    fn query(&mut self) -> Graph<u32, ()> {
-        let Self { n, .. } = self;
-        let queried: HashMap<u32, HashSet<u32>> = self
-            .inner
-            .iter_mut()
-            .map(|(k, stream)| {
-                (
-                    *k,
-                    stream
-                        .clone()
-                        .query()
-                        .unwrap_or_default()
-                        .keys()
-                        .copied()
-                        .filter_map(|e| e.try_into().ok())
-                        .collect(),
-                )
-            })
+    fn query(&self) -> Graph<VertexId, ()> {
+        let Self {
+            n,
+            ref inner,
+            delta,
+            p,
+            del,
+            s,
+        } = *self;
+        let queried: Vec<(VertexId, Vec<VertexId>)> = inner
+            .query_with(SparseRecovery::query_ref)
+            .into_iter()
+            .map(|(v, output)| (v, output.support().unwrap_or_default()))
             .collect();
 
-        let mut graph = Graph::default();
-        let mut d_prelim: HashMap<u32, u32> = HashMap::default();
-
-        for (v, neighbors) in queried.iter() {
-            for u in neighbors {
-                if u == v {
-                    continue;
-                }
-                let u_neighbors = queried.get(u);
-                let v_neighbors = queried.get(v);
-
-                let thresh =
-                    (1.0 - (1.5 * self.del)) as f64 * (self.delta as f64) * (self.p as f64);
-
-                let overlap = u_neighbors
-                    .zip(v_neighbors)
-                    .map(|(u, v)| u.intersection(&v).count() as f64)
-                    .unwrap_or_default();
-                if overlap >= thresh {
-                    // Answer is YES
-                    *d_prelim.entry(*u).or_default() += 1;
-                    *d_prelim.entry(*v).or_default() += 1;
-                    graph.add_edge(Edge::init(*u, *v));
-                }
-            }
-        }
+        let thresh = (1.0 - (1.5 * del)) * (delta as f64) * (p as f64);
+        let mut graph = overlap_graph(n, queried, thresh);
 
         let graph2 = graph.clone();
         for v in graph2.vertices() {
-            let thresh = (1.0 - self.del) * (compute_s(*n) / *n as f64);
+            let thresh = (1.0 - del) * (s / n as f64);
             if (graph.get_neighbors(v).map(|s| s.len()).unwrap_or_default() as f64) < thresh {
                 graph.remove_vertex(v)
             }
@@ -164,20 +329,176 @@ impl PairQuerier {
     }
 }
 
-type Vertex = u32;
+/// The graph of the adjacent sampled vertices, among `[0, n]`, with at least `thresh` recovered neighbors in common
+///
+/// A pair can only pass a positive threshold if the two vertices share a witness, i.e. a common recovered neighbor. The candidate
+/// pairs are enumerated from an inverted index from every witness to the sampled vertices adjacent to it, and the overlap of each
+/// is counted on the sorted neighbor lists, rather than intersecting the neighborhoods of every adjacent pair.
+fn overlap_graph(
+    n: VertexId,
+    queried: Vec<(VertexId, Vec<VertexId>)>,
+    thresh: f64,
+) -> Graph<VertexId, ()> {
+    let mut queried = queried;
+    queried.sort_unstable_by_key(|(v, _)| *v);
+
+    let mut vertices = Vec::with_capacity(queried.len());
+    let mut neighborhoods = SliceArena::default();
+    let mut witness_pairs = vec![];
+    for (i, (v, neighbors)) in queried.into_iter().enumerate() {
+        let mut neighbors: Vec<VertexId> = neighbors
+            .into_iter()
+            .filter(|u| *u != v && *u <= n)
+            .collect();
+        neighbors.sort_unstable();
+
+        witness_pairs.extend(neighbors.iter().map(|w| (*w as usize, i)));
+        neighborhoods.push(neighbors);
+        vertices.push(v);
+    }
+    let witnesses = SliceArena::group(n as usize + 1, witness_pairs);
+
+    let adjacent = |i: usize, j: usize| {
+        neighborhoods.get(i).binary_search(&vertices[j]).is_ok()
+            || neighborhoods.get(j).binary_search(&vertices[i]).is_ok()
+    };
+
+    let mut candidates: Vec<(usize, usize)> = vec![];
+    for w in 0..witnesses.len() {
+        let sharing = witnesses.get(w);
+        for (k, i) in sharing.iter().enumerate() {
+            candidates.extend(
+                sharing[k + 1..]
+                    .iter()
+                    .filter(|j| adjacent(*i, **j))
+                    .map(|j| (*i, *j)),
+            );
+        }
+    }
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let mut graph = Graph::default();
+    for (i, j) in candidates {
+        let overlap = sorted_intersection_count(neighborhoods.get(i), neighborhoods.get(j));
+        if overlap as f64 >= thresh {
+            // Answer is YES
+            graph.add_edge(Edge::init(vertices[i], vertices[j]));
+        }
+    }
+
+    graph
+}
+
+type Vertex = VertexId;
 
-type ColorSampling = HashSet<Color>;
-pub struct StreamColoring {
-    color_batches: HashMap<Vertex, (ColorSampling, ColorSampling, ColorSampling)>,
-    chi: HashMap<Color, HashSet<Vertex>>,
-    recovery: SparseRecovery<PowerFiniteFieldHasher>,
-    pair_querier: PairQuerier,
+/// Number of batches of colors sampled by each vertex
+const BATCHES: usize = 3;
+
+/// The color `c` is the vertex `COLOR_NODES + c` of a palette graph, apart from the vertices of the graph
+const COLOR_NODES: VertexId = 1 << 63;
+
+/// The `b`th batch of colors sampled by a vertex, in increasing order
+fn batch(color_batches: &ColorBatches, v: Vertex, b: usize) -> &[Color] {
+    color_batches.get(v, b)
+}
+
+/// The batches of colors sampled by every vertex, each drawn on first sight from the stream of a generator given by the vertex
+///
+/// The batches of a vertex only depend on the seed and on the vertex, so that clones of a colorer that discover the vertices in
+/// different orders still agree on them.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+struct ColorBatches {
+    seed: u64,
+    /// Probability with which each color is sampled, in each batch
+    p: f64,
+    /// Colors are sampled out of `[colors]`
+    colors: Color,
+    /// List `first[v] + b` is the `b`th batch of `v`
+    lists: SliceArena<Color>,
+    first: HashMap<Vertex, usize>,
+}
+
+impl ColorBatches {
+    fn init(seed: u64, p: f64, colors: Color) -> Self {
+        Self {
+            seed,
+            p,
+            colors,
+            lists: SliceArena::default(),
+            first: HashMap::new(),
+        }
+    }
+
+    /// Sample the batches of the vertex, unless it already has them
+    fn discover(&mut self, v: Vertex) {
+        if self.first.contains_key(&v) {
+            return;
+        }
+
+        let bern = Bernoulli::new(self.p)
+            .expect("[StreamColoring] p is clamped into [0, 1] at initialization");
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed);
+        rng.set_stream(v);
+
+        let colors = self.colors;
+        self.insert(
+            v,
+            (0..BATCHES)
+                .map(|_| (0..colors).filter(|_| bern.sample(&mut rng)).collect())
+                .collect(),
+        );
+    }
+
+    /// Give the vertex its batches, each in increasing order
+    fn insert(&mut self, v: Vertex, batches: Vec<Vec<Color>>) {
+        self.first.insert(v, self.lists.len());
+        for batch in batches {
+            self.lists.push(batch);
+        }
+    }
+
+    fn get(&self, v: Vertex, b: usize) -> &[Color] {
+        self.first
+            .get(&v)
+            .map_or(&[], |first| self.lists.get(first + b))
+    }
+
+    /// Number of colors sampled, over every vertex and batch
+    fn items(&self) -> usize {
+        self.lists.items()
+    }
+
+    fn layout_bytes(&self) -> usize {
+        self.lists.layout_bytes() + self.first.capacity() * size_of::<(Vertex, usize)>()
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone)]
+pub struct StreamColoring<F: HashFunction = PowerFiniteFieldHasher> {
+    /// The batches of colors sampled by every vertex seen so far
+    color_batches: ColorBatches,
+    recovery: SparseRecovery<F>,
+    pair_querier: PairQuerier<F>,
     // Values
-    vertices: HashSet<u32>,
+    vertices: HashSet<VertexId>,
+    /// Vertices are within `[0, n]`
+    n: VertexId,
+    /// Whether vertices missing from `vertices` are added as the stream reveals them, rather than rejected
+    discovering: bool,
     delta: u32,
+    ack_parameters: AckParameters,
+    /// Only reported at initialization, so it is not part of a checkpoint
+    #[cfg_attr(feature = "serde", serde(skip))]
+    parameters: ParameterLog,
 }
 
-impl Debug for StreamColoring {
+impl<F> Debug for StreamColoring<F>
+where
+    F: HashFunction,
+{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
@@ -189,306 +510,738 @@ impl Debug for StreamColoring {
     }
 }
 
-impl StreamColoring {
-    const EPSILON: f64 = 40.0;
-    const ALPHA: f64 = 10000.0;
-    /// Initiate a new StreamColoring instance under the ACK paper
+impl<F> StreamColoring<F>
+where
+    F: HashFunction + Clone,
+{
+    /// Initiate a new StreamColoring instance under the ACK paper, with the default parameters, `Err` if the samples fail
     ///
     /// - *n* : Size of the graph (|V|)
     /// - *delta* : Maximum degree within the graph
-    pub fn init(vertices: HashSet<&u32>, delta: u32) -> Self {
+    pub fn try_init(vertices: HashSet<&VertexId>, delta: u32) -> crate::Result<Self> {
+        Self::try_init_with(vertices, delta, AckParameters::default())
+    }
+
+    /// [try_init](StreamColoring::try_init), panicking if the samples fail
+    pub fn init_unchecked(vertices: HashSet<&VertexId>, delta: u32) -> Self {
+        Self::init_with_unchecked(vertices, delta, AckParameters::default())
+    }
+
+    /// Initiate a new StreamColoring instance, with the maximum degree estimated by a first pass over the stream, `Err` if the
+    /// samples fail
+    ///
+    /// The estimate is never below `Δ`, so the colorer may use a few more than `Δ + 1` colors.
+    pub fn try_init_with_degrees<G: HashFunction>(
+        vertices: HashSet<&VertexId>,
+        degrees: &DegreeSketch<G>,
+    ) -> crate::Result<Self> {
+        let delta = degrees.max_degree_estimate().try_into().unwrap_or(u32::MAX);
+        Self::try_init(vertices, delta)
+    }
+
+    /// [try_init_with_degrees](StreamColoring::try_init_with_degrees), panicking if the samples fail
+    pub fn init_with_degrees_unchecked<G: HashFunction>(
+        vertices: HashSet<&VertexId>,
+        degrees: &DegreeSketch<G>,
+    ) -> Self {
+        Self::try_init_with_degrees(vertices, degrees)
+            .unwrap_or_else(|error| panic!("[StreamColoring] {}", error))
+    }
+
+    /// [try_init_with](StreamColoring::try_init_with), panicking if the parameters are invalid
+    pub fn init_with_unchecked(
+        vertices: HashSet<&VertexId>,
+        delta: u32,
+        ack_parameters: AckParameters,
+    ) -> Self {
+        Self::try_init_with(vertices, delta, ack_parameters)
+            .unwrap_or_else(|error| panic!("[StreamColoring] {}", error))
+    }
+
+    /// Initiate a new StreamColoring instance under the ACK paper, `Err` if the parameters are invalid
+    pub fn try_init_with(
+        vertices: HashSet<&VertexId>,
+        delta: u32,
+        ack_parameters: AckParameters,
+    ) -> crate::Result<Self> {
         let n = **(vertices.iter().max().unwrap_or(&&0));
+        let mut colorer = Self::try_init_universe(n, delta, ack_parameters)?;
+
+        let mut vertices: Vec<VertexId> = vertices.into_iter().copied().collect();
+        vertices.sort_unstable();
 
-        println!(
-            "Minimum component size: {}",
-            ((1.0 - Self::EPSILON / 10.0) * delta as f64)
-        );
         let mut rng = rand::thread_rng();
-        let bern = {
-            let p = (Self::ALPHA as f64 * (n as f64).log2())
-                / (3_f64 * Self::EPSILON.pow(2) * (delta as f64 + 1_f64));
-            println!("[Stream Coloring]: {:?}", p);
-            Bernoulli::new(p)
-                .unwrap_or_else(|_| panic!("[StreamColoring] Bernoulli p value invalid: {}", p))
-        };
+        let p = colorer.color_batches.p;
+        let mut color_batches = colorer.parameters.sample(
+            "[StreamColoring] sampled colors",
+            vertices.len() as f64 * BATCHES as f64 * p * (delta as f64 + 1.0),
+            ack_parameters.sample_tolerance,
+            || {
+                let mut color_batches = ColorBatches::init(rng.gen(), p, delta as Color + 1);
+                vertices.iter().for_each(|v| color_batches.discover(*v));
+                color_batches
+            },
+            ColorBatches::items,
+        );
+        color_batches.lists.shrink_to_fit();
+
+        colorer.color_batches = color_batches;
+        colorer.vertices = vertices.into_iter().collect();
+        Ok(colorer)
+    }
 
-        let pair_querier = PairQuerier::init(n, delta as u64, Self::EPSILON / 10.0);
+    /// Initiate a new StreamColoring instance over the vertices `[0, n]`, discovered from the stream, with the default parameters,
+    /// `Err` if the samples fail
+    ///
+    /// A vertex samples its colors when the stream first reveals it, so arbitrary edge streams can be fed without collecting their
+    /// vertices beforehand. Only the bound `n` on the vertex ids is needed, to size the sparse recoveries.
+    pub fn try_init_discovering(n: VertexId, delta: u32) -> crate::Result<Self> {
+        Self::try_init_discovering_with(n, delta, AckParameters::default())
+    }
 
-        let mut color_batches: HashMap<u32, _> = Default::default();
-        let mut chi = HashMap::<Color, HashSet<Vertex>>::default();
+    /// [try_init_discovering](StreamColoring::try_init_discovering), panicking if the samples fail
+    pub fn init_discovering_unchecked(n: VertexId, delta: u32) -> Self {
+        Self::init_discovering_with_unchecked(n, delta, AckParameters::default())
+    }
 
-        for vertex in vertices.iter() {
-            let mut sample = || -> HashSet<Color> {
-                (0..(delta + 1))
-                    .into_iter()
-                    .filter(|color| {
-                        if bern.sample(&mut rng) {
-                            chi.entry((*color) as Color).or_default().insert(**vertex);
-                            return true;
-                        }
-                        false
-                    })
-                    .map(|i| i as Color)
-                    .collect()
-            };
+    /// [try_init_discovering_with](StreamColoring::try_init_discovering_with), panicking if the parameters are invalid
+    pub fn init_discovering_with_unchecked(
+        n: VertexId,
+        delta: u32,
+        ack_parameters: AckParameters,
+    ) -> Self {
+        Self::try_init_discovering_with(n, delta, ack_parameters)
+            .unwrap_or_else(|error| panic!("[StreamColoring] {}", error))
+    }
+
+    /// Initiate a new StreamColoring instance over the vertices `[0, n]`, discovered from the stream, `Err` if the parameters are
+    /// invalid
+    pub fn try_init_discovering_with(
+        n: VertexId,
+        delta: u32,
+        ack_parameters: AckParameters,
+    ) -> crate::Result<Self> {
+        Self::try_init_universe(n, delta, ack_parameters).map(|colorer| Self {
+            discovering: true,
+            ..colorer
+        })
+    }
 
-            color_batches.insert(**vertex, (sample(), sample(), sample()));
+    /// Initiate a new StreamColoring instance over the vertices `[0, n]`, discovered from the stream, whose structures hold about
+    /// `bytes`, `None` if the budget holds less than a color per vertex, a sampled vertex or a recovered edge
+    ///
+    /// Rather than the constants of the paper, the budget left by the vertices is split evenly between the sampled colors, the
+    /// pair querier and the recovery of the conflicting edges. Each third gives the largest list size, number of sampled vertices
+    /// and sparsity `s` it holds, from which `α`, the querier constant and the recovery constant are derived. The colors and the
+    /// vertices are sampled at random, so the budget holds in expectation.
+    pub fn with_memory_budget(n: VertexId, delta: u32, bytes: usize) -> Option<Self> {
+        let defaults = AckParameters::default();
+        let (universe, log_n) = (n as f64 + 1.0, (n.max(2) as f64).log2());
+        let delta = delta.max(1);
+        let del = defaults.epsilon / 10.0;
+
+        // The offsets and entry of its batches, its slot in the pair querier, and its entry in the set of vertices
+        let per_vertex = BATCHES * size_of::<usize>()
+            + 2 * size_of::<(Vertex, usize)>()
+            + size_of::<u32>()
+            + 2 * size_of::<VertexId>();
+        let fixed = size_of::<Self>() + (n as usize + 1) * per_vertex;
+        let share = bytes.checked_sub(fixed)? / 3;
+
+        // Largest fitting sparsity, by bisection as the bound grows with `s`
+        let edges = binomial(n + 1, 2).max(1);
+        let fits =
+            |s: u64| SparseRecovery::<F>::bytes_bound(edges, s, defaults.recovery_failure) <= share;
+        if !fits(1) {
+            return None;
+        }
+        let (mut low, mut high) = (1, edges);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if fits(mid) {
+                low = mid
+            } else {
+                high = mid - 1
+            }
         }
 
-        // Recovery data structure used to recover a subset of the edges
-        let s = compute_s(n);
-        println!("Creating sparse recovery");
-        let recovery = SparseRecovery::init(binomial(n.into(), 2), s.ceil() as u64, 0.01);
+        // Every sampled vertex holds a sparse recovery of its neighbors
+        let sketch = size_of::<VertexId>()
+            + SparseRecovery::<F>::bytes_bound(n + 1, delta as u64, defaults.querier_failure);
+        let sampled = (share / sketch) as f64;
+        // Colors of every vertex, over all of its batches
+        let list = (share / size_of::<Color>()) as f64 / universe;
+        if sampled < 1.0 || list < 1.0 {
+            return None;
+        }
 
-        println!("[Stream Coloring]: Completed Initialization");
+        let querier = (sampled / universe).min(1.0);
+        let colors = (list / (BATCHES as f64 * (delta as f64 + 1.0))).min(1.0);
+        let ack_parameters = AckParameters {
+            alpha: colors * 3.0 * defaults.epsilon.powi(2) * (delta as f64 + 1.0) / log_n,
+            querier_constant: querier * del.powi(2) * delta as f64 / log_n,
+            recovery_constant: low as f64 / (n.max(2) as f64 * log_n),
+            ..defaults
+        };
+        debug!("[StreamColoring] Budgeted parameters: {:?}", ack_parameters);
 
-        Self {
-            color_batches,
-            chi,
+        Self::try_init_discovering_with(n, delta, ack_parameters).ok()
+    }
+
+    /// A colorer over the vertices `[0, n]`, none of which has sampled its colors yet
+    fn try_init_universe(
+        n: VertexId,
+        delta: u32,
+        ack_parameters: AckParameters,
+    ) -> crate::Result<Self> {
+        ack_parameters.validate()?;
+
+        let AckParameters { epsilon, .. } = ack_parameters;
+
+        debug!(
+            "[StreamColoring] Minimum component size: {}",
+            ((1.0 - epsilon / 10.0) * delta as f64)
+        );
+        let mut parameters = ParameterLog::default();
+        let p = parameters.probability(
+            "[StreamColoring] p",
+            ack_parameters.sampling_probability(n, delta),
+        );
+        debug!("[StreamColoring] p: {}", p);
+
+        let pair_querier = PairQuerier::init(n, delta as u64, &ack_parameters, &mut parameters)?;
+
+        // Recovery data structure used to recover a subset of the edges, indexed by their slots among the vertices [0, n]
+        let start = start_dur!();
+        let s = ack_parameters.s(n);
+        let recovery = SparseRecovery::init(
+            binomial(n + 1, 2),
+            s.ceil() as u64,
+            ack_parameters.recovery_failure,
+        );
+        logdur!("[StreamColoring] Sparse recovery initialization", start);
+
+        Ok(Self {
+            color_batches: ColorBatches::init(rand::thread_rng().gen(), p, delta as Color + 1),
             recovery,
             pair_querier,
-            vertices: vertices.into_iter().copied().collect(),
+            vertices: HashSet::new(),
+            n,
+            discovering: false,
             delta,
-        }
+            ack_parameters,
+            parameters,
+        })
     }
 
-    pub fn feed(&mut self, token: (Edge<u32, ()>, bool)) {
+    /// Parameters the colorer was initialized with
+    pub fn ack_parameters(&self) -> &AckParameters {
+        &self.ack_parameters
+    }
+
+    /// Parameters that had to be adjusted to be valid for this graph, and the sizes of the samples drawn at initialization
+    pub fn parameters(&self) -> &ParameterLog {
+        &self.parameters
+    }
+
+    /// Bytes used by the sampled palettes
+    pub fn palette_bytes(&self) -> usize {
+        self.color_batches.layout_bytes()
+    }
+
+    /// Vertices of the graph, those seen so far if they are discovered from the stream
+    pub fn vertices(&self) -> &HashSet<VertexId> {
+        &self.vertices
+    }
+
+    /// Estimated degree of every vertex sampled by the pair querier, from the neighborhood recovered so far
+    ///
+    /// The structure is not consumed, so the estimates can be read before querying the coloring.
+    pub fn sampled_degrees(&self) -> HashMap<VertexId, DegreeEstimate> {
+        self.pair_querier.inner.degrees()
+    }
+
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
         let (u, v) = token.0.vertices();
-        let (batch1, batch2, batch3) = self
-            .color_batches
-            .get(u)
-            .expect("This stream includes vertices that are not present in the graph");
-
-        let check_match = |batch: &HashSet<Color>| -> bool {
-            batch.iter().any(|c| {
-                self.chi
-                    .get(c)
-                    .map(|set| set.contains(v))
-                    .unwrap_or_default()
+        for w in [u, v] {
+            if !self.vertices.contains(w) {
+                if !self.discovering || *w > self.n {
+                    panic!("[StreamColoring] This stream includes vertices that are not present in the graph");
+                }
+                self.vertices.insert(*w);
+                self.color_batches.discover(*w);
+            }
+        }
+
+        // u and v conflict when they sampled a common color, in any of their batches
+        let conflict = (0..BATCHES).any(|a| {
+            (0..BATCHES).any(|b| {
+                sorted_intersect(
+                    batch(&self.color_batches, *u, a),
+                    batch(&self.color_batches, *v, b),
+                )
             })
-        };
+        });
 
-        if check_match(batch1) || check_match(batch2) || check_match(batch3) {
+        if conflict {
             self.recovery.feed((token.0.to_d1(), token.1));
         }
         self.pair_querier.feed(token);
     }
 
-    pub fn query(self, actual_graph: &Graph<u32, ()>) -> Option<Coloring<u32>> {
+    /// Color the graph from the stream alone
+    ///
+    /// Fails with [Error::Unrecovered] if the conflict edges could not be recovered, and with [Error::NoColorAvailable] if a
+    /// sparse vertex sampled too few colors, both unlikely for large enough constants.
+    pub fn query(self) -> Result<Coloring<VertexId>, Error> {
+        self.color(None)
+    }
+
+    /// Query the structure, checking against the whole graph that every intermediate coloring is a proper partial coloring
+    ///
+    /// The almost-cliques of the decomposition are [validated](AlmostClique::validate) as well, and their violations logged as warnings.
+    ///
+    /// Available with the `validation` feature, for debugging: storing the graph defeats the purpose of streaming.
+    #[cfg(feature = "validation")]
+    pub fn query_validated(
+        self,
+        actual_graph: &Graph<VertexId, ()>,
+    ) -> Result<Coloring<VertexId>, Error> {
+        self.color(Some(actual_graph))
+    }
+
+    fn color(
+        self,
+        actual_graph: Option<&Graph<VertexId, ()>>,
+    ) -> Result<Coloring<VertexId>, Error> {
+        let validate = |coloring: &Coloring<VertexId>| {
+            if let Some(graph) = actual_graph {
+                assert!(graph.is_partial(coloring));
+            }
+        };
+
         // Find a proper list coloring, where any color for v \in L(v)
         let Self {
             pair_querier,
             delta,
             recovery,
             color_batches,
-            chi,
             vertices,
+            ack_parameters,
             ..
         } = self;
 
-        let mut pair_querier = pair_querier;
-
-        let result = recovery.query();
+        let start = start_dur!();
 
-        if let SparseRecoveryOutput::Pass(result) = result {
+        // Without any conflicting edge left, the recovery is zero rather than empty
+        let result = recovery.query().support().ok_or(Error::Unrecovered)?;
+        {
             let conflict_graph = {
-                let mut tmp: Graph<u32, ()> = Graph::default();
-                for e in result.keys().map(|k| Edge::from_d1(*k)) {
+                let mut tmp: Graph<VertexId, ()> = Graph::default();
+                for e in result.into_iter().map(Edge::from_d1) {
                     tmp.add_edge(e)
                 }
                 tmp
             };
 
-            println!("{}", &conflict_graph);
+            logdur!("[StreamColoring] Conflict graph recovery", start);
+            trace!("[StreamColoring] Conflict graph: {}", &conflict_graph);
+
             let h = pair_querier.query();
-            let del = Self::EPSILON / 10.0;
+            let del = ack_parameters.epsilon / 10.0;
 
-            let (v_sparse, conn_comp) = {
+            let (v_sparse, almost_cliques) = {
                 let min_comp_size = ((1.0 - del) * delta as f64) as usize;
 
-                let mut connected_components = ConnectedComponents::<u32, ()>::default();
+                let mut connected_components = ConnectedComponents::<VertexId, ()>::default();
 
                 if let Some(start) = h.vertices().iter().next() {
                     h.breadth_first(&mut connected_components, vec![start]);
                 }
 
-                let comp_verts: HashSet<_> = connected_components
+                let almost_cliques: Vec<AlmostClique> = connected_components
                     .data
                     .iter()
                     .filter(|g| g.vertices().len() >= min_comp_size)
-                    .flat_map(|g| g.vertices())
+                    .map(|g| AlmostClique::init(g.vertices().into_iter().copied().collect(), &h))
                     .collect();
 
                 let v_sparse: HashSet<_> = vertices
                     .into_iter()
-                    .filter(|v| !comp_verts.contains(v))
+                    .filter(|v| !almost_cliques.iter().any(|c| c.contains(v)))
                     .collect();
 
-                println!("Sparse vertices: {:?}", v_sparse.len());
-                println!(
-                    "Connected Components: (min size: {}) {:?}",
-                    min_comp_size,
-                    connected_components.data.len()
+                debug!("[StreamColoring] Sparse vertices: {}", v_sparse.len());
+                debug!(
+                    "[StreamColoring] Almost cliques: {} (min size: {})",
+                    almost_cliques.len(),
+                    min_comp_size
                 );
 
-                (v_sparse, connected_components)
+                (v_sparse, almost_cliques)
             };
+            // The invariants only hold with high probability, a violation is reported rather than failing the query
+            if let Some(graph) = actual_graph {
+                for almost_clique in almost_cliques.iter() {
+                    if let Err(violations) = almost_clique.validate(graph, delta, del) {
+                        violations
+                            .iter()
+                            .for_each(|v| warn!("[StreamColoring] Invalid almost-clique: {}", v));
+                    }
+                }
+            }
+            logdur!("[StreamColoring] Sparse-dense decomposition", start);
 
             let coloring_sparse_vertices = {
-                let batch1s: HashMap<_, HashSet<_>> = color_batches
-                    .iter()
-                    .map(|(k, batches)| (*k, batches.0.clone()))
-                    .collect();
-
-                // Something isn't right here, we SHOULD always be able to color with the sampled colors, maybe our probabilities are off.
-                // Should copy code from Constraint Problem
-                let mut coloring = HashMap::<u32, Color>::default();
+                let mut coloring = Coloring::<VertexId>::new();
                 for v in v_sparse {
-                    let color = batch1s.get(&v).cloned().and_then(|batch| {
-                        let mut batch = batch;
-                        if let Some(neighbors) = conflict_graph.get_neighbors(&v) {
-                            for n in neighbors {
-                                if let Some(neighbor_c) = coloring.get(&n.destination) {
-                                    batch.remove(neighbor_c);
-                                }
-                            }
-                        }
-                        batch.into_iter().next()
-                    });
-
-                    if let Some(color) = color {
-                        coloring.insert(v, color);
-                    } else {
-                        panic!("NO COLOR AVAILABLE")
-                    }
+                    let neighbor_colors: HashSet<Color> = conflict_graph
+                        .get_neighbors(&v)
+                        .map(|neighbors| {
+                            neighbors
+                                .iter()
+                                .filter_map(|n| coloring.get(&n.destination))
+                                .copied()
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let color = batch(&color_batches, v, 0)
+                        .iter()
+                        .find(|c| !neighbor_colors.contains(c))
+                        .copied();
+
+                    coloring.insert(v, color.ok_or(Error::NoColorAvailable(v))?);
                 }
                 coloring
             };
 
             let mut coloring = coloring_sparse_vertices;
 
-            assert!(actual_graph.is_partial(&coloring));
-
-            println!("{:?}", coloring);
+            validate(&coloring);
+            logdur!("[StreamColoring] Coloring of the sparse vertices", start);
+            trace!("[StreamColoring] Sparse coloring: {:?}", coloring);
+
+            // Almost-cliques initial coloring, the pairs of a colorful matching share a color of their second batch
+            for pair in colorful_matching(
+                &almost_cliques,
+                |v| batch(&color_batches, v, 1),
+                &conflict_graph,
+            ) {
+                coloring.insert(pair.u, pair.color);
+                coloring.insert(pair.v, pair.color);
+            }
 
-            // ALmost CLiques Initial Coloring
-            {
-                // For each color (c), if we can find a pair of vertices u,v such that (u,v) is not in G_conflict,
-                // u and v are not in the colorful matching yet, and L(u) and L(v) both contain (c),
-                // then we add (u,v) with this color to the colorful matching. Hence, this phase also takes O(n) time.
-                for comp in conn_comp.data.iter() {
-                    // This loop takes O(∆) time, since each almost clique has size O(∆)
-                    let vertices: &HashSet<_> = &comp.vertices().into_iter().copied().collect();
-                    let mut colors_used: HashSet<_> = HashSet::new();
+            validate(&coloring);
 
-                    for v in vertices {
-                        let (_, batch2, _) = color_batches.get(v).unwrap();
+            let complete = {
+                // Every almost-clique is matched against the same partial coloring, independently of the others
+                #[cfg(feature = "rayon")]
+                let matchings: Vec<_> = almost_cliques
+                    .par_iter()
+                    .map(|almost_clique| {
+                        palette_matching(almost_clique, &coloring, &conflict_graph, &color_batches)
+                    })
+                    .collect();
+                #[cfg(not(feature = "rayon"))]
+                let matchings: Vec<_> = almost_cliques
+                    .iter()
+                    .map(|almost_clique| {
+                        palette_matching(almost_clique, &coloring, &conflict_graph, &color_batches)
+                    })
+                    .collect();
 
-                        'inner: for (c, opts) in batch2
-                            .iter()
-                            .filter(|c| !colors_used.contains(c))
-                            .map(|c| (c, chi.get(c).unwrap()))
-                        {
-                            for u in opts {
-                                let edge: Edge<_, ()> = Edge::init(*u, *v);
-                                if !conflict_graph.has_edge(&edge)
-                                    && !coloring.contains_key(u)
-                                    && !coloring.contains_key(v)
-                                {
-                                    coloring.insert(*u, *c);
-                                    coloring.insert(*v, *c);
-                                    colors_used.insert(c);
-                                    break 'inner;
-
-                                    // 30: 24,27,36,42,48,52,58,63,65,66,67,68,69,71,82,95,98
-                                    // 30: 24,27,36,42,48,52,58,63,65,66,67,68,69,71,82,95,98
-                                }
-                            }
-                        }
+                // Two almost-cliques joined by a conflict edge may have given its endpoints the same color, those almost-cliques
+                // are matched again, one at a time, against the colors taken so far
+                let mut retry = vec![];
+                for (almost_clique, matching) in almost_cliques.iter().zip(matchings) {
+                    if !assign_matching(matching, &mut coloring, &conflict_graph) {
+                        retry.push(almost_clique);
                     }
-
-                    // O(∆) Time
-                    // for c in 0..(delta as Color + 1) {
-                    //     // If each vertex samples O(log n) colors, then this runs in O(log n) Time, and collects O(log n) vertices.
-                    //     // A given vertex samples a given color with probability log 2 / delta, since the
-                    //     let nodes = chi
-                    //         .get(&c)
-                    //         .unwrap_or_else(|| panic!("Bad Data"))
-                    //         .intersection(vertices);
-
-                    //     // This runs in polylog time, since nodes has size O(log n), this will run in O(log^2 n) time.
-                    //     let mut edges_to_check: HashSet<_> =
-                    //         nodes.clone().cartesian_product(nodes.clone()).collect();
-
-                    //     'inner: while let Some((u, v)) = edges_to_check.iter().next().cloned() {
-                    //         edges_to_check.remove(&(u, v));
-                    //         let edge: Edge<_, ()> = Edge::init(*u, *v);
-
-                    //         if !conflict_graph.has_edge(&edge)
-                    //             && !coloring.contains_key(u)
-                    //             && !coloring.contains_key(v)
-                    //         {
-                    //             coloring.insert(*u, c);
-                    //             coloring.insert(*v, c);
-                    //             break 'inner;
-
-                    //             // 30: 24,27,36,42,48,52,58,63,65,66,67,68,69,71,82,95,98
-                    //             // 30: 24,27,36,42,48,52,58,63,65,66,67,68,69,71,82,95,98
-                    //         }
-                    //     }
-                    // }
                 }
+                for almost_clique in retry {
+                    let matching =
+                        palette_matching(almost_clique, &coloring, &conflict_graph, &color_batches);
+                    assign_matching(matching, &mut coloring, &conflict_graph);
+                }
+
+                coloring
             };
 
-            assert!(actual_graph.is_partial(&coloring));
+            validate(&complete);
+            logdur!("[StreamColoring] Coloring of the almost-cliques", start);
 
-            let complete = {
-                for almost_clique in conn_comp.data.iter() {
-                    let mut palette_graph = Graph::<u32, ()>::default();
-
-                    // This takes O(∆) time since each almost_cliques has no more that (1 + 6*del) * delta vertices
-                    let uncolored_vertices: HashSet<_> = almost_clique
-                        .vertices()
-                        .into_iter()
-                        .filter(|v| !coloring.contains_key(v))
-                        .copied()
-                        .collect();
-
-                    println!("{:?}", uncolored_vertices.len());
-
-                    uncolored_vertices.iter().for_each(|v| {
-                        color_batches.get(&v).unwrap().2.iter().for_each(|c| {
-                            palette_graph.add_edge(Edge::init(*v, (*c).try_into().unwrap()))
-                        })
-                    });
+            Ok(complete)
+        }
+    }
+}
+
+/// Match the uncolored vertices of an almost-clique to the colors of their third batch that no colored neighbor in the conflict graph
+/// holds, as edges `(v, c)` of the palette graph
+///
+/// Only reads the coloring and the conflict graph, so the almost-cliques can be matched in parallel.
+fn palette_matching(
+    almost_clique: &AlmostClique,
+    coloring: &Coloring<VertexId>,
+    conflict_graph: &Graph<VertexId, ()>,
+    color_batches: &ColorBatches,
+) -> HashSet<Edge<VertexId, ()>> {
+    let mut palette_graph = Graph::<VertexId, ()>::default();
+
+    // This takes O(∆) time since each almost_cliques has no more that (1 + 6*del) * delta vertices
+    let uncolored_vertices: HashSet<_> = almost_clique
+        .vertices
+        .iter()
+        .filter(|v| !coloring.contains(v))
+        .copied()
+        .collect();
+
+    for v in uncolored_vertices.iter() {
+        let taken: HashSet<Color> = conflict_graph
+            .get_neighbors(v)
+            .map(|neighbors| {
+                neighbors
+                    .iter()
+                    .filter_map(|n| coloring.get(&n.destination))
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default();
 
-                    for edge in conflict_graph.clone() {
-                        let (u, v) = edge.vertices();
+        batch(color_batches, *v, 2)
+            .iter()
+            .filter(|c| !taken.contains(c))
+            .for_each(|c| palette_graph.add_edge(Edge::init(*v, COLOR_NODES + *c as VertexId)));
+    }
+    // Creating the Pallette Graph therefore takes O(∆ log2 n)
+    palette_graph.hopkroft_karp(Some(uncolored_vertices))
+}
 
-                        if let Some(c) = coloring.get(u) {
-                            palette_graph.remove_edge(Edge::init(*v, *c as u32))
-                        }
-                        if let Some(c) = coloring.get(v) {
-                            palette_graph.remove_edge(Edge::init(*u, *c as u32))
-                        }
-                    }
-                    // Creating the Pallette Graph therefore takes O(∆ log2 n)
-                    let matching = palette_graph.hopkroft_karp(Some(uncolored_vertices));
+/// Color every vertex `v` of a palette matching `(v, c)` with `c`, unless a neighbor in the conflict graph already has it
+///
+/// Returns whether every vertex of the matching was colored.
+fn assign_matching(
+    matching: HashSet<Edge<VertexId, ()>>,
+    coloring: &mut Coloring<VertexId>,
+    conflict_graph: &Graph<VertexId, ()>,
+) -> bool {
+    let mut complete = true;
+    for edge in matching {
+        let (v, c) = match edge.vertices() {
+            (v, c) if *c >= COLOR_NODES => (v, c),
+            (c, v) => (v, c),
+        };
+        let c = (*c - COLOR_NODES) as Color;
+        let clash = conflict_graph.get_neighbors(v).is_some_and(|neighbors| {
+            neighbors
+                .iter()
+                .any(|n| coloring.get(&n.destination) == Some(&c))
+        });
+
+        if clash || coloring.contains(v) {
+            complete = false;
+        } else {
+            coloring.insert(*v, c);
+        }
+    }
+    complete
+}
+
+/// Outcome of the colorer for one recovery constant, see [sweep_recovery_constant]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecoverySweep {
+    pub recovery_constant: f64,
+    /// Number of conflicting edges recovered
+    pub s: f64,
+    /// Bytes measured by [SpaceUsage], averaged over the runs
+    pub measured_bytes: f64,
+    pub runs: usize,
+    /// Number of runs that produced a proper coloring
+    pub successes: usize,
+}
+
+impl RecoverySweep {
+    pub fn success_rate(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.successes as f64 / self.runs as f64
+        }
+    }
+}
 
-                    for edge in matching {
-                        let (v, c) = edge.vertices();
-                        coloring.insert(*v, *c as Color);
+/// Color a graph of maximum degree `delta` `runs` times for every recovery constant, to show the trade-off between the space of
+/// the colorer and how often it succeeds
+///
+/// A run fails if the conflicting edges could not be recovered, if the coloring is not proper, or if the colorer ran out of
+/// sampled colors.
+pub fn sweep_recovery_constant(
+    graph: &Graph<VertexId, ()>,
+    delta: u32,
+    parameters: AckParameters,
+    constants: &[f64],
+    runs: usize,
+) -> Vec<RecoverySweep> {
+    let stream: Vec<_> = graph.clone().map(|e| (e, true)).collect();
+
+    constants
+        .iter()
+        .map(|constant| {
+            let parameters = AckParameters {
+                recovery_constant: *constant,
+                ..parameters
+            };
+
+            let mut bytes = 0;
+            let mut successes = 0;
+            for _ in 0..runs {
+                let mut colorer: StreamColoring =
+                    StreamColoring::init_with_unchecked(graph.vertices(), delta, parameters);
+                stream.iter().for_each(|token| colorer.feed(*token));
+                bytes += colorer.memory_usage();
+
+                if let Ok(coloring) = colorer.query() {
+                    if verify(&coloring, stream.iter().copied()) == Verdict::Proper {
+                        successes += 1;
                     }
                 }
-                coloring
-            };
+            }
+
+            RecoverySweep {
+                recovery_constant: *constant,
+                s: parameters.s(graph
+                    .vertices()
+                    .into_iter()
+                    .max()
+                    .copied()
+                    .unwrap_or_default()),
+                measured_bytes: bytes as f64 / runs.max(1) as f64,
+                runs,
+                successes,
+            }
+        })
+        .collect()
+}
 
-            assert!(actual_graph.is_partial(&complete));
+impl<F> StreamProcessor<(Edge<VertexId, ()>, bool), Result<Coloring<VertexId>, Error>>
+    for StreamColoring<F>
+where
+    F: HashFunction + Clone,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        StreamColoring::feed(self, token)
+    }
 
-            return Some(complete);
+    fn query(self) -> Result<Coloring<VertexId>, Error> {
+        StreamColoring::query(self)
+    }
+}
+
+impl<F> Merge for PairQuerier<F>
+where
+    F: HashFunction,
+{
+    fn merge(&mut self, other: &Self) {
+        self.inner.merge(&other.inner);
+    }
+}
+
+impl<F> SpaceUsage for PairQuerier<F>
+where
+    F: HashFunction,
+{
+    /// The sparse recoveries of the neighborhoods of the sampled vertices
+    fn space(&self) -> SpaceReport {
+        let bank = self.inner.space();
+
+        SpaceReport {
+            structure: "PairQuerier",
+            bound: "O(n p Δ log^2 n)",
+            parameters: vec![
+                ("n", self.n as f64),
+                ("p", self.p as f64),
+                ("Δ", self.delta as f64),
+            ],
+            theoretical_bits: bank.theoretical_bits,
+            measured_bytes: size_of::<Self>() + bank.measured_bytes
+                - size_of::<VertexSketchBank<SparseRecovery<F>>>(),
+            components: vec![bank],
+        }
+    }
+}
+
+impl<F> Merge for StreamColoring<F>
+where
+    F: HashFunction,
+{
+    /// Both colorers must share the sampled palettes, the sampled vertices of the pair querier, and the sparse recoveries
+    ///
+    /// Colorers that discover their vertices take the vertices discovered by either.
+    fn merge(&mut self, other: &Self) {
+        if self.delta != other.delta
+            || self.n != other.n
+            || self.color_batches.seed != other.color_batches.seed
+            || (!self.discovering && self.vertices != other.vertices)
+        {
+            panic!("[StreamColoring] Cannot merge colorers of different graphs");
+        }
+        for v in other.vertices.iter() {
+            if self.vertices.insert(*v) {
+                self.color_batches.discover(*v);
+            }
         }
+        self.recovery.merge(&other.recovery);
+        self.pair_querier.merge(&other.pair_querier);
+    }
+}
 
-        None
+impl<F> Provenance for StreamColoring<F>
+where
+    F: HashFunction + Clone,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "StreamColoring (ACK)",
+            paper_ref: "Assadi, Chen, Khanna, SODA 2019, https://arxiv.org/abs/1807.08886",
+            params: vec![
+                ("n", self.vertices.len() as f64),
+                ("Δ", self.delta as f64),
+                ("ε", self.ack_parameters.epsilon),
+                ("α", self.ack_parameters.alpha),
+                ("C", self.ack_parameters.recovery_constant),
+                ("sampled colors", self.color_batches.items() as f64),
+                ("sampled vertices", self.pair_querier.inner.len() as f64),
+            ],
+            seed: None,
+        }
+    }
+}
+
+impl<F> SpaceUsage for StreamColoring<F>
+where
+    F: HashFunction + Clone,
+{
+    /// `O(log n)` sampled colors per vertex, the sparse recovery of the conflicting edges, and the sketches of the pair querier
+    fn space(&self) -> SpaceReport {
+        let n = self.vertices.len() as f64;
+        let recovery = self.recovery.space();
+        let pair_querier = self.pair_querier.space();
+
+        let palettes = n * log(n) * log(self.delta as f64 + 1.0);
+
+        SpaceReport {
+            structure: "StreamColoring (ACK)",
+            bound: "O(n log^2 n)",
+            parameters: vec![("n", n), ("Δ", self.delta as f64)],
+            theoretical_bits: palettes + recovery.theoretical_bits + pair_querier.theoretical_bits,
+            measured_bytes: size_of::<Self>()
+                + self.palette_bytes()
+                + self.vertices.capacity() * size_of::<VertexId>()
+                + recovery.measured_bytes
+                - size_of::<SparseRecovery<F>>()
+                + pair_querier.measured_bytes
+                - size_of::<PairQuerier<F>>(),
+            components: vec![recovery, pair_querier],
+        }
     }
 }
 
@@ -498,18 +1251,500 @@ mod test {
     use std::fs;
 
     use super::*;
-    use crate::random_graph::bernoulli::BernoulliGraphDistribution;
+    use crate::random_graph::{
+        bernoulli::BernoulliGraphDistribution, net_edges, uniform::UniformGraphDistribution,
+    };
 
-    fn test_graph() -> Graph<u32, ()> {
+    fn test_graph() -> Graph<VertexId, ()> {
         let mut rng = rand::thread_rng();
         // Test for p = log n
         let n: f64 = 100.0;
         let p = 1.0 / n.log2();
-        BernoulliGraphDistribution::<u32>::init(n as u32, p)
+        BernoulliGraphDistribution::<VertexId>::init(n as VertexId, p)
             .unwrap()
             .sample(&mut rng)
     }
 
+    #[test]
+    fn overlaps() {
+        // A clique on [0, 5), with 5 and 6 attached to 0 only
+        let clique = |v: VertexId| -> Vec<VertexId> {
+            (0..5)
+                .filter(|u| *u != v)
+                .chain(if v == 0 { vec![5, 6] } else { vec![] })
+                .collect()
+        };
+        let queried: Vec<_> = (0..5)
+            .map(|v| (v, clique(v)))
+            .chain(vec![(5, vec![0]), (6, vec![0])])
+            .collect();
+
+        let graph = overlap_graph(10, queried.clone(), 3.0);
+        assert_eq!(graph.vertices().len(), 5);
+        assert!(graph.has_edge(&Edge::init(0, 4)));
+        assert!(!graph.has_edge(&Edge::init(0, 5)));
+
+        // 5 and 6 share the witness 0, but are not adjacent
+        let graph = overlap_graph(10, queried, 1.0);
+        assert!(graph.has_edge(&Edge::init(1, 2)));
+        assert!(!graph.has_edge(&Edge::init(5, 6)));
+
+        // The last vertex, n, is a witness too
+        let queried = vec![(0, vec![1, 2, 3]), (1, vec![0, 2, 3])];
+        assert!(overlap_graph(3, queried, 2.0).has_edge(&Edge::init(0, 1)));
+    }
+
+    #[test]
+    fn overlaps_match_friend_edges() {
+        use crate::graph::static_a::decomposition::SparseDense;
+
+        // With every neighborhood recovered, the overlap graph is the exact friend edge graph
+        let graph: Graph<VertexId, ()> = BernoulliGraphDistribution::init(40, 0.8)
+            .unwrap()
+            .sample(&mut rand::thread_rng());
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+        let epsilon = 0.3;
+
+        let queried = graph
+            .adj_list()
+            .iter()
+            .map(|(v, neighbors)| (*v, neighbors.iter().map(|n| n.destination).collect()))
+            .collect();
+        let overlaps = overlap_graph(40, queried, (1.0 - epsilon) * delta as f64);
+        let friends = graph.friend_edges(delta, epsilon);
+
+        let edges = |g: &Graph<VertexId, ()>| {
+            let mut edges: Vec<_> = g
+                .clone()
+                .map(|e| *e.vertices_ord().0 * 40 + *e.vertices_ord().1)
+                .collect();
+            edges.sort_unstable();
+            edges.dedup();
+            edges
+        };
+        assert_eq!(edges(&overlaps), edges(&friends));
+    }
+
+    #[test]
+    fn derived_parameters() {
+        let parameters = AckParameters {
+            epsilon: 1.0,
+            alpha: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(parameters.s(16), RECOVERY_CONSTANT * 64.0);
+
+        // 3 batches of (log 1024 / 3 (Δ + 1)) (Δ + 1) colors, i.e. ℓ = 10 colors, and C = ℓ^2 / (2 log n)
+        let derived = parameters.derived(1024, 99);
+        assert!((parameters.expected_list_size(1024, 99) - 10.0).abs() < 1e-9);
+        assert!((derived.recovery_constant - 5.0).abs() < 1e-9);
+        assert_eq!(derived.epsilon, 1.0);
+    }
+
+    #[test]
+    fn builder() {
+        let parameters = AckParameters::default()
+            .with_epsilon(1.0)
+            .with_alpha(50.0)
+            .with_failure_probability(0.05);
+        assert_eq!(parameters.querier_failure, 0.05);
+        assert_eq!(parameters.recovery_failure, 0.05);
+        assert_eq!(parameters.validate(), Ok(()));
+
+        assert_eq!(
+            parameters.with_epsilon(0.0).validate(),
+            Err(AckParametersError::NotPositive("epsilon", 0.0))
+        );
+        assert_eq!(
+            parameters.with_failure_probability(1.5).validate(),
+            Err(AckParametersError::NotProbability("recovery_failure", 1.5))
+        );
+
+        let graph = test_graph();
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+        assert!(matches!(
+            StreamColoring::<PowerFiniteFieldHasher>::try_init_with(
+                graph.vertices(),
+                delta,
+                parameters.with_alpha(f64::NAN)
+            ),
+            Err(Error::AckParameters(_))
+        ));
+
+        // The probability of the pair querier exceeds 1 on this small graph, and is clamped rather than rejected
+        assert!(parameters.querier_probability(40, delta) > 1.0);
+        let colorer: StreamColoring =
+            StreamColoring::try_init_with(graph.vertices(), delta, parameters).unwrap();
+        assert!(colorer
+            .parameters()
+            .adjustments()
+            .iter()
+            .any(|adjustment| adjustment.name == "[PairQuerier] p" && adjustment.used == 1.0));
+    }
+
+    #[test]
+    fn sample_diagnostics() {
+        let graph = test_graph();
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+
+        // A small α samples a few colors per vertex, rather than every color
+        let parameters = AckParameters {
+            alpha: 50.0,
+            epsilon: 1.0,
+            ..Default::default()
+        };
+        let colorer: StreamColoring =
+            StreamColoring::init_with_unchecked(graph.vertices(), delta, parameters);
+
+        let samples = colorer.parameters().samples();
+        assert_eq!(
+            samples.iter().map(|s| s.name).collect::<Vec<_>>(),
+            vec![
+                "[PairQuerier] sampled vertices",
+                "[StreamColoring] sampled colors"
+            ]
+        );
+        for sample in samples {
+            assert!(sample.attempts <= parameters.sample_tolerance.retries + 1);
+            assert_eq!(
+                sample.within,
+                sample.deviation() <= parameters.sample_tolerance.deviation
+            );
+        }
+
+        let info = colorer.info();
+        let sampled = info
+            .params
+            .iter()
+            .find(|(name, _)| *name == "sampled colors");
+        assert_eq!(sampled.map(|(_, v)| *v as usize), Some(samples[1].actual));
+    }
+
+    #[test]
+    fn recovery_sweep() {
+        let graph: Graph<VertexId, ()> = r"0: 1
+        1: 0,2
+        2: 1,3
+        3: 2"
+            .parse()
+            .unwrap();
+
+        let sweep = sweep_recovery_constant(&graph, 2, AckParameters::default(), &[1.0, 15.0], 2);
+
+        assert_eq!(sweep.len(), 2);
+        assert!(sweep[0].s < sweep[1].s);
+        assert!(sweep.iter().all(|r| r.runs == 2 && r.success_rate() <= 1.0));
+    }
+
+    #[test]
+    fn merge() {
+        let graph = test_graph();
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+        let stream: Vec<_> = graph.clone().map(|e| (e, true)).collect();
+
+        let mut whole: StreamColoring = StreamColoring::init_unchecked(graph.vertices(), delta);
+        let (mut left, mut right) = (whole.clone(), whole.clone());
+        for (i, token) in stream.iter().enumerate() {
+            whole.feed(*token);
+            if i % 2 == 0 {
+                left.feed(*token)
+            } else {
+                right.feed(*token)
+            }
+        }
+
+        left.merge(&right);
+        assert_eq!(left.sampled_degrees(), whole.sampled_degrees());
+    }
+
+    #[test]
+    fn space() {
+        let graph = test_graph();
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+
+        let mut colorer: StreamColoring = StreamColoring::init_unchecked(graph.vertices(), delta);
+        let empty = colorer.memory_usage();
+        graph.clone().for_each(|edge| colorer.feed((edge, true)));
+
+        let report = colorer.space();
+        assert!(report.measured_bytes > empty);
+        assert_eq!(report.components[1].structure, "PairQuerier");
+        assert_eq!(
+            report.components[1].measured_bytes,
+            colorer.pair_querier.memory_usage()
+        );
+        assert!(report.measured_bytes > colorer.pair_querier.memory_usage());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn checkpoint() {
+        let graph = test_graph();
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+        let stream: Vec<_> = graph.clone().map(|e| (e, true)).collect();
+        let (first, second) = stream.split_at(stream.len() / 2);
+
+        let mut colorer: StreamColoring = StreamColoring::init_unchecked(graph.vertices(), delta);
+        first.iter().for_each(|token| colorer.feed(*token));
+
+        let checkpoint = serde_json::to_string(&colorer).unwrap();
+        let mut resumed: StreamColoring = serde_json::from_str(&checkpoint).unwrap();
+
+        second.iter().for_each(|token| {
+            colorer.feed(*token);
+            resumed.feed(*token);
+        });
+        assert_eq!(resumed.sampled_degrees(), colorer.sampled_degrees());
+        assert_eq!(resumed.ack_parameters(), colorer.ack_parameters());
+    }
+
+    #[test]
+    fn small_graph_parameters() {
+        let graph: Graph<VertexId, ()> = r"0: 1
+        1: 0,2
+        2: 1,3
+        3: 2"
+            .parse()
+            .unwrap();
+
+        let colorer: StreamColoring = StreamColoring::init_unchecked(graph.vertices(), 2);
+
+        assert!(!colorer.parameters().adjustments().is_empty());
+        assert!(!colorer.parameters().has_warnings());
+    }
+
+    #[test]
+    fn estimated_delta() {
+        let graph: Graph<VertexId, ()> = r"0: 1,2,3
+        1: 0
+        2: 0
+        3: 0"
+            .parse()
+            .unwrap();
+        let mut degrees: DegreeSketch = DegreeSketch::init(4, Default::default());
+        graph.clone().for_each(|edge| degrees.feed(edge, true));
+
+        let colorer: StreamColoring =
+            StreamColoring::init_with_degrees_unchecked(graph.vertices(), &degrees);
+        assert!(colorer.delta >= 3);
+    }
+
+    #[test]
+    fn discovers_vertices() {
+        let graph = test_graph();
+        let n = *graph.vertices().into_iter().max().unwrap();
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+        let stream: Vec<_> = graph.clone().map(|e| (e, true)).collect();
+
+        let mut colorer: StreamColoring = StreamColoring::init_discovering_unchecked(n, delta);
+        let (mut forward, mut backward) = (colorer.clone(), colorer.clone());
+        stream.iter().for_each(|token| forward.feed(*token));
+        stream.iter().rev().for_each(|token| backward.feed(*token));
+
+        // The batches only depend on the vertex, not on when it was discovered
+        let discovered: HashSet<_> = graph.vertices().into_iter().copied().collect();
+        assert_eq!(forward.vertices(), &discovered);
+        for v in discovered.iter() {
+            for b in 0..BATCHES {
+                assert_eq!(
+                    batch(&forward.color_batches, *v, b),
+                    batch(&backward.color_batches, *v, b)
+                );
+            }
+        }
+
+        // Each half discovers the vertices of its own edges
+        let (first, second) = stream.split_at(stream.len() / 2);
+        let mut other = colorer.clone();
+        first.iter().for_each(|token| colorer.feed(*token));
+        second.iter().for_each(|token| other.feed(*token));
+        colorer.merge(&other);
+        assert_eq!(colorer.vertices(), &discovered);
+        assert_eq!(colorer.sampled_degrees(), forward.sampled_degrees());
+    }
+
+    #[test]
+    fn noisy_stream() {
+        let stream: Vec<(Edge<VertexId, ()>, bool)> = UniformGraphDistribution::init(60, 150)
+            .with_noise(300)
+            .sample(&mut rand::thread_rng());
+        let net = net_edges(&stream);
+        let mut graph: Graph<VertexId, ()> = Graph::default();
+        net.iter().for_each(|edge| graph.add_edge(*edge));
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+
+        // The sketches are linear, so the noise leaves them as if only the remaining edges were fed
+        let mut noisy: StreamColoring = StreamColoring::init_discovering_unchecked(59, delta);
+        let mut clean = noisy.clone();
+        stream.iter().for_each(|token| noisy.feed(*token));
+        net.iter().for_each(|edge| clean.feed((*edge, true)));
+
+        assert_eq!(noisy.sampled_degrees(), clean.sampled_degrees());
+        assert_eq!(
+            noisy.recovery.query_ref().support(),
+            clean.recovery.query_ref().support()
+        );
+    }
+
+    #[test]
+    fn deleted_conflicts() {
+        let graph = test_graph();
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+
+        let mut colorer: StreamColoring = StreamColoring::init_unchecked(graph.vertices(), delta);
+        graph.clone().for_each(|edge| colorer.feed((edge, true)));
+        graph.clone().for_each(|edge| colorer.feed((edge, false)));
+
+        // Nothing is left to recover, and every vertex keeps a color of its first batch
+        let coloring = colorer.query().unwrap();
+        assert_eq!(coloring.len(), graph.vertices().len());
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn validated() {
+        // Disjoint cliques, each of them an almost-clique
+        let mut graph: Graph<VertexId, ()> = Graph::default();
+        for clique in 0..4 {
+            for u in 0..12 {
+                for v in u + 1..12 {
+                    graph.add_edge(Edge::init(12 * clique + u, 12 * clique + v));
+                }
+            }
+        }
+        let delta = 11;
+
+        // Every vertex samples every color, so that the almost-cliques can always be completed
+        let mut colorer: StreamColoring = StreamColoring::init_with_unchecked(
+            graph.vertices(),
+            delta,
+            AckParameters::default().with_alpha(1e6),
+        );
+        graph.clone().for_each(|edge| colorer.feed((edge, true)));
+
+        let coloring = colorer.query_validated(&graph).unwrap();
+        assert!(graph.is_proper(&coloring));
+    }
+
+    #[test]
+    fn last_vertex() {
+        // Two disjoint cliques on [0, 24), the last vertex of the universe in the second
+        let mut graph: Graph<VertexId, ()> = Graph::default();
+        for clique in 0..2 {
+            for u in 0..12 {
+                for v in u + 1..12 {
+                    graph.add_edge(Edge::init(12 * clique + u, 12 * clique + v));
+                }
+            }
+        }
+        let (n, delta) = (23, 11);
+
+        // Every vertex samples every color, and is sampled by the pair querier
+        let parameters = AckParameters {
+            alpha: 1e6,
+            querier_constant: 1e6,
+            ..Default::default()
+        };
+        let mut colorer: StreamColoring =
+            StreamColoring::init_discovering_with_unchecked(n, delta, parameters);
+        assert!(colorer.pair_querier.inner.contains(&n));
+        graph.clone().for_each(|edge| colorer.feed((edge, true)));
+
+        let coloring = colorer.query().unwrap();
+        assert!(coloring.contains(&n));
+        assert!(graph.is_proper(&coloring));
+    }
+
+    #[test]
+    fn memory_budget() {
+        let (n, delta) = (300, 12);
+        assert!(
+            StreamColoring::<PowerFiniteFieldHasher>::with_memory_budget(n, delta, 10_000)
+                .is_none()
+        );
+
+        let mut previous = 0;
+        for bytes in [400_000, 1_000_000, 4_000_000] {
+            let mut colorer: StreamColoring =
+                StreamColoring::with_memory_budget(n, delta, bytes).unwrap();
+            // A circulant graph, every vertex of degree `delta`
+            for u in 0..=n {
+                for d in 1..=delta as VertexId / 2 {
+                    colorer.feed((Edge::init(u, (u + d) % (n + 1)), true));
+                }
+            }
+
+            let used = colorer.memory_usage();
+            assert!(used <= bytes, "{} bytes for a budget of {}", used, bytes);
+            // A larger budget buys larger structures
+            assert!(used > previous);
+            previous = used;
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn unknown_vertex() {
+        let mut colorer: StreamColoring =
+            StreamColoring::init_unchecked(vec![&0, &1].into_iter().collect(), 1);
+        colorer.feed((Edge::init(1, 2), true));
+    }
+
+    #[test]
+    #[should_panic]
+    fn discovered_vertex_beyond_bound() {
+        let mut colorer: StreamColoring = StreamColoring::init_discovering_unchecked(10, 1);
+        colorer.feed((Edge::init(3, 11), true));
+    }
+
+    #[test]
+    fn palette_graph_colors() {
+        // The colors of the batches are also vertices of the graph, and must not be confused with them
+        let conflict_graph: Graph<VertexId, ()> = r"0: 1
+        1: 0"
+            .parse()
+            .unwrap();
+        let mut color_batches = ColorBatches::init(0, 0.0, 2);
+        for v in 0..2 {
+            color_batches.insert(v, vec![vec![], vec![], vec![v as Color, 1 - v as Color]]);
+        }
+
+        let clique = AlmostClique::init((0..2).collect(), &conflict_graph);
+        let matching = palette_matching(&clique, &Coloring::new(), &conflict_graph, &color_batches);
+        assert_eq!(matching.len(), 2);
+
+        let mut coloring = Coloring::new();
+        assert!(assign_matching(matching, &mut coloring, &conflict_graph));
+        assert_eq!(coloring.len(), 2);
+        assert!(conflict_graph.is_proper(&coloring));
+    }
+
+    #[test]
+    fn clashing_matchings() {
+        // Two almost-cliques, joined by the conflict edge (1, 2), both matched independently to the color 7
+        let conflict_graph: Graph<VertexId, ()> = r"0: 1
+        1: 0,2
+        2: 1,3
+        3: 2"
+            .parse()
+            .unwrap();
+        let mut coloring = Coloring::new();
+        coloring.insert(0, 3);
+        coloring.insert(3, 4);
+
+        let matching = |v: VertexId| vec![Edge::init(COLOR_NODES + 7, v)].into_iter().collect();
+        assert!(assign_matching(matching(1), &mut coloring, &conflict_graph));
+        assert!(!assign_matching(
+            matching(2),
+            &mut coloring,
+            &conflict_graph
+        ));
+
+        assert_eq!(coloring.get(&1), Some(&7));
+        assert!(!coloring.contains(&2));
+        assert!(conflict_graph.is_partial(&coloring));
+    }
+
     #[test]
     fn tester() {
         let graph = test_graph();
@@ -528,26 +1763,21 @@ mod test {
 
         println!("Delta: {:?}", &delta);
 
-        let mut colorer = StreamColoring::init(
+        let mut colorer: StreamColoring = StreamColoring::init_unchecked(
             // This should change, we should pass in the graph and it should deal with converting this into an "n"
             graph.vertices(),
             delta,
         );
 
-        graph
-            .clone()
-            .into_iter()
-            .for_each(|e| colorer.feed((e, true)));
+        let stream: Vec<_> = graph.edges().map(|e| (e, true)).collect();
+        stream.iter().for_each(|token| colorer.feed(*token));
 
         println!("Completed Stream");
 
-        let coloring = colorer.query(&graph).unwrap();
+        let coloring = colorer.query().unwrap();
 
-        println!(
-            "Colors Used: {:?}",
-            coloring.values().into_iter().unique().count()
-        );
+        println!("Colors Used: {:?}", coloring.num_classes());
 
-        assert!(graph.is_proper(&coloring));
+        assert_eq!(verify(&coloring, stream), Verdict::Proper);
     }
 }
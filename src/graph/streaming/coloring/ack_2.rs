@@ -1,35 +1,53 @@
-use std::{
-    collections::{HashMap, HashSet},
-    convert::TryInto,
-    hash::Hash,
-    time,
-};
-
-use algebraics::traits::{CeilLog2, FloorLog2};
-use edge::EdgeDestination;
-use itertools::Itertools;
-use num_integer::{binomial, Roots};
-use num_traits::Pow;
-use rand::{distributions::Bernoulli, prelude::Distribution};
-
+//! Sparse-Dense Decomposition
+//!
+//! The decomposition of [Harris, Schneider and Su](https://arxiv.org/abs/1607.05567), computed in a single pass as in the
+//! [ACK paper](https://arxiv.org/pdf/1807.08886.pdf). Two adjacent vertices are friends when they share at least `(1 - δ)Δ`
+//! neighbors, a vertex is dense when at least `(1 - δ)Δ` of its neighbors are friends, and the components of the friend edges
+//! between dense vertices are almost-cliques. Every other vertex is sparse.
+//!
+//! The pass keeps the neighborhoods of vertices sampled with probability `p`, and a sample of the edges. The overlap of the
+//! endpoints of a sampled edge is estimated from the sampled vertices adjacent to both, a pair query, and the friends of a vertex
+//! from its sampled edges.
+
+use std::collections::HashSet;
+
+use algebraics::traits::CeilLog2;
+use num_integer::binomial;
+use rand::seq::IteratorRandom;
+
+use super::almost_clique::AlmostClique;
 use crate::{
     graph::{
-        edge,
         streaming::{
-            sampling::l0_sampling::L0Sampler,
-            sparse_recovery::s_sparse::{SparseRecovery, SparseRecoveryOutput},
+            connectivity::Components,
+            sketch_bank::VertexSketchBank,
+            sparse_recovery::{s_sparse::SparseRecovery, SparseRecoveryOutput},
+            Sketch, StreamProcessor,
         },
-        Edge, Graph, Graphed,
+        Edge, Graph, Graphed, VertexId,
+    },
+    utils::{
+        hash_function::HashFunction,
+        parameters::ParameterLog,
+        slab::{sorted_intersection_count, SliceArena},
     },
-    utils::hash_function::HashFunction,
 };
 
+/// Samples up to `k` distinct items from the support of a turnstile stream.
+///
+/// Rather than running `k` independent L0 samplers (which may all land on the same item), the
+/// stream is subsampled into nested levels, where level `l` keeps an item with probability `2^-l`.
+/// Each level is fed into an `s`-sparse recovery structure with `s = 4k`, so some level holds
+/// between `k` and `s` surviving items with good probability, and all of them can be recovered.
+///
+/// Since items are read off a single recovered support, the output never contains duplicates.
 #[derive(Clone, Debug)]
-struct KSampler<H>
+pub struct KSampler<H>
 where
     H: HashFunction + Clone,
 {
-    samplers: Vec<L0Sampler<H>>,
+    k: u64,
+    levels: Vec<(SparseRecovery<H>, H)>,
 }
 
 impl<H> KSampler<H>
@@ -37,204 +55,433 @@ where
     H: HashFunction + Clone,
 {
     pub fn init(n: u64, k: u64, delta: f32) -> Self {
-        let samplers = (0..k)
-            .into_iter()
-            .map(|_| L0Sampler::init(n, delta))
+        let n_pow = n.next_power_of_two();
+
+        let levels = (0..=n_pow.ceil_log2().unwrap_or_default() as u32)
+            .map(|l| {
+                let recovery = SparseRecovery::init(n, 4 * k, delta);
+                let hash_function = H::init(n_pow, 2_u64.pow(l));
+
+                (recovery, hash_function)
+            })
             .collect();
 
-        Self { samplers }
+        Self { k, levels }
     }
 
     pub fn feed(&mut self, token: (u64, bool)) {
-        self.samplers
-            .iter_mut()
-            .for_each(|sampler| sampler.feed(token));
+        let (j, _) = token;
+
+        self.levels.iter_mut().for_each(|(recovery, hasher)| {
+            if hasher.is_zero(j) {
+                recovery.feed(token)
+            }
+        })
     }
 
+    /// Returns `min(k, |support|)` distinct items of the support, with high probability.
+    ///
+    /// We walk the levels from the densest to the sparsest, and sample `k` items from the first
+    /// level that recovers at least `k` of them. If no level does, the support must have been
+    /// smaller than `k`, so we return the largest support we recovered.
     pub fn query(self) -> Vec<u64> {
-        self.samplers
-            .into_iter()
-            .filter_map(|s| s.query())
-            .map(|(e, _)| e)
-            .collect()
+        let Self { k, levels } = self;
+        let mut rng = rand::thread_rng();
+
+        let mut best: Vec<u64> = vec![];
+        for (recovery, _) in levels {
+            if let SparseRecoveryOutput::Recovered(support) = recovery.query() {
+                if support.len() as u64 >= k {
+                    return support
+                        .keys()
+                        .copied()
+                        .choose_multiple(&mut rng, k as usize);
+                }
+                if support.len() > best.len() {
+                    best = support.keys().copied().collect();
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Copy the sampler over the same domain, but with new random components
+    pub fn random_copy(&self) -> Self {
+        Self {
+            k: self.k,
+            levels: self
+                .levels
+                .iter()
+                .map(|(recovery, hasher)| (recovery.random_copy(), hasher.random_copy()))
+                .collect(),
+        }
+    }
+}
+
+impl<H> Sketch for KSampler<H>
+where
+    H: HashFunction + Clone,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        KSampler::feed(self, token)
     }
 }
 
-struct HSSDecomp<H>
+impl<H> StreamProcessor<(u64, bool), Vec<u64>> for KSampler<H>
+where
+    H: HashFunction + Clone,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        KSampler::feed(self, token)
+    }
+
+    fn query(self) -> Vec<u64> {
+        KSampler::query(self)
+    }
+}
+
+/// A sparse-dense decomposition of the vertices `[n]`: every vertex is either sparse, or in exactly one almost-clique
+///
+/// These are the two sets the ACK [StreamColoring](super::ack::StreamColoring) colors separately.
+#[derive(Clone, Debug)]
+pub struct Decomposition {
+    pub sparse: HashSet<VertexId>,
+    pub almost_cliques: Vec<AlmostClique>,
+    /// The sampled edges whose endpoints were found to be friends
+    pub friends: Graph<VertexId, ()>,
+}
+
+impl Decomposition {
+    pub fn is_sparse(&self, vertex: &VertexId) -> bool {
+        self.sparse.contains(vertex)
+    }
+
+    /// Index of the almost-clique of the vertex, `None` if it is sparse
+    pub fn almost_clique_of(&self, vertex: &VertexId) -> Option<usize> {
+        self.almost_cliques
+            .iter()
+            .position(|clique| clique.contains(vertex))
+    }
+}
+
+/// Computes a [Decomposition] of the graph of a turnstile stream of edges
+pub struct HSSDecomp<H>
 where
     H: HashFunction + Clone,
 {
     // Data
-    pub inner: HashMap<u32, KSampler<H>>,
+    /// The neighborhoods of the sampled vertices
+    pub inner: VertexSketchBank<KSampler<H>>,
     edges: KSampler<H>,
+    /// Edges of the stream, insertions minus deletions
+    edge_count: i64,
 
     // Metadata
-    n: u32,
+    n: VertexId,
     delta: u64,
-    p: f32,
+    p: f64,
     del: f64,
+    parameters: ParameterLog,
 }
 
 impl<H> HSSDecomp<H>
 where
     H: HashFunction + Clone,
 {
-    fn all(graph: Graph<u32, ()>) -> Graph<u32, ()> {
-        let n = graph.vertices().len() as u32;
+    /// Decompose a graph in memory, by streaming its edges
+    pub fn decompose(graph: &Graph<VertexId, ()>, eps: f64) -> crate::Result<Decomposition> {
+        let n = graph.vertices().into_iter().max().map_or(0, |v| v + 1);
         let delta = graph
             .adj_list()
-            .iter()
-            .map(|(_, n)| n.len())
+            .values()
+            .map(|neighbors| neighbors.len())
             .max()
             .unwrap_or_default() as u64;
 
-        let eps = 20.0;
-
-        let mut str = Self::init(n, delta, eps);
-
-        for edge in graph.clone() {
-            str.feed((edge, true));
+        let mut decomposition = Self::init(n, delta, eps)?;
+        for edge in graph.edges() {
+            decomposition.feed((edge, true));
         }
-
-        str.query(&graph)
+        Ok(decomposition.query())
     }
-    fn init(n: u32, delta: u64, eps: f32) -> Self {
+
+    /// Initialize the decomposition of a graph over the vertices `[n]`, of maximum degree `delta`, with `δ = ε / 10`
+    pub fn init(n: VertexId, delta: u64, eps: f64) -> crate::Result<Self> {
         let del = eps / 10.0;
 
-        let p = (5.0 * (n as f32).log2()) / (del.pow(2) as f32 * (delta as f32));
+        let mut parameters = ParameterLog::default();
+        let p = parameters.probability(
+            "[HSSDecomp] p",
+            (5.0 * (n.max(2) as f64).log2()) / (del.powi(2) * (delta.max(1) as f64)),
+        );
 
-        let thresh = (1.0 - (1.5 * del)) as f64 * (delta as f64) * (p as f64);
+        // Sampled edges
+        let k = n as f64 * (n.max(2) as f64).log2() / del.powi(2);
 
-        let k = n as f64 * (n as f64).log2() / (del.pow(2) as f64);
-        let vertex_threshold = (1.0 - del as f64) * (k / n as f64);
+        let base = KSampler::init(n, delta.max(1), 0.01);
+        let inner = VertexSketchBank::init(n, p, |_| base.random_copy())?;
 
-        println!(
-            "p: {}, n: {}, delta: {}, eps: {}, thresh: {}, k: {}, vertex_threshold: {}",
-            p, n, delta, eps, thresh, k, vertex_threshold
-        );
+        let edges = KSampler::init(binomial(n, 2).max(1), k.ceil() as u64, 0.01);
+        Ok(Self {
+            inner,
+            edges,
+            edge_count: 0,
+            n,
+            delta,
+            p,
+            del,
+            parameters,
+        })
+    }
 
-        let bern = Bernoulli::new(p as f64)
-            .unwrap_or_else(|_| panic!("[PairQuerier] Invalid Probability: {}", p));
+    /// Parameters that had to be adjusted to be valid for this graph
+    pub fn parameters(&self) -> &ParameterLog {
+        &self.parameters
+    }
 
-        let mut rng = rand::thread_rng();
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
 
-        let base = KSampler::init(n as u64, delta, 0.01);
-        let inner: HashMap<u32, KSampler<H>> = (0..n)
-            .into_iter()
-            .filter(|_| bern.sample(&mut rng))
-            .map(|v| (v, base.clone()))
-            .collect();
+        self.edge_count += if c { 1 } else { -1 };
+        self.edges.feed((edge.to_d1(), c));
+        self.inner.feed(token);
+    }
 
-        let edges = KSampler::init(binomial(n.into(), 2), k as u64, 0.01);
-        Self {
+    pub fn query(self) -> Decomposition {
+        let Self {
             inner,
             edges,
-            del: del.into(),
-            delta,
+            edge_count,
             n,
+            delta,
             p,
+            del,
+            ..
+        } = self;
+        let delta = delta as f64;
+
+        // The sampled vertices adjacent to each vertex, its witnesses
+        let witness_pairs = inner
+            .query::<Vec<u64>>()
+            .into_iter()
+            .flat_map(|(w, neighbors)| {
+                neighbors
+                    .into_iter()
+                    .filter(move |u| *u != w && *u < n)
+                    .map(move |u| (u as usize, w))
+            })
+            .collect();
+        let witnesses = SliceArena::group(n as usize, witness_pairs);
+
+        // PairQuery Phase: the sampled edges whose endpoints share enough witnesses are friends
+        let sampled: Vec<Edge<VertexId, ()>> =
+            edges.query().into_iter().map(Edge::from_d1).collect();
+        let thresh = (1.0 - 1.5 * del) * delta * p;
+
+        let mut friends = Graph::default();
+        for edge in sampled.iter() {
+            let (u, v) = edge.vertices();
+            let overlap =
+                sorted_intersection_count(witnesses.get(*u as usize), witnesses.get(*v as usize));
+            if overlap as f64 >= thresh {
+                friends.add_edge(*edge);
+            }
         }
-    }
 
-    pub fn feed(&mut self, token: (Edge<u32, ()>, bool)) {
-        let (j, c) = token;
-        let (u, v) = j.vertices();
+        // A dense vertex has nearly Δ friends, of which a fraction `q` of sampled edges
+        let q = if edge_count > 0 {
+            (sampled.len() as f64 / edge_count as f64).min(1.0)
+        } else {
+            1.0
+        };
+        let dense: HashSet<VertexId> = friends
+            .adj_list()
+            .iter()
+            .filter(|(_, neighbors)| neighbors.len() as f64 >= (1.0 - del) * q * delta)
+            .map(|(v, _)| *v)
+            .collect();
 
-        self.edges.feed((j.to_d1(), c));
+        // Almost-cliques are the large components of the friend edges between dense vertices
+        let mut components = Components::init(n);
+        for edge in friends.clone() {
+            let (u, v) = edge.vertices();
+            if dense.contains(u) && dense.contains(v) {
+                components.union(*u, *v);
+            }
+        }
+        let min_size = ((1.0 - del) * delta).max(1.0);
+        let almost_cliques: Vec<AlmostClique> = components
+            .members()
+            .into_iter()
+            .filter(|members| members.len() as f64 >= min_size && dense.contains(&members[0]))
+            .map(|members| AlmostClique::init(members.into_iter().collect(), &friends))
+            .collect();
+
+        let sparse = (0..n)
+            .filter(|v| !almost_cliques.iter().any(|clique| clique.contains(v)))
+            .collect();
 
-        self.inner.entry(*u).and_modify(|e| {
-            e.feed((*v as u64, c));
-        });
-        self.inner.entry(*v).and_modify(|e| {
-            e.feed((*u as u64, c));
-        });
+        Decomposition {
+            sparse,
+            almost_cliques,
+            friends,
+        }
     }
+}
 
-    pub fn query(self, actual_graph: &Graph<u32, ()>) -> Graph<u32, ()> {
-        // PairQuery Phase
+impl<H> StreamProcessor<(Edge<VertexId, ()>, bool), Decomposition> for HSSDecomp<H>
+where
+    H: HashFunction + Clone,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        HSSDecomp::feed(self, token)
+    }
 
-        let Self { inner, edges, .. } = self;
+    fn query(self) -> Decomposition {
+        HSSDecomp::query(self)
+    }
+}
 
-        let graph: Graph<u32, ()> = Graph::new(
-            inner
-                .into_iter()
-                .map(|(k, s)| {
-                    (
-                        k,
-                        s.query()
-                            .iter()
-                            .map(|e| EdgeDestination::init(*e as u32))
-                            .collect(),
-                    )
-                })
-                .collect(),
-        );
-        let edges = edges.query();
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+    use rand::prelude::Distribution;
 
-        // let mut graph = Graph::default();
+    use crate::{
+        random_graph::bernoulli::BernoulliGraphDistribution,
+        utils::hash_function::PowerFiniteFieldHasher,
+    };
 
-        // let thresh = (1.0 - (1.5 * self.del)) as f64 * (self.delta as f64) * (self.p as f64);
+    use super::*;
 
-        // println!("Thresh: {}", thresh);
+    /// Disjoint copies of `K_size`, followed by a cycle of sparse vertices
+    fn cliques_and_cycle(
+        cliques: VertexId,
+        size: VertexId,
+        cycle: VertexId,
+    ) -> Graph<VertexId, ()> {
+        let mut graph = Graph::default();
+        for c in 0..cliques {
+            let base = c * size;
+            for u in 0..size {
+                for v in u + 1..size {
+                    graph.add_edge(Edge::init(base + u, base + v));
+                }
+            }
+        }
+        let base = cliques * size;
+        for v in 0..cycle {
+            graph.add_edge(Edge::init(base + v, base + (v + 1) % cycle));
+        }
+        graph
+    }
 
-        // let mut incidency: HashMap<u32, u32> = HashMap::new();
+    #[test]
+    fn decomposition() {
+        let (cliques, size) = (4, 20);
+        let graph = cliques_and_cycle(cliques, size, 40);
+
+        let decomposition = HSSDecomp::<PowerFiniteFieldHasher>::decompose(&graph, 1.0).unwrap();
+
+        assert_eq!(decomposition.almost_cliques.len(), cliques as usize);
+        for clique in decomposition.almost_cliques.iter() {
+            assert_eq!(clique.len(), size as usize);
+            let first = *clique.vertices.iter().min().unwrap();
+            assert!((first..first + size).all(|v| clique.contains(&v)));
+            assert_eq!(clique.density, 1.0);
+        }
 
-        // for (u, neighbors) in queried.iter() {
-        //     for v in neighbors {
-        //         let edge = Edge::<u32, ()>::init(min(*u, *v), max(*u, *v));
-        //         let (u, v) = edge.vertices();
-        //         if u == v {
-        //             panic!("This should never happen");
-        //         }
-        //         let u_neighbors = queried.get(u);
-        //         let v_neighbors = queried.get(v);
+        assert_eq!(decomposition.sparse.len(), 40);
+        assert!((cliques * size..cliques * size + 40).all(|v| decomposition.is_sparse(&v)));
+        assert_eq!(
+            decomposition.almost_clique_of(&0),
+            decomposition.almost_clique_of(&(size - 1))
+        );
+        assert_ne!(
+            decomposition.almost_clique_of(&0),
+            decomposition.almost_clique_of(&size)
+        );
+    }
 
-        //         let overlap = u_neighbors
-        //             .and_then(|u| v_neighbors.map(|v| u.intersection(&v).count() as f64))
-        //             .unwrap_or_default();
+    #[test]
+    fn random_graph_partition() {
+        let graph = test_graph(100);
+        let decomposition = HSSDecomp::<PowerFiniteFieldHasher>::decompose(&graph, 1.0).unwrap();
+
+        // Every vertex is either sparse, or in a single almost-clique
+        for v in graph.vertices() {
+            let cliques = decomposition
+                .almost_cliques
+                .iter()
+                .filter(|clique| clique.contains(v))
+                .count();
+            assert_eq!(cliques + decomposition.is_sparse(v) as usize, 1);
+        }
+    }
 
-        //         if overlap >= thresh {
-        //             graph.add_edge(edge);
-        //             incidency.entry(*u).and_modify(|u| *u += 1);
-        //             incidency.entry(*v).and_modify(|u| *u += 1);
-        //         }
-        //     }
-        // }
+    #[test]
+    fn deletions() {
+        let graph = cliques_and_cycle(1, 20, 0);
+        let mut decomposition = HSSDecomp::<PowerFiniteFieldHasher>::init(20, 19, 0.5).unwrap();
+        for edge in graph.edges() {
+            decomposition.feed((edge, true));
+        }
+        // Removing the clique again leaves only sparse vertices
+        for edge in graph {
+            decomposition.feed((edge, false));
+        }
 
-        // let k = 100.0 * self.n as f64 * (self.n as f64).log2() / (self.del.pow(2));
+        let decomposition = decomposition.query();
+        assert!(decomposition.almost_cliques.is_empty());
+        assert_eq!(decomposition.sparse.len(), 20);
+    }
 
-        // let vertex_threshold = 1.0 - self.del as f64 * (k / self.n as f64);
+    #[test]
+    fn k_sampler_small_support() {
+        let support: HashSet<u64> = vec![3, 17, 256, 511, 900].into_iter().collect();
+        let mut sampler = KSampler::<PowerFiniteFieldHasher>::init(1000, 10, 0.01);
 
-        // for (u, count) in incidency {
-        //     if (count as f64) < vertex_threshold {
-        //         graph.remove_vertex(&u);
-        //     }
-        // }
+        support.iter().for_each(|j| sampler.feed((*j, true)));
 
-        // println!("{}", graph);
+        let sample = sampler.query();
 
-        Default::default()
+        assert_eq!(sample.len(), support.len());
+        assert!(sample.iter().all(|j| support.contains(j)));
+        assert_eq!(sample.iter().unique().count(), sample.len());
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::{
-        random_graph::bernoulli::BernoulliGraphDistribution,
-        utils::hash_function::PowerFiniteFieldHasher,
-    };
+    #[test]
+    fn k_sampler_large_support() {
+        let support: HashSet<u64> = (0..1000).step_by(3).collect();
+        let mut sampler = KSampler::<PowerFiniteFieldHasher>::init(1000, 10, 0.01);
 
-    use super::*;
+        support.iter().for_each(|j| sampler.feed((*j, true)));
+
+        let sample = sampler.query();
+
+        assert_eq!(sample.len(), 10);
+        assert!(sample.iter().all(|j| support.contains(j)));
+        assert_eq!(sample.iter().unique().count(), sample.len());
+    }
 
     #[test]
-    fn tester() {
-        HSSDecomp::<PowerFiniteFieldHasher>::all(test_graph(100));
+    fn k_sampler_deletions() {
+        let mut sampler = KSampler::<PowerFiniteFieldHasher>::init(1000, 10, 0.01);
+
+        (0..50).for_each(|j| sampler.feed((j, true)));
+        (0..47).for_each(|j| sampler.feed((j, false)));
+
+        let mut sample = sampler.query();
+        sample.sort_unstable();
+
+        assert_eq!(sample, vec![47, 48, 49]);
     }
 
-    fn test_graph(n: u32) -> Graph<u32, ()> {
+    fn test_graph(n: VertexId) -> Graph<VertexId, ()> {
         let mut rng = rand::thread_rng();
         let p = 0.7 / (n as f64).log2();
-        BernoulliGraphDistribution::<u32>::init(n as u32, p)
+        BernoulliGraphDistribution::<VertexId>::init(n as VertexId, p)
             .unwrap()
             .sample(&mut rng)
     }
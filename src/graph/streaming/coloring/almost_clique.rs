@@ -0,0 +1,312 @@
+//! Almost-Cliques of a sparse-dense decomposition
+//!
+//! Following the [ACK paper](https://arxiv.org/pdf/1807.08886.pdf), given a graph with maximum degree `Δ` and a parameter `δ`,
+//! an almost-clique is a set of vertices `C` such that:
+//! 1. `(1 - 6δ)Δ <= |C| <= (1 + 6δ)Δ`
+//! 2. Every vertex in `C` has at least `(1 - 7δ)Δ` neighbors inside of `C`
+//! 3. Every vertex in `C` has at most `7δΔ` neighbors outside of `C`
+//!
+//! As a consequence, an almost-clique has diameter at most 2.
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::Display,
+};
+
+use crate::graph::{
+    static_a::search::{Depths, Search},
+    EdgeDestination, Graph, Graphed, VertexId,
+};
+
+/// An almost-clique produced by a sparse-dense decomposition
+#[derive(Clone, Debug)]
+pub struct AlmostClique {
+    /// The vertices of the almost-clique
+    pub vertices: HashSet<VertexId>,
+    /// Fraction of the pairs of vertices within the almost-clique that are edges of the graph it was measured against
+    pub density: f64,
+}
+
+/// Reasons for which a set of vertices fails to be an almost-clique
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlmostCliqueViolation {
+    /// The almost-clique has fewer than `(1 - 6δ)Δ` vertices
+    TooSmall { size: usize, min: f64 },
+    /// The almost-clique has more than `(1 + 6δ)Δ` vertices
+    TooLarge { size: usize, max: f64 },
+    /// A vertex has fewer than `(1 - 7δ)Δ` neighbors inside of the almost-clique
+    FewInsideNeighbors {
+        vertex: VertexId,
+        count: usize,
+        min: f64,
+    },
+    /// A vertex has more than `7δΔ` neighbors outside of the almost-clique
+    ManyOutsideNeighbors {
+        vertex: VertexId,
+        count: usize,
+        max: f64,
+    },
+    /// The almost-clique has a diameter larger than 2, `None` if it is not even connected
+    Diameter(Option<usize>),
+}
+
+impl Display for AlmostCliqueViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooSmall { size, min } => {
+                write!(f, "{} vertices, fewer than the minimum of {:.1}", size, min)
+            }
+            Self::TooLarge { size, max } => {
+                write!(f, "{} vertices, more than the maximum of {:.1}", size, max)
+            }
+            Self::FewInsideNeighbors { vertex, count, min } => write!(
+                f,
+                "vertex {} has {} neighbors inside, fewer than {:.1}",
+                vertex, count, min
+            ),
+            Self::ManyOutsideNeighbors { vertex, count, max } => write!(
+                f,
+                "vertex {} has {} neighbors outside, more than {:.1}",
+                vertex, count, max
+            ),
+            Self::Diameter(Some(diameter)) => write!(f, "diameter {}, larger than 2", diameter),
+            Self::Diameter(None) => write!(f, "not connected"),
+        }
+    }
+}
+
+impl Error for AlmostCliqueViolation {}
+
+impl AlmostClique {
+    /// Create a new almost-clique, measuring its internal density against the provided graph
+    pub fn init<G>(vertices: HashSet<VertexId>, graph: &G) -> Self
+    where
+        G: Graphed<VertexId, ()>,
+    {
+        let mut clique = Self {
+            vertices,
+            density: 0.0,
+        };
+
+        let pairs = clique.len() * clique.len().saturating_sub(1) / 2;
+        if pairs > 0 {
+            clique.density = clique.edges(graph) as f64 / pairs as f64;
+        }
+
+        clique
+    }
+
+    /// Number of vertices within the almost-clique
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Whether the almost-clique has no vertices
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Whether the vertex belongs to the almost-clique
+    pub fn contains(&self, vertex: &VertexId) -> bool {
+        self.vertices.contains(vertex)
+    }
+
+    /// Number of edges of the graph with both endpoints inside of the almost-clique
+    pub fn edges<G>(&self, graph: &G) -> usize
+    where
+        G: Graphed<VertexId, ()>,
+    {
+        self.vertices
+            .iter()
+            .map(|v| self.inside_neighbors(graph, v))
+            .sum::<usize>()
+            / 2
+    }
+
+    /// Number of pairs of vertices inside of the almost-clique that are not edges of the graph
+    pub fn non_edges<G>(&self, graph: &G) -> usize
+    where
+        G: Graphed<VertexId, ()>,
+    {
+        self.len() * self.len().saturating_sub(1) / 2 - self.edges(graph)
+    }
+
+    /// Largest distance between two vertices of the almost-clique, using only edges inside of the almost-clique
+    ///
+    /// Returns `None` if the almost-clique is not connected
+    pub fn diameter<G>(&self, graph: &G) -> Option<usize>
+    where
+        G: Graphed<VertexId, ()>,
+    {
+        let induced: Graph<VertexId, ()> = Graph::new(
+            self.vertices
+                .iter()
+                .map(|v| {
+                    let neighbors: HashSet<EdgeDestination<VertexId, ()>> = graph
+                        .get_neighbors(v)
+                        .map(|neighbors| {
+                            neighbors
+                                .iter()
+                                .filter(|n| self.contains(&n.destination))
+                                .cloned()
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    (*v, neighbors)
+                })
+                .collect::<HashMap<_, _>>(),
+        );
+
+        self.vertices.iter().try_fold(0, |diameter, v| {
+            let mut depths = Depths::init(*v);
+            induced.breadth_first(&mut depths, vec![v]);
+
+            if depths.data.len() < self.len() {
+                return None;
+            }
+
+            depths
+                .data
+                .values()
+                .max()
+                .map(|eccentricity| diameter.max(*eccentricity))
+        })
+    }
+
+    /// Validate the almost-clique invariants, against the graph of maximum degree `delta`, with parameter `del`
+    ///
+    /// Returns every invariant that does not hold.
+    pub fn validate<G>(
+        &self,
+        graph: &G,
+        delta: u32,
+        del: f64,
+    ) -> Result<(), Vec<AlmostCliqueViolation>>
+    where
+        G: Graphed<VertexId, ()>,
+    {
+        let delta = delta as f64;
+        let mut violations = vec![];
+
+        let (min, max) = ((1.0 - 6.0 * del) * delta, (1.0 + 6.0 * del) * delta);
+        if (self.len() as f64) < min {
+            violations.push(AlmostCliqueViolation::TooSmall {
+                size: self.len(),
+                min,
+            });
+        }
+        if (self.len() as f64) > max {
+            violations.push(AlmostCliqueViolation::TooLarge {
+                size: self.len(),
+                max,
+            });
+        }
+
+        let (min_inside, max_outside) = ((1.0 - 7.0 * del) * delta, 7.0 * del * delta);
+        for vertex in self.vertices.iter() {
+            let degree = graph
+                .get_neighbors(vertex)
+                .map(|n| n.len())
+                .unwrap_or_default();
+            let inside = self.inside_neighbors(graph, vertex);
+
+            if (inside as f64) < min_inside {
+                violations.push(AlmostCliqueViolation::FewInsideNeighbors {
+                    vertex: *vertex,
+                    count: inside,
+                    min: min_inside,
+                });
+            }
+            if ((degree - inside) as f64) > max_outside {
+                violations.push(AlmostCliqueViolation::ManyOutsideNeighbors {
+                    vertex: *vertex,
+                    count: degree - inside,
+                    max: max_outside,
+                });
+            }
+        }
+
+        match self.diameter(graph) {
+            Some(diameter) if diameter <= 2 => {}
+            diameter => violations.push(AlmostCliqueViolation::Diameter(diameter)),
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    fn inside_neighbors<G>(&self, graph: &G, vertex: &VertexId) -> usize
+    where
+        G: Graphed<VertexId, ()>,
+    {
+        graph
+            .get_neighbors(vertex)
+            .map(|neighbors| {
+                neighbors
+                    .iter()
+                    .filter(|n| self.contains(&n.destination))
+                    .count()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::Edge;
+
+    /// A clique on [0, 10) missing the edge (0, 1), with a pendant vertex 10 attached to 0
+    fn test_graph() -> Graph<VertexId, ()> {
+        let mut graph = Graph::default();
+        for u in 0..10 {
+            for v in (u + 1)..10 {
+                if (u, v) != (0, 1) {
+                    graph.add_edge(Edge::init(u, v));
+                }
+            }
+        }
+        graph.add_edge(Edge::init(0, 10));
+        graph
+    }
+
+    #[test]
+    fn measures_density() {
+        let graph = test_graph();
+        let clique = AlmostClique::init((0..10).collect(), &graph);
+
+        assert_eq!(clique.edges(&graph), 44);
+        assert_eq!(clique.non_edges(&graph), 1);
+        assert!((clique.density - 44.0 / 45.0).abs() < f64::EPSILON);
+        assert_eq!(clique.diameter(&graph), Some(2));
+    }
+
+    #[test]
+    fn valid_almost_clique() {
+        let graph = test_graph();
+        let clique = AlmostClique::init((0..10).collect(), &graph);
+
+        assert_eq!(clique.validate(&graph, 10, 0.05), Ok(()));
+    }
+
+    #[test]
+    fn invalid_almost_clique() {
+        let graph = test_graph();
+        let clique = AlmostClique::init((0..11).collect(), &graph);
+
+        let violations = clique.validate(&graph, 10, 0.01).unwrap_err();
+
+        assert!(violations.contains(&AlmostCliqueViolation::TooLarge {
+            size: 11,
+            max: (1.0 + 6.0 * 0.01) * 10.0
+        }));
+        assert!(violations.iter().any(|v| matches!(
+            v,
+            AlmostCliqueViolation::FewInsideNeighbors { vertex: 10, .. }
+        )));
+    }
+}
@@ -0,0 +1,476 @@
+//! Bank of Colorers
+//!
+//! The [bcg](super::bcg) colorer needs a guess `k` of the degeneracy of the graph, which is unknown until the stream ends. A
+//! [ColorerBank] runs one colorer for each guess `k = 1, 2, 4, ...` over the same stream, and answers with the coloring of the
+//! smallest guess that succeeds.
+//!
+//! When the question is only whether the graph can be colored within a budget of colors, [ColorerBank::with_budget] runs the
+//! guesses up to the budget, since a larger guess yields more colors, and its query stops at the first coloring that fits.
+//!
+//! Every token goes to every colorer, so on long streams [ColorerBank::feed_batch] feeds batches of tokens to the colorers in
+//! parallel with the `rayon` feature, and [ColorerBank::query_best] queries them all for the coloring with the fewest colors.
+
+use std::{fmt::Debug, iter::successors, mem::size_of};
+
+use num_integer::binomial;
+
+use crate::graph::{
+    static_a::partition::Partition,
+    streaming::{
+        provenance::{AlgorithmInfo, Provenance},
+        space::{SpaceReport, SpaceUsage},
+        Merge, StreamProcessor,
+    },
+    Edge, VertexId,
+};
+use crate::utils::hash_function::{HashFunction, PowerFiniteFieldHasher};
+
+use super::{
+    bcg::{ColorTuple, StreamColoring},
+    compute_s,
+};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Answer of a [ColorerBank]
+#[derive(Debug)]
+pub enum BankOutcome {
+    /// The colorer of the smallest guess whose coloring fits the budget, if any
+    Colored {
+        guess: u64,
+        coloring: Partition<VertexId, ColorTuple>,
+    },
+    /// No guess found a coloring within the budget, `fewest` being the fewest colors of the colorings found
+    OverBudget { fewest: Option<usize> },
+}
+
+impl BankOutcome {
+    /// Whether a coloring within the budget was found
+    pub fn is_colored(&self) -> bool {
+        matches!(self, Self::Colored { .. })
+    }
+
+    /// The witness coloring, `None` if over budget
+    pub fn coloring(self) -> Option<Partition<VertexId, ColorTuple>> {
+        match self {
+            Self::Colored { coloring, .. } => Some(coloring),
+            Self::OverBudget { .. } => None,
+        }
+    }
+}
+
+/// Colorers of the guesses of the degeneracy, fed the same stream
+///
+/// Guesses that end up with the same palette as a smaller one are dropped, they would only repeat its work.
+pub struct ColorerBank<F: HashFunction = PowerFiniteFieldHasher> {
+    n: VertexId,
+    /// Maximum number of colors of an acceptable coloring, unlimited if `None`
+    budget: Option<usize>,
+    /// Colorers in increasing order of their guess
+    colorers: Vec<(u64, StreamColoring<F>)>,
+}
+
+impl<F> ColorerBank<F>
+where
+    F: HashFunction + Clone,
+{
+    /// Colorers for the guesses `1, 2, 4, ...` up to `n / 2`
+    ///
+    /// - *n* : Size of the Universe (number of vertices)
+    /// - *del* : Error Parameter for the SparseRecovery of every colorer
+    pub fn init(n: VertexId, del: f32) -> Self {
+        Self::with_guesses(n, None, guesses(n, None), del)
+    }
+
+    /// Colorers for the guesses that may color the graph with at most `budget` colors
+    ///
+    /// A colorer of guess `k` succeeds on graphs of degeneracy at most `k`, with about `k + 1` colors, so the guesses are
+    /// `1, 2, 4, ...` below the budget, and `budget - 1` itself.
+    pub fn with_budget(n: VertexId, budget: usize, del: f32) -> Self {
+        if budget == 0 {
+            panic!("[ColorerBank] The budget must be at least one color");
+        }
+
+        Self::with_guesses(n, Some(budget), guesses(n, Some(budget)), del)
+    }
+
+    /// Colorers for the guesses `1, 2, 4, ...` up to `n / 2`, whose structures hold at most `bytes` together
+    ///
+    /// The budget trades the number of guesses against the sparsity `s` of the colorers. The largest guesses are dropped until
+    /// the others fit with the sparsity `s = C n log n` of [init](ColorerBank::init), capped at the number of potential edges,
+    /// and the remaining guesses share the memory equally, each with the largest sparsity that fits in its share, see
+    /// [StreamColoring::with_memory_budget]. Only once the first guess is left alone does the budget shrink its sparsity, and so
+    /// grow its palette. `None` if the first guess cannot recover a single edge within the budget.
+    pub fn with_memory_budget(n: VertexId, bytes: usize, del: f32) -> Option<Self> {
+        let mut guesses = guesses(n, None);
+        let bytes = bytes.checked_sub(size_of::<Self>())?;
+
+        // A colorer is stored along with its guess, and never recovers more than every edge
+        let s = (compute_s(n).ceil() as u64).min(binomial(n, 2).max(1));
+        let colorer = StreamColoring::<F>::bytes_bound(n, s, del) + size_of::<u64>();
+        guesses.truncate((bytes / colorer).clamp(1, guesses.len()));
+
+        let share = (bytes / guesses.len()).checked_sub(size_of::<u64>())?;
+        let base = StreamColoring::with_memory_budget(n, guesses[0], share, del)?;
+
+        Some(Self::from_base(n, None, guesses, base))
+    }
+
+    fn with_guesses(n: VertexId, budget: Option<usize>, guesses: Vec<u64>, del: f32) -> Self {
+        let base = StreamColoring::init(n, guesses[0], del);
+        Self::from_base(n, budget, guesses, base)
+    }
+
+    /// The colorers of the guesses, sharing the sparse recovery of the colorer of the first
+    fn from_base(
+        n: VertexId,
+        budget: Option<usize>,
+        guesses: Vec<u64>,
+        base: StreamColoring<F>,
+    ) -> Self {
+        let mut palette = base.palette_size();
+
+        let mut colorers = Vec::with_capacity(guesses.len());
+        colorers.push((guesses[0], base));
+        for k in guesses.into_iter().skip(1) {
+            if let Some(colorer) = colorers[0].1.new_k(n, k) {
+                if colorer.palette_size() != palette {
+                    palette = colorer.palette_size();
+                    colorers.push((k, colorer));
+                }
+            }
+        }
+
+        Self {
+            n,
+            budget,
+            colorers,
+        }
+    }
+
+    pub fn budget(&self) -> Option<usize> {
+        self.budget
+    }
+
+    /// The guesses being run, in increasing order
+    pub fn guesses(&self) -> impl Iterator<Item = u64> + '_ {
+        self.colorers.iter().map(|(k, _)| *k)
+    }
+
+    /// The colorers being run, with their guess
+    pub fn colorers(&self) -> impl Iterator<Item = (u64, &StreamColoring<F>)> {
+        self.colorers.iter().map(|(k, colorer)| (*k, colorer))
+    }
+
+    /// Feed a token to every colorer
+    pub fn feed<W: Debug + Default + Clone>(&mut self, edge: Edge<VertexId, W>, c: bool) {
+        for (_, colorer) in self.colorers.iter_mut() {
+            colorer.feed(edge.clone(), c);
+        }
+    }
+
+    /// Feed a batch of tokens to every colorer, in order
+    ///
+    /// With the `rayon` feature, each colorer is fed the whole batch on a thread of the rayon pool, so larger batches leave
+    /// the threads less time waiting on each other.
+    pub fn feed_batch(&mut self, tokens: &[(Edge<VertexId, ()>, bool)])
+    where
+        F: Send,
+    {
+        let feed = |(_, colorer): &mut (u64, StreamColoring<F>)| {
+            for (edge, c) in tokens {
+                colorer.feed(*edge, *c);
+            }
+        };
+
+        #[cfg(feature = "rayon")]
+        self.colorers.par_iter_mut().for_each(feed);
+        #[cfg(not(feature = "rayon"))]
+        self.colorers.iter_mut().for_each(feed);
+    }
+
+    /// Query every colorer, in parallel with the `rayon` feature, for the coloring with the fewest colors within the budget
+    ///
+    /// Ties go to the smaller guess. Unlike [query](ColorerBank::query), a larger guess may win, when the coloring of a smaller
+    /// one happens to use more colors.
+    pub fn query_best(self) -> BankOutcome
+    where
+        F: Send,
+    {
+        let budget = self.budget.unwrap_or(usize::MAX);
+        let query = |(guess, colorer): (u64, StreamColoring<F>)| {
+            colorer.query().map(|coloring| (guess, coloring))
+        };
+
+        #[cfg(feature = "rayon")]
+        let colorings: Vec<_> = self.colorers.into_par_iter().filter_map(query).collect();
+        #[cfg(not(feature = "rayon"))]
+        let colorings: Vec<_> = self.colorers.into_iter().filter_map(query).collect();
+
+        let best = colorings
+            .into_iter()
+            .min_by_key(|(guess, coloring)| (coloring.num_classes(), *guess));
+        match best {
+            Some((guess, coloring)) if coloring.num_classes() <= budget => {
+                BankOutcome::Colored { guess, coloring }
+            }
+            best => BankOutcome::OverBudget {
+                fewest: best.map(|(_, coloring)| coloring.num_classes()),
+            },
+        }
+    }
+
+    /// Query the colorers in increasing order of their guess, stopping at the first coloring within the budget
+    pub fn query(self) -> BankOutcome {
+        let budget = self.budget.unwrap_or(usize::MAX);
+
+        let mut fewest = None;
+        for (guess, colorer) in self.colorers {
+            if let Some(coloring) = colorer.query() {
+                let count = coloring.num_classes();
+                if count <= budget {
+                    return BankOutcome::Colored { guess, coloring };
+                }
+                fewest = Some(fewest.map_or(count, |fewest: usize| fewest.min(count)));
+            }
+        }
+
+        BankOutcome::OverBudget { fewest }
+    }
+}
+
+/// Guesses `1, 2, 4, ...` up to `n / 2`, or below the budget followed by `budget - 1`
+fn guesses(n: VertexId, budget: Option<usize>) -> Vec<u64> {
+    let limit = match budget {
+        Some(budget) => (budget as u64 - 1).max(1),
+        None => (n / 2).max(1),
+    };
+
+    let mut guesses: Vec<u64> = successors(Some(1_u64), |k| Some(k * 2))
+        .take_while(|k| *k <= limit)
+        .collect();
+    if budget.is_some() && guesses.last() != Some(&limit) {
+        guesses.push(limit);
+    }
+    guesses
+}
+
+impl<F> StreamProcessor<(Edge<VertexId, ()>, bool), BankOutcome> for ColorerBank<F>
+where
+    F: HashFunction + Clone,
+{
+    fn feed(&mut self, (edge, c): (Edge<VertexId, ()>, bool)) {
+        ColorerBank::feed(self, edge, c)
+    }
+
+    fn query(self) -> BankOutcome {
+        ColorerBank::query(self)
+    }
+}
+
+impl<F> Merge for ColorerBank<F>
+where
+    F: HashFunction,
+{
+    /// Both banks must run the same guesses, with the same colorers
+    fn merge(&mut self, other: &Self) {
+        if self.budget != other.budget || self.colorers.len() != other.colorers.len() {
+            panic!("[ColorerBank] Cannot merge banks with different guesses");
+        }
+
+        for ((k, colorer), (other_k, other_colorer)) in
+            self.colorers.iter_mut().zip(other.colorers.iter())
+        {
+            if k != other_k {
+                panic!("[ColorerBank] Cannot merge banks with different guesses");
+            }
+            colorer.merge(other_colorer);
+        }
+    }
+}
+
+impl<F> SpaceUsage for ColorerBank<F>
+where
+    F: HashFunction,
+{
+    /// One colorer per guess
+    fn space(&self) -> SpaceReport {
+        let colorers: Vec<SpaceReport> = self.colorers.iter().map(|(_, c)| c.space()).collect();
+
+        SpaceReport {
+            structure: "ColorerBank",
+            bound: "O(log n) · space of StreamColoring",
+            parameters: vec![
+                ("n", self.n as f64),
+                ("guesses", self.colorers.len() as f64),
+            ],
+            theoretical_bits: colorers.iter().map(|c| c.theoretical_bits).sum(),
+            measured_bytes: size_of::<Self>()
+                + self.colorers.capacity() * size_of::<(u64, StreamColoring<F>)>()
+                + colorers
+                    .iter()
+                    .map(|c| c.measured_bytes - size_of::<StreamColoring<F>>())
+                    .sum::<usize>(),
+            components: colorers,
+        }
+    }
+}
+
+impl<F> Provenance for ColorerBank<F>
+where
+    F: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        let mut params = vec![
+            ("n", self.n as f64),
+            ("guesses", self.colorers.len() as f64),
+        ];
+        if let Some(budget) = self.budget {
+            params.push(("budget", budget as f64));
+        }
+
+        AlgorithmInfo {
+            name: "ColorerBank (BCG)",
+            paper_ref: "Bera, Chakrabarti, Ghosh, ICALP 2020, https://arxiv.org/abs/1905.00566",
+            params,
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::streaming::coloring::verifier::verify;
+
+    /// Disjoint copies of `K_4`, of degeneracy 3
+    fn cliques(n: VertexId) -> Vec<Edge<VertexId, ()>> {
+        (0..n / 4)
+            .flat_map(|i| {
+                let base = 4 * i;
+                (0..4).flat_map(move |u| (u + 1..4).map(move |v| Edge::init(base + u, base + v)))
+            })
+            .collect()
+    }
+
+    fn run(bank: &mut ColorerBank, edges: &[Edge<VertexId, ()>]) {
+        for edge in edges {
+            bank.feed(*edge, true);
+        }
+    }
+
+    #[test]
+    fn guesses_of_budget() {
+        assert_eq!(guesses(64, None), vec![1, 2, 4, 8, 16, 32]);
+        assert_eq!(guesses(64, Some(1)), vec![1]);
+        assert_eq!(guesses(64, Some(5)), vec![1, 2, 4]);
+        assert_eq!(guesses(64, Some(7)), vec![1, 2, 4, 6]);
+    }
+
+    #[test]
+    fn within_budget() {
+        let n = 40;
+        let edges = cliques(n);
+
+        let mut bank = ColorerBank::with_budget(n, 6, 0.01);
+        assert_eq!(bank.budget(), Some(6));
+        run(&mut bank, &edges);
+
+        match bank.query() {
+            BankOutcome::Colored { coloring, .. } => {
+                assert!(coloring.num_classes() <= 6);
+                let stream = edges.iter().map(|edge| (*edge, true));
+                assert!(verify(&coloring, stream).is_proper());
+            }
+            outcome => panic!("expected a coloring, got {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn over_budget() {
+        let n = 40;
+
+        // K_4 needs 4 colors
+        let mut bank = ColorerBank::with_budget(n, 3, 0.01);
+        run(&mut bank, &cliques(n));
+
+        let outcome = bank.query();
+        assert!(!outcome.is_colored());
+        assert!(matches!(outcome, BankOutcome::OverBudget { fewest: Some(fewest) } if fewest >= 4));
+    }
+
+    #[test]
+    fn memory_budget() {
+        let n = 16;
+        assert!(
+            ColorerBank::<PowerFiniteFieldHasher>::with_memory_budget(n, 1_000, 0.01).is_none()
+        );
+        let bank = |bytes| ColorerBank::with_memory_budget(n, bytes, 0.01).unwrap();
+        let guesses = |bank: &ColorerBank| {
+            (
+                bank.guesses().collect::<Vec<_>>(),
+                bank.colorers[0].1.sparsity(),
+            )
+        };
+
+        // The largest guesses are dropped first, the sparsity only shrinks once the first guess is alone
+        let (small, medium, large) = (bank(20_000), bank(3_000_000), bank(10_000_000));
+        let (small, medium, large) = (guesses(&small), guesses(&medium), guesses(&large));
+        assert_eq!(small.0, vec![1]);
+        assert!(small.1 < medium.1);
+        assert!(1 < medium.0.len() && medium.0.len() < large.0.len());
+        assert_eq!(medium.1, large.1);
+        assert!(large.0.starts_with(&medium.0));
+
+        let bytes = 3_000_000;
+        let mut bank = bank(bytes);
+        run(&mut bank, &cliques(n));
+        assert!(bank.memory_usage() <= bytes);
+
+        let coloring = bank.query().coloring().unwrap();
+        assert!(coloring.num_classes() >= 4);
+    }
+
+    #[test]
+    fn batches() {
+        let n = 40;
+        let edges: Vec<_> = cliques(n).into_iter().map(|edge| (edge, true)).collect();
+
+        let mut bank: ColorerBank = ColorerBank::init(n, 0.01);
+        edges.chunks(7).for_each(|batch| bank.feed_batch(batch));
+        let guesses: Vec<u64> = bank.guesses().collect();
+
+        match bank.query_best() {
+            BankOutcome::Colored { guess, coloring } => {
+                assert!(guesses.contains(&guess));
+                assert!(coloring.num_classes() >= 4);
+                assert!(verify(&coloring, edges).is_proper());
+            }
+            outcome => panic!("expected a coloring, got {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn best_over_budget() {
+        let n = 40;
+        let edges: Vec<_> = cliques(n).into_iter().map(|edge| (edge, true)).collect();
+
+        let mut bank: ColorerBank = ColorerBank::with_budget(n, 3, 0.01);
+        bank.feed_batch(&edges);
+
+        assert!(
+            matches!(bank.query_best(), BankOutcome::OverBudget { fewest: Some(fewest) } if fewest >= 4)
+        );
+    }
+
+    #[test]
+    fn unlimited() {
+        let n = 40;
+        let mut bank = ColorerBank::init(n, 0.01);
+        assert!(bank.budget().is_none());
+        run(&mut bank, &cliques(n));
+
+        let coloring = bank.query().coloring().unwrap();
+        assert!(coloring.num_classes() >= 4);
+    }
+}
@@ -1,28 +1,37 @@
 //! Coloring
 
 use super::compute_s;
+use log::debug;
 use num_integer::binomial;
 use rand::Rng;
-use std::{collections::HashMap, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug, mem::size_of};
 
-use crate::graph::streaming::sparse_recovery::s_sparse::SparseRecoveryOutput;
+use crate::graph::streaming::{
+    provenance::{AlgorithmInfo, Provenance},
+    space::{log, SpaceReport, SpaceUsage},
+    Merge, StreamProcessor,
+};
 use crate::graph::{
-    static_a::coloring::Colorer, streaming::sparse_recovery::s_sparse::SparseRecovery, Edge,
-    GraphWithRecaller, Graphed,
+    static_a::{coloring::Colorer, partition::Partition},
+    streaming::sparse_recovery::s_sparse::SparseRecovery,
+    Edge, GraphWithRecaller, Graphed, VertexId,
 };
 
-use crate::utils::hash_function::PowerFiniteFieldHasher;
+use crate::utils::hash_function::{HashFunction, PowerFiniteFieldHasher};
 
 /// Representation of a Color, we use tuple to differentiate when we re-color the monochromatic components
-type ColorTuple = (u32, u32);
+pub type ColorTuple = (u32, u32);
 
 /// Structure to support coloring a Graph in the streaming setting
 ///
 /// Algorithm for the coloring can be found [here](https://arxiv.org/pdf/1905.00566.pdf)
 ///
+/// Generic over the hash functions of the sparse recovery, [PowerFiniteFieldHasher] by default.
+///
 /// Total space required = O(|V| +  slog(s/del))
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone)]
-pub struct StreamColoring {
+pub struct StreamColoring<F: HashFunction = PowerFiniteFieldHasher> {
     /// Since of initial pallet for coloring the graph.
     ///
     /// Constant space
@@ -30,16 +39,21 @@ pub struct StreamColoring {
     /// Will start by holding the random color assignment, and ultimately will become the K + 1 coloring
     ///
     /// Stores n values, for n vertices, each ColorTuple is a tuple of 32bit integers, thus O(|V|) space
-    colors: HashMap<u32, ColorTuple>,
+    colors: HashMap<VertexId, ColorTuple>,
     /// The sparse recovery and detection data structure
     ///
     /// Space = Space required by SparseRecovery where n(edges) = n(vertices) choose 2
-    sparse_recovery: SparseRecovery<PowerFiniteFieldHasher>,
+    sparse_recovery: SparseRecovery<F>,
+    /// Number of monochromatic edges `s` the sparse recovery is sized for
+    sparsity: f64,
     #[cfg(test)]
     captured: Vec<u64>,
 }
 
-impl StreamColoring {
+impl<F> StreamColoring<F>
+where
+    F: HashFunction + Clone,
+{
     /// Initialize a new StreamColoring Instance
     ///
     /// - *n* : Size of the Universe (number of vertices)
@@ -48,42 +62,85 @@ impl StreamColoring {
     /// - *del* : Error Parameter for SparseRecovery
     //
     // k can be u32 as well
-    pub fn init(n: u32, k: u64, del: f32) -> Self {
+    pub fn init(n: VertexId, k: u64, del: f32) -> Self {
         // How many edges we ever want to collect
-        let s = compute_s(n);
-        let palette_size = (((2 * n as u64 * k) as f64) / s).ceil() as u32;
+        Self::init_with_sparsity(n, k, compute_s(n), del)
+    }
+
+    /// Initialize a colorer whose structures hold at most `bytes`, `None` if even a single edge cannot be recovered within them
+    ///
+    /// The sparsity `s` is the largest whose sparse recovery fits in the budget along with the random colors, rather than
+    /// `s = C n log n`. A smaller `s` means a larger palette `⌈2nk/s⌉` for the first pass, and so more colors in the end.
+    pub fn with_memory_budget(n: VertexId, k: u64, bytes: usize, del: f32) -> Option<Self> {
+        let fits = |s: u64| Self::bytes_bound(n, s, del) <= bytes;
+        if !fits(1) {
+            return None;
+        }
 
-        println!("s = {}; palette_size = {}", s, palette_size);
+        // Largest fitting sparsity, by bisection as the bound grows with `s`
+        let (mut low, mut high) = (1, binomial(n, 2).max(1));
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if fits(mid) {
+                low = mid
+            } else {
+                high = mid - 1
+            }
+        }
 
-        let mut colors = HashMap::<u32, ColorTuple>::new();
+        Some(Self::init_with_sparsity(n, k, low as f64, del))
+    }
+
+    /// Bytes held by a colorer of these parameters, once its sparse recovery is full
+    pub fn bytes_bound(n: VertexId, s: u64, del: f32) -> usize {
+        // The map of the colors has room for at most `2n` of them
+        size_of::<Self>()
+            + 2 * n as usize * size_of::<(VertexId, ColorTuple)>()
+            + SparseRecovery::<F>::bytes_bound(binomial(n, 2), s, del)
+            - size_of::<SparseRecovery<F>>()
+    }
+
+    fn init_with_sparsity(n: VertexId, k: u64, s: f64, del: f32) -> Self {
+        let palette_size = (((2 * n * k) as f64) / s).ceil() as u32;
+
+        debug!(
+            "[StreamColoring] s = {}; palette_size = {}",
+            s, palette_size
+        );
+
+        let mut colors = HashMap::<VertexId, ColorTuple>::new();
         let mut rng = rand::thread_rng();
 
         for i in 0..n {
             let color = rng.gen_range(0..palette_size) as u32;
             colors.insert(i, (0, color));
         }
-        let sparse_recovery = SparseRecovery::init(binomial(n as u64, 2), s.ceil() as u64, del);
+        let sparse_recovery = SparseRecovery::init(binomial(n, 2), s.ceil() as u64, del);
 
         Self {
             palette_size,
             colors,
             sparse_recovery,
+            sparsity: s,
             #[cfg(test)]
             captured: vec![],
         }
     }
 
-    pub fn new_k(&self, n: u32, k: u64) -> Option<Self> {
-        let s = compute_s(n);
+    pub fn new_k(&self, n: VertexId, k: u64) -> Option<Self> {
+        let s = self.sparsity;
 
-        let palette_size = (((2 * n as u64 * k) as f64) / s).ceil() as u32;
+        let palette_size = (((2 * n * k) as f64) / s).ceil() as u32;
         if palette_size == self.palette_size {
             return None;
         }
 
-        println!("s = {}; palette_size = {}", s, palette_size);
+        debug!(
+            "[StreamColoring] s = {}; palette_size = {}",
+            s, palette_size
+        );
 
-        let mut colors = HashMap::<u32, ColorTuple>::new();
+        let mut colors = HashMap::<VertexId, ColorTuple>::new();
         let mut rng = rand::thread_rng();
 
         for i in 0..n {
@@ -95,16 +152,27 @@ impl StreamColoring {
             palette_size,
             colors,
             sparse_recovery: self.sparse_recovery.clone(),
+            sparsity: s,
             #[cfg(test)]
             captured: vec![],
         })
     }
 
+    /// Number of random colors of the first pass, `⌈2nk/s⌉` for the guess `k`
+    pub fn palette_size(&self) -> u32 {
+        self.palette_size
+    }
+
+    /// Number of monochromatic edges `s` the colorer recovers
+    pub fn sparsity(&self) -> f64 {
+        self.sparsity
+    }
+
     /// Feed a token (and edge insertion of deletion) into the structure
     ///
     /// - *edge* : An edge between two vertices indicated by integers within *n*
     /// - *c* : True if edge is insertion, false if deletion
-    pub fn feed<W: Debug + Default>(&mut self, edge: Edge<u32, W>, c: bool) {
+    pub fn feed<W: Debug + Default>(&mut self, edge: Edge<VertexId, W>, c: bool) {
         let Self {
             colors,
             sparse_recovery,
@@ -129,8 +197,8 @@ impl StreamColoring {
 
     /// Query the structure to color the graph
     ///
-    /// Returns a list of tuples where the index is the vertex, and the value is the color. Colors are tuples, each unique tuple indicates a unique color.
-    pub fn query(self) -> Option<HashMap<u32, ColorTuple>> {
+    /// Returns the color classes of the vertices. Colors are tuples, each unique tuple indicates a unique color.
+    pub fn query(self) -> Option<Partition<VertexId, ColorTuple>> {
         let Self {
             palette_size,
             mut colors,
@@ -138,15 +206,14 @@ impl StreamColoring {
             ..
         } = self;
 
-        let mut monochromatic_graphs: HashMap<(u32, u32), GraphWithRecaller<u32, ()>> = (0
+        let mut monochromatic_graphs: HashMap<(u32, u32), GraphWithRecaller<VertexId, ()>> = (0
             ..palette_size)
-            .into_iter()
             .map(|color| ((0, color), Graphed::new(Default::default())))
             .collect();
 
-        if let SparseRecoveryOutput::Pass(sparse_recovery_output) = sparse_recovery.query() {
-            sparse_recovery_output.iter().for_each(|(edge, _)| {
-                let edge = Edge::from_d1(*edge);
+        if let Some(monochromatic) = sparse_recovery.query().support() {
+            monochromatic.into_iter().for_each(|edge| {
+                let edge = Edge::from_d1(edge);
 
                 let (color1, color2) = {
                     let (u, v) = edge.vertices();
@@ -176,27 +243,102 @@ impl StreamColoring {
                     });
                 });
 
-            Some(colors)
+            Some(colors.into())
         } else {
             None
         }
     }
 }
 
+impl<F> Merge for StreamColoring<F>
+where
+    F: HashFunction,
+{
+    /// Both colorers must share the random coloring, and the sparse recovery
+    fn merge(&mut self, other: &Self) {
+        if self.colors != other.colors {
+            panic!("[StreamColoring] Cannot merge colorers with different random colorings");
+        }
+        self.sparse_recovery.merge(&other.sparse_recovery);
+
+        #[cfg(test)]
+        self.captured.extend(other.captured.iter());
+    }
+}
+
+impl<F> SpaceUsage for StreamColoring<F>
+where
+    F: HashFunction,
+{
+    /// A color for every vertex, and the sparse recovery of the monochromatic edges
+    fn space(&self) -> SpaceReport {
+        let n = self.colors.len() as f64;
+        let recovery = self.sparse_recovery.space();
+
+        SpaceReport {
+            structure: "StreamColoring (BCG)",
+            bound: "O(n log n + s log(s/δ) · log n)",
+            parameters: vec![("n", n), ("palette", self.palette_size as f64)],
+            theoretical_bits: n * log(n) + recovery.theoretical_bits,
+            measured_bytes: size_of::<Self>()
+                + self.colors.capacity() * size_of::<(VertexId, ColorTuple)>()
+                + recovery.measured_bytes
+                - size_of::<SparseRecovery<F>>(),
+            components: vec![recovery],
+        }
+    }
+}
+
+impl<F> StreamProcessor<(Edge<VertexId, ()>, bool), Option<Partition<VertexId, ColorTuple>>>
+    for StreamColoring<F>
+where
+    F: HashFunction + Clone,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
+        StreamColoring::feed(self, edge, c)
+    }
+
+    fn query(self) -> Option<Partition<VertexId, ColorTuple>> {
+        StreamColoring::query(self)
+    }
+}
+
+impl<F> Provenance for StreamColoring<F>
+where
+    F: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "StreamColoring (BCG)",
+            paper_ref: "Bera, Chakrabarti, Ghosh, ICALP 2020, https://arxiv.org/abs/1905.00566",
+            params: vec![
+                ("n", self.colors.len() as f64),
+                ("palette", self.palette_size as f64),
+            ],
+            seed: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::{cmp::min, f32::INFINITY};
 
-    use itertools::Itertools;
+    use rand::prelude::Distribution;
 
     use super::*;
+    use crate::{
+        graph::streaming::coloring::verifier::{verify, Verdict},
+        random_graph::{net_edges, uniform::UniformGraphDistribution},
+    };
 
     #[test]
     fn comb() {
         assert_eq!(binomial(100, 2), 4950);
     }
 
-    fn test_stream() -> Vec<(Edge<u32, ()>, bool)> {
+    fn test_stream() -> Vec<(Edge<VertexId, ()>, bool)> {
         vec![
             ((1, 3), true),
             ((3, 2), true),
@@ -208,7 +350,7 @@ mod test {
             ((4, 5), true),
         ]
         .into_iter()
-        .map(|((u, v), c)| (Edge::init(u as u32, v as u32), c))
+        .map(|((u, v), c)| (Edge::init(u, v), c))
         .collect()
         // remaining edges
         //
@@ -229,7 +371,7 @@ mod test {
             .into_iter()
             .map(|i| {
                 let k = (2 as u32).pow(i) as u64;
-                StreamColoring::init(n as u32, k, 0.01)
+                StreamColoring::<PowerFiniteFieldHasher>::init(n as VertexId, k, 0.01)
             })
             .collect();
 
@@ -241,11 +383,114 @@ mod test {
 
         for colorer in colorers {
             if let Some(coloring) = colorer.query() {
-                let count = coloring.iter().unique().count();
+                let count = coloring.num_classes();
                 min_color = min(min_color, count);
             }
         }
 
         println!("{:?}", min_color);
     }
+
+    /// A hash function of its own, e.g. tabulation hashing, wrapping the field hasher here
+    #[derive(Clone, Debug)]
+    struct ShiftedHasher(PowerFiniteFieldHasher, u64);
+
+    impl HashFunction for ShiftedHasher {
+        fn init(n: u64, l: u64) -> Self {
+            Self(PowerFiniteFieldHasher::init(n, l), l)
+        }
+
+        fn compute(&self, x: u64) -> u64 {
+            (self.0.compute(x) + 1) % self.1
+        }
+
+        fn random_copy(&self) -> Self {
+            Self(self.0.random_copy(), self.1)
+        }
+    }
+
+    #[test]
+    fn custom_hasher() {
+        let mut colorer = StreamColoring::<ShiftedHasher>::init(10, 4, 0.01);
+        for (edge, c) in test_stream() {
+            colorer.feed(edge, c);
+        }
+
+        let coloring = colorer.query().unwrap();
+        assert_eq!(coloring.len(), 10);
+    }
+
+    #[test]
+    fn memory_budget() {
+        type Colorer = StreamColoring<PowerFiniteFieldHasher>;
+        let n = 200;
+        let bytes = 200_000;
+
+        assert!(Colorer::with_memory_budget(n, 4, 1_000, 0.01).is_none());
+
+        let mut colorer = Colorer::with_memory_budget(n, 4, bytes, 0.01).unwrap();
+        assert!(colorer.sparsity() < compute_s(n));
+        assert!(colorer.palette_size() > Colorer::init(n, 4, 0.01).palette_size());
+
+        // Every edge of the complete graph, more than any sparse recovery of the budget holds
+        for u in 0..n {
+            for v in u + 1..n {
+                colorer.feed(Edge::<VertexId, ()>::init(u, v), true);
+            }
+        }
+        assert!(colorer.memory_usage() <= bytes);
+    }
+
+    #[test]
+    fn merge() {
+        let mut left = StreamColoring::<PowerFiniteFieldHasher>::init(10, 2, 0.01);
+        let mut right = left.clone();
+        for (i, (edge, c)) in test_stream().into_iter().enumerate() {
+            if i % 2 == 0 {
+                left.feed(edge, c)
+            } else {
+                right.feed(edge, c)
+            }
+        }
+
+        left.merge(&right);
+        let coloring = left.query().unwrap();
+        for (u, v) in [(1, 3), (2, 4), (2, 5), (4, 5)] {
+            assert_ne!(coloring.get(&u), coloring.get(&v));
+        }
+    }
+
+    #[test]
+    fn noisy_stream() {
+        let stream: Vec<(Edge<VertexId, ()>, bool)> = UniformGraphDistribution::init(40, 60)
+            .with_noise(200)
+            .sample(&mut rand::thread_rng());
+
+        // The noise cancels out, leaving the monochromatic edges of the remaining graph
+        let mut noisy = StreamColoring::<PowerFiniteFieldHasher>::init(40, 4, 0.01);
+        let mut clean = noisy.clone();
+        stream.iter().for_each(|(edge, c)| noisy.feed(*edge, *c));
+        net_edges(&stream)
+            .into_iter()
+            .for_each(|edge| clean.feed(edge, true));
+        assert_eq!(
+            noisy.sparse_recovery.query_ref().support(),
+            clean.sparse_recovery.query_ref().support()
+        );
+
+        let coloring = noisy.query().unwrap();
+        assert_eq!(verify(&coloring, stream), Verdict::Proper);
+    }
+
+    #[test]
+    fn deleted_conflicts() {
+        // Every monochromatic edge is deleted, so that the recovery is zero
+        let mut colorer = StreamColoring::<PowerFiniteFieldHasher>::init(10, 2, 0.01);
+        for (edge, c) in test_stream() {
+            colorer.feed(edge, c);
+            colorer.feed(edge, !c);
+        }
+
+        assert!(colorer.query().is_some());
+    }
 }
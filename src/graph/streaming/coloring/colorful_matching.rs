@@ -0,0 +1,158 @@
+//! Colorful Matchings of Almost-Cliques
+//!
+//! Following the [ACK paper](https://arxiv.org/pdf/1807.08886.pdf), an almost-clique has nearly `Δ` vertices, so it cannot spare
+//! many colors. A colorful matching pairs up non-adjacent vertices of the almost-clique that sampled a common color, and gives both
+//! vertices of a pair that color. Every pair saves one color, which leaves enough colors for the remaining vertices of the
+//! almost-clique to be colored by a perfect matching of the palette graph.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::almost_clique::AlmostClique;
+use crate::graph::{Edge, Graph, Graphed, VertexId};
+
+/// Two non-adjacent vertices of an almost-clique, both given the color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorfulPair {
+    pub u: VertexId,
+    pub v: VertexId,
+    pub color: usize,
+}
+
+/// A colorful matching of every almost-clique, from the palette each vertex sampled and the graph of the conflicting edges
+///
+/// Within an almost-clique, no two pairs share a vertex or a color. Pairs of different almost-cliques may share a color, if an
+/// edge joins them the coloring has to check for it. Two vertices are non-adjacent when the conflict graph has no edge between
+/// them, which holds every edge between vertices with a common color.
+///
+/// The vertices of each almost-clique are visited in increasing order, each taking the first color of its palette it shares with
+/// an unmatched non-adjacent vertex. Runtime: `O(Σ |palette|)` per almost-clique, times the vertices sampling each color.
+pub fn colorful_matching<'p, P>(
+    almost_cliques: &[AlmostClique],
+    palette: P,
+    conflict_graph: &Graph<VertexId, ()>,
+) -> Vec<ColorfulPair>
+where
+    P: Fn(VertexId) -> &'p [usize],
+{
+    let mut pairs = vec![];
+
+    for almost_clique in almost_cliques {
+        let mut vertices: Vec<VertexId> = almost_clique.vertices.iter().copied().collect();
+        vertices.sort_unstable();
+
+        // The vertices of the almost-clique that sampled each color, in increasing order
+        let mut sampled: BTreeMap<usize, Vec<VertexId>> = BTreeMap::new();
+        for v in vertices.iter() {
+            for color in palette(*v) {
+                sampled.entry(*color).or_default().push(*v);
+            }
+        }
+
+        let mut matched: HashSet<VertexId> = HashSet::new();
+        let mut used: HashSet<usize> = HashSet::new();
+        for v in vertices.iter() {
+            if matched.contains(v) {
+                continue;
+            }
+
+            let pair = palette(*v)
+                .iter()
+                .filter(|color| !used.contains(color))
+                .find_map(|color| {
+                    sampled[color]
+                        .iter()
+                        .find(|u| {
+                            *u != v
+                                && !matched.contains(u)
+                                && !conflict_graph.has_edge(&Edge::init(**u, *v))
+                        })
+                        .map(|u| ColorfulPair {
+                            u: *u,
+                            v: *v,
+                            color: *color,
+                        })
+                });
+
+            if let Some(pair) = pair {
+                matched.insert(pair.u);
+                matched.insert(pair.v);
+                used.insert(pair.color);
+                pairs.push(pair);
+            }
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// `K_4` on `[0, 4)` without the edges `(0, 1)` and `(2, 3)`
+    fn conflict_graph() -> Graph<VertexId, ()> {
+        let mut graph = Graph::default();
+        for (u, v) in [(0, 2), (0, 3), (1, 2), (1, 3)] {
+            graph.add_edge(Edge::init(u, v));
+        }
+        graph
+    }
+
+    fn clique(graph: &Graph<VertexId, ()>) -> AlmostClique {
+        AlmostClique::init((0..4).collect(), graph)
+    }
+
+    #[test]
+    fn non_adjacent_pairs() {
+        let graph = conflict_graph();
+        let palettes: HashMap<VertexId, Vec<usize>> = vec![
+            (0, vec![5]),
+            (1, vec![5, 6]),
+            (2, vec![5, 6]),
+            (3, vec![6, 7]),
+        ]
+        .into_iter()
+        .collect();
+
+        let pairs = colorful_matching(&[clique(&graph)], |v| &palettes[&v], &graph);
+
+        // 2 also sampled 5, but is adjacent to 0
+        assert_eq!(
+            pairs,
+            vec![
+                ColorfulPair {
+                    u: 1,
+                    v: 0,
+                    color: 5
+                },
+                ColorfulPair {
+                    u: 3,
+                    v: 2,
+                    color: 6
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn colors_used_once() {
+        let graph = conflict_graph();
+        let palette = [5];
+
+        // Both pairs could take 5, but only one of them does
+        let pairs = colorful_matching(&[clique(&graph)], |_| &palette, &graph);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].color, 5);
+    }
+
+    #[test]
+    fn no_common_color() {
+        let graph = conflict_graph();
+        let palettes: Vec<Vec<usize>> = (0..4).map(|v| vec![v]).collect();
+
+        let pairs = colorful_matching(&[clique(&graph)], |v| &palettes[v as usize], &graph);
+        assert!(pairs.is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+//! Bank of ACK Colorers
+//!
+//! The [ack](super::ack) colorer samples its palettes from the `Δ + 1` colors before the stream starts, so it needs the maximum
+//! degree `Δ` of a graph that has not been seen yet. A [DeltaBank] runs one colorer for each guess `Δ' = 1, 2, 4, ...` up to
+//! `n - 1` over the same stream, and feeds a [DegreeSketch] alongside them. At query time the sketch, which never
+//! underestimates a degree, rules out the guesses below `Δ`, and the bank answers with the smallest remaining guess whose
+//! colorer succeeds, with at most `2Δ + 1` colors.
+
+use std::{collections::HashSet, iter::successors, mem::size_of};
+
+use crate::{
+    graph::{
+        static_a::coloring::Coloring,
+        streaming::{
+            frequency::{CountMinParameters, DegreeSketch},
+            provenance::{AlgorithmInfo, Provenance},
+            space::{SpaceReport, SpaceUsage},
+            Merge, StreamProcessor,
+        },
+        Edge, VertexId,
+    },
+    utils::hash_function::{HashFunction, PowerFiniteFieldHasher},
+    Error,
+};
+
+use super::ack::{AckParameters, StreamColoring};
+
+/// Coloring of a [DeltaBank], with the guess of the maximum degree it was found with
+#[derive(Debug)]
+pub struct GuessedColoring {
+    pub guess: u32,
+    pub coloring: Coloring<VertexId>,
+}
+
+/// ACK colorers of the guesses of the maximum degree, fed the same stream
+pub struct DeltaBank<F: HashFunction = PowerFiniteFieldHasher> {
+    /// Colorers in increasing order of their guess
+    colorers: Vec<(u32, StreamColoring<F>)>,
+    degrees: DegreeSketch<F>,
+}
+
+impl<F> DeltaBank<F>
+where
+    F: HashFunction + Clone,
+{
+    /// Colorers for the guesses `1, 2, 4, ...` up to `n - 1`, with the default parameters, `Err` if there are no vertices
+    pub fn try_init(vertices: HashSet<&VertexId>) -> crate::Result<Self> {
+        Self::try_init_with(vertices, AckParameters::default(), Default::default())
+    }
+
+    /// [try_init](DeltaBank::try_init), panicking if there are no vertices
+    pub fn init_unchecked(vertices: HashSet<&VertexId>) -> Self {
+        Self::init_with_unchecked(vertices, AckParameters::default(), Default::default())
+    }
+
+    /// Colorers for the guesses `1, 2, 4, ...` up to `n - 1`, `Err` if there are no vertices or the parameters are invalid
+    ///
+    /// - *ack_parameters* : Parameters of every colorer
+    /// - *degree_parameters* : Parameters of the sketch of the degrees, whose error only costs colorers being skipped
+    pub fn try_init_with(
+        vertices: HashSet<&VertexId>,
+        ack_parameters: AckParameters,
+        degree_parameters: CountMinParameters,
+    ) -> crate::Result<Self> {
+        let universe = match vertices.iter().max() {
+            Some(max) => **max + 1,
+            None => return Err(Error::EmptyGraph),
+        };
+
+        let colorers = guesses(vertices.len() as u32)
+            .into_iter()
+            .map(|guess| {
+                StreamColoring::try_init_with(vertices.clone(), guess, ack_parameters)
+                    .map(|colorer| (guess, colorer))
+            })
+            .collect::<crate::Result<_>>()?;
+
+        Ok(Self {
+            colorers,
+            degrees: DegreeSketch::init(universe, degree_parameters),
+        })
+    }
+
+    /// [try_init_with](DeltaBank::try_init_with), panicking if there are no vertices or the parameters are invalid
+    pub fn init_with_unchecked(
+        vertices: HashSet<&VertexId>,
+        ack_parameters: AckParameters,
+        degree_parameters: CountMinParameters,
+    ) -> Self {
+        Self::try_init_with(vertices, ack_parameters, degree_parameters)
+            .unwrap_or_else(|error| panic!("[DeltaBank] {}", error))
+    }
+
+    /// The guesses being run, in increasing order
+    pub fn guesses(&self) -> impl Iterator<Item = u32> + '_ {
+        self.colorers.iter().map(|(guess, _)| *guess)
+    }
+
+    /// The colorers being run, with their guess
+    pub fn colorers(&self) -> impl Iterator<Item = (u32, &StreamColoring<F>)> {
+        self.colorers
+            .iter()
+            .map(|(guess, colorer)| (*guess, colorer))
+    }
+
+    /// Upper bound on the maximum degree of the graph so far, from the sketch of the degrees, at most the largest guess
+    pub fn max_degree_estimate(&self) -> u64 {
+        let largest = self.colorers.last().map_or(0, |(guess, _)| *guess);
+        self.degrees.max_degree_estimate().min(largest as u64)
+    }
+
+    /// The smallest guess at least the estimated maximum degree, the first colorer the query tries
+    pub fn selected_guess(&self) -> Option<u32> {
+        let estimate = self.max_degree_estimate();
+        self.guesses().find(|guess| *guess as u64 >= estimate)
+    }
+
+    /// Feed a token to every colorer, and to the sketch of the degrees
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        for (_, colorer) in self.colorers.iter_mut() {
+            colorer.feed(token);
+        }
+        self.degrees.feed(token.0, token.1);
+    }
+
+    /// Query the colorers of the guesses at least the estimated maximum degree, in increasing order, stopping at the first
+    /// coloring
+    ///
+    /// The colorers of smaller guesses are dropped unqueried, their coloring could not be told proper without the graph.
+    pub fn query(self) -> Option<GuessedColoring> {
+        let estimate = self.max_degree_estimate();
+
+        self.colorers
+            .into_iter()
+            .filter(|(guess, _)| *guess as u64 >= estimate)
+            .find_map(|(guess, colorer)| {
+                colorer
+                    .query()
+                    .ok()
+                    .map(|coloring| GuessedColoring { guess, coloring })
+            })
+    }
+}
+
+/// Guesses `1, 2, 4, ...` below `n - 1`, followed by `n - 1`, the largest degree of a graph on `n` vertices
+fn guesses(n: u32) -> Vec<u32> {
+    let limit = n.saturating_sub(1).max(1);
+
+    let mut guesses: Vec<u32> = successors(Some(1_u32), |guess| guess.checked_mul(2))
+        .take_while(|guess| *guess < limit)
+        .collect();
+    guesses.push(limit);
+    guesses
+}
+
+impl<F> StreamProcessor<(Edge<VertexId, ()>, bool), Option<GuessedColoring>> for DeltaBank<F>
+where
+    F: HashFunction + Clone,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        DeltaBank::feed(self, token)
+    }
+
+    fn query(self) -> Option<GuessedColoring> {
+        DeltaBank::query(self)
+    }
+}
+
+impl<F> Merge for DeltaBank<F>
+where
+    F: HashFunction,
+{
+    /// Both banks must run the same guesses, with the same colorers and the same sketch of the degrees
+    fn merge(&mut self, other: &Self) {
+        if self.colorers.len() != other.colorers.len() {
+            panic!("[DeltaBank] Cannot merge banks with different guesses");
+        }
+
+        for ((guess, colorer), (other_guess, other_colorer)) in
+            self.colorers.iter_mut().zip(other.colorers.iter())
+        {
+            if guess != other_guess {
+                panic!("[DeltaBank] Cannot merge banks with different guesses");
+            }
+            colorer.merge(other_colorer);
+        }
+        self.degrees.merge(&other.degrees);
+    }
+}
+
+impl<F> SpaceUsage for DeltaBank<F>
+where
+    F: HashFunction + Clone,
+{
+    /// One colorer per guess, and the sketch of the degrees
+    fn space(&self) -> SpaceReport {
+        let mut components: Vec<SpaceReport> =
+            self.colorers.iter().map(|(_, c)| c.space()).collect();
+        components.push(self.degrees.space());
+
+        SpaceReport {
+            structure: "DeltaBank",
+            bound: "O(log n) · space of StreamColoring",
+            parameters: vec![("guesses", self.colorers.len() as f64)],
+            theoretical_bits: components.iter().map(|c| c.theoretical_bits).sum(),
+            measured_bytes: size_of::<Self>()
+                + self.colorers.capacity() * size_of::<(u32, StreamColoring<F>)>()
+                + components.iter().map(|c| c.measured_bytes).sum::<usize>()
+                - self.colorers.len() * size_of::<StreamColoring<F>>()
+                - size_of::<DegreeSketch<F>>(),
+            components,
+        }
+    }
+}
+
+impl<F> Provenance for DeltaBank<F>
+where
+    F: HashFunction + Clone,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "DeltaBank (ACK)",
+            paper_ref: "Assadi, Chen, Khanna, SODA 2019, https://arxiv.org/abs/1807.08886",
+            params: vec![
+                ("guesses", self.colorers.len() as f64),
+                ("Δ estimate", self.max_degree_estimate() as f64),
+            ],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::streaming::coloring::verifier::verify;
+
+    #[test]
+    fn guesses_of_n() {
+        assert_eq!(guesses(1), vec![1]);
+        assert_eq!(guesses(2), vec![1]);
+        assert_eq!(guesses(5), vec![1, 2, 4]);
+        assert_eq!(guesses(20), vec![1, 2, 4, 8, 16, 19]);
+    }
+
+    #[test]
+    fn empty() {
+        assert!(matches!(
+            DeltaBank::<PowerFiniteFieldHasher>::try_init(HashSet::new()),
+            Err(Error::EmptyGraph)
+        ));
+    }
+
+    #[test]
+    fn selects_a_guess_above_delta() {
+        // A star on 8 leaves, joined to a path, Δ = 9
+        let vertices: HashSet<VertexId> = (0..16).collect();
+        let stream: Vec<_> = (1..=8)
+            .map(|v| Edge::init(0, v))
+            .chain((8..15).map(|v| Edge::init(v, v + 1)))
+            .chain(vec![Edge::init(0, 15)])
+            .map(|edge| (edge, true))
+            .collect();
+
+        let mut bank: DeltaBank = DeltaBank::init_unchecked(vertices.iter().collect());
+        assert_eq!(bank.guesses().collect::<Vec<_>>(), vec![1, 2, 4, 8, 15]);
+        stream.iter().for_each(|token| bank.feed(*token));
+
+        assert!(bank.max_degree_estimate() >= 9);
+        assert_eq!(bank.selected_guess(), Some(15));
+        assert_eq!(bank.space().components.len(), 6);
+
+        if let Some(GuessedColoring { guess, coloring }) = bank.query() {
+            assert_eq!(guess, 15);
+            assert!(verify(&coloring, stream).is_proper());
+        }
+    }
+}
@@ -0,0 +1,526 @@
+//! Palette Sparsification
+//!
+//! The palette sparsification theorem of the [ACK paper](https://arxiv.org/pdf/1807.08886.pdf): if every vertex samples
+//! `O(log n)` colors out of `[Δ + 1]`, the graph can be properly colored, with high probability, by giving each vertex one of the
+//! colors it sampled. Only the edges whose endpoints sampled a common color can then conflict, and there are `O(n log^2 n)` of
+//! them, so a single pass recovers them with a sparse recovery, and the list coloring is solved once the stream ends.
+//!
+//! [PaletteSparsification] is this single pass, without the sparse-dense decomposition that the full [StreamColoring] uses to
+//! color in polynomial time. The list coloring is found by a backtracking search instead, bounded by
+//! [PaletteParameters::search_budget].
+//!
+//! [StreamColoring]: super::ack::StreamColoring
+
+use crate::{
+    graph::{
+        static_a::coloring::Coloring,
+        streaming::{
+            provenance::{AlgorithmInfo, Provenance},
+            space::{log, SpaceReport, SpaceUsage},
+            sparse_recovery::s_sparse::SparseRecovery,
+            Merge, StreamProcessor,
+        },
+        Edge, Graph, Graphed, VertexId,
+    },
+    utils::{
+        hash_function::{HashFunction, PowerFiniteFieldHasher},
+        slab::{sorted_intersect, SliceArena},
+    },
+};
+use num_integer::binomial;
+use rand::{seq::index, Rng};
+use std::{collections::HashSet, fmt::Debug, mem::size_of};
+
+type Color = usize;
+
+/// Parameters of [PaletteSparsification]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaletteParameters {
+    /// Each vertex samples `ℓ = ⌈C log n⌉` distinct colors, or all `Δ + 1` of them if there are fewer
+    pub list_constant: f64,
+    /// The colorer recovers up to `s = C n ℓ^2 / 2` conflicting edges, `C` times the expected number of conflicting edges
+    pub recovery_constant: f64,
+    /// Failure probability of the sparse recovery of the conflicting edges
+    pub recovery_failure: f32,
+    /// Number of colors tried by the list coloring search before it gives up
+    pub search_budget: usize,
+}
+
+impl Default for PaletteParameters {
+    fn default() -> Self {
+        Self {
+            list_constant: 2.0,
+            recovery_constant: 2.0,
+            recovery_failure: 0.01,
+            search_budget: 1_000_000,
+        }
+    }
+}
+
+impl PaletteParameters {
+    pub fn with_list_constant(self, list_constant: f64) -> Self {
+        Self {
+            list_constant,
+            ..self
+        }
+    }
+
+    pub fn with_recovery_constant(self, recovery_constant: f64) -> Self {
+        Self {
+            recovery_constant,
+            ..self
+        }
+    }
+
+    pub fn with_search_budget(self, search_budget: usize) -> Self {
+        Self {
+            search_budget,
+            ..self
+        }
+    }
+
+    /// Number of colors `ℓ` sampled by each vertex over the vertices `[n]`
+    pub fn list_size(&self, n: VertexId, delta: u32) -> usize {
+        ((self.list_constant * log(n as f64)).ceil() as usize).clamp(1, delta as usize + 1)
+    }
+
+    /// Number of conflicting edges recovered over the vertices `[n]`, never more than there are edges
+    pub fn s(&self, n: VertexId, delta: u32) -> u64 {
+        let list = self.list_size(n, delta) as f64;
+        let s = (self.recovery_constant * n as f64 * list.powi(2) / 2.0).ceil() as u64;
+        s.clamp(1, binomial(n + 1, 2).max(1))
+    }
+}
+
+/// Single pass `(Δ + 1)`-coloring by palette sparsification
+///
+/// Every vertex samples a list of `ℓ = O(log n)` colors at initialization. The stream is fed to a sparse recovery, keeping only the
+/// edges whose endpoints share a color of their lists, and the query list colors the recovered conflict graph.
+///
+/// Total space required = O(n log^2 n) for the lists and the conflicting edges, up to the log factors of the sparse recovery
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone)]
+pub struct PaletteSparsification<F: HashFunction = PowerFiniteFieldHasher> {
+    /// List `v` holds the colors sampled by `v`, in increasing order
+    palettes: SliceArena<Color>,
+    recovery: SparseRecovery<F>,
+    vertices: HashSet<VertexId>,
+    delta: u32,
+    parameters: PaletteParameters,
+}
+
+impl<F> Debug for PaletteSparsification<F>
+where
+    F: HashFunction,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PaletteSparsification")
+            .field("delta", &self.delta)
+            .field("parameters", &self.parameters)
+            .field("recovery", &self.recovery)
+            .finish()
+    }
+}
+
+impl<F> PaletteSparsification<F>
+where
+    F: HashFunction + Clone,
+{
+    /// Initiate a colorer of the vertices, with the default parameters
+    ///
+    /// - *vertices* : Vertices of the graph
+    /// - *delta* : Maximum degree within the graph
+    pub fn init(vertices: HashSet<&VertexId>, delta: u32) -> Self {
+        Self::init_with(vertices, delta, PaletteParameters::default())
+    }
+
+    pub fn init_with(
+        vertices: HashSet<&VertexId>,
+        delta: u32,
+        parameters: PaletteParameters,
+    ) -> Self {
+        Self::init_with_rng(vertices, delta, parameters, &mut rand::thread_rng())
+    }
+
+    /// Initiate a colorer, sampling the lists of colors from `rng`
+    ///
+    /// The sparse recovery still draws its hash functions from the thread rng.
+    pub fn init_with_rng<R: Rng + ?Sized>(
+        vertices: HashSet<&VertexId>,
+        delta: u32,
+        parameters: PaletteParameters,
+        rng: &mut R,
+    ) -> Self {
+        if !(parameters.list_constant > 0.0 && parameters.recovery_constant > 0.0) {
+            panic!("[PaletteSparsification] The constants must be positive");
+        }
+
+        let n = vertices.iter().max().map_or(0, |v| **v);
+        let list = parameters.list_size(n, delta);
+
+        // Every vertex id in [0, n] gets its list, empty if it is not in the graph, so that they can be indexed by the id
+        let mut palettes = SliceArena::default();
+        for vertex in 0..=n {
+            let mut palette = if vertices.contains(&vertex) {
+                index::sample(rng, delta as usize + 1, list).into_vec()
+            } else {
+                vec![]
+            };
+            palette.sort_unstable();
+            palettes.push(palette);
+        }
+        palettes.shrink_to_fit();
+
+        let recovery = SparseRecovery::init(
+            binomial(n + 1, 2),
+            parameters.s(n, delta),
+            parameters.recovery_failure,
+        );
+
+        Self {
+            palettes,
+            recovery,
+            vertices: vertices.into_iter().copied().collect(),
+            delta,
+            parameters,
+        }
+    }
+
+    pub fn parameters(&self) -> &PaletteParameters {
+        &self.parameters
+    }
+
+    /// Colors sampled by the vertex, in increasing order, empty if it is not a vertex of the graph
+    pub fn palette(&self, v: VertexId) -> &[Color] {
+        self.palettes.get(v as usize)
+    }
+
+    /// Feed a turnstile token, only kept if the endpoints of the edge share a sampled color
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (u, v) = token.0.vertices();
+        if !self.vertices.contains(u) || !self.vertices.contains(v) {
+            panic!("[PaletteSparsification] This stream includes vertices that are not present in the graph");
+        }
+
+        if sorted_intersect(self.palette(*u), self.palette(*v)) {
+            self.recovery.feed((token.0.to_d1(), token.1));
+        }
+    }
+
+    /// The edges recovered so far whose endpoints share a sampled color, `None` if there are too many of them
+    pub fn conflict_graph(&self) -> Option<Graph<VertexId, ()>> {
+        let mut graph = Graph::default();
+        for edge in self.recovery.query_ref().support()? {
+            graph.add_edge(Edge::from_d1(edge));
+        }
+        Some(graph)
+    }
+
+    /// Color every vertex with a color of its list, `None` if the conflict graph could not be recovered, or if the search ran out
+    /// of its budget
+    pub fn query(self) -> Option<Coloring<VertexId>> {
+        let conflict_graph = self.conflict_graph()?;
+        list_color(
+            &self.vertices,
+            |v| self.palette(v),
+            &conflict_graph,
+            self.parameters.search_budget,
+        )
+    }
+}
+
+/// A proper coloring of the vertices, each with a color of its palette, by backtracking over the vertices with the fewest colors
+/// left, `None` if there is none or if more than `budget` colors were tried
+///
+/// Vertices without a conflicting edge take the first color of their palette. Runtime: `O(budget n ℓ d)` for lists of size `ℓ`,
+/// and a conflict graph of maximum degree `d`.
+fn list_color<'p, P>(
+    vertices: &HashSet<VertexId>,
+    palette: P,
+    conflict_graph: &Graph<VertexId, ()>,
+    budget: usize,
+) -> Option<Coloring<VertexId>>
+where
+    P: Fn(VertexId) -> &'p [Color],
+{
+    let mut coloring = Coloring::new();
+
+    let neighbors = |v: &VertexId| -> Vec<VertexId> {
+        conflict_graph
+            .get_neighbors(v)
+            .map(|neighbors| neighbors.iter().map(|n| n.destination).collect())
+            .unwrap_or_default()
+    };
+    let available = |v: VertexId, coloring: &Coloring<VertexId>| -> Vec<Color> {
+        let taken: HashSet<Color> = neighbors(&v)
+            .iter()
+            .filter_map(|u| coloring.get(u))
+            .copied()
+            .collect();
+        palette(v)
+            .iter()
+            .rev()
+            .filter(|c| !taken.contains(c))
+            .copied()
+            .collect()
+    };
+
+    let mut uncolored: Vec<VertexId> = vec![];
+    for v in vertices.iter() {
+        if neighbors(v).is_empty() {
+            coloring.insert(*v, *palette(*v).first()?);
+        } else {
+            uncolored.push(*v);
+        }
+    }
+    uncolored.sort_unstable();
+
+    // The vertices colored by the search, with the colors they have not tried yet
+    let mut stack: Vec<(VertexId, Vec<Color>)> = vec![];
+    let mut tried = 0;
+    loop {
+        let next = uncolored
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i, available(*v, &coloring)))
+            .min_by_key(|(_, colors)| colors.len());
+        match next {
+            Some((i, colors)) => stack.push((uncolored.swap_remove(i), colors)),
+            None => return Some(coloring),
+        }
+
+        // Color the last vertex, backtracking to the vertices before it while it has no color left to try
+        loop {
+            let (v, colors) = stack.last_mut()?;
+            coloring.remove(v);
+            if let Some(color) = colors.pop() {
+                coloring.insert(*v, color);
+                tried += 1;
+                if tried > budget {
+                    return None;
+                }
+                break;
+            }
+            uncolored.push(*v);
+            stack.pop();
+        }
+    }
+}
+
+impl<F> StreamProcessor<(Edge<VertexId, ()>, bool), Option<Coloring<VertexId>>>
+    for PaletteSparsification<F>
+where
+    F: HashFunction + Clone,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        PaletteSparsification::feed(self, token)
+    }
+
+    fn query(self) -> Option<Coloring<VertexId>> {
+        PaletteSparsification::query(self)
+    }
+}
+
+impl<F> Merge for PaletteSparsification<F>
+where
+    F: HashFunction,
+{
+    /// Both colorers must share the sampled lists and the sparse recovery
+    fn merge(&mut self, other: &Self) {
+        if self.delta != other.delta || self.vertices != other.vertices {
+            panic!("[PaletteSparsification] Cannot merge colorers of different graphs");
+        }
+        self.recovery.merge(&other.recovery);
+    }
+}
+
+impl<F> Provenance for PaletteSparsification<F>
+where
+    F: HashFunction + Clone,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "PaletteSparsification (ACK)",
+            paper_ref: "Assadi, Chen, Khanna, SODA 2019, https://arxiv.org/abs/1807.08886",
+            params: vec![
+                ("n", self.vertices.len() as f64),
+                ("Δ", self.delta as f64),
+                (
+                    "ℓ",
+                    self.palettes.items() as f64 / self.vertices.len().max(1) as f64,
+                ),
+                ("C", self.parameters.recovery_constant),
+            ],
+            seed: None,
+        }
+    }
+}
+
+impl<F> SpaceUsage for PaletteSparsification<F>
+where
+    F: HashFunction + Clone,
+{
+    /// `O(log n)` sampled colors per vertex, and the sparse recovery of the conflicting edges
+    fn space(&self) -> SpaceReport {
+        let n = self.vertices.len() as f64;
+        let recovery = self.recovery.space();
+
+        let palettes = self.palettes.items() as f64 * log(self.delta as f64 + 1.0);
+
+        SpaceReport {
+            structure: "PaletteSparsification (ACK)",
+            bound: "O(n log^2 n)",
+            parameters: vec![("n", n), ("Δ", self.delta as f64)],
+            theoretical_bits: palettes + recovery.theoretical_bits,
+            measured_bytes: size_of::<Self>()
+                + self.palettes.layout_bytes()
+                + self.vertices.capacity() * size_of::<VertexId>()
+                + recovery.measured_bytes
+                - size_of::<SparseRecovery<F>>(),
+            components: vec![recovery],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{prelude::Distribution, rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        graph::{
+            static_a::coloring::Colorer,
+            streaming::coloring::verifier::{verify, Verdict},
+        },
+        random_graph::{net_edges, uniform::UniformGraphDistribution},
+    };
+
+    /// Cycle on `[0, n)`
+    fn cycle(n: VertexId) -> Graph<VertexId, ()> {
+        let mut graph = Graph::default();
+        for v in 0..n {
+            graph.add_edge(Edge::init(v, (v + 1) % n));
+        }
+        graph
+    }
+
+    #[test]
+    fn colors_from_palettes() {
+        let graph = cycle(30);
+        let mut colorer: PaletteSparsification = PaletteSparsification::init(graph.vertices(), 2);
+        graph.clone().for_each(|edge| colorer.feed((edge, true)));
+
+        let palettes: Vec<Vec<Color>> = (0..30).map(|v| colorer.palette(v).to_vec()).collect();
+        let coloring = colorer.query().unwrap();
+
+        assert_eq!(coloring.len(), 30);
+        assert!(graph.is_proper(&coloring));
+        for (v, color) in coloring.iter() {
+            assert!(palettes[*v as usize].contains(color));
+            assert!(*color <= 2);
+        }
+    }
+
+    #[test]
+    fn keeps_conflicting_edges() {
+        let graph = cycle(40);
+        let parameters = PaletteParameters::default().with_list_constant(0.2);
+        let mut colorer: PaletteSparsification = PaletteSparsification::init_with_rng(
+            graph.vertices(),
+            2,
+            parameters,
+            &mut StdRng::seed_from_u64(3),
+        );
+        graph.clone().for_each(|edge| colorer.feed((edge, true)));
+
+        let conflicts = colorer.conflict_graph().unwrap();
+        for edge in conflicts.clone() {
+            let (u, v) = edge.vertices();
+            assert!(graph.has_edge(&edge));
+            assert!(sorted_intersect(colorer.palette(*u), colorer.palette(*v)));
+        }
+        for edge in graph {
+            let (u, v) = edge.vertices();
+            if sorted_intersect(colorer.palette(*u), colorer.palette(*v)) {
+                assert!(conflicts.has_edge(&edge));
+            }
+        }
+    }
+
+    #[test]
+    fn deletions() {
+        let graph = cycle(10);
+        let mut colorer: PaletteSparsification = PaletteSparsification::init(graph.vertices(), 2);
+        graph.clone().for_each(|edge| colorer.feed((edge, true)));
+        graph.clone().for_each(|edge| colorer.feed((edge, false)));
+
+        assert_eq!(colorer.conflict_graph().unwrap().vertices().len(), 0);
+    }
+
+    #[test]
+    fn noisy_stream() {
+        let stream: Vec<(Edge<VertexId, ()>, bool)> = UniformGraphDistribution::init(50, 100)
+            .with_noise(300)
+            .sample(&mut rand::thread_rng());
+        let mut graph: Graph<VertexId, ()> = Graph::default();
+        net_edges(&stream)
+            .into_iter()
+            .for_each(|edge| graph.add_edge(edge));
+        let delta = graph.adj_list().values().map(|n| n.len()).max().unwrap() as u32;
+
+        // Noise edges may join vertices without any remaining edge, which are still vertices of the stream
+        let vertices: HashSet<VertexId> = (0..50).collect();
+        let mut colorer: PaletteSparsification =
+            PaletteSparsification::init(vertices.iter().collect(), delta);
+        stream.iter().for_each(|token| colorer.feed(*token));
+
+        let coloring = colorer.query().unwrap();
+        assert_eq!(coloring.len(), 50);
+        assert_eq!(verify(&coloring, stream), Verdict::Proper);
+    }
+
+    #[test]
+    fn backtracks() {
+        // A triangle, where 0 has to take 1, although it is tried with 0 first
+        let mut graph = Graph::default();
+        for (u, v) in [(0, 1), (1, 2), (0, 2)] {
+            graph.add_edge(Edge::init(u, v));
+        }
+        let palettes: Vec<Vec<Color>> = vec![vec![0, 1], vec![0, 2], vec![0, 2]];
+        let vertices: HashSet<VertexId> = (0..3).collect();
+
+        let coloring = list_color(&vertices, |v| &palettes[v as usize], &graph, 100).unwrap();
+        assert!(graph.is_proper(&coloring));
+        assert_eq!(coloring.get(&0), Some(&1));
+
+        // Nor can it be colored from two colors, or within a single try
+        let palettes: Vec<Vec<Color>> = vec![vec![0, 1]; 3];
+        assert!(list_color(&vertices, |v| &palettes[v as usize], &graph, 100).is_none());
+        let palettes: Vec<Vec<Color>> = vec![vec![0, 1], vec![0, 2], vec![0, 2]];
+        assert!(list_color(&vertices, |v| &palettes[v as usize], &graph, 1).is_none());
+    }
+
+    #[test]
+    fn merge() {
+        let graph = cycle(20);
+        let stream: Vec<_> = graph.clone().map(|e| (e, true)).collect();
+
+        let mut whole: PaletteSparsification = PaletteSparsification::init(graph.vertices(), 2);
+        let (mut left, mut right) = (whole.clone(), whole.clone());
+        for (i, token) in stream.iter().enumerate() {
+            whole.feed(*token);
+            if i % 2 == 0 {
+                left.feed(*token)
+            } else {
+                right.feed(*token)
+            }
+        }
+
+        left.merge(&right);
+        assert_eq!(
+            left.conflict_graph().unwrap().vertices(),
+            whole.conflict_graph().unwrap().vertices()
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Two Pass Coloring Verification
+//!
+//! Checking a coloring with [Colorer::is_proper](crate::graph::static_a::coloring::Colorer::is_proper) requires the whole graph,
+//! which is exactly what a streaming colorer avoids storing. Instead, the first pass over the stream computes the coloring, and a
+//! second pass replays the stream into a [ColoringVerifier], which only stores the coloring itself and the net multiplicity of the
+//! monochromatic edges.
+//!
+//! Deletions are supported: a monochromatic edge that is inserted and later deleted is not a conflict.
+
+use std::{collections::HashMap, fmt::Debug};
+
+use crate::graph::{static_a::partition::Partition, streaming::StreamProcessor, Edge, VertexId};
+
+/// Outcome of a verification
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// No edge of the stream joins two vertices of the same color
+    Proper,
+    /// Some endpoints have no color, and the coloring is proper on the colored vertices
+    Partial(Vec<VertexId>),
+    /// The edges, `(u, v)` with `u < v`, whose endpoints share a color
+    Improper(Vec<(VertexId, VertexId)>),
+}
+
+impl Verdict {
+    pub fn is_proper(&self) -> bool {
+        matches!(self, Self::Proper)
+    }
+}
+
+/// Checks a coloring against a replay of the stream it was computed from
+///
+/// Storage: the coloring, plus O(1) words for every monochromatic edge, and every uncolored vertex
+#[derive(Clone, Debug)]
+pub struct ColoringVerifier<'c, C> {
+    coloring: &'c Partition<VertexId, C>,
+    /// Net number of insertions of each monochromatic edge
+    conflicts: HashMap<(VertexId, VertexId), i64>,
+    /// Net number of insertions of the edges incident to each uncolored vertex
+    uncolored: HashMap<VertexId, i64>,
+}
+
+impl<'c, C> ColoringVerifier<'c, C>
+where
+    C: Ord + Clone,
+{
+    pub fn init(coloring: &'c Partition<VertexId, C>) -> Self {
+        Self {
+            coloring,
+            conflicts: HashMap::new(),
+            uncolored: HashMap::new(),
+        }
+    }
+
+    /// Feed the next token of the replayed stream
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
+        let (u, v) = edge.vertices();
+        let change = if c { 1 } else { -1 };
+
+        match (self.coloring.get(u), self.coloring.get(v)) {
+            (Some(a), Some(b)) => {
+                if a == b {
+                    *self.conflicts.entry((*u.min(v), *u.max(v))).or_default() += change;
+                }
+            }
+            (a, b) => {
+                if a.is_none() {
+                    *self.uncolored.entry(*u).or_default() += change;
+                }
+                if b.is_none() {
+                    *self.uncolored.entry(*v).or_default() += change;
+                }
+            }
+        }
+    }
+
+    /// Conflicts take precedence over uncolored vertices, both are sorted
+    pub fn query(self) -> Verdict {
+        let mut conflicts: Vec<_> = self
+            .conflicts
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(edge, _)| edge)
+            .collect();
+        let mut uncolored: Vec<_> = self
+            .uncolored
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(vertex, _)| vertex)
+            .collect();
+
+        if !conflicts.is_empty() {
+            conflicts.sort_unstable();
+            Verdict::Improper(conflicts)
+        } else if !uncolored.is_empty() {
+            uncolored.sort_unstable();
+            Verdict::Partial(uncolored)
+        } else {
+            Verdict::Proper
+        }
+    }
+}
+
+impl<'c, C> StreamProcessor<(Edge<VertexId, ()>, bool), Verdict> for ColoringVerifier<'c, C>
+where
+    C: Ord + Clone,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        ColoringVerifier::feed(self, token)
+    }
+
+    fn query(self) -> Verdict {
+        ColoringVerifier::query(self)
+    }
+}
+
+/// Replay a stream against a coloring
+pub fn verify<C, I>(coloring: &Partition<VertexId, C>, stream: I) -> Verdict
+where
+    C: Ord + Clone,
+    I: IntoIterator<Item = (Edge<VertexId, ()>, bool)>,
+{
+    let mut verifier = ColoringVerifier::init(coloring);
+    stream.into_iter().for_each(|token| verifier.feed(token));
+    verifier.query()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn stream(tokens: &[((VertexId, VertexId), bool)]) -> Vec<(Edge<VertexId, ()>, bool)> {
+        tokens
+            .iter()
+            .map(|((u, v), c)| (Edge::init(*u, *v), *c))
+            .collect()
+    }
+
+    #[test]
+    fn proper() {
+        let coloring: Partition<VertexId> = vec![(0, 0), (1, 1), (2, 0)].into_iter().collect();
+
+        assert_eq!(
+            verify(&coloring, stream(&[((0, 1), true), ((1, 2), true)])),
+            Verdict::Proper
+        );
+
+        // The monochromatic edge is deleted again before the end of the stream
+        assert_eq!(
+            verify(
+                &coloring,
+                stream(&[((0, 2), true), ((0, 1), true), ((2, 0), false)])
+            ),
+            Verdict::Proper
+        );
+    }
+
+    #[test]
+    fn improper() {
+        let coloring: Partition<VertexId> = vec![(0, 0), (1, 1), (2, 0)].into_iter().collect();
+
+        assert_eq!(
+            verify(&coloring, stream(&[((2, 0), true), ((0, 1), true)])),
+            Verdict::Improper(vec![(0, 2)])
+        );
+    }
+
+    #[test]
+    fn partial() {
+        let coloring: Partition<VertexId> = vec![(0, 0), (1, 1)].into_iter().collect();
+
+        assert_eq!(
+            verify(&coloring, stream(&[((0, 1), true), ((1, 5), true)])),
+            Verdict::Partial(vec![5])
+        );
+        assert!(verify(
+            &coloring,
+            stream(&[((0, 1), true), ((1, 5), true), ((1, 5), false)])
+        )
+        .is_proper());
+    }
+}
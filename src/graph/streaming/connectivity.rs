@@ -0,0 +1,876 @@
+//! Connectivity Sketches
+//!
+//! The spanning forest sketch of [Ahn, Guha and McGregor](https://doi.org/10.1137/1.9781611973099.40) gives every vertex `v` the
+//! incidence vector `a_v` over the edge space `[n choose 2]`, where `a_v[(u, w)]` is `+1` if `v = u < w`, `-1` if `u < w = v`, and
+//! `0` otherwise. Summing the vectors of a set of vertices cancels every edge inside the set, leaving exactly the edges leaving it,
+//! so an L0 sampler over the sum finds an edge out of every component, and Borůvka's algorithm builds a spanning forest.
+//!
+//! Since the samplers are linear, edges can also be subtracted after the stream. Taking `F_1` a spanning forest of `G`, `F_2` one of
+//! `G - F_1`, and so on, the union of `F_1, ..., F_k` is `k`-edge-connected if and only if `G` is, which [KConnectivity] checks exactly.
+//!
+//! Counting the connected components only takes the size of the forest, see [ForestSketch::query_component_count]. Without
+//! deletions, the [ComponentCounter] counts them exactly with a union find, in `O(n log n)` bits instead of `O(n log^3 n)`.
+//!
+//! The levels of the samplers also tell roughly how many edges leave a set of vertices, and the [CutQuery] of the sketches
+//! estimates the size of any cut from them.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    mem::size_of,
+};
+
+use algebraics::traits::CeilLog2;
+use num_integer::binomial;
+
+use crate::{
+    graph::{
+        streaming::{
+            provenance::{AlgorithmInfo, Provenance},
+            space::{log, SpaceReport, SpaceUsage},
+            sparse_recovery::{one_sparse::OneSparseRecovery, SparseRecoveryOutput},
+            Merge, StreamProcessor,
+        },
+        Edge, VertexId,
+    },
+    utils::hash_function::HashFunction,
+};
+
+/// One Borůvka round: an L0 sampler of the incidence vector of every vertex
+///
+/// The randomness of the samplers is shared by every vertex, so that the samplers of a set of vertices can be summed.
+#[derive(Clone, Debug)]
+struct Round<H> {
+    /// The edge `j` is kept at level `l` if `h_l(j) = 0`, i.e. with probability `2^-l`
+    hashers: Vec<H>,
+    /// The one-sparse recovery of each level, for each vertex
+    cells: Vec<Vec<OneSparseRecovery>>,
+}
+
+impl<H> Round<H>
+where
+    H: HashFunction,
+{
+    fn init(n: VertexId, universe: u64, base: &OneSparseRecovery) -> Self {
+        let universe_pow = universe.next_power_of_two().max(2);
+        let levels = universe_pow.ceil_log2().unwrap() as u32 + 1;
+
+        let hashers = (0..levels)
+            .map(|l| H::init(universe_pow, 2_u64.pow(l)))
+            .collect();
+        let empty: Vec<_> = (0..levels).map(|_| base.random_copy()).collect();
+
+        Self {
+            hashers,
+            cells: (0..n).map(|_| empty.clone()).collect(),
+        }
+    }
+
+    fn feed(&mut self, vertex: VertexId, token: (u64, bool)) {
+        let Self { hashers, cells } = self;
+        let (j, _) = token;
+
+        for (hasher, cell) in hashers.iter().zip(cells[vertex as usize].iter_mut()) {
+            if hasher.is_zero(j) {
+                cell.feed(token);
+            }
+        }
+    }
+
+    /// The sum of the samplers of every level over the set of vertices, which cancels the edges inside the set
+    fn sum(&self, component: &[VertexId]) -> Option<Vec<OneSparseRecovery>> {
+        let (first, rest) = component.split_first()?;
+
+        let mut sum = self.cells[*first as usize].clone();
+        for v in rest {
+            for (total, cell) in sum.iter_mut().zip(self.cells[*v as usize].iter()) {
+                total.combine(cell);
+            }
+        }
+        Some(sum)
+    }
+
+    /// Sample an edge leaving the set of vertices, `None` if the sampler failed or no edge leaves it
+    fn sample(&self, component: &[VertexId], n: VertexId) -> Option<Edge<VertexId, ()>> {
+        let sum = self.sum(component)?;
+
+        let inside: HashSet<&VertexId> = component.iter().collect();
+        sum.into_iter().find_map(|cell| match cell.query() {
+            SparseRecoveryOutput::Recovered((j, l)) if l != 0 => {
+                let edge = Edge::from_d1(j);
+                let (u, v) = edge.vertices_ord();
+                // Guard against the false positives of the one-sparse recovery
+                (*v < n && inside.contains(u) != inside.contains(v)).then_some(edge)
+            }
+            _ => None,
+        })
+    }
+
+    /// Whether each level kept none of the edges leaving the set of vertices
+    fn missed_levels(&self, side: &[VertexId]) -> Vec<bool> {
+        match self.sum(side) {
+            Some(sum) => sum
+                .iter()
+                .map(|cell| matches!(cell.query_ref(), SparseRecoveryOutput::Zero))
+                .collect(),
+            None => vec![true; self.hashers.len()],
+        }
+    }
+}
+
+/// Estimated number of edges across cuts, after a single pass over the stream
+///
+/// Connectivity only tells whether a cut is empty, the same sketches can also estimate how many edges cross any cut chosen after
+/// the stream ends, e.g. to compare candidate partitions of the vertices.
+pub trait CutQuery {
+    /// Estimated number of edges with exactly one endpoint in `side`
+    fn cut_size_estimate(&self, side: &HashSet<VertexId>) -> f64;
+}
+
+/// Maximum likelihood estimate of the size `c` of a cut, from whether each level of each sampler missed it
+///
+/// Level `l` keeps each edge with probability `2^-l`, so it misses all `c` edges with probability `q_l^c`, `q_l = 1 - 2^-l`. The
+/// score of `c` decreases with `c`, so its root is found by bisection. Level 0 keeps every edge, and decides whether the cut is empty.
+fn estimate_cut(missed: &[Vec<bool>]) -> f64 {
+    let samplers = missed.len();
+    let levels = missed.iter().map(Vec::len).min().unwrap_or(0);
+    let misses = |l: usize| missed.iter().filter(|m| m[l]).count() as f64;
+
+    if levels == 0 || 2.0 * misses(0) >= samplers as f64 {
+        return 0.0;
+    }
+
+    let score = |c: f64| {
+        (1..levels)
+            .map(|l| {
+                let q = 1.0 - 0.5_f64.powi(l as i32);
+                let missed = misses(l);
+                let hit = samplers as f64 - missed;
+                missed * q.ln() - hit * q.ln() * q.powf(c) / (1.0 - q.powf(c))
+            })
+            .sum::<f64>()
+    };
+
+    // The cut has at least one edge, and the last level keeps the edges of any cut with constant probability
+    let (mut low, mut high) = (0.0, (levels - 1) as f64);
+    if score(2_f64.powf(low)) <= 0.0 {
+        return 1.0;
+    }
+    if score(2_f64.powf(high)) >= 0.0 {
+        return 2_f64.powf(high);
+    }
+    for _ in 0..50 {
+        let middle = (low + high) / 2.0;
+        if score(2_f64.powf(middle)) > 0.0 {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    2_f64.powf(low)
+}
+
+/// Spanning forest of a turnstile graph stream over the vertices `[n]`
+///
+/// The forest is spanning with high probability, when a sampler fails the forest may miss some edges between components.
+///
+/// Storage: O(log n) rounds of `n` L0 samplers, O(n log^3 n) bits
+#[derive(Clone, Debug)]
+pub struct ForestSketch<H> {
+    n: VertexId,
+    rounds: Vec<Round<H>>,
+}
+
+impl<H> ForestSketch<H>
+where
+    H: HashFunction,
+{
+    /// Initialize a new sketch over the vertices `[n]`
+    pub fn init(n: VertexId) -> Self {
+        let universe = binomial(n, 2).max(1);
+        let base = OneSparseRecovery::init(universe);
+
+        // A component merges in a round whenever its sampler succeeds, which happens with constant probability
+        let rounds = 4 * (n.max(2).ceil_log2().unwrap() + 1);
+
+        Self {
+            n,
+            rounds: (0..rounds)
+                .map(|_| Round::init(n, universe, &base))
+                .collect(),
+        }
+    }
+
+    /// Feed an edge insertion or deletion, self loops are ignored
+    ///
+    /// Panics if an endpoint is not in `[n]`.
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
+        let (u, v) = edge.vertices_ord();
+        if u == v {
+            return;
+        }
+        assert!(
+            *v < self.n,
+            "[ForestSketch] {} is not a vertex of [{}]",
+            v,
+            self.n
+        );
+
+        let j = edge.to_d1();
+        for round in self.rounds.iter_mut() {
+            round.feed(*u, (j, c));
+            round.feed(*v, (j, !c));
+        }
+    }
+
+    /// The edges of a spanning forest of the streamed graph
+    ///
+    /// The number of connected components, counting isolated vertices, is `n` minus the number of edges.
+    pub fn query(self) -> Vec<Edge<VertexId, ()>> {
+        let mut components = Components::init(self.n);
+        let mut forest = vec![];
+
+        for round in self.rounds.iter() {
+            let found: Vec<_> = components
+                .members()
+                .iter()
+                .filter_map(|component| round.sample(component, self.n))
+                .collect();
+
+            for edge in found {
+                let (u, v) = edge.vertices();
+                if components.union(*u, *v) {
+                    forest.push(edge);
+                }
+            }
+
+            if forest.len() as u64 + 1 >= self.n {
+                break;
+            }
+        }
+
+        forest
+    }
+
+    /// The number of connected components of the streamed graph, counting isolated vertices
+    ///
+    /// Exact with high probability, and otherwise an overestimate: the forest found is always a forest of the graph.
+    pub fn query_component_count(self) -> usize {
+        self.n as usize - self.query().len()
+    }
+
+    /// The vertices of the side of a cut, panics if one is not in `[n]`
+    fn side(&self, side: &HashSet<VertexId>) -> Vec<VertexId> {
+        side.iter()
+            .map(|v| {
+                assert!(
+                    *v < self.n,
+                    "[ForestSketch] {} is not a vertex of [{}]",
+                    v,
+                    self.n
+                );
+                *v
+            })
+            .collect()
+    }
+}
+
+impl<H> CutQuery for ForestSketch<H>
+where
+    H: HashFunction,
+{
+    /// Every round samples the edge space independently, so the estimate improves with the number of rounds, `O(log n)`. An empty
+    /// cut is recognized with high probability.
+    ///
+    /// Runtime: O(|side| log^2 n)
+    fn cut_size_estimate(&self, side: &HashSet<VertexId>) -> f64 {
+        let side = self.side(side);
+        let missed: Vec<Vec<bool>> = self
+            .rounds
+            .iter()
+            .map(|round| round.missed_levels(&side))
+            .collect();
+        estimate_cut(&missed)
+    }
+}
+
+impl<H> SpaceUsage for ForestSketch<H>
+where
+    H: HashFunction,
+{
+    /// `n` one-sparse recoveries per level, `O(log n)` levels per round, and `O(log n)` rounds
+    fn space(&self) -> SpaceReport {
+        let rounds = self.rounds.len();
+        let levels = self.rounds.first().map(|r| r.hashers.len()).unwrap_or(0);
+        let n = self.n as f64;
+
+        SpaceReport {
+            structure: "ForestSketch",
+            bound: "O(n log^3 n)",
+            parameters: vec![
+                ("n", n),
+                ("rounds", rounds as f64),
+                ("levels", levels as f64),
+            ],
+            theoretical_bits: n * rounds as f64 * levels as f64 * log(n),
+            measured_bytes: size_of::<Self>()
+                + self
+                    .rounds
+                    .iter()
+                    .map(|round| {
+                        size_of::<Round<H>>()
+                            + round.hashers.capacity() * size_of::<H>()
+                            + round.cells.capacity() * size_of::<Vec<OneSparseRecovery>>()
+                            + round
+                                .cells
+                                .iter()
+                                .map(|c| c.capacity() * size_of::<OneSparseRecovery>())
+                                .sum::<usize>()
+                    })
+                    .sum::<usize>(),
+            components: vec![],
+        }
+    }
+}
+
+impl<H> StreamProcessor<(Edge<VertexId, ()>, bool), Vec<Edge<VertexId, ()>>> for ForestSketch<H>
+where
+    H: HashFunction,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        ForestSketch::feed(self, token)
+    }
+
+    fn query(self) -> Vec<Edge<VertexId, ()>> {
+        ForestSketch::query(self)
+    }
+}
+
+impl<H> Provenance for ForestSketch<H>
+where
+    H: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "ForestSketch",
+            paper_ref: "Ahn, Guha, McGregor, SODA 2012, https://doi.org/10.1137/1.9781611973099.40",
+            params: vec![("n", self.n as f64), ("rounds", self.rounds.len() as f64)],
+            seed: None,
+        }
+    }
+}
+
+/// Tests whether a turnstile graph stream over the vertices `[n]` is `k`-edge-connected
+///
+/// Keeps `k` independent [ForestSketch]es. At query time, the `i`th sketch has the forests found by the previous ones subtracted,
+/// and the union of the `k` forests, a certificate of at most `k(n - 1)` edges, is tested exactly.
+///
+/// Storage: O(k n log^3 n) bits
+#[derive(Clone, Debug)]
+pub struct KConnectivity<H> {
+    n: VertexId,
+    k: usize,
+    forests: Vec<ForestSketch<H>>,
+}
+
+impl<H> KConnectivity<H>
+where
+    H: HashFunction,
+{
+    /// Initialize a new tester over the vertices `[n]`, for the connectivity `k`
+    pub fn init(n: VertexId, k: usize) -> Self {
+        Self {
+            n,
+            k,
+            forests: (0..k).map(|_| ForestSketch::init(n)).collect(),
+        }
+    }
+
+    /// Feed an edge insertion or deletion, self loops are ignored
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        for forest in self.forests.iter_mut() {
+            forest.feed(token);
+        }
+    }
+
+    /// The union of the `k` edge disjoint forests, which is `k`-edge-connected if and only if the streamed graph is
+    ///
+    /// Edges appear once per forest containing them, which only happens for parallel edges.
+    pub fn certificate(self) -> Vec<Edge<VertexId, ()>> {
+        let mut certificate: Vec<Edge<VertexId, ()>> = vec![];
+
+        for mut sketch in self.forests {
+            for edge in certificate.iter() {
+                sketch.feed((*edge, false));
+            }
+            certificate.extend(sketch.query());
+        }
+
+        certificate
+    }
+
+    /// Whether the streamed graph is `k`-edge-connected
+    pub fn query(self) -> bool {
+        let (n, k) = (self.n, self.k);
+        edge_connected(n, &self.certificate(), k)
+    }
+}
+
+impl<H> CutQuery for KConnectivity<H>
+where
+    H: HashFunction,
+{
+    /// The rounds of all `k` forests sample the same stream independently, so the estimate is sharper than that of a single forest
+    ///
+    /// Runtime: O(k |side| log^2 n)
+    fn cut_size_estimate(&self, side: &HashSet<VertexId>) -> f64 {
+        let missed: Vec<Vec<bool>> = self
+            .forests
+            .iter()
+            .flat_map(|forest| {
+                let side = forest.side(side);
+                forest
+                    .rounds
+                    .iter()
+                    .map(|round| round.missed_levels(&side))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        estimate_cut(&missed)
+    }
+}
+
+impl<H> SpaceUsage for KConnectivity<H>
+where
+    H: HashFunction,
+{
+    fn space(&self) -> SpaceReport {
+        let components: Vec<SpaceReport> = self.forests.iter().map(|f| f.space()).collect();
+
+        SpaceReport {
+            structure: "KConnectivity",
+            bound: "O(k n log^3 n)",
+            parameters: vec![("n", self.n as f64), ("k", self.k as f64)],
+            theoretical_bits: components.iter().map(|c| c.theoretical_bits).sum(),
+            measured_bytes: size_of::<Self>()
+                + components
+                    .iter()
+                    .map(|c| c.measured_bytes - size_of::<ForestSketch<H>>())
+                    .sum::<usize>()
+                + self.forests.capacity() * size_of::<ForestSketch<H>>(),
+            components,
+        }
+    }
+}
+
+impl<H> StreamProcessor<(Edge<VertexId, ()>, bool), bool> for KConnectivity<H>
+where
+    H: HashFunction,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        KConnectivity::feed(self, token)
+    }
+
+    fn query(self) -> bool {
+        KConnectivity::query(self)
+    }
+}
+
+impl<H> Provenance for KConnectivity<H>
+where
+    H: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "KConnectivity",
+            paper_ref: "Ahn, Guha, McGregor, SODA 2012, https://doi.org/10.1137/1.9781611973099.40",
+            params: vec![("n", self.n as f64), ("k", self.k as f64)],
+            seed: None,
+        }
+    }
+}
+
+/// Exact number of connected components of an insertion-only stream of edges over the vertices `[n]`
+///
+/// Storage: O(n log n) bits, a union find
+///
+/// Runtime: O(log n) amortized per edge
+#[derive(Clone, Debug)]
+pub struct ComponentCounter {
+    components: Components,
+    count: usize,
+}
+
+impl ComponentCounter {
+    pub fn init(n: VertexId) -> Self {
+        Self {
+            components: Components::init(n),
+            count: n as usize,
+        }
+    }
+
+    /// Feed an edge insertion
+    ///
+    /// Panics if an endpoint is not in `[n]`.
+    pub fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        let (u, v) = edge.vertices();
+        let n = self.components.parent.len() as VertexId;
+        if *u >= n || *v >= n {
+            panic!(
+                "[ComponentCounter] ({}, {}) is not an edge of [{}]",
+                u, v, n
+            );
+        }
+
+        if self.components.union(*u, *v) {
+            self.count -= 1;
+        }
+    }
+
+    /// The number of connected components so far, counting isolated vertices
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn query(self) -> usize {
+        self.count
+    }
+}
+
+impl StreamProcessor<Edge<VertexId, ()>, usize> for ComponentCounter {
+    fn feed(&mut self, token: Edge<VertexId, ()>) {
+        ComponentCounter::feed(self, token)
+    }
+
+    fn query(self) -> usize {
+        ComponentCounter::query(self)
+    }
+}
+
+impl Merge for ComponentCounter {
+    /// Every vertex is connected to its root in the other stream
+    fn merge(&mut self, other: &Self) {
+        if self.components.parent.len() != other.components.parent.len() {
+            panic!("[ComponentCounter] Cannot merge counters of different vertices");
+        }
+        for (v, parent) in other.components.parent.iter().enumerate() {
+            if self.components.union(v as VertexId, *parent) {
+                self.count -= 1;
+            }
+        }
+    }
+}
+
+impl SpaceUsage for ComponentCounter {
+    /// One parent per vertex
+    fn space(&self) -> SpaceReport {
+        let n = self.components.parent.len() as f64;
+
+        SpaceReport {
+            structure: "ComponentCounter",
+            bound: "O(n log n)",
+            parameters: vec![("n", n)],
+            theoretical_bits: n * log(n),
+            measured_bytes: size_of::<Self>()
+                + self.components.parent.capacity() * size_of::<VertexId>(),
+            components: vec![],
+        }
+    }
+}
+
+impl Provenance for ComponentCounter {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "ComponentCounter",
+            paper_ref: "Tarjan, JACM 1975, https://doi.org/10.1145/321879.321884",
+            params: vec![("n", self.components.parent.len() as f64)],
+            seed: None,
+        }
+    }
+}
+
+/// Union find over `[n]`
+#[derive(Clone, Debug)]
+pub(crate) struct Components {
+    parent: Vec<VertexId>,
+}
+
+impl Components {
+    pub(crate) fn init(n: VertexId) -> Self {
+        Self {
+            parent: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, v: VertexId) -> VertexId {
+        let mut root = v;
+        while self.parent[root as usize] != root {
+            root = self.parent[root as usize];
+        }
+        let mut v = v;
+        while self.parent[v as usize] != root {
+            v = std::mem::replace(&mut self.parent[v as usize], root);
+        }
+        root
+    }
+
+    /// Merge the components of `u` and `v`, false if they already were the same
+    pub(crate) fn union(&mut self, u: VertexId, v: VertexId) -> bool {
+        let (u, v) = (self.find(u), self.find(v));
+        if u == v {
+            return false;
+        }
+        self.parent[u as usize] = v;
+        true
+    }
+
+    /// The vertices of each component
+    pub(crate) fn members(&mut self) -> Vec<Vec<VertexId>> {
+        let mut members: HashMap<VertexId, Vec<VertexId>> = HashMap::new();
+        for v in 0..self.parent.len() as VertexId {
+            let root = self.find(v);
+            members.entry(root).or_default().push(v);
+        }
+        members.into_values().collect()
+    }
+}
+
+/// Unit capacity flow network of an undirected multigraph, one unit per copy of an edge
+pub(crate) struct UnitFlow {
+    capacity: HashMap<(VertexId, VertexId), i64>,
+    neighbors: HashMap<VertexId, HashSet<VertexId>>,
+}
+
+impl UnitFlow {
+    pub(crate) fn init(edges: &[Edge<VertexId, ()>]) -> Self {
+        let mut capacity: HashMap<(VertexId, VertexId), i64> = HashMap::new();
+        let mut neighbors: HashMap<VertexId, HashSet<VertexId>> = HashMap::new();
+        for edge in edges {
+            let (u, v) = edge.vertices();
+            if u == v {
+                continue;
+            }
+            *capacity.entry((*u, *v)).or_default() += 1;
+            *capacity.entry((*v, *u)).or_default() += 1;
+            neighbors.entry(*u).or_default().insert(*v);
+            neighbors.entry(*v).or_default().insert(*u);
+        }
+
+        Self {
+            capacity,
+            neighbors,
+        }
+    }
+
+    /// The number of edge disjoint paths between `s` and `t`, counting at most `limit` of them
+    ///
+    /// Runtime: O(limit · m)
+    pub(crate) fn local_connectivity(&self, s: VertexId, t: VertexId, limit: usize) -> usize {
+        let mut residual = self.capacity.clone();
+
+        (0..limit)
+            .take_while(|_| {
+                // Breadth first search for an augmenting path from s to t
+                let mut previous: HashMap<VertexId, VertexId> = HashMap::new();
+                let mut queue = VecDeque::from(vec![s]);
+                while let Some(u) = queue.pop_front() {
+                    if u == t {
+                        break;
+                    }
+                    for v in self.neighbors.get(&u).into_iter().flatten() {
+                        if *v != s && !previous.contains_key(v) && residual[&(u, *v)] > 0 {
+                            previous.insert(*v, u);
+                            queue.push_back(*v);
+                        }
+                    }
+                }
+
+                if !previous.contains_key(&t) {
+                    return false;
+                }
+                let mut v = t;
+                while v != s {
+                    let u = previous[&v];
+                    *residual.get_mut(&(u, v)).unwrap() -= 1;
+                    *residual.get_mut(&(v, u)).unwrap() += 1;
+                    v = u;
+                }
+                true
+            })
+            .count()
+    }
+}
+
+/// Whether the multigraph over `[n]` is `k`-edge-connected, by `n - 1` unit capacity max flows of value at most `k`
+///
+/// Runtime: O(n k m)
+fn edge_connected(n: VertexId, edges: &[Edge<VertexId, ()>], k: usize) -> bool {
+    if n <= 1 || k == 0 {
+        return true;
+    }
+
+    let flow = UnitFlow::init(edges);
+    (1..n).all(|t| flow.local_connectivity(0, t, k) == k)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::utils::hash_function::PowerFiniteFieldHasher;
+
+    type Hasher = PowerFiniteFieldHasher;
+
+    fn cycle(n: VertexId) -> Vec<Edge<VertexId, ()>> {
+        (0..n).map(|v| Edge::init(v, (v + 1) % n)).collect()
+    }
+
+    #[test]
+    fn spanning_forest() {
+        let mut sketch = ForestSketch::<Hasher>::init(12);
+
+        // Two cycles, and a path which is deleted again
+        cycle(5)
+            .into_iter()
+            .chain((5..9).map(|v| Edge::init(v, v + 1)))
+            .chain(vec![
+                Edge::init(5, 9),
+                Edge::init(9, 10),
+                Edge::init(10, 11),
+            ])
+            .for_each(|e| sketch.feed((e, true)));
+        sketch.feed((Edge::init(9, 10), false));
+        sketch.feed((Edge::init(10, 11), false));
+
+        let forest = sketch.query();
+        let mut components = Components::init(12);
+        for edge in forest.iter() {
+            let (u, v) = edge.vertices();
+            assert!(components.union(*u, *v), "The forest has a cycle");
+        }
+
+        // {0..4}, {5..9}, {10}, {11}
+        assert_eq!(forest.len(), 12 - 4);
+        assert_eq!(components.find(3), components.find(0));
+        assert_eq!(components.find(9), components.find(5));
+        assert_ne!(components.find(0), components.find(5));
+    }
+
+    #[test]
+    fn component_count() {
+        let n = 30;
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+        let edges: Vec<_> = (0..25)
+            .map(|_| Edge::init(rng.gen_range(0..n), rng.gen_range(0..n)))
+            .collect();
+
+        // Half of the edges are inserted, then deleted again
+        let mut sketch = ForestSketch::<Hasher>::init(n);
+        let mut counter = ComponentCounter::init(n);
+        for (i, edge) in edges.iter().enumerate() {
+            sketch.feed((*edge, true));
+            if i % 2 == 0 {
+                counter.feed(*edge);
+            }
+        }
+        edges
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .for_each(|edge| sketch.feed((*edge, false)));
+
+        let mut components = Components::init(n);
+        let expected = n as usize
+            - edges
+                .iter()
+                .step_by(2)
+                .filter(|edge| components.union(*edge.vertices().0, *edge.vertices().1))
+                .count();
+        assert_eq!(counter.count(), expected);
+        assert_eq!(sketch.query_component_count(), expected);
+    }
+
+    #[test]
+    fn merge_counters() {
+        let (mut left, mut right) = (ComponentCounter::init(8), ComponentCounter::init(8));
+        left.feed(Edge::init(0, 1));
+        left.feed(Edge::init(2, 3));
+        right.feed(Edge::init(1, 2));
+        right.feed(Edge::init(5, 6));
+
+        left.merge(&right);
+        // {0, 1, 2, 3}, {4}, {5, 6}, {7}
+        assert_eq!(left.query(), 4);
+    }
+
+    #[test]
+    fn exact() {
+        assert!(edge_connected(6, &cycle(6), 2));
+        assert!(!edge_connected(6, &cycle(6), 3));
+        assert!(!edge_connected(7, &cycle(6), 1));
+
+        let mut doubled = cycle(6);
+        doubled.extend(cycle(6));
+        assert!(edge_connected(6, &doubled, 4));
+    }
+
+    #[test]
+    fn k_connectivity() {
+        let n = 10;
+        let complete: Vec<_> = (0..n)
+            .flat_map(|u| (u + 1..n).map(move |v| Edge::init(u, v)))
+            .collect();
+
+        let mut tester = KConnectivity::<Hasher>::init(n, 3);
+        complete.iter().for_each(|e| tester.feed((*e, true)));
+        assert!(tester.clone().query());
+
+        // Isolate vertex 0 down to two edges
+        (3..n).for_each(|v| tester.feed((Edge::init(0, v), false)));
+        assert!(!tester.clone().query());
+
+        tester.feed((Edge::init(0, 4), true));
+        let certificate = tester.clone().certificate();
+        assert!(certificate.len() <= 3 * (n as usize - 1));
+        assert!(tester.query());
+    }
+    #[test]
+    fn cut_size() {
+        // Every vertex of [0, 20) is joined to every vertex of [20, 40) of the same parity
+        let n = 40;
+        let edges: Vec<_> = (0..20)
+            .flat_map(|u| {
+                (20..40)
+                    .filter(move |v| (u + v) % 2 == 0)
+                    .map(move |v| Edge::init(u, v))
+            })
+            .collect();
+
+        let mut tester = KConnectivity::<Hasher>::init(n, 4);
+        edges.iter().for_each(|e| tester.feed((*e, true)));
+
+        let within = |side: HashSet<VertexId>, size: f64| {
+            let estimate = tester.cut_size_estimate(&side);
+            assert!(
+                size / 2.0 <= estimate && estimate <= 2.0 * size,
+                "{} for a cut of {}",
+                estimate,
+                size
+            );
+        };
+        within((0..20).collect(), 200.0);
+        within([3].into(), 10.0);
+        within((0..5).collect(), 50.0);
+
+        // The even and odd vertices are not connected
+        assert_eq!(tester.cut_size_estimate(&(0..40).step_by(2).collect()), 0.0);
+        assert_eq!(tester.cut_size_estimate(&HashSet::new()), 0.0);
+
+        tester.feed((Edge::init(0, 1), true));
+        let forest = &tester.forests[0];
+        let single = forest.cut_size_estimate(&(0..40).step_by(2).collect());
+        assert!((0.5..=2.0).contains(&single));
+    }
+}
@@ -1,12 +1,28 @@
+//! Approximate Counting
+//!
+//! The length of a stream, counted by the [Morris](https://doi.org/10.1145/359619.359627) counter in O(log log n) bits: a counter
+//! `x` is incremented with probability `2^-x` for every token, and `2^x - 1` is an unbiased estimate of the length, of variance
+//! `n (n - 1) / 2`.
+//!
+//! Morris+ averages `s = ⌈3 / (2ε²)⌉` independent counters, which are within `ε n` of the length with probability 2/3 by
+//! Chebyshev, and takes the median of `O(log(1/δ))` such averages, so that it is within `ε n` with probability `1 - δ`.
+
 use rand::Rng;
 
 pub trait Counting {
+    /// Estimate of the length of the stream, by a single counter
     fn morris(self) -> i32;
+
+    /// Estimate of the length of the stream, within a factor `1 ± ε` with probability `1 - δ`
+    fn morris_plus(self, epsilon: f64, delta: f64) -> u64;
+
+    /// [morris_plus](Counting::morris_plus), drawing the increments from the generator
+    fn morris_plus_with_rng<R: Rng + ?Sized>(self, epsilon: f64, delta: f64, rng: &mut R) -> u64;
 }
 
 impl<T> Counting for T
 where
-    T: core::iter::Iterator<Item = (i32, i32)> + Sized,
+    T: core::iter::Iterator + Sized,
 {
     fn morris(self) -> i32 {
         let mut x = 0;
@@ -22,4 +38,69 @@ where
 
         2_i32.pow(x) - 1
     }
+
+    fn morris_plus(self, epsilon: f64, delta: f64) -> u64 {
+        self.morris_plus_with_rng(epsilon, delta, &mut rand::thread_rng())
+    }
+
+    fn morris_plus_with_rng<R: Rng + ?Sized>(self, epsilon: f64, delta: f64, rng: &mut R) -> u64 {
+        if epsilon <= 0.0 || delta <= 0.0 || delta >= 1.0 {
+            panic!(
+                "[Counting] ε must be positive and δ within (0, 1): {}, {}",
+                epsilon, delta
+            );
+        }
+
+        let group_size = (1.5 / epsilon.powi(2)).ceil() as usize;
+        // An odd number of groups, so that the median is one of them
+        let groups = (4.0 * (1.0 / delta).ln()).ceil() as usize | 1;
+
+        let mut counters = vec![0_i32; groups * group_size];
+        self.for_each(|_| {
+            for x in counters.iter_mut() {
+                if rng.gen_bool(0.5_f64.powi(*x)) {
+                    *x += 1;
+                }
+            }
+        });
+
+        let mut averages: Vec<f64> = counters
+            .chunks(group_size)
+            .map(|group| {
+                group.iter().map(|x| 2_f64.powi(*x) - 1.0).sum::<f64>() / group_size as f64
+            })
+            .collect();
+        averages.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+        averages[averages.len() / 2].round() as u64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+
+    #[test]
+    fn morris_plus() {
+        let epsilon = 0.2;
+
+        for (seed, n) in [(0, 100), (1, 1_000), (2, 5_000)].iter() {
+            let mut rng = ChaCha20Rng::seed_from_u64(*seed);
+            let estimate = (0..*n)
+                .map(|i| i as f64)
+                .morris_plus_with_rng(epsilon, 0.05, &mut rng);
+
+            assert!(
+                (estimate as f64 - *n as f64).abs() <= epsilon * *n as f64,
+                "estimated a length of {}, expected {}",
+                estimate,
+                n
+            );
+        }
+
+        assert_eq!(std::iter::empty::<()>().morris_plus(0.5, 0.5), 0);
+    }
 }
@@ -1,46 +1,361 @@
 //! Distinct Counting
+//!
+//! [HyperLogLog](http://algo.inria.fr/flajolet/Publications/FlFuGaMe07.pdf) of Flajolet, Fusy, Gandouet and Meunier. Each item
+//! is hashed to 64 bits, the first `b` bits picking one of `m = 2^b` registers, which keeps the largest position of the first one
+//! bit among the remaining bits. The harmonic mean of `2^register` over the registers, scaled, estimates the number of distinct
+//! items with a standard error of `1.04 / √m`.
+//!
+//! For an error of `ε` with probability `1 - δ`, the registers are chosen so that the standard error is `ε / 2`, making an error
+//! beyond `ε` happen with probability at most `1/4` by Chebyshev, and the estimate is the median of `O(log(1/δ))` independent copies.
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem::size_of,
+};
 
-fn zeros(p: u64) -> u64 {
-    let (mut max, mut curr) = (0, 0);
-    while curr <= p / 2 {
-        if p % 2_u64.pow(curr as u32) == 0 {
-            max = curr
-        }
-        curr += 1;
-    }
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
 
-    max
-}
+use super::{
+    provenance::{AlgorithmInfo, Provenance},
+    space::{SpaceReport, SpaceUsage},
+    Merge, StreamProcessor,
+};
 
-fn calculate_hash<T: Hash>(t: &T) -> u64 {
+/// Hash of `t` under the hash function determined by `seed`
+fn calculate_hash<T: Hash + ?Sized>(t: &T, seed: u64) -> u64 {
     let mut s = DefaultHasher::new();
+    seed.hash(&mut s);
     t.hash(&mut s);
     s.finish()
 }
 
+/// Parameters of a [HyperLogLog]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DistinctParameters {
+    /// Relative error of the estimate
+    pub epsilon: f64,
+    /// Probability with which the estimate exceeds its error
+    pub delta: f64,
+}
+
+impl Default for DistinctParameters {
+    fn default() -> Self {
+        Self {
+            epsilon: 0.1,
+            delta: 0.05,
+        }
+    }
+}
+
+impl DistinctParameters {
+    /// Number of bits `b` of the hash that pick a register, between 4 and 18
+    pub fn register_bits(&self) -> u32 {
+        let registers = (2.08 / self.epsilon).powi(2).ceil() as u64;
+        registers.next_power_of_two().trailing_zeros().clamp(4, 18)
+    }
+
+    /// Number of independent copies the median is taken over, always odd
+    pub fn copies(&self) -> usize {
+        (4.0 * (1.0 / self.delta).ln()).ceil() as usize | 1
+    }
+}
+
+/// One HyperLogLog sketch
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Registers {
+    seed: u64,
+    registers: Vec<u8>,
+}
+
+impl Registers {
+    fn insert(&mut self, hash: u64, bits: u32) {
+        let index = (hash >> (64 - bits)) as usize;
+        // Position of the first one bit of the rest, 64 - b + 1 if there is none
+        let rank = ((hash << bits) | (1 << (bits - 1))).leading_zeros() as u8 + 1;
+
+        let register = &mut self.registers[index];
+        *register = (*register).max(rank);
+    }
+
+    fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|r| 2_f64.powi(-(*r as i32)))
+            .sum();
+        let raw = alpha * m * m / sum;
+
+        // Few items leave registers empty, linear counting is then more accurate
+        let empty = self.registers.iter().filter(|r| **r == 0).count();
+        if raw <= 2.5 * m && empty > 0 {
+            m * (m / empty as f64).ln()
+        } else {
+            raw
+        }
+    }
+}
+
+/// Estimates the number of distinct items of a stream, within a factor `1 ± ε` with probability `1 - δ`
+///
+/// Storage: O(log(1/δ) / ε^2) registers of O(log log n) bits
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct HyperLogLog<T: ?Sized> {
+    parameters: DistinctParameters,
+    bits: u32,
+    copies: Vec<Registers>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    item: PhantomData<fn(&T)>,
+}
+
+impl<T> HyperLogLog<T>
+where
+    T: Hash + ?Sized,
+{
+    pub fn init(parameters: DistinctParameters) -> Self {
+        Self::init_with_rng(parameters, &mut rand::thread_rng())
+    }
+
+    /// Initialize a counter, drawing the seeds of its hash functions from the generator
+    pub fn init_with_rng<R: Rng + ?Sized>(parameters: DistinctParameters, rng: &mut R) -> Self {
+        if parameters.epsilon <= 0.0 || parameters.delta <= 0.0 || parameters.delta >= 1.0 {
+            panic!(
+                "[HyperLogLog] ε must be positive and δ within (0, 1): {:?}",
+                parameters
+            );
+        }
+
+        let bits = parameters.register_bits();
+        let copies = (0..parameters.copies())
+            .map(|_| Registers {
+                seed: rng.gen(),
+                registers: vec![0; 1 << bits],
+            })
+            .collect();
+
+        Self {
+            parameters,
+            bits,
+            copies,
+            item: PhantomData,
+        }
+    }
+
+    /// Feed an item of the stream, repeated items leave the counter unchanged
+    pub fn insert(&mut self, item: &T) {
+        let bits = self.bits;
+        for copy in self.copies.iter_mut() {
+            copy.insert(calculate_hash(item, copy.seed), bits);
+        }
+    }
+
+    /// The estimate, without consuming the counter
+    pub fn estimate(&self) -> f64 {
+        let mut estimates: Vec<f64> = self.copies.iter().map(Registers::estimate).collect();
+        estimates.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+        estimates[estimates.len() / 2]
+    }
+
+    pub fn query(self) -> f64 {
+        self.estimate()
+    }
+}
+
+impl<T> StreamProcessor<T, f64> for HyperLogLog<T>
+where
+    T: Hash,
+{
+    fn feed(&mut self, token: T) {
+        self.insert(&token)
+    }
+
+    fn query(self) -> f64 {
+        HyperLogLog::query(self)
+    }
+}
+
+impl<T> Merge for HyperLogLog<T>
+where
+    T: ?Sized,
+{
+    /// A register of the union is the largest of the two registers
+    fn merge(&mut self, other: &Self) {
+        let seeds = |copies: &[Registers]| copies.iter().map(|c| c.seed).collect::<Vec<_>>();
+        if seeds(&self.copies) != seeds(&other.copies) || self.bits != other.bits {
+            panic!("[HyperLogLog] Cannot merge counters with different hash functions");
+        }
+
+        for (copy, other) in self.copies.iter_mut().zip(other.copies.iter()) {
+            copy.registers
+                .iter_mut()
+                .zip(other.registers.iter())
+                .for_each(|(register, other)| *register = (*register).max(*other));
+        }
+    }
+}
+
+impl<T> SpaceUsage for HyperLogLog<T>
+where
+    T: ?Sized,
+{
+    /// `m` registers of 6 bits per copy
+    fn space(&self) -> SpaceReport {
+        let registers = 1_usize << self.bits;
+        let copies = self.copies.len();
+
+        SpaceReport {
+            structure: "HyperLogLog",
+            bound: "O(log(1/δ)/ε² · log log n)",
+            parameters: vec![("m", registers as f64), ("copies", copies as f64)],
+            theoretical_bits: (copies * registers) as f64 * 6.0,
+            measured_bytes: size_of::<Self>()
+                + copies * (size_of::<Registers>() + registers * size_of::<u8>()),
+            components: vec![],
+        }
+    }
+}
+
+impl<T> Provenance for HyperLogLog<T>
+where
+    T: ?Sized,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "HyperLogLog",
+            paper_ref: "Flajolet, Fusy, Gandouet, Meunier, AofA 2007, http://algo.inria.fr/flajolet/Publications/FlFuGaMe07.pdf",
+            params: vec![
+                ("ε", self.parameters.epsilon),
+                ("δ", self.parameters.delta),
+                ("m", (1_u64 << self.bits) as f64),
+            ],
+            seed: None,
+        }
+    }
+}
+
+/// Number of distinct items of an iterator, estimated in a single pass
 pub trait Distinct {
-    /// Tidemark Algorithm
-    fn tidemark(self) -> f32;
+    /// Estimate with random hash functions
+    fn distinct(self, parameters: DistinctParameters) -> f64;
+    /// Estimate with the hash functions determined by `seed`
+    ///
+    /// The same seed and stream always produce the same estimate.
+    fn distinct_seeded(self, parameters: DistinctParameters, seed: u64) -> f64;
 }
 
-impl<T> Distinct for T
+impl<I> Distinct for I
 where
-    T: core::iter::Iterator<Item = (i32, i32)> + Sized,
+    I: Iterator,
+    I::Item: Hash,
 {
-    fn tidemark(self) -> f32 {
-        let mut z = 0;
-
-        self.for_each(|token| {
-            let hash = calculate_hash(&token);
-            let zeros = zeros(hash);
-            if zeros < z {
-                z = zeros
+    fn distinct(self, parameters: DistinctParameters) -> f64 {
+        self.distinct_seeded(parameters, rand::random())
+    }
+
+    fn distinct_seeded(self, parameters: DistinctParameters, seed: u64) -> f64 {
+        let mut counter =
+            HyperLogLog::init_with_rng(parameters, &mut ChaCha20Rng::seed_from_u64(seed));
+        self.for_each(|item| counter.insert(&item));
+        counter.query()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rank() {
+        let mut registers = Registers {
+            seed: 0,
+            registers: vec![0; 16],
+        };
+
+        // Register 0b1010, then a first one bit in the 3rd position
+        registers.insert((0b1010 << 60) | (0b001 << 57), 4);
+        assert_eq!(registers.registers[0b1010], 3);
+        // No one bit at all among the 60 remaining bits
+        registers.insert(0, 4);
+        assert_eq!(registers.registers[0], 61);
+    }
+
+    #[test]
+    fn deterministic() {
+        let stream = || (0..100).map(|i| (i, i + 1));
+        let parameters = DistinctParameters::default();
+
+        assert_eq!(
+            stream().distinct_seeded(parameters, 7),
+            stream().distinct_seeded(parameters, 7)
+        );
+        // Repeated tokens do not change the estimate
+        assert_eq!(
+            stream().chain(stream()).distinct_seeded(parameters, 7),
+            stream().distinct_seeded(parameters, 7)
+        );
+    }
+
+    #[test]
+    fn accuracy() {
+        let parameters = DistinctParameters {
+            epsilon: 0.1,
+            delta: 0.05,
+        };
+
+        for n in [10, 1_000, 100_000] {
+            let estimate = (0..n).map(|i| (i, -i)).distinct(parameters);
+            let n = n as f64;
+
+            assert!(
+                (estimate - n).abs() <= parameters.epsilon * n,
+                "estimated {} distinct tokens, expected {}",
+                estimate,
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn items() {
+        let words = [
+            "a", "stream", "of", "a", "few", "words", "of", "a", "stream",
+        ];
+
+        let mut counter: HyperLogLog<str> = HyperLogLog::init(Default::default());
+        words.iter().for_each(|word| counter.insert(word));
+        assert!((counter.estimate() - 5.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn merge() {
+        let parameters = DistinctParameters::default();
+        let base: HyperLogLog<u64> = HyperLogLog::init(parameters);
+        let (mut left, mut right, mut whole) = (base.clone(), base.clone(), base);
+
+        for i in 0..5_000 {
+            whole.insert(&i);
+            if i % 3 == 0 {
+                left.insert(&i);
+            } else {
+                right.insert(&i);
             }
-        });
+        }
+        left.merge(&right);
 
-        2_i32.pow(z as u32) as f32 * 2_f32.sqrt()
+        assert_eq!(left.estimate(), whole.estimate());
     }
 }
@@ -0,0 +1,405 @@
+//! Streaming Forest Testing
+//!
+//! A graph over `[n]` with `c` connected components is a forest if and only if it has exactly `n - c` edges, the edges of any of
+//! its spanning forests. Both testers count the edges, and compare them to a spanning forest: the [ForestTester] builds it with a
+//! union find on an insertion only stream, and the [StreamForest] recovers it from a [ForestSketch] on a turnstile stream.
+//!
+//! A graph that is not a forest has an edge `u - v` outside of its spanning forest, and that edge closes a cycle with the path from
+//! `u` to `v` within the forest. The [ForestTester] stores its forest, so it reports the cycle closed by the first such edge. The
+//! [StreamForest] subtracts its forest from the sketch at query time, and samples such an edge from what remains, which fails with
+//! small probability.
+//!
+//! Self loops are ignored, as by the other testers, so a parallel edge is the shortest cycle found.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    mem::size_of,
+};
+
+use crate::{
+    graph::{
+        streaming::{
+            connectivity::{Components, ForestSketch},
+            provenance::{AlgorithmInfo, Provenance},
+            space::{log, SpaceReport, SpaceUsage},
+            StreamProcessor,
+        },
+        Edge, VertexId,
+    },
+    utils::hash_function::HashFunction,
+};
+
+/// Outcome of a forest test
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Acyclicity {
+    /// The streamed graph is a forest
+    Forest,
+    /// The vertices of a cycle of the streamed graph, in order, the last one adjacent to the first
+    Cycle(Vec<VertexId>),
+    /// The streamed graph has a cycle, but none could be recovered
+    Cyclic,
+}
+
+impl Acyclicity {
+    pub fn is_forest(&self) -> bool {
+        matches!(self, Self::Forest)
+    }
+
+    /// The certificate cycle, if one was found
+    pub fn cycle(&self) -> Option<&[VertexId]> {
+        match self {
+            Self::Cycle(cycle) => Some(cycle),
+            _ => None,
+        }
+    }
+}
+
+/// The edges of a forest, by endpoint
+#[derive(Clone, Debug, Default)]
+struct Adjacency {
+    neighbors: HashMap<VertexId, Vec<VertexId>>,
+}
+
+impl Adjacency {
+    fn add(&mut self, u: VertexId, v: VertexId) {
+        self.neighbors.entry(u).or_default().push(v);
+        self.neighbors.entry(v).or_default().push(u);
+    }
+
+    /// The vertices of the path from `u` to `v`, both included, `None` if they are in different trees
+    fn path(&self, u: VertexId, v: VertexId) -> Option<Vec<VertexId>> {
+        let mut previous: HashMap<VertexId, VertexId> = HashMap::new();
+        let mut queue = VecDeque::from(vec![u]);
+        previous.insert(u, u);
+
+        while let Some(w) = queue.pop_front() {
+            if w == v {
+                let mut path = vec![v];
+                while *path.last().unwrap() != u {
+                    path.push(previous[path.last().unwrap()]);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for x in self.neighbors.get(&w).into_iter().flatten() {
+                if !previous.contains_key(x) {
+                    previous.insert(*x, w);
+                    queue.push_back(*x);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Number of edges
+    fn len(&self) -> usize {
+        self.neighbors.values().map(Vec::len).sum::<usize>() / 2
+    }
+}
+
+/// Tests whether an insertion only stream of edges over the vertices `[n]` is a forest, with a cycle as certificate otherwise
+///
+/// Storage: a union find and the spanning forest, O(n log n) bits
+///
+/// Runtime: O(log n) amortized per edge, and O(n) once for the first cycle
+#[derive(Clone, Debug)]
+pub struct ForestTester {
+    n: VertexId,
+    components: Components,
+    forest: Adjacency,
+    /// The cycle closed by the first edge outside of the forest
+    cycle: Option<Vec<VertexId>>,
+}
+
+impl ForestTester {
+    pub fn init(n: VertexId) -> Self {
+        Self {
+            n,
+            components: Components::init(n),
+            forest: Adjacency::default(),
+            cycle: None,
+        }
+    }
+
+    /// Feed an edge insertion, self loops are ignored
+    ///
+    /// Panics if an endpoint is not in `[n]`.
+    pub fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        let (u, v) = edge.vertices();
+        if *u >= self.n || *v >= self.n {
+            panic!(
+                "[ForestTester] ({}, {}) is not an edge of [{}]",
+                u, v, self.n
+            );
+        }
+        if u == v || self.cycle.is_some() {
+            return;
+        }
+
+        if self.components.union(*u, *v) {
+            self.forest.add(*u, *v);
+        } else {
+            self.cycle = self.forest.path(*u, *v);
+        }
+    }
+
+    /// Whether the stream so far is a forest
+    pub fn is_forest(&self) -> bool {
+        self.cycle.is_none()
+    }
+
+    /// [Forest](Acyclicity::Forest), or the cycle closed by the first edge outside of the forest
+    pub fn query(self) -> Acyclicity {
+        match self.cycle {
+            Some(cycle) => Acyclicity::Cycle(cycle),
+            None => Acyclicity::Forest,
+        }
+    }
+}
+
+impl StreamProcessor<Edge<VertexId, ()>, Acyclicity> for ForestTester {
+    fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        ForestTester::feed(self, edge)
+    }
+
+    fn query(self) -> Acyclicity {
+        ForestTester::query(self)
+    }
+}
+
+impl SpaceUsage for ForestTester {
+    /// A parent per vertex, and both directions of every edge of the forest
+    fn space(&self) -> SpaceReport {
+        let n = self.n as f64;
+        let edges = self.forest.len();
+
+        SpaceReport {
+            structure: "ForestTester",
+            bound: "O(n log n)",
+            parameters: vec![("n", n), ("forest edges", edges as f64)],
+            theoretical_bits: (n + 2.0 * edges as f64) * log(n),
+            measured_bytes: size_of::<Self>()
+                + self.n as usize * size_of::<VertexId>()
+                + self.forest.neighbors.capacity() * size_of::<(VertexId, Vec<VertexId>)>()
+                + 2 * edges * size_of::<VertexId>()
+                + self
+                    .cycle
+                    .as_ref()
+                    .map_or(0, |c| c.capacity() * size_of::<VertexId>()),
+            components: vec![],
+        }
+    }
+}
+
+impl Provenance for ForestTester {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "ForestTester",
+            paper_ref: "Tarjan, JACM 1975, https://doi.org/10.1145/321879.321884",
+            params: vec![("n", self.n as f64)],
+            seed: None,
+        }
+    }
+}
+
+/// Tests whether a turnstile graph stream over the vertices `[n]` is a forest
+///
+/// The edges are counted with multiplicity, so that the stream must not delete an edge it did not insert.
+///
+/// Storage: a spanning forest sketch, O(n log^3 n) bits, cloned once at query time
+#[derive(Clone, Debug)]
+pub struct StreamForest<H> {
+    n: VertexId,
+    sketch: ForestSketch<H>,
+    /// Number of edges, self loops excluded
+    edges: i64,
+}
+
+impl<H> StreamForest<H>
+where
+    H: HashFunction,
+{
+    /// Initialize a new tester over the vertices `[n]`
+    pub fn init(n: VertexId) -> Self {
+        Self {
+            n,
+            sketch: ForestSketch::init(n),
+            edges: 0,
+        }
+    }
+
+    /// Feed an edge insertion or deletion, self loops are ignored
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
+        let (u, v) = edge.vertices();
+        if u == v {
+            return;
+        }
+
+        self.sketch.feed(token);
+        self.edges += if c { 1 } else { -1 };
+    }
+
+    /// Number of edges of the streamed graph, self loops excluded
+    pub fn edge_count(&self) -> i64 {
+        self.edges
+    }
+
+    /// [Forest](Acyclicity::Forest) with high probability if the streamed graph is one, and otherwise a cycle, or
+    /// [Cyclic](Acyclicity::Cyclic) when the sampler of an edge outside of the forest failed
+    ///
+    /// A forest is never reported for a graph with a cycle, since the recovered forest is always a forest of the graph.
+    pub fn query(self) -> Acyclicity
+    where
+        H: Clone,
+    {
+        let Self { sketch, edges, .. } = self;
+
+        let forest = sketch.clone().query();
+        if edges <= forest.len() as i64 {
+            return Acyclicity::Forest;
+        }
+
+        let mut remainder = sketch;
+        let mut adjacency = Adjacency::default();
+        for edge in forest.iter() {
+            remainder.feed((*edge, false));
+            let (u, v) = edge.vertices();
+            adjacency.add(*u, *v);
+        }
+
+        remainder
+            .query()
+            .iter()
+            .find_map(|edge| {
+                let (u, v) = edge.vertices();
+                adjacency.path(*u, *v)
+            })
+            .map_or(Acyclicity::Cyclic, Acyclicity::Cycle)
+    }
+}
+
+impl<H> StreamProcessor<(Edge<VertexId, ()>, bool), Acyclicity> for StreamForest<H>
+where
+    H: HashFunction + Clone,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        StreamForest::feed(self, token)
+    }
+
+    fn query(self) -> Acyclicity {
+        StreamForest::query(self)
+    }
+}
+
+impl<H> SpaceUsage for StreamForest<H>
+where
+    H: HashFunction,
+{
+    fn space(&self) -> SpaceReport {
+        let sketch = self.sketch.space();
+
+        SpaceReport {
+            structure: "StreamForest",
+            bound: sketch.bound,
+            parameters: vec![("n", self.n as f64)],
+            theoretical_bits: sketch.theoretical_bits + log(self.n as f64),
+            measured_bytes: size_of::<Self>() + sketch.measured_bytes
+                - size_of::<ForestSketch<H>>(),
+            components: vec![sketch],
+        }
+    }
+}
+
+impl<H> Provenance for StreamForest<H>
+where
+    H: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "StreamForest",
+            paper_ref: "Ahn, Guha, McGregor, SODA 2012, https://doi.org/10.1137/1.9781611973099.40",
+            params: vec![("n", self.n as f64)],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::hash_function::PowerFiniteFieldHasher;
+
+    /// Whether consecutive vertices of the cycle, the last and the first included, are joined by an edge
+    fn is_cycle(cycle: &[VertexId], edges: &[(VertexId, VertexId)]) -> bool {
+        cycle.len() >= 2
+            && (0..cycle.len()).all(|i| {
+                let (u, v) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+                edges.contains(&(u, v)) || edges.contains(&(v, u))
+            })
+    }
+
+    /// A path on `[0, 6)`, a star around 6 on `[6, 10)`, and 10 isolated
+    fn forest() -> Vec<(VertexId, VertexId)> {
+        (0..5)
+            .map(|v| (v, v + 1))
+            .chain((7..10).map(|v| (6, v)))
+            .collect()
+    }
+
+    #[test]
+    fn insertion_only() {
+        let mut tester = ForestTester::init(11);
+        forest()
+            .into_iter()
+            .for_each(|(u, v)| tester.feed(Edge::init(u, v)));
+        tester.feed(Edge::init(4, 4));
+        assert!(tester.is_forest());
+        assert_eq!(tester.clone().query(), Acyclicity::Forest);
+
+        let mut edges = forest();
+        edges.extend([(1, 4), (8, 9)]);
+        tester.feed(Edge::init(1, 4));
+        tester.feed(Edge::init(8, 9));
+
+        let cycle = tester.query();
+        assert_eq!(cycle.cycle(), Some(&[1, 2, 3, 4][..]));
+        assert!(is_cycle(cycle.cycle().unwrap(), &edges));
+    }
+
+    #[test]
+    #[should_panic]
+    fn outside_of_the_vertices() {
+        ForestTester::init(5).feed(Edge::init(2, 5));
+    }
+
+    #[test]
+    fn turnstile() {
+        let mut tester: StreamForest<PowerFiniteFieldHasher> = StreamForest::init(11);
+        forest()
+            .into_iter()
+            .for_each(|(u, v)| tester.feed((Edge::init(u, v), true)));
+        assert_eq!(tester.clone().query(), Acyclicity::Forest);
+
+        // Closing a cycle through the star, then opening it elsewhere
+        let mut edges = forest();
+        edges.push((7, 9));
+        tester.feed((Edge::init(7, 9), true));
+        let cycle = tester.clone().query();
+        assert!(is_cycle(cycle.cycle().unwrap(), &edges));
+        assert_eq!(cycle.cycle().unwrap().len(), 3);
+
+        tester.feed((Edge::init(6, 7), false));
+        assert_eq!(tester.edge_count(), 8);
+        assert!(tester.clone().query().is_forest());
+
+        // A parallel edge is a cycle of length 2
+        tester.feed((Edge::init(0, 1), true));
+        let cycle = tester.query();
+        if let Some(cycle) = cycle.cycle() {
+            assert_eq!(cycle.len(), 2);
+        }
+        assert!(!cycle.is_forest());
+    }
+}
@@ -0,0 +1,1296 @@
+//! Frequency Estimation
+//!
+//! A [CountMin] sketch of [Cormode and Muthukrishnan](https://doi.org/10.1016/j.jalgor.2003.12.001) estimates the frequency
+//! `f_j` of every `j \in [n]` in a turnstile stream. Each of `d = ⌈ln(1/δ)⌉` rows hashes `[n]` into `w = ⌈e/ε⌉` counters,
+//! and the estimate of `f_j` is the smallest of its `d` counters. As long as no frequency goes negative (strict turnstile),
+//!
+//! ```text
+//! f_j <= estimate(j) <= f_j + ε ||f||_1    with probability 1 - δ
+//! ```
+//!
+//! The heavy hitters, the `j` with `f_j >= φ ||f||_1`, are found without scanning `[n]` by keeping a sketch for every level of
+//! the dyadic ranges of `[n]`, and only descending into the ranges heavy enough to contain a heavy hitter.
+//!
+//! A [CountSketch] of [Charikar, Chen and Farach-Colton](https://doi.org/10.1016/S0304-3975(03)00400-6) adds a random sign to
+//! every counter update, so that the collisions cancel out in expectation. Its estimates err in both directions, but within
+//! `ε ||f||_2`, which is much smaller than `ε ||f||_1` on skewed streams, and they remain correct when frequencies go negative.
+//!
+//! [EdgeFrequencies] applies the Count-Min sketch to a stream of edges, with one sketch of the multiplicities of the edges, and one of
+//! the degrees, and [DegreeSketch] only keeps the latter, to estimate the maximum degree and the quantiles of the degrees.
+//!
+//! On insertion only streams, the deterministic [MisraGries] summary finds the heavy hitters in `O(1/ε)` counters, without any
+//! hashing, and [HeavyVertices] applies it to the endpoints of a stream of edges to find the vertices of high degree.
+
+use std::{collections::HashMap, fmt::Debug, mem::size_of};
+
+use num_integer::binomial;
+
+use crate::{
+    graph::{
+        streaming::{
+            provenance::{AlgorithmInfo, Provenance},
+            quantiles::GreenwaldKhanna,
+            space::{log, SpaceReport, SpaceUsage},
+            Merge, Sketch, StreamProcessor,
+        },
+        Edge, VertexId,
+    },
+    utils::hash_function::{HashFunction, PowerFiniteFieldHasher},
+};
+
+/// Parameters of a [CountMin] sketch
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CountMinParameters {
+    /// Additive error of the estimates, as a fraction of `||f||_1`
+    pub epsilon: f64,
+    /// Probability with which an estimate exceeds its error
+    pub delta: f64,
+    /// Threshold of the heavy hitters returned by the query, as a fraction of `||f||_1`
+    pub phi: f64,
+}
+
+impl Default for CountMinParameters {
+    fn default() -> Self {
+        Self {
+            epsilon: 0.01,
+            delta: 0.01,
+            phi: 0.05,
+        }
+    }
+}
+
+/// A row of counters, indexed by a hash function
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+struct Row<F> {
+    function: F,
+    counters: Vec<i64>,
+}
+
+/// Estimates the frequencies of a turnstile stream over `[n]`
+///
+/// Storage: O(log n · log(1/δ) / ε) counters, one sketch per level of the dyadic ranges
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct CountMin<F: HashFunction = PowerFiniteFieldHasher> {
+    n: u64,
+    parameters: CountMinParameters,
+    /// Rows of every level, level `l` counting the ranges `[2^l i, 2^l (i + 1))`
+    levels: Vec<Vec<Row<F>>>,
+    /// `||f||_1`, exact as long as no frequency goes negative
+    total: i64,
+    /// Tag of the random components, shared by clones
+    seed: u64,
+}
+
+impl<F> CountMin<F>
+where
+    F: HashFunction,
+{
+    /// Initialize a sketch of the frequencies of `[n]`
+    pub fn init(n: u64, parameters: CountMinParameters) -> Self {
+        if parameters.epsilon <= 0.0 || parameters.delta <= 0.0 || parameters.delta >= 1.0 {
+            panic!(
+                "[CountMin] ε must be positive and δ within (0, 1): {:?}",
+                parameters
+            );
+        }
+
+        let n_pow = n.max(2).next_power_of_two();
+        let width = (std::f64::consts::E / parameters.epsilon).ceil().max(2.0) as u64;
+        let width = width.next_power_of_two();
+        let depth = (1.0 / parameters.delta).ln().ceil().max(1.0) as usize;
+
+        let base = F::init(n_pow, width);
+        let levels = (0..=n_pow.trailing_zeros())
+            .map(|_| {
+                (0..depth)
+                    .map(|_| Row {
+                        function: base.random_copy(),
+                        counters: vec![0; width as usize],
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            n,
+            parameters,
+            levels,
+            total: 0,
+            seed: rand::random(),
+        }
+    }
+
+    pub fn parameters(&self) -> CountMinParameters {
+        self.parameters
+    }
+
+    /// Feed a token `(j, c)`, an insertion of `j` if `c` is true, a deletion otherwise
+    pub fn feed(&mut self, (j, c): (u64, bool)) {
+        self.update(j, if c { 1 } else { -1 });
+    }
+
+    /// Add `delta` to the frequency of `j`
+    pub fn update(&mut self, j: u64, delta: i64) {
+        if j >= self.n {
+            panic!("[CountMin] {} is outside of the universe [{}]", j, self.n);
+        }
+
+        for (level, rows) in self.levels.iter_mut().enumerate() {
+            let range = j >> level;
+            for row in rows.iter_mut() {
+                row.counters[row.function.compute(range) as usize] += delta;
+            }
+        }
+        self.total += delta;
+    }
+
+    fn estimate_level(&self, level: usize, range: u64) -> i64 {
+        self.levels[level]
+            .iter()
+            .map(|row| row.counters[row.function.compute(range) as usize])
+            .min()
+            .unwrap()
+    }
+
+    /// Point query: an estimate of `f_j`, from above, within `ε ||f||_1`
+    pub fn estimate(&self, j: u64) -> i64 {
+        self.estimate_level(0, j)
+    }
+
+    /// `||f||_1`, the sum of the frequencies
+    pub fn total(&self) -> i64 {
+        self.total
+    }
+
+    /// Every `j` with `f_j >= φ ||f||_1`, with its estimate, in increasing order of `j`
+    ///
+    /// Elements of frequency down to `(φ - ε) ||f||_1` may be returned as well.
+    ///
+    /// Runtime: O(log n · log(1/δ) / φ) point queries, as at most `1/φ` ranges of a level are heavy
+    pub fn heavy_hitters(&self, phi: f64) -> Vec<(u64, i64)> {
+        let threshold = (phi * self.total as f64).ceil().max(1.0) as i64;
+
+        let mut ranges = vec![0];
+        for level in (0..self.levels.len()).rev() {
+            ranges = ranges
+                .into_iter()
+                .flat_map(|range: u64| {
+                    if level + 1 == self.levels.len() {
+                        vec![range]
+                    } else {
+                        vec![2 * range, 2 * range + 1]
+                    }
+                })
+                .filter(|range| (range << level) < self.n)
+                .filter(|range| self.estimate_level(level, *range) >= threshold)
+                .collect();
+        }
+
+        ranges.into_iter().map(|j| (j, self.estimate(j))).collect()
+    }
+
+    /// The heavy hitters, for the threshold `φ` of the parameters
+    pub fn query(self) -> Vec<(u64, i64)> {
+        self.heavy_hitters(self.parameters.phi)
+    }
+}
+
+impl<F> Sketch for CountMin<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        CountMin::feed(self, token)
+    }
+}
+
+impl<F> StreamProcessor<(u64, bool), Vec<(u64, i64)>> for CountMin<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        CountMin::feed(self, token)
+    }
+
+    fn query(self) -> Vec<(u64, i64)> {
+        CountMin::query(self)
+    }
+}
+
+impl<F> Merge for CountMin<F>
+where
+    F: HashFunction,
+{
+    /// The counters are linear in the stream, so they add up
+    fn merge(&mut self, other: &Self) {
+        if self.seed != other.seed {
+            panic!("[CountMin] Cannot merge sketches with different hash functions");
+        }
+
+        for (rows, other_rows) in self.levels.iter_mut().zip(other.levels.iter()) {
+            for (row, other_row) in rows.iter_mut().zip(other_rows.iter()) {
+                row.counters
+                    .iter_mut()
+                    .zip(other_row.counters.iter())
+                    .for_each(|(counter, other)| *counter += other);
+            }
+        }
+        self.total += other.total;
+    }
+}
+
+impl<F> SpaceUsage for CountMin<F>
+where
+    F: HashFunction,
+{
+    /// `log n` levels of `d` rows of `w` counters
+    fn space(&self) -> SpaceReport {
+        let levels = self.levels.len();
+        let depth = self.levels[0].len();
+        let width = self.levels[0][0].counters.len();
+
+        SpaceReport {
+            structure: "CountMin",
+            bound: "O(log n · log(1/δ)/ε · log m)",
+            parameters: vec![
+                ("n", self.n as f64),
+                ("d", depth as f64),
+                ("w", width as f64),
+            ],
+            theoretical_bits: (levels * depth * width) as f64 * log(self.n as f64),
+            measured_bytes: size_of::<Self>()
+                + levels * size_of::<Vec<Row<F>>>()
+                + levels * depth * (size_of::<Row<F>>() + width * size_of::<i64>()),
+            components: vec![],
+        }
+    }
+}
+
+impl<F> Provenance for CountMin<F>
+where
+    F: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "CountMin",
+            paper_ref: "Cormode, Muthukrishnan, J. Algorithms 2005, https://doi.org/10.1016/j.jalgor.2003.12.001",
+            params: vec![
+                ("n", self.n as f64),
+                ("ε", self.parameters.epsilon),
+                ("δ", self.parameters.delta),
+                ("φ", self.parameters.phi),
+            ],
+            seed: Some(self.seed),
+        }
+    }
+}
+
+/// Median of the values, the upper one for an even number of values
+fn median(mut values: Vec<i64>) -> i64 {
+    values.sort_unstable();
+    values[values.len() / 2]
+}
+
+/// A row of counters, indexed by a hash function, each update signed by another
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+struct SignedRow<F> {
+    bucket: F,
+    sign: F,
+    counters: Vec<i64>,
+}
+
+impl<F> SignedRow<F>
+where
+    F: HashFunction,
+{
+    fn sign(&self, j: u64) -> i64 {
+        if self.sign.is_zero(j) {
+            1
+        } else {
+            -1
+        }
+    }
+
+    fn estimate(&self, j: u64) -> i64 {
+        self.sign(j) * self.counters[self.bucket.compute(j) as usize]
+    }
+}
+
+/// Estimates the frequencies of a general turnstile stream over `[n]`, within `ε ||f||_2`
+///
+/// The `d = O(log(1/δ))` rows have `w = ⌈3/ε²⌉` counters each, and the estimate of `f_j` is the median over the rows.
+///
+/// Storage: O(log(1/δ) / ε²) counters
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct CountSketch<F: HashFunction = PowerFiniteFieldHasher> {
+    n: u64,
+    epsilon: f64,
+    delta: f64,
+    rows: Vec<SignedRow<F>>,
+    /// Tag of the random components, shared by clones
+    seed: u64,
+}
+
+impl<F> CountSketch<F>
+where
+    F: HashFunction,
+{
+    /// Initialize a sketch of the frequencies of `[n]`
+    ///
+    /// - *epsilon* : Additive error of the estimates, as a fraction of `||f||_2`
+    /// - *delta* : Probability with which an estimate exceeds its error
+    pub fn init(n: u64, epsilon: f64, delta: f64) -> Self {
+        if epsilon <= 0.0 || delta <= 0.0 || delta >= 1.0 {
+            panic!(
+                "[CountSketch] ε must be positive and δ within (0, 1): {}, {}",
+                epsilon, delta
+            );
+        }
+
+        let n_pow = n.max(2).next_power_of_two();
+        let width = (3.0 / epsilon.powi(2)).ceil().max(2.0) as u64;
+        let width = width.next_power_of_two();
+        // An odd number of rows, so that the median is one of them
+        let depth = (4.0 * (1.0 / delta).ln()).ceil() as usize | 1;
+
+        let bucket = F::init(n_pow, width);
+        let sign = F::init(n_pow, 2);
+        let rows = (0..depth)
+            .map(|_| SignedRow {
+                bucket: bucket.random_copy(),
+                sign: sign.random_copy(),
+                counters: vec![0; width as usize],
+            })
+            .collect();
+
+        Self {
+            n,
+            epsilon,
+            delta,
+            rows,
+            seed: rand::random(),
+        }
+    }
+
+    /// Feed a token `(j, c)`, an insertion of `j` if `c` is true, a deletion otherwise
+    pub fn feed(&mut self, (j, c): (u64, bool)) {
+        self.update(j, if c { 1 } else { -1 });
+    }
+
+    /// Add `delta` to the frequency of `j`
+    pub fn update(&mut self, j: u64, delta: i64) {
+        if j >= self.n {
+            panic!(
+                "[CountSketch] {} is outside of the universe [{}]",
+                j, self.n
+            );
+        }
+
+        for row in self.rows.iter_mut() {
+            let sign = row.sign(j);
+            row.counters[row.bucket.compute(j) as usize] += sign * delta;
+        }
+    }
+
+    /// Point query: an estimate of `f_j`, within `ε ||f||_2`
+    pub fn estimate(&self, j: u64) -> i64 {
+        median(self.rows.iter().map(|row| row.estimate(j)).collect())
+    }
+
+    /// Estimate of `F_2 = ||f||_2^2`, the median over the rows of the sum of their squared counters
+    pub fn f2(&self) -> f64 {
+        median(
+            self.rows
+                .iter()
+                .map(|row| row.counters.iter().map(|c| c * c).sum())
+                .collect(),
+        ) as f64
+    }
+
+    /// The estimate of `F_2`, every row being a tug-of-war estimator over its buckets
+    pub fn query(self) -> f64 {
+        self.f2()
+    }
+}
+
+impl<F> Sketch for CountSketch<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        CountSketch::feed(self, token)
+    }
+}
+
+impl<F> StreamProcessor<(u64, bool), f64> for CountSketch<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        CountSketch::feed(self, token)
+    }
+
+    fn query(self) -> f64 {
+        CountSketch::query(self)
+    }
+}
+
+impl<F> Merge for CountSketch<F>
+where
+    F: HashFunction,
+{
+    /// The counters are linear in the stream, so they add up
+    fn merge(&mut self, other: &Self) {
+        if self.seed != other.seed {
+            panic!("[CountSketch] Cannot merge sketches with different hash functions");
+        }
+
+        for (row, other_row) in self.rows.iter_mut().zip(other.rows.iter()) {
+            row.counters
+                .iter_mut()
+                .zip(other_row.counters.iter())
+                .for_each(|(counter, other)| *counter += other);
+        }
+    }
+}
+
+impl<F> SpaceUsage for CountSketch<F>
+where
+    F: HashFunction,
+{
+    /// `d` rows of `w` counters
+    fn space(&self) -> SpaceReport {
+        let depth = self.rows.len();
+        let width = self.rows[0].counters.len();
+
+        SpaceReport {
+            structure: "CountSketch",
+            bound: "O(log(1/δ)/ε² · log m)",
+            parameters: vec![
+                ("n", self.n as f64),
+                ("d", depth as f64),
+                ("w", width as f64),
+            ],
+            theoretical_bits: (depth * width) as f64 * log(self.n as f64),
+            measured_bytes: size_of::<Self>()
+                + depth * (size_of::<SignedRow<F>>() + width * size_of::<i64>()),
+            components: vec![],
+        }
+    }
+}
+
+impl<F> Provenance for CountSketch<F>
+where
+    F: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "CountSketch",
+            paper_ref: "Charikar, Chen, Farach-Colton, TCS 2004, https://doi.org/10.1016/S0304-3975(03)00400-6",
+            params: vec![
+                ("n", self.n as f64),
+                ("ε", self.epsilon),
+                ("δ", self.delta),
+            ],
+            seed: Some(self.seed),
+        }
+    }
+}
+
+/// Heavy edges and vertices of a stream of edges
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeavyHitters {
+    /// Edges of multiplicity at least `φ m`, with their estimated multiplicity
+    pub edges: Vec<(Edge<VertexId, ()>, i64)>,
+    /// Vertices of degree at least `φ 2m`, with their estimated degree
+    pub vertices: Vec<(VertexId, i64)>,
+}
+
+/// Estimates the multiplicities of the edges, and the degrees of the vertices, of a turnstile stream of edges over `[n]`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct EdgeFrequencies<F: HashFunction = PowerFiniteFieldHasher> {
+    /// Frequencies of the edges, over `[n choose 2]`
+    edges: CountMin<F>,
+    /// Frequencies of the endpoints, over `[n]`
+    degrees: CountMin<F>,
+}
+
+impl<F> EdgeFrequencies<F>
+where
+    F: HashFunction,
+{
+    pub fn init(n: VertexId, parameters: CountMinParameters) -> Self {
+        Self {
+            edges: CountMin::init(binomial(n, 2), parameters),
+            degrees: CountMin::init(n, parameters),
+        }
+    }
+
+    pub fn feed<W: Debug + Default>(&mut self, edge: Edge<VertexId, W>, c: bool) {
+        let delta = if c { 1 } else { -1 };
+        let (u, v) = edge.vertices();
+
+        self.edges.update(edge.to_d1(), delta);
+        self.degrees.update(*u, delta);
+        self.degrees.update(*v, delta);
+    }
+
+    /// Estimated number of copies of the edge `(u, v)` in the graph
+    pub fn multiplicity(&self, u: VertexId, v: VertexId) -> i64 {
+        self.edges
+            .estimate(Edge::<VertexId, ()>::init(u, v).to_d1())
+    }
+
+    /// Estimated degree of `v`, copies of an edge counted with multiplicity
+    pub fn degree(&self, v: VertexId) -> i64 {
+        self.degrees.estimate(v)
+    }
+
+    /// Number of edges in the graph, with multiplicity
+    pub fn edge_count(&self) -> i64 {
+        self.edges.total()
+    }
+
+    pub fn heavy_hitters(&self, phi: f64) -> HeavyHitters {
+        HeavyHitters {
+            edges: self
+                .edges
+                .heavy_hitters(phi)
+                .into_iter()
+                .map(|(j, count)| (Edge::from_d1(j), count))
+                .collect(),
+            vertices: self.degrees.heavy_hitters(phi),
+        }
+    }
+
+    /// The heavy hitters, for the threshold `φ` of the parameters
+    pub fn query(self) -> HeavyHitters {
+        self.heavy_hitters(self.edges.parameters.phi)
+    }
+}
+
+impl<F> StreamProcessor<(Edge<VertexId, ()>, bool), HeavyHitters> for EdgeFrequencies<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, (edge, c): (Edge<VertexId, ()>, bool)) {
+        EdgeFrequencies::feed(self, edge, c)
+    }
+
+    fn query(self) -> HeavyHitters {
+        EdgeFrequencies::query(self)
+    }
+}
+
+impl<F> Merge for EdgeFrequencies<F>
+where
+    F: HashFunction,
+{
+    fn merge(&mut self, other: &Self) {
+        self.edges.merge(&other.edges);
+        self.degrees.merge(&other.degrees);
+    }
+}
+
+impl<F> SpaceUsage for EdgeFrequencies<F>
+where
+    F: HashFunction,
+{
+    /// A sketch over the edges, and one over the vertices
+    fn space(&self) -> SpaceReport {
+        let edges = self.edges.space();
+        let degrees = self.degrees.space();
+
+        SpaceReport {
+            structure: "EdgeFrequencies",
+            bound: "O(log n · log(1/δ)/ε · log m)",
+            parameters: vec![("n", self.degrees.n as f64)],
+            theoretical_bits: edges.theoretical_bits + degrees.theoretical_bits,
+            measured_bytes: size_of::<Self>() + edges.measured_bytes + degrees.measured_bytes
+                - 2 * size_of::<CountMin<F>>(),
+            components: vec![edges, degrees],
+        }
+    }
+}
+
+impl<F> Provenance for EdgeFrequencies<F>
+where
+    F: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "EdgeFrequencies (CountMin)",
+            ..self.edges.info()
+        }
+    }
+}
+
+/// Estimates the degree of every vertex of a turnstile stream of edges over `[n]`, and the maximum degree
+///
+/// A [CountMin] sketch of the endpoints, whose frequencies are the degrees. Every estimate is at least the degree, and at most
+/// `ε 2m` above it with probability `1 - δ`. Colorers that must be given `Δ` before their stream can take
+/// [max_degree_estimate](DegreeSketch::max_degree_estimate) from a first pass over it.
+///
+/// Storage: O(log n · log(1/δ) / ε) counters
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct DegreeSketch<F: HashFunction = PowerFiniteFieldHasher> {
+    degrees: CountMin<F>,
+}
+
+impl<F> DegreeSketch<F>
+where
+    F: HashFunction,
+{
+    pub fn init(n: VertexId, parameters: CountMinParameters) -> Self {
+        Self {
+            degrees: CountMin::init(n, parameters),
+        }
+    }
+
+    /// Feed an edge insertion or deletion, self loops are ignored
+    pub fn feed<W: Debug + Default>(&mut self, edge: Edge<VertexId, W>, c: bool) {
+        let delta = if c { 1 } else { -1 };
+        let (u, v) = edge.vertices();
+        if u == v {
+            return;
+        }
+
+        self.degrees.update(*u, delta);
+        self.degrees.update(*v, delta);
+    }
+
+    /// Estimated degree of `v`, never below it
+    pub fn degree(&self, v: VertexId) -> i64 {
+        self.degrees.estimate(v)
+    }
+
+    /// Number of edges in the graph, with multiplicity
+    pub fn edge_count(&self) -> i64 {
+        self.degrees.total() / 2
+    }
+
+    /// Largest estimated degree, at least `Δ`, and at most `n - 1` as in a simple graph
+    ///
+    /// Runtime: O(n log(1/δ)), every vertex is estimated
+    pub fn max_degree_estimate(&self) -> u64 {
+        let n = self.degrees.n;
+        let max = (0..n).map(|v| self.degree(v)).max().unwrap_or_default();
+
+        (max.max(0) as u64).min(n.saturating_sub(1))
+    }
+
+    /// Vertices of degree at least `φ 2m`, with their estimated degree
+    pub fn heavy_hitters(&self, phi: f64) -> Vec<(VertexId, i64)> {
+        self.degrees.heavy_hitters(phi)
+    }
+
+    /// A [GreenwaldKhanna] summary of the estimated degrees of the vertices, whose quantiles are the percentiles of the degree
+    /// distribution, within `εn` in rank, and `ε 2m` in degree as the estimates of the sketch
+    ///
+    /// Runtime: O(n log(1/δ)), every vertex is estimated
+    pub fn degree_quantiles(&self, epsilon: f64) -> GreenwaldKhanna {
+        let mut summary = GreenwaldKhanna::init(epsilon);
+        (0..self.degrees.n).for_each(|v| summary.feed(self.degree(v).max(0) as u64));
+        summary
+    }
+
+    /// The estimate of the maximum degree
+    pub fn query(self) -> u64 {
+        self.max_degree_estimate()
+    }
+}
+
+impl<F> StreamProcessor<(Edge<VertexId, ()>, bool), u64> for DegreeSketch<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, (edge, c): (Edge<VertexId, ()>, bool)) {
+        DegreeSketch::feed(self, edge, c)
+    }
+
+    fn query(self) -> u64 {
+        DegreeSketch::query(self)
+    }
+}
+
+impl<F> Merge for DegreeSketch<F>
+where
+    F: HashFunction,
+{
+    fn merge(&mut self, other: &Self) {
+        self.degrees.merge(&other.degrees);
+    }
+}
+
+impl<F> SpaceUsage for DegreeSketch<F>
+where
+    F: HashFunction,
+{
+    /// The sketch over the vertices
+    fn space(&self) -> SpaceReport {
+        let degrees = self.degrees.space();
+
+        SpaceReport {
+            structure: "DegreeSketch",
+            bound: degrees.bound,
+            parameters: vec![("n", self.degrees.n as f64)],
+            theoretical_bits: degrees.theoretical_bits,
+            measured_bytes: size_of::<Self>() + degrees.measured_bytes - size_of::<CountMin<F>>(),
+            components: vec![degrees],
+        }
+    }
+}
+
+impl<F> Provenance for DegreeSketch<F>
+where
+    F: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "DegreeSketch (CountMin)",
+            ..self.degrees.info()
+        }
+    }
+}
+
+/// Finds the frequent items of an insertion only stream over `[n]`, deterministically
+///
+/// The summary of [Misra and Gries](https://doi.org/10.1016/0167-6423(82)90012-0) keeps at most `k` counters. An item with a
+/// counter increments it, an item without one takes a free counter, and when none is free every counter is decremented instead.
+/// Each decrement cancels `k + 1` tokens, so for a stream of length `N`,
+///
+/// ```text
+/// f_j - N / (k + 1) <= estimate(j) <= f_j
+/// ```
+///
+/// Storage: O(k) counters, independent of `n`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MisraGries {
+    k: usize,
+    counters: HashMap<u64, u64>,
+    /// Length of the stream
+    total: u64,
+}
+
+impl MisraGries {
+    /// Initialize a summary of `k` counters, underestimating every frequency by at most `N / (k + 1)`
+    pub fn init(k: usize) -> Self {
+        if k == 0 {
+            panic!("[MisraGries] At least one counter is needed");
+        }
+
+        Self {
+            k,
+            counters: HashMap::with_capacity(k + 1),
+            total: 0,
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Feed the next item of the stream
+    pub fn feed(&mut self, j: u64) {
+        self.total += 1;
+
+        if let Some(count) = self.counters.get_mut(&j) {
+            *count += 1;
+        } else if self.counters.len() < self.k {
+            self.counters.insert(j, 1);
+        } else {
+            self.counters.retain(|_, count| {
+                *count -= 1;
+                *count > 0
+            });
+        }
+    }
+
+    /// Lower bound on the frequency of `j`, within `N / (k + 1)` of it
+    pub fn estimate(&self, j: u64) -> u64 {
+        self.counters.get(&j).copied().unwrap_or_default()
+    }
+
+    /// Length of the stream
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Largest underestimate of a frequency, `N / (k + 1)`
+    pub fn error(&self) -> u64 {
+        self.total / (self.k as u64 + 1)
+    }
+
+    /// Items of estimated frequency at least `threshold`, by decreasing estimate
+    pub fn above(&self, threshold: u64) -> Vec<(u64, u64)> {
+        let mut items: Vec<(u64, u64)> = self
+            .counters
+            .iter()
+            .filter(|(_, count)| **count >= threshold.max(1))
+            .map(|(j, count)| (*j, *count))
+            .collect();
+        items.sort_unstable_by(|(i, a), (j, b)| b.cmp(a).then(i.cmp(j)));
+        items
+    }
+
+    /// Every item with a counter, by decreasing estimate, a superset of the items of frequency above `N / (k + 1)`
+    pub fn query(self) -> Vec<(u64, u64)> {
+        self.above(1)
+    }
+}
+
+impl StreamProcessor<u64, Vec<(u64, u64)>> for MisraGries {
+    fn feed(&mut self, j: u64) {
+        MisraGries::feed(self, j)
+    }
+
+    fn query(self) -> Vec<(u64, u64)> {
+        MisraGries::query(self)
+    }
+}
+
+impl Merge for MisraGries {
+    /// The counters add up, then the `(k + 1)`th largest is subtracted from all of them, as in the mergeable summaries of
+    /// [Agarwal et al.](https://doi.org/10.1145/2500128), which keeps the error within `N / (k + 1)` of the combined stream
+    fn merge(&mut self, other: &Self) {
+        if self.k != other.k {
+            panic!(
+                "[MisraGries] Cannot merge summaries of {} and {} counters",
+                self.k, other.k
+            );
+        }
+
+        for (j, count) in other.counters.iter() {
+            *self.counters.entry(*j).or_default() += count;
+        }
+        self.total += other.total;
+
+        if self.counters.len() > self.k {
+            let mut counts: Vec<u64> = self.counters.values().copied().collect();
+            counts.sort_unstable_by(|a, b| b.cmp(a));
+            let cut = counts[self.k];
+
+            self.counters.retain(|_, count| {
+                *count = count.saturating_sub(cut);
+                *count > 0
+            });
+        }
+    }
+}
+
+impl SpaceUsage for MisraGries {
+    /// `k` items, each with a counter
+    fn space(&self) -> SpaceReport {
+        let k = self.k as f64;
+
+        SpaceReport {
+            structure: "MisraGries",
+            bound: "O(k (log n + log N))",
+            parameters: vec![("k", k), ("N", self.total as f64)],
+            theoretical_bits: k * (64.0 + log(self.total as f64)),
+            measured_bytes: size_of::<Self>() + self.counters.capacity() * 2 * size_of::<u64>(),
+            components: vec![],
+        }
+    }
+}
+
+impl Provenance for MisraGries {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "MisraGries",
+            paper_ref: "Misra, Gries, Science of Computer Programming 1982, https://doi.org/10.1016/0167-6423(82)90012-0",
+            params: vec![("k", self.k as f64)],
+            seed: None,
+        }
+    }
+}
+
+/// Parameters of [HeavyVertices]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeavyVertexParameters {
+    /// Threshold of the reported degrees, as a fraction of the number of edges `m`
+    pub phi: f64,
+    /// Slack of the threshold, vertices of degree below `(φ - ε) m` are never reported
+    pub epsilon: f64,
+}
+
+impl Default for HeavyVertexParameters {
+    fn default() -> Self {
+        Self {
+            phi: 0.05,
+            epsilon: 0.01,
+        }
+    }
+}
+
+/// Reports the vertices of high degree of an insertion only stream of edges, deterministically
+///
+/// Every vertex of degree at least `φ m` is reported, and none of degree below `(φ - ε) m`. A [MisraGries] summary of the endpoints,
+/// `2m` tokens over `⌈2/ε⌉` counters, underestimates the degrees by at most `ε m`. These are the candidates for the dense part
+/// of a sparse-dense decomposition, whose vertices have degree close to `Δ`.
+///
+/// Storage: O(1/ε) counters
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeavyVertices {
+    parameters: HeavyVertexParameters,
+    endpoints: MisraGries,
+}
+
+impl HeavyVertices {
+    pub fn init(parameters: HeavyVertexParameters) -> Self {
+        if parameters.epsilon <= 0.0 || parameters.phi < parameters.epsilon {
+            panic!(
+                "[HeavyVertices] ε must be positive and at most φ: {:?}",
+                parameters
+            );
+        }
+
+        Self {
+            parameters,
+            endpoints: MisraGries::init((2.0 / parameters.epsilon).ceil() as usize),
+        }
+    }
+
+    pub fn parameters(&self) -> HeavyVertexParameters {
+        self.parameters
+    }
+
+    /// Feed the next edge of the stream, self loops are ignored
+    pub fn feed<W: Debug + Default>(&mut self, edge: Edge<VertexId, W>) {
+        let (u, v) = edge.vertices();
+        if u != v {
+            self.endpoints.feed(*u);
+            self.endpoints.feed(*v);
+        }
+    }
+
+    /// Number of edges of the stream
+    pub fn edge_count(&self) -> u64 {
+        self.endpoints.total() / 2
+    }
+
+    /// Lower bound on the degree of `v`, within `ε m` of it
+    pub fn degree(&self, v: VertexId) -> u64 {
+        self.endpoints.estimate(v)
+    }
+
+    /// Vertices of estimated degree at least `(φ - ε) m`, by decreasing estimate
+    pub fn heavy(&self, phi: f64) -> Vec<(VertexId, u64)> {
+        let m = self.edge_count() as f64;
+        let threshold = ((phi - self.parameters.epsilon) * m).ceil().max(0.0) as u64;
+
+        self.endpoints.above(threshold)
+    }
+
+    /// The heavy vertices, for the threshold `φ` of the parameters
+    pub fn query(self) -> Vec<(VertexId, u64)> {
+        self.heavy(self.parameters.phi)
+    }
+}
+
+impl StreamProcessor<Edge<VertexId, ()>, Vec<(VertexId, u64)>> for HeavyVertices {
+    fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        HeavyVertices::feed(self, edge)
+    }
+
+    fn query(self) -> Vec<(VertexId, u64)> {
+        HeavyVertices::query(self)
+    }
+}
+
+impl Merge for HeavyVertices {
+    fn merge(&mut self, other: &Self) {
+        self.endpoints.merge(&other.endpoints);
+    }
+}
+
+impl SpaceUsage for HeavyVertices {
+    /// The summary of the endpoints
+    fn space(&self) -> SpaceReport {
+        let endpoints = self.endpoints.space();
+
+        SpaceReport {
+            structure: "HeavyVertices",
+            bound: "O(1/ε · log n)",
+            parameters: vec![("ε", self.parameters.epsilon), ("φ", self.parameters.phi)],
+            theoretical_bits: endpoints.theoretical_bits,
+            measured_bytes: size_of::<Self>() + endpoints.measured_bytes - size_of::<MisraGries>(),
+            components: vec![endpoints],
+        }
+    }
+}
+
+impl Provenance for HeavyVertices {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "HeavyVertices (MisraGries)",
+            params: vec![("φ", self.parameters.phi), ("ε", self.parameters.epsilon)],
+            ..self.endpoints.info()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn point_queries() {
+        let n = 1000;
+        let parameters = CountMinParameters {
+            epsilon: 0.01,
+            delta: 0.01,
+            ..Default::default()
+        };
+        let mut sketch: CountMin = CountMin::init(n, parameters);
+
+        // f_j = j mod 10, with the insertions of 500..1000 deleted again
+        for j in 0..n {
+            for _ in 0..(j % 10) {
+                sketch.feed((j, true));
+            }
+        }
+        for j in 500..n {
+            for _ in 0..(j % 10) {
+                sketch.feed((j, false));
+            }
+        }
+
+        let total = sketch.total();
+        assert_eq!(total, (0..500).map(|j| (j % 10) as i64).sum::<i64>());
+
+        let slack = (parameters.epsilon * total as f64) as i64;
+        let within = (0..n)
+            .filter(|j| {
+                let f = if *j < 500 { (j % 10) as i64 } else { 0 };
+                let estimate = sketch.estimate(*j);
+                f <= estimate && estimate <= f + slack
+            })
+            .count();
+        assert_eq!(within, n as usize);
+    }
+
+    #[test]
+    fn heavy_hitters() {
+        let n = 1 << 12;
+        let mut sketch: CountMin = CountMin::init(n, Default::default());
+
+        for j in 0..n {
+            sketch.feed((j, true));
+        }
+        for heavy in [7, 2048, 4095] {
+            for _ in 0..1000 {
+                sketch.feed((heavy, true));
+            }
+        }
+
+        let hitters: Vec<u64> = sketch.query().into_iter().map(|(j, _)| j).collect();
+        assert_eq!(hitters, vec![7, 2048, 4095]);
+    }
+
+    #[test]
+    fn merge() {
+        let n = 100;
+        let base: CountMin = CountMin::init(n, Default::default());
+        let (mut left, mut right) = (base.clone(), base);
+
+        (0..50).for_each(|j| left.feed((j, true)));
+        (0..50).for_each(|j| right.feed((j, j % 2 == 0)));
+        left.merge(&right);
+
+        assert_eq!(left.total(), 50);
+        assert!(left.estimate(0) >= 2);
+    }
+
+    #[test]
+    fn edges() {
+        let n = 50;
+        let mut frequencies: EdgeFrequencies = EdgeFrequencies::init(n, Default::default());
+
+        // A star around 0, with the edge (0, 1) repeated
+        for v in 1..n {
+            frequencies.feed(Edge::<VertexId, ()>::init(0, v), true);
+        }
+        for _ in 0..20 {
+            frequencies.feed(Edge::<VertexId, ()>::init(1, 0), true);
+        }
+        frequencies.feed(Edge::<VertexId, ()>::init(0, 2), false);
+
+        assert_eq!(frequencies.edge_count(), 68);
+        assert!(frequencies.multiplicity(0, 1) >= 21);
+        assert!(frequencies.degree(0) >= 68);
+
+        let heavy = frequencies.query();
+        assert_eq!(heavy.edges, vec![(Edge::init(0, 1), 21)]);
+        assert_eq!(heavy.vertices.first().map(|(v, _)| *v), Some(0));
+    }
+
+    #[test]
+    fn count_sketch() {
+        let n = 1000;
+        let (epsilon, delta) = (0.1, 0.01);
+        let mut sketch: CountSketch = CountSketch::init(n, epsilon, delta);
+
+        // f_j = j mod 7 - 3, negative frequencies included
+        let f = |j: u64| (j % 7) as i64 - 3;
+        for j in 0..n {
+            sketch.update(j, f(j));
+        }
+
+        let norm = (0..n).map(|j| f(j).pow(2)).sum::<i64>() as f64;
+        let slack = epsilon * norm.sqrt();
+        let within = (0..n)
+            .filter(|j| ((sketch.estimate(*j) - f(*j)).abs() as f64) <= slack)
+            .count();
+        assert!(within >= 980, "{} estimates within {}", within, slack);
+
+        let f2 = sketch.query();
+        assert!((f2 - norm).abs() <= 0.2 * norm, "{} against {}", f2, norm);
+    }
+
+    #[test]
+    fn count_sketch_merge() {
+        let base: CountSketch = CountSketch::init(100, 0.1, 0.05);
+        let (mut left, mut right, mut whole) = (base.clone(), base.clone(), base);
+
+        for j in 0..100 {
+            whole.feed((j, true));
+            whole.feed((j / 2, j % 3 != 0));
+            left.feed((j, true));
+            right.feed((j / 2, j % 3 != 0));
+        }
+        left.merge(&right);
+
+        assert!((0..100).all(|j| left.estimate(j) == whole.estimate(j)));
+        assert_eq!(left.f2(), whole.f2());
+    }
+
+    #[test]
+    fn misra_gries() {
+        // 1 is half of the stream, 2 a quarter, and the rest are distinct
+        let stream = (0..4000).map(|i| match i % 4 {
+            0 | 2 => 1,
+            1 => 2,
+            _ => 100 + i,
+        });
+        let mut summary = MisraGries::init(9);
+        stream.for_each(|j| summary.feed(j));
+
+        let error = summary.error();
+        assert_eq!(error, 400);
+        assert!(summary.estimate(1) <= 2000 && summary.estimate(1) + error >= 2000);
+        assert!(summary.estimate(2) <= 1000 && summary.estimate(2) + error >= 1000);
+        assert!(summary.counters.len() <= 9);
+
+        let heavy: Vec<_> = summary
+            .query()
+            .into_iter()
+            .take(2)
+            .map(|(j, _)| j)
+            .collect();
+        assert_eq!(heavy, vec![1, 2]);
+    }
+
+    #[test]
+    fn misra_gries_merge() {
+        let (mut left, mut right) = (MisraGries::init(4), MisraGries::init(4));
+        for i in 0..1000 {
+            let j = if i % 3 == 0 { 7 } else { i };
+            if i < 500 {
+                left.feed(j);
+            } else {
+                right.feed(j);
+            }
+        }
+        left.merge(&right);
+
+        assert_eq!(left.total(), 1000);
+        assert!(left.counters.len() <= 4);
+        assert!(left.estimate(7) <= 334 && left.estimate(7) + left.error() >= 334);
+    }
+
+    #[test]
+    fn heavy_vertices() {
+        // A star of 200 leaves around 0, one of 50 around 1, and a perfect matching of 400 vertices
+        let edges = (2..202)
+            .map(|v| Edge::init(0, v))
+            .chain((202..252).map(|v| Edge::init(1, v)))
+            .chain((0..200).map(|i| Edge::init(300 + 2 * i, 301 + 2 * i)));
+
+        let mut heavy = HeavyVertices::init(HeavyVertexParameters {
+            phi: 0.2,
+            epsilon: 0.05,
+        });
+        edges.for_each(|edge: Edge<VertexId, ()>| heavy.feed(edge));
+        assert_eq!(heavy.edge_count(), 450);
+
+        // deg(0) = 200 >= 0.2 m = 90, and deg(1) = 50 < 0.15 m = 67.5
+        let vertices: Vec<_> = heavy.heavy(0.2).into_iter().map(|(v, _)| v).collect();
+        assert_eq!(vertices, vec![0]);
+        let degree = heavy.degree(0) as f64;
+        assert!(degree <= 200.0 && degree + heavy.parameters().epsilon * 450.0 >= 200.0);
+        assert!(heavy.heavy(0.1).iter().any(|(v, _)| *v == 1));
+    }
+
+    #[test]
+    fn degree_sketch() {
+        let parameters = CountMinParameters {
+            epsilon: 0.01,
+            ..Default::default()
+        };
+        let mut sketch: DegreeSketch = DegreeSketch::init(500, parameters);
+
+        // A star of 60 leaves around 0, a path through the other vertices, and a deleted star of 100 leaves around 1
+        let star = (2..62).map(|v| (Edge::init(0, v), true));
+        let path = (100..499).map(|v| (Edge::init(v, v + 1), true));
+        let deleted =
+            (100..200).flat_map(|v| vec![(Edge::init(1, v), true), (Edge::init(v, 1), false)]);
+        star.chain(path)
+            .chain(deleted)
+            .for_each(|(edge, c): (Edge<VertexId, ()>, bool)| sketch.feed(edge, c));
+        sketch.feed(Edge::<VertexId, ()>::init(3, 3), true);
+
+        let m = 60 + 399;
+        assert_eq!(sketch.edge_count(), m);
+        assert!(sketch.degree(0) >= 60);
+
+        let estimate = sketch.max_degree_estimate() as f64;
+        assert!(estimate >= 60.0);
+        assert!(
+            estimate <= 60.0 + parameters.epsilon * 2.0 * m as f64,
+            "{}",
+            estimate
+        );
+        assert_eq!(sketch.heavy_hitters(0.05)[0].0, 0);
+    }
+    #[test]
+    fn degree_quantiles() {
+        let parameters = CountMinParameters {
+            epsilon: 0.001,
+            ..Default::default()
+        };
+        let mut sketch: DegreeSketch = DegreeSketch::init(500, parameters);
+
+        // 39 isolated vertices, 62 of degree 1, 398 of degree 2 along a path, and the center of a star of 60 leaves
+        let star = (2..62).map(|v| Edge::init(0, v));
+        let path = (100..499).map(|v| Edge::init(v, v + 1));
+        star.chain(path)
+            .for_each(|edge: Edge<VertexId, ()>| sketch.feed(edge, true));
+
+        let quantiles = sketch.degree_quantiles(0.01);
+        assert_eq!(quantiles.count(), 500);
+        assert_eq!(quantiles.quantile(0.02), Some(0));
+        assert_eq!(quantiles.quantile(0.5), Some(2));
+        assert!(quantiles.quantile(1.0).unwrap() >= 60);
+    }
+}
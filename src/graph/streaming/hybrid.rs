@@ -0,0 +1,306 @@
+//! Hybrid Exact and Sketched Summaries
+//!
+//! Sketches pay their polylogarithmic factors even on streams small enough to be stored outright. A [HybridGraphSummary] keeps
+//! the edges exactly, as their multiplicities, while they fit in a memory budget. Once the buffer outgrows the budget, it builds a
+//! [ForestSketch], feeds it the buffered edges, drops the buffer, and feeds the sketch from then on. Small streams get exact
+//! answers, big ones the guarantees of the sketch, through the same API.
+
+use std::{collections::HashMap, mem::size_of};
+
+use super::{
+    connectivity::{Components, ForestSketch},
+    provenance::{AlgorithmInfo, Provenance},
+    space::{SpaceReport, SpaceUsage},
+    StreamProcessor,
+};
+use crate::{
+    graph::{Edge, VertexId},
+    utils::hash_function::HashFunction,
+};
+
+/// Bytes of a buffered edge, its index and its multiplicity
+const ENTRY: usize = size_of::<u64>() + size_of::<i64>();
+
+#[derive(Clone, Debug)]
+enum Mode<H> {
+    /// Multiplicity of every edge with a nonzero one, by index
+    Exact(HashMap<u64, i64>),
+    Sketch(ForestSketch<H>),
+}
+
+/// Connectivity of a graph, from a [HybridGraphSummary]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphSummary {
+    /// A spanning forest, with high probability if the edges were sketched
+    pub forest: Vec<Edge<VertexId, ()>>,
+    /// Number of connected components, isolated vertices included
+    pub components: u64,
+    /// Every edge of the graph, if the stream fit within the budget
+    pub edges: Option<Vec<Edge<VertexId, ()>>>,
+}
+
+impl GraphSummary {
+    /// Whether the summary was computed from the edges themselves
+    pub fn is_exact(&self) -> bool {
+        self.edges.is_some()
+    }
+}
+
+/// Summary of a turnstile graph stream over the vertices `[n]`, exact until its buffer of edges exceeds `budget` bytes
+///
+/// Storage: O(min(m log n, budget)) bits while exact, then those of a [ForestSketch]
+#[derive(Clone, Debug)]
+pub struct HybridGraphSummary<H> {
+    n: VertexId,
+    budget: usize,
+    /// Number of insertions minus number of deletions, tracked in both modes
+    edge_count: i64,
+    mode: Mode<H>,
+}
+
+impl<H> HybridGraphSummary<H>
+where
+    H: HashFunction,
+{
+    /// Initialize a summary over the vertices `[n]`, storing edges exactly in at most `budget` bytes
+    pub fn init(n: VertexId, budget: usize) -> Self {
+        Self {
+            n,
+            budget,
+            edge_count: 0,
+            mode: Mode::Exact(HashMap::new()),
+        }
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Whether the edges are still stored exactly
+    pub fn is_exact(&self) -> bool {
+        matches!(self.mode, Mode::Exact(_))
+    }
+
+    /// Number of edges of the graph so far, exact in both modes
+    pub fn edge_count(&self) -> i64 {
+        self.edge_count
+    }
+
+    /// Bytes of the buffer of exact edges, 0 once sketched
+    pub fn buffer_bytes(&self) -> usize {
+        match &self.mode {
+            Mode::Exact(buffer) => buffer.len() * ENTRY,
+            Mode::Sketch(_) => 0,
+        }
+    }
+
+    /// Feed an edge insertion or deletion, self loops are ignored
+    ///
+    /// Panics if an endpoint is not in `[n]`.
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
+        let (u, v) = edge.vertices_ord();
+        if u == v {
+            return;
+        }
+        assert!(
+            *v < self.n,
+            "[HybridGraphSummary] {} is not a vertex of [{}]",
+            v,
+            self.n
+        );
+        self.edge_count += if c { 1 } else { -1 };
+
+        match &mut self.mode {
+            Mode::Exact(buffer) => {
+                let j = edge.to_d1();
+                let multiplicity = buffer.entry(j).or_default();
+                *multiplicity += if c { 1 } else { -1 };
+                if *multiplicity == 0 {
+                    buffer.remove(&j);
+                }
+
+                if buffer.len() * ENTRY > self.budget {
+                    self.sketch();
+                }
+            }
+            Mode::Sketch(sketch) => sketch.feed((edge, c)),
+        }
+    }
+
+    /// Switch to the sketch, feeding it every buffered edge as many times as its multiplicity
+    fn sketch(&mut self) {
+        let mut sketch = ForestSketch::init(self.n);
+        if let Mode::Exact(buffer) = &self.mode {
+            for (j, multiplicity) in buffer.iter() {
+                let edge = Edge::from_d1(*j);
+                (0..multiplicity.abs()).for_each(|_| sketch.feed((edge, *multiplicity > 0)));
+            }
+        }
+        self.mode = Mode::Sketch(sketch);
+    }
+
+    /// The spanning forest and components of the streamed graph, and its edges if they were kept
+    pub fn query(self) -> GraphSummary {
+        let (forest, edges) = match self.mode {
+            Mode::Exact(buffer) => {
+                let mut edges: Vec<Edge<VertexId, ()>> = buffer
+                    .into_iter()
+                    .filter(|(_, multiplicity)| *multiplicity > 0)
+                    .map(|(j, _)| Edge::from_d1(j))
+                    .collect();
+                edges.sort_by_key(Edge::to_d1);
+
+                let mut components = Components::init(self.n);
+                let forest = edges
+                    .iter()
+                    .filter(|edge| {
+                        let (u, v) = edge.vertices();
+                        components.union(*u, *v)
+                    })
+                    .copied()
+                    .collect();
+                (forest, Some(edges))
+            }
+            Mode::Sketch(sketch) => (sketch.query(), None),
+        };
+
+        GraphSummary {
+            components: self.n - forest.len() as u64,
+            forest,
+            edges,
+        }
+    }
+}
+
+impl<H> StreamProcessor<(Edge<VertexId, ()>, bool), GraphSummary> for HybridGraphSummary<H>
+where
+    H: HashFunction,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        HybridGraphSummary::feed(self, token)
+    }
+
+    fn query(self) -> GraphSummary {
+        HybridGraphSummary::query(self)
+    }
+}
+
+impl<H> SpaceUsage for HybridGraphSummary<H>
+where
+    H: HashFunction,
+{
+    /// The buffer while exact, the sketch afterwards
+    fn space(&self) -> SpaceReport {
+        let n = self.n as f64;
+        let (theoretical_bits, measured_bytes, components) = match &self.mode {
+            Mode::Exact(buffer) => (
+                (buffer.len() * ENTRY * 8) as f64,
+                buffer.capacity() * ENTRY,
+                vec![],
+            ),
+            Mode::Sketch(sketch) => {
+                let report = sketch.space();
+                (
+                    report.theoretical_bits,
+                    report.measured_bytes - size_of::<ForestSketch<H>>(),
+                    vec![report],
+                )
+            }
+        };
+
+        SpaceReport {
+            structure: "HybridGraphSummary",
+            bound: "O(min(m log n, budget)), then space of ForestSketch",
+            parameters: vec![("n", n), ("budget", self.budget as f64)],
+            theoretical_bits,
+            measured_bytes: size_of::<Self>() + measured_bytes,
+            components,
+        }
+    }
+}
+
+impl<H> Provenance for HybridGraphSummary<H>
+where
+    H: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "HybridGraphSummary",
+            paper_ref: "Ahn, Guha, McGregor, SODA 2012, https://doi.org/10.1137/1.9781611973099.40, past the budget",
+            params: vec![
+                ("n", self.n as f64),
+                ("budget", self.budget as f64),
+                ("exact", self.is_exact() as u8 as f64),
+            ],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::hash_function::PowerFiniteFieldHasher;
+
+    type Summary = HybridGraphSummary<PowerFiniteFieldHasher>;
+
+    /// Two paths, `0 - 1 - ... - 9` and `10 - ... - 19`, with a chord inserted then deleted
+    fn stream() -> Vec<(Edge<VertexId, ()>, bool)> {
+        let mut stream: Vec<_> = (0..9)
+            .chain(10..19)
+            .map(|v| (Edge::init(v, v + 1), true))
+            .collect();
+        stream.push((Edge::init(0, 19), true));
+        stream.push((Edge::init(19, 0), false));
+        stream
+    }
+
+    fn summarize(budget: usize) -> (bool, GraphSummary) {
+        let mut summary = Summary::init(20, budget);
+        stream().into_iter().for_each(|token| summary.feed(token));
+        assert_eq!(summary.edge_count(), 18);
+
+        (summary.is_exact(), summary.query())
+    }
+
+    #[test]
+    fn exact() {
+        let (exact, summary) = summarize(1 << 20);
+
+        assert!(exact && summary.is_exact());
+        assert_eq!(summary.edges.as_ref().unwrap().len(), 18);
+        assert!(!summary.edges.unwrap().contains(&Edge::init(0, 19)));
+        assert_eq!((summary.forest.len(), summary.components), (18, 2));
+    }
+
+    #[test]
+    fn sketched() {
+        // Room for 4 edges, the stream switches to the sketch on the 5th
+        let (exact, summary) = summarize(4 * ENTRY);
+
+        assert!(!exact && !summary.is_exact());
+        assert_eq!(summary.components, 2);
+        assert!(summary
+            .forest
+            .iter()
+            .all(|edge| stream().contains(&(*edge, true))));
+    }
+
+    #[test]
+    fn budget() {
+        let mut summary = Summary::init(10, 2 * ENTRY);
+
+        summary.feed((Edge::init(0, 1), true));
+        summary.feed((Edge::init(1, 2), true));
+        summary.feed((Edge::init(1, 2), false));
+        summary.feed((Edge::init(2, 3), true));
+        assert!(summary.is_exact());
+        assert_eq!(summary.buffer_bytes(), 2 * ENTRY);
+
+        summary.feed((Edge::init(3, 4), true));
+        assert!(!summary.is_exact());
+        assert_eq!(summary.buffer_bytes(), 0);
+        assert!(summary.space().components[0].structure == "ForestSketch");
+    }
+}
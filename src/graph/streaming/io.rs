@@ -0,0 +1,313 @@
+//! Edge Stream Files
+//!
+//! Datasets of edges (SNAP, KONECT, network repository) list one edge per line, as two vertex ids separated by a delimiter,
+//! sometimes followed by a weight, with comment lines at the top. An [EdgeStreamReader] turns such a file into the stream of
+//! tokens that the streaming structures are fed, one line at a time, so the file is never held in memory.
+//!
+//! ```text
+//! # source target [weight] [+|-]
+//! 0 1
+//! 0 2 5
+//! 0 2 5 -
+//! ```
+//!
+//! With [turnstile](EdgeStreamParameters::turnstile) set, every line ends with `+` for an insertion, or `-` for a deletion.
+//!
+//! Whole instances of the DIMACS flow and matching benchmarks, with their optimal values, are read by [dimacs].
+
+pub mod dimacs;
+
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    io::{self, BufRead, Lines},
+    marker::PhantomData,
+};
+
+use crate::graph::{parse::Weight, Edge, VertexId};
+
+/// Format of the lines of an [EdgeStreamReader]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdgeStreamParameters {
+    /// Separator of the fields of a line, any run of whitespace if `None`
+    pub delimiter: Option<char>,
+    /// Lines starting with this prefix are skipped, as are blank lines
+    pub comment: Option<String>,
+    /// Whether a weight follows the two vertices
+    pub weighted: bool,
+    /// Whether the line ends with a `+` or `-` marker, for an insertion or a deletion
+    pub turnstile: bool,
+}
+
+impl Default for EdgeStreamParameters {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            comment: Some("#".to_string()),
+            weighted: false,
+            turnstile: false,
+        }
+    }
+}
+
+impl EdgeStreamParameters {
+    /// Fields separated by the delimiter, e.g. `,` for csv or `\t` for tsv
+    pub fn delimited(delimiter: char) -> Self {
+        Self {
+            delimiter: Some(delimiter),
+            ..Default::default()
+        }
+    }
+}
+
+/// Reasons for which a line cannot be turned into a token
+#[derive(Debug)]
+pub enum ReadError {
+    /// The underlying reader failed
+    Io(io::Error),
+    /// The line, numbered from 1, is malformed
+    Parse { line: usize, message: String },
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read the stream: {}", e),
+            Self::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse { .. } => None,
+        }
+    }
+}
+
+impl ReadError {
+    /// The malformed line, `None` if the reader failed
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::Io(_) => None,
+            Self::Parse { line, .. } => Some(*line),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Iterator over the tokens `(edge, c)` of a stream of lines, `c` being false for a deletion
+///
+/// Every malformed line yields an error, after which reading continues with the next line.
+pub struct EdgeStreamReader<R, W = ()> {
+    lines: Lines<R>,
+    line: usize,
+    parameters: EdgeStreamParameters,
+    label: PhantomData<W>,
+}
+
+impl<R, W> EdgeStreamReader<R, W>
+where
+    R: BufRead,
+    W: Weight,
+{
+    pub fn init(reader: R, parameters: EdgeStreamParameters) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: 0,
+            parameters,
+            label: PhantomData,
+        }
+    }
+
+    /// Number of lines read so far, skipped ones included
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    fn parse(&self, line: &str) -> Result<(Edge<VertexId, W>, bool), String> {
+        let mut fields: Box<dyn Iterator<Item = &str>> = match self.parameters.delimiter {
+            Some(delimiter) => Box::new(line.split(delimiter).map(str::trim)),
+            None => Box::new(line.split_whitespace()),
+        };
+        let mut next = |name: &str| fields.next().ok_or(format!("missing {}", name));
+
+        let vertex = |field: &str| {
+            field
+                .parse::<VertexId>()
+                .map_err(|e| format!("invalid vertex {:?}: {}", field, e))
+        };
+        let u = vertex(next("source")?)?;
+        let v = vertex(next("target")?)?;
+
+        let mut edge = Edge::init(u, v);
+        if self.parameters.weighted {
+            let field = next("weight")?;
+            let weight =
+                W::parse_weight(field).map_err(|e| format!("invalid weight {:?}: {}", field, e))?;
+            edge.update_label(weight);
+        }
+
+        let c = if self.parameters.turnstile {
+            match next("deletion marker")? {
+                "+" => true,
+                "-" => false,
+                marker => return Err(format!("invalid deletion marker {:?}", marker)),
+            }
+        } else {
+            true
+        };
+
+        match fields.next() {
+            Some(extra) => Err(format!("unexpected field {:?}", extra)),
+            None => Ok((edge, c)),
+        }
+    }
+}
+
+impl<R, W> Iterator for EdgeStreamReader<R, W>
+where
+    R: BufRead,
+    W: Weight,
+{
+    type Item = Result<(Edge<VertexId, W>, bool), ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            self.line += 1;
+
+            let trimmed = line.trim();
+            let comment = self
+                .parameters
+                .comment
+                .as_deref()
+                .is_some_and(|prefix| trimmed.starts_with(prefix));
+            if trimmed.is_empty() || comment {
+                continue;
+            }
+
+            return Some(self.parse(trimmed).map_err(|message| ReadError::Parse {
+                line: self.line,
+                message,
+            }));
+        }
+    }
+}
+
+impl<R, W> Debug for EdgeStreamReader<R, W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EdgeStreamReader")
+            .field("line", &self.line)
+            .field("parameters", &self.parameters)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Token<W> = Result<(Edge<VertexId, W>, bool), ReadError>;
+
+    fn tokens<W: Weight>(input: &str, parameters: EdgeStreamParameters) -> Vec<Token<W>> {
+        EdgeStreamReader::init(input.as_bytes(), parameters).collect()
+    }
+
+    #[test]
+    fn plain() {
+        let input = "# FromNodeId\tToNodeId\n0\t1\n\n1 \t 2\n";
+        let edges: Vec<_> = tokens::<()>(input, Default::default())
+            .into_iter()
+            .map(|token| token.unwrap())
+            .collect();
+
+        assert_eq!(
+            edges,
+            vec![(Edge::init(0, 1), true), (Edge::init(1, 2), true)]
+        );
+    }
+
+    #[test]
+    fn weighted_turnstile() {
+        let parameters = EdgeStreamParameters {
+            weighted: true,
+            turnstile: true,
+            ..EdgeStreamParameters::delimited(',')
+        };
+        let input = "% header\n0,1,5,+\n0, 1, 5, -\n";
+
+        let edges: Vec<(Edge<VertexId, u64>, bool)> = tokens(
+            input,
+            EdgeStreamParameters {
+                comment: Some("%".to_string()),
+                ..parameters.clone()
+            },
+        )
+        .into_iter()
+        .map(|token| token.unwrap())
+        .collect();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(*edges[0].0.label(), 5);
+        assert!(edges[0].1 && !edges[1].1);
+
+        // The weight is checked to be present, but dropped, for unlabelled edges
+        let edges = tokens::<()>("0,1,5,+", parameters);
+        assert_eq!(edges[0].as_ref().unwrap().0, Edge::init(0, 1));
+    }
+
+    #[test]
+    fn malformed() {
+        let input = "0 1\n0 x\n0\n0 1 2\n3 4\n";
+        let tokens = tokens::<()>(input, Default::default());
+
+        let lines: Vec<_> = tokens
+            .iter()
+            .map(|token| token.as_ref().err().and_then(ReadError::line))
+            .collect();
+        assert_eq!(lines, vec![None, Some(2), Some(3), Some(4), None]);
+        assert_eq!(
+            tokens[2].as_ref().unwrap_err().to_string(),
+            "line 3: missing target"
+        );
+    }
+
+    /// Reads a stream the way an application would, failing on the first malformed line
+    fn count(input: &[u8]) -> anyhow::Result<usize> {
+        let mut count = 0;
+        for token in EdgeStreamReader::<_, ()>::init(input, Default::default()) {
+            token.map_err(|e| anyhow::Error::new(e).context("reading the edge stream"))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    #[test]
+    fn error_chain() {
+        assert_eq!(count(b"0 1\n1 2\n").unwrap(), 2);
+
+        let error = count(b"0 1\n1 y\n").unwrap_err();
+        assert_eq!(error.to_string(), "reading the edge stream");
+        assert_eq!(
+            error.downcast_ref::<ReadError>().and_then(ReadError::line),
+            Some(2)
+        );
+
+        // Invalid UTF-8 fails the reader itself, which is the source of the error
+        let error = count(b"0 1\n\xff\n").unwrap_err();
+        let read = error.downcast_ref::<ReadError>().unwrap();
+        assert!(matches!(read, ReadError::Io(_)));
+        assert!(read.source().unwrap().is::<io::Error>());
+        assert_eq!(error.chain().count(), 3);
+    }
+}
@@ -0,0 +1,438 @@
+//! DIMACS Benchmark Files
+//!
+//! Instances of the DIMACS implementation challenges, along with the optimal values published for them, check the flow and
+//! matching algorithms against known answers. Every line starts with a letter giving its kind, `c` lines being comments:
+//!
+//! ```text
+//! c max-flow                    c matching              c solution
+//! p max 4 5                     p edge 4 3              s 5
+//! n 1 s                         e 1 2                   f 1 2 3
+//! n 4 t                         e 2 3 7                 m 1 2
+//! a 1 2 3                       e 3 4
+//! a 1 3 2
+//! ...
+//! ```
+//!
+//! - A max-flow network has a `p max NODES ARCS` line, the source and the sink as `n ID s` and `n ID t`, and one `a SRC DST CAP`
+//!   line per arc
+//! - A matching instance has a `p edge NODES EDGES` line, and one `e U V [WEIGHT]` line per edge, of weight 1 when it is missing
+//! - A solution has its value as `s VALUE`, followed by the flow of the arcs as `f SRC DST FLOW`, or the matched edges as `m U V`
+//!
+//! The files number vertices from 1, they are numbered from 0 once read, so that they are the vertices `[n]` of the streaming
+//! structures.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::BufRead,
+};
+
+use super::ReadError;
+use crate::graph::{Edge, Graph, Graphed, VertexId};
+
+/// A max-flow network
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlowNetwork {
+    pub nodes: u64,
+    pub source: VertexId,
+    pub sink: VertexId,
+    /// Directed arcs, labelled by their capacity
+    pub arcs: Vec<Edge<VertexId, u64>>,
+}
+
+impl FlowNetwork {
+    /// Every vertex, and the arcs labelled by their capacity
+    pub fn graph(&self) -> Graph<VertexId, u64> {
+        let mut graph = Graph::new((0..self.nodes).map(|v| (v, HashSet::new())).collect());
+        self.arcs.iter().for_each(|arc| graph.add_edge(*arc));
+        graph
+    }
+
+    /// Total capacity of the arcs leaving `side`, an upper bound on the flow if `side` holds the source and not the sink
+    pub fn cut_capacity(&self, side: &HashSet<VertexId>) -> u64 {
+        self.arcs
+            .iter()
+            .filter(|arc| {
+                let (u, v) = arc.vertices();
+                side.contains(u) && !side.contains(v)
+            })
+            .map(|arc| *arc.label())
+            .sum()
+    }
+
+    /// Check that the flows of a [Solution] respect the capacities and are conserved at every vertex but the source and the
+    /// sink, and return the value of the flow
+    ///
+    /// Parallel arcs between the same vertices share their capacity.
+    pub fn verify_flow(&self, solution: &Solution) -> Result<u64, String> {
+        let mut capacities = HashMap::<(VertexId, VertexId), u64>::new();
+        for arc in self.arcs.iter() {
+            let (u, v) = arc.vertices();
+            *capacities.entry((*u, *v)).or_default() += arc.label();
+        }
+
+        let mut excess = vec![0_i64; self.nodes as usize];
+        let mut flows = HashMap::<(VertexId, VertexId), u64>::new();
+        for (u, v, flow) in solution.pairs.iter().copied() {
+            if u >= self.nodes || v >= self.nodes {
+                return Err(format!("the arc ({}, {}) is outside of the network", u, v));
+            }
+            *flows.entry((u, v)).or_default() += flow;
+            excess[u as usize] -= flow as i64;
+            excess[v as usize] += flow as i64;
+        }
+
+        for ((u, v), flow) in flows {
+            let capacity = capacities.get(&(u, v)).copied().unwrap_or_default();
+            if flow > capacity {
+                return Err(format!(
+                    "flow {} on the arc ({}, {}) exceeds its capacity {}",
+                    flow, u, v, capacity
+                ));
+            }
+        }
+        if let Some(v) = (0..self.nodes)
+            .find(|v| *v != self.source && *v != self.sink && excess[*v as usize] != 0)
+        {
+            return Err(format!("flow is not conserved at {}", v));
+        }
+
+        Ok(excess[self.sink as usize].max(0) as u64)
+    }
+}
+
+/// A matching instance
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchingInstance {
+    pub nodes: u64,
+    /// Undirected edges, labelled by their weight
+    pub edges: Vec<Edge<VertexId, u64>>,
+}
+
+impl MatchingInstance {
+    /// Every vertex, and the edges labelled by their weight
+    pub fn graph(&self) -> Graph<VertexId, u64> {
+        let mut graph = Graph::new((0..self.nodes).map(|v| (v, HashSet::new())).collect());
+        self.edges.iter().for_each(|edge| graph.add_edge(*edge));
+        graph
+    }
+
+    /// Check that the matched edges of a [Solution] are edges of the instance, no two of them sharing a vertex, and return the
+    /// number of matched edges and their total weight
+    pub fn verify_matching(&self, solution: &Solution) -> Result<(u64, u64), String> {
+        let weights: HashMap<u64, u64> = self
+            .edges
+            .iter()
+            .map(|edge| (edge.to_d1(), *edge.label()))
+            .collect();
+
+        let mut matched = HashSet::new();
+        let mut weight = 0;
+        for (u, v, _) in solution.pairs.iter().copied() {
+            if u == v {
+                return Err(format!("({}, {}) is a self loop", u, v));
+            }
+            let edge = Edge::<VertexId, u64>::init(u, v);
+            weight += weights
+                .get(&edge.to_d1())
+                .ok_or_else(|| format!("({}, {}) is not an edge of the instance", u, v))?;
+            if let Some(w) = [u, v].iter().find(|w| !matched.insert(**w)) {
+                return Err(format!("{} is matched twice", w));
+            }
+        }
+
+        Ok((solution.pairs.len() as u64, weight))
+    }
+}
+
+/// Optimal value of an instance, with the flows `(u, v, flow)` or the matched edges `(u, v, 1)` achieving it, if given
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Solution {
+    pub value: u64,
+    pub pairs: Vec<(VertexId, VertexId, u64)>,
+}
+
+/// Fields of the next line that is neither blank nor a comment, with its number
+struct Lines<R> {
+    lines: std::io::Lines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> Lines<R> {
+    fn next(&mut self) -> Option<Result<Vec<String>, ReadError>> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            self.line += 1;
+
+            let fields: Vec<String> = line.split_whitespace().map(String::from).collect();
+            if !fields.is_empty() && fields[0] != "c" {
+                return Some(Ok(fields));
+            }
+        }
+    }
+
+    fn error(&self, message: String) -> ReadError {
+        ReadError::Parse {
+            line: self.line,
+            message,
+        }
+    }
+}
+
+/// Read the lines of a file, handing the fields of each one to `statement`, then check the counts of the problem line
+///
+/// Returns the number of nodes, and the number of the last line.
+fn read<R, F>(reader: R, problem: &str, mut statement: F) -> Result<(u64, usize), ReadError>
+where
+    R: BufRead,
+    F: FnMut(u64, &[String]) -> Result<(), String>,
+{
+    let mut lines = Lines {
+        lines: reader.lines(),
+        line: 0,
+    };
+    let mut header: Option<(u64, usize)> = None;
+    let mut count = 0;
+
+    while let Some(fields) = lines.next() {
+        let fields = fields?;
+        match (fields[0].as_str(), header) {
+            ("p", None) => {
+                if fields.len() != 4 || fields[1] != problem {
+                    return Err(lines.error(format!("expected \"p {} NODES COUNT\"", problem)));
+                }
+                let parse = |field: &str| {
+                    field
+                        .parse::<u64>()
+                        .map_err(|e| lines.error(format!("invalid count {:?}: {}", field, e)))
+                };
+                header = Some((parse(&fields[2])?, parse(&fields[3])? as usize));
+            }
+            ("p", Some(_)) => return Err(lines.error("duplicate problem line".to_string())),
+            (_, None) => return Err(lines.error("expected the problem line first".to_string())),
+            (_, Some((nodes, _))) => {
+                if matches!(fields[0].as_str(), "a" | "e") {
+                    count += 1;
+                }
+                statement(nodes, &fields).map_err(|message| lines.error(message))?;
+            }
+        }
+    }
+
+    let (nodes, expected) = header.ok_or_else(|| lines.error("missing problem line".into()))?;
+    if count != expected {
+        return Err(lines.error(format!(
+            "the problem line announces {} arcs or edges, found {}",
+            expected, count
+        )));
+    }
+    Ok((nodes, lines.line))
+}
+
+/// Vertex numbered from 1 among `nodes`, numbered from 0
+fn vertex(field: Option<&String>, nodes: u64) -> Result<VertexId, String> {
+    let field = field.ok_or("missing vertex")?;
+    match field.parse::<VertexId>() {
+        Ok(v) if 1 <= v && v <= nodes => Ok(v - 1),
+        Ok(v) => Err(format!("vertex {} is outside of [1, {}]", v, nodes)),
+        Err(e) => Err(format!("invalid vertex {:?}: {}", field, e)),
+    }
+}
+
+fn number(field: Option<&String>, name: &str) -> Result<u64, String> {
+    let field = field.ok_or(format!("missing {}", name))?;
+    field
+        .parse()
+        .map_err(|e| format!("invalid {} {:?}: {}", name, field, e))
+}
+
+fn end(fields: &[String], len: usize) -> Result<(), String> {
+    match fields.get(len) {
+        Some(extra) => Err(format!("unexpected field {:?}", extra)),
+        None => Ok(()),
+    }
+}
+
+/// Read a max-flow network, in the `p max` format
+pub fn read_max_flow<R: BufRead>(reader: R) -> Result<FlowNetwork, ReadError> {
+    let (mut source, mut sink) = (None, None);
+    let mut arcs = vec![];
+
+    let (nodes, last) = read(reader, "max", |nodes, fields| match fields[0].as_str() {
+        "n" => {
+            let v = vertex(fields.get(1), nodes)?;
+            let terminal = match fields.get(2).map(String::as_str) {
+                Some("s") => &mut source,
+                Some("t") => &mut sink,
+                _ => return Err("expected a node designated s or t".to_string()),
+            };
+            if terminal.replace(v).is_some() {
+                return Err(format!("{} is designated twice", fields[2]));
+            }
+            end(fields, 3)
+        }
+        "a" => {
+            let mut arc =
+                Edge::init_directed(vertex(fields.get(1), nodes)?, vertex(fields.get(2), nodes)?);
+            arc.update_label(number(fields.get(3), "capacity")?);
+            arcs.push(arc);
+            end(fields, 4)
+        }
+        kind => Err(format!("unexpected line of kind {:?}", kind)),
+    })?;
+
+    let missing = |name: &str| ReadError::Parse {
+        line: last,
+        message: format!("missing the {}", name),
+    };
+    Ok(FlowNetwork {
+        nodes,
+        source: source.ok_or_else(|| missing("source"))?,
+        sink: sink.ok_or_else(|| missing("sink"))?,
+        arcs,
+    })
+}
+
+/// Read a matching instance, in the `p edge` format
+pub fn read_matching<R: BufRead>(reader: R) -> Result<MatchingInstance, ReadError> {
+    let mut edges = vec![];
+
+    let (nodes, _) = read(reader, "edge", |nodes, fields| match fields[0].as_str() {
+        "e" => {
+            let mut edge = Edge::init(vertex(fields.get(1), nodes)?, vertex(fields.get(2), nodes)?);
+            if fields.len() > 3 {
+                edge.update_label(number(fields.get(3), "weight")?);
+            } else {
+                edge.update_label(1);
+            }
+            edges.push(edge);
+            end(fields, 4)
+        }
+        kind => Err(format!("unexpected line of kind {:?}", kind)),
+    })?;
+
+    Ok(MatchingInstance { nodes, edges })
+}
+
+/// Flow `f SRC DST FLOW` of an arc, or matched edge `m U V`
+fn pair(fields: &[String]) -> Result<(VertexId, VertexId, u64), String> {
+    let u = vertex(fields.get(1), VertexId::MAX)?;
+    let v = vertex(fields.get(2), VertexId::MAX)?;
+    if fields[0] == "f" {
+        let flow = number(fields.get(3), "flow")?;
+        end(fields, 4).map(|_| (u, v, flow))
+    } else {
+        end(fields, 3).map(|_| (u, v, 1))
+    }
+}
+
+/// Read the solution of an instance, vertices being numbered from 1 in the file
+pub fn read_solution<R: BufRead>(reader: R) -> Result<Solution, ReadError> {
+    let mut lines = Lines {
+        lines: reader.lines(),
+        line: 0,
+    };
+    let mut solution: Option<Solution> = None;
+
+    while let Some(fields) = lines.next() {
+        let fields = fields?;
+        let result = match (fields[0].as_str(), solution.as_mut()) {
+            ("s", None) => number(fields.get(1), "value").and_then(|value| {
+                solution = Some(Solution {
+                    value,
+                    pairs: vec![],
+                });
+                end(&fields, 2)
+            }),
+            ("s", Some(_)) => Err("duplicate solution line".to_string()),
+            (_, None) => Err("expected the solution line first".to_string()),
+            ("f" | "m", Some(solution)) => pair(&fields).map(|pair| solution.pairs.push(pair)),
+            (kind, _) => Err(format!("unexpected line of kind {:?}", kind)),
+        };
+        result.map_err(|message| lines.error(message))?;
+    }
+
+    solution.ok_or_else(|| lines.error("missing solution line".to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The network of the DIMACS format description, of maximum flow 5
+    const NETWORK: &str = "c example
+p max 4 5
+n 1 s
+n 4 t
+a 1 2 4
+a 1 3 2
+a 2 3 2
+a 2 4 1
+a 3 4 5
+";
+
+    #[test]
+    fn max_flow() {
+        let network = read_max_flow(NETWORK.as_bytes()).unwrap();
+
+        assert_eq!((network.nodes, network.source, network.sink), (4, 0, 3));
+        assert_eq!(network.arcs.len(), 5);
+        assert!(network.arcs.iter().all(|arc| arc.is_directed()));
+        assert_eq!(network.graph().get_neighbors(&0).unwrap().len(), 2);
+
+        // The published optimum, achieved by the flow and matched by a cut
+        let solution =
+            read_solution("s 5\nf 1 2 3\nf 1 3 2\nf 2 3 2\nf 2 4 1\nf 3 4 4\n".as_bytes()).unwrap();
+        assert_eq!(network.verify_flow(&solution), Ok(solution.value));
+        assert_eq!(network.cut_capacity(&vec![0].into_iter().collect()), 6);
+        assert_eq!(network.cut_capacity(&vec![0, 1].into_iter().collect()), 5);
+
+        let overflow = Solution {
+            value: 4,
+            pairs: vec![(0, 1, 1), (1, 3, 2)],
+        };
+        assert!(network.verify_flow(&overflow).is_err());
+    }
+
+    #[test]
+    fn matching() {
+        // Two disjoint paths on 3 edges, of maximum matching 4
+        let input = "p edge 8 6\ne 1 2\ne 2 3\ne 3 4\ne 5 6 3\ne 6 7\ne 7 8\n";
+        let instance = read_matching(input.as_bytes()).unwrap();
+        let solution =
+            read_solution("c optimum\ns 4\nm 1 2\nm 3 4\nm 5 6\nm 7 8\n".as_bytes()).unwrap();
+
+        assert_eq!(instance.edges[3].vertices(), (&4, &5));
+        assert_eq!(*instance.edges[3].label(), 3);
+        assert_eq!(instance.graph().get_neighbors(&5).unwrap().len(), 2);
+        assert_eq!(instance.verify_matching(&solution), Ok((solution.value, 6)));
+
+        let overlapping = Solution {
+            value: 2,
+            pairs: vec![(0, 1), (1, 2)]
+                .into_iter()
+                .map(|(u, v)| (u, v, 1))
+                .collect(),
+        };
+        assert!(instance.verify_matching(&overlapping).is_err());
+        let missing = Solution {
+            value: 1,
+            pairs: vec![(0, 7, 1)],
+        };
+        assert!(instance.verify_matching(&missing).is_err());
+    }
+
+    #[test]
+    fn malformed() {
+        let line = |input: &str| read_max_flow(input.as_bytes()).unwrap_err().line();
+
+        assert_eq!(line("a 1 2 3\n"), Some(1));
+        assert_eq!(line("p max 2 1\nn 1 s\nn 2 t\nc\na 1 3 1\n"), Some(5));
+        assert_eq!(line("p max 2 1\nn 1 s\nn 2 s\n"), Some(3));
+        assert_eq!(line("p max 2 2\nn 1 s\nn 2 t\na 1 2 1\n"), Some(4));
+        assert!(read_matching("p max 2 0\n".as_bytes()).is_err());
+        assert!(read_solution("m 1 2\n".as_bytes()).is_err());
+    }
+}
@@ -1,41 +1,87 @@
 //! Graph Streaming Algorithms
 //!
 //! Many of the functions here are implemented based off of the lecture notes from Dartmouth's [CS35 Spring 2020 Lecture Notes](https://www.cs.dartmouth.edu/~ac/Teach/CS35-Spring20/Notes/lecnotes.pdf)
+//!
+//! With the `serde` feature, the sketches can be serialized, so that a long running job can checkpoint its state and resume after a crash.
+//! With the `futures` feature, they can also be fed from an async `Stream`, e.g. of edges read off a socket, with the functions of `asynchronous`.
 
-#[derive(Debug)]
-pub enum Stream<S, Q> {
-    InStream(S),
-    Queried(Q),
-}
+/// A structure fed a stream of tokens, one at a time, and consumed by a query once the stream ends
+///
+/// Every streaming structure of this crate implements it, with the token type of its stream, e.g. `(Edge<VertexId, ()>, bool)`
+/// for a turnstile stream of edges, and its output. Harnesses can then feed and query any of them generically.
+pub trait StreamProcessor<T, O> {
+    fn feed(&mut self, token: T);
 
-impl<S, Q> Stream<S, Q> {
-    /// Panics if the stream as already been consumed.
-    fn as_stream(&mut self) -> &mut S {
-        match self {
-            Self::InStream(s) => s,
-            _ => panic!("Cannot capture a stream that has already been consumed"),
-        }
-    }
+    fn query(self) -> O;
 }
 
-pub trait Query<T> {
-    fn query(self) -> T;
+/// A sketch that summarizes a turnstile stream of tokens `(j, c)`, where `j \in [n]` and `c` is true for an insertion, and false for a deletion
+pub trait Sketch {
+    fn feed(&mut self, token: (u64, bool));
 }
 
-impl<S, Q> Stream<S, Q>
-where
-    S: Query<Q>,
-{
-    fn query(self) -> Q {
-        match self {
-            Self::InStream(stream) => stream.query(),
-            Self::Queried(res) => res,
-        }
-    }
+/// A sketch that can absorb another sketch, as if it had also been fed the stream of the other
+///
+/// Both sketches must share their random components (hash functions, seeds, sampled vertices), i.e. be clones of a sketch taken
+/// before either was fed. A stream can then be split across threads or machines, fed to each clone independently, and merged before the query.
+pub trait Merge {
+    /// Panics if the random components differ, when the implementation can tell
+    fn merge(&mut self, other: &Self);
 }
 
+pub mod arrival;
+#[cfg(feature = "futures")]
+pub mod asynchronous;
+pub mod bipartite;
+pub mod blocks;
+pub mod chromatic;
+pub mod clustering;
 pub mod coloring;
-mod counting;
-mod distinct;
+pub mod connectivity;
+pub mod counting;
+pub mod distinct;
+pub mod forest;
+pub mod frequency;
+pub mod hybrid;
+pub mod io;
+pub mod moments;
+pub mod parallel;
+pub mod position;
+pub mod progress;
+pub mod provenance;
+pub mod quantiles;
 pub mod sampling;
+pub mod simulator;
+pub mod sketch_bank;
+pub mod space;
+pub mod spanning;
 pub mod sparse_recovery;
+pub mod sparsifier;
+pub mod triangles;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        graph::{streaming::connectivity::ForestSketch, Edge, VertexId},
+        utils::hash_function::PowerFiniteFieldHasher,
+    };
+
+    fn run<T, O, P>(mut processor: P, stream: impl IntoIterator<Item = T>) -> O
+    where
+        P: StreamProcessor<T, O>,
+    {
+        stream.into_iter().for_each(|token| processor.feed(token));
+        processor.query()
+    }
+
+    #[test]
+    fn generic_pipeline() {
+        let path: Vec<(Edge<VertexId, ()>, bool)> =
+            (0..5).map(|v| (Edge::init(v, v + 1), true)).collect();
+
+        let forest: Vec<Edge<VertexId, ()>> =
+            run(ForestSketch::<PowerFiniteFieldHasher>::init(6), path);
+        assert_eq!(forest.len(), 5);
+    }
+}
@@ -0,0 +1,236 @@
+//! Frequency Moments
+//!
+//! The `k`th frequency moment of a stream over `[n]` is `F_k = Σ f_j^k`. `F_0` is the number of distinct tokens, estimated by
+//! the HyperLogLog of [distinct](super::distinct), and `F_1` the length of the stream, counted approximately by the Morris
+//! counters of [counting](super::counting). This module estimates `F_2` with the tug-of-war sketch of [Alon, Matias and Szegedy](https://doi.org/10.1006/jcss.1997.1545):
+//!
+//! A counter `z = Σ s(j) f_j` of random signs `s(j)`, drawn from a 4-wise independent family, has `E[z²] = F_2` and
+//! `Var[z²] <= 2 F_2²`. The average of `t = ⌈6/ε²⌉` counters is within `ε F_2` with probability 2/3, and the median of
+//! `O(log(1/δ))` such averages with probability `1 - δ`.
+
+use std::mem::size_of;
+
+use rand::Rng;
+
+use crate::{
+    graph::streaming::{
+        provenance::{AlgorithmInfo, Provenance},
+        space::{log, SpaceReport, SpaceUsage},
+        Merge, Sketch, StreamProcessor,
+    },
+    utils::hash_function::{HashFunction, PolynomialHasher},
+};
+
+/// Independence of the signs, the variance bound needs 4
+const INDEPENDENCE: usize = 4;
+
+/// Estimates the second frequency moment `F_2 = ||f||_2^2` of a turnstile stream over `[n]`, within a factor `1 ± ε`
+///
+/// Storage: O(log(1/δ) / ε²) counters, and as many hash functions of 4 words
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct TugOfWar {
+    n: u64,
+    epsilon: f64,
+    delta: f64,
+    /// Number of counters averaged by each group
+    group_size: usize,
+    signs: Vec<PolynomialHasher>,
+    counters: Vec<i64>,
+}
+
+impl TugOfWar {
+    /// Initialize an estimator of `F_2` over `[n]`
+    ///
+    /// - *epsilon* : Relative error of the estimate
+    /// - *delta* : Probability with which the estimate exceeds its error
+    pub fn init(n: u64, epsilon: f64, delta: f64) -> Self {
+        Self::init_with_rng(n, epsilon, delta, &mut rand::thread_rng())
+    }
+
+    /// Initialize an estimator, drawing its signs from the generator
+    pub fn init_with_rng<R: Rng + ?Sized>(n: u64, epsilon: f64, delta: f64, rng: &mut R) -> Self {
+        if epsilon <= 0.0 || delta <= 0.0 || delta >= 1.0 {
+            panic!(
+                "[TugOfWar] ε must be positive and δ within (0, 1): {}, {}",
+                epsilon, delta
+            );
+        }
+
+        let group_size = (6.0 / epsilon.powi(2)).ceil() as usize;
+        // An odd number of groups, so that the median is one of them
+        let groups = (4.0 * (1.0 / delta).ln()).ceil() as usize | 1;
+
+        let signs = (0..groups * group_size)
+            .map(|_| PolynomialHasher::init_with_rng(n.max(2), 2, INDEPENDENCE, rng))
+            .collect();
+
+        Self {
+            n,
+            epsilon,
+            delta,
+            group_size,
+            signs,
+            counters: vec![0; groups * group_size],
+        }
+    }
+
+    /// Feed a token `(j, c)`, an insertion of `j` if `c` is true, a deletion otherwise
+    pub fn feed(&mut self, (j, c): (u64, bool)) {
+        self.update(j, if c { 1 } else { -1 });
+    }
+
+    /// Add `delta` to the frequency of `j`
+    pub fn update(&mut self, j: u64, delta: i64) {
+        if j >= self.n {
+            panic!("[TugOfWar] {} is outside of the universe [{}]", j, self.n);
+        }
+
+        for (sign, counter) in self.signs.iter().zip(self.counters.iter_mut()) {
+            if sign.is_zero(j) {
+                *counter += delta;
+            } else {
+                *counter -= delta;
+            }
+        }
+    }
+
+    /// The estimate of `F_2`, without consuming the estimator
+    pub fn estimate(&self) -> f64 {
+        let mut averages: Vec<f64> = self
+            .counters
+            .chunks(self.group_size)
+            .map(|group| group.iter().map(|z| (z * z) as f64).sum::<f64>() / self.group_size as f64)
+            .collect();
+        averages.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+        averages[averages.len() / 2]
+    }
+
+    pub fn query(self) -> f64 {
+        self.estimate()
+    }
+}
+
+impl Sketch for TugOfWar {
+    fn feed(&mut self, token: (u64, bool)) {
+        TugOfWar::feed(self, token)
+    }
+}
+
+impl StreamProcessor<(u64, bool), f64> for TugOfWar {
+    fn feed(&mut self, token: (u64, bool)) {
+        TugOfWar::feed(self, token)
+    }
+
+    fn query(self) -> f64 {
+        TugOfWar::query(self)
+    }
+}
+
+impl Merge for TugOfWar {
+    /// The counters are linear in the stream, so they add up
+    fn merge(&mut self, other: &Self) {
+        if self.signs != other.signs {
+            panic!("[TugOfWar] Cannot merge estimators with different signs");
+        }
+
+        self.counters
+            .iter_mut()
+            .zip(other.counters.iter())
+            .for_each(|(counter, other)| *counter += other);
+    }
+}
+
+impl SpaceUsage for TugOfWar {
+    /// One counter, and one hash function, per estimator
+    fn space(&self) -> SpaceReport {
+        let count = self.counters.len();
+
+        SpaceReport {
+            structure: "TugOfWar (AMS)",
+            bound: "O(log(1/δ)/ε² · (log m + log n))",
+            parameters: vec![
+                ("n", self.n as f64),
+                ("estimators", count as f64),
+                ("group", self.group_size as f64),
+            ],
+            theoretical_bits: count as f64 * (log(self.n as f64) + INDEPENDENCE as f64 * 61.0),
+            measured_bytes: size_of::<Self>()
+                + count
+                    * (size_of::<i64>()
+                        + size_of::<PolynomialHasher>()
+                        + INDEPENDENCE * size_of::<u64>()),
+            components: vec![],
+        }
+    }
+}
+
+impl Provenance for TugOfWar {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "TugOfWar (AMS)",
+            paper_ref: "Alon, Matias, Szegedy, JCSS 1999, https://doi.org/10.1006/jcss.1997.1545",
+            params: vec![("n", self.n as f64), ("ε", self.epsilon), ("δ", self.delta)],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+
+    #[test]
+    fn second_moment() {
+        let n = 500;
+        let epsilon = 0.2;
+
+        for seed in 0..5 {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            let mut estimator = TugOfWar::init_with_rng(n, epsilon, 0.05, &mut rng);
+
+            // A skewed stream, with deletions: f_j = 1 for most j, and a few large frequencies
+            for j in 0..n {
+                estimator.feed((j, true));
+                if j % 50 == 0 {
+                    estimator.update(j, 20);
+                    estimator.feed((j, false));
+                }
+            }
+            let f2 = (0..n)
+                .map(|j| if j % 50 == 0 { 400 } else { 1 })
+                .sum::<i64>() as f64;
+
+            let estimate = estimator.query();
+            assert!(
+                (estimate - f2).abs() <= epsilon * f2,
+                "seed {}: {} against {}",
+                seed,
+                estimate,
+                f2
+            );
+        }
+    }
+
+    #[test]
+    fn merge() {
+        let base = TugOfWar::init(100, 0.5, 0.1);
+        let (mut left, mut right, mut whole) = (base.clone(), base.clone(), base);
+
+        for j in 0..100 {
+            whole.feed((j, true));
+            if j % 2 == 0 {
+                left.feed((j, true));
+            } else {
+                right.feed((j, true));
+            }
+        }
+        left.merge(&right);
+
+        assert_eq!(left.estimate(), whole.estimate());
+    }
+}
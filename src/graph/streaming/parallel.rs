@@ -0,0 +1,256 @@
+//! Sharded Stream Ingestion
+//!
+//! A single thread feeding a sketch falls far behind the disk on the largest datasets. The linear sketches of this crate can
+//! instead be fed any partition of the stream, one part per thread, and [Merge]d at the end, as long as every part is fed to a
+//! clone of the same unfed sketch, so that all of them share their hash functions and seeds.
+//!
+//! [ShardedIngest] does just that: every worker thread owns a clone of the base sketch, the tokens are handed to the workers in
+//! batches, in turn, and the query merges the clones before querying the result. The thread feeding the [ShardedIngest], e.g.
+//! reading an [EdgeStreamReader](super::io::EdgeStreamReader), only parses the stream and fills the batches.
+
+use std::{
+    mem::take,
+    panic::resume_unwind,
+    sync::mpsc::{sync_channel, SyncSender},
+    thread::{self, JoinHandle},
+};
+
+use super::{Merge, StreamProcessor};
+
+/// Parameters of a [ShardedIngest]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShardParameters {
+    /// Number of worker threads, each feeding a clone of the sketch
+    pub shards: usize,
+    /// Number of tokens handed to a worker at once
+    pub batch: usize,
+    /// Number of batches waiting for each worker before the feeding thread blocks, which bounds the memory of the queues
+    pub queue: usize,
+}
+
+impl Default for ShardParameters {
+    /// A shard per available core
+    fn default() -> Self {
+        Self {
+            shards: thread::available_parallelism().map_or(1, |n| n.get()),
+            batch: 4096,
+            queue: 4,
+        }
+    }
+}
+
+impl ShardParameters {
+    pub fn with_shards(self, shards: usize) -> Self {
+        Self { shards, ..self }
+    }
+
+    pub fn with_batch(self, batch: usize) -> Self {
+        Self { batch, ..self }
+    }
+}
+
+/// A worker thread, fed batches through its channel, that returns its sketch once the channel closes
+struct Shard<S, T> {
+    sender: SyncSender<Vec<T>>,
+    worker: JoinHandle<S>,
+}
+
+/// A sketch fed by several threads, each feeding a clone of it a share of the stream
+///
+/// The clones are merged by the query, so the sketch must be unfed when the ingest starts. Tokens are spread across the shards
+/// in no particular order: sketches whose output depends on the order of their stream, or on which tokens end up together,
+/// e.g. the colorers, will not give the output of a single pass.
+pub struct ShardedIngest<S, T> {
+    shards: Vec<Shard<S, T>>,
+    /// Tokens not handed to a worker yet
+    buffer: Vec<T>,
+    batch: usize,
+    /// Shard receiving the next batch
+    next: usize,
+}
+
+impl<S, T> ShardedIngest<S, T>
+where
+    S: Merge + Clone + Send + 'static,
+    T: Send + 'static,
+{
+    /// Start the workers, each with a clone of the unfed sketch
+    ///
+    /// Panics if there are no shards, or if the batches are empty.
+    pub fn init<Q>(base: S, parameters: ShardParameters) -> Self
+    where
+        S: StreamProcessor<T, Q>,
+    {
+        let ShardParameters {
+            shards,
+            batch,
+            queue,
+        } = parameters;
+        if shards == 0 || batch == 0 {
+            panic!(
+                "[ShardedIngest] There must be at least one shard, and one token per batch, not {} and {}",
+                shards, batch
+            );
+        }
+
+        let shards = (0..shards)
+            .map(|_| {
+                let (sender, receiver) = sync_channel::<Vec<T>>(queue);
+                let mut sketch = base.clone();
+                let worker = thread::spawn(move || {
+                    for tokens in receiver {
+                        tokens.into_iter().for_each(|token| sketch.feed(token));
+                    }
+                    sketch
+                });
+
+                Shard { sender, worker }
+            })
+            .collect();
+
+        Self {
+            shards,
+            buffer: Vec::with_capacity(batch),
+            batch,
+            next: 0,
+        }
+    }
+
+    /// Number of worker threads
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Queue the token, handing the batch to the next worker once it is full
+    ///
+    /// Blocks while the queue of that worker is full.
+    pub fn feed(&mut self, token: T) {
+        self.buffer.push(token);
+        if self.buffer.len() == self.batch {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+
+        let tokens = take(&mut self.buffer);
+        self.buffer.reserve(self.batch);
+
+        if self.shards[self.next].sender.send(tokens).is_err() {
+            // The worker only hangs up by panicking, which joining it reports
+            let Shard { worker, .. } = self.shards.swap_remove(self.next);
+            let panic = worker
+                .join()
+                .err()
+                .expect("[ShardedIngest] A worker stopped before the end of the stream");
+            resume_unwind(panic);
+        }
+        self.next = (self.next + 1) % self.shards.len();
+    }
+
+    /// Wait for the workers to feed their last batch, and merge their sketches
+    ///
+    /// A panic within a worker is resumed on the calling thread.
+    pub fn finish(mut self) -> S {
+        self.flush();
+
+        let mut sketches = self.shards.into_iter().map(|Shard { sender, worker }| {
+            drop(sender);
+            worker.join().unwrap_or_else(|panic| resume_unwind(panic))
+        });
+
+        let mut merged = sketches
+            .next()
+            .expect("[ShardedIngest] There is at least one shard");
+        sketches.for_each(|sketch| merged.merge(&sketch));
+        merged
+    }
+}
+
+impl<S, T, Q> StreamProcessor<T, Q> for ShardedIngest<S, T>
+where
+    S: StreamProcessor<T, Q> + Merge + Clone + Send + 'static,
+    T: Send + 'static,
+{
+    fn feed(&mut self, token: T) {
+        ShardedIngest::feed(self, token)
+    }
+
+    fn query(self) -> Q {
+        self.finish().query()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        graph::{
+            streaming::{
+                connectivity::ComponentCounter, sparse_recovery::s_sparse::SparseRecovery,
+            },
+            Edge, VertexId,
+        },
+        utils::hash_function::PowerFiniteFieldHasher,
+    };
+
+    fn parameters(shards: usize) -> ShardParameters {
+        ShardParameters::default().with_shards(shards).with_batch(3)
+    }
+
+    #[test]
+    fn same_as_one_pass() {
+        let base: SparseRecovery<PowerFiniteFieldHasher> = SparseRecovery::init(1000, 20, 0.01);
+        let stream: Vec<(u64, bool)> = (0..200)
+            .map(|i| (i * 7 % 1000, true))
+            .chain((10..200).map(|i| (i * 7 % 1000, false)))
+            .collect();
+
+        let mut whole = base.clone();
+        stream.iter().for_each(|token| whole.feed(*token));
+
+        for shards in [1, 2, 5] {
+            let mut ingest = ShardedIngest::init(base.clone(), parameters(shards));
+            stream.iter().for_each(|token| ingest.feed(*token));
+
+            let sharded = ingest.finish();
+            assert_eq!(
+                sharded.query_ref().support(),
+                whole.query_ref().support(),
+                "{} shards",
+                shards
+            );
+        }
+    }
+
+    #[test]
+    fn components() {
+        // Two paths, on the even and on the odd vertices
+        let n = 60;
+        let stream: Vec<Edge<VertexId, ()>> = (0..n - 2).map(|v| Edge::init(v, v + 2)).collect();
+
+        let mut ingest = ShardedIngest::init(ComponentCounter::init(n), parameters(4));
+        assert_eq!(ingest.shards(), 4);
+        stream.into_iter().for_each(|token| ingest.feed(token));
+
+        assert_eq!(StreamProcessor::query(ingest), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn worker_panic() {
+        let mut ingest = ShardedIngest::init(ComponentCounter::init(10), parameters(2));
+        ingest.feed(Edge::init(0, 20));
+        ingest.finish();
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_shards() {
+        let base: SparseRecovery<PowerFiniteFieldHasher> = SparseRecovery::init(10, 2, 0.01);
+        ShardedIngest::<_, (u64, bool)>::init(base, parameters(0));
+    }
+}
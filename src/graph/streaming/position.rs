@@ -0,0 +1,207 @@
+//! Stream Positions
+//!
+//! A sketch queried in the middle of a live stream summarizes some prefix of it. Wrapping the sketch in [Positioned]
+//! records the position of the last token it was fed, so every query output can report which prefix it is "as of".
+//! When several sketches are queried, comparing those positions tells whether the outputs form a consistent snapshot.
+
+use super::{Sketch, StreamProcessor};
+
+/// Position of a token within its stream
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamPosition {
+    /// Number of tokens preceding this one in the stream
+    pub sequence: u64,
+    /// Time at which the token arrived, in units chosen by the driver of the stream
+    pub timestamp: Option<u64>,
+}
+
+impl StreamPosition {
+    /// The position of the token following this one
+    pub fn next(&self) -> Self {
+        Self {
+            sequence: self.sequence + 1,
+            timestamp: None,
+        }
+    }
+}
+
+/// The output of a query, along with the position of the last token the sketch had seen
+///
+/// The position is `None` if the sketch was never fed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot<Q> {
+    pub output: Q,
+    pub position: Option<StreamPosition>,
+}
+
+impl<Q> Snapshot<Q> {
+    /// Whether both snapshots summarize the same prefix of the stream
+    pub fn consistent_with<R>(&self, other: &Snapshot<R>) -> bool {
+        self.position.map(|p| p.sequence) == other.position.map(|p| p.sequence)
+    }
+}
+
+/// A sketch that keeps track of its position within the stream
+#[derive(Clone, Debug)]
+pub struct Positioned<S> {
+    inner: S,
+    position: Option<StreamPosition>,
+}
+
+impl<S> Positioned<S>
+where
+    S: Sketch,
+{
+    pub fn init(inner: S) -> Self {
+        Self {
+            inner,
+            position: None,
+        }
+    }
+
+    /// Feed a token, along with its position in the stream
+    ///
+    /// Panics if the position is before that of a token already fed.
+    pub fn feed_at(&mut self, token: (u64, bool), position: StreamPosition) {
+        if let Some(last) = self.position {
+            if position.sequence < last.sequence {
+                panic!(
+                    "[Positioned] Token #{} arrived after token #{}",
+                    position.sequence, last.sequence
+                );
+            }
+        }
+
+        self.inner.feed(token);
+        self.position = Some(position);
+    }
+
+    /// Feed the token following the last one fed, without a timestamp
+    pub fn feed(&mut self, token: (u64, bool)) {
+        let position = self.position.map(|p| p.next()).unwrap_or_default();
+        self.feed_at(token, position);
+    }
+
+    /// Position of the last token fed, `None` if no token has been fed
+    pub fn position(&self) -> Option<StreamPosition> {
+        self.position
+    }
+
+    /// The underlying sketch
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Query the sketch without consuming it, so that the stream may continue
+    pub fn snapshot<Q>(&self) -> Snapshot<Q>
+    where
+        S: StreamProcessor<(u64, bool), Q> + Clone,
+    {
+        Snapshot {
+            output: self.inner.clone().query(),
+            position: self.position,
+        }
+    }
+}
+
+impl<S> Sketch for Positioned<S>
+where
+    S: Sketch,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        Positioned::feed(self, token)
+    }
+}
+
+impl<S, Q> StreamProcessor<(u64, bool), Snapshot<Q>> for Positioned<S>
+where
+    S: Sketch + StreamProcessor<(u64, bool), Q>,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        Positioned::feed(self, token)
+    }
+
+    fn query(self) -> Snapshot<Q> {
+        Snapshot {
+            output: self.inner.query(),
+            position: self.position,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        graph::streaming::sparse_recovery::{s_sparse::SparseRecovery, SparseRecoveryOutput},
+        utils::hash_function::PowerFiniteFieldHasher,
+    };
+
+    type Output = SparseRecoveryOutput<HashMap<u64, i64>>;
+
+    fn sketch() -> Positioned<SparseRecovery<PowerFiniteFieldHasher>> {
+        Positioned::init(SparseRecovery::init(20, 5, 0.01))
+    }
+
+    #[test]
+    fn tracks_position() {
+        let mut sketch = sketch();
+        assert_eq!(sketch.position(), None);
+
+        sketch.feed((3, true));
+        sketch.feed((4, true));
+        assert_eq!(sketch.position().map(|p| p.sequence), Some(1));
+
+        sketch.feed_at(
+            (4, false),
+            StreamPosition {
+                sequence: 10,
+                timestamp: Some(1_000),
+            },
+        );
+
+        let snapshot: Snapshot<Output> = sketch.query();
+        assert_eq!(
+            snapshot.position,
+            Some(StreamPosition {
+                sequence: 10,
+                timestamp: Some(1_000)
+            })
+        );
+        assert!(matches!(
+            snapshot.output,
+            SparseRecoveryOutput::Recovered(support) if support.keys().eq([3].iter())
+        ));
+    }
+
+    #[test]
+    fn consistent_snapshots() {
+        let (mut a, mut b) = (sketch(), sketch());
+
+        a.feed((1, true));
+        b.feed((2, true));
+        let (first_a, first_b): (Snapshot<Output>, Snapshot<Output>) = (a.snapshot(), b.snapshot());
+        assert!(first_a.consistent_with(&first_b));
+
+        a.feed((3, true));
+        let second_a: Snapshot<Output> = a.snapshot();
+        assert!(!second_a.consistent_with(&first_b));
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_order() {
+        let mut sketch = sketch();
+
+        sketch.feed_at(
+            (1, true),
+            StreamPosition {
+                sequence: 5,
+                timestamp: None,
+            },
+        );
+        sketch.feed_at((2, true), StreamPosition::default());
+    }
+}
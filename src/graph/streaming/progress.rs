@@ -0,0 +1,361 @@
+//! Stream Progress
+//!
+//! Feeding millions of edges into a handful of structures takes minutes, with nothing to show for it until the query. Wrapping a
+//! structure in [Monitored] counts the tokens it is fed and the time it spends on them, and reports that [Progress] to a
+//! [ProgressObserver] every so many tokens, and once more after the query, e.g. to drive a progress bar. Structures implementing
+//! [SpaceUsage] can also report the bytes they hold, measured at each report.
+//!
+//! Like the [GraphObserver](crate::graph::observer::GraphObserver)s, a pair of observers is an observer, and so is a closure, a
+//! `Vec` recording every report, and the sending half of a channel of [Progress].
+
+use std::{
+    sync::mpsc::Sender,
+    time::{Duration, Instant},
+};
+
+use log::info;
+
+use super::{
+    space::{human_bytes, SpaceUsage},
+    StreamProcessor,
+};
+
+/// How far a structure is through its stream
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    /// Label of the structure, to tell apart the reports of several structures sharing an observer
+    pub label: &'static str,
+    /// Number of tokens fed so far
+    pub tokens: u64,
+    /// Time spent within the feed of the structure
+    pub feeding: Duration,
+    /// Time since the first token was fed, including the time spent outside of the structure
+    pub elapsed: Duration,
+    /// Bytes held by the structure, if it was monitored with [Monitored::with_memory]
+    pub measured_bytes: Option<usize>,
+}
+
+impl Progress {
+    /// Tokens fed per second of feeding, `0.0` before the first token
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.feeding.as_secs_f64();
+        if seconds > 0.0 {
+            self.tokens as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// The fraction of a stream of `total` tokens fed so far, for progress bars
+    pub fn fraction(&self, total: u64) -> f64 {
+        if total == 0 {
+            1.0
+        } else {
+            (self.tokens as f64 / total as f64).min(1.0)
+        }
+    }
+}
+
+/// Callbacks run on the progress of a [Monitored] structure, every one of them does nothing by default
+pub trait ProgressObserver {
+    /// Another interval of tokens was fed
+    fn on_progress(&mut self, _progress: &Progress) {}
+
+    /// The structure was queried, after its last token
+    fn on_finish(&mut self, _progress: &Progress) {}
+}
+
+/// Observes nothing
+impl ProgressObserver for () {}
+
+/// Both observers see every report, the first one first
+impl<A, B> ProgressObserver for (A, B)
+where
+    A: ProgressObserver,
+    B: ProgressObserver,
+{
+    fn on_progress(&mut self, progress: &Progress) {
+        self.0.on_progress(progress);
+        self.1.on_progress(progress);
+    }
+
+    fn on_finish(&mut self, progress: &Progress) {
+        self.0.on_finish(progress);
+        self.1.on_finish(progress);
+    }
+}
+
+/// Records every report, in order
+impl ProgressObserver for Vec<Progress> {
+    fn on_progress(&mut self, progress: &Progress) {
+        self.push(*progress);
+    }
+
+    fn on_finish(&mut self, progress: &Progress) {
+        self.push(*progress);
+    }
+}
+
+/// Sends every report to the receiving half of the channel, e.g. held by the thread drawing the progress bar
+impl ProgressObserver for Sender<Progress> {
+    fn on_progress(&mut self, progress: &Progress) {
+        self.send(*progress).ok();
+    }
+
+    fn on_finish(&mut self, progress: &Progress) {
+        self.send(*progress).ok();
+    }
+}
+
+/// Runs the closure on every report, the last one included
+impl<F> ProgressObserver for F
+where
+    F: FnMut(&Progress),
+{
+    fn on_progress(&mut self, progress: &Progress) {
+        self(progress)
+    }
+
+    fn on_finish(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+/// Logs every report at the info level, through the [log] facade
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogProgress;
+
+impl ProgressObserver for LogProgress {
+    fn on_progress(&mut self, progress: &Progress) {
+        info!(
+            "[{}] {} tokens, {:.0} tokens/s{}",
+            progress.label,
+            progress.tokens,
+            progress.throughput(),
+            progress
+                .measured_bytes
+                .map(|bytes| format!(", {}", human_bytes(bytes as f64)))
+                .unwrap_or_default()
+        );
+    }
+
+    fn on_finish(&mut self, progress: &Progress) {
+        info!(
+            "[{}] Queried after {} tokens, {:?} feeding, {:?} elapsed",
+            progress.label, progress.tokens, progress.feeding, progress.elapsed
+        );
+    }
+}
+
+/// A structure that reports its progress through the stream to an observer
+///
+/// Timing each token costs two reads of the clock, negligible next to the sketches of this crate, but not next to a counter.
+#[derive(Clone, Debug)]
+pub struct Monitored<S, O> {
+    inner: S,
+    observer: O,
+    label: &'static str,
+    /// Number of tokens between two reports
+    interval: u64,
+    tokens: u64,
+    /// Number of tokens since the last report
+    pending: u64,
+    feeding: Duration,
+    started: Option<Instant>,
+    memory: Option<fn(&S) -> usize>,
+}
+
+impl<S, O> Monitored<S, O>
+where
+    O: ProgressObserver,
+{
+    /// Monitor the structure, reporting every 100 000 tokens
+    pub fn init(inner: S, observer: O) -> Self {
+        Self {
+            inner,
+            observer,
+            label: "stream",
+            interval: 100_000,
+            tokens: 0,
+            pending: 0,
+            feeding: Duration::ZERO,
+            started: None,
+            memory: None,
+        }
+    }
+
+    pub fn with_label(self, label: &'static str) -> Self {
+        Self { label, ..self }
+    }
+
+    /// Report every `interval` tokens, panics if it is 0
+    pub fn with_interval(self, interval: u64) -> Self {
+        if interval == 0 {
+            panic!("[Monitored] The interval between two reports must be positive");
+        }
+        Self { interval, ..self }
+    }
+
+    /// Measure the bytes held by the structure at every report
+    ///
+    /// A [SpaceReport](super::space::SpaceReport) walks the whole structure, so the interval should not be too short.
+    pub fn with_memory(self) -> Self
+    where
+        S: SpaceUsage,
+    {
+        Self {
+            memory: Some(|inner: &S| inner.space().measured_bytes),
+            ..self
+        }
+    }
+
+    /// The progress so far
+    pub fn progress(&self) -> Progress {
+        Progress {
+            label: self.label,
+            tokens: self.tokens,
+            feeding: self.feeding,
+            elapsed: self.started.map(|s| s.elapsed()).unwrap_or_default(),
+            measured_bytes: self.memory.map(|memory| memory(&self.inner)),
+        }
+    }
+
+    /// The underlying structure
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// The structure and the observer, without a last report
+    pub fn into_parts(self) -> (S, O) {
+        (self.inner, self.observer)
+    }
+
+    /// Feed a token to the structure, reporting the progress at the end of an interval
+    fn feed_with<F>(&mut self, feed: F)
+    where
+        F: FnOnce(&mut S),
+    {
+        let start = Instant::now();
+        self.started.get_or_insert(start);
+
+        feed(&mut self.inner);
+
+        self.feeding += start.elapsed();
+        self.tokens += 1;
+        self.pending += 1;
+        if self.pending == self.interval {
+            self.pending = 0;
+            let progress = self.progress();
+            self.observer.on_progress(&progress);
+        }
+    }
+}
+
+impl<S, O, T, Q> StreamProcessor<T, Q> for Monitored<S, O>
+where
+    S: StreamProcessor<T, Q>,
+    O: ProgressObserver,
+{
+    fn feed(&mut self, token: T) {
+        self.feed_with(|inner| inner.feed(token))
+    }
+
+    /// Query the structure, and report the progress once more, the time of the query counted as feeding
+    fn query(self) -> Q {
+        let mut progress = self.progress();
+        let Self {
+            inner,
+            mut observer,
+            ..
+        } = self;
+
+        let start = Instant::now();
+        let output = inner.query();
+        progress.feeding += start.elapsed();
+        progress.elapsed += start.elapsed();
+
+        observer.on_finish(&progress);
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc::channel;
+
+    use super::*;
+    use crate::{
+        graph::{
+            streaming::{coloring::bcg::StreamColoring, connectivity::ForestSketch},
+            Edge, VertexId,
+        },
+        utils::hash_function::PowerFiniteFieldHasher,
+    };
+
+    fn path(n: VertexId) -> Vec<(Edge<VertexId, ()>, bool)> {
+        (0..n - 1).map(|v| (Edge::init(v, v + 1), true)).collect()
+    }
+
+    #[test]
+    fn reports_every_interval() {
+        let forest: ForestSketch<PowerFiniteFieldHasher> = ForestSketch::init(10);
+        let mut monitored = Monitored::init(forest, vec![])
+            .with_label("forest")
+            .with_interval(4);
+        path(10).into_iter().for_each(|token| monitored.feed(token));
+
+        let reports = monitored.observer().clone();
+        assert_eq!(reports.iter().map(|p| p.tokens).collect::<Vec<_>>(), [4, 8]);
+        assert!(reports.iter().all(|p| p.label == "forest"));
+        assert!(reports.iter().all(|p| p.measured_bytes.is_none()));
+        assert!(reports[0].feeding <= reports[1].feeding);
+        assert!(reports[1].feeding <= reports[1].elapsed);
+
+        let forest: Vec<Edge<VertexId, ()>> = monitored.query();
+        assert_eq!(forest.len(), 9);
+    }
+
+    #[test]
+    fn reports_memory() {
+        let colorer: StreamColoring = StreamColoring::init(20, 2, 0.01);
+        let (sender, receiver) = channel();
+        let mut monitored = Monitored::init(colorer, sender)
+            .with_interval(5)
+            .with_memory();
+        path(20).into_iter().for_each(|token| monitored.feed(token));
+        monitored.query();
+
+        let reports: Vec<Progress> = receiver.iter().collect();
+        assert_eq!(
+            reports.iter().map(|p| p.tokens).collect::<Vec<_>>(),
+            [5, 10, 15, 19]
+        );
+        assert!(reports.iter().all(|p| p.measured_bytes.unwrap() > 0));
+        assert!(reports[3].fraction(19) == 1.0 && reports[0].fraction(20) == 0.25);
+    }
+
+    #[test]
+    fn combined_observers() {
+        let mut finished = 0;
+        {
+            let forest: ForestSketch<PowerFiniteFieldHasher> = ForestSketch::init(5);
+            let observer = (vec![], |p: &Progress| finished = p.tokens);
+            let mut monitored = Monitored::init(forest, observer).with_interval(1);
+            path(5).into_iter().for_each(|token| monitored.feed(token));
+
+            assert_eq!(monitored.observer().0.len(), 4);
+            let _: Vec<Edge<VertexId, ()>> = monitored.query();
+        }
+        assert_eq!(finished, 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_interval() {
+        Monitored::init((), ()).with_interval(0);
+    }
+}
@@ -0,0 +1,118 @@
+//! Algorithm Provenance
+//!
+//! Results of experiments get shared, and a number without the algorithm, paper and parameters that produced it cannot be
+//! reproduced. Every research grade structure implements [Provenance], and its output can be wrapped in a [Documented] that carries
+//! the [AlgorithmInfo] along, e.g.
+//!
+//! ```text
+//! StreamColoring (BCG) [Bera, Chakrabarti, Ghosh, ICALP 2020, https://arxiv.org/abs/1905.00566] n=4039, k=20, palette=3; seed unknown
+//! ```
+
+use std::fmt::Display;
+
+use super::StreamProcessor;
+
+/// Where a result comes from
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlgorithmInfo {
+    /// Name of the structure, and of the variant if several implement the same problem
+    pub name: &'static str,
+    /// Authors, venue and link of the paper the algorithm follows
+    pub paper_ref: &'static str,
+    /// Parameters the structure was initialized with
+    pub params: Vec<(&'static str, f64)>,
+    /// Seed of the randomness, `None` if it was drawn from the thread rng, and the run cannot be replayed
+    pub seed: Option<u64>,
+}
+
+impl Display for AlgorithmInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]", self.name, self.paper_ref)?;
+        for (i, (name, value)) in self.params.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(f, "{}{}={}", separator, name, value)?;
+        }
+        match self.seed {
+            Some(seed) => write!(f, "; seed {}", seed),
+            None => write!(f, "; seed unknown"),
+        }
+    }
+}
+
+/// A structure that can describe the algorithm it implements
+pub trait Provenance {
+    fn info(&self) -> AlgorithmInfo;
+
+    /// Query the structure, keeping its description along with the output
+    fn query_documented<T, Q>(self) -> Documented<Q>
+    where
+        Self: StreamProcessor<T, Q> + Sized,
+    {
+        let info = self.info();
+        Documented {
+            info,
+            output: self.query(),
+        }
+    }
+}
+
+/// The output of a structure, along with where it comes from
+#[derive(Clone, Debug, PartialEq)]
+pub struct Documented<T> {
+    pub info: AlgorithmInfo,
+    pub output: T,
+}
+
+impl<T> Documented<T> {
+    pub fn init(info: AlgorithmInfo, output: T) -> Self {
+        Self { info, output }
+    }
+
+    /// Transform the output, keeping its description
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Documented<U> {
+        Documented {
+            info: self.info,
+            output: f(self.output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        graph::{
+            streaming::sparse_recovery::{s_sparse::SparseRecovery, SparseRecoveryOutput},
+            Edge,
+        },
+        utils::hash_function::PowerFiniteFieldHasher,
+    };
+
+    #[test]
+    fn display() {
+        let info = AlgorithmInfo {
+            name: "Example",
+            paper_ref: "Nobody, 2021",
+            params: vec![("n", 10.0), ("ε", 0.5)],
+            seed: Some(7),
+        };
+
+        assert_eq!(
+            info.to_string(),
+            "Example [Nobody, 2021] n=10, ε=0.5; seed 7"
+        );
+    }
+
+    #[test]
+    fn documented_query() {
+        let mut recovery = SparseRecovery::<PowerFiniteFieldHasher>::init(100, 10, 0.01);
+        recovery.feed((Edge::<u64, ()>::init(1, 2).to_d1(), true));
+
+        let documented: Documented<SparseRecoveryOutput<_>> = recovery.query_documented();
+        assert_eq!(documented.info.name, "SparseRecovery");
+        assert_eq!(documented.info.params[..2], [("n", 100.0), ("s", 10.0)]);
+
+        let support = documented.map(|output| output.unwrap_or_default());
+        assert_eq!(support.output.len(), 1);
+    }
+}
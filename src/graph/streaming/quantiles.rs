@@ -0,0 +1,244 @@
+//! Quantile Summaries
+//!
+//! The summary of [Greenwald and Khanna](https://doi.org/10.1145/375663.375670) answers quantile queries over an insertion only
+//! stream of `N` values, within a rank error of `εN`, from `O(log(εN) / ε)` tuples. Each tuple `(v, g, Δ)` stores a value `v`
+//! of the stream, the gap `g` between the smallest rank of `v` and that of the previous tuple, and the uncertainty `Δ` on its
+//! rank, so that the rank of `v` lies within `[Σ g, Σ g + Δ]`, the sum running over the tuples up to `v`. Every `1 / 2ε`
+//! insertions, neighbouring tuples are merged as long as the uncertainty of the merged tuple stays below `2εN`.
+//!
+//! Fed the estimated degree of every vertex, e.g. by [DegreeSketch::degree_quantiles](super::frequency::DegreeSketch::degree_quantiles),
+//! the summary gives the percentiles of the degree distribution of a streamed graph, without storing the degrees.
+
+use std::mem::size_of;
+
+use super::{
+    provenance::{AlgorithmInfo, Provenance},
+    space::{log, SpaceReport, SpaceUsage},
+    StreamProcessor,
+};
+
+/// A value of the stream, with the gap to the smallest rank of the previous tuple and the uncertainty on its rank
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Tuple {
+    value: u64,
+    g: u64,
+    delta: u64,
+}
+
+/// Approximate quantiles of an insertion only stream of values
+///
+/// Storage: O(log(εN) / ε) tuples, independent of the universe of the values
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GreenwaldKhanna {
+    epsilon: f64,
+    /// Sorted by value
+    tuples: Vec<Tuple>,
+    /// Length of the stream
+    count: u64,
+    /// Number of insertions since the last compression
+    pending: u64,
+}
+
+impl GreenwaldKhanna {
+    /// Initialize a summary whose quantiles are within `εN` of their rank
+    pub fn init(epsilon: f64) -> Self {
+        if !(epsilon > 0.0 && epsilon < 1.0) {
+            panic!("[GreenwaldKhanna] ε must be within (0, 1), not {}", epsilon);
+        }
+
+        Self {
+            epsilon,
+            tuples: vec![],
+            count: 0,
+            pending: 0,
+        }
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    /// Length of the stream
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Number of tuples of the summary
+    pub fn len(&self) -> usize {
+        self.tuples.len()
+    }
+
+    /// Whether the stream is empty
+    pub fn is_empty(&self) -> bool {
+        self.tuples.is_empty()
+    }
+
+    /// Largest uncertainty of a tuple, `2εN`
+    fn capacity(&self) -> u64 {
+        (2.0 * self.epsilon * self.count as f64).floor() as u64
+    }
+
+    /// Feed the next value of the stream
+    pub fn feed(&mut self, value: u64) {
+        let position = self.tuples.partition_point(|t| t.value <= value);
+        // The smallest and largest values so far have an exact rank
+        let delta = if position == 0 || position == self.tuples.len() {
+            0
+        } else {
+            self.capacity()
+        };
+        self.tuples.insert(position, Tuple { value, g: 1, delta });
+        self.count += 1;
+
+        self.pending += 1;
+        if self.pending as f64 >= 1.0 / (2.0 * self.epsilon) {
+            self.pending = 0;
+            self.compress();
+        }
+    }
+
+    /// Merge every tuple into the next one, from the largest values down, as long as the uncertainty stays within `2εN`
+    ///
+    /// The first and last tuples are never merged away, so that the extremes remain exact.
+    fn compress(&mut self) {
+        let capacity = self.capacity();
+        let mut i = self.tuples.len().saturating_sub(2);
+        while i >= 1 {
+            let (current, next) = (self.tuples[i], self.tuples[i + 1]);
+            if current.g + next.g + next.delta <= capacity {
+                self.tuples[i + 1].g += current.g;
+                self.tuples.remove(i);
+            }
+            i -= 1;
+        }
+    }
+
+    /// A value of the stream whose rank is within `εN` of `⌈φN⌉`, `None` if the stream is empty
+    ///
+    /// `φ` is clamped to `[0, 1]`, `0` gives the smallest value and `1` the largest.
+    pub fn quantile(&self, phi: f64) -> Option<u64> {
+        let n = self.count as f64;
+        let bound = (phi.clamp(0.0, 1.0) * n).ceil() + self.epsilon * n;
+
+        let mut rank = 0;
+        let mut previous = self.tuples.first()?.value;
+        for tuple in self.tuples.iter() {
+            rank += tuple.g;
+            if (rank + tuple.delta) as f64 > bound {
+                return Some(previous);
+            }
+            previous = tuple.value;
+        }
+        Some(previous)
+    }
+
+    /// Every value of the summary with the smallest rank it may have, an approximate cumulative distribution of the stream
+    pub fn query(self) -> Vec<(u64, u64)> {
+        self.tuples
+            .iter()
+            .scan(0, |rank, tuple| {
+                *rank += tuple.g;
+                Some((tuple.value, *rank))
+            })
+            .collect()
+    }
+}
+
+impl StreamProcessor<u64, Vec<(u64, u64)>> for GreenwaldKhanna {
+    fn feed(&mut self, value: u64) {
+        GreenwaldKhanna::feed(self, value)
+    }
+
+    fn query(self) -> Vec<(u64, u64)> {
+        GreenwaldKhanna::query(self)
+    }
+}
+
+impl SpaceUsage for GreenwaldKhanna {
+    /// A value and two ranks per tuple
+    fn space(&self) -> SpaceReport {
+        let n = self.count as f64;
+
+        SpaceReport {
+            structure: "GreenwaldKhanna",
+            bound: "O(log(εN) / ε)",
+            parameters: vec![("ε", self.epsilon), ("N", n)],
+            theoretical_bits: self.tuples.len() as f64 * (64.0 + 2.0 * log(n)),
+            measured_bytes: size_of::<Self>() + self.tuples.capacity() * size_of::<Tuple>(),
+            components: vec![],
+        }
+    }
+}
+
+impl Provenance for GreenwaldKhanna {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "GreenwaldKhanna",
+            paper_ref: "Greenwald, Khanna, SIGMOD 2001, https://doi.org/10.1145/375663.375670",
+            params: vec![("ε", self.epsilon)],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{seq::SliceRandom, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+
+    #[test]
+    fn rank_error() {
+        let n = 10_000;
+        let mut values: Vec<u64> = (0..n).collect();
+        values.shuffle(&mut ChaCha20Rng::seed_from_u64(5));
+
+        let mut summary = GreenwaldKhanna::init(0.01);
+        values.into_iter().for_each(|v| summary.feed(v));
+        assert_eq!(summary.count(), n);
+        assert!(summary.len() < 1000, "{} tuples", summary.len());
+
+        for i in 0..=20 {
+            let phi = i as f64 / 20.0;
+            let quantile = summary.quantile(phi).unwrap() as f64;
+            // The value v has rank v + 1
+            assert!(
+                (quantile + 1.0 - phi * n as f64).abs() <= 0.01 * n as f64 + 1.0,
+                "{} for φ = {}",
+                quantile,
+                phi
+            );
+        }
+        assert_eq!(summary.quantile(0.0), Some(0));
+        assert_eq!(summary.quantile(1.0), Some(n - 1));
+    }
+
+    #[test]
+    fn repeated_values() {
+        let mut summary = GreenwaldKhanna::init(0.05);
+        (0..300).for_each(|i| summary.feed(if i % 3 == 0 { 7 } else { 2 }));
+
+        assert_eq!(summary.quantile(0.5), Some(2));
+        assert_eq!(summary.quantile(0.9), Some(7));
+
+        let cdf = summary.query();
+        assert_eq!(cdf.last(), Some(&(7, 300)));
+        assert!(cdf.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn empty() {
+        let summary = GreenwaldKhanna::init(0.1);
+        assert!(summary.is_empty());
+        assert_eq!(summary.quantile(0.5), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_epsilon() {
+        GreenwaldKhanna::init(0.0);
+    }
+}
@@ -1,3 +1,4 @@
 //! Sampling
 
 pub mod l0_sampling;
+pub mod lp_sampling;
@@ -1,57 +1,275 @@
-//! L0 Sampling - Broken b/c of hash functions being from [n(prime)] -> [l(=2^k)]
+//! L0 Sampling
+//!
+//! Samples a coordinate uniformly among the nonzero coordinates of a turnstile vector, along with its value. A hash function
+//! `h : [n] -> [2^L]` assigns every coordinate `j` to the levels `0..=tz(h(j))`, `tz` counting trailing zeros, so level `l` holds
+//! each coordinate with probability `2^-l`, and the levels are nested. Each level keeps a one-sparse recovery; at the level where
+//! about one nonzero coordinate survives, the recovery succeeds and returns it.
+//!
+//! With `h` drawn from an `O(log(1/δ))`-wise independent family, the sample is uniform up to a factor `1 ± δ`, and the `log(1/δ)`
+//! independent repetitions fail together with probability at most `δ`.
+//! [Cormode and Firmani](https://doi.org/10.1007/s10619-013-7131-9) survey the variants.
 
-use algebraics::traits::CeilLog2;
+use rand::Rng;
 
-use crate::graph::streaming::sparse_recovery::one_sparse::{
-    OneSparseRecovery, OneSparseRecoveryOutput,
+use std::mem::size_of;
+
+use crate::graph::streaming::{
+    provenance::{AlgorithmInfo, Provenance},
+    space::{log, SpaceReport, SpaceUsage},
+    sparse_recovery::one_sparse::OneSparseRecovery,
+    Merge, StreamProcessor,
 };
 
-use crate::utils::hash_function::{HashFunction, PowerFiniteFieldHasher};
+use crate::utils::{
+    finite_field::FiniteField,
+    hash_function::{HashFunction, PolynomialHasher},
+};
 
+/// One independent copy of the sampler: a hash function, and a one-sparse recovery for each of its levels
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug)]
-pub struct L0Sampler<H>
-where
-    H: HashFunction,
-{
-    inner: Vec<(OneSparseRecovery, H)>,
-}
-
-impl<H> L0Sampler<H>
-where
-    H: HashFunction,
-{
-    pub fn init(n: u64, _delta: f32) -> Self {
-        let mut inner = vec![];
-        let n_pow = n.next_power_of_two();
-
-        for l in 0..n_pow.ceil_log2().unwrap() as u32 {
-            let recover = OneSparseRecovery::init(n_pow);
-            let hash_function = H::init(n_pow, 2_u64.pow(l));
-
-            inner.push((recover, hash_function));
-        }
-        Self { inner }
+struct Repetition {
+    hasher: PolynomialHasher,
+    levels: Vec<OneSparseRecovery>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct L0Sampler {
+    n: u64,
+    delta: f32,
+    inner: Vec<Repetition>,
+}
+
+impl L0Sampler {
+    /// Initialize a sampler over the coordinates `[n]`, that fails with probability at most `delta`
+    pub fn init(n: u64, delta: f32) -> Self {
+        Self::init_with_rng(n, delta, &mut rand::thread_rng())
+    }
+
+    /// Initialize a sampler, drawing its hash functions and fingerprints from the generator
+    pub fn init_with_rng<R: Rng + ?Sized>(n: u64, delta: f32, rng: &mut R) -> Self {
+        let n_pow = n.max(2).next_power_of_two();
+        let levels = n_pow.trailing_zeros() as usize + 1;
+        let rounds = (1.0 / delta as f64).log2().ceil().max(1.0) as usize;
+        let k = rounds.max(2);
+        let order = FiniteField::for_universe(n_pow).order();
+
+        let inner = (0..rounds)
+            .map(|_| Repetition {
+                hasher: PolynomialHasher::init_with_rng(n_pow, n_pow, k, rng),
+                levels: (0..levels)
+                    .map(|_| OneSparseRecovery::init_with_rng(n_pow, order, rng))
+                    .collect(),
+            })
+            .collect();
+
+        Self { n, delta, inner }
     }
 
     pub fn feed(&mut self, token: (u64, bool)) {
         let (j, c) = token;
+        self.feed_weighted((j, if c { 1 } else { -1 }))
+    }
 
-        self.inner.iter_mut().for_each(|(recovery, hasher)| {
-            if hasher.is_zero(j) {
-                recovery.feed((j, c))
-            }
+    /// Add `Δ` to the coordinate `j`, `token = (j, Δ)`, as `|Δ|` tokens of [feed](L0Sampler::feed) would
+    pub fn feed_weighted(&mut self, token: (u64, i64)) {
+        let (j, _) = token;
+
+        self.inner.iter_mut().for_each(|repetition| {
+            let deepest = repetition.hasher.compute(j).trailing_zeros() as usize;
+            repetition
+                .levels
+                .iter_mut()
+                .take(deepest + 1)
+                .for_each(|recovery| recovery.feed_weighted(token));
         })
     }
 
+    /// A nonzero coordinate and its value, `None` if the vector is zero or every repetition failed
     pub fn query(self) -> Option<(u64, i64)> {
-        for (recovery, _) in self.inner {
-            let query = recovery.query();
-            match query {
-                OneSparseRecoveryOutput::VeryLikely(l, i) => return Some((i, l)),
-                _ => continue,
+        self.query_ref()
+    }
+
+    /// [query](L0Sampler::query) the sampler by reference, so that it can be queried mid-stream and fed further
+    pub fn query_ref(&self) -> Option<(u64, i64)> {
+        self.inner.iter().find_map(|repetition| {
+            // The sparsest levels come last
+            repetition
+                .levels
+                .iter()
+                .rev()
+                .find_map(|recovery| recovery.query_ref().recovered())
+        })
+    }
+}
+
+impl Merge for L0Sampler {
+    fn merge(&mut self, other: &Self) {
+        if self.inner.len() != other.inner.len() {
+            panic!("[L0Sampler] Cannot merge samplers over different universes");
+        }
+
+        for (repetition, other) in self.inner.iter_mut().zip(other.inner.iter()) {
+            if repetition.hasher != other.hasher {
+                panic!("[L0Sampler] Cannot merge samplers with different hash functions");
+            }
+            repetition
+                .levels
+                .iter_mut()
+                .zip(other.levels.iter())
+                .for_each(|(recovery, other)| recovery.merge(other));
+        }
+    }
+}
+
+impl SpaceUsage for L0Sampler {
+    /// `log(1/δ)` repetitions of a one-sparse recovery for each of the `log n` levels, and a `log(1/δ)`-wise independent hash function
+    fn space(&self) -> SpaceReport {
+        let rounds = self.inner.len();
+        let levels = self.inner.first().map_or(0, |r| r.levels.len());
+        let k = self.inner.first().map_or(0, |r| r.hasher.k());
+
+        SpaceReport {
+            structure: "L0Sampler",
+            bound: "O(log^2 n · log(1/δ))",
+            parameters: vec![
+                ("n", self.n as f64),
+                ("δ", self.delta as f64),
+                ("log n", levels as f64),
+            ],
+            theoretical_bits: (rounds * (levels + k)) as f64 * log(self.n as f64),
+            measured_bytes: size_of::<Self>()
+                + self.inner.capacity() * size_of::<Repetition>()
+                + self
+                    .inner
+                    .iter()
+                    .map(|r| {
+                        r.levels.capacity() * size_of::<OneSparseRecovery>()
+                            + r.hasher.k() * size_of::<u64>()
+                    })
+                    .sum::<usize>(),
+            components: vec![],
+        }
+    }
+}
+
+impl StreamProcessor<(u64, bool), Option<(u64, i64)>> for L0Sampler {
+    fn feed(&mut self, token: (u64, bool)) {
+        L0Sampler::feed(self, token)
+    }
+
+    fn query(self) -> Option<(u64, i64)> {
+        L0Sampler::query(self)
+    }
+}
+
+impl Provenance for L0Sampler {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "L0Sampler",
+            paper_ref: "Chakrabarti, CS35 Lecture Notes, https://www.cs.dartmouth.edu/~ac/Teach/CS35-Spring20/Notes/lecnotes.pdf",
+            params: vec![
+                ("n", self.n as f64),
+                ("δ", self.delta as f64),
+                ("repetitions", self.inner.len() as f64),
+            ],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn single() {
+        let mut sampler = L0Sampler::init(100, 0.01);
+        sampler.feed((42, true));
+        sampler.feed((42, true));
+        assert_eq!(sampler.query_ref(), Some((42, 2)));
+
+        // Queried mid-stream, and fed further
+        sampler.feed((42, false));
+        sampler.feed((42, false));
+        assert_eq!(sampler.query(), None);
+    }
+
+    #[test]
+    fn uniform() {
+        let (n, trials) = (256, 1600);
+        let support: Vec<u64> = (0..8).map(|i| 31 * i + 5).collect();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let mut counts: HashMap<u64, u32> = HashMap::new();
+        let mut failures = 0;
+        for _ in 0..trials {
+            let mut sampler = L0Sampler::init_with_rng(n, 0.05, &mut rng);
+            // Deleted coordinates must never be sampled
+            (0..n)
+                .filter(|j| j % 3 == 0)
+                .for_each(|j| sampler.feed((j, true)));
+            support.iter().for_each(|j| sampler.feed((*j, true)));
+            (0..n)
+                .filter(|j| j % 3 == 0)
+                .for_each(|j| sampler.feed((j, false)));
+
+            match sampler.query() {
+                Some((j, value)) => {
+                    assert!(support.contains(&j), "{} was not in the support", j);
+                    assert_eq!(value, 1);
+                    *counts.entry(j).or_default() += 1;
+                }
+                None => failures += 1,
             }
         }
 
-        None
+        assert!(
+            failures as f64 <= 0.05 * trials as f64,
+            "{} failures",
+            failures
+        );
+
+        let expected = (trials - failures) as f64 / support.len() as f64;
+        assert_eq!(counts.len(), support.len());
+        assert!(
+            counts
+                .values()
+                .all(|c| (*c as f64 - expected).abs() < 0.3 * expected),
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut a = L0Sampler::init_with_rng(64, 0.1, &mut rng);
+        let mut b = a.clone();
+
+        a.feed((7, true));
+        a.feed((9, true));
+        b.feed((9, false));
+        a.merge(&b);
+
+        assert_eq!(a.query(), Some((7, 1)));
+    }
+
+    #[test]
+    fn weighted() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut sampler = L0Sampler::init_with_rng(100, 0.01, &mut rng);
+        sampler.feed_weighted((42, 5));
+        sampler.feed_weighted((17, 3));
+        sampler.feed_weighted((17, -3));
+        assert_eq!(sampler.query_ref(), Some((42, 5)));
+
+        sampler.feed_weighted((42, -5));
+        assert_eq!(sampler.query(), None);
     }
 }
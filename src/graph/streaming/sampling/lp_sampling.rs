@@ -0,0 +1,472 @@
+//! Lp Sampling
+//!
+//! Samples a coordinate `i` of a turnstile vector `f` with probability `|f_i|^p / ||f||_p^p`, for `p = 1` or `p = 2`, by the
+//! precision sampling of [Jowhari, Sağlam and Tardos](https://arxiv.org/abs/1012.4889):
+//! 1. Each coordinate is scaled by a random `u_i` uniform in `(0, 1)`, into `z_i = f_i / u_i^{1/p}`
+//! 2. A threshold `T = ε^{-1/p} ||f||_p` is set from an estimate of the norm, drawn independently of the `u_i`
+//! 3. `|z_i| >= T` exactly when `u_i <= |f_i|^p / T^p`, which happens with probability `ε |f_i|^p / ||f||_p^p`, so the
+//!    coordinate that crosses the threshold, found with a Count-Sketch of `z`, is the sample
+//!
+//! A round crosses the threshold with probability about `ε`, so `O(log(1/δ) / ε)` independent rounds fail together with
+//! probability at most `δ`. The norm is estimated with a `p`-stable sketch: Cauchy variables for `p = 1`, Gaussians for `p = 2`.
+
+use std::{f64::consts::PI, mem::size_of};
+
+use rand::Rng;
+
+use crate::{
+    graph::streaming::{
+        provenance::{AlgorithmInfo, Provenance},
+        space::{log, SpaceReport, SpaceUsage},
+        Merge, StreamProcessor,
+    },
+    utils::hash_function::{HashFunction, PolynomialHasher},
+};
+
+/// Range of the hash functions that draw the uniform variables
+const UNIFORM_RANGE: u64 = 1 << 32;
+/// Independence of the hash functions that draw random variables
+const INDEPENDENCE: usize = 4;
+
+/// The norm a sampler samples proportionally to
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lp {
+    /// Proportionally to `|f_i|`
+    L1,
+    /// Proportionally to `f_i^2`
+    L2,
+}
+
+impl Lp {
+    fn p(&self) -> f64 {
+        match self {
+            Self::L1 => 1.0,
+            Self::L2 => 2.0,
+        }
+    }
+}
+
+/// Parameters of an [LpSampler]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LpParameters {
+    /// Probability with which a round crosses its threshold
+    pub epsilon: f64,
+    /// Probability with which every round fails
+    pub delta: f64,
+    /// Buckets of each row of the Count-Sketches, rounded up to a power of two
+    pub buckets: u64,
+    /// Rows of the Count-Sketches
+    pub rows: usize,
+    /// Rows of the sketch that estimates the norm
+    pub norm_rows: usize,
+}
+
+impl Default for LpParameters {
+    fn default() -> Self {
+        Self {
+            epsilon: 0.25,
+            delta: 0.05,
+            buckets: 64,
+            rows: 5,
+            norm_rows: 96,
+        }
+    }
+}
+
+/// A uniform variable in `(0, 1)`, drawn by a hash function of range [UNIFORM_RANGE]
+fn uniform(hasher: &PolynomialHasher, i: u64) -> f64 {
+    (hasher.compute(i) as f64 + 0.5) / UNIFORM_RANGE as f64
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    values[values.len() / 2]
+}
+
+/// A row of a Count-Sketch
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+struct Row {
+    bucket: PolynomialHasher,
+    sign: PolynomialHasher,
+    counters: Vec<f64>,
+}
+
+impl Row {
+    fn sign(&self, i: u64) -> f64 {
+        if self.sign.is_zero(i) {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    fn update(&mut self, i: u64, value: f64) {
+        let sign = self.sign(i);
+        self.counters[self.bucket.compute(i) as usize] += sign * value;
+    }
+
+    fn estimate(&self, i: u64) -> f64 {
+        self.sign(i) * self.counters[self.bucket.compute(i) as usize]
+    }
+}
+
+/// One attempt at a sample: the scaling of the coordinates, and a Count-Sketch of the scaled vector
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+struct Round {
+    scale: PolynomialHasher,
+    rows: Vec<Row>,
+}
+
+/// Samples a nonzero coordinate of a turnstile vector over `[n]` proportionally to its `p`-th power
+///
+/// Storage: O(log(1/δ)/ε) Count-Sketches of O(rows · buckets) words, plus O(norm rows) words
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct LpSampler {
+    n: u64,
+    norm: Lp,
+    parameters: LpParameters,
+    /// Pairs of uniform variables of each row of the norm sketch, a Gaussian needing both and a Cauchy only the first
+    stable: Vec<(PolynomialHasher, PolynomialHasher)>,
+    sums: Vec<f64>,
+    rounds: Vec<Round>,
+}
+
+impl LpSampler {
+    /// Initialize a sampler over the coordinates `[n]`
+    pub fn init(n: u64, norm: Lp, parameters: LpParameters) -> Self {
+        Self::init_with_rng(n, norm, parameters, &mut rand::thread_rng())
+    }
+
+    /// Initialize a sampler, drawing its hash functions from the generator
+    pub fn init_with_rng<R: Rng + ?Sized>(
+        n: u64,
+        norm: Lp,
+        parameters: LpParameters,
+        rng: &mut R,
+    ) -> Self {
+        let n_pow = n.max(2).next_power_of_two();
+        let buckets = parameters.buckets.max(1).next_power_of_two();
+        let parameters = LpParameters {
+            buckets,
+            ..parameters
+        };
+        let count = ((1.0 / parameters.delta).ln() / parameters.epsilon)
+            .ceil()
+            .max(1.0) as usize;
+
+        let mut hasher = |l: u64| PolynomialHasher::init_with_rng(n_pow, l, INDEPENDENCE, rng);
+
+        let stable = (0..parameters.norm_rows)
+            .map(|_| (hasher(UNIFORM_RANGE), hasher(UNIFORM_RANGE)))
+            .collect();
+        let rounds = (0..count)
+            .map(|_| Round {
+                scale: hasher(UNIFORM_RANGE),
+                rows: (0..parameters.rows)
+                    .map(|_| Row {
+                        bucket: hasher(buckets),
+                        sign: hasher(2),
+                        counters: vec![0.0; buckets as usize],
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            n,
+            norm,
+            parameters,
+            stable,
+            sums: vec![0.0; parameters.norm_rows],
+            rounds,
+        }
+    }
+
+    /// Sampler proportional to `|f_i|`
+    pub fn l1(n: u64, parameters: LpParameters) -> Self {
+        Self::init(n, Lp::L1, parameters)
+    }
+
+    /// Sampler proportional to `f_i^2`
+    pub fn l2(n: u64, parameters: LpParameters) -> Self {
+        Self::init(n, Lp::L2, parameters)
+    }
+
+    /// The `p`-stable variable of a row of the norm sketch, for the coordinate `i`
+    fn stable(&self, row: usize, i: u64) -> f64 {
+        let (first, second) = &self.stable[row];
+        let u = uniform(first, i);
+
+        match self.norm {
+            Lp::L1 => (PI * (u - 0.5)).tan(),
+            Lp::L2 => (-2.0 * u.ln()).sqrt() * (2.0 * PI * uniform(second, i)).cos(),
+        }
+    }
+
+    pub fn feed(&mut self, token: (u64, bool)) {
+        let (i, c) = token;
+        self.feed_weighted((i, if c { 1 } else { -1 }))
+    }
+
+    /// Add `Δ` to the coordinate `i`, `token = (i, Δ)`, as `|Δ|` tokens of [feed](LpSampler::feed) would
+    pub fn feed_weighted(&mut self, token: (u64, i64)) {
+        let (i, delta) = token;
+        let value = delta as f64;
+        let p = self.norm.p();
+
+        for row in 0..self.sums.len() {
+            self.sums[row] += value * self.stable(row, i);
+        }
+
+        for round in self.rounds.iter_mut() {
+            let scaled = value / uniform(&round.scale, i).powf(1.0 / p);
+            round.rows.iter_mut().for_each(|row| row.update(i, scaled));
+        }
+    }
+
+    /// Estimate of `||f||_p`, the median of `|Y|` for `Y` a `p`-stable variable scaled by the norm
+    pub fn norm_estimate(&self) -> f64 {
+        let median_of_standard = match self.norm {
+            Lp::L1 => 1.0,
+            Lp::L2 => 0.674_489_750_196_081_7,
+        };
+
+        median(self.sums.iter().map(|s| s.abs()).collect()) / median_of_standard
+    }
+
+    /// A nonzero coordinate and its value, `None` if the vector is zero or every round failed
+    pub fn query(self) -> Option<(u64, i64)> {
+        let norm = self.norm_estimate();
+        if norm < 0.5 {
+            return None;
+        }
+
+        let p = self.norm.p();
+        let threshold = norm * self.parameters.epsilon.powf(-1.0 / p);
+
+        self.rounds.iter().find_map(|round| {
+            let estimate = |i: u64| median(round.rows.iter().map(|row| row.estimate(i)).collect());
+            let (i, z) = (0..self.n)
+                .map(|i| (i, estimate(i)))
+                .max_by(|(_, a), (_, b)| a.abs().partial_cmp(&b.abs()).unwrap())?;
+
+            if z.abs() < threshold {
+                return None;
+            }
+
+            let value = (z * uniform(&round.scale, i).powf(1.0 / p)).round() as i64;
+            if value == 0 {
+                None
+            } else {
+                Some((i, value))
+            }
+        })
+    }
+}
+
+impl Merge for LpSampler {
+    fn merge(&mut self, other: &Self) {
+        if self.stable != other.stable
+            || self.rounds.len() != other.rounds.len()
+            || self
+                .rounds
+                .iter()
+                .zip(other.rounds.iter())
+                .any(|(a, b)| a.scale != b.scale)
+        {
+            panic!("[LpSampler] Cannot merge samplers with different hash functions");
+        }
+
+        self.sums
+            .iter_mut()
+            .zip(other.sums.iter())
+            .for_each(|(a, b)| *a += b);
+        for (round, other) in self.rounds.iter_mut().zip(other.rounds.iter()) {
+            for (row, other) in round.rows.iter_mut().zip(other.rows.iter()) {
+                row.counters
+                    .iter_mut()
+                    .zip(other.counters.iter())
+                    .for_each(|(a, b)| *a += b);
+            }
+        }
+    }
+}
+
+impl SpaceUsage for LpSampler {
+    /// `log(1/δ)/ε` Count-Sketches, and the norm sketch
+    fn space(&self) -> SpaceReport {
+        let rounds = self.rounds.len() as f64;
+        let LpParameters {
+            buckets,
+            rows,
+            norm_rows,
+            ..
+        } = self.parameters;
+        let words = rounds * (rows as f64 * buckets as f64) + norm_rows as f64;
+
+        SpaceReport {
+            structure: "LpSampler",
+            bound: "O(log(1/δ)/ε · rows · buckets · log n)",
+            parameters: vec![
+                ("n", self.n as f64),
+                ("p", self.norm.p()),
+                ("rounds", rounds),
+                ("rows", rows as f64),
+                ("buckets", buckets as f64),
+            ],
+            theoretical_bits: words * log(self.n as f64),
+            measured_bytes: size_of::<Self>()
+                + self.sums.capacity() * size_of::<f64>()
+                + self.stable.capacity() * size_of::<(PolynomialHasher, PolynomialHasher)>()
+                + self
+                    .rounds
+                    .iter()
+                    .map(|round| {
+                        size_of::<Round>()
+                            + round
+                                .rows
+                                .iter()
+                                .map(|row| {
+                                    size_of::<Row>() + row.counters.capacity() * size_of::<f64>()
+                                })
+                                .sum::<usize>()
+                    })
+                    .sum::<usize>(),
+            components: vec![],
+        }
+    }
+}
+
+impl StreamProcessor<(u64, bool), Option<(u64, i64)>> for LpSampler {
+    fn feed(&mut self, token: (u64, bool)) {
+        LpSampler::feed(self, token)
+    }
+
+    fn query(self) -> Option<(u64, i64)> {
+        LpSampler::query(self)
+    }
+}
+
+impl Provenance for LpSampler {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "LpSampler",
+            paper_ref: "Jowhari, Sağlam, Tardos, PODS 2011, https://arxiv.org/abs/1012.4889",
+            params: vec![
+                ("n", self.n as f64),
+                ("p", self.norm.p()),
+                ("ε", self.parameters.epsilon),
+                ("δ", self.parameters.delta),
+            ],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    /// Frequency with which each coordinate is sampled, over samplers fed the same stream
+    fn frequencies(norm: Lp, stream: &[(u64, bool)], trials: usize) -> HashMap<u64, f64> {
+        let mut rng = StdRng::seed_from_u64(23);
+        let mut counts: HashMap<u64, f64> = HashMap::new();
+
+        let mut successes = 0;
+        for _ in 0..trials {
+            let mut sampler = LpSampler::init_with_rng(32, norm, Default::default(), &mut rng);
+            stream.iter().for_each(|token| sampler.feed(*token));
+
+            if let Some((i, _)) = sampler.query() {
+                *counts.entry(i).or_default() += 1.0;
+                successes += 1;
+            }
+        }
+
+        assert!(
+            successes as f64 >= 0.9 * trials as f64,
+            "{} successes",
+            successes
+        );
+        counts.values_mut().for_each(|c| *c /= successes as f64);
+        counts
+    }
+
+    /// `f_3 = 1`, `f_10 = 2`, `f_20 = -5`, with a coordinate inserted and deleted again
+    fn stream() -> Vec<(u64, bool)> {
+        let mut stream = vec![(3, true), (7, true)];
+        stream.extend((0..2).map(|_| (10, true)));
+        stream.extend((0..5).map(|_| (20, false)));
+        stream.push((7, false));
+        stream
+    }
+
+    #[test]
+    fn l1() {
+        let frequencies = frequencies(Lp::L1, &stream(), 600);
+
+        assert!(!frequencies.contains_key(&7));
+        for (i, expected) in [(3, 1.0 / 8.0), (10, 2.0 / 8.0), (20, 5.0 / 8.0)] {
+            let frequency = frequencies.get(&i).copied().unwrap_or_default();
+            assert!(
+                (frequency - expected).abs() < 0.08,
+                "{}: {:?}",
+                i,
+                frequencies
+            );
+        }
+    }
+
+    #[test]
+    fn l2() {
+        let frequencies = frequencies(Lp::L2, &stream(), 600);
+
+        assert!(!frequencies.contains_key(&7));
+        for (i, expected) in [(3, 1.0 / 30.0), (10, 4.0 / 30.0), (20, 25.0 / 30.0)] {
+            let frequency = frequencies.get(&i).copied().unwrap_or_default();
+            assert!(
+                (frequency - expected).abs() < 0.08,
+                "{}: {:?}",
+                i,
+                frequencies
+            );
+        }
+    }
+
+    #[test]
+    fn value_and_merge() {
+        let mut rng = StdRng::seed_from_u64(29);
+        let mut a = LpSampler::init_with_rng(16, Lp::L2, Default::default(), &mut rng);
+        let mut b = a.clone();
+        let empty = a.clone();
+
+        (0..3).for_each(|_| a.feed((5, true)));
+        b.feed((5, false));
+        a.merge(&b);
+
+        assert_eq!(a.query(), Some((5, 2)));
+        assert_eq!(empty.query(), None);
+    }
+
+    #[test]
+    fn weighted() {
+        let mut rng = StdRng::seed_from_u64(29);
+        let mut repeated = LpSampler::init_with_rng(16, Lp::L1, Default::default(), &mut rng);
+        let mut weighted = repeated.clone();
+
+        (0..4).for_each(|_| repeated.feed((9, false)));
+        weighted.feed_weighted((9, -4));
+
+        assert!((weighted.norm_estimate() - repeated.norm_estimate()).abs() < 1e-9);
+        assert_eq!(weighted.query(), Some((9, -4)));
+    }
+}
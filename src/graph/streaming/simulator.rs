@@ -0,0 +1,281 @@
+//! Exact Streaming Simulator
+//!
+//! The output of a streaming algorithm should not depend on the order of its stream, but a single hand written stream only
+//! exercises one order. The simulator replays a small stream in all of its orders, or in many random orders, and checks
+//! every output against the exact answer computed statically. Failures report the seed of the order, so that they can be reproduced.
+
+use std::{collections::HashSet, fmt::Debug, hash::Hash};
+
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::graph::{Edge, Graphed};
+
+/// Longest stream for which every order is simulated, `8! = 40320` orders
+pub const MAX_EXHAUSTIVE: usize = 8;
+
+/// Orders in which the stream is replayed
+#[derive(Clone, Copy, Debug)]
+pub enum Orders {
+    /// Every permutation of the stream, only for streams of at most [MAX_EXHAUSTIVE] tokens
+    Exhaustive,
+    /// `runs` random permutations, the `i`th shuffled with the seed `seed + i`
+    Random { runs: usize, seed: u64 },
+}
+
+/// A stream order on which the output of the algorithm was rejected
+#[derive(Clone, Debug)]
+pub struct SimulationFailure<T> {
+    /// Seed of the order, `None` for exhaustive simulations
+    pub seed: Option<u64>,
+    pub stream: Vec<T>,
+}
+
+/// The outcome of a simulation
+#[derive(Clone, Debug)]
+pub struct SimulationReport<T> {
+    /// Number of orders simulated
+    pub runs: usize,
+    pub failures: Vec<SimulationFailure<T>>,
+}
+
+impl<T> SimulationReport<T>
+where
+    T: Debug,
+{
+    /// Fraction of the orders on which the output was rejected
+    pub fn failure_rate(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.failures.len() as f64 / self.runs as f64
+        }
+    }
+
+    /// Panics if the failure rate exceeds `rate`, reporting the failed orders
+    ///
+    /// Exact algorithms should use a rate of 0, randomized ones their error probability.
+    pub fn assert_failure_rate(&self, rate: f64) {
+        if self.failure_rate() > rate {
+            panic!(
+                "[StreamSimulator] {} of {} orders failed, above the allowed rate of {}: {:?}",
+                self.failures.len(),
+                self.runs,
+                rate,
+                self.failures
+            );
+        }
+    }
+}
+
+/// Replays a fixed set of tokens in many orders
+#[derive(Clone, Debug)]
+pub struct StreamSimulator<T> {
+    stream: Vec<T>,
+}
+
+impl<T> StreamSimulator<T>
+where
+    T: Clone + Debug,
+{
+    pub fn init(stream: Vec<T>) -> Self {
+        Self { stream }
+    }
+
+    /// The tokens of the stream, in their original order
+    pub fn stream(&self) -> &[T] {
+        &self.stream
+    }
+
+    /// Run the algorithm on every order, checking each output
+    ///
+    /// - *algorithm* : Initializes the algorithm, feeds it the stream in the order provided, and queries it
+    /// - *check* : Whether an output is correct, typically by comparing against the exact static answer
+    ///
+    /// Panics if the orders are exhaustive, and the stream is longer than [MAX_EXHAUSTIVE].
+    pub fn run<O, A, C>(&self, orders: Orders, algorithm: A, check: C) -> SimulationReport<T>
+    where
+        A: FnMut(Vec<T>) -> O,
+        C: Fn(&O) -> bool,
+    {
+        let mut algorithm = algorithm;
+        let mut report = SimulationReport {
+            runs: 0,
+            failures: vec![],
+        };
+
+        let mut simulate = |seed: Option<u64>, stream: Vec<T>| {
+            report.runs += 1;
+            if !check(&algorithm(stream.clone())) {
+                report.failures.push(SimulationFailure { seed, stream });
+            }
+        };
+
+        match orders {
+            Orders::Exhaustive => {
+                if self.stream.len() > MAX_EXHAUSTIVE {
+                    panic!(
+                        "[StreamSimulator] Cannot simulate every order of a stream of {} tokens",
+                        self.stream.len()
+                    );
+                }
+                self.stream
+                    .iter()
+                    .cloned()
+                    .permutations(self.stream.len())
+                    .for_each(|stream| simulate(None, stream));
+            }
+            Orders::Random { runs, seed } => {
+                for seed in (0..runs as u64).map(|i| seed.wrapping_add(i)) {
+                    let mut stream = self.stream.clone();
+                    stream.shuffle(&mut StdRng::seed_from_u64(seed));
+                    simulate(Some(seed), stream);
+                }
+            }
+        }
+
+        report
+    }
+
+    /// The order of the stream shuffled by a seed reported in a failure
+    pub fn replay(&self, seed: u64) -> Vec<T> {
+        let mut stream = self.stream.clone();
+        stream.shuffle(&mut StdRng::seed_from_u64(seed));
+        stream
+    }
+}
+
+impl<T> StreamSimulator<(Edge<T, ()>, bool)>
+where
+    T: Hash + Eq + Ord + Clone + Debug,
+{
+    /// The insertion stream of the edges of an undirected graph, each edge appearing once
+    pub fn insertions<G>(graph: &G) -> Self
+    where
+        G: Graphed<T, ()>,
+    {
+        let edges: HashSet<(T, T)> = graph
+            .adj_list()
+            .iter()
+            .flat_map(|(u, neighbors)| {
+                neighbors.iter().map(move |n| {
+                    let v = n.destination.clone();
+                    if *u <= v {
+                        (u.clone(), v)
+                    } else {
+                        (v, u.clone())
+                    }
+                })
+            })
+            .collect();
+
+        Self::init(
+            edges
+                .into_iter()
+                .sorted()
+                .map(|(u, v)| (Edge::init(u, v), true))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        graph::{
+            streaming::sparse_recovery::{s_sparse::SparseRecovery, SparseRecoveryOutput},
+            Graph, VertexId,
+        },
+        utils::hash_function::PowerFiniteFieldHasher,
+    };
+
+    fn star() -> Graph<VertexId, ()> {
+        let mut graph = Graph::default();
+        for leaf in 1..6 {
+            graph.add_edge(Edge::init(0, leaf));
+        }
+        graph.add_edge(Edge::init(1, 2));
+        graph
+    }
+
+    /// Rebuilds the graph from its stream
+    fn rebuild(stream: Vec<(Edge<VertexId, ()>, bool)>) -> Graph<VertexId, ()> {
+        let mut graph = Graph::default();
+        for (edge, c) in stream {
+            if c {
+                graph.add_edge(edge);
+            } else {
+                graph.remove_edge(edge);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn exhaustive() {
+        let graph = star();
+        let simulator = StreamSimulator::insertions(&graph);
+
+        assert_eq!(simulator.stream().len(), 6);
+
+        let report = simulator.run(Orders::Exhaustive, rebuild, |output| {
+            output.adj_list() == graph.adj_list()
+        });
+
+        assert_eq!(report.runs, 720);
+        report.assert_failure_rate(0.0);
+    }
+
+    #[test]
+    fn reports_seeds() {
+        let simulator = StreamSimulator::init((0..20).collect::<Vec<u32>>());
+
+        // Rejects every order that does not start with 0
+        let report = simulator.run(
+            Orders::Random { runs: 50, seed: 7 },
+            |stream| stream[0],
+            |first| *first == 0,
+        );
+
+        assert_eq!(report.runs, 50);
+        assert!(!report.failures.is_empty());
+        for failure in report.failures {
+            assert_eq!(simulator.replay(failure.seed.unwrap()), failure.stream);
+        }
+    }
+
+    #[test]
+    fn sparse_recovery_neighborhood() {
+        let graph = star();
+        let simulator = StreamSimulator::insertions(&graph);
+
+        let report = simulator.run(
+            Orders::Random { runs: 50, seed: 0 },
+            |stream| {
+                let mut sketch = SparseRecovery::<PowerFiniteFieldHasher>::init(10, 10, 0.01);
+                stream
+                    .into_iter()
+                    .filter(|(edge, _)| edge.vertices().0 == &0)
+                    .for_each(|(edge, c)| sketch.feed((*edge.vertices().1, c)));
+                sketch.query()
+            },
+            |output| match output {
+                SparseRecoveryOutput::Recovered(support) => {
+                    support.keys().copied().collect::<HashSet<u64>>() == (1..6).collect()
+                }
+                _ => false,
+            },
+        );
+
+        report.assert_failure_rate(0.1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_long() {
+        let simulator = StreamSimulator::init((0..10).collect::<Vec<u32>>());
+
+        simulator.run(Orders::Exhaustive, |_| (), |_| true);
+    }
+}
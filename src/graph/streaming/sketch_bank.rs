@@ -0,0 +1,338 @@
+//! Vertex Indexed Sketch Families
+//!
+//! Many streaming algorithms pick a random subset of vertices at the start of the stream, and maintain a sketch of the
+//! neighborhood of each picked vertex. This module handles the sampling, and the routing of edge tokens to those sketches.
+
+use std::{collections::HashMap, mem::size_of};
+
+use rand::{distributions::Distribution, Rng};
+
+use super::{
+    space::{log, SpaceReport, SpaceUsage},
+    sparse_recovery::{s_sparse::SparseRecovery, SparseRecoveryOutput},
+    Merge, Sketch, StreamProcessor,
+};
+use crate::{
+    error::bernoulli,
+    graph::{Edge, VertexId},
+    utils::{hash_function::HashFunction, slab::VertexSlab},
+    Result,
+};
+
+/// The degree of a sampled vertex, as recovered from the sparse recovery of its neighborhood
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DegreeEstimate {
+    /// The number of distinct neighbors, correct with the success probability of the recovery
+    Recovered(u64),
+    /// The neighborhood is larger than the sparsity of the recovery
+    Exceeds(u64),
+    /// The recovery was inconsistent
+    Unknown,
+}
+
+impl DegreeEstimate {
+    /// The recovered degree, `None` if the neighborhood could not be recovered
+    pub fn recovered(&self) -> Option<u64> {
+        match self {
+            Self::Recovered(degree) => Some(*degree),
+            _ => None,
+        }
+    }
+}
+
+/// A family of sketches indexed by a random subset of the vertices `[n]`
+///
+/// Each vertex is kept independently with probability `p`, and receives its own sketch of its neighborhood.
+///
+/// Storage: O(n) words for the index, plus O(pn * space of S)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct VertexSketchBank<S> {
+    inner: VertexSlab<S>,
+}
+
+impl<S> VertexSketchBank<S>
+where
+    S: Sketch,
+{
+    /// Initialize a new bank over the vertices `[n]`
+    ///
+    /// - *n* : Number of vertices
+    /// - *p* : Probability with which each vertex is sampled
+    /// - *sketch* : Instantiates the sketch of a sampled vertex, each call should use new random components
+    pub fn init<F>(n: VertexId, p: f64, sketch: F) -> Result<Self>
+    where
+        F: FnMut(VertexId) -> S,
+    {
+        Self::init_with_rng(n, p, sketch, &mut rand::thread_rng())
+    }
+
+    /// Initialize a new bank, sampling the vertices with the given generator, e.g. a stream of a
+    /// [RandomnessManager](crate::utils::randomness::RandomnessManager) so that the sample can be reproduced
+    pub fn init_with_rng<F, R>(n: VertexId, p: f64, sketch: F, rng: &mut R) -> Result<Self>
+    where
+        F: FnMut(VertexId) -> S,
+        R: Rng + ?Sized,
+    {
+        let bern = bernoulli(p)?;
+        let mut sketch = sketch;
+
+        let mut inner = VertexSlab::init(n);
+        for v in (0..n).filter(|_| bern.sample(rng)) {
+            inner.insert(v, sketch(v));
+        }
+
+        Ok(Self { inner })
+    }
+
+    /// Feed an edge token into the bank
+    ///
+    /// The sketch of each sampled endpoint receives the other endpoint as its coordinate.
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, c) = token;
+        let (u, v) = edge.vertices();
+
+        if let Some(sketch) = self.inner.get_mut(u) {
+            sketch.feed((*v, c));
+        }
+        if let Some(sketch) = self.inner.get_mut(v) {
+            sketch.feed((*u, c));
+        }
+    }
+
+    /// Whether the vertex was sampled into the bank
+    pub fn contains(&self, vertex: &VertexId) -> bool {
+        self.inner.contains(vertex)
+    }
+
+    /// The sketch of a sampled vertex
+    pub fn get(&self, vertex: &VertexId) -> Option<&S> {
+        self.inner.get(vertex)
+    }
+
+    /// The sampled vertices
+    pub fn vertices(&self) -> impl Iterator<Item = &VertexId> {
+        self.inner.vertices()
+    }
+
+    /// Number of sampled vertices
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Whether no vertex was sampled
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Query every sketch within the bank
+    pub fn query<Q>(self) -> HashMap<VertexId, Q>
+    where
+        S: StreamProcessor<(u64, bool), Q>,
+    {
+        self.inner
+            .into_iter()
+            .map(|(v, sketch)| (v, sketch.query()))
+            .collect()
+    }
+
+    /// Query every sketch within the bank by reference, leaving the bank free to be fed further
+    pub fn query_with<Q>(&self, query: impl Fn(&S) -> Q) -> HashMap<VertexId, Q> {
+        self.inner
+            .iter()
+            .map(|(v, sketch)| (*v, query(sketch)))
+            .collect()
+    }
+}
+
+impl<S, Q> StreamProcessor<(Edge<VertexId, ()>, bool), HashMap<VertexId, Q>> for VertexSketchBank<S>
+where
+    S: Sketch + StreamProcessor<(u64, bool), Q>,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        VertexSketchBank::feed(self, token)
+    }
+
+    fn query(self) -> HashMap<VertexId, Q> {
+        VertexSketchBank::query(self)
+    }
+}
+
+impl<S> Merge for VertexSketchBank<S>
+where
+    S: Merge,
+{
+    /// Both banks must have sampled the same vertices
+    fn merge(&mut self, other: &Self) {
+        if self.inner.len() != other.inner.len() {
+            panic!("[VertexSketchBank] Cannot merge banks that sampled different vertices");
+        }
+
+        for (v, sketch) in other.inner.iter() {
+            self.inner
+                .get_mut(v)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "[VertexSketchBank] Vertex {} was not sampled by both banks",
+                        v
+                    )
+                })
+                .merge(sketch);
+        }
+    }
+}
+
+impl<F> VertexSketchBank<SparseRecovery<F>>
+where
+    F: HashFunction + Clone,
+{
+    /// Estimated degree of every sampled vertex, from the neighborhood recovered so far
+    ///
+    /// Runtime: the query of every sketch
+    pub fn degrees(&self) -> HashMap<VertexId, DegreeEstimate> {
+        self.inner
+            .iter()
+            .map(|(v, sketch)| {
+                let estimate = match sketch.query_ref() {
+                    SparseRecoveryOutput::Recovered(support) => DegreeEstimate::Recovered(
+                        support.values().filter(|c| **c != 0).count() as u64,
+                    ),
+                    SparseRecoveryOutput::Zero => DegreeEstimate::Recovered(0),
+                    SparseRecoveryOutput::NotSparse => DegreeEstimate::Exceeds(sketch.sparsity()),
+                    SparseRecoveryOutput::Inconsistent => DegreeEstimate::Unknown,
+                };
+                (*v, estimate)
+            })
+            .collect()
+    }
+}
+
+impl<S> SpaceUsage for VertexSketchBank<S>
+where
+    S: SpaceUsage,
+{
+    /// The index over `[n]`, plus the sketches of the sampled vertices
+    fn space(&self) -> SpaceReport {
+        let n = self.inner.universe() as f64;
+        let sketches: Vec<SpaceReport> = self.inner.iter().map(|(_, s)| s.space()).collect();
+
+        SpaceReport {
+            structure: "VertexSketchBank",
+            bound: "O(n log n) + |S| · space of S",
+            parameters: vec![("n", n), ("|S|", self.inner.len() as f64)],
+            theoretical_bits: n * log(n) + sketches.iter().map(|s| s.theoretical_bits).sum::<f64>(),
+            measured_bytes: size_of::<Self>()
+                + self.inner.layout_bytes()
+                + sketches
+                    .iter()
+                    .map(|s| s.measured_bytes - size_of::<S>())
+                    .sum::<usize>(),
+            components: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        graph::streaming::sparse_recovery::{s_sparse::SparseRecovery, SparseRecoveryOutput},
+        utils::{hash_function::PowerFiniteFieldHasher, randomness::RandomnessManager},
+        Error,
+    };
+
+    #[test]
+    fn routes_by_endpoint() {
+        let base = SparseRecovery::<PowerFiniteFieldHasher>::init(20, 10, 0.01);
+        let mut bank = VertexSketchBank::init(20, 1.0, |_| base.random_copy()).unwrap();
+
+        assert_eq!(bank.len(), 20);
+
+        (1..6).for_each(|v| bank.feed((Edge::init(0, v), true)));
+        bank.feed((Edge::init(0, 5), false));
+
+        let neighborhoods = bank.query::<SparseRecoveryOutput<HashMap<u64, i64>>>();
+
+        let neighbors_0: HashSet<u64> = neighborhoods
+            .get(&0)
+            .map(|output| match output {
+                SparseRecoveryOutput::Recovered(support) => support.keys().copied().collect(),
+                _ => HashSet::new(),
+            })
+            .unwrap();
+
+        assert_eq!(neighbors_0, (1..5).collect());
+        assert!(matches!(
+            neighborhoods.get(&3),
+            Some(SparseRecoveryOutput::Recovered(support)) if support.keys().eq([0].iter())
+        ));
+        assert!(matches!(
+            neighborhoods.get(&5),
+            Some(SparseRecoveryOutput::Zero)
+        ));
+    }
+
+    #[test]
+    fn degrees() {
+        let base = SparseRecovery::<PowerFiniteFieldHasher>::init(20, 3, 0.01);
+        let mut bank = VertexSketchBank::init(20, 1.0, |_| base.random_copy()).unwrap();
+
+        (1..6).for_each(|v| bank.feed((Edge::init(0, v), true)));
+        bank.feed((Edge::init(1, 2), true));
+
+        let degrees = bank.degrees();
+        assert_eq!(degrees[&0], DegreeEstimate::Exceeds(3));
+        assert_eq!(degrees[&1], DegreeEstimate::Recovered(2));
+        assert_eq!(degrees[&5].recovered(), Some(1));
+        assert_eq!(degrees[&7], DegreeEstimate::Recovered(0));
+        assert_eq!(degrees.len(), 20);
+    }
+
+    #[test]
+    fn merge() {
+        let base = SparseRecovery::<PowerFiniteFieldHasher>::init(20, 10, 0.01);
+        let mut left = VertexSketchBank::init(20, 1.0, |_| base.random_copy()).unwrap();
+        let mut right = left.clone();
+
+        (1..6).for_each(|v| left.feed((Edge::init(0, v), true)));
+        (6..9).for_each(|v| right.feed((Edge::init(v, 0), true)));
+        right.feed((Edge::init(0, 1), false));
+
+        left.merge(&right);
+        let degrees = left.degrees();
+        assert_eq!(degrees[&0], DegreeEstimate::Recovered(7));
+        assert_eq!(degrees[&1], DegreeEstimate::Recovered(0));
+        assert_eq!(degrees[&7], DegreeEstimate::Recovered(1));
+    }
+
+    #[test]
+    fn reproducible_sample() {
+        let sample = |seed| {
+            let mut manager = RandomnessManager::init(seed);
+            VertexSketchBank::init_with_rng(
+                200,
+                0.5,
+                |_| SparseRecovery::<PowerFiniteFieldHasher>::init(20, 10, 0.01),
+                &mut manager.stream("bank"),
+            )
+            .unwrap()
+            .vertices()
+            .copied()
+            .collect::<HashSet<_>>()
+        };
+
+        assert_eq!(sample(3), sample(3));
+        assert_ne!(sample(3), sample(4));
+    }
+
+    #[test]
+    fn invalid_probability() {
+        let bank = VertexSketchBank::init(20, 1.5, |_| {
+            SparseRecovery::<PowerFiniteFieldHasher>::init(20, 10, 0.01)
+        });
+
+        assert!(matches!(bank, Err(Error::InvalidProbability(p)) if p == 1.5));
+    }
+}
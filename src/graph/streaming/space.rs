@@ -0,0 +1,176 @@
+//! Space Reporting
+//!
+//! The point of a streaming algorithm is its space bound, so experiments should show it. Every structure implementing [SpaceUsage]
+//! reports its bound symbolically, the bound evaluated on the parameters it was built with, and the bytes it actually holds, e.g.
+//!
+//! ```text
+//! SparseRecovery: O(s log(s/δ) · log n) = ~1.6 MB for n=8154741, s=900, t=11; measured 2.1 MB
+//! ```
+//!
+//! Bounds are evaluated with every hidden constant taken to be 1, and logarithms in base 2, so only the growth of the ratio
+//! between measured and theoretical space across experiments is meaningful, not its value.
+
+use std::fmt::Display;
+
+/// The space used by a streaming structure, in theory and in practice
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpaceReport {
+    /// Name of the structure
+    pub structure: &'static str,
+    /// The asymptotic bound, e.g. `O(s log(s/δ) · log n)`
+    pub bound: &'static str,
+    /// Parameters the bound is evaluated on
+    pub parameters: Vec<(&'static str, f64)>,
+    /// The bound evaluated on the parameters, in bits
+    pub theoretical_bits: f64,
+    /// Bytes held by the structure, inline and on the heap
+    pub measured_bytes: usize,
+    /// Reports of the structures this one is built from, already accounted for in its own totals
+    pub components: Vec<SpaceReport>,
+}
+
+impl SpaceReport {
+    pub fn theoretical_bytes(&self) -> f64 {
+        self.theoretical_bits / 8.0
+    }
+
+    /// Measured over theoretical space
+    pub fn ratio(&self) -> f64 {
+        self.measured_bytes as f64 / self.theoretical_bytes()
+    }
+
+    fn write_indented(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|(name, value)| {
+                if value.fract() == 0.0 {
+                    format!("{}={}", name, value)
+                } else {
+                    format!("{}={:.3}", name, value)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let evaluated_on = if parameters.is_empty() {
+            parameters
+        } else {
+            format!(" for {}", parameters)
+        };
+
+        write!(
+            f,
+            "{}{}: {} = ~{}{}; measured {}",
+            "  ".repeat(depth),
+            self.structure,
+            self.bound,
+            human_bytes(self.theoretical_bytes()),
+            evaluated_on,
+            human_bytes(self.measured_bytes as f64)
+        )?;
+
+        for component in self.components.iter() {
+            writeln!(f)?;
+            component.write_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for SpaceReport {
+    /// One line per structure, components indented below the structure they belong to
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_indented(f, 0)
+    }
+}
+
+/// A structure that can report its space usage
+pub trait SpaceUsage {
+    fn space(&self) -> SpaceReport;
+
+    /// Bytes held by the structure, the [measured](SpaceReport::measured_bytes) space of its report
+    ///
+    /// To check a structure against a memory budget while it is fed, without reading the whole report.
+    fn memory_usage(&self) -> usize {
+        self.space().measured_bytes
+    }
+}
+
+/// Base 2 logarithm, at least 1 so that bounds on tiny universes do not vanish
+pub fn log(x: f64) -> f64 {
+    x.log2().max(1.0)
+}
+
+/// A byte count with a decimal unit, e.g. `3.2 MB`
+pub fn human_bytes(bytes: f64) -> String {
+    let units = ["B", "kB", "MB", "GB", "TB"];
+
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < units.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{:.0} {}", value, units[unit])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        graph::streaming::sparse_recovery::s_sparse::SparseRecovery,
+        utils::hash_function::PowerFiniteFieldHasher,
+    };
+
+    #[test]
+    fn bytes() {
+        assert_eq!(human_bytes(512.0), "512 B");
+        assert_eq!(human_bytes(3_200_000.0), "3.2 MB");
+        assert_eq!(human_bytes(4.5e15), "4500.0 TB");
+    }
+
+    #[test]
+    fn display() {
+        let report = SpaceReport {
+            structure: "Outer",
+            bound: "O(n)",
+            parameters: vec![("n", 4039.0), ("p", 0.25)],
+            theoretical_bits: 8_000.0,
+            measured_bytes: 2_000,
+            components: vec![SpaceReport {
+                structure: "Inner",
+                bound: "O(1)",
+                parameters: vec![],
+                theoretical_bits: 8.0,
+                measured_bytes: 8,
+                components: vec![],
+            }],
+        };
+
+        assert_eq!(
+            report.to_string(),
+            "Outer: O(n) = ~1.0 kB for n=4039, p=0.250; measured 2.0 kB\n  Inner: O(1) = ~1 B; measured 8 B"
+        );
+        assert_eq!(report.ratio(), 2.0);
+    }
+
+    #[test]
+    fn sparse_recovery() {
+        let mut sketch = SparseRecovery::<PowerFiniteFieldHasher>::init(1_000, 50, 0.01);
+        let empty = sketch.space();
+
+        assert_eq!(empty.parameters[..2], [("n", 1_000.0), ("s", 50.0)]);
+
+        (0..50).for_each(|j| sketch.feed((j, true)));
+        let full = sketch.space();
+
+        assert_eq!(full.theoretical_bits, empty.theoretical_bits);
+        assert!(full.measured_bytes > empty.measured_bytes);
+        assert_eq!(sketch.memory_usage(), full.measured_bytes);
+    }
+}
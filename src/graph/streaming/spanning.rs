@@ -0,0 +1,312 @@
+//! Minimum Spanning Forests
+//!
+//! The single pass semi-streaming algorithm of [Feigenbaum, Kannan, McGregor, Suri and Zhang](https://doi.org/10.1016/j.tcs.2005.09.013)
+//! for insertion-only streams of weighted edges. It keeps a spanning forest of the edges seen so far: an edge between two trees
+//! joins them, and an edge closing a cycle replaces the heaviest edge of the cycle, if that one is heavier. By the cycle property,
+//! the edge dropped is in no minimum spanning forest of the stream, so the forest kept is a minimum one.
+
+use std::{fmt::Debug, mem::size_of};
+
+use super::{
+    provenance::{AlgorithmInfo, Provenance},
+    space::{log, SpaceReport, SpaceUsage},
+    Merge, StreamProcessor,
+};
+use crate::graph::{Edge, VertexId};
+
+/// Minimum spanning forest of an insertion-only stream of edges over the vertices `[n]`, weighted by their labels
+///
+/// Storage: O(n log n) bits, the forest
+///
+/// Runtime: O(n) per edge
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct MinimumSpanningForest<W> {
+    /// The neighbors of every vertex in the forest, with the weight of the edge
+    forest: Vec<Vec<(VertexId, W)>>,
+    edge_count: usize,
+}
+
+impl<W> MinimumSpanningForest<W>
+where
+    W: Ord + Copy + Debug + Default,
+{
+    pub fn init(n: VertexId) -> Self {
+        Self {
+            forest: vec![vec![]; n as usize],
+            edge_count: 0,
+        }
+    }
+
+    /// Number of edges of the forest
+    pub fn len(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edge_count == 0
+    }
+
+    /// Number of trees of the forest, isolated vertices included
+    pub fn components(&self) -> usize {
+        self.forest.len() - self.edge_count
+    }
+
+    /// Feed an edge, weighted by its label, self loops are ignored
+    ///
+    /// Panics if an endpoint is not in `[n]`.
+    pub fn feed(&mut self, edge: Edge<VertexId, W>) {
+        let (u, v) = edge.vertices();
+        let (u, v, weight) = (*u, *v, *edge.label());
+        let n = self.forest.len() as VertexId;
+        if u >= n || v >= n {
+            panic!(
+                "[MinimumSpanningForest] ({}, {}) is not an edge of [{}]",
+                u, v, n
+            );
+        }
+        if u == v {
+            return;
+        }
+
+        match self.path(u, v) {
+            None => {
+                self.link(u, v, weight);
+                self.edge_count += 1;
+            }
+            Some(path) => {
+                let (heaviest, (a, b)) = path
+                    .windows(2)
+                    .map(|pair| (self.weight(pair[0], pair[1]), (pair[0], pair[1])))
+                    .max_by_key(|(weight, _)| *weight)
+                    .unwrap();
+                if heaviest > weight {
+                    self.cut(a, b);
+                    self.link(u, v, weight);
+                }
+            }
+        }
+    }
+
+    /// The vertices of the path from `u` to `v` in the forest, `None` if they are in different trees
+    fn path(&self, u: VertexId, v: VertexId) -> Option<Vec<VertexId>> {
+        let mut parents: Vec<Option<VertexId>> = vec![None; self.forest.len()];
+        parents[u as usize] = Some(u);
+
+        let mut stack = vec![u];
+        while let Some(w) = stack.pop() {
+            if w == v {
+                let mut path = vec![v];
+                while *path.last().unwrap() != u {
+                    path.push(parents[*path.last().unwrap() as usize].unwrap());
+                }
+                return Some(path);
+            }
+            for (x, _) in self.forest[w as usize].iter() {
+                if parents[*x as usize].is_none() {
+                    parents[*x as usize] = Some(w);
+                    stack.push(*x);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn weight(&self, u: VertexId, v: VertexId) -> W {
+        self.forest[u as usize]
+            .iter()
+            .find(|(x, _)| *x == v)
+            .map(|(_, weight)| *weight)
+            .unwrap()
+    }
+
+    fn link(&mut self, u: VertexId, v: VertexId, weight: W) {
+        self.forest[u as usize].push((v, weight));
+        self.forest[v as usize].push((u, weight));
+    }
+
+    fn cut(&mut self, u: VertexId, v: VertexId) {
+        self.forest[u as usize].retain(|(x, _)| *x != v);
+        self.forest[v as usize].retain(|(x, _)| *x != u);
+    }
+
+    /// The edges of the forest, `(u, v)` with `u < v`, in increasing order, without consuming the structure
+    pub fn edges(&self) -> Vec<Edge<VertexId, W>> {
+        self.forest
+            .iter()
+            .enumerate()
+            .flat_map(|(u, neighbors)| {
+                let u = u as VertexId;
+                let mut edges: Vec<_> = neighbors
+                    .iter()
+                    .filter(|(v, _)| u < *v)
+                    .map(|(v, weight)| {
+                        let mut edge = Edge::init(u, *v);
+                        edge.update_label(*weight);
+                        edge
+                    })
+                    .collect();
+                edges.sort_unstable_by_key(|edge| *edge.vertices().1);
+                edges
+            })
+            .collect()
+    }
+
+    pub fn query(self) -> Vec<Edge<VertexId, W>> {
+        self.edges()
+    }
+}
+
+impl<W> StreamProcessor<Edge<VertexId, W>, Vec<Edge<VertexId, W>>> for MinimumSpanningForest<W>
+where
+    W: Ord + Copy + Debug + Default,
+{
+    fn feed(&mut self, token: Edge<VertexId, W>) {
+        MinimumSpanningForest::feed(self, token)
+    }
+
+    fn query(self) -> Vec<Edge<VertexId, W>> {
+        MinimumSpanningForest::query(self)
+    }
+}
+
+impl<W> Merge for MinimumSpanningForest<W>
+where
+    W: Ord + Copy + Debug + Default,
+{
+    /// A minimum spanning forest of the union of two streams is one of the union of their minimum spanning forests
+    fn merge(&mut self, other: &Self) {
+        if self.forest.len() != other.forest.len() {
+            panic!("[MinimumSpanningForest] Cannot merge forests of different vertices");
+        }
+        other.edges().into_iter().for_each(|edge| self.feed(edge));
+    }
+}
+
+impl<W> SpaceUsage for MinimumSpanningForest<W> {
+    /// Both endpoints and the weight of each edge of the forest
+    fn space(&self) -> SpaceReport {
+        let n = self.forest.len() as f64;
+        let entry = size_of::<(VertexId, W)>();
+
+        SpaceReport {
+            structure: "MinimumSpanningForest",
+            bound: "O(n log n)",
+            parameters: vec![("n", n), ("edges", self.edge_count as f64)],
+            theoretical_bits: n * log(n),
+            measured_bytes: size_of::<Self>()
+                + self
+                    .forest
+                    .iter()
+                    .map(|neighbors| size_of::<Vec<(VertexId, W)>>() + neighbors.capacity() * entry)
+                    .sum::<usize>(),
+            components: vec![],
+        }
+    }
+}
+
+impl<W> Provenance for MinimumSpanningForest<W> {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "MinimumSpanningForest",
+            paper_ref: "Feigenbaum, Kannan, McGregor, Suri, Zhang, TCS 2005, https://doi.org/10.1016/j.tcs.2005.09.013",
+            params: vec![("n", self.forest.len() as f64)],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::graph::streaming::connectivity::Components;
+
+    fn weighted(u: VertexId, v: VertexId, weight: u64) -> Edge<VertexId, u64> {
+        let mut edge = Edge::init(u, v);
+        edge.update_label(weight);
+        edge
+    }
+
+    /// Weight of a minimum spanning forest, by Kruskal's algorithm
+    fn kruskal(n: VertexId, edges: &[Edge<VertexId, u64>]) -> u64 {
+        let mut edges = edges.to_vec();
+        edges.sort_by_key(|edge| *edge.label());
+
+        let mut components = Components::init(n);
+        edges
+            .iter()
+            .filter(|edge| components.union(*edge.vertices().0, *edge.vertices().1))
+            .map(|edge| *edge.label())
+            .sum()
+    }
+
+    #[test]
+    fn cycle() {
+        let mut forest = MinimumSpanningForest::init(4);
+        forest.feed(weighted(0, 1, 5));
+        forest.feed(weighted(1, 2, 3));
+        forest.feed(weighted(2, 0, 4));
+        assert_eq!((forest.len(), forest.components()), (2, 2));
+
+        // The heaviest edge of the triangle is replaced
+        assert_eq!(forest.query(), vec![weighted(0, 2, 4), weighted(1, 2, 3)]);
+    }
+
+    #[test]
+    fn random_streams() {
+        let n = 40;
+        for seed in 0..5 {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            let edges: Vec<_> = (0..200)
+                .map(|_| {
+                    weighted(
+                        rng.gen_range(0..n),
+                        rng.gen_range(0..n),
+                        rng.gen_range(0..100),
+                    )
+                })
+                .collect();
+
+            let mut forest = MinimumSpanningForest::init(n);
+            edges.iter().for_each(|edge| forest.feed(*edge));
+            let weight: u64 = forest.edges().iter().map(|edge| *edge.label()).sum();
+
+            assert_eq!(weight, kruskal(n, &edges), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn merge() {
+        let n = 30;
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let edges: Vec<_> = (0..150)
+            .map(|_| {
+                weighted(
+                    rng.gen_range(0..n),
+                    rng.gen_range(0..n),
+                    rng.gen_range(0..50),
+                )
+            })
+            .collect();
+
+        let (mut left, mut right) = (
+            MinimumSpanningForest::init(n),
+            MinimumSpanningForest::init(n),
+        );
+        for (i, edge) in edges.iter().enumerate() {
+            if i % 2 == 0 {
+                left.feed(*edge);
+            } else {
+                right.feed(*edge);
+            }
+        }
+        left.merge(&right);
+
+        let weight: u64 = left.query().iter().map(|edge| *edge.label()).sum();
+        assert_eq!(weight, kruskal(n, &edges));
+    }
+}
@@ -1,4 +1,83 @@
 //! Sparse Recovery
 
+use std::collections::HashMap;
+
 pub mod one_sparse;
 pub mod s_sparse;
+
+/// Output of a sparse recovery structure, shared by the [one-sparse](one_sparse::OneSparseRecovery) and
+/// [s-sparse](s_sparse::SparseRecovery) structures
+///
+/// A one-sparse structure recovers its coordinate along with the value, `(j, c)`, and an s-sparse structure the map of every
+/// coordinate of the support to its value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SparseRecoveryOutput<T> {
+    Recovered(T),
+    /// The vector has more nonzero coordinates than the structure recovers
+    NotSparse,
+    /// Every coordinate of the vector is zero
+    Zero,
+    /// Two of the one-sparse structures recovered different values for the same coordinate
+    Inconsistent,
+}
+
+impl<T> SparseRecoveryOutput<T> {
+    /// The recovered output, `None` if nothing was recovered
+    pub fn recovered(self) -> Option<T> {
+        match self {
+            Self::Recovered(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl<T> SparseRecoveryOutput<T>
+where
+    T: Default,
+{
+    pub fn unwrap_or_default(self) -> T {
+        match self {
+            Self::Recovered(x) => x,
+            _ => T::default(),
+        }
+    }
+}
+
+impl SparseRecoveryOutput<HashMap<u64, i64>> {
+    /// The coordinates of positive value, in increasing order, `None` if the vector could not be recovered
+    ///
+    /// In a turnstile stream of edges, these are the edges left once the deletions cancel their insertions. A vector of zeros has
+    /// an empty support.
+    pub fn support(self) -> Option<Vec<u64>> {
+        match self {
+            Self::Recovered(vector) => {
+                let mut support: Vec<u64> = vector
+                    .into_iter()
+                    .filter(|(_, value)| *value > 0)
+                    .map(|(j, _)| j)
+                    .collect();
+                support.sort_unstable();
+                Some(support)
+            }
+            Self::Zero => Some(vec![]),
+            Self::NotSparse | Self::Inconsistent => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn support() {
+        let vector: HashMap<u64, i64> = vec![(4, 2), (1, 1), (3, -1)].into_iter().collect();
+
+        assert_eq!(
+            SparseRecoveryOutput::Recovered(vector).support(),
+            Some(vec![1, 4])
+        );
+        assert_eq!(SparseRecoveryOutput::Zero.support(), Some(vec![]));
+        assert_eq!(SparseRecoveryOutput::NotSparse.support(), None);
+    }
+}
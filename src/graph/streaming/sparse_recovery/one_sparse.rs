@@ -1,30 +1,56 @@
 //! One Sparse Recovery
-use std::fmt::Debug;
+use std::{convert::TryFrom, fmt::Debug, mem::size_of};
 
-use num_primes::Generator;
 use rand::Rng;
 
-use crate::utils::finite_field::{FieldElement, FiniteField};
+use super::SparseRecoveryOutput;
+use crate::{
+    graph::streaming::{
+        space::{log, SpaceReport, SpaceUsage},
+        Merge, StreamProcessor,
+    },
+    utils::finite_field::{FieldElement, FiniteField},
+};
+
+/// Streams a recovery structure accepts
+///
+/// Without deletions, a vector is one-sparse if and only if every token has the same coordinate, so an [InsertOnly](RecoveryMode::InsertOnly)
+/// structure keeps that coordinate and a count instead of fingerprints, skips the finite field arithmetic, and answers exactly.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecoveryMode {
+    /// Insertions and deletions
+    #[default]
+    Turnstile,
+    /// Insertions only, a deletion panics
+    InsertOnly,
+}
 
 /// One Sparse Recovery Data Structure.
 ///
 /// This includes both the Fingerprint values, and the initializing values, including a finite field to person arithmetic within
 ///
 /// This stores its data within constant space, not dependent on the input. O(1) bits
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone)]
 pub struct OneSparseRecovery {
     /// Fingerprint
     l: i64,
-    z: i64,
+    /// Sum of the coordinates, wrapping, so that it is exact whenever the vector is one-sparse
+    z: i128,
     p: FieldElement,
 
     /// Init values
     r: FieldElement,
-    n: u64,
+    n: u128,
     field: FiniteField,
 
+    mode: RecoveryMode,
+    /// Insert only: whether two different coordinates were fed, the coordinate of the first token is kept in `z`
+    mixed: bool,
+
     #[cfg(test)]
-    pub stream: Vec<u64>,
+    pub stream: Vec<u128>,
 }
 
 impl Debug for OneSparseRecovery {
@@ -41,32 +67,31 @@ impl Debug for OneSparseRecovery {
     }
 }
 
-/// Output for a One S
-#[derive(Debug, PartialEq)]
-pub enum OneSparseRecoveryOutput {
-    Zero,
-    /// First element is the value, second element is the index
-    VeryLikely(i64, u64),
-    NotOneSparse,
-}
-
 impl OneSparseRecovery {
     /// Initialize a new `OneSparseRecovery` DS, where the size of our universe is given as `n`.
-    #[allow(clippy::many_single_char_names)]
     pub fn init(n: u64) -> Self {
-        let mut rng = rand::thread_rng();
-        let prime_bits = (3.0 * (n as f64).log2()).ceil() as u64 + 1;
-        // For some reason it cannot find a prime on 11 bits, no idea why?
-        let prime = Generator::new_prime(prime_bits);
-        let order = prime
-            .to_u32_digits()
-            .into_iter()
-            .enumerate()
-            .fold(0, |val, (i, next)| {
-                let digit_value = 32u64.pow(i as u32) * next as u64;
-                val + digit_value
-            });
+        Self::init_wide(n as u128)
+    }
+
+    /// [init](OneSparseRecovery::init) over a universe of `u128` size, whose coordinates are fed by
+    /// [feed_wide](OneSparseRecovery::feed_wide)
+    pub fn init_wide(n: u128) -> Self {
+        let order = FiniteField::for_universe_wide(n).order();
+        Self::init_wide_with_rng(n, order, &mut rand::thread_rng())
+    }
+
+    pub fn init_with_order(n: u64, order: u64) -> Self {
+        Self::init_with_rng(n, order, &mut rand::thread_rng())
+    }
+
+    /// Initialize over a field of the given order, drawing the evaluation point `r` from the generator
+    pub fn init_with_rng<R: Rng + ?Sized>(n: u64, order: u64, rng: &mut R) -> Self {
+        Self::init_wide_with_rng(n as u128, order, rng)
+    }
 
+    /// [init_with_rng](OneSparseRecovery::init_with_rng) over a universe of `u128` size
+    #[allow(clippy::many_single_char_names)]
+    pub fn init_wide_with_rng<R: Rng + ?Sized>(n: u128, order: u64, rng: &mut R) -> Self {
         let r = rng.gen_range(0..order).into();
 
         let (l, z, p) = (0, 0, 0.into());
@@ -78,75 +103,250 @@ impl OneSparseRecovery {
             r,
             n,
             field: FiniteField::new(order),
+            mode: RecoveryMode::Turnstile,
+            mixed: false,
             #[cfg(test)]
             stream: vec![],
         }
     }
 
-    #[allow(clippy::many_single_char_names)]
-    pub fn init_with_order(n: u64, order: u64) -> Self {
-        let mut rng = rand::thread_rng();
+    /// Initialize a structure over the universe `[n]` for a stream without deletions
+    ///
+    /// No finite field is needed, so this is also much cheaper than `init`.
+    pub fn init_insert_only(n: u64) -> Self {
+        Self::init_insert_only_wide(n as u128)
+    }
 
-        let r = rng.gen_range(0..order).into();
+    /// [init_insert_only](OneSparseRecovery::init_insert_only) over a universe of `u128` size
+    pub fn init_insert_only_wide(n: u128) -> Self {
+        OneSparseRecovery {
+            l: 0,
+            z: 0,
+            p: 0.into(),
+            r: 0.into(),
+            n,
+            field: FiniteField::new(2),
+            mode: RecoveryMode::InsertOnly,
+            mixed: false,
+            #[cfg(test)]
+            stream: vec![],
+        }
+    }
 
-        let (l, z, p) = (0, 0, 0.into());
+    pub fn mode(&self) -> RecoveryMode {
+        self.mode
+    }
+
+    /// Random copy; an empty structure over the same universe and finite field, with a new random evaluation point `r`
+    ///
+    /// This is much cheaper than `init` since no new prime needs to be generated.
+    pub fn random_copy(&self) -> Self {
+        if self.mode == RecoveryMode::InsertOnly {
+            return Self::init_insert_only_wide(self.n);
+        }
+        let mut rng = rand::thread_rng();
 
         OneSparseRecovery {
-            l,
-            z,
-            p,
-            r,
-            n,
-            field: FiniteField::new(order),
+            l: 0,
+            z: 0,
+            p: 0.into(),
+            r: rng.gen_range(0..self.field.order()).into(),
+            n: self.n,
+            field: self.field,
+            mode: RecoveryMode::Turnstile,
+            mixed: false,
             #[cfg(test)]
             stream: vec![],
         }
     }
 
+    /// Add the fingerprints of another structure, as if its stream had been fed into this one
+    ///
+    /// Both structures must share `r` and the finite field, i.e. one must be a clone of the other, taken before any token was fed.
+    pub fn combine(&mut self, other: &Self) {
+        if self.mode == RecoveryMode::InsertOnly {
+            if self.l == 0 {
+                self.z = other.z;
+                self.mixed = other.mixed;
+            } else if other.l != 0 {
+                self.mixed |= other.mixed || other.z != self.z;
+            }
+            self.l += other.l;
+
+            #[cfg(test)]
+            self.stream.extend(other.stream.iter());
+            return;
+        }
+
+        self.l += other.l;
+        self.z = self.z.wrapping_add(other.z);
+        self.p = self.field.add(self.p, other.p);
+
+        #[cfg(test)]
+        self.stream.extend(other.stream.iter());
+    }
+
     /// Process a token of some stream into the stream of the `OneSparseRecovery` DS.
     ///
     /// `token = (j, c)`
     ///
     /// Expectations:
     /// 1. `j \in [n]`
-    /// 2. `c \in {-1, 1} - false -> -1; true -> 1`
+    /// 2. `c \in {-1, 1} - false -> -1; true -> 1`, and `c = 1` in the [InsertOnly](RecoveryMode::InsertOnly) mode
     pub fn feed(&mut self, token: (u64, bool)) {
         let (coordinate, value) = token;
-        let value_int = if value { 1 } else { -1 };
-        self.l += value_int;
-        self.z += value_int * coordinate as i64;
+        self.feed_wide((coordinate as u128, value))
+    }
+
+    /// [feed](OneSparseRecovery::feed) a coordinate of a universe of `u128` size, below `2^127`
+    pub fn feed_wide(&mut self, token: (u128, bool)) {
+        let (coordinate, value) = token;
+        self.feed_weighted_wide((coordinate, if value { 1 } else { -1 }))
+    }
 
-        let power = self.field.pow(self.r, coordinate);
+    /// Process a weighted token `(j, Δ)`, adding `Δ` to the coordinate `j`, as `|Δ|` tokens of [feed](OneSparseRecovery::feed) would
+    ///
+    /// `Δ >= 0` in the [InsertOnly](RecoveryMode::InsertOnly) mode.
+    pub fn feed_weighted(&mut self, token: (u64, i64)) {
+        let (coordinate, delta) = token;
+        self.feed_weighted_wide((coordinate as u128, delta))
+    }
+
+    /// [feed_weighted](OneSparseRecovery::feed_weighted) a coordinate of a universe of `u128` size, below `2^127`
+    pub fn feed_weighted_wide(&mut self, token: (u128, i64)) {
+        let (coordinate, delta) = token;
+        if self.mode == RecoveryMode::InsertOnly {
+            if delta < 0 {
+                panic!(
+                    "[OneSparseRecovery] Deletion of {} from an insert only stream",
+                    coordinate
+                );
+            }
+            if delta == 0 {
+                return;
+            }
+
+            if self.l == 0 {
+                self.z = coordinate as i128;
+            } else if self.z != coordinate as i128 {
+                self.mixed = true;
+            }
+            self.l += delta;
+
+            #[cfg(test)]
+            self.stream.push(coordinate);
+            return;
+        }
+
+        self.l += delta;
+        self.z = self
+            .z
+            .wrapping_add((delta as i128).wrapping_mul(coordinate as i128));
+
+        let power = self.field.pow_wide(self.r, coordinate);
 
         #[cfg(test)]
         self.stream.push(coordinate);
 
-        self.p = if value {
-            self.field.add(self.p, power)
-        } else {
-            self.field.add(self.p, self.field.neg(power))
-        };
+        self.p = self
+            .field
+            .add(self.p, self.field.mul(self.field.mod_p_i64(delta), power));
     }
 
     /// Query a `OneSparseRecovery` DS. using the mathematical proof from [lecture notes](https://www.cs.dartmouth.edu/~ac/Teach/CS35-Spring20/Notes/lecnotes.pdf#page=41&zoom=100,96,854)
     /// we know that provided the values of our fingerprints we will reach either guaranteed not-one-sparse or very likely one-sparse
     ///
-    /// This outputs a false positive with probability: O(1/n^2)
-    pub fn query(self) -> OneSparseRecoveryOutput {
+    /// The recovered coordinate comes with its value. This outputs a false positive with probability: O(1/n^2), and never in the
+    /// [InsertOnly](RecoveryMode::InsertOnly) mode
+    pub fn query(self) -> SparseRecoveryOutput<(u64, i64)> {
+        self.query_ref()
+    }
+
+    /// [query](OneSparseRecovery::query) the structure by reference, so that it can be queried mid-stream and fed further
+    ///
+    /// A coordinate recovered beyond the `u64`s, which a structure fed by [feed](OneSparseRecovery::feed) never holds, is a
+    /// false positive, and the vector is reported as not sparse.
+    pub fn query_ref(&self) -> SparseRecoveryOutput<(u64, i64)> {
+        match self.query_wide() {
+            SparseRecoveryOutput::Recovered((j, l)) => match u64::try_from(j) {
+                Ok(j) => SparseRecoveryOutput::Recovered((j, l)),
+                Err(_) => SparseRecoveryOutput::NotSparse,
+            },
+            SparseRecoveryOutput::NotSparse => SparseRecoveryOutput::NotSparse,
+            SparseRecoveryOutput::Zero => SparseRecoveryOutput::Zero,
+            SparseRecoveryOutput::Inconsistent => SparseRecoveryOutput::Inconsistent,
+        }
+    }
+
+    /// [query](OneSparseRecovery::query) the structure by reference, for a coordinate of a universe of `u128` size
+    ///
+    /// The coordinate is `z / l`, which must be a nonnegative integer, checked against the fingerprint `p`.
+    pub fn query_wide(&self) -> SparseRecoveryOutput<(u128, i64)> {
         let Self {
-            l, z, p, r, field, ..
-        } = self;
-        if p == 0 && z == 0 && l == z {
-            OneSparseRecoveryOutput::Zero
+            l,
+            z,
+            p,
+            r,
+            field,
+            mode,
+            mixed,
+            ..
+        } = *self;
+        if mode == RecoveryMode::InsertOnly {
+            return match (l, mixed) {
+                (0, _) => SparseRecoveryOutput::Zero,
+                (_, true) => SparseRecoveryOutput::NotSparse,
+                (_, false) => SparseRecoveryOutput::Recovered((z as u128, l)),
+            };
+        }
+        if p == 0 && z == 0 && l == 0 {
+            return SparseRecoveryOutput::Zero;
+        }
+        if l == 0 || z % l as i128 != 0 || z / (l as i128) < 0 {
+            return SparseRecoveryOutput::NotSparse;
+        }
+
+        let coordinate = (z / l as i128) as u128;
+        if p != field.mul(field.mod_p_i64(l), field.pow_wide(r, coordinate)) {
+            SparseRecoveryOutput::NotSparse
         } else {
-            let divided = (z as f32) / (l as f32);
-            if (divided.round() - divided).abs() > f32::EPSILON
-                || p != field.mul(field.mod_p_i64(l), field.pow(r, divided.round() as u64))
-            {
-                OneSparseRecoveryOutput::NotOneSparse
-            } else {
-                OneSparseRecoveryOutput::VeryLikely(l, divided.round() as u64)
-            }
+            SparseRecoveryOutput::Recovered((coordinate, l))
+        }
+    }
+}
+
+impl StreamProcessor<(u64, bool), SparseRecoveryOutput<(u64, i64)>> for OneSparseRecovery {
+    fn feed(&mut self, token: (u64, bool)) {
+        OneSparseRecovery::feed(self, token)
+    }
+
+    fn query(self) -> SparseRecoveryOutput<(u64, i64)> {
+        OneSparseRecovery::query(self)
+    }
+}
+
+impl Merge for OneSparseRecovery {
+    fn merge(&mut self, other: &Self) {
+        if self.mode != other.mode {
+            panic!("[OneSparseRecovery] Cannot merge structures of different modes");
+        }
+        if self.r != other.r || self.field.order() != other.field.order() {
+            panic!("[OneSparseRecovery] Cannot merge structures with different evaluation points");
+        }
+        self.combine(other);
+    }
+}
+
+impl SpaceUsage for OneSparseRecovery {
+    /// Three fingerprints, the largest in a field of order `O(n^3)`
+    fn space(&self) -> SpaceReport {
+        SpaceReport {
+            structure: "OneSparseRecovery",
+            bound: "O(log n)",
+            parameters: vec![("n", self.n as f64)],
+            theoretical_bits: log(self.n as f64),
+            measured_bytes: size_of::<Self>(),
+            components: vec![],
         }
     }
 }
@@ -181,7 +381,7 @@ mod test {
 
         let res = recover.query();
 
-        assert_eq!(res, OneSparseRecoveryOutput::VeryLikely(1, 6))
+        assert_eq!(res, SparseRecoveryOutput::Recovered((6, 1)))
     }
 
     #[test]
@@ -209,7 +409,7 @@ mod test {
 
         let res = recover.query();
 
-        assert_eq!(res, OneSparseRecoveryOutput::Zero)
+        assert_eq!(res, SparseRecoveryOutput::Zero)
     }
 
     #[test]
@@ -230,6 +430,100 @@ mod test {
 
         let res = recover.query();
 
-        assert_eq!(res, OneSparseRecoveryOutput::NotOneSparse)
+        assert_eq!(res, SparseRecoveryOutput::NotSparse)
+    }
+
+    #[test]
+    fn combine() {
+        let mut left = OneSparseRecovery::init(10);
+        let mut right = left.clone();
+
+        left.feed((3, true));
+        left.feed((7, true));
+        right.feed((7, false));
+
+        left.combine(&right);
+        assert_eq!(left.query(), SparseRecoveryOutput::Recovered((3, 1)));
+    }
+
+    #[test]
+    fn insert_only() {
+        let mut recover = OneSparseRecovery::init_insert_only(10);
+        assert_eq!(recover.query_ref(), SparseRecoveryOutput::Zero);
+
+        (0..3).for_each(|_| recover.feed((6, true)));
+        assert_eq!(recover.query_ref(), SparseRecoveryOutput::Recovered((6, 3)));
+
+        let mut other = recover.random_copy();
+        other.feed((2, true));
+        recover.merge(&other);
+        assert_eq!(recover.query(), SparseRecoveryOutput::NotSparse);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_only_deletion() {
+        let mut recover = OneSparseRecovery::init_insert_only(10);
+        recover.feed((6, true));
+        recover.feed((6, false));
+    }
+
+    #[test]
+    #[should_panic]
+    fn merge_independent() {
+        let mut left = OneSparseRecovery::init_with_order(10, 1009);
+        let mut right = left.random_copy();
+        while right.r == left.r {
+            right = left.random_copy();
+        }
+
+        left.merge(&right);
+    }
+
+    #[test]
+    fn large_coordinates() {
+        // Beyond the integers a float holds exactly
+        let mut recover = OneSparseRecovery::init(1 << 50);
+        recover.feed(((1 << 40) + 3, true));
+        recover.feed(((1 << 45) + 1, true));
+        recover.feed(((1 << 45) + 1, false));
+        assert_eq!(
+            recover.query_ref(),
+            SparseRecoveryOutput::Recovered(((1 << 40) + 3, 1))
+        );
+
+        let mut wide = OneSparseRecovery::init_wide(1 << 100);
+        (0..3).for_each(|_| wide.feed_wide(((1 << 90) + 7, true)));
+        assert_eq!(
+            wide.query_wide(),
+            SparseRecoveryOutput::Recovered(((1 << 90) + 7, 3))
+        );
+        // Which does not fit the coordinates of a u64
+        assert_eq!(wide.query_ref(), SparseRecoveryOutput::NotSparse);
+
+        wide.feed_wide((5, true));
+        assert_eq!(wide.query_wide(), SparseRecoveryOutput::NotSparse);
+    }
+
+    #[test]
+    fn weighted() {
+        let mut recover = OneSparseRecovery::init(10);
+        let mut repeated = recover.clone();
+
+        recover.feed_weighted((4, 7));
+        recover.feed_weighted((2, -3));
+        recover.feed_weighted((2, 3));
+        (0..7).for_each(|_| repeated.feed((4, true)));
+        assert_eq!(recover.p, repeated.p);
+        assert_eq!(recover.query_ref(), SparseRecoveryOutput::Recovered((4, 7)));
+
+        recover.feed_weighted((4, -9));
+        assert_eq!(recover.query(), SparseRecoveryOutput::Recovered((4, -2)));
+
+        let mut insert_only = OneSparseRecovery::init_insert_only(10);
+        insert_only.feed_weighted((6, 0));
+        assert_eq!(insert_only.query_ref(), SparseRecoveryOutput::Zero);
+        insert_only.feed_weighted((6, 4));
+        assert_eq!(insert_only.query(), SparseRecoveryOutput::Recovered((6, 4)));
     }
 }
@@ -1,20 +1,41 @@
 //! Generalized `s`-Sparse Recovery
 
-use super::one_sparse::{OneSparseRecovery, OneSparseRecoveryOutput};
-use crate::{graph::streaming::Query, printdur, start_dur, utils::hash_function::HashFunction};
-use num_primes::Generator;
-
-use std::{collections::HashMap, fmt::Debug};
+use super::{
+    one_sparse::{OneSparseRecovery, RecoveryMode},
+    SparseRecoveryOutput,
+};
+use crate::{
+    graph::streaming::{
+        provenance::{AlgorithmInfo, Provenance},
+        space::{log, SpaceReport, SpaceUsage},
+        Merge, Sketch, StreamProcessor,
+    },
+    logdur, start_dur,
+    utils::{finite_field::FiniteField, hash_function::HashFunction},
+};
+use log::trace;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use std::{collections::HashMap, convert::TryFrom, fmt::Debug, mem::size_of};
 
 /// `S`-Sparse Recovery Data Structure
 ///
 /// Algorithm for recovery and detection is based off of [Algorithm 15](https://www.cs.dartmouth.edu/~ac/Teach/CS35-Spring20/Notes/lecnotes.pdf)
 ///
 /// Storage: O(tlog(t) + tlog(n))
+///
+/// Without deletions, see [init_insert_only](SparseRecovery::init_insert_only), the coordinates are stored directly, up to `s` of
+/// them, in O(s log n) bits.
+///
+/// Universes beyond the `u64`s, e.g. the edges of a graph of more than `2^32` vertices indexed by
+/// [to_d1_wide](crate::graph::Edge::to_d1_wide), are handled by [init_wide](SparseRecovery::init_wide), with hash functions
+/// that [compute_wide](HashFunction::compute_wide) over them, such as the [PolynomialHasher](crate::utils::hash_function::PolynomialHasher).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone)]
 pub struct SparseRecovery<F: HashFunction> {
     /// The domain of the sparse recover structure
-    n: u64,
+    n: u128,
     /// Sparsity Parameter
     ///
     /// Constant Space
@@ -26,9 +47,17 @@ pub struct SparseRecovery<F: HashFunction> {
     /// Hash Functions for hashing to the Sparse recovery systems
     /// Store O(t * HF bits)
     functions: Vec<F>,
-    /// One sparse recovery order calculation,
-    // this helps speed up finding a prime number for the OneSparseRecover finite field
+    /// Order of the finite field of the one sparse recoveries, see [FiniteField::for_universe]
     order: u64,
+    /// Seed of the evaluation points of the one sparse recoveries, which are created on their first token
+    ///
+    /// Clones share it, so that the recoveries of a bucket agree across clones and can be merged.
+    seed: u64,
+    mode: RecoveryMode,
+    /// Insert only: the count of each coordinate, until more than `s` coordinates were fed
+    coordinates: HashMap<u128, i64>,
+    /// Insert only: whether more than `s` coordinates were fed
+    overflow: bool,
 }
 
 impl<F: HashFunction> Debug for SparseRecovery<F> {
@@ -37,26 +66,6 @@ impl<F: HashFunction> Debug for SparseRecovery<F> {
     }
 }
 
-#[derive(Debug)]
-pub enum SparseRecoveryOutput<T> {
-    Pass(T),
-    NotSSparse,
-    Empty,
-    InConsistent,
-}
-
-impl<T> SparseRecoveryOutput<T>
-where
-    T: Default,
-{
-    pub fn unwrap_or_default(self) -> T {
-        match self {
-            Self::Pass(x) => x,
-            _ => T::default(),
-        }
-    }
-}
-
 impl<F> SparseRecovery<F>
 where
     F: HashFunction,
@@ -67,78 +76,200 @@ where
     /// - *s* : Sparsity we wish to detect
     /// - *del* : Error probability controller
     pub fn init(n: u64, s: u64, del: f32) -> Self {
-        let mut s = s;
-        if n < s {
-            s = n
-        }
+        Self::init_wide(n as u128, s, del)
+    }
+
+    /// [init](SparseRecovery::init) over a universe of `u128` size, whose coordinates are fed by
+    /// [feed_wide](SparseRecovery::feed_wide)
+    pub fn init_wide(n: u128, s: u64, del: f32) -> Self {
+        let s = (s as u128).min(n) as u64;
 
         let t = (s as f32 / del).log2().ceil() as u64;
 
-        let order = {
-            let prime_bits = (3_f64 * (n as f64).log2()).ceil() as u64 + 1;
-            let prime = Generator::new_prime(prime_bits);
-            prime
-                .to_u32_digits()
-                .into_iter()
-                .enumerate()
-                .fold(0, |val, (i, next)| {
-                    let digit_value = 32u64.pow(i as u32) * next as u64;
-                    val + digit_value
-                })
-        };
+        let order = FiniteField::for_universe_wide(n).order();
 
         let n_pow = n.next_power_of_two();
         let s_pow = (2 * s).next_power_of_two();
 
-        println!("n: {:?} -> s: {:?}, t: {}", n_pow, s_pow, t);
-
-        // println!("S-Sparse Setup: t: [{}], [{}] -> [{}]", t, n_pow, s_pow);
-        let start = start_dur!();
+        trace!(
+            "[SparseRecovery] n: {:?} -> s: {:?}, t: {}",
+            n_pow,
+            s_pow,
+            t
+        );
 
         let structures = (0..t).into_iter().map(|_| HashMap::new()).collect();
 
-        // printdur!("Structured", start);
         let start = start_dur!();
-
-        let hash_base = F::init(n_pow, s_pow);
-        printdur!("Hash Base", start);
+        let hash_base = F::init_wide(n_pow, s_pow);
+        logdur!("[SparseRecovery] Hash base", start);
         let functions = (0..t)
             .into_iter()
             .map(|_| hash_base.random_copy())
             .collect();
 
-        // printdur!("Functions", start);
+        Self {
+            n,
+            s,
+            structures,
+            functions,
+            order,
+            seed: rand::random(),
+            mode: RecoveryMode::Turnstile,
+            coordinates: HashMap::new(),
+            overflow: false,
+        }
+    }
 
+    /// Bytes held by a structure of these parameters once every bucket of every row holds a recovery
+    ///
+    /// An upper bound on its [measured](SpaceUsage::space) space, to size a structure against a memory budget before building it.
+    pub fn bytes_bound(n: u64, s: u64, del: f32) -> usize {
+        let s = s.min(n);
+        let t = (s as f32 / del).log2().ceil() as usize;
+        let buckets = (2 * s).next_power_of_two() as usize;
+
+        // The map of a row holding `b` recoveries has room for at most `2b`
+        size_of::<Self>()
+            + t * (size_of::<HashMap<u64, OneSparseRecovery>>()
+                + size_of::<F>()
+                + 2 * buckets * size_of::<(u64, OneSparseRecovery)>())
+    }
+
+    /// Initialize a structure for a stream without deletions, which needs neither hash functions nor one sparse recoveries
+    ///
+    /// The recovery is exact, and a deletion panics.
+    pub fn init_insert_only(n: u64, s: u64) -> Self {
+        Self::init_insert_only_wide(n as u128, s)
+    }
+
+    /// [init_insert_only](SparseRecovery::init_insert_only) over a universe of `u128` size
+    pub fn init_insert_only_wide(n: u128, s: u64) -> Self {
         Self {
+            n,
+            s: (s as u128).min(n) as u64,
+            structures: vec![],
+            functions: vec![],
+            order: 0,
+            seed: 0,
+            mode: RecoveryMode::InsertOnly,
+            coordinates: HashMap::new(),
+            overflow: false,
+        }
+    }
+
+    pub fn mode(&self) -> RecoveryMode {
+        self.mode
+    }
+
+    /// Insert only: count `count` more occurrences of the coordinate `j`
+    fn insert(&mut self, j: u128, count: i64) {
+        if self.overflow {
+            return;
+        }
+        if !self.coordinates.contains_key(&j) && self.coordinates.len() as u64 >= self.s {
+            self.overflow = true;
+            self.coordinates = HashMap::new();
+            return;
+        }
+        *self.coordinates.entry(j).or_default() += count;
+    }
+
+    /// The sparsity `s` the structure recovers up to
+    pub fn sparsity(&self) -> u64 {
+        self.s
+    }
+
+    /// Random copy; copy the structure, using identical domain, sparsity and finite field order, but initialize new hash functions
+    ///
+    /// This is much cheaper than `init` since no new prime needs to be generated.
+    pub fn random_copy(&self) -> Self {
+        if self.mode == RecoveryMode::InsertOnly {
+            return Self::init_insert_only_wide(self.n, self.s);
+        }
+        let Self {
             n,
             s,
             structures,
             functions,
             order,
+            ..
+        } = self;
+
+        Self {
+            n: *n,
+            s: *s,
+            structures: structures.iter().map(|_| HashMap::new()).collect(),
+            functions: functions.iter().map(|f| f.random_copy()).collect(),
+            order: *order,
+            seed: rand::random(),
+            mode: RecoveryMode::Turnstile,
+            coordinates: HashMap::new(),
+            overflow: false,
         }
     }
 
     /// Feed a token into the Structure
     pub fn feed(&mut self, token: (u64, bool)) {
+        let (j, c) = token;
+        self.feed_wide((j as u128, c))
+    }
+
+    /// [feed](SparseRecovery::feed) a coordinate of a universe of `u128` size
+    pub fn feed_wide(&mut self, token: (u128, bool)) {
+        let (j, c) = token;
+        self.feed_weighted_wide((j, if c { 1 } else { -1 }))
+    }
+
+    /// Feed a weighted token `(j, Δ)`, e.g. the multiplicity of an edge of a multigraph, in place of `|Δ|` repeated tokens
+    pub fn feed_weighted(&mut self, token: (u64, i64)) {
+        let (j, delta) = token;
+        self.feed_weighted_wide((j as u128, delta))
+    }
+
+    /// [feed_weighted](SparseRecovery::feed_weighted) a coordinate of a universe of `u128` size
+    pub fn feed_weighted_wide(&mut self, token: (u128, i64)) {
+        if self.mode == RecoveryMode::InsertOnly {
+            let (j, delta) = token;
+            if delta < 0 {
+                panic!(
+                    "[SparseRecovery] Deletion of {} from an insert only stream",
+                    j
+                );
+            }
+            if delta != 0 {
+                self.insert(j, delta);
+            }
+            return;
+        }
+
         let Self {
             structures,
             functions,
             n,
+            s,
             order,
+            seed,
             ..
         } = self;
         let (j, _) = token;
+        let buckets = (2 * *s).next_power_of_two();
 
         structures
             .iter_mut()
             .zip(functions.iter())
             .enumerate()
-            .for_each(|(_, (recoveries, hasher))| {
-                let hashed_index = hasher.compute(j);
+            .for_each(|(row, (recoveries, hasher))| {
+                let hashed_index = hasher.compute_wide(j);
                 recoveries
                     .entry(hashed_index)
-                    .or_insert_with(|| OneSparseRecovery::init_with_order(*n, *order))
-                    .feed(token)
+                    .or_insert_with(|| {
+                        // Each bucket draws its evaluation point from its own stream of the seed
+                        let mut rng = ChaCha20Rng::seed_from_u64(*seed);
+                        rng.set_stream(row as u64 * buckets + hashed_index);
+                        OneSparseRecovery::init_wide_with_rng(*n, *order, &mut rng)
+                    })
+                    .feed_weighted_wide(token)
             });
     }
 
@@ -148,25 +279,59 @@ where
     ///
     /// If the stream was not s-sparse, or if one of the one-sparse recovery systems got an answer wrong, then we return `None`.
     pub fn query(self) -> SparseRecoveryOutput<HashMap<u64, i64>> {
+        self.query_ref()
+    }
+
+    /// [query](SparseRecovery::query) the structure by reference, so that it can be queried mid-stream and fed further
+    ///
+    /// A coordinate recovered beyond the `u64`s, which a structure fed by [feed](SparseRecovery::feed) never holds, is a false
+    /// positive, and the vector is reported as not sparse.
+    pub fn query_ref(&self) -> SparseRecoveryOutput<HashMap<u64, i64>> {
+        match self.query_wide() {
+            SparseRecoveryOutput::Recovered(recovery) => recovery
+                .into_iter()
+                .map(|(j, value)| u64::try_from(j).map(|j| (j, value)))
+                .collect::<Result<_, _>>()
+                .map_or(
+                    SparseRecoveryOutput::NotSparse,
+                    SparseRecoveryOutput::Recovered,
+                ),
+            SparseRecoveryOutput::NotSparse => SparseRecoveryOutput::NotSparse,
+            SparseRecoveryOutput::Zero => SparseRecoveryOutput::Zero,
+            SparseRecoveryOutput::Inconsistent => SparseRecoveryOutput::Inconsistent,
+        }
+    }
+
+    /// [query](SparseRecovery::query) the structure by reference, for the coordinates of a universe of `u128` size
+    pub fn query_wide(&self) -> SparseRecoveryOutput<HashMap<u128, i64>> {
+        if self.mode == RecoveryMode::InsertOnly {
+            return if self.overflow {
+                SparseRecoveryOutput::NotSparse
+            } else if self.coordinates.is_empty() {
+                SparseRecoveryOutput::Zero
+            } else {
+                SparseRecoveryOutput::Recovered(self.coordinates.clone())
+            };
+        }
+
         let mut recovery = HashMap::new();
 
         let mut can_return = false;
 
-        for (_, row) in self.structures.into_iter().enumerate() {
-            for (_, (_, cell)) in row.into_iter().enumerate() {
-                let res = cell.query();
-                match res {
-                    OneSparseRecoveryOutput::VeryLikely(lambda, i) => {
+        for row in self.structures.iter() {
+            for cell in row.values() {
+                match cell.query_wide() {
+                    SparseRecoveryOutput::Recovered((i, lambda)) => {
                         if recovery
                             .get(&i)
                             .map(|val| val != &lambda)
                             .unwrap_or_default()
                         {
-                            return SparseRecoveryOutput::InConsistent;
+                            return SparseRecoveryOutput::Inconsistent;
                         }
                         recovery.insert(i, lambda);
                         if recovery.keys().len() > self.s as usize {
-                            return SparseRecoveryOutput::NotSSparse;
+                            return SparseRecoveryOutput::NotSparse;
                         }
                         can_return = true
                     }
@@ -175,9 +340,122 @@ where
             }
         }
         if can_return {
-            SparseRecoveryOutput::Pass(recovery)
+            SparseRecoveryOutput::Recovered(recovery)
         } else {
-            SparseRecoveryOutput::Empty
+            SparseRecoveryOutput::Zero
+        }
+    }
+}
+
+impl<F> Sketch for SparseRecovery<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        SparseRecovery::feed(self, token)
+    }
+}
+
+impl<F> Merge for SparseRecovery<F>
+where
+    F: HashFunction,
+{
+    /// Buckets fed by either structure are merged, or copied from `other` if only it fed them
+    fn merge(&mut self, other: &Self) {
+        if self.mode != other.mode {
+            panic!("[SparseRecovery] Cannot merge structures of different modes");
+        }
+        if self.mode == RecoveryMode::InsertOnly {
+            self.overflow |= other.overflow;
+            for (j, count) in other.coordinates.iter() {
+                self.insert(*j, *count);
+            }
+            return;
+        }
+
+        if self.seed != other.seed || self.structures.len() != other.structures.len() {
+            panic!("[SparseRecovery] Cannot merge structures with different random components");
+        }
+
+        for (row, other_row) in self.structures.iter_mut().zip(other.structures.iter()) {
+            for (bucket, cell) in other_row.iter() {
+                match row.get_mut(bucket) {
+                    Some(existing) => existing.merge(cell),
+                    None => {
+                        row.insert(*bucket, cell.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<F> StreamProcessor<(u64, bool), SparseRecoveryOutput<HashMap<u64, i64>>> for SparseRecovery<F>
+where
+    F: HashFunction,
+{
+    fn feed(&mut self, token: (u64, bool)) {
+        SparseRecovery::feed(self, token)
+    }
+
+    fn query(self) -> SparseRecoveryOutput<HashMap<u64, i64>> {
+        SparseRecovery::query(self)
+    }
+}
+
+impl<F> SpaceUsage for SparseRecovery<F>
+where
+    F: HashFunction,
+{
+    /// `t = log(s/δ)` rows of `O(s)` one-sparse recoveries
+    fn space(&self) -> SpaceReport {
+        let t = self.structures.len();
+        let cells: usize = self
+            .structures
+            .iter()
+            .map(|row| row.capacity() * size_of::<(u64, OneSparseRecovery)>())
+            .sum();
+
+        if self.mode == RecoveryMode::InsertOnly {
+            return SpaceReport {
+                structure: "SparseRecovery",
+                bound: "O(s log n)",
+                parameters: vec![("n", self.n as f64), ("s", self.s as f64)],
+                theoretical_bits: self.s as f64 * log(self.n as f64),
+                measured_bytes: size_of::<Self>()
+                    + self.coordinates.capacity() * size_of::<(u128, i64)>(),
+                components: vec![],
+            };
+        }
+
+        SpaceReport {
+            structure: "SparseRecovery",
+            bound: "O(s log(s/δ) · log n)",
+            parameters: vec![("n", self.n as f64), ("s", self.s as f64), ("t", t as f64)],
+            theoretical_bits: self.s as f64 * t as f64 * log(self.n as f64),
+            measured_bytes: size_of::<Self>()
+                + self.structures.capacity() * size_of::<HashMap<u64, OneSparseRecovery>>()
+                + cells
+                + self.functions.capacity() * size_of::<F>(),
+            components: vec![],
+        }
+    }
+}
+
+impl<F> Provenance for SparseRecovery<F>
+where
+    F: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "SparseRecovery",
+            paper_ref: "Chakrabarti, CS35 Lecture Notes, https://www.cs.dartmouth.edu/~ac/Teach/CS35-Spring20/Notes/lecnotes.pdf",
+            params: vec![
+                ("n", self.n as f64),
+                ("s", self.s as f64),
+                ("t", self.structures.len() as f64),
+            ],
+            seed: None,
         }
     }
 }
@@ -186,7 +464,11 @@ where
 mod test {
     use std::collections::HashSet;
 
-    use crate::utils::hash_function::PowerFiniteFieldHasher;
+    use crate::{
+        graph::{Edge, VertexId},
+        printdur,
+        utils::hash_function::{PolynomialHasher, PowerFiniteFieldHasher},
+    };
 
     use super::*;
 
@@ -218,7 +500,7 @@ mod test {
 
         for _ in 0..n {
             let res = large_not_sparse();
-            if matches!(res, SparseRecoveryOutput::Pass(_)) {
+            if matches!(res, SparseRecoveryOutput::Recovered(_)) {
                 incorrect += 1;
             }
         }
@@ -235,7 +517,7 @@ mod test {
 
         for _ in 0..n {
             let res = large_sparse();
-            if !matches!(res, SparseRecoveryOutput::Pass(_)) {
+            if !matches!(res, SparseRecoveryOutput::Recovered(_)) {
                 incorrect += 1;
             }
         }
@@ -254,4 +536,191 @@ mod test {
 
         println!("{:?}", recovery.query())
     }
+
+    #[test]
+    fn merge() {
+        let base = SparseRecovery::<PowerFiniteFieldHasher>::init(5000, 100, 0.01);
+        let (mut left, mut right) = (base.clone(), base);
+
+        (0..30).for_each(|token| {
+            if token % 2 == 0 {
+                left.feed((token, true))
+            } else {
+                right.feed((token, true))
+            }
+        });
+        // Deleted on the other side of the split
+        right.feed((0, false));
+
+        left.merge(&right);
+        let recovered: HashSet<u64> = match left.query() {
+            SparseRecoveryOutput::Recovered(support) => support
+                .into_iter()
+                .filter(|(_, c)| *c != 0)
+                .map(|(j, _)| j)
+                .collect(),
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(recovered, (1..30).collect());
+    }
+
+    #[test]
+    fn bytes_bound() {
+        let (n, s) = (5000, 100);
+        let mut recovery = SparseRecovery::<PowerFiniteFieldHasher>::init(n, s, 0.01);
+        (0..n).for_each(|j| recovery.feed((j, true)));
+
+        let bound = SparseRecovery::<PowerFiniteFieldHasher>::bytes_bound(n, s, 0.01);
+        assert!(recovery.space().measured_bytes <= bound);
+        assert!(bound < SparseRecovery::<PowerFiniteFieldHasher>::bytes_bound(n, 2 * s, 0.01));
+    }
+
+    #[test]
+    fn mid_stream() {
+        let mut recovery = SparseRecovery::<PowerFiniteFieldHasher>::init(5000, 100, 0.01);
+
+        (0..50).for_each(|token| recovery.feed((token * 7, true)));
+        match recovery.query_ref() {
+            SparseRecoveryOutput::Recovered(support) => assert_eq!(support.len(), 50),
+            other => panic!("{:?}", other),
+        }
+
+        // Deleting half of the support after the first query
+        (0..25).for_each(|token| recovery.feed((token * 7, false)));
+        match recovery.query_ref() {
+            SparseRecoveryOutput::Recovered(support) => {
+                assert_eq!(support, (25..50).map(|j| (j * 7, 1)).collect())
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn wide_edges() {
+        // The edges of a graph of 2^40 vertices, whose indices take 79 bits
+        let n = Edge::<VertexId, ()>::init(0, 1 << 40).to_d1_wide();
+        let mut recovery = SparseRecovery::<PolynomialHasher>::init_wide(n, 20, 0.01);
+
+        let edges: Vec<Edge<VertexId, ()>> = (0..30)
+            .map(|i| Edge::init(i * 1_000_003, (1 << 40) - 1 - i))
+            .collect();
+        edges
+            .iter()
+            .for_each(|e| recovery.feed_wide((e.to_d1_wide(), true)));
+        edges[..15]
+            .iter()
+            .for_each(|e| recovery.feed_wide((e.to_d1_wide(), false)));
+
+        let recovered: HashSet<(VertexId, VertexId)> = match recovery.query_wide() {
+            SparseRecoveryOutput::Recovered(support) => support
+                .into_iter()
+                .filter(|(_, c)| *c != 0)
+                .map(|(j, _)| {
+                    let edge = Edge::<VertexId, ()>::from_d1_wide(j);
+                    let (u, v) = edge.vertices();
+                    (*u, *v)
+                })
+                .collect(),
+            other => panic!("{:?}", other),
+        };
+        let expected = edges[15..]
+            .iter()
+            .map(|e| {
+                let (u, v) = e.vertices();
+                (*u, *v)
+            })
+            .collect();
+        assert_eq!(recovered, expected);
+    }
+
+    #[test]
+    fn insert_only() {
+        let mut recovery = SparseRecovery::<PowerFiniteFieldHasher>::init_insert_only(5000, 100);
+        assert!(matches!(recovery.query_ref(), SparseRecoveryOutput::Zero));
+
+        (0..90).for_each(|token| recovery.feed((token % 45, true)));
+        match recovery.query_ref() {
+            SparseRecoveryOutput::Recovered(support) => {
+                assert_eq!(support, (0..45).map(|j| (j, 2)).collect())
+            }
+            other => panic!("{:?}", other),
+        }
+
+        let mut other = recovery.random_copy();
+        (45..101).for_each(|token| other.feed((token, true)));
+        recovery.merge(&other);
+        assert!(matches!(recovery.query(), SparseRecoveryOutput::NotSparse));
+    }
+
+    /// Time of the turnstile and insert only structures on the same insertions
+    #[test]
+    #[ignore]
+    fn insert_only_speed() {
+        let (n, s) = (1_000_000, 1000);
+
+        let start = start_dur!();
+        let mut recovery = SparseRecovery::<PowerFiniteFieldHasher>::init(n, s, 0.01);
+        (0..s).for_each(|token| recovery.feed((token * 997 % n, true)));
+        assert!(matches!(
+            recovery.query(),
+            SparseRecoveryOutput::Recovered(_)
+        ));
+        printdur!("Turnstile", start);
+
+        let mut recovery = SparseRecovery::<PowerFiniteFieldHasher>::init_insert_only(n, s);
+        (0..s).for_each(|token| recovery.feed((token * 997 % n, true)));
+        assert!(matches!(
+            recovery.query(),
+            SparseRecoveryOutput::Recovered(_)
+        ));
+        printdur!("Insert only", start);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn checkpoint() {
+        let mut recovery = SparseRecovery::<PowerFiniteFieldHasher>::init(5000, 100, 0.01);
+        (0..15).for_each(|token| recovery.feed((token, true)));
+
+        let checkpoint = serde_json::to_string(&recovery).unwrap();
+        let mut resumed: SparseRecovery<PowerFiniteFieldHasher> =
+            serde_json::from_str(&checkpoint).unwrap();
+
+        (15..30).for_each(|token| {
+            recovery.feed((token, true));
+            resumed.feed((token, true));
+        });
+
+        match (recovery.query(), resumed.query()) {
+            (
+                SparseRecoveryOutput::Recovered(original),
+                SparseRecoveryOutput::Recovered(resumed),
+            ) => {
+                assert_eq!(original, resumed)
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn weighted() {
+        // Edges of a multigraph, with their multiplicities
+        let mut recovery = SparseRecovery::<PowerFiniteFieldHasher>::init(5000, 20, 0.01);
+        (0..10).for_each(|j| recovery.feed_weighted((j * 37, j as i64 + 1)));
+        recovery.feed_weighted((0, -1));
+        recovery.feed_weighted((37, -1));
+
+        let mut expected: HashMap<u64, i64> = (1..10).map(|j| (j * 37, j as i64 + 1)).collect();
+        expected.insert(37, 1);
+        match recovery.query() {
+            SparseRecoveryOutput::Recovered(support) => assert_eq!(
+                support
+                    .into_iter()
+                    .filter(|(_, c)| *c != 0)
+                    .collect::<HashMap<_, _>>(),
+                expected
+            ),
+            other => panic!("{:?}", other),
+        }
+    }
 }
@@ -0,0 +1,528 @@
+//! Streaming Cut Sparsification
+//!
+//! The sparsifier of [Ahn, Guha and McGregor](https://doi.org/10.1145/2213556.2213560) samples every edge by its connectivity. The
+//! graph `G_i` keeps each edge with probability `2^-i`, nested so that `G_{i+1} ⊆ G_i`, and a [KConnectivity] certificate `H_i`
+//! of `G_i` is kept for every level. An edge `e` is kept with weight `2^i` for the first level `i` at which its endpoints are less
+//! than `k`-edge-connected in `H_i`, if `e` is in `H_i`. With `k = O(ε^-2 log n)`, every cut is preserved within `1 ± ε` with high
+//! probability.
+//!
+//! The weights are integers, so the sparsifier can be given directly to the [MinCut] routines. Its [CutQuery] is the weight of the
+//! cut in the sparsifier.
+//!
+//! On insertion only streams, the [SpectralStreamSparsifier] keeps a spectral sparsifier, which also preserves every cut, by merge
+//! and reduce: the stream is cut into blocks, every block is sparsified as it fills up, and two sparsifiers of the same level are
+//! merged and sparsified again into one of the next level, as the carries of a binary counter. A sparsifier of level `l` has
+//! been sparsified `l` times, so every level uses the error `ε / L`, `L` the largest level, and the errors compound to
+//! `(1 ± ε / L)^L ≈ 1 ± ε`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    mem::size_of,
+};
+
+use algebraics::traits::CeilLog2;
+use num_integer::binomial;
+use rand::Rng;
+
+use crate::{
+    graph::{
+        static_a::{
+            cut::MinCut,
+            spectral::{spectral_sparsify, EdgeWeights, SpectralParameters},
+        },
+        streaming::{
+            connectivity::{CutQuery, KConnectivity, UnitFlow},
+            provenance::{AlgorithmInfo, Provenance},
+            space::{log, SpaceReport, SpaceUsage},
+            StreamProcessor,
+        },
+        Edge, Graph, Graphed, VertexId,
+    },
+    utils::hash_function::HashFunction,
+};
+
+/// Parameters of a [CutSparsifier]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SparsifierParameters {
+    /// Relative error on every cut
+    pub epsilon: f64,
+    /// The connectivity threshold is `k = connectivity_constant · log n / ε^2`
+    pub connectivity_constant: f64,
+}
+
+impl Default for SparsifierParameters {
+    fn default() -> Self {
+        Self {
+            epsilon: 0.5,
+            connectivity_constant: 1.0,
+        }
+    }
+}
+
+impl SparsifierParameters {
+    /// The connectivity threshold `k` over `n` vertices
+    pub fn k(&self, n: VertexId) -> usize {
+        let log_n = (n.max(2) as f64).log2();
+        (self.connectivity_constant * log_n / self.epsilon.powi(2))
+            .ceil()
+            .max(1.0) as usize
+    }
+}
+
+/// Weighted subgraph preserving every cut of a turnstile graph stream over the vertices `[n]`
+///
+/// Storage: O(log n) levels of a `k`-connectivity certificate, O(ε^-2 n log^5 n) bits
+#[derive(Clone, Debug)]
+pub struct CutSparsifier<H> {
+    n: VertexId,
+    parameters: SparsifierParameters,
+    /// The level of an edge `j` is the number of trailing zeros of `h(j)`
+    hasher: H,
+    levels: Vec<KConnectivity<H>>,
+}
+
+impl<H> CutSparsifier<H>
+where
+    H: HashFunction,
+{
+    pub fn init(n: VertexId, parameters: SparsifierParameters) -> Self {
+        let universe = binomial(n, 2).max(2).next_power_of_two();
+        let levels = universe.ceil_log2().unwrap() as u64 + 1;
+        let k = parameters.k(n);
+
+        Self {
+            n,
+            parameters,
+            hasher: H::init(universe, universe),
+            levels: (0..levels).map(|_| KConnectivity::init(n, k)).collect(),
+        }
+    }
+
+    pub fn parameters(&self) -> &SparsifierParameters {
+        &self.parameters
+    }
+
+    /// Feed an edge insertion or deletion, self loops are ignored
+    pub fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        let (edge, _) = token;
+        let (u, v) = edge.vertices();
+        if u == v {
+            return;
+        }
+
+        let level = self.hasher.compute(edge.to_d1()).trailing_zeros() as usize;
+        for sketch in self.levels.iter_mut().take(level + 1) {
+            sketch.feed(token);
+        }
+    }
+
+    /// The sparsifier, every edge labelled with its weight, a power of two
+    pub fn query(self) -> Graph<VertexId, u64> {
+        let k = self.parameters.k(self.n);
+
+        let flows: Vec<(HashSet<(VertexId, VertexId)>, UnitFlow)> = self
+            .levels
+            .into_iter()
+            .map(|sketch| {
+                let certificate = sketch.certificate();
+                let edges = certificate
+                    .iter()
+                    .map(|e| {
+                        let (u, v) = e.vertices_ord();
+                        (*u, *v)
+                    })
+                    .collect();
+                (edges, UnitFlow::init(&certificate))
+            })
+            .collect();
+
+        // First level at which the endpoints of each edge are less than k-edge-connected
+        let mut first_sparse: HashMap<(VertexId, VertexId), usize> = HashMap::new();
+        let mut sparsifier = Graph::default();
+
+        for (i, (edges, _)) in flows.iter().enumerate() {
+            for (u, v) in edges.iter() {
+                let level = *first_sparse.entry((*u, *v)).or_insert_with(|| {
+                    flows
+                        .iter()
+                        .position(|(_, flow)| flow.local_connectivity(*u, *v, k) < k)
+                        .unwrap_or(flows.len())
+                });
+
+                if level == i {
+                    let mut edge = Edge::init(*u, *v);
+                    edge.update_label(1 << i);
+                    sparsifier.add_edge(edge);
+                }
+            }
+        }
+
+        sparsifier
+    }
+}
+
+impl<H> StreamProcessor<(Edge<VertexId, ()>, bool), Graph<VertexId, u64>> for CutSparsifier<H>
+where
+    H: HashFunction,
+{
+    fn feed(&mut self, token: (Edge<VertexId, ()>, bool)) {
+        CutSparsifier::feed(self, token)
+    }
+
+    fn query(self) -> Graph<VertexId, u64> {
+        CutSparsifier::query(self)
+    }
+}
+
+impl<H> CutQuery for CutSparsifier<H>
+where
+    H: HashFunction + Clone,
+{
+    /// The weight of the cut in the sparsifier, within `1 ± ε` of its size with high probability
+    ///
+    /// Every call builds the sparsifier anew, to query many cuts, [query](CutSparsifier::query) it once and take the
+    /// [cut_weight](MinCut::cut_weight) of each cut instead.
+    fn cut_size_estimate(&self, side: &HashSet<VertexId>) -> f64 {
+        self.clone().query().cut_weight(side) as f64
+    }
+}
+
+impl<H> Provenance for CutSparsifier<H>
+where
+    H: HashFunction,
+{
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "CutSparsifier",
+            paper_ref: "Ahn, Guha, McGregor, PODS 2012, https://doi.org/10.1145/2213556.2213560",
+            params: vec![
+                ("n", self.n as f64),
+                ("ε", self.parameters.epsilon),
+                ("k", self.parameters.k(self.n) as f64),
+                ("levels", self.levels.len() as f64),
+            ],
+            seed: None,
+        }
+    }
+}
+
+impl<H> SpaceUsage for CutSparsifier<H>
+where
+    H: HashFunction,
+{
+    fn space(&self) -> SpaceReport {
+        let components: Vec<SpaceReport> = self.levels.iter().map(|l| l.space()).collect();
+
+        SpaceReport {
+            structure: "CutSparsifier",
+            bound: "O(ε^-2 n log^5 n)",
+            parameters: vec![
+                ("n", self.n as f64),
+                ("ε", self.parameters.epsilon),
+                ("levels", self.levels.len() as f64),
+            ],
+            theoretical_bits: components.iter().map(|c| c.theoretical_bits).sum(),
+            measured_bytes: size_of::<Self>()
+                + self.levels.capacity() * size_of::<KConnectivity<H>>()
+                + components
+                    .iter()
+                    .map(|c| c.measured_bytes - size_of::<KConnectivity<H>>())
+                    .sum::<usize>(),
+            components,
+        }
+    }
+}
+
+/// Spectral sparsifier of an insertion only graph stream over the vertices `[n]`, by merge and reduce
+///
+/// The stream is assumed to be a simple graph, of at most `n^2 / 2` edges, which bounds the number of levels. Parallel edges add up
+/// their weights.
+///
+/// Storage: O(log n) levels of O(ε^-2 n log^3 n) weighted edges
+///
+/// Runtime: O(n^3) for every block of the stream, amortized over its edges
+#[derive(Clone, Debug)]
+pub struct SpectralStreamSparsifier {
+    n: VertexId,
+    parameters: SpectralParameters,
+    /// Parameters of the sparsification of every level, of error `ε / L`
+    level_parameters: SpectralParameters,
+    /// Edges of the block being filled
+    buffer: EdgeWeights<VertexId>,
+    /// Number of edges of a block
+    block: usize,
+    /// The sparsifier of `2^l` blocks of the stream at level `l`, if any
+    levels: Vec<Option<EdgeWeights<VertexId>>>,
+}
+
+impl SpectralStreamSparsifier {
+    pub fn init(n: VertexId, parameters: SpectralParameters) -> Self {
+        let m = binomial(n.max(2), 2) as f64;
+
+        // The block size depends on the error of the levels, and the number of levels on the block size
+        let mut levels = 1;
+        let (level_parameters, block) = loop {
+            let level_parameters = parameters.with_epsilon(parameters.epsilon / levels as f64);
+            let block = level_parameters.samples(n as usize).max(1);
+            let needed = (m / block as f64).log2().ceil().max(0.0) as usize + 1;
+            if needed <= levels {
+                break (level_parameters, block);
+            }
+            levels = needed;
+        };
+
+        Self {
+            n,
+            parameters,
+            level_parameters,
+            buffer: EdgeWeights::new(),
+            block,
+            levels: vec![],
+        }
+    }
+
+    pub fn parameters(&self) -> &SpectralParameters {
+        &self.parameters
+    }
+
+    /// Feed an edge insertion, self loops are ignored
+    pub fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        self.feed_with_rng(edge, &mut rand::thread_rng())
+    }
+
+    /// [feed](SpectralStreamSparsifier::feed), drawing the samples from the generator once the block is full
+    pub fn feed_with_rng<R: Rng + ?Sized>(&mut self, edge: Edge<VertexId, ()>, rng: &mut R) {
+        let (u, v) = edge.vertices_ord();
+        if u == v {
+            return;
+        }
+
+        *self.buffer.entry((*u, *v)).or_default() += 1.0;
+        if self.buffer.len() == self.block {
+            let block = std::mem::take(&mut self.buffer);
+            self.carry(block, rng);
+        }
+    }
+
+    /// Insert a sparsifier at level 0, merging it up the levels as long as they are occupied
+    fn carry<R: Rng + ?Sized>(&mut self, block: EdgeWeights<VertexId>, rng: &mut R) {
+        let mut carried = spectral_sparsify(&block, self.level_parameters, rng);
+
+        for level in 0.. {
+            if level == self.levels.len() {
+                self.levels.push(None);
+            }
+            match self.levels[level].take() {
+                Some(mut sparsifier) => {
+                    for (e, w) in carried {
+                        *sparsifier.entry(e).or_default() += w;
+                    }
+                    carried = spectral_sparsify(&sparsifier, self.level_parameters, rng);
+                }
+                None => {
+                    self.levels[level] = Some(carried);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Number of weighted edges kept, across the levels and the block being filled
+    pub fn len(&self) -> usize {
+        self.buffer.len() + self.levels.iter().flatten().map(|l| l.len()).sum::<usize>()
+    }
+
+    /// Whether no edge was kept
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The sparsifier, the union of the sparsifiers of the levels and of the block being filled
+    pub fn query(self) -> EdgeWeights<VertexId> {
+        let mut sparsifier = self.buffer;
+        for (e, w) in self.levels.into_iter().flatten().flatten() {
+            *sparsifier.entry(e).or_default() += w;
+        }
+        sparsifier
+    }
+}
+
+impl StreamProcessor<Edge<VertexId, ()>, EdgeWeights<VertexId>> for SpectralStreamSparsifier {
+    fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        SpectralStreamSparsifier::feed(self, edge)
+    }
+
+    fn query(self) -> EdgeWeights<VertexId> {
+        SpectralStreamSparsifier::query(self)
+    }
+}
+
+impl Provenance for SpectralStreamSparsifier {
+    fn info(&self) -> AlgorithmInfo {
+        AlgorithmInfo {
+            name: "SpectralStreamSparsifier",
+            paper_ref: "Spielman, Srivastava, SICOMP 2011, https://doi.org/10.1137/080734029",
+            params: vec![
+                ("n", self.n as f64),
+                ("ε", self.parameters.epsilon),
+                ("block", self.block as f64),
+            ],
+            seed: None,
+        }
+    }
+}
+
+impl SpaceUsage for SpectralStreamSparsifier {
+    /// Two endpoints and a weight per kept edge
+    fn space(&self) -> SpaceReport {
+        let entry = size_of::<((VertexId, VertexId), f64)>();
+
+        SpaceReport {
+            structure: "SpectralStreamSparsifier",
+            bound: "O(ε^-2 n log^3 n)",
+            parameters: vec![
+                ("n", self.n as f64),
+                ("ε", self.parameters.epsilon),
+                ("levels", self.levels.len() as f64),
+            ],
+            theoretical_bits: self.len() as f64 * (2.0 * log(self.n as f64) + 64.0),
+            measured_bytes: size_of::<Self>()
+                + self.levels.capacity() * size_of::<Option<EdgeWeights<VertexId>>>()
+                + (self.buffer.capacity()
+                    + self
+                        .levels
+                        .iter()
+                        .flatten()
+                        .map(|l| l.capacity())
+                        .sum::<usize>())
+                    * entry,
+            components: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::{
+        graph::static_a::spectral::quadratic_form, utils::hash_function::PowerFiniteFieldHasher,
+    };
+
+    type Sparsifier = CutSparsifier<PowerFiniteFieldHasher>;
+
+    /// Two cliques on `[0, 6)` and `[6, 12)`, joined by two edges
+    fn barbell() -> Vec<Edge<VertexId, ()>> {
+        let clique = |offset: VertexId| {
+            (0..6).flat_map(move |u| (u + 1..6).map(move |v| Edge::init(u + offset, v + offset)))
+        };
+        clique(0)
+            .chain(clique(6))
+            .chain(vec![Edge::init(0, 6), Edge::init(5, 11)])
+            .collect()
+    }
+
+    #[test]
+    fn exact_when_dense_enough() {
+        // k exceeds every connectivity, so every edge is kept with weight 1
+        let mut sparsifier = Sparsifier::init(12, SparsifierParameters::default());
+        barbell()
+            .into_iter()
+            .for_each(|e| sparsifier.feed((e, true)));
+        sparsifier.feed((Edge::init(5, 11), false));
+
+        let graph = sparsifier.query();
+        let (weight, side) = graph.stoer_wagner().unwrap();
+
+        assert_eq!(weight, 1);
+        assert!(side == (0..6).collect() || side == (6..12).collect());
+        assert!(graph.adj_list().values().flatten().all(|n| n.label == 1));
+    }
+
+    #[test]
+    fn sampled() {
+        let parameters = SparsifierParameters {
+            epsilon: 1.0,
+            connectivity_constant: 0.5,
+        };
+        assert_eq!(parameters.k(12), 2);
+
+        let mut sparsifier = Sparsifier::init(12, parameters);
+        barbell()
+            .into_iter()
+            .for_each(|e| sparsifier.feed((e, true)));
+        sparsifier.feed((Edge::init(5, 11), false));
+        let graph = sparsifier.query();
+
+        // The bridge is the only edge of connectivity below 2, so it keeps weight 1
+        let mut bridge = Edge::init(0, 6);
+        bridge.update_label(1);
+        assert!(graph.has_edge(&bridge));
+        assert!(graph
+            .adj_list()
+            .values()
+            .flatten()
+            .all(|n| n.label.is_power_of_two()));
+    }
+    #[test]
+    fn cut_queries() {
+        let mut sparsifier = Sparsifier::init(12, SparsifierParameters::default());
+        barbell()
+            .into_iter()
+            .for_each(|e| sparsifier.feed((e, true)));
+
+        let left: HashSet<VertexId> = (0..6).collect();
+        assert_eq!(sparsifier.cut_size_estimate(&left), 2.0);
+        assert_eq!(sparsifier.cut_size_estimate(&[0].into()), 6.0);
+        assert_eq!(sparsifier.cut_size_estimate(&(0..12).collect()), 0.0);
+    }
+    #[test]
+    fn spectral_stream() {
+        let n = 60;
+        let edges: Vec<Edge<VertexId, ()>> = (0..n)
+            .flat_map(|u| (u + 1..n).map(move |v| Edge::init(u, v)))
+            .collect();
+        let exact: EdgeWeights<VertexId> = edges
+            .iter()
+            .map(|e| (*e.vertices_ord().0, *e.vertices_ord().1))
+            .map(|e| (e, 1.0))
+            .collect();
+
+        // Few enough samples that the blocks fill up, and are merged
+        let parameters = SpectralParameters {
+            epsilon: 1.0,
+            sampling_constant: 0.02,
+        };
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let mut sparsifier = SpectralStreamSparsifier::init(n, parameters);
+        edges
+            .iter()
+            .for_each(|e| sparsifier.feed_with_rng(*e, &mut rng));
+        sparsifier.feed_with_rng(Edge::init(3, 3), &mut rng);
+        assert!(sparsifier.len() < edges.len());
+
+        let sparse = sparsifier.query();
+        for _ in 0..10 {
+            let x: HashMap<VertexId, f64> = (0..n).map(|v| (v, rng.gen_range(-1.0..1.0))).collect();
+            let (exact, sparse) = (quadratic_form(&exact, &x), quadratic_form(&sparse, &x));
+            assert!(
+                (sparse - exact).abs() <= 0.5 * exact,
+                "{} instead of {}",
+                sparse,
+                exact
+            );
+        }
+    }
+
+    #[test]
+    fn spectral_stream_exact_until_a_block_fills() {
+        let mut sparsifier = SpectralStreamSparsifier::init(12, SpectralParameters::default());
+        barbell().into_iter().for_each(|e| sparsifier.feed(e));
+
+        let sparse = sparsifier.query();
+        assert_eq!(sparse.len(), barbell().len());
+        assert!(sparse.values().all(|w| *w == 1.0));
+    }
+}
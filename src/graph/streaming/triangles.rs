@@ -0,0 +1,319 @@
+//! Streaming Triangle Counting
+//!
+//! Neighborhood sampling, from [Pavan, Tangwongsan, Tirthapura and Wu](https://doi.org/10.14778/2556549.2556569), over an insertion
+//! only stream of `m` edges. Each estimator samples an edge `r1` uniformly, then `r2` uniformly among the `c` edges adjacent to `r1`
+//! that arrive after it, and waits for the edge closing the wedge `r1 r2`. Its estimate, `c m` if the wedge closed and `0` otherwise,
+//! is unbiased with variance at most `2 m Δ T`, where `T` is the number of triangles.
+//!
+//! The mean of `8 m Δ / (ε^2 T)` estimators is within `εT` of `T` with probability `3/4` by Chebyshev, and the median of `8 ln(1/δ)`
+//! such means with probability `1 - δ` by Hoeffding. Since `m Δ / T` is not known ahead of the stream, it is a parameter.
+
+use std::mem::size_of;
+
+use rand::Rng;
+
+use crate::graph::{
+    streaming::{
+        provenance::{AlgorithmInfo, Provenance},
+        space::{log, SpaceReport, SpaceUsage},
+        StreamProcessor,
+    },
+    Edge, VertexId,
+};
+
+/// Parameters of a [TriangleCounter]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriangleParameters {
+    /// Relative error
+    pub epsilon: f64,
+    /// Failure probability
+    pub delta: f64,
+    /// Upper bound on `m Δ / T`, the number of estimators grows linearly with it
+    pub ratio: f64,
+    /// Estimators per mean are `mean_constant · ratio / ε^2`
+    pub mean_constant: f64,
+    /// Means per median are `median_constant · ln(1/δ)`
+    pub median_constant: f64,
+}
+
+impl Default for TriangleParameters {
+    fn default() -> Self {
+        Self {
+            epsilon: 0.1,
+            delta: 0.05,
+            ratio: 100.0,
+            mean_constant: 8.0,
+            median_constant: 8.0,
+        }
+    }
+}
+
+impl TriangleParameters {
+    /// Number of estimators averaged in each mean
+    pub fn estimators_per_mean(&self) -> usize {
+        (self.mean_constant * self.ratio / self.epsilon.powi(2))
+            .ceil()
+            .max(1.0) as usize
+    }
+
+    /// Number of means the median is taken over, always odd
+    pub fn means(&self) -> usize {
+        let means = (self.median_constant * (1.0 / self.delta).ln())
+            .ceil()
+            .max(1.0) as usize;
+        means | 1
+    }
+}
+
+/// A single neighborhood sampling estimator
+#[derive(Clone, Copy, Debug, Default)]
+struct Estimator {
+    r1: Option<Edge<VertexId, ()>>,
+    r2: Option<Edge<VertexId, ()>>,
+    /// Edges adjacent to `r1` that arrived after it
+    c: u64,
+    closed: bool,
+}
+
+impl Estimator {
+    /// Feed the `m`th edge of the stream
+    fn feed<R: Rng>(&mut self, edge: Edge<VertexId, ()>, m: u64, rng: &mut R) {
+        if rng.gen_range(0..m) == 0 {
+            *self = Self {
+                r1: Some(edge),
+                ..Self::default()
+            };
+            return;
+        }
+
+        let r1 = match self.r1 {
+            Some(r1) => r1,
+            None => return,
+        };
+        if !adjacent(&r1, &edge) {
+            return;
+        }
+
+        self.c += 1;
+        if rng.gen_range(0..self.c) == 0 {
+            self.r2 = Some(edge);
+            self.closed = false;
+        } else if let Some(r2) = self.r2 {
+            if closes(&r1, &r2, &edge) {
+                self.closed = true;
+            }
+        }
+    }
+
+    fn estimate(&self, m: u64) -> f64 {
+        if self.closed {
+            (self.c * m) as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Whether two distinct edges share an endpoint
+fn adjacent(e: &Edge<VertexId, ()>, f: &Edge<VertexId, ()>) -> bool {
+    let ((u1, v1), (u2, v2)) = (e.vertices_ord(), f.vertices_ord());
+    (u1, v1) != (u2, v2) && (u1 == u2 || u1 == v2 || v1 == u2 || v1 == v2)
+}
+
+/// Whether the edge joins the two ends of the wedge `r1 r2`
+fn closes(r1: &Edge<VertexId, ()>, r2: &Edge<VertexId, ()>, edge: &Edge<VertexId, ()>) -> bool {
+    let (a, b) = r1.vertices_ord();
+    let (c, d) = r2.vertices_ord();
+    let ends = match (a == c, a == d, b == c, b == d) {
+        (true, _, _, _) => (b, d),
+        (_, true, _, _) => (b, c),
+        (_, _, true, _) => (a, d),
+        (_, _, _, true) => (a, c),
+        _ => return false,
+    };
+
+    let (u, v) = edge.vertices_ord();
+    (u, v) == ends || (v, u) == ends
+}
+
+/// Estimates the number of triangles of an insertion only graph stream, within a factor `1 ± ε` with probability `1 - δ`
+///
+/// Every edge must appear at most once, and self loops are ignored.
+///
+/// Storage: O(ratio / ε^2 · log(1/δ)) estimators of O(log n) bits
+#[derive(Clone, Debug)]
+pub struct TriangleCounter {
+    parameters: TriangleParameters,
+    estimators: Vec<Estimator>,
+    /// Number of edges so far
+    m: u64,
+}
+
+impl TriangleCounter {
+    pub fn init(parameters: TriangleParameters) -> Self {
+        let count = parameters.estimators_per_mean() * parameters.means();
+
+        Self {
+            parameters,
+            estimators: vec![Estimator::default(); count],
+            m: 0,
+        }
+    }
+
+    pub fn parameters(&self) -> &TriangleParameters {
+        &self.parameters
+    }
+
+    /// Feed the next edge of the stream
+    pub fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        let (u, v) = edge.vertices();
+        if u == v {
+            return;
+        }
+
+        self.m += 1;
+        let mut rng = rand::thread_rng();
+        for estimator in self.estimators.iter_mut() {
+            estimator.feed(edge, self.m, &mut rng);
+        }
+    }
+
+    /// The median of the means of the estimators
+    pub fn query(self) -> f64 {
+        let Self {
+            parameters,
+            estimators,
+            m,
+        } = self;
+
+        let mut means: Vec<f64> = estimators
+            .chunks(parameters.estimators_per_mean())
+            .map(|chunk| chunk.iter().map(|e| e.estimate(m)).sum::<f64>() / chunk.len() as f64)
+            .collect();
+        means.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        means[means.len() / 2]
+    }
+}
+
+impl SpaceUsage for TriangleCounter {
+    /// Each estimator holds two edges and a counter
+    fn space(&self) -> SpaceReport {
+        let count = self.estimators.len() as f64;
+        let n_bits = log(self.m as f64);
+
+        SpaceReport {
+            structure: "TriangleCounter",
+            bound: "O(ratio / ε^2 · log(1/δ) · log n)",
+            parameters: vec![
+                ("ε", self.parameters.epsilon),
+                ("δ", self.parameters.delta),
+                ("ratio", self.parameters.ratio),
+                ("estimators", count),
+            ],
+            theoretical_bits: count * 5.0 * n_bits,
+            measured_bytes: size_of::<Self>() + self.estimators.capacity() * size_of::<Estimator>(),
+            components: vec![],
+        }
+    }
+}
+
+impl StreamProcessor<Edge<VertexId, ()>, f64> for TriangleCounter {
+    fn feed(&mut self, edge: Edge<VertexId, ()>) {
+        TriangleCounter::feed(self, edge)
+    }
+
+    fn query(self) -> f64 {
+        TriangleCounter::query(self)
+    }
+}
+
+impl Provenance for TriangleCounter {
+    fn info(&self) -> AlgorithmInfo {
+        let TriangleParameters {
+            epsilon,
+            delta,
+            ratio,
+            mean_constant,
+            median_constant,
+        } = self.parameters;
+
+        AlgorithmInfo {
+            name: "TriangleCounter",
+            paper_ref: "Pavan, Tangwongsan, Tirthapura, Wu, VLDB 2013, https://doi.org/10.14778/2556549.2556569",
+            params: vec![
+                ("ε", epsilon),
+                ("δ", delta),
+                ("ratio", ratio),
+                ("mean constant", mean_constant),
+                ("median constant", median_constant),
+            ],
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wedges() {
+        let (a, b, c) = (Edge::init(0, 1), Edge::init(2, 1), Edge::init(0, 2));
+
+        assert!(adjacent(&a, &b));
+        assert!(!adjacent(&a, &a));
+        assert!(!adjacent(&a, &Edge::init(2, 3)));
+
+        assert!(closes(&a, &b, &c));
+        assert!(closes(&b, &c, &a));
+        assert!(!closes(&a, &b, &Edge::init(0, 3)));
+    }
+
+    #[test]
+    fn parameters() {
+        let parameters = TriangleParameters {
+            epsilon: 0.5,
+            delta: 0.1,
+            ratio: 10.0,
+            ..Default::default()
+        };
+
+        assert_eq!(parameters.estimators_per_mean(), 320);
+        assert_eq!(parameters.means(), 19);
+    }
+
+    #[test]
+    fn complete_graph() {
+        // K_12 has 220 triangles, and m Δ / T = 66 · 11 / 220 = 3.3
+        let n = 12;
+        let mut counter = TriangleCounter::init(TriangleParameters {
+            epsilon: 0.2,
+            delta: 0.01,
+            ratio: 3.3,
+            ..Default::default()
+        });
+
+        (0..n)
+            .flat_map(|u| (u + 1..n).map(move |v| Edge::init(u, v)))
+            .for_each(|e| counter.feed(e));
+
+        let estimate = counter.query();
+        assert!(
+            (estimate - 220.0).abs() <= 0.2 * 220.0,
+            "estimated {} triangles",
+            estimate
+        );
+    }
+
+    #[test]
+    fn triangle_free() {
+        let mut counter = TriangleCounter::init(TriangleParameters {
+            ratio: 1.0,
+            ..Default::default()
+        });
+        (0..50).for_each(|v| counter.feed(Edge::init(v, v + 1)));
+
+        assert_eq!(counter.query(), 0.0);
+    }
+}
@@ -3,8 +3,19 @@
 //! ## Collaborators
 //! - Benjamin Cape '22
 //! - Professor Amit Chakrabarti
+//!
+//! ## Diagnostics
+//! The streaming structures report their internal parameters and the time spent in each phase through the [log] facade, at the
+//! debug and trace levels, and the adjustments of [ParameterLog](utils::parameters::ParameterLog) at the info and warn levels.
+//! Nothing is printed unless the application installs a logger, e.g. `env_logger` with `RUST_LOG=g_raph=debug`.
+pub mod corpus;
+pub mod error;
+pub mod evaluation;
 pub mod graph;
 pub mod random_graph;
+pub mod registry;
 pub mod utils;
 #[macro_use]
 pub mod macros;
+
+pub use error::{Error, Result};
@@ -1,5 +1,8 @@
 //! Useful Macros
 
+#[doc(hidden)]
+pub use log as __log;
+
 #[macro_export]
 macro_rules! start_dur {
     () => {{
@@ -16,3 +19,16 @@ macro_rules! printdur {
         let $start_time = start_dur!();
     };
 }
+
+/// Log the time elapsed since `$start_time` at the debug level, and restart it, for the phases of the library
+///
+/// [printdur] prints instead, for the benchmarks.
+#[macro_export]
+macro_rules! logdur {
+    ($label:literal, $start_time:ident) => {
+        let duration = std::time::Instant::now().duration_since($start_time);
+        $crate::macros::__log::debug!("{}: {:?}", $label, duration);
+        #[allow(unused_variables)]
+        let $start_time = $crate::start_dur!();
+    };
+}
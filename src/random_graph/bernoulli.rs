@@ -3,15 +3,19 @@ use std::{fmt::Debug, hash::Hash};
 use num_integer::binomial;
 
 use rand::{
-    distributions::{Bernoulli, BernoulliError},
+    distributions::Bernoulli,
     prelude::{Distribution, ThreadRng},
 };
 
-use crate::graph::{Edge, Graph, Graphed};
+use crate::{
+    error::bernoulli,
+    graph::{Edge, Graph, Graphed, VertexId},
+    Result,
+};
 
 pub struct BernoulliGraphDistribution<T> {
     /// Nodes in the Graph
-    nodes: u32,
+    nodes: VertexId,
     /// Probability that an edge is added into the graph
     bern: Bernoulli,
     /// The noise (useful for edge streams in the turnstile setting)
@@ -34,14 +38,10 @@ impl<T> BernoulliGraphDistribution<T> {
     /// When sampling with this, we will get a stream of size ((copies * edges) + 2*noise).
     /// What remains in the stream will be a set of edges sampled with uniform probability.
     /// The noise is simply to make the graph interesting with turnstile streams.
-    pub fn init(nodes: u32, p: f64) -> Result<Self, BernoulliError> {
-        if !(0.0..=1.0).contains(&p) {
-            return Err(BernoulliError::InvalidProbability);
-        }
-
+    pub fn init(nodes: VertexId, p: f64) -> Result<Self> {
         Ok(Self {
             nodes,
-            bern: Bernoulli::new(p).unwrap(),
+            bern: bernoulli(p)?,
             noise: 0,
             copies: 1,
             last: None,
@@ -74,10 +74,10 @@ impl<T> BernoulliGraphDistribution<T> {
 }
 
 /// Generates a Graph Stream
-impl<T> rand::distributions::Distribution<Vec<(Edge<u32, ()>, bool)>>
+impl<T> rand::distributions::Distribution<Vec<(Edge<VertexId, ()>, bool)>>
     for BernoulliGraphDistribution<T>
 {
-    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(Edge<u32, ()>, bool)> {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(Edge<VertexId, ()>, bool)> {
         let Self {
             nodes,
             bern,
@@ -108,8 +108,10 @@ impl<T> rand::distributions::Distribution<Vec<(Edge<u32, ()>, bool)>>
     }
 }
 
-impl rand::distributions::Distribution<Graph<u32, ()>> for BernoulliGraphDistribution<u32> {
-    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Graph<u32, ()> {
+impl rand::distributions::Distribution<Graph<VertexId, ()>>
+    for BernoulliGraphDistribution<VertexId>
+{
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Graph<VertexId, ()> {
         let Self {
             nodes,
             bern,
@@ -129,12 +131,12 @@ impl rand::distributions::Distribution<Graph<u32, ()>> for BernoulliGraphDistrib
     }
 }
 
-impl Iterator for BernoulliGraphDistribution<(Edge<u32, ()>, bool)> {
-    type Item = (Edge<u32, ()>, bool);
+impl Iterator for BernoulliGraphDistribution<(Edge<VertexId, ()>, bool)> {
+    type Item = (Edge<VertexId, ()>, bool);
     fn next(&mut self) -> Option<Self::Item> {
         let next_edge = if let Some(last) = self.last {
             let next = last.0.to_d1() + 1;
-            if next >= binomial(self.nodes as u64, 2) {
+            if next >= binomial(self.nodes, 2) {
                 return None;
             }
             (Edge::from_d1(last.0.to_d1() + 1), true)
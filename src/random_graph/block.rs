@@ -0,0 +1,95 @@
+//! Stochastic Block Model
+//!
+//! Vertices are split into blocks of the same size, and two vertices are adjacent with a probability that depends on whether they
+//! are in the same block: dense communities, sparsely connected to each other.
+
+use rand::distributions::{Bernoulli, Distribution};
+
+use crate::{
+    error::bernoulli,
+    graph::{Edge, Graphed, VertexId},
+    Result,
+};
+
+pub struct StochasticBlockModel {
+    /// Number of blocks
+    blocks: VertexId,
+    /// Vertices per block, block `i` holds `[i size, (i + 1) size)`
+    size: VertexId,
+    /// Probability of an edge within a block
+    inside: Bernoulli,
+    /// Probability of an edge between blocks
+    outside: Bernoulli,
+}
+
+impl StochasticBlockModel {
+    pub fn init(blocks: VertexId, size: VertexId, p_in: f64, p_out: f64) -> Result<Self> {
+        Ok(Self {
+            blocks,
+            size,
+            inside: bernoulli(p_in)?,
+            outside: bernoulli(p_out)?,
+        })
+    }
+
+    pub fn block(&self, v: VertexId) -> VertexId {
+        v / self.size
+    }
+}
+
+impl<G: Graphed<VertexId, ()>> Distribution<G> for StochasticBlockModel {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> G {
+        let stream: Vec<_> = self.sample(rng);
+
+        let mut graph = G::new(Default::default());
+
+        for edge in stream {
+            graph.add_edge(edge.0)
+        }
+
+        graph
+    }
+}
+
+impl Distribution<Vec<(Edge<VertexId, ()>, bool)>> for StochasticBlockModel {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(Edge<VertexId, ()>, bool)> {
+        let n = self.blocks * self.size;
+
+        let mut stream = vec![];
+        for u in 0..n {
+            for v in u + 1..n {
+                let bern = if self.block(u) == self.block(v) {
+                    &self.inside
+                } else {
+                    &self.outside
+                };
+                if bern.sample(rng) {
+                    stream.push((Edge::init(u, v), true));
+                }
+            }
+        }
+        stream
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::graph::Graph;
+
+    #[test]
+    fn disjoint_cliques() {
+        let model = StochasticBlockModel::init(3, 5, 1.0, 0.0).unwrap();
+        let graph: Graph<VertexId, ()> = model.sample(&mut ChaCha20Rng::seed_from_u64(0));
+
+        for (v, neighbors) in graph.adj_list() {
+            assert_eq!(neighbors.len(), 4);
+            assert!(neighbors
+                .iter()
+                .all(|u| model.block(u.destination) == model.block(*v)));
+        }
+    }
+}
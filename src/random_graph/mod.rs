@@ -1,5 +1,31 @@
 //! Generation Models for Graphs
 
+use std::collections::HashMap;
+
+use crate::graph::{Edge, VertexId};
+
 pub mod bernoulli;
+pub mod block;
 pub mod partite;
+pub mod planted;
+pub mod regular;
 pub mod uniform;
+
+/// The edges left once a turnstile stream is replayed, i.e. of positive net multiplicity, in increasing order of their index
+pub fn net_edges<'s, I>(stream: I) -> Vec<Edge<VertexId, ()>>
+where
+    I: IntoIterator<Item = &'s (Edge<VertexId, ()>, bool)>,
+{
+    let mut multiplicities: HashMap<u64, i64> = HashMap::new();
+    for (edge, c) in stream {
+        *multiplicities.entry(edge.to_d1()).or_default() += if *c { 1 } else { -1 };
+    }
+
+    let mut edges: Vec<u64> = multiplicities
+        .into_iter()
+        .filter(|(_, multiplicity)| *multiplicity > 0)
+        .map(|(edge, _)| edge)
+        .collect();
+    edges.sort_unstable();
+    edges.into_iter().map(Edge::from_d1).collect()
+}
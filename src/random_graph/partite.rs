@@ -1,13 +1,17 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
-use rand::distributions::{Bernoulli, BernoulliError};
+use rand::distributions::Bernoulli;
 
-use crate::graph::{Edge, Graphed};
+use crate::{
+    error::bernoulli,
+    graph::{Edge, Graphed, VertexId},
+    Result,
+};
 
 pub struct BernoulliPartiteGraph {
     /// Nodes
-    n: u32,
+    n: VertexId,
     /// Probability of an edge
     p: f64,
     /// Partitions
@@ -17,15 +21,13 @@ pub struct BernoulliPartiteGraph {
 }
 
 impl BernoulliPartiteGraph {
-    pub fn init(n: u32, p: f64, k: u32) -> Result<Self, BernoulliError> {
-        if !(0.0..=1.0).contains(&p) {
-            return Err(BernoulliError::InvalidProbability);
-        }
+    pub fn init(n: VertexId, p: f64, k: u32) -> Result<Self> {
+        bernoulli(p)?;
         Ok(Self { n, p, k, copies: 1 })
     }
 }
 
-impl<G: Graphed<u32, ()>> rand::distributions::Distribution<G> for BernoulliPartiteGraph {
+impl<G: Graphed<VertexId, ()>> rand::distributions::Distribution<G> for BernoulliPartiteGraph {
     fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> G {
         let stream: Vec<_> = self.sample(rng);
 
@@ -39,10 +41,10 @@ impl<G: Graphed<u32, ()>> rand::distributions::Distribution<G> for BernoulliPart
     }
 }
 
-impl rand::distributions::Distribution<Vec<(Edge<u32, ()>, bool)>> for BernoulliPartiteGraph {
-    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(Edge<u32, ()>, bool)> {
+impl rand::distributions::Distribution<Vec<(Edge<VertexId, ()>, bool)>> for BernoulliPartiteGraph {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(Edge<VertexId, ()>, bool)> {
         let Self { n, p, k, copies } = self;
-        let partition: HashMap<u32, Vec<u32>> =
+        let partition: HashMap<u32, Vec<VertexId>> =
             (0..*n).into_iter().fold(HashMap::new(), |curr, v| {
                 let mut curr = curr;
                 curr.entry(rng.gen_range(0..*k)).or_default().push(v);
@@ -50,7 +52,8 @@ impl rand::distributions::Distribution<Vec<(Edge<u32, ()>, bool)>> for Bernoulli
                 curr
             });
 
-        let bern = Bernoulli::new(*p).unwrap();
+        let bern =
+            Bernoulli::new(*p).expect("[BernoulliPartiteGraph] p is checked at initialization");
 
         let partition = &partition;
 
@@ -66,7 +69,7 @@ impl rand::distributions::Distribution<Vec<(Edge<u32, ()>, bool)>> for Bernoulli
                         .into_iter()
                         .cartesian_product(b_verts.into_iter())
                         .map(|(src, dst)| (Edge::init(src, dst), true))
-                        .collect::<Vec<(Edge<u32, ()>, bool)>>()
+                        .collect::<Vec<(Edge<VertexId, ()>, bool)>>()
                 })
             })
             .filter_map(|e| {
@@ -101,13 +104,13 @@ mod test {
 
         let stream: Vec<_> = sampler.sample(&mut rng);
 
-        let mut graph = GraphWithRecaller::new(Default::default());
+        let mut graph: GraphWithRecaller<VertexId, ()> = GraphWithRecaller::new(Default::default());
 
         for (edge, _) in stream {
             graph.add_edge(edge)
         }
 
-        let colors = graph.color_degeneracy().values().unique().count();
+        let colors = graph.color_degeneracy().num_classes();
 
         assert_eq!(colors, 10);
     }
@@ -0,0 +1,85 @@
+//! Planted Cliques
+//!
+//! A Bernoulli graph, in which a random set of `k` vertices is made a clique: its chromatic number is at least `k`, whatever the
+//! colorer.
+
+use rand::{
+    distributions::{Bernoulli, Distribution},
+    seq::index,
+};
+
+use crate::{
+    error::bernoulli,
+    graph::{Edge, Graphed, VertexId},
+    Result,
+};
+
+pub struct PlantedClique {
+    nodes: VertexId,
+    bern: Bernoulli,
+    /// Size of the clique
+    k: VertexId,
+}
+
+impl PlantedClique {
+    pub fn init(nodes: VertexId, p: f64, k: VertexId) -> Result<Self> {
+        Ok(Self {
+            nodes,
+            bern: bernoulli(p)?,
+            k: k.min(nodes),
+        })
+    }
+}
+
+impl<G: Graphed<VertexId, ()>> Distribution<G> for PlantedClique {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> G {
+        let stream: Vec<_> = self.sample(rng);
+
+        let mut graph = G::new(Default::default());
+
+        for edge in stream {
+            graph.add_edge(edge.0)
+        }
+
+        graph
+    }
+}
+
+impl Distribution<Vec<(Edge<VertexId, ()>, bool)>> for PlantedClique {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(Edge<VertexId, ()>, bool)> {
+        let mut clique = vec![false; self.nodes as usize];
+        index::sample(rng, self.nodes as usize, self.k as usize)
+            .into_iter()
+            .for_each(|v| clique[v] = true);
+
+        let mut stream = vec![];
+        for u in 0..self.nodes {
+            for v in u + 1..self.nodes {
+                let planted = clique[u as usize] && clique[v as usize];
+                if planted || self.bern.sample(rng) {
+                    stream.push((Edge::init(u, v), true));
+                }
+            }
+        }
+        stream
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::graph::{static_a::coloring::Colorer, Graph};
+
+    #[test]
+    fn clique_needs_k_colors() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let graph: Graph<VertexId, ()> = PlantedClique::init(40, 0.05, 9).unwrap().sample(&mut rng);
+
+        let coloring = graph.color_degeneracy();
+        assert!(graph.is_proper(&coloring));
+        assert!(coloring.num_classes() >= 9);
+    }
+}
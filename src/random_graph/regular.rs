@@ -0,0 +1,118 @@
+//! Random Regular Graphs
+//!
+//! Every vertex has the same degree `d`. Edges are drawn by pairing the `d` copies of every vertex at random, as in the
+//! configuration model, but only among pairs that would not make a self loop or a parallel edge, as suggested by
+//! [Steger and Wormald](https://doi.org/10.1017/S0963548399003867). Pairing starts over in the rare case where no such pair is
+//! left.
+
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::graph::{Edge, Graphed, VertexId};
+
+/// Random pairs drawn before looking for a suitable pair exhaustively
+const ATTEMPTS: usize = 64;
+
+pub struct RandomRegularGraph {
+    nodes: VertexId,
+    degree: VertexId,
+}
+
+impl RandomRegularGraph {
+    /// `None` if no simple graph on `nodes` vertices has every degree equal to `degree`
+    pub fn init(nodes: VertexId, degree: VertexId) -> Option<Self> {
+        if degree >= nodes.max(1) || (nodes * degree) % 2 == 1 {
+            return None;
+        }
+        Some(Self { nodes, degree })
+    }
+
+    fn pairing<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Vec<Edge<VertexId, ()>>> {
+        let mut copies: Vec<VertexId> = (0..self.nodes)
+            .flat_map(|v| (0..self.degree).map(move |_| v))
+            .collect();
+        let mut edges: HashSet<(VertexId, VertexId)> = HashSet::new();
+        let suitable = |edges: &HashSet<_>, u: VertexId, v: VertexId| {
+            u != v && !edges.contains(&(u.min(v), u.max(v)))
+        };
+
+        while !copies.is_empty() {
+            let pair = (0..ATTEMPTS)
+                .map(|_| {
+                    (
+                        rng.gen_range(0..copies.len()),
+                        rng.gen_range(0..copies.len()),
+                    )
+                })
+                .find(|(i, j)| suitable(&edges, copies[*i], copies[*j]))
+                .or_else(|| {
+                    (0..copies.len())
+                        .flat_map(|i| (i + 1..copies.len()).map(move |j| (i, j)))
+                        .find(|(i, j)| suitable(&edges, copies[*i], copies[*j]))
+                })?;
+
+            let (u, v) = (copies[pair.0], copies[pair.1]);
+            edges.insert((u.min(v), u.max(v)));
+            copies.swap_remove(pair.0.max(pair.1));
+            copies.swap_remove(pair.0.min(pair.1));
+        }
+
+        Some(edges.into_iter().map(|(u, v)| Edge::init(u, v)).collect())
+    }
+}
+
+impl<G: Graphed<VertexId, ()>> rand::distributions::Distribution<G> for RandomRegularGraph {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> G {
+        let stream: Vec<_> = self.sample(rng);
+
+        let mut graph = G::new(Default::default());
+
+        for edge in stream {
+            graph.add_edge(edge.0)
+        }
+
+        graph
+    }
+}
+
+impl rand::distributions::Distribution<Vec<(Edge<VertexId, ()>, bool)>> for RandomRegularGraph {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(Edge<VertexId, ()>, bool)> {
+        let mut edges = loop {
+            if let Some(edges) = self.pairing(rng) {
+                break edges;
+            }
+        };
+
+        // The order of a HashSet is not reproducible across runs
+        edges.sort_unstable_by_key(|edge| (*edge.vertices().0, *edge.vertices().1));
+        edges.into_iter().map(|edge| (edge, true)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{prelude::Distribution, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::graph::Graph;
+
+    #[test]
+    fn regular() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        for (n, d) in [(10, 3), (30, 7), (7, 6)] {
+            let graph: Graph<VertexId, ()> =
+                RandomRegularGraph::init(n, d).unwrap().sample(&mut rng);
+
+            assert_eq!(graph.vertices().len(), n as usize);
+            assert!(graph
+                .adj_list()
+                .values()
+                .all(|neighbors| neighbors.len() == d as usize));
+        }
+
+        assert!(RandomRegularGraph::init(5, 3).is_none());
+        assert!(RandomRegularGraph::init(4, 4).is_none());
+    }
+}
@@ -1,14 +1,17 @@
 //! Creates a Random Graph using Uniform Distribution for edge selections
 
+use std::collections::HashMap;
+
 use itertools::Itertools;
-use rand::prelude::IteratorRandom;
+use rand::prelude::{IteratorRandom, SliceRandom};
 
-use crate::graph::{Edge, Graphed};
+use super::net_edges;
+use crate::graph::{Edge, Graphed, VertexId};
 
 /// Uniform Distribution Generator
 pub struct UniformGraphDistribution {
     /// The number of nodes in our graph
-    nodes: u32,
+    nodes: VertexId,
     /// The number of edges we want to be in our graph
     edges: u32,
     /// The noise (useful for edge streams in the turnstile setting)
@@ -26,9 +29,9 @@ impl UniformGraphDistribution {
     ///
     ///
     /// When sampling with this, we will get a stream of size ((copies * edges) + 2*noise).
-    /// What remains in the stream will be a set of edges sampled with uniform probability.
+    /// What remains in the stream will be a set of distinct edges, without self loops, sampled with uniform probability.
     /// The noise is simply to make the graph interesting with turnstile streams.
-    pub fn init(nodes: u32, edges: u32) -> Self {
+    pub fn init(nodes: VertexId, edges: u32) -> Self {
         Self {
             nodes,
             edges,
@@ -57,14 +60,14 @@ impl UniformGraphDistribution {
     }
 }
 
-impl<G: Graphed<u32, ()>> rand::distributions::Distribution<G> for UniformGraphDistribution {
+impl<G: Graphed<VertexId, ()>> rand::distributions::Distribution<G> for UniformGraphDistribution {
+    /// The graph of the edges left once the stream is replayed, i.e. of positive net multiplicity
     fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> G {
         let stream: Vec<_> = self.sample(rng);
 
         let mut graph = G::new(Default::default());
-
-        for edge in stream {
-            graph.add_edge(edge.0)
+        for edge in net_edges(&stream) {
+            graph.add_edge(edge)
         }
 
         graph
@@ -72,30 +75,43 @@ impl<G: Graphed<u32, ()>> rand::distributions::Distribution<G> for UniformGraphD
 }
 
 /// Generates a Graph Stream
-impl rand::distributions::Distribution<Vec<(Edge<u32, ()>, bool)>> for UniformGraphDistribution {
-    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(Edge<u32, ()>, bool)> {
-        let noise: Vec<_> = (0..self.noise)
+///
+/// The edges, with their copies, and the noise are shuffled together. A noise edge is inserted and later deleted, so that
+/// deletions are interleaved with the insertions, and every prefix of the stream is a graph with non-negative multiplicities.
+impl rand::distributions::Distribution<Vec<(Edge<VertexId, ()>, bool)>>
+    for UniformGraphDistribution
+{
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(Edge<VertexId, ()>, bool)> {
+        let mut stream: Vec<Edge<VertexId, ()>> = (0..self.nodes)
+            .tuple_combinations()
+            .map(|(src, dst)| Edge::init(src, dst))
+            .choose_multiple(rng, self.edges as usize)
             .into_iter()
-            .flat_map(|_| {
-                let v1 = rng.gen_range(0..self.nodes - 1);
-                let edge = Edge::init(v1, rng.gen_range(v1..self.nodes));
-                vec![(edge, true), (edge, false)]
-            })
+            .flat_map(|e| (0..rng.gen_range(1..self.copies + 1)).map(move |_| e))
             .collect();
+        let mut insertions: HashMap<u64, usize> = stream.iter().map(|edge| edge.to_d1()).counts();
 
-        (0..self.nodes)
-            .into_iter()
-            .cartesian_product(0..self.nodes)
-            .filter(|(a, b)| a != b)
-            .map(|(src, dst)| (Edge::init(src, dst), true))
-            .choose_multiple(rng, self.edges as usize)
+        if self.nodes > 1 {
+            for _ in 0..self.noise {
+                let v1 = rng.gen_range(0..self.nodes - 1);
+                let edge = Edge::init(v1, rng.gen_range(v1 + 1..self.nodes));
+                *insertions.entry(edge.to_d1()).or_default() += 1;
+                stream.push(edge);
+                stream.push(edge);
+            }
+        }
+        stream.shuffle(rng);
+
+        // The first occurrences of an edge are its insertions, and the remaining ones the deletions of its noise
+        stream
             .into_iter()
-            .flat_map(|e| {
-                (0..rng.gen_range(1..self.copies + 1))
-                    .into_iter()
-                    .map(move |_| e)
+            .map(|edge| match insertions.get_mut(&edge.to_d1()) {
+                Some(remaining) if *remaining > 0 => {
+                    *remaining -= 1;
+                    (edge, true)
+                }
+                _ => (edge, false),
             })
-            .chain(noise)
             .collect()
     }
 }
@@ -104,6 +120,7 @@ mod test {
     use rand::prelude::Distribution;
 
     use super::*;
+    use crate::graph::Graph;
 
     #[test]
     fn sample_simple() {
@@ -125,6 +142,31 @@ mod test {
         assert_eq!(stream.len(), 70)
     }
 
+    #[test]
+    fn noise_is_deleted() {
+        let mut rng = rand::thread_rng();
+        let dist = UniformGraphDistribution::init(10, 30).with_noise(40);
+
+        let stream: Vec<_> = dist.sample(&mut rng);
+        assert!(stream.iter().any(|(_, c)| !c));
+
+        // No prefix deletes an edge it has not inserted, and the 30 edges are left
+        let mut multiplicities: HashMap<u64, i64> = HashMap::new();
+        for (edge, c) in stream.iter() {
+            let (u, v) = edge.vertices();
+            assert_ne!(u, v);
+
+            let multiplicity = multiplicities.entry(edge.to_d1()).or_default();
+            *multiplicity += if *c { 1 } else { -1 };
+            assert!(*multiplicity >= 0);
+        }
+        assert_eq!(multiplicities.values().filter(|m| **m > 0).count(), 30);
+        assert_eq!(net_edges(&stream).len(), 30);
+
+        let graph: Graph<VertexId, ()> = dist.sample(&mut rng);
+        assert_eq!(graph.edges().count(), 30);
+    }
+
     #[test]
     fn sample_with_copies() {
         let mut rng = rand::thread_rng();
@@ -0,0 +1,319 @@
+//! Algorithm Registry
+//!
+//! Front ends (a command line, an experiment harness, language bindings) pick the algorithm to run by name. Instead of each of them
+//! matching on the names, a [Registry] maps every name to a runner with the same input and output: a graph in memory, and the
+//! partition of its vertices that the algorithm computes. Streaming algorithms are run by feeding them the edges of the graph.
+//!
+//! [Registry::default] holds the algorithms of this crate. Algorithms defined elsewhere are added with [Registry::register], or
+//! with [Registry::register_measured] when they also report the space of their data structures.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryInto,
+    error::Error,
+    fmt::{Debug, Display},
+};
+
+#[cfg(feature = "rayon")]
+use crate::graph::static_a::parallel::ParallelColoring;
+use crate::graph::{
+    static_a::{coloring::Colorer, partition::Partition},
+    streaming::{
+        coloring::{ack, bcg},
+        space::{SpaceReport, SpaceUsage},
+    },
+    Graph, GraphWithRecaller, Graphed, VertexId,
+};
+
+/// Computes a partition of the vertices of a graph, `None` if a randomized algorithm failed
+pub type Runner = Box<dyn Fn(&Graph<VertexId, ()>) -> Option<Partition<VertexId>> + Send + Sync>;
+
+/// A [Runner] that also reports the space of the data structures it built, `None` if it does not account for it
+pub type MeasuredRunner = Box<
+    dyn Fn(&Graph<VertexId, ()>) -> (Option<Partition<VertexId>>, Option<SpaceReport>)
+        + Send
+        + Sync,
+>;
+
+/// Reasons for which a registry cannot produce a partition
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    /// No algorithm is registered under the name
+    Unknown(String),
+    /// The algorithm ran, but failed to produce an output
+    Failed(&'static str),
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "no algorithm named {}", name),
+            Self::Failed(name) => write!(f, "{} failed to produce an output", name),
+        }
+    }
+}
+
+impl Error for RegistryError {}
+
+/// An algorithm, with the name it is registered under
+pub struct Algorithm {
+    pub name: &'static str,
+    /// One line summary, for listings
+    pub description: &'static str,
+    runner: MeasuredRunner,
+}
+
+impl Debug for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Algorithm")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+impl Algorithm {
+    pub fn run(&self, graph: &Graph<VertexId, ()>) -> Result<Partition<VertexId>, RegistryError> {
+        self.run_measured(graph).0
+    }
+
+    /// Run the algorithm, along with the space of its data structures if it reports it
+    pub fn run_measured(
+        &self,
+        graph: &Graph<VertexId, ()>,
+    ) -> (
+        Result<Partition<VertexId>, RegistryError>,
+        Option<SpaceReport>,
+    ) {
+        let (partition, space) = (self.runner)(graph);
+        (partition.ok_or(RegistryError::Failed(self.name)), space)
+    }
+}
+
+/// Algorithms indexed by name
+#[derive(Debug)]
+pub struct Registry {
+    algorithms: BTreeMap<&'static str, Algorithm>,
+}
+
+impl Registry {
+    /// A registry without any algorithm
+    pub fn empty() -> Self {
+        Self {
+            algorithms: BTreeMap::new(),
+        }
+    }
+
+    /// Register an algorithm, returning the one it replaces
+    pub fn register<R>(
+        &mut self,
+        name: &'static str,
+        description: &'static str,
+        runner: R,
+    ) -> Option<Algorithm>
+    where
+        R: Fn(&Graph<VertexId, ()>) -> Option<Partition<VertexId>> + Send + Sync + 'static,
+    {
+        self.register_measured(name, description, move |graph| (runner(graph), None))
+    }
+
+    /// Register an algorithm that reports the space of its data structures, returning the one it replaces
+    pub fn register_measured<R>(
+        &mut self,
+        name: &'static str,
+        description: &'static str,
+        runner: R,
+    ) -> Option<Algorithm>
+    where
+        R: Fn(&Graph<VertexId, ()>) -> (Option<Partition<VertexId>>, Option<SpaceReport>)
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.algorithms.insert(
+            name,
+            Algorithm {
+                name,
+                description,
+                runner: Box::new(runner),
+            },
+        )
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Algorithm> {
+        self.algorithms.get(name)
+    }
+
+    /// The registered names, in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.algorithms.keys().copied()
+    }
+
+    pub fn algorithms(&self) -> impl Iterator<Item = &Algorithm> {
+        self.algorithms.values()
+    }
+
+    /// Run the algorithm registered under `name` on the graph
+    pub fn run(
+        &self,
+        name: &str,
+        graph: &Graph<VertexId, ()>,
+    ) -> Result<Partition<VertexId>, RegistryError> {
+        self.get(name)
+            .ok_or_else(|| RegistryError::Unknown(name.to_string()))?
+            .run(graph)
+    }
+}
+
+impl Default for Registry {
+    /// The algorithms of this crate
+    fn default() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register(
+            "degeneracy",
+            "Greedy coloring in degeneracy order, at most κ + 1 colors",
+            |graph| Some(graph.color_degeneracy()),
+        );
+        registry.register(
+            "greedy",
+            "Greedy coloring in arbitrary order, at most Δ + 1 colors",
+            |graph| Some(graph.greedy(None)),
+        );
+        registry.register(
+            "randomized",
+            "Random colors from a palette of Δ + 1, conflicts recolored",
+            |graph| Some(graph.randomized()),
+        );
+        registry.register_measured(
+            "bcg",
+            "Streaming κ-coloring of Bera, Chakrabarti and Ghosh",
+            run_bcg,
+        );
+        registry.register_measured(
+            "ack",
+            "Streaming (Δ + 1)-coloring of Assadi, Chen and Khanna",
+            run_ack,
+        );
+        #[cfg(feature = "rayon")]
+        registry.register(
+            "speculative",
+            "Parallel speculative coloring, conflicts resolved in rounds",
+            |graph| Some(graph.speculative_coloring()),
+        );
+
+        registry
+    }
+}
+
+/// Largest minimum degree met while repeatedly removing a vertex of minimum degree
+fn degeneracy(graph: &Graph<VertexId, ()>) -> usize {
+    let mut graph: GraphWithRecaller<VertexId, ()> = graph.clone().into();
+
+    let mut degeneracy = 0;
+    while let Some((_, degree)) = graph.min_degree() {
+        graph.remove_min();
+        degeneracy = degeneracy.max(degree);
+    }
+    degeneracy
+}
+
+fn run_bcg(graph: &Graph<VertexId, ()>) -> (Option<Partition<VertexId>>, Option<SpaceReport>) {
+    let n = graph.vertices().into_iter().max().map_or(0, |v| v + 1);
+    let k = degeneracy(graph).max(1) as u64;
+
+    let mut colorer: bcg::StreamColoring = bcg::StreamColoring::init(n, k, 0.01);
+    for edge in graph.edges() {
+        colorer.feed(edge, true);
+    }
+    let space = colorer.space();
+
+    // Colors are tuples, and the colorer colors every vertex of the universe
+    let coloring = colorer.query().map(|coloring| {
+        let colors: HashMap<_, _> = coloring.into_colors();
+        let mut relabeled: HashMap<(u32, u32), usize> = HashMap::new();
+        graph
+            .vertices()
+            .into_iter()
+            .map(|v| {
+                let next = relabeled.len();
+                (*v, *relabeled.entry(colors[v]).or_insert(next))
+            })
+            .collect()
+    });
+    (coloring, Some(space))
+}
+
+fn run_ack(graph: &Graph<VertexId, ()>) -> (Option<Partition<VertexId>>, Option<SpaceReport>) {
+    let delta: u32 = match graph
+        .adj_list()
+        .values()
+        .map(|neighbors| neighbors.len())
+        .max()
+    {
+        Some(delta) => delta.try_into().unwrap(),
+        None => return (None, None),
+    };
+
+    let mut colorer: ack::StreamColoring =
+        match ack::StreamColoring::try_init(graph.vertices(), delta) {
+            Ok(colorer) => colorer,
+            Err(_) => return (None, None),
+        };
+    for edge in graph.edges() {
+        colorer.feed((edge, true));
+    }
+    let space = colorer.space();
+    (colorer.query().ok(), Some(space))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::Edge;
+
+    fn cycle() -> Graph<VertexId, ()> {
+        let mut graph = Graph::default();
+        for v in 0..5 {
+            graph.add_edge(Edge::init(v, (v + 1) % 5));
+        }
+        graph
+    }
+
+    #[test]
+    fn runs_by_name() {
+        let registry = Registry::default();
+        let graph = cycle();
+
+        for name in ["degeneracy", "greedy", "randomized", "bcg"] {
+            let coloring = registry.run(name, &graph).unwrap();
+            assert_eq!(coloring.len(), 5, "{}", name);
+            assert!(graph.is_proper(&coloring), "{}", name);
+        }
+
+        assert_eq!(
+            registry.run("louvain", &graph).unwrap_err(),
+            RegistryError::Unknown("louvain".to_string())
+        );
+    }
+
+    #[test]
+    fn register() {
+        let mut registry = Registry::empty();
+        assert!(registry.names().next().is_none());
+
+        registry.register("one", "Every vertex colored 0", |graph| {
+            Some(graph.vertices().into_iter().map(|v| (*v, 0)).collect())
+        });
+        assert!(registry
+            .register("fails", "Never succeeds", |_| None)
+            .is_none());
+
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["fails", "one"]);
+        assert_eq!(registry.run("one", &cycle()).unwrap().num_classes(), 1);
+        assert_eq!(
+            registry.run("fails", &cycle()).unwrap_err(),
+            RegistryError::Failed("fails")
+        );
+    }
+}
@@ -13,11 +13,15 @@ use algebraics::{
 use itertools::Itertools;
 use num_bigint::BigInt;
 use num_bigint::ToBigUint;
+use num_primes::Generator;
+
+use crate::{Error, Result};
 
 fn bits(val: &u64) -> u64 {
     (*val as f64).log2().ceil() as u64
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 /// Wrapper for an element of F_{2^n}
 pub struct TwoPowerFieldPoly(u64);
@@ -124,6 +128,7 @@ impl Primitive {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 // A finite field of order 2^n
 pub struct PowerFiniteField {
@@ -132,31 +137,37 @@ pub struct PowerFiniteField {
 }
 
 impl PowerFiniteField {
-    /// Create a new Power Field, given an order and irreducible.
+    /// Create a new Power Field, given an order and irreducible of degree `log2(order)`, failing if the order is not two to the
+    /// degree of the irreducible.
     ///
     /// O(1) in time
-    pub fn init_with_irreducible(order: u64, irreducible: Primitive) -> Self {
-        if !order.is_power_of_two() {
-            panic!("Order of FField must be a power of two: {}", order);
+    pub fn init_with_irreducible(order: u64, irreducible: Primitive) -> Result<Self> {
+        let degree = order.floor_log2().filter(|_| order.is_power_of_two());
+        if degree != Some(irreducible.deg as usize) {
+            return Err(Error::FieldOrder(order));
         }
-        assert!(irreducible.deg == order.floor_log2().unwrap() as u8);
-        Self {
+        Ok(Self {
             order,
             irreducible: irreducible.poly,
-        }
+        })
     }
     /// Create a new Prime Power Field, given an order.
     ///
     /// O(log^2(n)) where n is the order.
-    pub fn init(order: u64) -> Self {
+    pub fn init(order: u64) -> Result<Self> {
         if !order.is_power_of_two() {
-            panic!("Order of FField must be a power of two: {}", order);
+            return Err(Error::FieldOrder(order));
         }
         let degree = (order as f64).log2() as u8;
 
         Self::init_with_irreducible(order, Primitive::of_degree(degree))
     }
 
+    /// [init](PowerFiniteField::init), panicking if the order is not a power of two
+    pub fn init_unchecked(order: u64) -> Self {
+        Self::init(order).unwrap_or_else(|e| panic!("{}", e))
+    }
+
     /// Reduce an element to be within the field, using mod the field's selected irreducible.
     pub fn reduce(&self, value: u64) -> u64 {
         let mut value = value;
@@ -227,6 +238,7 @@ impl rand::distributions::Distribution<PrimePowerFieldElement> for PowerFiniteFi
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy)]
 /// A holder for an element of a field, because of strange life-times
 /// the prime power field element cannot hold a reference to the field. But if we could, that would be really nice.
@@ -246,6 +258,7 @@ impl Debug for PrimePowerFieldElement {
 /// What we can do, is semantically enforce that values passed into the FiniteField functions are FieldElements, rather than simply u64s.
 ///
 /// Since we are using only one field at a time, this should suffice.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct FieldElement(u64);
 
@@ -279,6 +292,7 @@ impl std::cmp::PartialEq<u64> for FieldElement {
 ///
 /// This implementation is not correct, order MUST be prime for this to in fact be a finite field
 /// - must add this requirement to the code.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy)]
 pub struct FiniteField {
     order: u64,
@@ -290,13 +304,30 @@ impl FiniteField {
         Self { order }
     }
 
-    /// Converts an i32 into a field element of the current field
+    /// The field used to fingerprint vectors over a universe of size `n`: the smallest prime order at least `n^3`
+    ///
+    /// The order depends on `n` alone, so structures over the same universe agree on their field, and a run is reproducible.
+    /// Universes above `2^21` are capped to an order of about `2^63`, since the elements are `u64`s.
+    pub fn for_universe(n: u64) -> Self {
+        Self::for_universe_wide(n as u128)
+    }
+
+    /// [for_universe](FiniteField::for_universe) over a universe of `u128` size, whose order is capped all the same
+    pub fn for_universe_wide(n: u128) -> Self {
+        let target = n.checked_pow(3).unwrap_or(u128::MAX).min(1 << 63) as u64;
+        let order = Generator::next_prime(target.max(2))
+            .unwrap_or_else(|| panic!("[FiniteField] No prime order above {}", target));
+        Self::new(order)
+    }
+
+    /// Number of elements of the field
+    pub fn order(&self) -> u64 {
+        self.order
+    }
+
+    /// Converts an i64 into a field element of the current field
     pub fn mod_p_i64(&self, val: i64) -> FieldElement {
-        if val >= 0 {
-            self.mod_p(val as u64)
-        } else {
-            (self.order - val.abs() as u64).into()
-        }
+        ((val as i128).rem_euclid(self.order as i128) as u64).into()
     }
 
     /// Converts a u64 into a field element of the current field
@@ -306,14 +337,19 @@ impl FiniteField {
 
     /// Compute base^expo within the field
     pub fn pow(&self, base: FieldElement, expo: u64) -> FieldElement {
+        self.pow_wide(base, expo as u128)
+    }
+
+    /// Compute base^expo within the field, for an exponent of up to 128 bits, e.g. the index of an edge of a huge graph
+    pub fn pow_wide(&self, base: FieldElement, expo: u128) -> FieldElement {
         if expo == 0 {
             return 1.into();
         }
         // If the exponent is odd, get it to even, and continue
         if expo % 2 == 1 {
-            self.mul(base, self.pow(base, expo - 1))
+            self.mul(base, self.pow_wide(base, expo - 1))
         } else {
-            self.pow(self.mul(base, base), expo / 2)
+            self.pow_wide(self.mul(base, base), expo / 2)
         }
     }
 
@@ -374,6 +410,17 @@ mod test {
         assert_eq!(result, 3)
     }
 
+    #[test]
+    fn test_power_wide() {
+        let field = test_field();
+        let base = field.mod_p_i64(-20);
+
+        // By Fermat's little theorem, base^22 = 1 in F_23, so the exponent is taken mod 22
+        let expo = (1_u128 << 100) + 100;
+        let reduced = (expo % 22) as u64;
+        assert_eq!(field.pow_wide(base, expo), field.pow(base, reduced));
+    }
+
     #[test]
     fn test_multiply() {
         let field = test_field();
@@ -416,6 +463,42 @@ mod test {
         assert_eq!(result, 3)
     }
 
+    #[test]
+    fn for_universe() {
+        assert_eq!(FiniteField::for_universe(10).order(), 1009);
+        assert_eq!(FiniteField::for_universe(1).order(), 2);
+        // n^3 overflows
+        assert!(FiniteField::for_universe(1 << 40).order() > 1 << 63);
+    }
+
+    #[test]
+    fn power_field_order() {
+        assert!(matches!(
+            PowerFiniteField::init(12),
+            Err(Error::FieldOrder(12))
+        ));
+        assert!(PowerFiniteField::init(16).is_ok());
+        assert!(matches!(
+            PowerFiniteField::init(0),
+            Err(Error::FieldOrder(0))
+        ));
+    }
+
+    #[test]
+    fn power_field_irreducible() {
+        assert!(PowerFiniteField::init_with_irreducible(16, Primitive::of_degree(4)).is_ok());
+        assert!(matches!(
+            PowerFiniteField::init_with_irreducible(16, Primitive::of_degree(5)),
+            Err(Error::FieldOrder(16))
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn power_field_order_unchecked() {
+        PowerFiniteField::init_unchecked(12);
+    }
+
     fn helper(s: &str) {
         let res = s.split(" + ").fold(0, |res, cur| {
             if cur == "1" {
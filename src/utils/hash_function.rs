@@ -1,6 +1,6 @@
 //! Supporting randomized Hash Functions
-use rand::{prelude::Distribution, thread_rng};
-use std::fmt::Debug;
+use rand::{prelude::Distribution, thread_rng, Rng};
+use std::{convert::TryFrom, fmt::Debug};
 
 use super::finite_field::{PowerFiniteField, PrimePowerFieldElement};
 
@@ -11,8 +11,26 @@ use super::finite_field::{PowerFiniteField, PrimePowerFieldElement};
 pub trait HashFunction: Debug {
     /// Initialize a new hash function. This should
     fn init(n: u64, l: u64) -> Self;
+    /// Initialize a new hash function over a domain of `u128` size, e.g. the edges of a graph of more than `2^32` vertices
+    ///
+    /// By default, the domain must fit in a `u64`.
+    fn init_wide(n: u128, l: u64) -> Self
+    where
+        Self: Sized,
+    {
+        let n = u64::try_from(n)
+            .unwrap_or_else(|_| panic!("[HashFunction] Universe is too large: {}", n));
+        Self::init(n, l)
+    }
     /// Computes the value of h(x), where h is the current hash function
     fn compute(&self, x: u64) -> u64;
+    /// Computes the value of h(x) for an element of a domain of `u128` size, which agrees with [compute](HashFunction::compute)
+    /// on the elements of a `u64`
+    fn compute_wide(&self, x: u128) -> u64 {
+        let x = u64::try_from(x)
+            .unwrap_or_else(|_| panic!("[HashFunction] {} is out of the domain of {:?}", x, self));
+        self.compute(x)
+    }
     /// Computes the boolean value of h(x) = *0*, where h is the current hash function
     fn is_zero(&self, x: u64) -> bool {
         self.compute(x) == 0
@@ -21,6 +39,7 @@ pub trait HashFunction: Debug {
     fn random_copy(&self) -> Self;
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone)]
 /// A Hash Function implementation that performs calculations within a prime power field using the following methodology
 ///
@@ -54,7 +73,7 @@ impl PowerFiniteFieldHasher {
 impl HashFunction for PowerFiniteFieldHasher {
     fn init(n: u64, l: u64) -> Self {
         let mut rng = thread_rng();
-        let field = PowerFiniteField::init(n);
+        let field = PowerFiniteField::init_unchecked(n);
 
         Self::init_a_b(field, field.sample(&mut rng), field.sample(&mut rng), l)
     }
@@ -80,20 +99,149 @@ impl HashFunction for PowerFiniteFieldHasher {
     }
 }
 
+/// The Mersenne prime `2^61 - 1`, the order of the field of [PolynomialHasher]
+const MERSENNE_61: u64 = (1 << 61) - 1;
+
+/// Reduce a product of two elements of `F_{2^61 - 1}`
+fn mersenne_reduce(x: u128) -> u64 {
+    let x = (x & MERSENNE_61 as u128) + (x >> 61);
+    let x = (x & MERSENNE_61 as u128) + (x >> 61);
+    let x = x as u64;
+    if x >= MERSENNE_61 {
+        x - MERSENNE_61
+    } else {
+        x
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A Hash Function drawn from a k-wise independent family, for domains of up to `2^61 - 1` elements
+///
+/// f(x) = a_0 + a_1 x + ... + a_{k-1} x^{k-1}; calculations all performed within F_p, with p = 2^61 - 1
+/// g(x) = rightmost l bits of f(x)
+///
+/// The coefficients are uniform in F_p, so the values of f on any k distinct points are independent and uniform. Keeping the
+/// rightmost bits of a uniform element of F_p is uniform over `[2^l]` up to a bias of `2^l / p`.
+///
+/// Over a larger domain, see [init_wide_with_rng](PolynomialHasher::init_wide_with_rng), the elements of at least `p` are first
+/// folded into F_p: split into limbs `x_0, x_1, x_2` of 60 bits, `x` becomes `r^3 + x_2 r^2 + x_1 r + x_0` for a random point
+/// `r`, which two distinct elements share with probability at most `3 / p`.
+pub struct PolynomialHasher {
+    /// Coefficients, lowest degree first
+    coefficients: Vec<u64>,
+    mask: u64,
+    /// The point folding the elements of a wide domain, `None` if the domain is within F_p
+    fold: Option<u64>,
+}
+
+impl PolynomialHasher {
+    /// Initialize a hash function from `[n]` to `[l]` drawn from a `k`-wise independent family
+    pub fn init_k(n: u64, l: u64, k: usize) -> Self {
+        Self::init_with_rng(n, l, k, &mut thread_rng())
+    }
+
+    /// Initialize a hash function from a `k`-wise independent family, drawing its coefficients from the generator
+    pub fn init_with_rng<R: Rng + ?Sized>(n: u64, l: u64, k: usize, rng: &mut R) -> Self {
+        if n > MERSENNE_61 {
+            panic!("[PolynomialHasher] Universe is too large: {}", n)
+        }
+        if !l.is_power_of_two() {
+            panic!("Hash Function range MUST be a power of two: {}", l)
+        }
+
+        Self {
+            coefficients: (0..k.max(1))
+                .map(|_| rng.gen_range(0..MERSENNE_61))
+                .collect(),
+            mask: l - 1,
+            fold: None,
+        }
+    }
+
+    /// [init_with_rng](PolynomialHasher::init_with_rng) over a domain of `u128` size, whose elements beyond F_p are folded
+    pub fn init_wide_with_rng<R: Rng + ?Sized>(n: u128, l: u64, k: usize, rng: &mut R) -> Self {
+        if n <= MERSENNE_61 as u128 {
+            return Self::init_with_rng(n as u64, l, k, rng);
+        }
+
+        let mut hasher = Self::init_with_rng(MERSENNE_61, l, k, rng);
+        hasher.fold = Some(rng.gen_range(0..MERSENNE_61));
+        hasher
+    }
+
+    /// The element of F_p an element of the wide domain is folded into
+    fn fold(&self, r: u64, x: u128) -> u64 {
+        const LIMB: u128 = (1 << 60) - 1;
+        let limbs = [x >> 120, (x >> 60) & LIMB, x & LIMB];
+
+        limbs.iter().fold(1, |acc, limb| {
+            mersenne_reduce(acc as u128 * r as u128 + limb)
+        })
+    }
+
+    /// Independence of the family the function is drawn from
+    pub fn k(&self) -> usize {
+        self.coefficients.len()
+    }
+}
+
+impl HashFunction for PolynomialHasher {
+    /// A pairwise independent function
+    fn init(n: u64, l: u64) -> Self {
+        Self::init_k(n, l, 2)
+    }
+
+    /// A pairwise independent function, up to the collisions of the folding
+    fn init_wide(n: u128, l: u64) -> Self {
+        Self::init_wide_with_rng(n, l, 2, &mut thread_rng())
+    }
+
+    fn compute(&self, x: u64) -> u64 {
+        let value = self.coefficients.iter().rev().fold(0, |acc, a| {
+            mersenne_reduce(acc as u128 * x as u128 + *a as u128)
+        });
+
+        value & self.mask
+    }
+
+    fn compute_wide(&self, x: u128) -> u64 {
+        if x < MERSENNE_61 as u128 {
+            return self.compute(x as u64);
+        }
+        match self.fold {
+            Some(r) => self.compute(self.fold(r, x)),
+            None => panic!("[PolynomialHasher] {} is out of the domain", x),
+        }
+    }
+
+    fn random_copy(&self) -> Self {
+        let mut rng = thread_rng();
+        Self {
+            coefficients: (0..self.k())
+                .map(|_| rng.gen_range(0..MERSENNE_61))
+                .collect(),
+            mask: self.mask,
+            fold: self.fold.map(|_| rng.gen_range(0..MERSENNE_61)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
 
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
 
     use itertools::Itertools;
     use num_traits::Pow;
+    use rand::SeedableRng;
 
     use super::*;
 
     fn two_universal(n: u64, l: u64) -> Vec<(f32, f32)> {
         let n = n.next_power_of_two();
         let l = l.next_power_of_two();
-        let field = PowerFiniteField::init(n);
+        let field = PowerFiniteField::init_unchecked(n);
 
         let mut results: Vec<_> = (0..n)
             .into_iter()
@@ -147,4 +295,70 @@ mod test {
         let res = two_universal(32, 16);
         println!("{:?}", res);
     }
+
+    #[test]
+    fn mersenne() {
+        assert_eq!(mersenne_reduce(MERSENNE_61 as u128), 0);
+        assert_eq!(
+            mersenne_reduce((MERSENNE_61 - 1) as u128 * (MERSENNE_61 - 1) as u128),
+            1
+        );
+
+        let hasher = PolynomialHasher {
+            coefficients: vec![3, 2, 1],
+            mask: (1 << 20) - 1,
+            fold: None,
+        };
+        assert_eq!(hasher.compute(5), 3 + 2 * 5 + 25);
+    }
+
+    #[test]
+    fn polynomial_uniform() {
+        let (n, l, trials) = (64, 8, 4000);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+
+        // Pairs of distinct points land in each of the l^2 pairs of buckets about as often
+        let mut counts = HashMap::<(u64, u64), u32>::new();
+        for _ in 0..trials {
+            let hasher = PolynomialHasher::init_with_rng(n, l, 4, &mut rng);
+            *counts
+                .entry((hasher.compute(3), hasher.compute(40)))
+                .or_default() += 1;
+        }
+
+        let expected = trials as f64 / (l * l) as f64;
+        assert_eq!(counts.len() as u64, l * l);
+        assert!(counts
+            .values()
+            .all(|c| (*c as f64 - expected).abs() < 0.5 * expected));
+    }
+
+    #[test]
+    fn polynomial_wide() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(12);
+        let narrow = PolynomialHasher::init_wide_with_rng(1 << 40, 1 << 10, 2, &mut rng);
+        assert_eq!(narrow.fold, None);
+
+        let hasher = PolynomialHasher::init_wide_with_rng(1 << 100, 1 << 10, 2, &mut rng);
+        let r = hasher.fold.unwrap();
+        assert_eq!(hasher.compute_wide(12345), hasher.compute(12345));
+
+        // Elements differing in a single limb, or only beyond F_p, are folded apart
+        let wide = [
+            MERSENNE_61 as u128,
+            1 << 60,
+            1 << 120,
+            (1 << 120) + 5,
+            (1 << 99) + 12345,
+        ];
+        let folded: HashSet<u64> = wide.iter().map(|x| hasher.fold(r, *x)).collect();
+        assert_eq!(folded.len(), wide.len());
+        assert!(wide.iter().all(|x| hasher.compute_wide(*x) < 1 << 10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn polynomial_narrow_domain() {
+        PolynomialHasher::init_k(1 << 20, 8, 2).compute_wide(1 << 64);
+    }
 }
@@ -2,3 +2,6 @@
 
 pub mod finite_field;
 pub mod hash_function;
+pub mod parameters;
+pub mod randomness;
+pub mod slab;
@@ -0,0 +1,233 @@
+//! Sanity checks for the parameters of randomized algorithms
+//!
+//! Many of the probabilities in the streaming algorithms are only meaningful asymptotically, i.e. for large `n` and `Δ`.
+//! On small graphs they may fall outside of `[0, 1]`, so rather than panicking we clamp them, and keep a record of what was changed.
+//!
+//! The random samples drawn with those probabilities are only of their expected size with high probability. An unlucky sample,
+//! far too small to be useful or far too large to fit in memory, is drawn again before any token is fed, see [ParameterLog::sample].
+
+use log::{info, warn};
+
+/// How concerning an adjustment is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    /// The value was out of range in an expected way, e.g. a sampling probability above 1 on a small graph, where keeping everything is what we want
+    Info,
+    /// The value was meaningless, e.g. negative or NaN, the parameters provided to the algorithm should be revisited
+    Warning,
+}
+
+/// A parameter that had to be changed to be valid
+#[derive(Clone, Debug, PartialEq)]
+pub struct Adjustment {
+    /// Name of the parameter
+    pub name: &'static str,
+    /// The value computed by the algorithm
+    pub requested: f64,
+    /// The value that is actually used
+    pub used: f64,
+    pub level: Level,
+}
+
+/// How far the size of a sample may be from its expected size, before the sample is drawn again
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleTolerance {
+    /// Largest accepted [deviation](SampleDiagnostics::deviation)
+    pub deviation: f64,
+    /// Number of times a sample is drawn again, the last draw being kept even if it deviates
+    pub retries: usize,
+}
+
+impl Default for SampleTolerance {
+    fn default() -> Self {
+        Self {
+            deviation: 0.5,
+            retries: 3,
+        }
+    }
+}
+
+/// Size of a random sample drawn while initializing an algorithm, against its expected size
+#[derive(Clone, Debug, PartialEq)]
+pub struct SampleDiagnostics {
+    /// Name of the sample
+    pub name: &'static str,
+    pub expected: f64,
+    pub actual: usize,
+    /// Number of draws, 1 if the first one was within the tolerance
+    pub attempts: usize,
+    /// Whether the kept draw is within the tolerance
+    pub within: bool,
+}
+
+impl SampleDiagnostics {
+    /// Relative distance of the actual size to the expected one, `|actual - expected| / expected`
+    ///
+    /// Expected sizes below 1 are taken to be 1, a sample that is expected to be nearly empty cannot deviate much.
+    pub fn deviation(&self) -> f64 {
+        (self.actual as f64 - self.expected).abs() / self.expected.max(1.0)
+    }
+}
+
+/// Collects the adjustments made while initializing an algorithm
+#[derive(Clone, Debug, Default)]
+pub struct ParameterLog {
+    adjustments: Vec<Adjustment>,
+    samples: Vec<SampleDiagnostics>,
+}
+
+impl ParameterLog {
+    /// Validate a probability, clamping it into `[0, 1]`
+    ///
+    /// - Values above 1 are clamped to 1, and logged as `Info`
+    /// - Negative and NaN values are clamped to 0, and logged as `Warning`
+    pub fn probability(&mut self, name: &'static str, p: f64) -> f64 {
+        let (used, level) = if p.is_nan() || p < 0.0 {
+            (0.0, Level::Warning)
+        } else if p > 1.0 {
+            (1.0, Level::Info)
+        } else {
+            return p;
+        };
+
+        let adjustment = Adjustment {
+            name,
+            requested: p,
+            used,
+            level,
+        };
+        match level {
+            Level::Info => info!("[Parameters] {:?}", adjustment),
+            Level::Warning => warn!("[Parameters] {:?}", adjustment),
+        }
+        self.adjustments.push(adjustment);
+
+        used
+    }
+
+    /// All adjustments made so far
+    pub fn adjustments(&self) -> &[Adjustment] {
+        &self.adjustments
+    }
+
+    /// Draw a sample of expected size `expected`, drawing it again while its size deviates beyond the tolerance
+    ///
+    /// - *draw* : Draws the sample, with new randomness on every call
+    /// - *size* : Size of a drawn sample
+    pub fn sample<T, D, S>(
+        &mut self,
+        name: &'static str,
+        expected: f64,
+        tolerance: SampleTolerance,
+        mut draw: D,
+        size: S,
+    ) -> T
+    where
+        D: FnMut() -> T,
+        S: Fn(&T) -> usize,
+    {
+        let mut attempts = 0;
+        loop {
+            let sample = draw();
+            attempts += 1;
+
+            let diagnostics = SampleDiagnostics {
+                name,
+                expected,
+                actual: size(&sample),
+                attempts,
+                within: true,
+            };
+            let within = diagnostics.deviation() <= tolerance.deviation;
+            if within || attempts > tolerance.retries {
+                let diagnostics = SampleDiagnostics {
+                    within,
+                    ..diagnostics
+                };
+                if !within {
+                    warn!("[Parameters] {:?}", diagnostics);
+                }
+                self.samples.push(diagnostics);
+
+                return sample;
+            }
+        }
+    }
+
+    /// Sizes of the samples drawn so far
+    pub fn samples(&self) -> &[SampleDiagnostics] {
+        &self.samples
+    }
+
+    /// Whether any adjustment made so far is a `Warning`, or any sample deviates beyond its tolerance
+    pub fn has_warnings(&self) -> bool {
+        self.adjustments.iter().any(|a| a.level == Level::Warning)
+            || self.samples.iter().any(|s| !s.within)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn valid_probability() {
+        let mut log = ParameterLog::default();
+
+        assert!((log.probability("p", 0.3) - 0.3).abs() < f64::EPSILON);
+        assert!(log.adjustments().is_empty());
+    }
+
+    #[test]
+    fn clamped_probability() {
+        let mut log = ParameterLog::default();
+
+        assert!((log.probability("large", 4.2) - 1.0).abs() < f64::EPSILON);
+        assert!(!log.has_warnings());
+
+        assert!(log.probability("negative", -0.5).abs() < f64::EPSILON);
+        assert!(log.probability("nan", f64::NAN).abs() < f64::EPSILON);
+        assert!(log.has_warnings());
+
+        assert_eq!(
+            log.adjustments()
+                .iter()
+                .map(|a| (a.name, a.level))
+                .collect::<Vec<_>>(),
+            vec![
+                ("large", Level::Info),
+                ("negative", Level::Warning),
+                ("nan", Level::Warning)
+            ]
+        );
+    }
+
+    #[test]
+    fn resampled() {
+        let mut log = ParameterLog::default();
+        let tolerance = SampleTolerance {
+            deviation: 0.5,
+            retries: 3,
+        };
+
+        // Draws of sizes 0, 30, 10: the first two are too far from 10
+        let mut sizes = vec![0, 30, 10].into_iter();
+        let sample = log.sample("lucky", 10.0, tolerance, || sizes.next().unwrap(), |s| *s);
+        assert_eq!(sample, 10);
+
+        // Every draw is empty, the last one is kept
+        let sample: Vec<u64> = log.sample("unlucky", 10.0, tolerance, Vec::new, Vec::len);
+        assert!(sample.is_empty());
+
+        assert_eq!(
+            log.samples()
+                .iter()
+                .map(|s| (s.name, s.actual, s.attempts, s.within))
+                .collect::<Vec<_>>(),
+            vec![("lucky", 10, 3, true), ("unlucky", 0, 4, false)]
+        );
+        assert!((log.samples()[1].deviation() - 1.0).abs() < f64::EPSILON);
+        assert!(log.has_warnings());
+    }
+}
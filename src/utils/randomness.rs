@@ -0,0 +1,138 @@
+//! Reproducible Randomness
+//!
+//! An experiment draws randomness in many places: hash functions, sampled vertices, palettes, estimators. Rather than seeding each
+//! of them by hand, a [RandomnessManager] holds one master seed and hands out a ChaCha generator per named component. Every generator
+//! shares the key derived from the master seed, and is told apart by its stream id, a hash of the component name, so the components
+//! are statistically independent while the whole experiment is reproduced from the master seed alone.
+
+use std::collections::BTreeMap;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+/// FNV-1a, stable across platforms and compiler versions, unlike the hasher of the standard library
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Derives independent named random streams from a master seed
+#[derive(Clone, Debug)]
+pub struct RandomnessManager {
+    seed: u64,
+    /// Stream id of every name handed out so far
+    streams: BTreeMap<String, u64>,
+}
+
+impl RandomnessManager {
+    pub fn init(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: BTreeMap::new(),
+        }
+    }
+
+    /// A manager with a random master seed, which is kept so that the run can be replayed
+    pub fn from_entropy() -> Self {
+        Self::init(rand::random())
+    }
+
+    /// The master seed
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The generator of a named component
+    ///
+    /// Panics if the name was already handed out, since two components sharing a stream would be perfectly correlated.
+    pub fn stream(&mut self, name: &str) -> ChaCha20Rng {
+        let id = fnv1a(name.bytes());
+
+        if let Some((other, _)) = self.streams.iter().find(|(_, other)| **other == id) {
+            panic!(
+                "[RandomnessManager] Stream {:?} was already handed out, as {:?}",
+                name, other
+            );
+        }
+        self.streams.insert(name.to_string(), id);
+
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed);
+        rng.set_stream(id);
+        rng
+    }
+
+    /// The generator of the `index`th member of a family of components, e.g. the sketch of each vertex
+    pub fn indexed(&mut self, name: &str, index: u64) -> ChaCha20Rng {
+        self.stream(&format!("{}[{}]", name, index))
+    }
+
+    /// A manager for a named subsystem, whose own streams are independent of the streams of this manager
+    pub fn child(&mut self, name: &str) -> Self {
+        let mut rng = self.stream(name);
+        Self::init(rand::Rng::gen(&mut rng))
+    }
+
+    /// Names and stream ids handed out so far, in order of name
+    pub fn streams(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.streams.iter().map(|(name, id)| (name.as_str(), *id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::Rng;
+
+    use super::*;
+
+    fn draws(rng: &mut ChaCha20Rng) -> Vec<u64> {
+        (0..8).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn reproducible() {
+        let mut first = RandomnessManager::init(42);
+        let mut second = RandomnessManager::init(42);
+
+        // The order in which streams are requested does not matter
+        let (a, b) = (first.stream("hash"), first.stream("palette"));
+        let (d, c) = (second.stream("palette"), second.stream("hash"));
+
+        assert_eq!(draws(&mut a.clone()), draws(&mut c.clone()));
+        assert_eq!(draws(&mut b.clone()), draws(&mut d.clone()));
+        assert_ne!(draws(&mut a.clone()), draws(&mut b.clone()));
+
+        assert_ne!(
+            draws(&mut RandomnessManager::init(43).stream("hash")),
+            draws(&mut a.clone())
+        );
+        assert_eq!(first.streams().count(), 2);
+    }
+
+    #[test]
+    fn families() {
+        let mut manager = RandomnessManager::init(7);
+        let mut child = manager.child("bank");
+
+        let vertex_0 = draws(&mut child.indexed("vertex", 0));
+        let vertex_1 = draws(&mut child.indexed("vertex", 1));
+        assert_ne!(vertex_0, vertex_1);
+
+        assert_eq!(
+            draws(
+                &mut RandomnessManager::init(7)
+                    .child("bank")
+                    .indexed("vertex", 1)
+            ),
+            vertex_1
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn shared_stream() {
+        let mut manager = RandomnessManager::init(7);
+        manager.stream("hash");
+        manager.stream("hash");
+    }
+}
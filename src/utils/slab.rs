@@ -0,0 +1,318 @@
+//! Dense Storage for Per-Vertex Structures
+//!
+//! Streaming algorithms on million vertex graphs keep some small structure for every vertex, e.g. sampled palettes or sketches.
+//! Storing each in its own `HashMap` entry, or worse its own `HashSet`, fragments the heap and multiplies the allocation overhead.
+//! Since vertices are dense ids in `[n]`, these layouts index contiguous arrays by the id instead.
+
+use std::{convert::TryFrom, mem::size_of};
+
+use crate::graph::VertexId;
+
+/// Marks a vertex without an item in a [VertexSlab]
+const EMPTY: u32 = u32::MAX;
+
+/// Items for a subset of the vertices `[n]`, stored contiguously
+///
+/// Storage: O(n) words for the slots, plus the items themselves
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct VertexSlab<S> {
+    /// Position of the item of each vertex within `items`, [EMPTY] if it has none
+    slots: Vec<u32>,
+    items: Vec<(VertexId, S)>,
+}
+
+impl<S> VertexSlab<S> {
+    /// An empty slab over the vertices `[n]`
+    pub fn init(n: VertexId) -> Self {
+        Self {
+            slots: vec![EMPTY; n as usize],
+            items: vec![],
+        }
+    }
+
+    /// Set the item of a vertex, returning the previous one
+    ///
+    /// Panics if the vertex is not in `[n]`.
+    pub fn insert(&mut self, vertex: VertexId, item: S) -> Option<S> {
+        match self.slots[vertex as usize] {
+            EMPTY => {
+                self.slots[vertex as usize] =
+                    u32::try_from(self.items.len()).expect("[VertexSlab] More than u32::MAX items");
+                self.items.push((vertex, item));
+                None
+            }
+            slot => Some(std::mem::replace(&mut self.items[slot as usize].1, item)),
+        }
+    }
+
+    pub fn get(&self, vertex: &VertexId) -> Option<&S> {
+        match self.slots.get(*vertex as usize) {
+            Some(&EMPTY) | None => None,
+            Some(slot) => Some(&self.items[*slot as usize].1),
+        }
+    }
+
+    pub fn get_mut(&mut self, vertex: &VertexId) -> Option<&mut S> {
+        match self.slots.get(*vertex as usize) {
+            Some(&EMPTY) | None => None,
+            Some(slot) => Some(&mut self.items[*slot as usize].1),
+        }
+    }
+
+    pub fn contains(&self, vertex: &VertexId) -> bool {
+        self.get(vertex).is_some()
+    }
+
+    /// The vertices with an item, in the order they were inserted
+    pub fn vertices(&self) -> impl Iterator<Item = &VertexId> {
+        self.items.iter().map(|(v, _)| v)
+    }
+
+    /// The vertices with an item, along with it, in the order they were inserted
+    pub fn iter(&self) -> impl Iterator<Item = (&VertexId, &S)> {
+        self.items.iter().map(|(v, item)| (v, item))
+    }
+
+    /// Number of vertices `n` the slab is over
+    pub fn universe(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Number of vertices with an item
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Bytes used by the layout, not counting the heap allocations owned by the items
+    pub fn layout_bytes(&self) -> usize {
+        self.slots.capacity() * size_of::<u32>()
+            + self.items.capacity() * size_of::<(VertexId, S)>()
+    }
+}
+
+impl<S> IntoIterator for VertexSlab<S> {
+    type Item = (VertexId, S);
+    type IntoIter = std::vec::IntoIter<(VertexId, S)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// A sequence of variable length lists, flattened into a single array
+///
+/// The `i`th list is `items[offsets[i]..offsets[i + 1]]`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct SliceArena<T> {
+    offsets: Vec<usize>,
+    items: Vec<T>,
+}
+
+impl<T> Default for SliceArena<T> {
+    fn default() -> Self {
+        Self {
+            offsets: vec![0],
+            items: vec![],
+        }
+    }
+}
+
+impl<T> SliceArena<T> {
+    /// Append a list, returning its index
+    pub fn push<I>(&mut self, list: I) -> usize
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.items.extend(list);
+        self.offsets.push(self.items.len());
+        self.offsets.len() - 2
+    }
+
+    /// The `i`th list, empty if there are not that many lists
+    pub fn get(&self, i: usize) -> &[T] {
+        match (self.offsets.get(i), self.offsets.get(i + 1)) {
+            (Some(start), Some(end)) => &self.items[*start..*end],
+            _ => &[],
+        }
+    }
+
+    /// Number of lists
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of items, over all of the lists
+    pub fn items(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Release the capacity left over from pushing lists
+    pub fn shrink_to_fit(&mut self) {
+        self.offsets.shrink_to_fit();
+        self.items.shrink_to_fit();
+    }
+
+    /// Bytes used by the layout
+    pub fn layout_bytes(&self) -> usize {
+        self.offsets.capacity() * size_of::<usize>() + self.items.capacity() * size_of::<T>()
+    }
+}
+
+impl<T> SliceArena<T>
+where
+    T: Ord,
+{
+    /// Group items by key, the `k`th list holding the distinct items of key `k` in sorted order, for keys in `[keys]`
+    ///
+    /// Runtime: O(keys + m log m), for m items
+    pub fn group(keys: usize, pairs: Vec<(usize, T)>) -> Self {
+        let mut pairs = pairs;
+        pairs.sort_unstable();
+        pairs.dedup();
+
+        let mut offsets = vec![0; keys + 1];
+        for (k, _) in pairs.iter() {
+            offsets[k + 1] += 1;
+        }
+        for k in 0..keys {
+            offsets[k + 1] += offsets[k];
+        }
+
+        Self {
+            offsets,
+            items: pairs.into_iter().map(|(_, item)| item).collect(),
+        }
+    }
+}
+
+/// Whether two sorted slices share an item
+///
+/// Runtime: O(|a| + |b|)
+pub fn sorted_intersect<T: Ord>(a: &[T], b: &[T]) -> bool {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => return true,
+        }
+    }
+    false
+}
+
+/// Number of items shared by two sorted slices of distinct items
+///
+/// Merges the slices when their lengths are comparable, and otherwise gallops through the longer one, searching for every item of
+/// the shorter one in a window of doubling length.
+///
+/// Runtime: O(min(|a|, |b|) log(max(|a|, |b|) / min(|a|, |b|)) + min(|a|, |b|))
+pub fn sorted_intersection_count<T: Ord>(a: &[T], b: &[T]) -> usize {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+    if short.len() * 8 < long.len() {
+        let mut count = 0;
+        let mut rest = long;
+        for item in short {
+            // Smallest window of doubling length whose last item is at least `item`
+            let mut bound = 1;
+            while bound < rest.len() && rest[bound - 1] < *item {
+                bound *= 2;
+            }
+            let window = &rest[..bound.min(rest.len())];
+            match window.binary_search(item) {
+                Ok(i) => {
+                    count += 1;
+                    rest = &rest[i + 1..];
+                }
+                Err(i) => rest = &rest[i..],
+            }
+            if rest.is_empty() {
+                break;
+            }
+        }
+        return count;
+    }
+
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < short.len() && j < long.len() {
+        match short[i].cmp(&long[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vertex_slab() {
+        let mut slab = VertexSlab::init(10);
+
+        assert_eq!(slab.insert(3, "a"), None);
+        assert_eq!(slab.insert(7, "b"), None);
+        assert_eq!(slab.insert(3, "c"), Some("a"));
+
+        assert_eq!(slab.len(), 2);
+        assert_eq!(slab.get(&3), Some(&"c"));
+        assert_eq!(slab.get(&4), None);
+        assert_eq!(slab.get(&42), None);
+        assert_eq!(slab.vertices().copied().collect::<Vec<_>>(), vec![3, 7]);
+    }
+
+    #[test]
+    fn slice_arena() {
+        let mut arena = SliceArena::default();
+
+        assert_eq!(arena.push(vec![1, 2]), 0);
+        assert_eq!(arena.push(vec![]), 1);
+        assert_eq!(arena.push(vec![3]), 2);
+
+        assert_eq!(arena.len(), 3);
+        assert_eq!(arena.get(0), &[1, 2]);
+        assert!(arena.get(1).is_empty());
+        assert_eq!(arena.get(2), &[3]);
+        assert!(arena.get(3).is_empty());
+
+        let grouped = SliceArena::group(3, vec![(2, 'x'), (0, 'b'), (0, 'a'), (2, 'x')]);
+        assert_eq!(grouped.get(0), &['a', 'b']);
+        assert!(grouped.get(1).is_empty());
+        assert_eq!(grouped.get(2), &['x']);
+    }
+
+    #[test]
+    fn intersect() {
+        assert!(sorted_intersect(&[1, 4, 9], &[2, 4]));
+        assert!(!sorted_intersect(&[1, 4, 9], &[2, 5, 10]));
+        assert!(!sorted_intersect::<u32>(&[], &[1]));
+    }
+
+    #[test]
+    fn intersection_count() {
+        assert_eq!(sorted_intersection_count(&[1, 4, 9], &[2, 4, 9]), 2);
+        assert_eq!(sorted_intersection_count::<u32>(&[], &[1]), 0);
+
+        // Skewed enough to gallop
+        let long: Vec<u32> = (0..1000).map(|i| 3 * i).collect();
+        assert_eq!(sorted_intersection_count(&[0, 4, 9, 2997], &long), 3);
+        assert_eq!(sorted_intersection_count(&long, &[1, 3000]), 0);
+        assert_eq!(sorted_intersection_count(&[5, 6, 7], &long), 1);
+    }
+}
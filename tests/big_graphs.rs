@@ -1,9 +1,15 @@
 use g_raph::{
     self,
     graph::{
-        edge::Edge, static_a::coloring::Colorer,
+        edge::Edge,
+        static_a::coloring::Colorer,
         streaming::coloring::ack::StreamColoring as ACKColorer,
-        streaming::coloring::bcg::StreamColoring, Graph, GraphWithRecaller, Graphed,
+        streaming::coloring::bank::{BankOutcome, ColorerBank},
+        streaming::coloring::verifier::{verify, Verdict},
+        streaming::io::{EdgeStreamParameters, EdgeStreamReader},
+        streaming::provenance::Provenance,
+        streaming::space::SpaceUsage,
+        Graph, GraphWithRecaller, Graphed, VertexId,
     },
     printdur,
     random_graph::bernoulli::BernoulliGraphDistribution,
@@ -12,96 +18,89 @@ use g_raph::{
 use itertools::Itertools;
 
 use rand::prelude::Distribution;
-use std::{
-    collections::HashSet,
-    convert::TryInto,
-    f32::INFINITY,
-    fs::File,
-    io::{self, BufRead},
-};
+use std::{convert::TryInto, fs::File, io::BufReader};
+
+/// Number of tokens fed to the colorers of the bank at once
+const BATCH: usize = 10_000;
 
 macro_rules! graph_test {
     ($n:expr, $edges:expr) => {{
         println!("-------------- Starting Graph Test --------------");
 
         let start = start_dur!();
-        let base = StreamColoring::init($n as u32, 1, 0.01);
-        let mut next_colorers: Vec<_> = (1..($n.log2().floor() as u32))
-            .into_iter()
-            .filter_map(|i| {
-                let k = 2_u32.pow(i) as u64;
-                base.new_k($n as u32, k)
-            })
-            .collect();
-
-        let mut colorers = vec![base];
-        colorers.append(&mut next_colorers);
+        let mut bank: ColorerBank = ColorerBank::init($n as VertexId, 0.01);
 
-        let mut whole_graph = GraphWithRecaller::new(Default::default());
+        let mut whole_graph: GraphWithRecaller<VertexId, ()> =
+            GraphWithRecaller::new(Default::default());
 
         printdur!("Initialization", start);
         println!("--------------------------------------------------");
         let start = start_dur!();
 
+        // The colorers are fed a batch at a time, in parallel with the rayon feature
         let mut len = 0;
-        for (edge, c) in $edges {
-            for colorer in &mut colorers {
-                colorer.feed(edge, c)
+        for batch in &$edges.into_iter().chunks(BATCH) {
+            let batch: Vec<_> = batch.collect();
+            bank.feed_batch(&batch);
+            for (edge, _) in batch.iter() {
+                whole_graph.add_edge(*edge);
             }
-            whole_graph.add_edge(edge);
-            len += 1;
+            len += batch.len();
         }
 
         println!("Stream Length: {}", len);
+        bank.colorers()
+            .for_each(|(_, colorer)| println!("{}\n{}", colorer.info(), colorer.space()));
         printdur!("Stream", start);
         println!("--------------------------------------------------");
 
-        let mut min_color = INFINITY as usize;
-        for (i, colorer) in colorers.into_iter().enumerate() {
-            if let Some(coloring) = colorer.query() {
-                let count = coloring.values().unique().count();
-                println!("Estimate #{} -> {} Coloring", i, count);
-                if count < min_color {
-                    min_color = count;
-                    break;
-                }
-            } else {
-                println!("Estimate #{} -> Not Sparse Enough", i);
+        // Without a coloring, there is nothing to compare the bank with
+        let min_color = match bank.query_best() {
+            BankOutcome::Colored { guess, coloring } => {
+                println!("Guess {} -> {} Coloring", guess, coloring.num_classes());
+                Some(coloring.num_classes())
             }
-        }
+            BankOutcome::OverBudget { .. } => {
+                println!("No guess was sparse enough");
+                None
+            }
+        };
 
-        let actual = whole_graph.color_degeneracy().values().unique().count();
+        let actual = whole_graph.color_degeneracy().num_classes();
 
         println!("--------------------------------------------------");
         println!("Results: (K + 1): {:?}, Streaming: {:?}", actual, min_color);
         println!("-------------- Completed Graph Test --------------");
 
-        assert!((actual as isize - min_color as isize).abs() <= 2 || actual <= min_color);
+        if let Some(min_color) = min_color {
+            assert!((actual as isize - min_color as isize).abs() <= 2 || actual <= min_color);
+        }
 
         (actual, min_color)
     }};
 }
 
-macro_rules! graph_file_test {
-    ($file_name:expr, $n:expr, $split:expr) => {{
-        let file = File::open(format!("./big_graphs/{}", $file_name)).unwrap();
-
-        let edges = io::BufReader::new(file)
-            .lines()
-            .filter_map(|r| r.ok())
-            .map(|line| {
-                let mut split = line.split($split);
-                let v1: u32 = split.next().unwrap().parse().unwrap();
-                let v2: u32 = split.next().unwrap().parse().unwrap();
+/// The edges of a dataset, whose fields are separated by `delimiter`
+fn read_edges(
+    file_name: &str,
+    delimiter: char,
+) -> impl Iterator<Item = (Edge<VertexId, ()>, bool)> {
+    let file = File::open(format!("./big_graphs/{}", file_name)).unwrap();
 
-                (Edge::<u32, ()>::init(v1, v2), true)
-            });
+    EdgeStreamReader::init(
+        BufReader::new(file),
+        EdgeStreamParameters::delimited(delimiter),
+    )
+    .map(|token| token.unwrap())
+}
 
-        graph_test!($n, edges)
+macro_rules! graph_file_test {
+    ($file_name:expr, $n:expr, $split:expr) => {{
+        graph_test!($n, read_edges($file_name, $split))
     }};
 }
 
-fn ack_test_graph(graph: Graph<u32, ()>) {
+fn ack_test_graph(graph: Graph<VertexId, ()>) {
     let max_degree: u32 = graph
         .adj_list()
         .values()
@@ -111,40 +110,60 @@ fn ack_test_graph(graph: Graph<u32, ()>) {
         .try_into()
         .unwrap();
 
-    let mut ack_colorer = ACKColorer::init(graph.vertices().into_iter().collect(), max_degree);
+    let mut ack_colorer: ACKColorer =
+        ACKColorer::init_unchecked(graph.vertices().into_iter().collect(), max_degree);
 
     println!("Initialization: {:?}", ack_colorer);
 
-    // This should not need to be cloned
-    for edge in graph.clone() {
-        ack_colorer.feed((edge, true))
+    // Replayed for the second pass of the verification
+    let stream: Vec<_> = graph.edges().map(|e| (e, true)).collect();
+    for token in stream.iter() {
+        ack_colorer.feed(*token)
     }
 
     println!("Stream Completed");
+    println!("{}", ack_colorer.info());
+    println!("{}", ack_colorer.space());
 
     let coloring = ack_colorer.query().unwrap();
 
-    println!("Colors Used: {:?}", coloring.values().unique().count());
+    println!("Colors Used: {:?}", coloring.num_classes());
 
-    assert!(graph.is_proper(coloring));
+    assert_eq!(verify(&coloring, stream), Verdict::Proper);
 }
 
-fn ack_test(file_name: &str, vertices: u32, separator: &str) {
-    let file = File::open(format!("./big_graphs/{}", file_name)).unwrap();
+/// Colors a dataset with the speculative parallel colorer, and compares to the degeneracy coloring
+#[cfg(feature = "rayon")]
+fn speculative_test(file_name: &str, separator: char) {
+    use g_raph::graph::static_a::parallel::ParallelColoring;
 
-    let mut graph = Graph::default();
+    let mut graph: Graph<VertexId, ()> = Graph::default();
+    read_edges(file_name, separator).for_each(|(edge, _)| {
+        let (v1, v2) = edge.vertices();
 
-    io::BufReader::new(file)
-        .lines()
-        .filter_map(|r| r.ok())
-        .map(|line| {
-            let mut split = line.split(separator);
-            let v1: u32 = split.next().unwrap().parse().unwrap();
-            let v2: u32 = split.next().unwrap().parse().unwrap();
+        // Some datasets list self loops, which no coloring can be proper for
+        if v1 != v2 {
+            graph.add_edge(edge);
+        }
+    });
+
+    let start = start_dur!();
+    let coloring = graph.speculative_coloring();
+    printdur!("Speculative Coloring", start);
 
-            (Edge::<u32, ()>::init(v1, v2), true)
-        })
-        .for_each(|(edge, _)| graph.add_edge(edge));
+    let actual = graph.color_degeneracy().num_classes();
+    println!(
+        "Results: Degeneracy: {:?}, Speculative: {:?}",
+        actual,
+        coloring.num_classes()
+    );
+
+    assert!(graph.is_proper(&coloring));
+}
+
+fn ack_test(file_name: &str, separator: char) {
+    let mut graph = Graph::default();
+    read_edges(file_name, separator).for_each(|(edge, _)| graph.add_edge(edge));
 
     ack_test_graph(graph);
 }
@@ -152,43 +171,64 @@ fn ack_test(file_name: &str, vertices: u32, separator: &str) {
 #[test]
 #[ignore]
 fn facebook_combined() {
-    graph_file_test!("facebook_combined.txt", 4_039_f32, " ");
+    graph_file_test!("facebook_combined.txt", 4_039_f32, ' ');
 }
 
 #[test]
 #[ignore]
 fn facebook_combined_ack() {
-    ack_test("facebook_combined.txt", 4_039, " ");
+    ack_test("facebook_combined.txt", ' ');
+}
+
+#[test]
+#[ignore]
+#[cfg(feature = "rayon")]
+fn facebook_combined_speculative() {
+    speculative_test("facebook_combined.txt", ' ');
 }
 
 #[test]
 #[ignore]
 fn facebook_artists() {
-    graph_file_test!("artist_edges.txt", 50_515_f32, ",");
+    graph_file_test!("artist_edges.txt", 50_515_f32, ',');
+}
+
+#[test]
+#[ignore]
+#[cfg(feature = "rayon")]
+fn facebook_artists_speculative() {
+    speculative_test("artist_edges.txt", ',');
 }
 
 #[test]
 #[ignore]
 fn youtube() {
-    graph_file_test!("com-youtube.ungraph.txt", 1_134_890_f32, "\t");
+    graph_file_test!("com-youtube.ungraph.txt", 1_134_890_f32, '\t');
+}
+
+#[test]
+#[ignore]
+#[cfg(feature = "rayon")]
+fn youtube_speculative() {
+    speculative_test("com-youtube.ungraph.txt", '\t');
 }
 
 #[test]
 #[ignore]
 fn ratbrain() {
-    graph_file_test!("ratbrain.txt", 496_f32, " ");
+    graph_file_test!("ratbrain.txt", 496_f32, ' ');
 }
 
 #[test]
 #[ignore]
 fn ratbrain_ack() {
-    ack_test("ratbrain.txt", 496, " ");
+    ack_test("ratbrain.txt", ' ');
 }
 
 #[test]
 #[ignore]
 fn fake_test() {
-    graph_file_test!("fake.txt", 10_f32, " ");
+    graph_file_test!("fake.txt", 10_f32, ' ');
 }
 
 #[test]
@@ -207,3 +247,16 @@ fn erdos_renyi_sample_dense_ack() {
 
     ack_test_graph(graph.sample(&mut rng));
 }
+
+#[test]
+#[ignore]
+fn evaluation() {
+    let start = start_dur!();
+    let report = g_raph::evaluation::evaluate(
+        &g_raph::registry::Registry::default(),
+        &g_raph::evaluation::Suite::standard(),
+    );
+    printdur!("Evaluation", start);
+
+    println!("{}", report);
+}
@@ -0,0 +1,53 @@
+//! Regression Corpus
+//!
+//! Replays every case of `tests/corpus`, each one a run of a colorer of the registry on a generated graph, see
+//! [corpus](g_raph::corpus). A run worth keeping, e.g. the graph on which a randomized test failed, is recorded with:
+//!
+//! ```text
+//! CORPUS_ALGORITHM=bcg CORPUS_GRAPH="bernoulli 40 0.3" CORPUS_SEED=17 cargo test --test corpus -- --ignored
+//! ```
+
+use std::env;
+
+use g_raph::{
+    corpus::{Case, Corpus, Replay},
+    registry::Registry,
+};
+
+const DIRECTORY: &str = "./tests/corpus";
+
+#[test]
+fn replay() {
+    let registry = Registry::default();
+    let corpus = Corpus::open(DIRECTORY).unwrap();
+
+    let replays = corpus.replay(&registry).unwrap();
+    assert!(!replays.is_empty());
+
+    let failures: Vec<_> = replays
+        .iter()
+        .filter(|(_, replay)| !replay.passes())
+        .map(|(case, replay)| (case.file_name(), *replay))
+        .collect();
+    assert!(failures.is_empty(), "{:?}", failures);
+
+    for (case, replay) in replays {
+        if replay == Replay::Fixed {
+            println!("{} failed when recorded, and passes now", case.file_name());
+        }
+    }
+}
+
+#[test]
+#[ignore]
+fn record() {
+    let variable = |name: &str| env::var(name).unwrap_or_else(|_| panic!("{} is not set", name));
+
+    let algorithm = variable("CORPUS_ALGORITHM");
+    let graph = variable("CORPUS_GRAPH").parse().unwrap();
+    let seed = variable("CORPUS_SEED").parse().unwrap();
+
+    let case = Case::record(&Registry::default(), &algorithm, graph, seed, vec![]).unwrap();
+    let path = Corpus::open(DIRECTORY).unwrap().save(&case).unwrap();
+    println!("{:?} recorded in {}", case.outcome, path.display());
+}
@@ -0,0 +1,328 @@
+//! Soak Test
+//!
+//! Interleaves random graph mutations, stream tokens, queries and round trips across the public API, checking invariants against
+//! a simple model after every operation. Every run is determined by its seed, which is printed on failure.
+//!
+//! The quick run executes with the rest of the tests, the long one is ignored by default and configured through the environment:
+//!
+//! ```text
+//! SOAK_SEED=7 SOAK_ITERATIONS=1000000 cargo test --test soak -- --ignored
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use g_raph::{
+    graph::{
+        static_a::{
+            coloring::Colorer, feedback::FeedbackArcSet, partition::Partition, search::Search,
+        },
+        streaming::{
+            position::{Positioned, Snapshot},
+            sparse_recovery::{s_sparse::SparseRecovery, SparseRecoveryOutput},
+        },
+        Edge, Graph, GraphWithRecaller, Graphed, VertexId,
+    },
+    utils::hash_function::PowerFiniteFieldHasher,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Number of vertices
+const N: VertexId = 24;
+/// Sparsity of the edge sketch, which is only checked while the graph has at most this many edges
+const S: u64 = 40;
+
+type Output = SparseRecoveryOutput<HashMap<u64, i64>>;
+
+struct Soak {
+    rng: StdRng,
+    graph: Graph<VertexId, ()>,
+    recaller: GraphWithRecaller<VertexId, ()>,
+    /// The model, every edge `(u, v)` with `u < v`
+    edges: HashSet<(VertexId, VertexId)>,
+    sketch: Positioned<SparseRecovery<PowerFiniteFieldHasher>>,
+    tokens: u64,
+    partition: Partition<VertexId>,
+    /// Queries of the sketch, and how many were wrong
+    queries: usize,
+    wrong: usize,
+}
+
+impl Soak {
+    fn init(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            graph: Graph::default(),
+            recaller: GraphWithRecaller::default(),
+            edges: HashSet::new(),
+            sketch: Positioned::init(SparseRecovery::init(N * (N - 1) / 2 + N, S, 0.01)),
+            tokens: 0,
+            partition: Partition::new(),
+            queries: 0,
+            wrong: 0,
+        }
+    }
+
+    fn vertex(&mut self) -> VertexId {
+        self.rng.gen_range(0..N)
+    }
+
+    fn existing_edge(&mut self) -> Option<(VertexId, VertexId)> {
+        if self.edges.is_empty() {
+            return None;
+        }
+        let i = self.rng.gen_range(0..self.edges.len());
+        self.edges.iter().nth(i).copied()
+    }
+
+    fn feed(&mut self, (u, v): (VertexId, VertexId), c: bool) {
+        self.sketch
+            .feed((Edge::<VertexId, ()>::init(u, v).to_d1(), c));
+        self.tokens += 1;
+    }
+
+    fn add_edge(&mut self) {
+        let (u, v) = (self.vertex(), self.vertex());
+        if u == v {
+            return;
+        }
+        let key = (u.min(v), u.max(v));
+
+        self.graph.add_edge(Edge::init(u, v));
+        self.recaller.add_edge(Edge::init(u, v));
+        if self.edges.insert(key) {
+            self.feed(key, true);
+        }
+    }
+
+    fn remove_edge(&mut self) {
+        if let Some((u, v)) = self.existing_edge() {
+            self.graph.remove_edge(Edge::init(v, u));
+            self.recaller.remove_edge(Edge::init(u, v));
+            self.edges.remove(&(u, v));
+            self.feed((u, v), false);
+        }
+    }
+
+    fn remove_vertex(&mut self) {
+        let vertex = self.vertex();
+
+        self.graph.remove_vertex(&vertex);
+        self.recaller.remove_vertex(&vertex);
+        self.drop_incident(vertex);
+    }
+
+    fn remove_min(&mut self) {
+        if let Some(vertex) = self.recaller.remove_min() {
+            self.graph.remove_vertex(&vertex);
+            self.drop_incident(vertex);
+        }
+    }
+
+    fn drop_incident(&mut self, vertex: VertexId) {
+        let incident: Vec<_> = self
+            .edges
+            .iter()
+            .filter(|(u, v)| *u == vertex || *v == vertex)
+            .copied()
+            .collect();
+        for edge in incident {
+            self.edges.remove(&edge);
+            self.feed(edge, false);
+        }
+    }
+
+    fn query_sketch(&mut self) {
+        let snapshot: Snapshot<Output> = self.sketch.snapshot();
+
+        assert_eq!(
+            snapshot
+                .position
+                .map(|p| p.sequence + 1)
+                .unwrap_or_default(),
+            self.tokens
+        );
+
+        if self.edges.len() as u64 > S {
+            return;
+        }
+
+        let expected: HashMap<u64, i64> = self
+            .edges
+            .iter()
+            .map(|(u, v)| (Edge::<VertexId, ()>::init(*u, *v).to_d1(), 1))
+            .collect();
+
+        self.queries += 1;
+        let correct = match snapshot.output {
+            SparseRecoveryOutput::Recovered(support) => support == expected,
+            SparseRecoveryOutput::Zero => expected.is_empty(),
+            _ => false,
+        };
+        if !correct {
+            self.wrong += 1;
+        }
+    }
+
+    fn recolor(&mut self) {
+        let (vertex, color) = (self.vertex(), self.rng.gen_range(0..6));
+        if self.rng.gen_bool(0.2) {
+            self.partition.remove(&vertex);
+        } else {
+            self.partition.insert(vertex, color);
+        }
+    }
+
+    fn merge(&mut self) {
+        let (into, from) = (self.rng.gen_range(0..6), self.rng.gen_range(0..6));
+        let moved = self.partition.class(&from).map(|c| c.len()).unwrap_or(0);
+        let merged = self.partition.merge(into, from);
+
+        assert_eq!(merged, if into == from { 0 } else { moved });
+    }
+
+    /// Static algorithms on the current graph
+    fn static_queries(&mut self) {
+        let coloring = self.graph.color_degeneracy();
+        assert!(self.graph.is_proper(&coloring));
+
+        if let Some(cycle) = self.graph.find_cycle(false) {
+            assert!(cycle.len() >= 3);
+            for (u, v) in cycle.iter().zip(cycle.iter().cycle().skip(1)) {
+                assert!(self.graph.has_edge(&Edge::init(*u, *v)));
+            }
+        }
+
+        assert_eq!(self.graph.to_dag().find_cycle(true), None);
+    }
+
+    /// Serialize the graph and parse it back
+    fn round_trip(&mut self) {
+        let serialized = self
+            .graph
+            .adj_list()
+            .iter()
+            .filter(|(_, neighbors)| !neighbors.is_empty())
+            .map(|(v, neighbors)| {
+                let neighbors: Vec<String> = neighbors
+                    .iter()
+                    .map(|n| n.destination.to_string())
+                    .collect();
+                format!("{}: {}", v, neighbors.join(","))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let parsed: Graph<VertexId, ()> = serialized.parse().unwrap();
+        assert_eq!(edges(&parsed), self.edges);
+    }
+
+    fn check_invariants(&self) {
+        assert_eq!(edges(&self.graph), self.edges);
+        assert_eq!(edges(&self.recaller), self.edges);
+
+        for (v, neighbors) in self.graph.adj_list() {
+            for n in neighbors {
+                assert!(
+                    self.graph.has_edge(&Edge::init(n.destination, *v)),
+                    "({}, {}) is only stored in one direction",
+                    v,
+                    n.destination
+                );
+            }
+        }
+
+        let min = self.recaller.adj_list().values().map(|n| n.len()).min();
+        match self.recaller.min_degree() {
+            Some((vertex, degree)) => {
+                assert_eq!(Some(degree), min, "Recalled minimum degree of {}", vertex);
+                assert_eq!(
+                    self.recaller.get_neighbors(&vertex).map(|n| n.len()),
+                    Some(degree),
+                    "Recalled vertex {} is stale",
+                    vertex
+                );
+            }
+            None => assert_eq!(min, None),
+        }
+
+        let classes: usize = self.partition.classes().map(|(_, c)| c.len()).sum();
+        assert_eq!(classes, self.partition.len());
+        for (vertex, color) in self.partition.iter() {
+            assert!(self.partition.class(color).unwrap().contains(vertex));
+        }
+    }
+
+    fn step(&mut self) {
+        match self.rng.gen_range(0..100) {
+            0..=34 => self.add_edge(),
+            35..=54 => self.remove_edge(),
+            55..=59 => self.remove_vertex(),
+            60..=62 => self.remove_min(),
+            63..=74 => self.query_sketch(),
+            75..=86 => self.recolor(),
+            87..=90 => self.merge(),
+            91..=95 => self.static_queries(),
+            _ => self.round_trip(),
+        }
+        self.check_invariants();
+    }
+}
+
+/// Every edge of the graph, as `(u, v)` with `u < v`
+fn edges<G: Graphed<VertexId, ()>>(graph: &G) -> HashSet<(VertexId, VertexId)> {
+    graph
+        .adj_list()
+        .iter()
+        .flat_map(|(u, neighbors)| {
+            neighbors
+                .iter()
+                .map(move |n| (*u.min(&n.destination), *u.max(&n.destination)))
+        })
+        .collect()
+}
+
+/// Run the soak loop, panicking with the seed and step at which an invariant broke
+fn soak(seed: u64, iterations: usize) {
+    let mut soak = Soak::init(seed);
+
+    for i in 0..iterations {
+        if let Err(e) = catch_unwind(AssertUnwindSafe(|| soak.step())) {
+            panic!("[Soak] Seed {} failed at step {}: {:?}", seed, i, e);
+        }
+    }
+
+    assert!(
+        soak.wrong * 10 <= soak.queries,
+        "[Soak] Seed {}: {} of {} sketch queries were wrong",
+        seed,
+        soak.wrong,
+        soak.queries
+    );
+}
+
+#[test]
+fn quick() {
+    for seed in 0..4 {
+        soak(seed, 2_000);
+    }
+}
+
+#[test]
+#[ignore]
+fn long() {
+    let seed = env::var("SOAK_SEED")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(rand::random);
+    let iterations = env::var("SOAK_ITERATIONS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(100_000);
+
+    println!("[Soak] Seed {}, {} iterations", seed, iterations);
+    soak(seed, iterations);
+}